@@ -0,0 +1,212 @@
+//! Agent States - What Every Agent Is Up To
+//!
+//! This module implements a state machine over every agent:
+//! - Idle and Working: at work, with nothing or something open
+//! - Blocked: waiting on another agent's answer for longer than the
+//!   configured minutes
+//! - OnLeave: on paid time off; agents now and then take a few days, as long
+//!   as someone else in their department stays available
+//! - Offline: outside their working hours or shift, or backed off by the
+//!   supervisor after failures
+//! - Each agent's state and since when, and a count of transitions
+//!
+//! The orchestrator moves agents between states every simulated hour and at
+//! their turns: Offline and on-leave agents sit their turns out, and blocked
+//! Ops agents hand their unanswered tickets to available colleagues.
+
+use crate::config::AgentStateSettings;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentState {
+    #[default]
+    Idle,
+    Working,
+    Blocked,
+    OnLeave,
+    Offline,
+}
+
+impl AgentState {
+    pub const ALL: [AgentState; 5] = [AgentState::Idle, AgentState::Working, AgentState::Blocked, AgentState::OnLeave, AgentState::Offline];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentState::Idle => "idle",
+            AgentState::Working => "working",
+            AgentState::Blocked => "blocked",
+            AgentState::OnLeave => "on leave",
+            AgentState::Offline => "offline",
+        }
+    }
+
+    /// Whether the agent takes its turns
+    pub fn is_available(&self) -> bool {
+        !matches!(self, AgentState::OnLeave | AgentState::Offline)
+    }
+}
+
+/// What decides an agent's state
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signals {
+    /// Within its working hours or shift
+    pub on_duty: bool,
+    /// Backed off by the supervisor
+    pub failing: bool,
+    /// Open work items
+    pub backlog: usize,
+    /// Since when it has waited on another agent, if it does
+    pub waiting_since: Option<DateTime<Utc>>,
+}
+
+/// An agent's state and since when it is in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentStatus {
+    pub state: AgentState,
+    pub since: DateTime<Utc>,
+}
+
+/// A move from one state to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub from: AgentState,
+    pub to: AgentState,
+}
+
+/// Every agent's state and who is on leave until when
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentStates {
+    statuses: BTreeMap<Uuid, AgentStatus>,
+    leave: BTreeMap<Uuid, DateTime<Utc>>,
+    /// State changes so far
+    pub transitions: u64,
+}
+
+impl AgentStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// State an agent's signals put it in; failures come first, then leave,
+    /// then working hours
+    pub fn next_state(&self, agent_id: &Uuid, signals: &Signals, settings: &AgentStateSettings, now: DateTime<Utc>) -> AgentState {
+        let blocked_after = chrono::Duration::minutes(settings.blocked_after_minutes);
+        if signals.failing {
+            AgentState::Offline
+        } else if self.on_leave(agent_id, now) {
+            AgentState::OnLeave
+        } else if !signals.on_duty {
+            AgentState::Offline
+        } else if signals.waiting_since.is_some_and(|since| now - since >= blocked_after) {
+            AgentState::Blocked
+        } else if signals.backlog > 0 {
+            AgentState::Working
+        } else {
+            AgentState::Idle
+        }
+    }
+
+    /// Move an agent to the state its signals put it in; returns the
+    /// transition when the state changed
+    pub fn update(&mut self, agent_id: Uuid, signals: &Signals, settings: &AgentStateSettings, now: DateTime<Utc>) -> Option<Transition> {
+        let to = self.next_state(&agent_id, signals, settings, now);
+        let status = self.statuses.entry(agent_id).or_insert(AgentStatus { state: to, since: now });
+        if status.state == to {
+            return None;
+        }
+        let from = std::mem::replace(status, AgentStatus { state: to, since: now }).state;
+        self.transitions += 1;
+        Some(Transition { from, to })
+    }
+
+    /// An agent's state; agents never updated are idle
+    pub fn state(&self, agent_id: &Uuid) -> AgentState {
+        self.statuses.get(agent_id).map_or(AgentState::Idle, |status| status.state)
+    }
+
+    pub fn status(&self, agent_id: &Uuid) -> Option<&AgentStatus> {
+        self.statuses.get(agent_id)
+    }
+
+    pub fn on_leave(&self, agent_id: &Uuid, now: DateTime<Utc>) -> bool {
+        self.leave.get(agent_id).is_some_and(|until| now < *until)
+    }
+
+    /// Send agents on leave by chance, never the last available one of a
+    /// department; `agents` are the agents with their departments. Returns
+    /// who went on leave until when
+    pub fn plan_leave(&mut self, agents: &[(Uuid, String)], settings: &AgentStateSettings, now: DateTime<Utc>, rng: &mut impl Rng) -> Vec<(Uuid, DateTime<Utc>)> {
+        self.leave.retain(|_, until| now < *until);
+        let mut granted = Vec::new();
+        for (agent_id, department) in agents {
+            if self.on_leave(agent_id, now) || !rng.gen_bool(settings.leave_chance.clamp(0.0, 1.0)) {
+                continue;
+            }
+            let present = agents.iter()
+                .filter(|(other, other_department)| other_department == department && !self.on_leave(other, now))
+                .count();
+            if present < 2 {
+                continue;
+            }
+            let until = now + chrono::Duration::days(i64::from(settings.leave_days));
+            self.leave.insert(*agent_id, until);
+            granted.push((*agent_id, until));
+        }
+        granted
+    }
+
+    /// Forget agents no longer in the company
+    pub fn retain(&mut self, agents: &[Uuid]) {
+        self.statuses.retain(|agent_id, _| agents.contains(agent_id));
+        self.leave.retain(|agent_id, _| agents.contains(agent_id));
+    }
+
+    /// Number of agents in each state, every state included
+    pub fn counts(&self) -> BTreeMap<AgentState, usize> {
+        let mut counts: BTreeMap<AgentState, usize> = AgentState::ALL.iter().map(|state| (*state, 0)).collect();
+        for status in self.statuses.values() {
+            *counts.entry(status.state).or_default() += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_signals_drive_agents_between_states() {
+        let settings = AgentStateSettings { leave_chance: 1.0, ..AgentStateSettings::default() };
+        let start: DateTime<Utc> = "2024-03-04T10:00:00Z".parse().unwrap();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let signals = |on_duty: bool, failing: bool, backlog: usize, waiting_since: Option<DateTime<Utc>>| Signals { on_duty, failing, backlog, waiting_since };
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut states = AgentStates::new();
+
+        assert_eq!(states.update(alice, &signals(true, false, 0, None), &settings, at(0)), None);
+        let working = states.update(alice, &signals(true, false, 2, Some(at(0))), &settings, at(60));
+        assert_eq!(working, Some(Transition { from: AgentState::Idle, to: AgentState::Working }));
+        assert_eq!(states.next_state(&alice, &signals(true, false, 2, Some(at(0))), &settings, at(240)), AgentState::Blocked);
+        assert_eq!(states.next_state(&alice, &signals(false, false, 2, None), &settings, at(600)), AgentState::Offline);
+        assert_eq!(states.next_state(&alice, &signals(true, true, 2, None), &settings, at(60)), AgentState::Offline);
+
+        // Only one of the two goes on leave, so Ops keeps someone
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let ops = vec![(alice, "Ops".to_string()), (bob, "Ops".to_string())];
+        let granted = states.plan_leave(&ops, &settings, at(0), &mut rng);
+        assert_eq!(granted, vec![(alice, at(3 * 24 * 60))]);
+        states.update(alice, &signals(true, false, 2, None), &settings, at(60));
+        states.update(bob, &signals(true, false, 0, None), &settings, at(60));
+        let counts = states.counts();
+        assert_eq!((counts[&AgentState::OnLeave], counts[&AgentState::Idle], counts[&AgentState::Blocked]), (1, 1, 0));
+        assert!(!states.state(&alice).is_available());
+        assert_eq!(states.transitions, 2);
+    }
+}
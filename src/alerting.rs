@@ -0,0 +1,214 @@
+//! Alerting - Rules Over Server and Cluster Metrics
+//!
+//! This module implements the alert rules evaluated against DevOps metrics:
+//! - Rules on server CPU, memory and disk usage and on cluster health, each
+//!   firing once its threshold is breached for a number of checks in a row
+//! - Deduplication: a rule fires once per target and counts repeats until
+//!   the breach ends
+//! - Auto-resolution once a target is back under the threshold or gone
+//! - Silence windows that keep matching rules and targets quiet for a time
+//!
+//! The orchestrator samples the metrics, runs the checks and sends the
+//! resulting alerts to the DevOps agent that owns each target.
+
+use crate::config::{AlertRule, AlertingSettings};
+use crate::departments::ops::Severity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Resolved alerts kept; the oldest go first
+const MAX_RESOLVED: usize = 500;
+
+/// What a rule measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Server CPU usage in percent
+    Cpu,
+    /// Server memory usage in percent
+    Memory,
+    /// Server disk usage in percent
+    Disk,
+    /// 1 while a cluster is not healthy, 0 otherwise
+    ClusterDegraded,
+}
+
+/// A metric reading of one server or cluster
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    /// Server hostname or cluster name
+    pub target: String,
+    pub metric: AlertMetric,
+    pub value: f64,
+    /// DevOps agent that runs the target
+    pub owner: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: Uuid,
+    pub rule: String,
+    pub target: String,
+    pub severity: Severity,
+    pub owner: Uuid,
+    /// Reading that fired the alert
+    pub value: f64,
+    pub fired_at: chrono::DateTime<chrono::Utc>,
+    /// Checks that found the breach again while the alert was active
+    pub repeats: u32,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// An alert firing or resolving at a check
+#[derive(Debug, Clone)]
+pub enum AlertChange {
+    Fired(Alert),
+    Resolved(Alert),
+}
+
+/// Active and recently resolved alerts, and the breach streaks behind them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertEngine {
+    active: Vec<Alert>,
+    resolved: Vec<Alert>,
+    /// Checks in a row each rule has been breached, by "rule/target"
+    streaks: HashMap<String, u32>,
+    /// Alerts a silence kept from firing
+    silenced: u32,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(&self) -> &[Alert] {
+        &self.active
+    }
+
+    pub fn resolved(&self) -> &[Alert] {
+        &self.resolved
+    }
+
+    pub fn silenced(&self) -> u32 {
+        self.silenced
+    }
+
+    /// Check every rule against the samples; returns the alerts that fired
+    /// or resolved
+    pub fn evaluate(&mut self, samples: &[MetricSample], settings: &AlertingSettings, now: chrono::DateTime<chrono::Utc>) -> Vec<AlertChange> {
+        let mut changes = Vec::new();
+        let mut breached = Vec::new();
+        for rule in &settings.rules {
+            for sample in samples.iter().filter(|sample| sample.metric == rule.metric) {
+                let key = format!("{}/{}", rule.name, sample.target);
+                if sample.value <= rule.threshold {
+                    self.streaks.remove(&key);
+                    continue;
+                }
+                let streak = self.streaks.entry(key).or_insert(0);
+                *streak += 1;
+                if *streak < rule.for_checks.max(1) {
+                    continue;
+                }
+                breached.push((rule.name.clone(), sample.target.clone()));
+                if let Some(alert) = self.active.iter_mut().find(|alert| alert.rule == rule.name && alert.target == sample.target) {
+                    alert.repeats += 1;
+                } else if silenced(settings, rule, &sample.target, now) {
+                    self.silenced += 1;
+                } else {
+                    let alert = fire(rule, sample, now);
+                    self.active.push(alert.clone());
+                    changes.push(AlertChange::Fired(alert));
+                }
+            }
+        }
+
+        // Alerts whose breach ended, or whose target or rule is gone, resolve
+        let (still_active, ended): (Vec<Alert>, Vec<Alert>) = std::mem::take(&mut self.active).into_iter()
+            .partition(|alert| breached.iter().any(|(rule, target)| *rule == alert.rule && *target == alert.target));
+        self.active = still_active;
+        for mut alert in ended {
+            alert.resolved_at = Some(now);
+            self.resolved.push(alert.clone());
+            changes.push(AlertChange::Resolved(alert));
+        }
+        if self.resolved.len() > MAX_RESOLVED {
+            let excess = self.resolved.len() - MAX_RESOLVED;
+            self.resolved.drain(..excess);
+        }
+        changes
+    }
+}
+
+fn fire(rule: &AlertRule, sample: &MetricSample, now: chrono::DateTime<chrono::Utc>) -> Alert {
+    Alert {
+        id: Uuid::new_v4(),
+        rule: rule.name.clone(),
+        target: sample.target.clone(),
+        severity: rule.severity.clone(),
+        owner: sample.owner,
+        value: sample.value,
+        fired_at: now,
+        repeats: 0,
+        resolved_at: None,
+    }
+}
+
+/// Whether a silence window covers the rule and target at `now`
+fn silenced(settings: &AlertingSettings, rule: &AlertRule, target: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+    settings.silences.iter().any(|silence| {
+        silence.from <= now && now < silence.until
+            && silence.rule.iter().all(|name| *name == rule.name)
+            && silence.target.iter().all(|prefix| target.starts_with(prefix.as_str()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Silence;
+
+    fn cpu(value: f64) -> Vec<MetricSample> {
+        vec![MetricSample { target: "web-1".to_string(), metric: AlertMetric::Cpu, value, owner: Uuid::nil() }]
+    }
+
+    #[test]
+    fn test_rule_fires_after_streak_dedups_and_resolves() {
+        let settings = AlertingSettings::default();
+        let now = chrono::Utc::now();
+        let mut engine = AlertEngine::new();
+
+        // The default CPU rule needs 3 checks over 90%
+        assert!(engine.evaluate(&cpu(95.0), &settings, now).is_empty());
+        assert!(engine.evaluate(&cpu(95.0), &settings, now).is_empty());
+        assert!(matches!(engine.evaluate(&cpu(95.0), &settings, now)[..], [AlertChange::Fired(_)]));
+        assert!(engine.evaluate(&cpu(95.0), &settings, now).is_empty());
+        assert_eq!(engine.active()[0].repeats, 1);
+
+        assert!(matches!(engine.evaluate(&cpu(40.0), &settings, now)[..], [AlertChange::Resolved(_)]));
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn test_silence_window_keeps_rule_quiet() {
+        let now = chrono::Utc::now();
+        let mut settings = AlertingSettings::default();
+        settings.silences.push(Silence {
+            rule: Some("high-cpu".to_string()),
+            target: Some("web-".to_string()),
+            from: now - chrono::Duration::hours(1),
+            until: now + chrono::Duration::hours(1),
+        });
+        let mut engine = AlertEngine::new();
+        for _ in 0..4 {
+            assert!(engine.evaluate(&cpu(95.0), &settings, now).is_empty());
+        }
+        assert_eq!(engine.silenced(), 2);
+
+        // Once the window is over the breach fires
+        let later = now + chrono::Duration::hours(2);
+        assert_eq!(engine.evaluate(&cpu(95.0), &settings, later).len(), 1);
+    }
+}
@@ -0,0 +1,453 @@
+//! KPI Analytics - History, Trends & Seasonality
+//!
+//! This module implements analysis over sampled company KPIs:
+//! - A bounded history of KPI samples on the simulated clock; rates and
+//!   means are sampled per interval from running counters, so each sample
+//!   reflects that interval alone
+//! - Rolling averages and least-squares trend detection
+//! - Week-over-week and other period-over-period comparisons
+//! - Additive seasonality decomposition into trend, seasonal and residual parts
+//! - Plain-language insights ("MTTR improved 18% this quarter") backed by the
+//!   series they were derived from, for executive reports
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Samples kept per KPI (a little over a year of hourly samples)
+const SAMPLES_PER_KPI: usize = 24 * 400;
+
+/// Relative change below which a KPI is reported as stable
+const STABLE_CHANGE_PCT: f64 = 2.0;
+
+/// Company KPIs tracked over time
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Kpi {
+    /// Mean time to resolve the incidents resolved since the previous
+    /// sample, in hours
+    Mttr,
+    OpenIncidents,
+    OpenTickets,
+    MonthlyRevenue,
+    /// Share of messages delivered since the previous sample that failed,
+    /// in percent
+    DeliveryFailureRate,
+    /// Share of steps since the previous sample run with an intake breaker
+    /// open, in percent
+    OverloadShare,
+    /// Mean time from the start of a DDoS attack until it was mitigated, in
+    /// minutes
+    TimeToMitigate,
+    /// Mean time recently routed support tickets waited in the queue, in
+    /// minutes
+    TicketQueueWait,
+}
+
+impl Kpi {
+    pub const ALL: [Kpi; 8] = [Kpi::Mttr, Kpi::OpenIncidents, Kpi::OpenTickets, Kpi::MonthlyRevenue, Kpi::DeliveryFailureRate, Kpi::OverloadShare, Kpi::TimeToMitigate, Kpi::TicketQueueWait];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kpi::Mttr => "MTTR",
+            Kpi::OpenIncidents => "Open incidents",
+            Kpi::OpenTickets => "Open tickets",
+            Kpi::MonthlyRevenue => "Monthly revenue",
+            Kpi::DeliveryFailureRate => "Delivery failure rate",
+            Kpi::OverloadShare => "Overload share",
+            Kpi::TimeToMitigate => "DDoS time to mitigate",
+            Kpi::TicketQueueWait => "Ticket queue wait",
+        }
+    }
+
+    /// Whether a rising value is good news
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, Kpi::MonthlyRevenue)
+    }
+}
+
+/// Single KPI observation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct KpiSample {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub value: f64,
+}
+
+/// Direction a series is moving in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Direction {
+    Rising,
+    Falling,
+    Flat,
+}
+
+/// Least-squares trend of a series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trend {
+    /// Change in value per simulated day
+    pub slope_per_day: f64,
+    pub direction: Direction,
+}
+
+/// Mean of one period compared with the period before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodChange {
+    pub previous: f64,
+    pub current: f64,
+    /// Relative change in percent; `None` when the previous mean was zero
+    pub change_pct: Option<f64>,
+}
+
+/// Additive decomposition: value = trend + seasonal + residual
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decomposition {
+    pub trend: Vec<Option<f64>>,
+    /// Seasonal component for each position in the period
+    pub seasonal: Vec<f64>,
+    pub residual: Vec<Option<f64>>,
+}
+
+/// Reporting periods for comparisons
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Week,
+    Month,
+    Quarter,
+}
+
+impl ReportPeriod {
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            ReportPeriod::Week => chrono::Duration::days(7),
+            ReportPeriod::Month => chrono::Duration::days(30),
+            ReportPeriod::Quarter => chrono::Duration::days(91),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ReportPeriod::Week => "week",
+            ReportPeriod::Month => "month",
+            ReportPeriod::Quarter => "quarter",
+        }
+    }
+}
+
+/// Statement about a KPI with the series supporting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insight {
+    pub kpi: Kpi,
+    pub period: ReportPeriod,
+    pub statement: String,
+    pub change: PeriodChange,
+    /// Daily averages over the compared periods
+    pub supporting: Vec<KpiSample>,
+}
+
+impl Insight {
+    /// Fields for report templates, so packs can phrase insights themselves
+    pub fn template_context(&self) -> serde_json::Value {
+        let pct = self.change.change_pct;
+        serde_json::json!({
+            "statement": self.statement,
+            "kpi_name": self.kpi.name(),
+            "period": self.period.label(),
+            "change_pct": pct.map(|pct| format!("{:.0}", pct.abs())),
+            "steady": pct.is_some_and(|pct| pct.abs() < STABLE_CHANGE_PCT),
+            "improved": pct.is_some_and(|pct| (pct > 0.0) == self.kpi.higher_is_better()),
+            "previous": format!("{:.2}", self.change.previous),
+            "current": format!("{:.2}", self.change.current),
+        })
+    }
+}
+
+/// Running totals behind the per-interval KPIs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct KpiCounters {
+    pub resolved_incidents: u64,
+    /// Summed time to resolve of those incidents
+    pub resolution_hours: f64,
+    pub deliveries: u64,
+    pub failed_deliveries: u64,
+    pub observed_steps: u64,
+    pub overloaded_steps: u64,
+}
+
+impl KpiCounters {
+    /// Mean time to resolve, delivery failure rate and overload share of
+    /// what happened since `previous`; `None` where nothing happened
+    ///
+    /// Totals that went down, e.g. once merged duplicates are dropped, count
+    /// as nothing having happened.
+    pub fn since(&self, previous: &KpiCounters) -> (Option<f64>, Option<f64>, Option<f64>) {
+        let ratio = |part: f64, whole: u64| (whole > 0 && part >= 0.0).then(|| part / whole as f64);
+        let resolved = self.resolved_incidents.saturating_sub(previous.resolved_incidents);
+        let deliveries = self.deliveries.saturating_sub(previous.deliveries);
+        let steps = self.observed_steps.saturating_sub(previous.observed_steps);
+        (
+            ratio(self.resolution_hours - previous.resolution_hours, resolved),
+            ratio(self.failed_deliveries.saturating_sub(previous.failed_deliveries) as f64, deliveries).map(|rate| rate * 100.0),
+            ratio(self.overloaded_steps.saturating_sub(previous.overloaded_steps) as f64, steps).map(|share| share * 100.0),
+        )
+    }
+}
+
+/// Sampled history of every KPI
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KpiHistory {
+    series: BTreeMap<Kpi, VecDeque<KpiSample>>,
+    /// Counters as of the previous sample
+    #[serde(default)]
+    counters: KpiCounters,
+}
+
+impl KpiHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a KPI value at a point in simulated time
+    pub fn record(&mut self, kpi: Kpi, at: chrono::DateTime<chrono::Utc>, value: f64) {
+        let series = self.series.entry(kpi).or_default();
+        if series.len() == SAMPLES_PER_KPI {
+            series.pop_front();
+        }
+        series.push_back(KpiSample { at, value });
+    }
+
+    /// Record the per-interval KPIs from the running counters as of `at`
+    pub fn record_counters(&mut self, at: chrono::DateTime<chrono::Utc>, counters: KpiCounters) {
+        let (mttr, failure_rate, overload_share) = counters.since(&self.counters);
+        self.counters = counters;
+        for (kpi, value) in [(Kpi::Mttr, mttr), (Kpi::DeliveryFailureRate, failure_rate), (Kpi::OverloadShare, overload_share)] {
+            if let Some(value) = value {
+                self.record(kpi, at, value);
+            }
+        }
+    }
+
+    /// Samples of a KPI, oldest first
+    pub fn series(&self, kpi: Kpi) -> Vec<KpiSample> {
+        self.series.get(&kpi).map(|s| s.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Latest value of a KPI
+    pub fn latest(&self, kpi: Kpi) -> Option<f64> {
+        self.series.get(&kpi)?.back().map(|sample| sample.value)
+    }
+
+    /// Insights for every KPI with enough history to compare the period
+    pub fn insights(&self, now: chrono::DateTime<chrono::Utc>, period: ReportPeriod) -> Vec<Insight> {
+        Kpi::ALL.iter()
+            .filter_map(|&kpi| insight(kpi, &self.series(kpi), now, period))
+            .collect()
+    }
+}
+
+/// Trailing rolling average over `window` samples
+pub fn rolling_average(samples: &[KpiSample], window: usize) -> Vec<KpiSample> {
+    if window == 0 {
+        return Vec::new();
+    }
+    samples.windows(window)
+        .map(|w| KpiSample {
+            at: w[w.len() - 1].at,
+            value: w.iter().map(|s| s.value).sum::<f64>() / window as f64,
+        })
+        .collect()
+}
+
+/// Least-squares slope of a series, classified against its mean level
+pub fn trend(samples: &[KpiSample]) -> Option<Trend> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let origin = samples[0].at;
+    let xs: Vec<f64> = samples.iter().map(|s| (s.at - origin).num_seconds() as f64 / 86_400.0).collect();
+    let n = samples.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = samples.iter().map(|s| s.value).sum::<f64>() / n;
+
+    let covariance: f64 = xs.iter().zip(samples).map(|(x, s)| (x - mean_x) * (s.value - mean_y)).sum();
+    let variance: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    let slope_per_day = covariance / variance;
+
+    // Flat when the fitted change over the whole span is small next to the mean level
+    let span_days = xs[xs.len() - 1];
+    let relative = if mean_y.abs() > f64::EPSILON { (slope_per_day * span_days / mean_y.abs()) * 100.0 } else { slope_per_day * span_days };
+    let direction = if relative.abs() < STABLE_CHANGE_PCT {
+        Direction::Flat
+    } else if slope_per_day > 0.0 {
+        Direction::Rising
+    } else {
+        Direction::Falling
+    };
+
+    Some(Trend { slope_per_day, direction })
+}
+
+/// Compare the mean of the last `period` with the period before it
+pub fn period_change(samples: &[KpiSample], now: chrono::DateTime<chrono::Utc>, period: chrono::Duration) -> Option<PeriodChange> {
+    let current = mean_between(samples, now - period, now)?;
+    let previous = mean_between(samples, now - period - period, now - period)?;
+    let change_pct = if previous.abs() > f64::EPSILON { Some((current - previous) / previous.abs() * 100.0) } else { None };
+    Some(PeriodChange { previous, current, change_pct })
+}
+
+/// Week-over-week comparison
+pub fn week_over_week(samples: &[KpiSample], now: chrono::DateTime<chrono::Utc>) -> Option<PeriodChange> {
+    period_change(samples, now, chrono::Duration::days(7))
+}
+
+/// Classical additive decomposition with a seasonal cycle of `period` samples
+pub fn decompose(samples: &[KpiSample], period: usize) -> Option<Decomposition> {
+    if period < 2 || samples.len() < period * 2 {
+        return None;
+    }
+    let values: Vec<f64> = samples.iter().map(|s| s.value).collect();
+
+    // Centered moving average; even periods use a 2×period average
+    let half = period / 2;
+    let trend: Vec<Option<f64>> = (0..values.len())
+        .map(|i| {
+            if i < half || i + half >= values.len() {
+                return None;
+            }
+            let average = if period % 2 == 0 {
+                let window = &values[i - half..=i + half];
+                (window[0] / 2.0 + window[1..window.len() - 1].iter().sum::<f64>() + window[window.len() - 1] / 2.0) / period as f64
+            } else {
+                values[i - half..=i + half].iter().sum::<f64>() / period as f64
+            };
+            Some(average)
+        })
+        .collect();
+
+    let mut sums = vec![0.0; period];
+    let mut counts = vec![0usize; period];
+    for (i, (value, level)) in values.iter().zip(&trend).enumerate() {
+        if let Some(level) = level {
+            sums[i % period] += value - level;
+            counts[i % period] += 1;
+        }
+    }
+    let raw: Vec<f64> = sums.iter().zip(&counts).map(|(sum, &count)| if count > 0 { sum / count as f64 } else { 0.0 }).collect();
+    // Seasonal effects sum to zero over a cycle
+    let offset = raw.iter().sum::<f64>() / period as f64;
+    let seasonal: Vec<f64> = raw.iter().map(|effect| effect - offset).collect();
+
+    let residual = values.iter().zip(&trend).enumerate()
+        .map(|(i, (value, level))| level.map(|level| value - level - seasonal[i % period]))
+        .collect();
+
+    Some(Decomposition { trend, seasonal, residual })
+}
+
+/// Describe how a KPI moved over the last period compared with the one before
+pub fn insight(kpi: Kpi, samples: &[KpiSample], now: chrono::DateTime<chrono::Utc>, period: ReportPeriod) -> Option<Insight> {
+    let change = period_change(samples, now, period.duration())?;
+
+    let statement = match change.change_pct {
+        Some(pct) if pct.abs() < STABLE_CHANGE_PCT => format!("{} held steady this {}", kpi.name(), period.label()),
+        Some(pct) => {
+            let improved = (pct > 0.0) == kpi.higher_is_better();
+            format!("{} {} {:.0}% this {}", kpi.name(), if improved { "improved" } else { "worsened" }, pct.abs(), period.label())
+        }
+        None => format!("{} went from {:.2} to {:.2} this {}", kpi.name(), change.previous, change.current, period.label()),
+    };
+
+    let window_start = now - period.duration() - period.duration();
+    let supporting = daily_averages(samples.iter().filter(|s| s.at >= window_start && s.at < now));
+
+    Some(Insight { kpi, period, statement, change, supporting })
+}
+
+fn mean_between(samples: &[KpiSample], from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let values: Vec<f64> = samples.iter().filter(|s| s.at >= from && s.at < to).map(|s| s.value).collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+fn daily_averages<'a>(samples: impl Iterator<Item = &'a KpiSample>) -> Vec<KpiSample> {
+    let mut days: BTreeMap<chrono::NaiveDate, (f64, usize)> = BTreeMap::new();
+    for sample in samples {
+        let day = days.entry(sample.at.date_naive()).or_insert((0.0, 0));
+        day.0 += sample.value;
+        day.1 += 1;
+    }
+    days.into_iter()
+        .map(|(day, (sum, count))| KpiSample {
+            at: day.and_hms_opt(0, 0, 0).expect("midnight").and_utc(),
+            value: sum / count as f64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn daily(values: &[f64]) -> Vec<KpiSample> {
+        let start = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        values.iter().enumerate()
+            .map(|(i, &value)| KpiSample { at: start + chrono::Duration::days(i as i64), value })
+            .collect()
+    }
+
+    #[test]
+    fn test_mttr_improvement_insight() {
+        // MTTR drops from 5h to 4.1h between two quarters
+        let values: Vec<f64> = (0..182).map(|day| if day < 91 { 5.0 } else { 4.1 }).collect();
+        let samples = daily(&values);
+        let now = samples[samples.len() - 1].at + chrono::Duration::hours(1);
+
+        let insight = insight(Kpi::Mttr, &samples, now, ReportPeriod::Quarter).unwrap();
+        assert_eq!(insight.statement, "MTTR improved 18% this quarter");
+        assert!(!insight.supporting.is_empty());
+
+        assert_eq!(trend(&samples).unwrap().direction, Direction::Falling);
+        let wow = week_over_week(&samples, now).unwrap();
+        assert_eq!(wow.change_pct, Some(0.0));
+    }
+
+    #[test]
+    fn test_counters_are_sampled_per_interval() {
+        let mut history = KpiHistory::new();
+        let start = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut counters = KpiCounters { resolved_incidents: 10, resolution_hours: 100.0, deliveries: 100, failed_deliveries: 50, ..KpiCounters::default() };
+        history.record_counters(start, counters);
+
+        // Two quick resolutions and a clean hour barely move the all-time means
+        counters.resolved_incidents += 2;
+        counters.resolution_hours += 2.0;
+        counters.deliveries += 100;
+        history.record_counters(start + chrono::Duration::hours(1), counters);
+        assert_eq!(history.latest(Kpi::Mttr), Some(1.0));
+        assert_eq!(history.latest(Kpi::DeliveryFailureRate), Some(0.0));
+
+        // Nothing resolved in an hour records no MTTR sample
+        history.record_counters(start + chrono::Duration::hours(2), counters);
+        assert_eq!(history.series(Kpi::Mttr).len(), 2);
+        assert!(history.series(Kpi::OverloadShare).is_empty());
+    }
+
+    #[test]
+    fn test_decomposition_recovers_weekly_season() {
+        let weekly = [0.0, 1.0, 2.0, 3.0, 2.0, 1.0, -9.0];
+        let values: Vec<f64> = (0..70).map(|day| 100.0 + day as f64 * 0.5 + weekly[day % 7]).collect();
+        let decomposition = decompose(&daily(&values), 7).unwrap();
+
+        let expected_offset = weekly.iter().sum::<f64>() / 7.0;
+        for (i, effect) in decomposition.seasonal.iter().enumerate() {
+            assert!((effect - (weekly[i] - expected_offset)).abs() < 1e-9);
+        }
+        assert!(decomposition.residual.iter().flatten().all(|r| r.abs() < 1e-9));
+        assert_eq!(rolling_average(&daily(&[1.0, 2.0, 3.0]), 2).iter().map(|s| s.value).collect::<Vec<_>>(), vec![1.5, 2.5]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Control API - REST Interface to a Running Simulation
+//!
+//! This module implements an HTTP API (built with the `api` feature) for
+//! driving the company from external tooling:
+//! - Pausing and resuming the simulation, running it a step at a time, and
+//!   changing its speed
+//! - Listing, hiring and offboarding agents, and any agent's status report
+//! - Injecting incidents, support tickets and customer projects
+//! - Fetching reports: the weekly summary, open risks and doctor findings
+//! - Listing recent conversation threads and any thread's message history
+//!
+//! When `token_env` is configured every request must carry
+//! `Authorization: Bearer <token>`; without one the API may only listen on
+//! a loopback address.
+//!
+//! Handlers do not touch the simulation themselves: each request is handed
+//! to the orchestrator, which answers it between events, so injected work
+//! goes through the same message flow as generated work.
+
+use crate::departments::ops::{Priority, Severity};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot, Notify};
+
+/// Requests waiting for the orchestrator before handlers push back
+const REQUEST_QUEUE: usize = 64;
+
+/// What a client asks of the simulation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiCommand {
+    Pause,
+    Resume,
+    /// Run this many steps, then pause
+    Step(u32),
+    SetSpeed(f32),
+    Status,
+    ListAgents,
+    /// Status report of the agent with this name or id
+    AgentStatus(String),
+    Hire(HireRequest),
+    /// Offboard the agent with this name or id
+    Offboard(String),
+    InjectIncident(IncidentRequest),
+    InjectTicket(TicketRequest),
+    InjectProject,
+    Report(String),
+    /// Status of the deployment with this id
+    DeploymentStatus(String),
+    ListThreads,
+    /// Message history of the thread with this id
+    ThreadHistory(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpeedRequest {
+    pub multiplier: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StepRequest {
+    #[serde(default = "default_steps")]
+    pub steps: u32,
+}
+
+fn default_steps() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HireRequest {
+    pub department: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IncidentRequest {
+    pub title: String,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    #[serde(default)]
+    pub affected_services: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TicketRequest {
+    pub title: String,
+    #[serde(default = "default_priority")]
+    pub priority: Priority,
+    pub customer_id: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Routing tags, e.g. `billing`
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn default_severity() -> Severity {
+    Severity::Sev3
+}
+
+fn default_priority() -> Priority {
+    Priority::Normal
+}
+
+/// Command with the channel its answer goes back on; `Err` is the client's fault
+#[derive(Debug)]
+pub struct ApiRequest {
+    pub command: ApiCommand,
+    pub reply: oneshot::Sender<Result<Value, String>>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    requests: mpsc::Sender<ApiRequest>,
+    arrived: Arc<Notify>,
+}
+
+/// Orchestrator side of the API: the requests clients have made
+#[derive(Debug)]
+pub struct ControlApi {
+    requests: mpsc::Receiver<ApiRequest>,
+    arrived: Arc<Notify>,
+}
+
+impl ControlApi {
+    /// Bind the API and serve it in the background; with `token_env` set,
+    /// requests must present the token read from that variable
+    pub async fn serve(addr: &str, token_env: Option<&str>) -> Result<Self, ApiError> {
+        let token = match token_env {
+            Some(env) => Some(std::env::var(env).map_err(|_| ApiError::MissingToken(env.to_string()))?),
+            None => None,
+        };
+        let listener = TcpListener::bind(addr).await
+            .map_err(|e| ApiError::Bind(addr.to_string(), e))?;
+        println!("🎛️ Control API on http://{}", addr);
+
+        let (sender, requests) = mpsc::channel(REQUEST_QUEUE);
+        let arrived = Arc::new(Notify::new());
+        let mut app = router(ApiState { requests: sender, arrived: arrived.clone() });
+        if let Some(token) = token {
+            app = app.layer(middleware::from_fn_with_state(Arc::new(token), require_token));
+        }
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                eprintln!("⚠️ Control API stopped: {}", e);
+            }
+        });
+        Ok(Self { requests, arrived })
+    }
+
+    /// Next request already waiting, if any
+    pub fn try_next(&mut self) -> Option<ApiRequest> {
+        self.requests.try_recv().ok()
+    }
+
+    /// Resolve once a request has arrived since the last call
+    pub async fn arrived(&self) {
+        self.arrived.notified().await;
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/pause", post(|state: State<ApiState>| dispatch(state, ApiCommand::Pause)))
+        .route("/resume", post(|state: State<ApiState>| dispatch(state, ApiCommand::Resume)))
+        .route("/step", post(|state: State<ApiState>, body: Option<Json<StepRequest>>| {
+            dispatch(state, ApiCommand::Step(body.map_or(default_steps(), |Json(body)| body.steps)))
+        }))
+        .route("/status", get(|state: State<ApiState>| dispatch(state, ApiCommand::Status)))
+        .route("/speed", put(|state: State<ApiState>, Json(body): Json<SpeedRequest>| {
+            dispatch(state, ApiCommand::SetSpeed(body.multiplier))
+        }))
+        .route("/agents", get(|state: State<ApiState>| dispatch(state, ApiCommand::ListAgents))
+            .post(|state: State<ApiState>, Json(body): Json<HireRequest>| dispatch(state, ApiCommand::Hire(body))))
+        .route("/agents/:agent", get(|state: State<ApiState>, Path(agent): Path<String>| {
+            dispatch(state, ApiCommand::AgentStatus(agent))
+        }).delete(|state: State<ApiState>, Path(agent): Path<String>| {
+            dispatch(state, ApiCommand::Offboard(agent))
+        }))
+        .route("/incidents", post(|state: State<ApiState>, Json(body): Json<IncidentRequest>| {
+            dispatch(state, ApiCommand::InjectIncident(body))
+        }))
+        .route("/tickets", post(|state: State<ApiState>, Json(body): Json<TicketRequest>| {
+            dispatch(state, ApiCommand::InjectTicket(body))
+        }))
+        .route("/projects", post(|state: State<ApiState>| dispatch(state, ApiCommand::InjectProject)))
+        .route("/deployments/:id", get(|state: State<ApiState>, Path(id): Path<String>| {
+            dispatch(state, ApiCommand::DeploymentStatus(id))
+        }))
+        .route("/threads", get(|state: State<ApiState>| dispatch(state, ApiCommand::ListThreads)))
+        .route("/threads/:id", get(|state: State<ApiState>, Path(id): Path<String>| {
+            dispatch(state, ApiCommand::ThreadHistory(id))
+        }))
+        .route("/reports/:name", get(|state: State<ApiState>, Path(name): Path<String>| {
+            dispatch(state, ApiCommand::Report(name))
+        }))
+        .with_state(state)
+}
+
+/// Refuse requests that do not carry the bearer token
+async fn require_token(State(token): State<Arc<String>>, request: Request, next: Next) -> Response {
+    if !authorized(request.headers(), &token) {
+        return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "missing or invalid bearer token" }))).into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether the headers carry `Authorization: Bearer <token>`
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(presented) = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Compare every byte so the time taken does not reveal the token
+    presented.len() == token.len()
+        && presented.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Hand a command to the orchestrator and wait for its answer
+async fn dispatch(State(state): State<ApiState>, command: ApiCommand) -> (StatusCode, Json<Value>) {
+    let (reply, answer) = oneshot::channel();
+    if state.requests.send(ApiRequest { command, reply }).await.is_err() {
+        return unavailable();
+    }
+    state.arrived.notify_one();
+
+    match answer.await {
+        Ok(Ok(body)) => (StatusCode::OK, Json(body)),
+        Ok(Err(error)) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": error }))),
+        Err(_) => unavailable(),
+    }
+}
+
+fn unavailable() -> (StatusCode, Json<Value>) {
+    (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "error": "simulation is not running" })))
+}
+
+/// Control API errors
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Failed to bind control API on {0}: {1}")]
+    Bind(String, std::io::Error),
+
+    #[error("Control API token not found in environment variable {0}")]
+    MissingToken(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_reach_the_orchestrator_and_answers_come_back() {
+        let (sender, mut requests) = mpsc::channel(REQUEST_QUEUE);
+        let arrived = Arc::new(Notify::new());
+        let state = ApiState { requests: sender, arrived: arrived.clone() };
+
+        let orchestrator = tokio::spawn(async move {
+            let first = requests.recv().await.unwrap();
+            assert_eq!(first.command, ApiCommand::SetSpeed(4.0));
+            first.reply.send(Ok(serde_json::json!({ "speed_multiplier": 4.0 }))).unwrap();
+            let second = requests.recv().await.unwrap();
+            second.reply.send(Err("unknown report 'nope'".to_string())).unwrap();
+        });
+
+        let (status, Json(body)) = dispatch(State(state.clone()), ApiCommand::SetSpeed(4.0)).await;
+        assert_eq!((status, body["speed_multiplier"].as_f64()), (StatusCode::OK, Some(4.0)));
+        let (status, _) = dispatch(State(state.clone()), ApiCommand::Report("nope".to_string())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        orchestrator.await.unwrap();
+
+        // Once the orchestrator is gone requests are refused
+        let (status, _) = dispatch(State(state), ApiCommand::Pause).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let ticket: TicketRequest = serde_json::from_str(r#"{"title": "Cannot log in", "customer_id": null}"#).unwrap();
+        assert_eq!(ticket.priority, Priority::Normal);
+    }
+
+    #[test]
+    fn test_requests_need_the_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert!(!authorized(&headers, "s3cret"));
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert!(!authorized(&headers, "s3cret"));
+        headers.insert(header::AUTHORIZATION, "Basic s3cret".parse().unwrap());
+        assert!(!authorized(&headers, "s3cret"));
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(authorized(&headers, "s3cret"));
+    }
+}
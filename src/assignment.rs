@@ -0,0 +1,158 @@
+//! Work Assignment - Skill-Based Matching
+//!
+//! This module implements matching work to the agent best suited for it:
+//! - Kinds of work (features, deployments, tickets, incidents, ...) and the
+//!   skill each one draws on
+//! - Candidate ranking by skill, discounted by the open work each agent
+//!   already carries, so busy experts do not get everything
+//! - Durations and success probabilities scaled by the assignee's skill
+//!
+//! Agents report their own skill for a kind of work through
+//! `DepartmentAgent::skill`; departments without that skill are skipped.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Skill points an agent's score loses for each open work item
+const LOAD_PENALTY: f64 = 10.0;
+
+/// Kind of work, each drawing on one skill of one department
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum WorkKind {
+    /// Building a project feature (Engineering coding skill)
+    Feature,
+    /// Shipping a release (DevOps deployment skill)
+    Deployment,
+    /// Servers and capacity (DevOps infrastructure skill)
+    Infrastructure,
+    /// Customer support tickets (Ops support skill)
+    Ticket,
+    /// Service incidents and rollouts (Ops incident skill)
+    Incident,
+    /// Security incidents (InfoSec incident response skill)
+    SecurityIncident,
+    /// Network changes (Networking network skill)
+    Network,
+    /// Marketing campaigns (Marketing campaign skill)
+    Campaign,
+}
+
+impl WorkKind {
+    pub const ALL: [WorkKind; 8] = [
+        WorkKind::Feature,
+        WorkKind::Deployment,
+        WorkKind::Infrastructure,
+        WorkKind::Ticket,
+        WorkKind::Incident,
+        WorkKind::SecurityIncident,
+        WorkKind::Network,
+        WorkKind::Campaign,
+    ];
+
+    /// Department that does this kind of work
+    pub fn department(&self) -> &'static str {
+        match self {
+            WorkKind::Feature => "Engineering",
+            WorkKind::Deployment | WorkKind::Infrastructure => "DevOps",
+            WorkKind::Ticket | WorkKind::Incident => "Ops",
+            WorkKind::SecurityIncident => "InfoSec",
+            WorkKind::Network => "Networking",
+            WorkKind::Campaign => "Marketing",
+        }
+    }
+
+    /// Work of a department's project task
+    pub fn for_department(department: &str) -> Option<Self> {
+        match department {
+            "Engineering" => Some(WorkKind::Feature),
+            "DevOps" => Some(WorkKind::Deployment),
+            "Ops" => Some(WorkKind::Incident),
+            "InfoSec" => Some(WorkKind::SecurityIncident),
+            "Networking" => Some(WorkKind::Network),
+            "Marketing" => Some(WorkKind::Campaign),
+            _ => None,
+        }
+    }
+}
+
+/// Agent that could take a piece of work
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub agent_id: Uuid,
+    /// Skill for the work, 0-100
+    pub skill: u8,
+    /// Open work items the agent already carries
+    pub load: usize,
+}
+
+impl Candidate {
+    fn score(&self) -> f64 {
+        f64::from(self.skill) - LOAD_PENALTY * self.load as f64
+    }
+}
+
+/// Work matched to an agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub agent_id: Uuid,
+    pub skill: u8,
+    /// Expected duration at the assignee's skill
+    pub duration: chrono::Duration,
+    /// Chance each attempt at the work succeeds
+    pub success_probability: f64,
+}
+
+/// Best-suited, least-loaded candidate; ties go to the more skilled, then
+/// the lower id so assignments are reproducible
+pub fn best_candidate(candidates: &[Candidate]) -> Option<&Candidate> {
+    candidates.iter().max_by(|a, b| {
+        a.score().total_cmp(&b.score())
+            .then(a.skill.cmp(&b.skill))
+            .then(b.agent_id.cmp(&a.agent_id))
+    })
+}
+
+/// Match work of the given base duration to the best candidate
+pub fn assign(candidates: &[Candidate], base_duration: chrono::Duration) -> Option<Assignment> {
+    best_candidate(candidates).map(|candidate| Assignment {
+        agent_id: candidate.agent_id,
+        skill: candidate.skill,
+        duration: scaled_duration(base_duration, candidate.skill),
+        success_probability: success_probability(candidate.skill),
+    })
+}
+
+/// Duration at a skill level: half the base for a perfect score, the base
+/// at 50 and one and a half times it for a novice
+pub fn scaled_duration(base: chrono::Duration, skill: u8) -> chrono::Duration {
+    let factor = 1.5 - f64::from(skill.min(100)) / 100.0;
+    chrono::Duration::minutes((base.num_minutes() as f64 * factor).round() as i64)
+}
+
+/// Chance an attempt succeeds: a coin flip for a novice, certain at 100
+pub fn success_probability(skill: u8) -> f64 {
+    0.5 + f64::from(skill.min(100)) / 200.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skill_wins_until_the_expert_is_busy() {
+        let (expert, generalist) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut candidates = vec![
+            Candidate { agent_id: expert, skill: 95, load: 0 },
+            Candidate { agent_id: generalist, skill: 75, load: 0 },
+        ];
+        let assignment = assign(&candidates, chrono::Duration::hours(10)).unwrap();
+        assert_eq!(assignment.agent_id, expert);
+        assert_eq!(assignment.duration, chrono::Duration::minutes(330));
+        assert!((assignment.success_probability - 0.975).abs() < 1e-9);
+
+        // Three open items cost the expert more than its 20-point edge
+        candidates[0].load = 3;
+        assert_eq!(best_candidate(&candidates).unwrap().agent_id, generalist);
+        assert!(assign(&[], chrono::Duration::hours(1)).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! Autoscaling - Server Fleets Sized by Load and Cost
+//!
+//! This module implements the decisions behind DevOps server auto-scaling:
+//! - Fleets: a server and the servers auto-scaling added for it, named
+//!   `<hostname>-scale-<n>`
+//! - Scale-up while a fleet runs hot, up to its maximum size and only while
+//!   the extra server fits the DevOps budget
+//! - Scale-down once a fleet has been idle for a number of checks in a row,
+//!   newest server first, down to its minimum size
+//! - Server costs from the finance model: while the servers cost more than
+//!   the budget, idle fleets shrink without waiting out the window
+//!
+//! Fleets below their minimum size grow whatever the cost. DevOps agents
+//! provision and remove the servers; this module only decides.
+
+use crate::config::AutoscalingSettings;
+use crate::departments::devops::ServerStatus;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What scaling may cost, from the finance model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScalingPolicy {
+    pub settings: AutoscalingSettings,
+    /// Dollars a server costs per month
+    pub server_monthly_cost: f64,
+    /// Dollars per month the servers may cost in all; no limit when unset
+    pub monthly_budget: Option<f64>,
+}
+
+impl ScalingPolicy {
+    fn within_budget(&self, servers: usize) -> bool {
+        self.monthly_budget.iter().all(|budget| servers as f64 * self.server_monthly_cost <= *budget)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalingDecision {
+    /// Add a server to the fleet
+    ScaleUp { fleet: String, reason: String },
+    /// Remove a server of the fleet
+    ScaleDown { fleet: String, hostname: String, reason: String },
+    /// A hot fleet the limits or the budget keep from growing
+    Held { fleet: String, reason: String },
+}
+
+/// Fleet a server belongs to
+pub fn fleet_of(hostname: &str) -> &str {
+    hostname.split("-scale-").next().unwrap_or(hostname)
+}
+
+/// Idle streaks of the fleets between checks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Autoscaler {
+    /// Checks in a row each fleet has been idle
+    idle_checks: BTreeMap<String, u32>,
+}
+
+impl Autoscaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decide, fleet by fleet, which to grow and which to shrink at a check
+    pub fn decide<'a>(&mut self, servers: impl IntoIterator<Item = &'a ServerStatus>, policy: &ScalingPolicy) -> Vec<ScalingDecision> {
+        let mut fleets: BTreeMap<&str, Vec<&ServerStatus>> = BTreeMap::new();
+        for server in servers {
+            fleets.entry(fleet_of(&server.hostname)).or_default().push(server);
+        }
+        self.idle_checks.retain(|fleet, _| fleets.contains_key(fleet.as_str()));
+
+        let settings = &policy.settings;
+        let mut total = fleets.values().map(Vec::len).sum::<usize>();
+        let over_budget = !policy.within_budget(total);
+        let mut decisions = Vec::new();
+        for (fleet, members) in fleets {
+            let limits = settings.limits(fleet);
+            let usage = members.iter().map(|server| f64::from(server.cpu_usage.max(server.memory_usage))).sum::<f64>() / members.len() as f64;
+            let idle = usage < settings.scale_down_percent;
+            let idle_checks = self.idle_checks.entry(fleet.to_string()).or_insert(0);
+            *idle_checks = if idle { *idle_checks + 1 } else { 0 };
+
+            let grow = if members.len() < limits.min_servers {
+                Some(format!("{} servers, below the minimum of {}", members.len(), limits.min_servers))
+            } else if usage > settings.scale_up_percent {
+                let reason = format!("{:.0}% used", usage);
+                if members.len() >= limits.max_servers {
+                    decisions.push(ScalingDecision::Held { fleet: fleet.to_string(), reason: format!("{} at the maximum of {} servers", reason, limits.max_servers) });
+                    None
+                } else if !policy.within_budget(total + 1) {
+                    let cost = (total + 1) as f64 * policy.server_monthly_cost;
+                    decisions.push(ScalingDecision::Held { fleet: fleet.to_string(), reason: format!("{}, another server would cost ${:.0}/month, over budget", reason, cost) });
+                    None
+                } else {
+                    Some(reason)
+                }
+            } else {
+                None
+            };
+            if let Some(reason) = grow {
+                total += 1;
+                decisions.push(ScalingDecision::ScaleUp { fleet: fleet.to_string(), reason });
+                continue;
+            }
+
+            let shrink = if members.len() > limits.max_servers {
+                Some(format!("{} servers, above the maximum of {}", members.len(), limits.max_servers))
+            } else if idle && members.len() > limits.min_servers && (*idle_checks >= settings.scale_down_after_checks || over_budget) {
+                Some(format!("{:.0}% used for {} checks", usage, idle_checks))
+            } else {
+                None
+            };
+            if let Some(reason) = shrink {
+                // The newest server goes first, and the fleet's first server last
+                let Some(newest) = members.iter().min_by_key(|server| (server.hostname == fleet, server.uptime)) else {
+                    continue;
+                };
+                *idle_checks = 0;
+                total -= 1;
+                decisions.push(ScalingDecision::ScaleDown { fleet: fleet.to_string(), hostname: newest.hostname.clone(), reason });
+            }
+        }
+        decisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::devops::ServerState;
+
+    fn server(hostname: &str, usage: f32, uptime: u64) -> ServerStatus {
+        ServerStatus {
+            id: hostname.to_string(),
+            hostname: hostname.to_string(),
+            status: ServerState::Online,
+            cpu_usage: usage,
+            memory_usage: usage,
+            disk_usage: 0.0,
+            uptime,
+            last_check: chrono::Utc::now(),
+            spec: None,
+        }
+    }
+
+    #[test]
+    fn test_scales_up_within_budget_and_down_after_idle_window() {
+        let mut policy = ScalingPolicy { server_monthly_cost: 200.0, monthly_budget: Some(600.0), ..ScalingPolicy::default() };
+        let mut autoscaler = Autoscaler::new();
+
+        // Two hot servers fit a third under the budget, not a fourth
+        let fleet = vec![server("web-1", 95.0, 900), server("web-1-scale-1", 95.0, 300)];
+        assert!(matches!(&autoscaler.decide(&fleet, &policy)[..], [ScalingDecision::ScaleUp { .. }]));
+        let fleet = vec![server("web-1", 95.0, 900), server("web-1-scale-1", 95.0, 300), server("web-1-scale-2", 95.0, 0)];
+        assert!(matches!(&autoscaler.decide(&fleet, &policy)[..], [ScalingDecision::Held { .. }]));
+
+        // Idle for three checks, the newest server goes
+        let idle: Vec<ServerStatus> = fleet.iter().map(|server| ServerStatus { cpu_usage: 10.0, memory_usage: 10.0, ..server.clone() }).collect();
+        assert!(autoscaler.decide(&idle, &policy).is_empty());
+        assert!(autoscaler.decide(&idle, &policy).is_empty());
+        assert_eq!(autoscaler.decide(&idle, &policy), vec![ScalingDecision::ScaleDown {
+            fleet: "web-1".to_string(),
+            hostname: "web-1-scale-2".to_string(),
+            reason: "10% used for 3 checks".to_string(),
+        }]);
+
+        // Over budget, an idle fleet shrinks at once, but not below its minimum
+        policy.monthly_budget = Some(100.0);
+        assert!(matches!(&autoscaler.decide(&idle[..2], &policy)[..], [ScalingDecision::ScaleDown { .. }]));
+        assert!(autoscaler.decide(&idle[..1], &policy).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! Backups - Catalogs, Restore Drills and Recovery Objectives
+//!
+//! This module implements what DevOps keeps of its backups:
+//! - A catalog of backups per system, servers by hostname and clusters by
+//!   name, pruned once they are older than the retention period; the newest
+//!   backup of a system is always kept
+//! - Restore drills at a set interval, each restoring the newest backup of
+//!   one system in turn; a drill can fail, and a failed backup is never
+//!   counted as restorable again
+//! - Recovery point and recovery time objectives computed from the catalog
+//!   and the drills: the data a restore right now would lose, and the time
+//!   the slowest restore took
+//!
+//! DevOps agents take the backups and run the drills at midnight on the
+//! simulated clock.
+
+use crate::config::BackupSettings;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Drills kept; the oldest go first
+const MAX_DRILLS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub id: Uuid,
+    pub system: String,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub size_gb: u32,
+    /// Set by a restore drill: whether the backup could be restored
+    pub restorable: Option<bool>,
+}
+
+/// Restore of a backup into a scratch environment to prove it works
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreDrill {
+    pub backup_id: Uuid,
+    pub system: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub succeeded: bool,
+    /// How long the restore took
+    pub restore_minutes: i64,
+    /// Data written since the backup, lost had it been a real restore
+    pub data_loss_minutes: i64,
+}
+
+/// Recovery objectives as the backups and drills stand
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryMetrics {
+    pub systems: usize,
+    pub backups: usize,
+    /// Age of the newest backup of the worst-covered system, in minutes
+    pub rpo_minutes: Option<i64>,
+    /// Slowest of the latest successful drills per system, in minutes
+    pub rto_minutes: Option<i64>,
+    pub drills: usize,
+    pub failed_drills: usize,
+    /// Systems whose newest drill failed
+    pub unrestorable: Vec<String>,
+}
+
+impl RecoveryMetrics {
+    /// Fold in another agent's metrics, keeping the worst objectives
+    pub fn combine(&mut self, other: RecoveryMetrics) {
+        self.systems += other.systems;
+        self.backups += other.backups;
+        self.rpo_minutes = self.rpo_minutes.max(other.rpo_minutes);
+        self.rto_minutes = self.rto_minutes.max(other.rto_minutes);
+        self.drills += other.drills;
+        self.failed_drills += other.failed_drills;
+        self.unrestorable.extend(other.unrestorable);
+    }
+}
+
+/// Backups of every system a DevOps agent runs, and the drills on them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupCatalog {
+    /// Oldest first, by system
+    backups: BTreeMap<String, Vec<Backup>>,
+    drills: Vec<RestoreDrill>,
+    /// System the next drill restores after
+    last_drilled: Option<String>,
+}
+
+impl BackupCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn backups(&self, system: &str) -> &[Backup] {
+        self.backups.get(system).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn drills(&self) -> &[RestoreDrill] {
+        &self.drills
+    }
+
+    pub fn record(&mut self, system: &str, size_gb: u32, now: chrono::DateTime<chrono::Utc>) -> &Backup {
+        let backups = self.backups.entry(system.to_string()).or_default();
+        backups.push(Backup { id: Uuid::new_v4(), system: system.to_string(), taken_at: now, size_gb, restorable: None });
+        backups.last().expect("just pushed")
+    }
+
+    /// Drop backups older than the retention period, keeping each system's
+    /// newest, and systems that are gone; returns how many went
+    pub fn prune(&mut self, systems: &[String], retention_days: u32, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let cutoff = now - chrono::Duration::days(i64::from(retention_days));
+        let before: usize = self.backups.values().map(Vec::len).sum();
+        self.backups.retain(|system, _| systems.contains(system));
+        for backups in self.backups.values_mut() {
+            let newest = backups.len().saturating_sub(1);
+            let mut index = 0;
+            backups.retain(|backup| {
+                index += 1;
+                index > newest || backup.taken_at >= cutoff
+            });
+        }
+        before - self.backups.values().map(Vec::len).sum::<usize>()
+    }
+
+    /// Whether a drill is due at `now`
+    pub fn drill_due(&self, settings: &BackupSettings, now: chrono::DateTime<chrono::Utc>) -> bool {
+        settings.drill_every_days > 0 && self.drills.last()
+            .iter().all(|drill| now - drill.started_at >= chrono::Duration::days(i64::from(settings.drill_every_days)))
+    }
+
+    /// Restore the newest backup of the next system in turn
+    pub fn drill(&mut self, settings: &BackupSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Option<&RestoreDrill> {
+        let system = self.backups.keys()
+            .find(|system| self.last_drilled.iter().all(|last| *system > last))
+            .or_else(|| self.backups.keys().next())?
+            .clone();
+        let backup = self.backups.get_mut(&system)?.last_mut()?;
+        let restore_minutes = (f64::from(backup.size_gb) * settings.restore_minutes_per_gb * rng.gen_range(0.5..1.5)).round().max(1.0) as i64;
+        let succeeded = !rng.gen_bool(settings.drill_failure_probability.clamp(0.0, 1.0));
+        backup.restorable = Some(succeeded);
+        self.drills.push(RestoreDrill {
+            backup_id: backup.id,
+            system: system.clone(),
+            started_at: now,
+            succeeded,
+            restore_minutes,
+            data_loss_minutes: (now - backup.taken_at).num_minutes(),
+        });
+        if self.drills.len() > MAX_DRILLS {
+            self.drills.remove(0);
+        }
+        self.last_drilled = Some(system);
+        self.drills.last()
+    }
+
+    pub fn metrics(&self, now: chrono::DateTime<chrono::Utc>) -> RecoveryMetrics {
+        // A backup a drill could not restore protects nothing
+        let rpo_minutes = self.backups.values()
+            .filter_map(|backups| backups.iter().rev().find(|backup| backup.restorable != Some(false)))
+            .map(|backup| (now - backup.taken_at).num_minutes())
+            .max();
+        let mut latest: BTreeMap<&str, &RestoreDrill> = BTreeMap::new();
+        for drill in &self.drills {
+            latest.insert(&drill.system, drill);
+        }
+        RecoveryMetrics {
+            systems: self.backups.len(),
+            backups: self.backups.values().map(Vec::len).sum(),
+            rpo_minutes,
+            rto_minutes: latest.values().filter(|drill| drill.succeeded).map(|drill| drill.restore_minutes).max(),
+            drills: self.drills.len(),
+            failed_drills: self.drills.iter().filter(|drill| !drill.succeeded).count(),
+            unrestorable: latest.values().filter(|drill| !drill.succeeded).map(|drill| drill.system.clone()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_prune_drill_and_recovery_objectives() {
+        let settings = BackupSettings { drill_failure_probability: 0.0, restore_minutes_per_gb: 1.0, ..BackupSettings::default() };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let day = |n: i64| start + chrono::Duration::days(n);
+        let mut catalog = BackupCatalog::new();
+        for n in 0..40 {
+            catalog.record("db-1", 100, day(n));
+        }
+        catalog.record("web-1", 10, day(38));
+
+        // 30 days kept; web-1's only backup stays however old
+        let systems = vec!["db-1".to_string(), "web-1".to_string()];
+        assert_eq!(catalog.prune(&systems, 30, day(39)), 9);
+        assert_eq!(catalog.backups("db-1").len(), 31);
+        assert_eq!(catalog.prune(&systems, 1, day(45)), 30);
+        assert_eq!(catalog.backups("web-1").len(), 1);
+
+        // Drills take turns and restore each system's newest backup
+        assert!(catalog.drill_due(&settings, day(45)));
+        let drill = catalog.drill(&settings, day(45), &mut rng).unwrap();
+        assert_eq!((drill.system.as_str(), drill.data_loss_minutes), ("db-1", 6 * 24 * 60));
+        assert!((50..=150).contains(&drill.restore_minutes));
+        assert!(!catalog.drill_due(&settings, day(46)));
+        assert_eq!(catalog.drill(&settings, day(46), &mut rng).unwrap().system, "web-1");
+
+        let metrics = catalog.metrics(day(46));
+        assert_eq!(metrics.rpo_minutes, Some(8 * 24 * 60));
+        assert_eq!(metrics.rto_minutes, Some(catalog.drills()[0].restore_minutes));
+        assert_eq!((metrics.drills, metrics.failed_drills), (2, 0));
+    }
+}
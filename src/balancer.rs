@@ -0,0 +1,228 @@
+//! Load Balancer Runtime - Health Checks, Ejection and Request Distribution
+//!
+//! This module implements what Networking's load balancers do while they
+//! run:
+//! - Health checks at each balancer's interval against the DevOps server
+//!   behind every backend, found by its DNS A record. Online servers pass,
+//!   degraded ones answer too slowly half the time, and servers offline,
+//!   critical or in maintenance fail; backends with no DevOps server pass
+//! - Ejection of a backend after `unhealthy_threshold` failed checks in a
+//!   row, and readmission after `healthy_threshold` passed ones
+//! - Requests spread over the healthy backends by the balancer's algorithm:
+//!   evenly for round robin, by weight for weighted round robin, towards
+//!   idle servers for least connections, and by client address for IP hash;
+//!   with no healthy backend requests are dropped
+//!
+//! The orchestrator runs the checks every simulation step and keeps
+//! per-balancer and per-backend request counts.
+
+use crate::departments::devops::{ServerState, ServerStatus};
+use crate::departments::networking::{BackendServer, HealthCheckType, LoadBalancer, LoadBalancerStatus, LoadBalancingAlgorithm};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+
+/// Checks run per call at most; more would not change the outcome
+const MAX_CHECKS: u64 = 20;
+
+/// Distinct clients IP hash spreads requests over
+const CLIENTS: u32 = 256;
+
+/// CPU usage assumed for backends no DevOps server stands behind
+const UNKNOWN_CPU_USAGE: f32 = 50.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoadBalancerStats {
+    pub checks: u64,
+    pub ejections: u64,
+    pub readmissions: u64,
+    pub requests: u64,
+    /// Requests that arrived with no healthy backend
+    pub dropped: u64,
+}
+
+/// A backend ejected or readmitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendChange {
+    pub load_balancer: String,
+    pub backend: IpAddr,
+    /// Hostname of the server behind it, if known
+    pub hostname: Option<String>,
+    pub healthy: bool,
+}
+
+/// Whether one check of a backend on the given server passes
+fn probe(server: Option<&ServerStatus>, check_type: &HealthCheckType, rng: &mut impl Rng) -> bool {
+    let Some(server) = server else {
+        return true;
+    };
+    match server.status {
+        ServerState::Online => true,
+        // A ping still gets through to a slow server
+        ServerState::Degraded => *check_type == HealthCheckType::ICMP || rng.gen_bool(0.5),
+        ServerState::Offline | ServerState::Critical | ServerState::Maintenance => false,
+    }
+}
+
+/// Run the checks due over `elapsed_seconds`, ejecting and readmitting
+/// backends. `servers` are the DevOps servers by the address DNS gives
+/// them.
+pub fn run_checks(lb: &mut LoadBalancer, servers: &HashMap<IpAddr, &ServerStatus>, elapsed_seconds: u64, rng: &mut impl Rng) -> Vec<BackendChange> {
+    let check = lb.health_check.clone();
+    let checks = (elapsed_seconds / u64::from(check.interval_seconds.max(1))).clamp(1, MAX_CHECKS);
+    let mut changes = Vec::new();
+    for _ in 0..checks {
+        lb.stats.checks += 1;
+        for backend in lb.backends.iter_mut() {
+            let server = servers.get(&backend.ip_address).copied();
+            if probe(server, &check.check_type, rng) {
+                backend.consecutive_failures = 0;
+                backend.consecutive_successes += 1;
+            } else {
+                backend.consecutive_successes = 0;
+                backend.consecutive_failures += 1;
+            }
+            let flipped = if backend.healthy {
+                backend.consecutive_failures >= check.unhealthy_threshold.max(1)
+            } else {
+                backend.consecutive_successes >= check.healthy_threshold.max(1)
+            };
+            if flipped {
+                backend.healthy = !backend.healthy;
+                if backend.healthy {
+                    lb.stats.readmissions += 1;
+                } else {
+                    lb.stats.ejections += 1;
+                }
+                changes.push(BackendChange {
+                    load_balancer: lb.name.clone(),
+                    backend: backend.ip_address,
+                    hostname: server.map(|server| server.hostname.clone()),
+                    healthy: backend.healthy,
+                });
+            }
+        }
+    }
+    if lb.status != LoadBalancerStatus::Draining {
+        lb.status = if lb.backends.iter().any(|backend| backend.healthy) { LoadBalancerStatus::Active } else { LoadBalancerStatus::Offline };
+    }
+    changes
+}
+
+/// Split `requests` in proportion to `shares`, handing the rounding
+/// remainder to the largest shares
+fn apportion(requests: u64, shares: &[f64]) -> Vec<u64> {
+    let total: f64 = shares.iter().sum();
+    if total <= 0.0 {
+        return apportion(requests, &vec![1.0; shares.len()]);
+    }
+    let mut counts: Vec<u64> = shares.iter().map(|share| (requests as f64 * share / total).floor() as u64).collect();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| shares[b].total_cmp(&shares[a]));
+    let remainder = requests - counts.iter().sum::<u64>();
+    for index in order.into_iter().cycle().take(remainder as usize) {
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Spread requests over the healthy backends by the balancer's algorithm;
+/// returns the requests each backend got
+pub fn distribute(lb: &mut LoadBalancer, requests: u64, servers: &HashMap<IpAddr, &ServerStatus>) -> BTreeMap<IpAddr, u64> {
+    lb.stats.requests += requests;
+    let healthy: Vec<&mut BackendServer> = lb.backends.iter_mut().filter(|backend| backend.healthy).collect();
+    if healthy.is_empty() {
+        lb.stats.dropped += requests;
+        return BTreeMap::new();
+    }
+    let shares: Vec<f64> = match lb.algorithm {
+        LoadBalancingAlgorithm::RoundRobin => vec![1.0; healthy.len()],
+        LoadBalancingAlgorithm::WeightedRoundRobin => healthy.iter().map(|backend| f64::from(backend.weight)).collect(),
+        LoadBalancingAlgorithm::LeastConnections => healthy.iter()
+            .map(|backend| {
+                let cpu = servers.get(&backend.ip_address).map_or(UNKNOWN_CPU_USAGE, |server| server.cpu_usage);
+                f64::from((100.0 - cpu).max(1.0))
+            })
+            .collect(),
+        LoadBalancingAlgorithm::IPHash => {
+            let mut clients = vec![0.0; healthy.len()];
+            for client in 0..CLIENTS {
+                let mut hasher = DefaultHasher::new();
+                (&lb.name, client).hash(&mut hasher);
+                clients[(hasher.finish() % healthy.len() as u64) as usize] += 1.0;
+            }
+            clients
+        }
+    };
+    let counts = apportion(requests, &shares);
+    let mut served = BTreeMap::new();
+    for (backend, count) in healthy.into_iter().zip(counts) {
+        backend.requests += count;
+        *served.entry(backend.ip_address).or_default() += count;
+    }
+    served
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::networking::HealthCheck;
+    use rand::SeedableRng;
+
+    fn server(hostname: &str, status: ServerState, cpu_usage: f32) -> ServerStatus {
+        ServerStatus {
+            id: hostname.to_string(),
+            hostname: hostname.to_string(),
+            status,
+            cpu_usage,
+            memory_usage: 40.0,
+            disk_usage: 40.0,
+            uptime: 0,
+            last_check: chrono::Utc::now(),
+            spec: None,
+        }
+    }
+
+    fn backend(last_octet: u8, weight: u32) -> BackendServer {
+        BackendServer { ip_address: IpAddr::from([10, 1, 0, last_octet]), port: 443, weight, healthy: true, consecutive_successes: 0, consecutive_failures: 0, requests: 0 }
+    }
+
+    #[test]
+    fn test_failing_backends_are_ejected_readmitted_and_skipped() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut lb = LoadBalancer {
+            id: "lb-web".to_string(),
+            name: "web".to_string(),
+            algorithm: LoadBalancingAlgorithm::WeightedRoundRobin,
+            backends: vec![backend(1, 3), backend(2, 1)],
+            health_check: HealthCheck { check_type: HealthCheckType::HTTP, interval_seconds: 10, timeout_seconds: 5, healthy_threshold: 3, unhealthy_threshold: 2 },
+            status: LoadBalancerStatus::Active,
+            stats: LoadBalancerStats::default(),
+        };
+        fn by_address<'a>(web_1: &'a ServerStatus, web_2: &'a ServerStatus) -> HashMap<IpAddr, &'a ServerStatus> {
+            HashMap::from([(IpAddr::from([10, 1, 0, 1]), web_1), (IpAddr::from([10, 1, 0, 2]), web_2)])
+        }
+        let (web_1, mut web_2) = (server("web-1", ServerState::Online, 20.0), server("web-2", ServerState::Offline, 80.0));
+
+        // One failed check is not enough to eject
+        assert!(run_checks(&mut lb, &by_address(&web_1, &web_2), 10, &mut rng).is_empty());
+        let changes = run_checks(&mut lb, &by_address(&web_1, &web_2), 10, &mut rng);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].hostname.as_deref(), changes[0].healthy), (Some("web-2"), false));
+        let served = distribute(&mut lb, 100, &by_address(&web_1, &web_2));
+        assert_eq!(served, BTreeMap::from([(IpAddr::from([10, 1, 0, 1]), 100)]));
+
+        web_2.status = ServerState::Online;
+        assert!(run_checks(&mut lb, &by_address(&web_1, &web_2), 20, &mut rng).is_empty());
+        assert!(run_checks(&mut lb, &by_address(&web_1, &web_2), 10, &mut rng)[0].healthy);
+        assert_eq!(distribute(&mut lb, 100, &by_address(&web_1, &web_2))[&IpAddr::from([10, 1, 0, 2])], 25);
+
+        // Least connections favours the idle server
+        lb.algorithm = LoadBalancingAlgorithm::LeastConnections;
+        assert_eq!(distribute(&mut lb, 100, &by_address(&web_1, &web_2))[&IpAddr::from([10, 1, 0, 1])], 80);
+        assert_eq!((lb.stats.ejections, lb.stats.readmissions, lb.stats.requests, lb.stats.dropped), (1, 1, 300, 0));
+    }
+}
@@ -0,0 +1,170 @@
+//! Capacity Planning - Utilization Trends and Exhaustion Forecasts
+//!
+//! This module implements Ops' capacity planning:
+//! - Daily samples of how full each resource is, in percent: CPU across the
+//!   DevOps servers, open tickets against what the Ops agents can hold, and
+//!   the busiest network segment's traffic against its capacity
+//! - A trend fitted over the last few simulated weeks of samples, and a
+//!   forecast of the day each resource reaches its limit at that rate
+//! - Resources forecast to run out within the planning horizon get a
+//!   proactive request, at most once per horizon: more servers from DevOps,
+//!   and budget from Finance for the servers, network capacity or Ops hires
+//!
+//! The orchestrator samples the resources every simulated midnight and sends
+//! the requests.
+
+use crate::analytics::{self, KpiSample};
+use crate::config::CapacitySettings;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Daily samples kept per resource, a year's worth
+const MAX_SAMPLES: usize = 365;
+
+/// Samples a trend needs before it is trusted, a week's worth
+const MIN_SAMPLES: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    ServerCpu,
+    Tickets,
+    Traffic,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 3] = [Resource::ServerCpu, Resource::Tickets, Resource::Traffic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resource::ServerCpu => "server CPU",
+            Resource::Tickets => "Ops ticket capacity",
+            Resource::Traffic => "network capacity",
+        }
+    }
+}
+
+/// Where a resource is heading at its current rate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityForecast {
+    pub resource: Resource,
+    pub at: DateTime<Utc>,
+    /// Latest sample, in percent
+    pub current: f64,
+    /// Fitted growth in percentage points per week
+    pub growth_per_week: f64,
+    /// When it reaches the limit; `None` when it is not growing
+    pub exhausted_at: Option<DateTime<Utc>>,
+}
+
+impl CapacityForecast {
+    pub fn description(&self) -> String {
+        match self.exhausted_at {
+            Some(exhausted_at) => format!("{} at {:.0}%, growing {:.1} points a week, runs out around {}",
+                                          self.resource.name(), self.current, self.growth_per_week, exhausted_at.format("%Y-%m-%d")),
+            None => format!("{} at {:.0}%, not growing", self.resource.name(), self.current),
+        }
+    }
+}
+
+/// Utilization history and the forecasts made from it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacityPlanner {
+    samples: BTreeMap<Resource, VecDeque<KpiSample>>,
+    /// When a request was last sent for each resource
+    requested: BTreeMap<Resource, DateTime<Utc>>,
+    /// Latest forecast of each resource
+    forecasts: BTreeMap<Resource, CapacityForecast>,
+}
+
+impl CapacityPlanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record how full a resource is, in percent
+    pub fn record(&mut self, resource: Resource, at: DateTime<Utc>, percent: f64) {
+        let samples = self.samples.entry(resource).or_default();
+        samples.push_back(KpiSample { at, value: percent });
+        if samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+    }
+
+    /// Forecast a resource from its samples over the lookback; `None` until
+    /// there are enough of them
+    pub fn forecast(&self, resource: Resource, settings: &CapacitySettings, now: DateTime<Utc>) -> Option<CapacityForecast> {
+        let since = now - chrono::Duration::weeks(i64::from(settings.lookback_weeks));
+        let samples: Vec<KpiSample> = self.samples.get(&resource)?.iter().filter(|sample| sample.at >= since).copied().collect();
+        if samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let slope_per_day = analytics::trend(&samples)?.slope_per_day;
+        let current = samples[samples.len() - 1].value;
+        let exhausted_at = if current >= settings.limit_percent {
+            Some(now)
+        } else if slope_per_day > 0.0 {
+            let days = (settings.limit_percent - current) / slope_per_day;
+            Some(now + chrono::Duration::minutes((days * 24.0 * 60.0).min(1e9).round() as i64))
+        } else {
+            None
+        };
+        Some(CapacityForecast { resource, at: now, current, growth_per_week: slope_per_day * 7.0, exhausted_at })
+    }
+
+    /// Forecast every resource and return those running out within the
+    /// horizon that were not requested within it already
+    pub fn plan(&mut self, settings: &CapacitySettings, now: DateTime<Utc>) -> Vec<CapacityForecast> {
+        let horizon = chrono::Duration::weeks(i64::from(settings.horizon_weeks));
+        let mut due = Vec::new();
+        for resource in Resource::ALL {
+            let Some(forecast) = self.forecast(resource, settings, now) else {
+                continue;
+            };
+            let running_out = forecast.exhausted_at.is_some_and(|at| at <= now + horizon);
+            let requested = self.requested.get(&resource).is_some_and(|at| now < *at + horizon);
+            if running_out && !requested {
+                self.requested.insert(resource, now);
+                due.push(forecast.clone());
+            }
+            self.forecasts.insert(resource, forecast);
+        }
+        due
+    }
+
+    pub fn forecasts(&self) -> impl Iterator<Item = &CapacityForecast> {
+        self.forecasts.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growing_resources_are_requested_once_per_horizon() {
+        let settings = CapacitySettings::default();
+        let start: DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let day = |days: i64| start + chrono::Duration::days(days);
+        let mut planner = CapacityPlanner::new();
+        for days in 0..14 {
+            planner.record(Resource::ServerCpu, day(days), 40.0 + days as f64);
+            planner.record(Resource::Tickets, day(days), 50.0);
+        }
+        planner.record(Resource::Traffic, day(13), 99.0);
+
+        // CPU gains 7 points a week: 85% is 32 days past the 53% of today
+        let due = planner.plan(&settings, day(13));
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].resource, due[0].growth_per_week.round()), (Resource::ServerCpu, 7.0));
+        assert_eq!(due[0].exhausted_at, Some(day(45)));
+        assert_eq!(planner.forecast(Resource::Tickets, &settings, day(13)).unwrap().exhausted_at, None);
+        // Too few samples of traffic to forecast
+        assert!(planner.forecast(Resource::Traffic, &settings, day(13)).is_none());
+
+        planner.record(Resource::ServerCpu, day(14), 54.0);
+        assert!(planner.plan(&settings, day(14)).is_empty());
+        assert_eq!(planner.forecasts().count(), 2);
+    }
+}
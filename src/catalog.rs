@@ -0,0 +1,552 @@
+//! Service Catalog - Customers, Services & Tenancy
+//!
+//! This module implements the company's hosted service model:
+//! - Services offered to customers and their base monthly price
+//! - Customer accounts with shared or dedicated tenancy, an SLA contract
+//!   by tier and the projects being built for them
+//! - Customer satisfaction, rising with tickets resolved within the contract,
+//!   good CSAT answers and delivered projects, falling with late tickets,
+//!   poor CSAT answers and incidents
+//! - Deployments: one shared pool per service plus dedicated stacks
+//! - Fault injection with a blast radius derived from tenancy
+//! - Per-service alert routing: who hears which severities, in and out of
+//!   business hours, and who it escalates to when nobody fixes it
+//!
+//! Shared tenancy is cheap but a noisy neighbour or pool failure hits every
+//! customer on the pool. Dedicated tenancy costs more and keeps an incident
+//! contained to a single customer.
+
+use crate::config::{CustomerSettings, SatisfactionSettings};
+use crate::departments::ops::{CustomerTier, Severity};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How a customer's workloads are placed on infrastructure
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Tenancy {
+    Shared,
+    Dedicated,
+}
+
+impl Tenancy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tenancy::Shared => "shared",
+            Tenancy::Dedicated => "dedicated",
+        }
+    }
+}
+
+/// Service offered in the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDefinition {
+    pub name: String,
+    /// Monthly price per customer on shared tenancy
+    pub base_monthly_price: f64,
+}
+
+/// Service levels a customer's contract promises
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SlaContract {
+    /// Hours within which a support ticket is resolved
+    pub ticket_resolution_hours: i64,
+    /// Hours within which an incident affecting the customer is resolved
+    pub incident_resolution_hours: i64,
+}
+
+impl SlaContract {
+    pub fn for_tier(tier: CustomerTier) -> Self {
+        match tier {
+            CustomerTier::Standard => Self { ticket_resolution_hours: 72, incident_resolution_hours: 24 },
+            CustomerTier::Premium => Self { ticket_resolution_hours: 24, incident_resolution_hours: 8 },
+            CustomerTier::Enterprise => Self { ticket_resolution_hours: 8, incident_resolution_hours: 4 },
+        }
+    }
+}
+
+fn default_satisfaction() -> f64 {
+    SatisfactionSettings::default().initial
+}
+
+/// Customer account and its contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerAccount {
+    pub id: String,
+    pub name: String,
+    pub tier: CustomerTier,
+    pub tenancy: Tenancy,
+    pub onboarded_at: chrono::DateTime<chrono::Utc>,
+    /// Projects being built for the customer
+    #[serde(default)]
+    pub active_projects: Vec<Uuid>,
+    /// 0-100
+    #[serde(default = "default_satisfaction")]
+    pub satisfaction: f64,
+    /// Correlation ids of incidents affecting the customer, still open
+    #[serde(default)]
+    pub open_incidents: Vec<(Uuid, Severity)>,
+}
+
+impl CustomerAccount {
+    pub fn new(id: &str, name: &str, tier: CustomerTier, tenancy: Tenancy, onboarded_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            tier,
+            tenancy,
+            onboarded_at,
+            active_projects: Vec::new(),
+            satisfaction: default_satisfaction(),
+            open_incidents: Vec::new(),
+        }
+    }
+
+    pub fn contract(&self) -> SlaContract {
+        SlaContract::for_tier(self.tier)
+    }
+
+    fn adjust_satisfaction(&mut self, delta: f64) {
+        self.satisfaction = (self.satisfaction + delta).clamp(0.0, 100.0);
+    }
+
+    /// A ticket of the customer's was resolved after `hours`
+    pub fn ticket_resolved(&mut self, hours: f64, settings: &SatisfactionSettings) {
+        let promised = self.contract().ticket_resolution_hours as f64;
+        if hours <= promised {
+            self.adjust_satisfaction(settings.ticket_on_time);
+        } else {
+            // Worse the further past the contract, up to three times the penalty
+            let overrun = ((hours - promised) / promised).min(3.0);
+            self.adjust_satisfaction(-settings.ticket_late * (1.0 + overrun));
+        }
+    }
+
+    /// The customer answered a ticket's CSAT survey with `score` out of 5
+    pub fn ticket_rated(&mut self, score: u8, settings: &SatisfactionSettings) {
+        self.adjust_satisfaction((f64::from(score) - 3.0) * settings.csat_point);
+    }
+
+    /// An incident started affecting the customer
+    pub fn incident_declared(&mut self, correlation_id: Uuid, severity: Severity, settings: &SatisfactionSettings) {
+        self.adjust_satisfaction(-settings.incident * severity_weight(&severity));
+        self.open_incidents.push((correlation_id, severity));
+    }
+
+    /// An incident affecting the customer was resolved after `hours`;
+    /// false if it was not one of the customer's
+    pub fn incident_resolved(&mut self, correlation_id: Uuid, hours: f64, settings: &SatisfactionSettings) -> bool {
+        let Some(index) = self.open_incidents.iter().position(|(id, _)| *id == correlation_id) else {
+            return false;
+        };
+        let (_, severity) = self.open_incidents.remove(index);
+        if hours > self.contract().incident_resolution_hours as f64 {
+            self.adjust_satisfaction(-settings.incident * severity_weight(&severity));
+        }
+        true
+    }
+}
+
+/// How much more an incident of this severity hurts than a Sev4
+fn severity_weight(severity: &Severity) -> f64 {
+    match severity {
+        Severity::Sev1 => 4.0,
+        Severity::Sev2 => 2.0,
+        Severity::Sev3 => 1.0,
+        Severity::Sev4 => 0.5,
+    }
+}
+
+/// Running instance of a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deployment {
+    pub id: Uuid,
+    pub service: String,
+    pub tenancy: Tenancy,
+    /// Customers served by this deployment
+    pub customers: Vec<String>,
+}
+
+impl Deployment {
+    /// Name used for the deployment in alerts and incidents
+    pub fn label(&self) -> String {
+        match self.tenancy {
+            Tenancy::Shared => format!("{}-shared", self.service),
+            Tenancy::Dedicated => format!("{}-{}", self.service, self.customers.first().map(String::as_str).unwrap_or("dedicated")),
+        }
+    }
+}
+
+/// Alert recipient standing for whoever is on call in the paging rotation
+pub const ON_CALL: &str = "on-call";
+
+/// Recipients of alerts with the given severities
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertRoute {
+    pub severities: Vec<Severity>,
+    /// Departments to notify, or `on-call`
+    pub notify: Vec<String>,
+}
+
+/// How a service's alerts are routed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RoutingPolicy {
+    /// Routes during business hours
+    pub routes: Vec<AlertRoute>,
+    /// Routes outside business hours; the business-hours routes when empty
+    pub after_hours: Vec<AlertRoute>,
+    /// Simulated minutes an alert's incident may stay open before escalating
+    pub escalation_delay_minutes: Option<i64>,
+    /// Recipients of the escalation
+    pub escalate_to: Vec<String>,
+}
+
+impl Default for RoutingPolicy {
+    fn default() -> Self {
+        Self {
+            routes: vec![AlertRoute {
+                severities: vec![Severity::Sev1, Severity::Sev2, Severity::Sev3, Severity::Sev4],
+                notify: vec!["DevOps".to_string()],
+            }],
+            after_hours: Vec::new(),
+            escalation_delay_minutes: None,
+            escalate_to: Vec::new(),
+        }
+    }
+}
+
+impl RoutingPolicy {
+    /// Recipients of an alert, in policy order without repeats
+    pub fn recipients(&self, severity: &Severity, business_hours: bool) -> Vec<String> {
+        let routes = if business_hours || self.after_hours.is_empty() { &self.routes } else { &self.after_hours };
+        let mut recipients: Vec<String> = Vec::new();
+        for target in routes.iter().filter(|route| route.severities.contains(severity)).flat_map(|route| &route.notify) {
+            if !recipients.contains(target) {
+                recipients.push(target.clone());
+            }
+        }
+        recipients
+    }
+
+    /// Delay before an unresolved alert escalates, if it ever does
+    pub fn escalation_delay(&self) -> Option<chrono::Duration> {
+        self.escalation_delay_minutes
+            .filter(|_| !self.escalate_to.is_empty())
+            .map(chrono::Duration::minutes)
+    }
+}
+
+/// Kind of injected infrastructure fault
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum FaultKind {
+    /// One tenant exhausts resources on a shared pool
+    NoisyNeighbor,
+    /// The deployment itself degrades
+    Degradation,
+}
+
+/// Infrastructure fault and the customers it affects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfrastructureFault {
+    pub kind: FaultKind,
+    pub deployment_id: Uuid,
+    pub deployment: String,
+    pub service: String,
+    pub tenancy: Tenancy,
+    /// Tenant causing a noisy-neighbour fault
+    pub noisy_customer: Option<String>,
+    pub affected_customers: Vec<String>,
+}
+
+/// Catalog of services, customers and deployments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceCatalog {
+    pub services: Vec<ServiceDefinition>,
+    pub customers: HashMap<String, CustomerAccount>,
+    pub deployments: HashMap<Uuid, Deployment>,
+    /// Price multiplier applied to dedicated contracts
+    pub dedicated_price_multiplier: f64,
+    /// Alert routing per service; services not listed use the default policy
+    #[serde(default)]
+    pub routing: HashMap<String, RoutingPolicy>,
+}
+
+impl Default for ServiceCatalog {
+    fn default() -> Self {
+        Self::new(vec![
+            ServiceDefinition { name: "web-frontend".to_string(), base_monthly_price: 400.0 },
+            ServiceDefinition { name: "api-gateway".to_string(), base_monthly_price: 600.0 },
+            ServiceDefinition { name: "database".to_string(), base_monthly_price: 1000.0 },
+        ])
+    }
+}
+
+impl ServiceCatalog {
+    /// Create a catalog with one empty shared pool per service
+    pub fn new(services: Vec<ServiceDefinition>) -> Self {
+        let deployments = services.iter()
+            .map(|service| {
+                let id = Uuid::new_v4();
+                (id, Deployment {
+                    id,
+                    service: service.name.clone(),
+                    tenancy: Tenancy::Shared,
+                    customers: vec![],
+                })
+            })
+            .collect();
+
+        Self {
+            services,
+            customers: HashMap::new(),
+            deployments,
+            dedicated_price_multiplier: 2.5,
+            routing: HashMap::new(),
+        }
+    }
+
+    /// Sign up a customer and place it on shared pools or dedicated stacks
+    pub fn onboard_customer(&mut self, name: &str, tier: CustomerTier, tenancy: Tenancy) -> String {
+        let customer_id = format!("cust-{}", self.customers.len() + 1);
+
+        match tenancy {
+            Tenancy::Shared => {
+                for deployment in self.deployments.values_mut().filter(|d| d.tenancy == Tenancy::Shared) {
+                    deployment.customers.push(customer_id.clone());
+                }
+            }
+            Tenancy::Dedicated => {
+                for service in &self.services {
+                    let id = Uuid::new_v4();
+                    self.deployments.insert(id, Deployment {
+                        id,
+                        service: service.name.clone(),
+                        tenancy: Tenancy::Dedicated,
+                        customers: vec![customer_id.clone()],
+                    });
+                }
+            }
+        }
+
+        self.customers.insert(customer_id.clone(), CustomerAccount::new(&customer_id, name, tier, tenancy, chrono::Utc::now()));
+
+        customer_id
+    }
+
+    /// Sign up customers with tenancy and tier drawn from the settings
+    pub fn onboard_random_customers<R: Rng>(&mut self, settings: &CustomerSettings, rng: &mut R) {
+        for i in 1..=settings.initial_count {
+            let tenancy = if rng.gen::<f32>() < settings.dedicated_share { Tenancy::Dedicated } else { Tenancy::Shared };
+            // Dedicated contracts are sold to the larger accounts
+            let tier = match (tenancy, rng.gen_range(0..3)) {
+                (Tenancy::Dedicated, _) => CustomerTier::Enterprise,
+                (Tenancy::Shared, 0) => CustomerTier::Premium,
+                (Tenancy::Shared, _) => CustomerTier::Standard,
+            };
+            let customer_id = self.onboard_customer(&format!("Customer {}", i), tier, tenancy);
+            if let Some(customer) = self.customers.get_mut(&customer_id) {
+                customer.satisfaction = settings.satisfaction.initial;
+            }
+        }
+    }
+
+    /// Monthly contract cost for a customer
+    pub fn monthly_cost(&self, customer_id: &str) -> Option<f64> {
+        let customer = self.customers.get(customer_id)?;
+        let base: f64 = self.services.iter().map(|s| s.base_monthly_price).sum();
+        Some(match customer.tenancy {
+            Tenancy::Shared => base,
+            Tenancy::Dedicated => base * self.dedicated_price_multiplier,
+        })
+    }
+
+    /// Total monthly revenue across all contracts
+    pub fn monthly_revenue(&self) -> f64 {
+        self.customers.keys().filter_map(|id| self.monthly_cost(id)).sum()
+    }
+
+    /// Number of customers on each tenancy model
+    pub fn tenancy_counts(&self) -> (usize, usize) {
+        let dedicated = self.customers.values().filter(|c| c.tenancy == Tenancy::Dedicated).count();
+        (self.customers.len() - dedicated, dedicated)
+    }
+
+    /// Deployments running a service and the customers they serve
+    pub fn service_footprint(&self, service: &str) -> (usize, usize) {
+        self.deployments.values()
+            .filter(|d| d.service == service)
+            .fold((0, 0), |(deployments, customers), d| (deployments + 1, customers + d.customers.len()))
+    }
+
+    /// Alert routing policy of a service
+    pub fn routing_policy(&self, service: &str) -> RoutingPolicy {
+        self.routing.get(service).cloned().unwrap_or_default()
+    }
+
+    pub fn set_routing_policy(&mut self, service: &str, policy: RoutingPolicy) {
+        self.routing.insert(service.to_string(), policy);
+    }
+
+    /// Pick a random customer, if any are signed up
+    pub fn random_customer<R: Rng>(&self, rng: &mut R) -> Option<&CustomerAccount> {
+        let mut ids: Vec<&String> = self.customers.keys().collect();
+        ids.sort();
+        if ids.is_empty() {
+            return None;
+        }
+        self.customers.get(ids[rng.gen_range(0..ids.len())])
+    }
+
+    /// Give a new project to a random customer; returns the customer's id
+    pub fn assign_project<R: Rng>(&mut self, project_id: Uuid, rng: &mut R) -> Option<String> {
+        let customer_id = self.random_customer(rng)?.id.clone();
+        self.customers.get_mut(&customer_id)?.active_projects.push(project_id);
+        Some(customer_id)
+    }
+
+    /// Mark a project delivered to its customer; returns the customer's id
+    pub fn deliver_project(&mut self, project_id: Uuid, settings: &SatisfactionSettings) -> Option<String> {
+        let customer = self.customers.values_mut().find(|customer| customer.active_projects.contains(&project_id))?;
+        customer.active_projects.retain(|id| *id != project_id);
+        customer.adjust_satisfaction(settings.project_delivered);
+        Some(customer.id.clone())
+    }
+
+    /// Mean satisfaction over all customers
+    pub fn average_satisfaction(&self) -> Option<f64> {
+        (!self.customers.is_empty())
+            .then(|| self.customers.values().map(|customer| customer.satisfaction).sum::<f64>() / self.customers.len() as f64)
+    }
+
+    /// Inject a fault into a random deployment that has customers
+    ///
+    /// Faults on a shared pool with more than one tenant are noisy-neighbour
+    /// events: one tenant's load degrades the pool for everyone on it.
+    pub fn inject_fault<R: Rng>(&self, rng: &mut R) -> Option<InfrastructureFault> {
+        let mut candidates: Vec<&Deployment> = self.deployments.values()
+            .filter(|d| !d.customers.is_empty())
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        candidates.sort_by_key(|d| d.label());
+        let deployment = candidates[rng.gen_range(0..candidates.len())];
+
+        let noisy_customer = if deployment.tenancy == Tenancy::Shared && deployment.customers.len() > 1 {
+            Some(deployment.customers[rng.gen_range(0..deployment.customers.len())].clone())
+        } else {
+            None
+        };
+
+        Some(InfrastructureFault {
+            kind: if noisy_customer.is_some() { FaultKind::NoisyNeighbor } else { FaultKind::Degradation },
+            deployment_id: deployment.id,
+            deployment: deployment.label(),
+            service: deployment.service.clone(),
+            tenancy: deployment.tenancy,
+            noisy_customer,
+            affected_customers: deployment.customers.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_dedicated_costs_more() {
+        let mut catalog = ServiceCatalog::default();
+        let shared = catalog.onboard_customer("Acme", CustomerTier::Standard, Tenancy::Shared);
+        let dedicated = catalog.onboard_customer("Globex", CustomerTier::Enterprise, Tenancy::Dedicated);
+
+        let shared_cost = catalog.monthly_cost(&shared).unwrap();
+        let dedicated_cost = catalog.monthly_cost(&dedicated).unwrap();
+        assert_eq!(shared_cost, 2000.0);
+        assert_eq!(dedicated_cost, shared_cost * 2.5);
+        assert_eq!(catalog.tenancy_counts(), (1, 1));
+    }
+
+    #[test]
+    fn test_satisfaction_follows_contract() {
+        let settings = SatisfactionSettings::default();
+        let mut catalog = ServiceCatalog::default();
+        let id = catalog.onboard_customer("Globex", CustomerTier::Enterprise, Tenancy::Dedicated);
+        let customer = catalog.customers.get_mut(&id).unwrap();
+        let start = customer.satisfaction;
+
+        customer.ticket_resolved(4.0, &settings);
+        assert_eq!(customer.satisfaction, start + settings.ticket_on_time);
+        // Twice the 8h contract: the penalty doubles
+        customer.ticket_resolved(16.0, &settings);
+        assert_eq!(customer.satisfaction, start + settings.ticket_on_time - 2.0 * settings.ticket_late);
+
+        let before = customer.satisfaction;
+        let incident = Uuid::new_v4();
+        customer.incident_declared(incident, Severity::Sev2, &settings);
+        assert!(customer.incident_resolved(incident, 6.0, &settings));
+        assert!(!customer.incident_resolved(incident, 6.0, &settings));
+        assert_eq!(customer.satisfaction, before - 4.0 * settings.incident);
+
+        let project = Uuid::new_v4();
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(catalog.assign_project(project, &mut rng), Some(id.clone()));
+        assert_eq!(catalog.deliver_project(project, &settings), Some(id.clone()));
+        assert!(catalog.customers[&id].active_projects.is_empty());
+    }
+
+    #[test]
+    fn test_fault_blast_radius_follows_tenancy() {
+        let mut catalog = ServiceCatalog::default();
+        catalog.onboard_customer("Acme", CustomerTier::Standard, Tenancy::Shared);
+        catalog.onboard_customer("Initech", CustomerTier::Premium, Tenancy::Shared);
+        let dedicated = catalog.onboard_customer("Globex", CustomerTier::Enterprise, Tenancy::Dedicated);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let fault = catalog.inject_fault(&mut rng).unwrap();
+            match fault.tenancy {
+                Tenancy::Shared => {
+                    assert_eq!(fault.kind, FaultKind::NoisyNeighbor);
+                    assert_eq!(fault.affected_customers.len(), 2);
+                    assert!(!fault.affected_customers.contains(&dedicated));
+                }
+                Tenancy::Dedicated => {
+                    assert_eq!(fault.kind, FaultKind::Degradation);
+                    assert_eq!(fault.affected_customers, vec![dedicated.clone()]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_routing_policy_switches_after_hours() {
+        let mut catalog = ServiceCatalog::default();
+        catalog.set_routing_policy("database", RoutingPolicy {
+            routes: vec![
+                AlertRoute { severities: vec![Severity::Sev1, Severity::Sev2], notify: vec!["DevOps".to_string(), ON_CALL.to_string()] },
+                AlertRoute { severities: vec![Severity::Sev3, Severity::Sev4], notify: vec!["DevOps".to_string()] },
+            ],
+            after_hours: vec![
+                AlertRoute { severities: vec![Severity::Sev1, Severity::Sev2], notify: vec![ON_CALL.to_string()] },
+            ],
+            escalation_delay_minutes: Some(30),
+            escalate_to: vec!["Ops".to_string()],
+        });
+
+        let database = catalog.routing_policy("database");
+        assert_eq!(database.recipients(&Severity::Sev2, true), vec!["DevOps".to_string(), ON_CALL.to_string()]);
+        assert_eq!(database.recipients(&Severity::Sev2, false), vec![ON_CALL.to_string()]);
+        assert!(database.recipients(&Severity::Sev3, false).is_empty());
+        assert_eq!(database.escalation_delay(), Some(chrono::Duration::minutes(30)));
+
+        // Services without a policy keep alerting DevOps and never escalate
+        let frontend = catalog.routing_policy("web-frontend");
+        assert_eq!(frontend.recipients(&Severity::Sev3, false), vec!["DevOps".to_string()]);
+        assert_eq!(frontend.escalation_delay(), None);
+    }
+}
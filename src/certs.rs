@@ -0,0 +1,285 @@
+//! TLS Certificates - Inventory, Renewal and Expiry
+//!
+//! This module implements the certificates Networking keeps for the
+//! company's endpoints:
+//! - One certificate per catalog service and environment, for
+//!   `<service>.<zone>` in production and `<service>.<environment>.<zone>`
+//!   elsewhere; certificates the inventory finds for an endpoint it has not
+//!   seen before are part way through their lifetime
+//! - Automated renewal: a renewal task runs once a certificate is within
+//!   `renew_before_days` of expiry, and a failed one is retried the next
+//!   day. Certificates renewed by hand only get a renewal task once DevOps
+//!   is warned, whenever DevOps gets to it
+//! - Expiry warnings `warn_before_days` ahead, and a lapse when a
+//!   certificate expires unrenewed
+//!
+//! The orchestrator checks the inventory every evening, alerts DevOps and
+//! InfoSec, and declares an outage for every certificate lapsing on a
+//! production endpoint.
+
+use crate::config::CertificateSettings;
+use crate::departments::infosec::Severity;
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Environment whose endpoints customers use
+pub const PRODUCTION: &str = "production";
+
+/// Why renewal attempts fail
+const RENEWAL_FAILURES: &[&str] = &[
+    "ACME DNS challenge record not found",
+    "CA rate limit reached",
+    "CA unreachable",
+    "domain validation timed out",
+];
+
+/// A domain that needs a certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub domain: String,
+    pub service: String,
+    pub environment: String,
+}
+
+/// Endpoints of the services in every environment; `zone` is the DNS
+/// origin they live under
+pub fn endpoints(services: &[String], environments: &[String], zone: &str) -> Vec<Endpoint> {
+    let zone = zone.trim_end_matches('.');
+    let mut endpoints = Vec::new();
+    for service in services {
+        for environment in environments {
+            let domain = if environment == PRODUCTION { format!("{}.{}", service, zone) } else { format!("{}.{}.{}", service, environment, zone) };
+            endpoints.push(Endpoint { domain, service: service.clone(), environment: environment.clone() });
+        }
+    }
+    endpoints
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Certificate {
+    pub domain: String,
+    pub service: String,
+    pub environment: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Renewed by automation rather than by hand
+    pub automated: bool,
+    /// When the pending renewal task runs
+    pub renewal_at: Option<DateTime<Utc>>,
+    /// Renewal attempts failed in a row
+    pub failed_renewals: u32,
+    /// DevOps and InfoSec were warned of the coming expiry
+    pub warned: bool,
+    pub lapsed: bool,
+}
+
+impl Certificate {
+    pub fn production(&self) -> bool {
+        self.environment == PRODUCTION
+    }
+
+    /// Whole days until expiry, negative once expired
+    pub fn days_left(&self, now: DateTime<Utc>) -> i64 {
+        (self.expires_at - now).num_seconds().div_euclid(86_400)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CertificateEventKind {
+    Issued,
+    RenewalScheduled { at: DateTime<Utc> },
+    Renewed,
+    RenewalFailed { reason: String },
+    Expiring { days_left: i64 },
+    Lapsed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CertificateEvent {
+    pub domain: String,
+    pub service: String,
+    pub environment: String,
+    pub expires_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: CertificateEventKind,
+}
+
+impl CertificateEvent {
+    fn new(certificate: &Certificate, kind: CertificateEventKind) -> Self {
+        Self {
+            domain: certificate.domain.clone(),
+            service: certificate.service.clone(),
+            environment: certificate.environment.clone(),
+            expires_at: certificate.expires_at,
+            kind,
+        }
+    }
+
+    pub fn production(&self) -> bool {
+        self.environment == PRODUCTION
+    }
+
+    /// Whether DevOps and InfoSec hear of it
+    pub fn alerts(&self) -> bool {
+        matches!(self.kind, CertificateEventKind::Expiring { .. } | CertificateEventKind::RenewalFailed { .. } | CertificateEventKind::Lapsed)
+    }
+
+    pub fn severity(&self) -> Severity {
+        match (&self.kind, self.production()) {
+            (CertificateEventKind::Lapsed, true) => Severity::Critical,
+            (CertificateEventKind::Lapsed, false) | (CertificateEventKind::Expiring { .. }, true) => Severity::High,
+            (CertificateEventKind::Expiring { .. }, false) => Severity::Medium,
+            (CertificateEventKind::RenewalFailed { .. }, _) => Severity::Low,
+            _ => Severity::Info,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match &self.kind {
+            CertificateEventKind::Issued => format!("Certificate for {} found, expires {}", self.domain, self.expires_at.format("%Y-%m-%d")),
+            CertificateEventKind::RenewalScheduled { at } => format!("Renewal of the certificate for {} scheduled for {}", self.domain, at.format("%Y-%m-%d")),
+            CertificateEventKind::Renewed => format!("Certificate for {} renewed until {}", self.domain, self.expires_at.format("%Y-%m-%d")),
+            CertificateEventKind::RenewalFailed { reason } => format!("Renewal of the certificate for {} failed: {}", self.domain, reason),
+            CertificateEventKind::Expiring { days_left } => format!("Certificate for {} expires in {} days", self.domain, days_left),
+            CertificateEventKind::Lapsed => format!("Certificate for {} expired on {}", self.domain, self.expires_at.format("%Y-%m-%d")),
+        }
+    }
+}
+
+/// Certificates by domain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CertificateInventory {
+    pub certificates: BTreeMap<String, Certificate>,
+}
+
+impl CertificateInventory {
+    /// Track a certificate for every endpoint and drop those of endpoints
+    /// that are gone
+    pub fn sync(&mut self, endpoints: &[Endpoint], now: DateTime<Utc>, settings: &CertificateSettings, rng: &mut impl Rng) -> Vec<CertificateEvent> {
+        self.certificates.retain(|domain, _| endpoints.iter().any(|endpoint| endpoint.domain == *domain));
+        let lifetime = Duration::days(i64::from(settings.lifetime_days.max(1)));
+        let mut events = Vec::new();
+        for endpoint in endpoints {
+            if self.certificates.contains_key(&endpoint.domain) {
+                continue;
+            }
+            let issued_at = now - Duration::hours(rng.gen_range(0..lifetime.num_hours()));
+            let certificate = Certificate {
+                domain: endpoint.domain.clone(),
+                service: endpoint.service.clone(),
+                environment: endpoint.environment.clone(),
+                issued_at,
+                expires_at: issued_at + lifetime,
+                automated: !rng.gen_bool(settings.manual_share.clamp(0.0, 1.0)),
+                renewal_at: None,
+                failed_renewals: 0,
+                warned: false,
+                lapsed: false,
+            };
+            events.push(CertificateEvent::new(&certificate, CertificateEventKind::Issued));
+            self.certificates.insert(endpoint.domain.clone(), certificate);
+        }
+        events
+    }
+
+    /// Warn of coming expiries, record lapses, schedule renewal tasks and
+    /// run the ones due
+    pub fn check(&mut self, now: DateTime<Utc>, settings: &CertificateSettings, rng: &mut impl Rng) -> Vec<CertificateEvent> {
+        let mut events = Vec::new();
+        for certificate in self.certificates.values_mut() {
+            let days_left = certificate.days_left(now);
+            if certificate.expires_at <= now {
+                if !certificate.lapsed {
+                    certificate.lapsed = true;
+                    events.push(CertificateEvent::new(certificate, CertificateEventKind::Lapsed));
+                }
+            } else if days_left < i64::from(settings.warn_before_days) && !certificate.warned {
+                certificate.warned = true;
+                events.push(CertificateEvent::new(certificate, CertificateEventKind::Expiring { days_left }));
+            }
+
+            if certificate.renewal_at.is_none() {
+                if certificate.automated && days_left < i64::from(settings.renew_before_days) {
+                    certificate.renewal_at = Some(now);
+                } else if !certificate.automated && (certificate.warned || certificate.lapsed) {
+                    let at = now + Duration::days(i64::from(rng.gen_range(0..=settings.manual_renewal_max_days)));
+                    certificate.renewal_at = Some(at);
+                    events.push(CertificateEvent::new(certificate, CertificateEventKind::RenewalScheduled { at }));
+                }
+            }
+
+            if certificate.renewal_at.is_some_and(|at| at <= now) {
+                events.push(Self::renew(certificate, now, settings, rng));
+            }
+        }
+        events
+    }
+
+    /// Run a certificate's renewal task
+    fn renew(certificate: &mut Certificate, now: DateTime<Utc>, settings: &CertificateSettings, rng: &mut impl Rng) -> CertificateEvent {
+        if rng.gen_bool(settings.renewal_failure_probability.clamp(0.0, 1.0)) {
+            certificate.failed_renewals += 1;
+            certificate.renewal_at = Some(now + Duration::days(1));
+            let reason = RENEWAL_FAILURES[rng.gen_range(0..RENEWAL_FAILURES.len())].to_string();
+            return CertificateEvent::new(certificate, CertificateEventKind::RenewalFailed { reason });
+        }
+        certificate.issued_at = now;
+        certificate.expires_at = now + Duration::days(i64::from(settings.lifetime_days.max(1)));
+        certificate.renewal_at = None;
+        certificate.failed_renewals = 0;
+        certificate.warned = false;
+        certificate.lapsed = false;
+        CertificateEvent::new(certificate, CertificateEventKind::Renewed)
+    }
+
+    /// Certificates expired and not yet renewed
+    pub fn lapsed(&self) -> impl Iterator<Item = &Certificate> {
+        self.certificates.values().filter(|certificate| certificate.lapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_renewal_warnings_and_lapses() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let settings = CertificateSettings { renewal_failure_probability: 0.0, ..CertificateSettings::default() };
+        let now = Utc::now();
+        let endpoints = endpoints(&["api".to_string()], &["staging".to_string(), PRODUCTION.to_string()], "aivertco.internal.");
+        assert_eq!(endpoints[0].domain, "api.staging.aivertco.internal");
+        assert_eq!(endpoints[1].domain, "api.aivertco.internal");
+
+        let mut inventory = CertificateInventory::default();
+        assert_eq!(inventory.sync(&endpoints, now, &settings, &mut rng).len(), 2);
+        assert!(inventory.sync(&endpoints, now, &settings, &mut rng).is_empty());
+
+        // Automation renews a month ahead, before anyone is warned
+        let automated = inventory.certificates.get_mut("api.staging.aivertco.internal").unwrap();
+        automated.automated = true;
+        automated.expires_at = now + Duration::days(20);
+        // Nobody gets to this one until two days after it expires
+        let manual = inventory.certificates.get_mut("api.aivertco.internal").unwrap();
+        manual.automated = false;
+        manual.expires_at = now + Duration::days(5);
+        manual.renewal_at = Some(now + Duration::days(7));
+        let kinds = |events: &[CertificateEvent], domain: &str| events.iter().filter(|event| event.domain == domain).map(|event| event.kind.clone()).collect::<Vec<_>>();
+
+        let events = inventory.check(now, &settings, &mut rng);
+        assert_eq!(kinds(&events, "api.staging.aivertco.internal"), vec![CertificateEventKind::Renewed]);
+        assert_eq!(kinds(&events, "api.aivertco.internal")[0], CertificateEventKind::Expiring { days_left: 5 });
+        assert!(events.iter().filter(|event| event.alerts()).all(|event| event.production()));
+
+        let events = inventory.check(now + Duration::days(6), &settings, &mut rng);
+        assert_eq!(events.len(), 1);
+        assert_eq!((&events[0].kind, events[0].severity()), (&CertificateEventKind::Lapsed, Severity::Critical));
+        assert_eq!(inventory.lapsed().count(), 1);
+        assert_eq!(kinds(&inventory.check(now + Duration::days(7), &settings, &mut rng), "api.aivertco.internal"), vec![CertificateEventKind::Renewed]);
+        assert_eq!(inventory.lapsed().count(), 0);
+    }
+}
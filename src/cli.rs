@@ -0,0 +1,249 @@
+//! Command-Line Interface
+//!
+//! This module defines the `aivertco` command line:
+//! - `run` (default): start a new simulation
+//! - `resume <snapshot>`: continue an interrupted run
+//! - `daemon`: run continuously as a service with health endpoints
+//! - `validate-config`: check the configuration and exit
+//! - `report <snapshot>`: summarize a saved simulation or print an incident
+//!   timeline; `report --history <db>` queries the SQLite history instead
+//! - `export-chat <snapshot>`: render agent conversations as chat logs
+//! - `generate`: write labeled synthetic datasets without running the live loop
+//! - `replay <journal>`: feed a recorded message journal into a fresh simulation
+//! - `doctor <snapshot>`: check a saved simulation for structural problems
+
+use crate::config::SimulationConfig;
+use crate::synthetic::{Dataset, ExportSchema};
+use crate::transcripts::ExportFormat;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// AI Company Simulation
+#[derive(Debug, Parser)]
+#[command(name = "aivertco", version, about = "Autonomous AI company simulation")]
+pub struct Cli {
+    /// Configuration file (defaults to ./aivertco.toml when present)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start a new simulation
+    Run(RunArgs),
+
+    /// Resume a simulation from a snapshot
+    Resume {
+        /// Snapshot file written by a previous run
+        snapshot: PathBuf,
+
+        #[command(flatten)]
+        overrides: RunArgs,
+    },
+
+    /// Run continuously as a service, resuming from the latest rotated snapshot
+    Daemon(RunArgs),
+
+    /// Validate the configuration and exit
+    ValidateConfig,
+
+    /// Print a summary report from a snapshot
+    Report {
+        /// Snapshot file to report on
+        #[arg(default_value = "simulation_snapshot.json")]
+        snapshot: PathBuf,
+
+        /// Print the timeline of this incident instead of the summary
+        #[arg(long)]
+        incident: Option<Uuid>,
+
+        /// Print the message history of this conversation thread instead of the summary
+        #[arg(long, conflicts_with = "incident")]
+        thread: Option<Uuid>,
+
+        /// Summarize this SQLite history database instead of a snapshot (`sqlite` feature)
+        #[arg(long, conflicts_with_all = ["incident", "thread"])]
+        history: Option<PathBuf>,
+
+        /// Only count history created on or after this date (YYYY-MM-DD)
+        #[arg(long, requires = "history")]
+        since: Option<chrono::NaiveDate>,
+    },
+
+    /// Export agent conversations from a snapshot as chat logs
+    ExportChat {
+        /// Snapshot file to export from
+        #[arg(default_value = "simulation_snapshot.json")]
+        snapshot: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Slack)]
+        format: ExportFormat,
+
+        /// Directory to write the export into
+        #[arg(long, default_value = "chat_export")]
+        out: PathBuf,
+    },
+
+    /// Generate labeled synthetic datasets without running the simulation
+    Generate(GenerateArgs),
+
+    /// Feed a message journal into a fresh simulation and compare agent responses
+    Replay {
+        /// Journal written by a run with `--journal`
+        journal: PathBuf,
+
+        /// Exit with an error when agents respond differently than recorded
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Check a snapshot for structural problems and suggest fixes
+    Doctor {
+        /// Snapshot file to check
+        #[arg(default_value = "simulation_snapshot.json")]
+        snapshot: PathBuf,
+
+        /// Print the findings as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Options for `generate`; unset values come from the `[synthetic]` config
+#[derive(Debug, Clone, Default, Args)]
+pub struct GenerateArgs {
+    /// Dataset to generate (repeatable, defaults to all)
+    #[arg(long = "dataset", value_enum)]
+    pub datasets: Vec<Dataset>,
+
+    /// Records per dataset, overriding the configured volumes
+    #[arg(long)]
+    pub count: Option<usize>,
+
+    /// Output file format
+    #[arg(long, value_enum)]
+    pub format: Option<ExportSchema>,
+
+    /// Directory to write the datasets into
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Seed for reproducible datasets
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// End of the covered period (RFC 3339), e.g. 2024-06-01T00:00:00Z
+    #[arg(long)]
+    pub end: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Run-time overrides shared by `run` and `resume`
+#[derive(Debug, Clone, Default, Args)]
+pub struct RunArgs {
+    /// Stop after this many simulation steps
+    #[arg(long)]
+    pub steps: Option<u64>,
+
+    /// Stop after this much real time, e.g. 90s, 45m, 2h or 1d
+    #[arg(long, value_parser = crate::config::parse_duration)]
+    pub duration: Option<u64>,
+
+    /// Stop after this many simulated days
+    #[arg(long)]
+    pub sim_days: Option<u32>,
+
+    /// Simulation speed multiplier (1.0 = real-time)
+    #[arg(long)]
+    pub speed: Option<f32>,
+
+    /// Run events back-to-back on the simulated clock instead of in real time
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Seed for the orchestrator's random number generator
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Append every dispatched message to this journal file
+    #[arg(long, value_name = "PATH")]
+    pub journal: Option<PathBuf>,
+}
+
+impl RunArgs {
+    /// Apply the command-line overrides on top of a loaded configuration
+    pub fn apply(&self, config: &mut SimulationConfig) {
+        if let Some(steps) = self.steps {
+            config.max_steps = Some(steps);
+        }
+        if let Some(seconds) = self.duration {
+            config.max_wall_seconds = Some(seconds);
+        }
+        if let Some(days) = self.sim_days {
+            config.max_sim_days = Some(days);
+        }
+        if let Some(speed) = self.speed {
+            config.speed_multiplier = speed;
+        }
+        if self.headless {
+            config.headless = true;
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        if let Some(journal) = &self.journal {
+            config.journal.path = Some(journal.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_subcommand_defaults_to_run() {
+        let cli = Cli::try_parse_from(["aivertco"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_run_flags_override_config() {
+        let cli = Cli::try_parse_from(["aivertco", "run", "--steps", "50", "--speed", "5", "--headless", "--seed", "9", "--journal", "run.jsonl",
+                                       "--duration", "45m", "--sim-days", "30"]).unwrap();
+        let Some(Command::Run(args)) = cli.command else {
+            panic!("expected run subcommand");
+        };
+
+        let mut config = SimulationConfig::default();
+        args.apply(&mut config);
+        assert_eq!(config.max_steps, Some(50));
+        assert_eq!(config.speed_multiplier, 5.0);
+        assert!(config.headless);
+        assert_eq!(config.seed, Some(9));
+        assert_eq!(config.journal.path, Some(PathBuf::from("run.jsonl")));
+        assert_eq!((config.max_wall_seconds, config.max_sim_days), (Some(2700), Some(30)));
+        assert!(Cli::try_parse_from(["aivertco", "run", "--duration", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_resume_requires_snapshot() {
+        assert!(Cli::try_parse_from(["aivertco", "resume"]).is_err());
+        assert!(Cli::try_parse_from(["aivertco", "resume", "state.json", "--steps", "10"]).is_ok());
+    }
+
+    #[test]
+    fn test_generate_datasets() {
+        let cli = Cli::try_parse_from(["aivertco", "generate", "--dataset", "tickets", "--dataset", "network-flows", "--format", "csv"]).unwrap();
+        let Some(Command::Generate(args)) = cli.command else {
+            panic!("expected generate subcommand");
+        };
+        assert_eq!(args.datasets, vec![Dataset::Tickets, Dataset::NetworkFlows]);
+        assert_eq!(args.format, Some(ExportSchema::Csv));
+        assert!(args.count.is_none());
+    }
+}
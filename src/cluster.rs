@@ -0,0 +1,229 @@
+//! Cluster Scheduling - Nodes, Pods and Bin-Packing
+//!
+//! This module implements the container clusters DevOps runs workloads on:
+//! - Nodes with CPU, memory and storage capacity, which fail and recover
+//! - Workloads of identical pods, each requesting a share of a node
+//! - A scheduler that bin-packs pending pods onto the fullest node they fit
+//!   on, largest pods first
+//! - Rescheduling: pods of a failed node go back to pending and are placed
+//!   on the nodes left
+//! - Node counts DevOps auto-scaling uses to add nodes for pending pods and
+//!   remove idle ones
+//!
+//! A pod no node can take stays pending until capacity frees up or a node is
+//! added.
+
+use crate::quota::Resources;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Capacity of every node a cluster adds
+pub const NODE_CAPACITY: Resources = Resources { cpu_cores: 16, memory_gb: 64, storage_gb: 500 };
+
+/// Nodes a new cluster starts with, and the fewest it scales down to
+pub const MIN_NODES: usize = 2;
+
+/// Most nodes a cluster scales up to
+pub const MAX_NODES: usize = 20;
+
+/// What a pod of a deployed project requests
+pub const POD_REQUESTS: Resources = Resources { cpu_cores: 2, memory_gb: 4, storage_gb: 20 };
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ClusterHealth {
+    Healthy,
+    Degraded,
+    Critical,
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub name: String,
+    pub capacity: Resources,
+    /// Accepting and running pods
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pod {
+    pub id: Uuid,
+    /// Workload the pod is a replica of
+    pub workload: String,
+    pub requests: Resources,
+    /// Node the pod runs on; pending while unset
+    pub node: Option<String>,
+}
+
+/// A cluster of nodes and the pods scheduled onto them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    pub name: String,
+    pub nodes: Vec<Node>,
+    pub pods: Vec<Pod>,
+    pub status: ClusterHealth,
+    pub last_health_check: chrono::DateTime<chrono::Utc>,
+    /// Number the next node added is named after
+    next_node: u32,
+}
+
+impl Cluster {
+    pub fn new(name: &str, nodes: usize) -> Self {
+        let mut cluster = Self {
+            name: name.to_string(),
+            nodes: Vec::new(),
+            pods: Vec::new(),
+            status: ClusterHealth::Healthy,
+            last_health_check: chrono::Utc::now(),
+            next_node: 1,
+        };
+        for _ in 0..nodes {
+            cluster.add_node();
+        }
+        cluster
+    }
+
+    pub fn healthy_nodes(&self) -> usize {
+        self.nodes.iter().filter(|node| node.ready).count()
+    }
+
+    pub fn pending_pods(&self) -> usize {
+        self.pods.iter().filter(|pod| pod.node.is_none()).count()
+    }
+
+    /// Resources requested by the pods on a node
+    pub fn allocated(&self, node: &str) -> Resources {
+        self.pods.iter()
+            .filter(|pod| pod.node.as_deref() == Some(node))
+            .fold(Resources::default(), |total, pod| total.plus(&pod.requests))
+    }
+
+    /// Add `replicas` pods of a workload, pending until scheduled
+    pub fn submit(&mut self, workload: &str, requests: Resources, replicas: u32) {
+        for _ in 0..replicas {
+            self.pods.push(Pod { id: Uuid::new_v4(), workload: workload.to_string(), requests, node: None });
+        }
+    }
+
+    /// Remove every pod of a workload; returns how many there were
+    pub fn remove_workload(&mut self, workload: &str) -> usize {
+        let before = self.pods.len();
+        self.pods.retain(|pod| pod.workload != workload);
+        before - self.pods.len()
+    }
+
+    /// Place pending pods, largest first, on the ready node with the least
+    /// room left that still fits them; returns how many were placed
+    pub fn schedule(&mut self) -> usize {
+        let mut pending: Vec<usize> = (0..self.pods.len()).filter(|&i| self.pods[i].node.is_none()).collect();
+        pending.sort_by_key(|&i| std::cmp::Reverse(self.pods[i].requests.cpu_cores + self.pods[i].requests.memory_gb));
+
+        let mut placed = 0;
+        for i in pending {
+            let requests = self.pods[i].requests;
+            let best = self.nodes.iter()
+                .filter(|node| node.ready)
+                .map(|node| (node, node.capacity.minus(&self.allocated(&node.name))))
+                .filter(|(_, free)| requests.fits_within(free))
+                .min_by_key(|(_, free)| free.cpu_cores + free.memory_gb)
+                .map(|(node, _)| node.name.clone());
+            if let Some(node) = best {
+                self.pods[i].node = Some(node);
+                placed += 1;
+            }
+        }
+        self.update_status();
+        placed
+    }
+
+    pub fn add_node(&mut self) -> String {
+        let name = format!("{}-node-{}", self.name, self.next_node);
+        self.next_node += 1;
+        self.nodes.push(Node { name: name.clone(), capacity: NODE_CAPACITY, ready: true });
+        self.update_status();
+        name
+    }
+
+    /// Remove ready nodes running no pods, keeping at least `keep` nodes;
+    /// returns the names removed
+    pub fn remove_idle_nodes(&mut self, keep: usize) -> Vec<String> {
+        let mut removed = Vec::new();
+        while self.nodes.len() > keep {
+            let Some(index) = self.nodes.iter().rposition(|node| node.ready && self.allocated(&node.name) == Resources::default()) else {
+                break;
+            };
+            removed.push(self.nodes.remove(index).name);
+        }
+        self.update_status();
+        removed
+    }
+
+    /// Take a node out and send its pods back to pending; returns how many
+    /// were evicted
+    pub fn fail_node(&mut self, name: &str) -> usize {
+        let Some(node) = self.nodes.iter_mut().find(|node| node.name == name) else {
+            return 0;
+        };
+        node.ready = false;
+        let mut evicted = 0;
+        for pod in self.pods.iter_mut().filter(|pod| pod.node.as_deref() == Some(name)) {
+            pod.node = None;
+            evicted += 1;
+        }
+        self.update_status();
+        evicted
+    }
+
+    pub fn recover_node(&mut self, name: &str) {
+        if let Some(node) = self.nodes.iter_mut().find(|node| node.name == name) {
+            node.ready = true;
+        }
+        self.update_status();
+    }
+
+    fn update_status(&mut self) {
+        let healthy = self.healthy_nodes();
+        self.status = if healthy == 0 {
+            ClusterHealth::Offline
+        } else if healthy * 2 < self.nodes.len() {
+            ClusterHealth::Critical
+        } else if healthy < self.nodes.len() || self.pending_pods() > 0 {
+            ClusterHealth::Degraded
+        } else {
+            ClusterHealth::Healthy
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bin_packing_and_rescheduling() {
+        let mut cluster = Cluster::new("production", 2);
+        // Seven 2-core pods fill the first node before touching the second
+        cluster.submit("api", POD_REQUESTS, 7);
+        assert_eq!(cluster.schedule(), 7);
+        assert_eq!(cluster.allocated("production-node-1").cpu_cores, 14);
+        assert_eq!(cluster.allocated("production-node-2").cpu_cores, 0);
+        assert_eq!(cluster.status, ClusterHealth::Healthy);
+
+        // The failed node's pods move over; the one that does not fit waits
+        cluster.submit("web", POD_REQUESTS, 2);
+        cluster.schedule();
+        assert_eq!(cluster.fail_node("production-node-1"), 8);
+        assert_eq!(cluster.schedule(), 7);
+        assert_eq!(cluster.pending_pods(), 1);
+        assert_eq!(cluster.status, ClusterHealth::Degraded);
+
+        // A new node takes it; once the pods are gone the extra nodes go too
+        cluster.add_node();
+        assert_eq!(cluster.schedule(), 1);
+        cluster.recover_node("production-node-1");
+        cluster.remove_workload("api");
+        cluster.remove_workload("web");
+        assert_eq!(cluster.remove_idle_nodes(MIN_NODES).len(), 1);
+        assert_eq!(cluster.nodes.len(), MIN_NODES);
+    }
+}
@@ -0,0 +1,3201 @@
+//! Simulation Configuration - File & Environment Loading
+//!
+//! This module implements loading the simulation configuration from:
+//! - A TOML configuration file (`aivertco.toml` by default)
+//! - `AIVERTCO_*` environment variable overrides
+//! - Built-in defaults for anything left unspecified
+//!
+//! All sources are merged before validation, so a bad value is reported at
+//! startup regardless of where it came from.
+
+use crate::alerting::AlertMetric;
+use crate::catalog::RoutingPolicy;
+use crate::departments::devops::DeploymentStrategy;
+use crate::departments::networking::SecurityLevel;
+use crate::departments::ops::{Priority, Severity};
+use crate::dlp::{Classification, DataStore};
+use crate::infra::InfraPlan;
+use crate::personality::Personality;
+use crate::policy::TurnDecision;
+use crate::quota::Resources;
+use crate::scorecard::Grade;
+use crate::staffing::{ScheduledChange, StaffingChange};
+use crate::synthetic::ExportSchema;
+use crate::wan::LinkKind;
+use crate::webhooks::EntityEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Configuration file read when no explicit path is given
+pub const DEFAULT_CONFIG_FILE: &str = "aivertco.toml";
+
+/// Top-level simulation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    /// Simulation speed multiplier (1.0 = real-time)
+    pub speed_multiplier: f32,
+    /// Enable autonomous operation
+    pub autonomous_mode: bool,
+    /// Working hours (start, end)
+    pub working_hours: (u8, u8),
+    /// Maximum simulation steps
+    pub max_steps: Option<u64>,
+    /// Real seconds a run may take before it stops
+    pub max_wall_seconds: Option<u64>,
+    /// Simulated days a run may cover before it stops
+    pub max_sim_days: Option<u32>,
+    /// Run events back-to-back instead of pacing them in real time
+    pub headless: bool,
+    /// Simulated minutes between orchestrator steps and agent turns
+    pub step_minutes: u32,
+    /// Where to write the state snapshot on shutdown
+    pub snapshot_path: Option<PathBuf>,
+    /// Seed for the orchestrator's random number generator
+    pub seed: Option<u64>,
+    /// Number of staff agents per department (managers are extra)
+    pub department_sizes: DepartmentSizes,
+    /// Per-step probabilities of orchestrator events
+    pub event_probabilities: EventProbabilities,
+    /// Customers signed up when a new simulation starts
+    pub customers: CustomerSettings,
+    /// Long-running daemon mode settings
+    pub daemon: DaemonSettings,
+    /// Synthetic dataset generation settings
+    pub synthetic: SyntheticSettings,
+    /// Department intake circuit breakers
+    pub intake: IntakeSettings,
+    /// Report rendering
+    pub reports: ReportSettings,
+    /// Retries and dead-lettering of failed deliveries
+    pub delivery: DeliverySettings,
+    /// How long unanswered blocking requests count in deadlock detection
+    pub deadlocks: DeadlockSettings,
+    /// Outbound notifications of entity changes
+    pub webhooks: WebhookSettings,
+    /// Two-way ticket sync with Jira or GitHub Issues (`issue-sync` feature)
+    pub issue_sync: IssueSyncSettings,
+    /// Recording of dispatched messages for replay
+    pub journal: JournalSettings,
+    /// Prometheus endpoint for simulation internals
+    pub metrics: MetricsSettings,
+    /// REST control API for a running simulation (`api` feature)
+    pub api: ApiSettings,
+    /// Paging on-call responders for severe incidents
+    pub paging: PagingSettings,
+    /// Emails to customers about tickets and campaigns
+    pub email: EmailSettings,
+    /// Source repository Engineering commits to
+    pub vcs: VcsSettings,
+    /// Backoff and recovery of failing agents
+    pub supervision: SupervisionSettings,
+    /// Release notes published for shipped deployments
+    pub release_notes: ReleaseNotesSettings,
+    /// Service scorecards and the monthly operations review
+    pub scorecards: ScorecardSettings,
+    /// Resource quotas of teams and projects on the shared clusters
+    pub quotas: QuotaSettings,
+    /// Alert routing policies by service name
+    pub alert_routing: BTreeMap<String, RoutingPolicy>,
+    /// Company risk register and its quarterly review
+    pub risks: RiskSettings,
+    /// Status rollups, delegation and approvals along the reporting lines
+    pub org: OrgSettings,
+    /// Quarterly goals, allocations and reorganizations set by the CEO
+    pub executive: ExecutiveSettings,
+    /// Hiring and offboarding while the simulation runs
+    pub staffing: StaffingSettings,
+    /// Revenue, running costs, SLA penalties and bankruptcy
+    pub economy: EconomySettings,
+    /// Resolutions, runbooks and post-mortems agents share
+    pub knowledge: KnowledgeSettings,
+    /// How releases take over production traffic, and the environments
+    /// builds are promoted through on the way
+    pub deployments: DeploymentSettings,
+    /// Build queue and CI/CD pipelines between merge and deployment
+    pub pipelines: PipelineSettings,
+    /// Alert rules over server and cluster metrics, and silence windows
+    pub alerting: AlertingSettings,
+    /// Servers, clusters and load balancers DevOps keeps the infrastructure
+    /// in line with; empty leaves it to deployments and auto-scaling
+    pub infrastructure: InfraPlan,
+    /// When DevOps adds and removes servers, and how many each fleet may have
+    pub autoscaling: AutoscalingSettings,
+    /// Backup retention and restore drills
+    pub backups: BackupSettings,
+    /// Patches released to servers and the maintenance windows applying them
+    pub patching: PatchingSettings,
+    /// Vulnerability catalog, scan findings and remediation deadlines
+    pub scanning: ScanSettings,
+    /// Simulated attack campaigns against the company's defenses
+    pub red_team: RedTeamSettings,
+    /// Accounts, access requests and access reviews
+    pub iam: IamSettings,
+    /// Security policies compliance audits check
+    pub security_policies: SecurityPolicySettings,
+    /// Simulated phishing campaigns and security-awareness training
+    pub phishing: PhishingSettings,
+    /// Indicator feeds, hunting through traffic and threat advisories
+    pub threat_intel: ThreatIntelSettings,
+    /// Classified data stores and DLP monitoring of where their data goes
+    pub dlp: DlpSettings,
+    /// Resolving security incidents and their post-mortems
+    pub forensics: ForensicsSettings,
+    /// Service traffic over Networking's segments and the metrics it drives
+    pub traffic: TrafficSettings,
+    /// Load balancer health checks and request distribution
+    pub load_balancing: LoadBalancingSettings,
+    /// TLS certificates of the service endpoints and their renewal
+    pub certificates: CertificateSettings,
+    /// Sites, the WAN links and VPNs between them and multi-region services
+    pub wan: WanSettings,
+    /// DDoS attacks on the public segments and their mitigation
+    pub ddos: DdosSettings,
+    /// NetFlow-style flow logs of the traffic and who consumes them
+    pub flow_logs: FlowLogSettings,
+    /// How long support tickets take and how customers respond to them
+    pub tickets: TicketSettings,
+    /// Shared queue new tickets wait in and how they are routed to Ops agents
+    pub ticket_queue: TicketQueueSettings,
+    /// How approved changes are carried out, fail and are reviewed
+    pub changes: ChangeSettings,
+    /// Utilization forecasts and the capacity requested ahead of them
+    pub capacity: CapacitySettings,
+    /// Status page updates, customer notices and SLA credits for incidents
+    pub status_page: StatusPageSettings,
+    /// Remediation steps Ops tries on common incidents before escalating
+    pub runbooks: RunbookSettings,
+    /// Ops shifts around the clock and the handoffs between them
+    pub shifts: ShiftSettings,
+    /// Agent states: leave, and when waiting agents count as blocked
+    pub agent_states: AgentStateSettings,
+    /// What agents remember of their recent messages and work
+    pub memory: MemorySettings,
+    /// Skills agents gain from experience and lose from disuse
+    pub progression: ProgressionSettings,
+    /// Policies deciding what agents do on their turns
+    pub decisions: DecisionSettings,
+    /// Traits shaping agents' turns, work durations, messages and responses
+    pub personalities: PersonalitySettings,
+    /// Departments whose agents run Rhai scripts (`scripting` feature)
+    pub scripting: ScriptingSettings,
+    /// Ticket, incident and project history in SQLite (`sqlite` feature)
+    pub history: HistorySettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DepartmentSizes {
+    pub engineering: usize,
+    pub devops: usize,
+    pub infosec: usize,
+    pub networking: usize,
+    pub ops: usize,
+    pub marketing: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventProbabilities {
+    /// New customer project received
+    pub new_project: f32,
+    /// Security incident detected
+    pub security_incident: f32,
+    /// Infrastructure issue detected
+    pub infrastructure_issue: f32,
+    /// Customer support request received
+    pub customer_request: f32,
+    /// Random inter-agent message
+    pub agent_message: f32,
+    /// Agent runs its daily tasks
+    pub daily_tasks: f32,
+    /// Orchestrator prints a health check
+    pub health_check: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomerSettings {
+    /// Number of customers onboarded at start
+    pub initial_count: usize,
+    /// Fraction of customers on dedicated tenancy
+    pub dedicated_share: f32,
+    /// How customers' satisfaction moves with the service they get
+    pub satisfaction: SatisfactionSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SatisfactionSettings {
+    /// Score (0-100) of a newly onboarded customer
+    pub initial: f64,
+    /// Gain for a ticket resolved within the contract
+    pub ticket_on_time: f64,
+    /// Loss for a late ticket, growing up to four times with the overrun
+    pub ticket_late: f64,
+    /// Loss for a Sev3 incident when declared, and again if resolved late;
+    /// Sev1 counts 4x, Sev2 2x, Sev4 half
+    pub incident: f64,
+    /// Gain for a delivered project
+    pub project_delivered: f64,
+    /// Gain per CSAT point above 3 on a ticket survey, loss per point below
+    pub csat_point: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonSettings {
+    /// Address the liveness/readiness endpoints listen on
+    pub health_addr: String,
+    /// Directory for rotated snapshots
+    pub snapshot_dir: PathBuf,
+    /// Steps between rotated snapshots
+    pub snapshot_every_steps: u64,
+    /// Number of rotated snapshots to keep
+    pub keep_snapshots: usize,
+    /// Hours between summary reports (weekly by default)
+    pub report_interval_hours: i64,
+    /// Directory summary reports are written to
+    pub report_dir: PathBuf,
+    /// HTTP endpoint summary reports are posted to
+    pub report_webhook: Option<String>,
+    /// Address summary reports are emailed to; needs `[email.transport]`
+    pub report_email: Option<String>,
+    /// Seconds without a loop heartbeat before liveness fails
+    pub liveness_timeout_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliverySettings {
+    /// Delivery attempts after the first before a message is dead-lettered
+    pub max_retries: u32,
+    /// Simulated minutes before the first retry; doubles on every attempt
+    pub retry_backoff_minutes: i64,
+    /// Dead letters kept for inspection
+    pub dead_letter_capacity: usize,
+    /// Messages delivered to each agent per step, most urgent first
+    pub batch_size: usize,
+    /// Batches a message may be left out of before it is delivered ahead of
+    /// more urgent traffic
+    pub max_passed_over: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// Endpoints notified of entity changes
+    pub subscriptions: Vec<WebhookSubscription>,
+    /// Delivery attempts after the first before a notification is dropped
+    pub max_retries: u32,
+    /// Simulated minutes before the first retry; doubles on every attempt
+    pub retry_backoff_minutes: i64,
+}
+
+/// One endpoint and the entity changes it wants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    /// Plain-HTTP endpoint notifications are posted to
+    pub url: String,
+    /// Events to send, e.g. `["ticket.created", "incident.resolved"]`
+    pub events: Vec<EntityEvent>,
+    /// Environment variable holding the shared secret for the HMAC-SHA256
+    /// signature header; the secret itself never goes into config
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// Allowed values per entity attribute, e.g. `severity = ["Sev1", "Sev2"]`
+    #[serde(default)]
+    pub filters: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueSyncSettings {
+    /// External tracker to mirror tickets into; sync is off when unset
+    pub tracker: Option<TrackerSettings>,
+    /// Simulated minutes between sync rounds
+    pub interval_minutes: i64,
+    /// Label put on mirrored issues and used to find them again
+    pub label: String,
+    /// Most issues created or updated per round, to stay within API rate limits
+    pub max_writes_per_sync: usize,
+    /// File keeping the ticket-to-issue links across restarts
+    pub state_path: PathBuf,
+}
+
+/// External issue tracker; API tokens are read from the named environment variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrackerSettings {
+    Github {
+        /// Repository as `owner/name`
+        repo: String,
+        #[serde(default = "default_github_api")]
+        api_url: String,
+        #[serde(default = "default_github_token_env")]
+        token_env: String,
+    },
+    Jira {
+        /// Site URL, e.g. `https://acme.atlassian.net`
+        base_url: String,
+        project_key: String,
+        /// Account the API token belongs to
+        email: String,
+        #[serde(default = "default_jira_issue_type")]
+        issue_type: String,
+        #[serde(default = "default_jira_token_env")]
+        token_env: String,
+    },
+}
+
+fn default_github_api() -> String {
+    "https://api.github.com".to_string()
+}
+
+fn default_github_token_env() -> String {
+    "GITHUB_TOKEN".to_string()
+}
+
+fn default_jira_issue_type() -> String {
+    "Task".to_string()
+}
+
+fn default_jira_token_env() -> String {
+    "JIRA_API_TOKEN".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JournalSettings {
+    /// JSONL file every dispatched message is appended to; off when unset
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    /// Address the `/metrics` endpoint listens on; off when unset
+    pub addr: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    /// Address the control API listens on; off when unset
+    pub addr: Option<String>,
+    /// Environment variable holding the bearer token clients must present;
+    /// required unless `addr` is a loopback address
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// SQLite database the history is kept in; off when unset
+    pub database: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PagingSettings {
+    /// Service pages are sent through; paging is off when unset
+    pub provider: Option<PagingProviderSettings>,
+    /// Least severe incident that pages the on-call responder
+    pub min_severity: Severity,
+    /// Simulated minutes a responder has to acknowledge before the page escalates
+    pub ack_timeout_minutes: i64,
+    /// Responders tried after the primary before a page is left unanswered
+    pub max_escalations: u32,
+}
+
+/// Paging service; real services read their keys from the named environment variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PagingProviderSettings {
+    /// Built-in stand-in that acknowledges like a responder would
+    Mock {
+        /// Chance a page is acknowledged at all
+        #[serde(default = "default_mock_ack_probability")]
+        ack_probability: f64,
+        /// Simulated minutes before an acknowledgement, as `[min, max]`
+        #[serde(default = "default_mock_ack_minutes")]
+        ack_minutes: (i64, i64),
+    },
+    /// PagerDuty Events API v2, with the REST API for acknowledgements (`paging` feature)
+    Pagerduty {
+        #[serde(default = "default_pagerduty_events_url")]
+        events_url: String,
+        #[serde(default = "default_pagerduty_api_url")]
+        api_url: String,
+        /// Integration key of the service pages are raised on
+        #[serde(default = "default_pagerduty_routing_key_env")]
+        routing_key_env: String,
+        #[serde(default = "default_pagerduty_token_env")]
+        token_env: String,
+    },
+    /// Opsgenie Alert API (`paging` feature)
+    Opsgenie {
+        #[serde(default = "default_opsgenie_api_url")]
+        api_url: String,
+        #[serde(default = "default_opsgenie_key_env")]
+        api_key_env: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisionSettings {
+    /// Simulated minutes a failing agent sits out, doubled per failure in a row
+    pub backoff_minutes: i64,
+    /// Longest backoff in simulated minutes
+    pub max_backoff_minutes: i64,
+    /// Failures in a row before the agent is restarted
+    pub restart_after_failures: u32,
+    /// Restarts from a checkpoint before the agent is replaced with a fresh one
+    pub max_restarts: u32,
+    /// Simulated minutes between checkpoints of healthy agents
+    pub checkpoint_interval_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VcsSettings {
+    /// Repository name, part of every commit hash
+    pub repository: String,
+    /// Branch features are merged into and releases are cut from
+    pub default_branch: String,
+    /// Infrastructure faults this soon after a release get it reverted
+    pub revert_window_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScorecardSettings {
+    /// Availability target in percent; full SLO marks at or above it
+    pub slo_target: f64,
+    /// Hosting cost per request in dollars that still earns full cost marks
+    pub cost_per_request_budget: f64,
+    /// Tech debt points every service accrues per simulated day
+    pub tech_debt_per_day: f64,
+    /// Services graded below this get an improvement task at the review
+    pub min_grade: Grade,
+    /// Requests each customer sends a service per hour
+    pub requests_per_customer_hour: u64,
+    /// Hosting cost in dollars of one deployment per hour
+    pub deployment_hourly_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaSettings {
+    /// Resources one merged feature of a project needs when deployed
+    pub footprint_per_feature: Resources,
+    /// Quota of every project on the shared clusters
+    pub project_default: Resources,
+    /// Quota of teams not listed in `teams`
+    pub team_default: Resources,
+    /// Quotas of individual teams by department name
+    pub teams: BTreeMap<String, Resources>,
+    /// Utilization in percent from which DevOps provisions more capacity
+    pub capacity_high_water_percent: f64,
+    /// Who approves a quota increase, smallest increases first
+    pub approval: Vec<ApprovalTier>,
+}
+
+/// Row of the quota approval matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalTier {
+    /// Largest increase in percent of the current limit this tier approves
+    pub max_increase_percent: f64,
+    /// Department whose manager approves
+    pub approver: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskSettings {
+    /// Risks scoring at least this (likelihood × impact, 1-25) get an
+    /// executive owner at the quarterly review
+    pub owner_threshold: u8,
+    /// Days an owner has to mitigate a risk before it counts as overdue
+    pub mitigation_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrgSettings {
+    /// Open items between a manager's busiest and least busy report at which
+    /// the manager moves work between them; 0 turns delegation off
+    pub delegate_backlog_gap: usize,
+    /// Merged features a team manager may approve shipping in one change
+    pub manager_change_limit: u32,
+    /// Merged features a department head may approve; larger changes go to the CEO
+    pub head_change_limit: u32,
+    /// Largest quota increase in percent the CEO approves beyond the
+    /// approval matrix
+    pub ceo_budget_percent: f64,
+    /// Have agents send their managers daily status updates, rolled up into
+    /// department reports for the CEO
+    pub status_updates: bool,
+    /// Open items at which an agent's backlog is reported as a risk
+    pub risk_backlog: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutiveSettings {
+    /// Improvement in percent on each KPI the CEO sets as the quarter's goal
+    pub goal_improvement_percent: f64,
+    /// Open work items per agent a department is staffed for
+    pub backlog_per_agent: usize,
+    /// Share of monthly revenue in percent budgeted across departments
+    pub budget_share_percent: f64,
+    /// Weekly reviews in a row missing the revenue goal before hiring freezes
+    pub freeze_after_weeks: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaffingSettings {
+    /// HR reviews every department's workload at midnight and hires or
+    /// offboards to match it
+    pub hr_reviews: bool,
+    /// Open items per agent above which HR hires into a department
+    pub hire_above_backlog: f64,
+    /// Open items per agent below which HR offboards the least busy agent,
+    /// never below the department's starting size
+    pub offboard_below_backlog: f64,
+    /// Agents a department may grow to, manager included
+    pub max_department_size: usize,
+    /// Hires and offboardings scripted for days of the run
+    pub changes: Vec<ScheduledChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EconomySettings {
+    /// Cash in the bank when the company starts
+    pub starting_cash: f64,
+    /// Paid when a project is delivered
+    pub project_revenue: f64,
+    /// Salary and overhead per agent per month
+    pub agent_monthly_cost: f64,
+    /// Hosting cost per deployment per month
+    pub server_monthly_cost: f64,
+    /// Share of the customer's monthly contract in percent refunded for each
+    /// ticket or incident resolved past its SLA
+    pub sla_penalty_percent: f64,
+    /// Cash below which the company is bankrupt
+    pub bankruptcy_cash: f64,
+    /// End the run when the company goes bankrupt
+    pub stop_on_bankruptcy: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeSettings {
+    /// Ops agents search the knowledge base before working an incident
+    pub enabled: bool,
+    /// Hours of work an incident takes with no known fix
+    pub incident_hours: f64,
+    /// Keyword similarity, from 0 to 1, for an article to cover an incident
+    pub min_similarity: f64,
+    /// Share of the usual work an incident takes with a known resolution
+    pub known_fix_effort: f64,
+    /// Share of the usual work an incident takes with a runbook
+    pub runbook_effort: f64,
+    /// Times a resolution is reused before it becomes a runbook
+    pub runbook_after_uses: u32,
+    /// Incidents this severe or worse get a post-mortem
+    pub post_mortem_severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeploymentSettings {
+    /// All at once, canary or blue/green; failed releases roll back either way
+    pub strategy: DeploymentStrategy,
+    /// Environments a build is promoted through, production last; builds go
+    /// straight to production when empty
+    pub environments: Vec<EnvironmentSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentSettings {
+    pub name: String,
+    /// Simulated minutes a build runs here before it moves on
+    #[serde(default)]
+    pub soak_minutes: u32,
+    /// Builds need an Ops change approval to be deployed here
+    #[serde(default)]
+    pub requires_approval: bool,
+}
+
+impl EnvironmentSettings {
+    pub fn new(name: &str, soak_minutes: u32, requires_approval: bool) -> Self {
+        Self { name: name.to_string(), soak_minutes, requires_approval }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertingSettings {
+    /// Check the rules at every hourly SLA check
+    pub enabled: bool,
+    pub rules: Vec<AlertRule>,
+    pub silences: Vec<Silence>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Fires above this value
+    pub threshold: f64,
+    /// Checks in a row above the threshold before the rule fires
+    #[serde(default = "default_for_checks")]
+    pub for_checks: u32,
+    pub severity: Severity,
+}
+
+fn default_for_checks() -> u32 {
+    1
+}
+
+/// Time during which matching rules do not fire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    /// Rule silenced; every rule when unset
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Prefix of the servers or clusters silenced; all when unset
+    #[serde(default)]
+    pub target: Option<String>,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub until: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Days backups are kept; each system's newest is kept regardless
+    pub retention_days: u32,
+    /// Days between restore drills; 0 runs none
+    pub drill_every_days: u32,
+    /// Chance a restore drill finds its backup broken
+    pub drill_failure_probability: f64,
+    /// Minutes a restore usually takes per GB backed up
+    pub restore_minutes_per_gb: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanSettings {
+    /// Known vulnerabilities scans match assets against
+    pub catalog_size: usize,
+    /// Share of catalog vulnerabilities at each severity
+    pub severity_weights: SeverityWeights,
+    /// Chance a scan finds an asset exposed to a vulnerability that applies
+    /// to it
+    pub exposure_probability: f64,
+    /// Days a finding may stay open, by severity, before it is overdue
+    pub remediation_days: RemediationDays,
+    /// Chance the re-scan after a patch still finds the vulnerability
+    pub verification_failure_probability: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeverityWeights {
+    pub critical: f64,
+    pub high: f64,
+    pub medium: f64,
+    pub low: f64,
+    pub info: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemediationDays {
+    pub critical: u32,
+    pub high: u32,
+    pub medium: u32,
+    pub low: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedTeamSettings {
+    /// Run attack campaigns and report on them at the end of the run
+    pub enabled: bool,
+    /// Days between campaigns
+    pub every_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IamSettings {
+    /// Keep accounts for agents, decide access requests and review access
+    pub enabled: bool,
+    /// Chance a new account enrolls in MFA by itself; access reviews
+    /// enroll the rest
+    pub mfa_enrollment_probability: f64,
+    /// Chance an agent asks for a role beyond its baseline each day
+    pub request_probability: f64,
+    /// Days an approved role lasts before a review revokes it
+    pub grant_days: u32,
+    /// Days between access reviews
+    pub review_every_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhishingSettings {
+    /// Run phishing campaigns against agents
+    pub enabled: bool,
+    /// Days between campaigns
+    pub every_days: u32,
+    /// Agents phished by one campaign, drawn across departments
+    pub targets_per_campaign: usize,
+    /// Chance an agent without any awareness clicks
+    pub base_click_probability: f64,
+    /// Awareness agents outside InfoSec start with, from 0 to 1
+    pub initial_awareness: f64,
+    /// Share of the gap to full awareness a training session closes
+    pub training_gain: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreatIntelSettings {
+    /// Hunt for indicators in the day's traffic and raise advisories
+    pub enabled: bool,
+    /// Indicator feeds, STIX 2.1 bundles or JSON lists, reloaded nightly
+    pub feeds: Vec<PathBuf>,
+    /// Firewall, DNS, proxy and endpoint log entries simulated each day
+    pub observations_per_day: usize,
+    /// Chance an observation is threat activity matching an indicator
+    pub malicious_probability: f64,
+    /// Days an advisory lasts
+    pub advisory_days: u32,
+    /// Share of what the defenses would miss an advisory catches
+    pub detection_boost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DlpSettings {
+    /// Monitor transfers out of the data stores for exposed data
+    pub enabled: bool,
+    /// Data stores and their classification
+    pub stores: Vec<DataStore>,
+    /// Transfers out of the stores simulated each day
+    pub transfers_per_day: usize,
+    /// Chance a transfer goes to a segment below its data's level
+    pub misroute_probability: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForensicsSettings {
+    /// Resolve security incidents and write post-mortems of them
+    pub enabled: bool,
+    /// Hours of InfoSec work a security incident takes to resolve
+    pub incident_hours: f64,
+    /// Days after the resolution a post-mortem's action items are due
+    pub action_item_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrafficSettings {
+    /// Derive network metrics from simulated service traffic
+    pub enabled: bool,
+    /// Traffic each customer of a service sends
+    pub customer_mbps: f64,
+    /// Traffic each project being built for a customer adds
+    pub project_mbps: f64,
+    /// Capacity of a segment until Networking upgrades it
+    pub segment_capacity_mbps: u64,
+    /// Latency of crossing an idle segment
+    pub hop_latency_ms: f64,
+    /// How far a day's traffic may stray from the demand, as a share of it
+    pub variability: f64,
+    /// Utilization at which Networking doubles a segment's capacity
+    pub upgrade_utilization: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadBalancingSettings {
+    /// Health-check backends and spread requests every step
+    pub enabled: bool,
+    /// Requests reaching each load balancer
+    pub requests_per_second: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CertificateSettings {
+    /// Check the certificate inventory every evening
+    pub enabled: bool,
+    /// Days a certificate is valid once issued
+    pub lifetime_days: u32,
+    /// Days before expiry automation starts renewing
+    pub renew_before_days: u32,
+    /// Days before expiry DevOps and InfoSec are warned
+    pub warn_before_days: u32,
+    /// Chance a renewal attempt fails; it is retried the next day
+    pub renewal_failure_probability: f64,
+    /// Share of certificates renewed by hand rather than by automation
+    pub manual_share: f64,
+    /// Days at most DevOps takes to renew a certificate by hand once warned
+    pub manual_renewal_max_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WanSettings {
+    /// Fail, repair and route around WAN links every step
+    pub enabled: bool,
+    pub sites: Vec<String>,
+    pub links: Vec<WanLinkSettings>,
+    /// Sites each multi-region service runs in
+    pub services: BTreeMap<String, Vec<String>>,
+    /// Simulated minutes a failed link takes to repair
+    pub repair_minutes: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WanLinkSettings {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub kind: LinkKind,
+    /// One-way latency
+    pub latency_ms: f64,
+    /// Chance the link fails in any hour
+    #[serde(default)]
+    pub failure_probability: f64,
+}
+
+impl WanLinkSettings {
+    pub fn new(from: &str, to: &str, kind: LinkKind, latency_ms: f64, failure_probability: f64) -> Self {
+        Self { from: from.to_string(), to: to.to_string(), kind, latency_ms, failure_probability }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DdosSettings {
+    /// Start and mitigate attacks every step
+    pub enabled: bool,
+    /// Attacks expected in 30 simulated days
+    pub attacks_per_month: f64,
+    /// Attack traffic at most, as a multiple of the segment's capacity
+    pub peak_multiplier: f64,
+    /// Hours an attack lasts at most
+    pub max_duration_hours: u32,
+    /// Minutes into an attack rate-limiting firewall rules are in place
+    pub rate_limit_minutes: u32,
+    /// Minutes into an attack the load balancers scrub its traffic
+    pub scrubbing_minutes: u32,
+    /// Minutes into an attack the upstream provider blackholes the segment
+    pub blackhole_minutes: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowLogSettings {
+    /// Capture a record of every flow each step
+    pub enabled: bool,
+    /// JSON lines file the records are appended to; not written when unset
+    pub path: Option<PathBuf>,
+    /// Correlate the day's records with the threat intel indicators
+    pub threat_hunting: bool,
+    /// Watch the day's records for classified data leaving its segment
+    pub dlp: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketSettings {
+    /// Ops agents work tickets through to resolution and closure
+    pub enabled: bool,
+    /// Hours of work per point of complexity (1-5) at a support skill of
+    /// 100; less skilled agents take longer
+    pub hours_per_complexity: f64,
+    /// Chance a ticket needs more information from the customer
+    pub pending_customer_chance: f64,
+    /// Hours the customer takes to reply or to reopen, at least and at most
+    pub customer_reply_hours: (u32, u32),
+    /// Chance the customer reopens a resolved ticket
+    pub reopen_chance: f64,
+    /// Share of customers answering the CSAT survey when a ticket closes
+    pub survey_response_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketQueueSettings {
+    /// New tickets wait in a shared queue routed across Ops agents; each goes
+    /// straight to the best-suited agent otherwise
+    pub enabled: bool,
+    /// Open tickets an agent holds before the queue holds the rest back
+    pub max_open_per_agent: usize,
+    /// Tickets that need more support skill than most
+    pub routes: Vec<TicketRoute>,
+}
+
+/// Tickets with a tag, or of a priority and above, go to agents with at
+/// least `min_skill` support skill while one has room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TicketRoute {
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub min_priority: Option<Priority>,
+    pub min_skill: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangeSettings {
+    /// Ops carries out approved changes at their scheduled time
+    pub enabled: bool,
+    /// Chance a change fails, for Low, Medium, High and Critical risk
+    pub failure_chances: [f64; 4],
+    /// Minutes a change takes, at least and at most
+    pub duration_minutes: (u32, u32),
+    /// Chance rolling back a failed change restores service
+    pub rollback_success_rate: f64,
+    /// Hours after a change finishes that it is reviewed
+    pub review_after_hours: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapacitySettings {
+    /// Ops forecasts utilization and requests capacity before it runs out
+    pub enabled: bool,
+    /// Weeks of daily samples the trend is fitted over
+    pub lookback_weeks: u32,
+    /// Weeks ahead a resource running out is requested for
+    pub horizon_weeks: u32,
+    /// Utilization in percent at which a resource counts as exhausted
+    pub limit_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusPageSettings {
+    /// Incidents are posted on the status page
+    pub enabled: bool,
+    /// Least severe incident posted
+    pub min_severity: Severity,
+    /// Minutes a resolved incident is monitored before it is marked resolved
+    pub monitoring_minutes: u32,
+    /// Send affected customers a notice with every update
+    pub notify_customers: bool,
+    /// Credits by monthly uptime; the largest tier reached applies
+    pub credit_tiers: Vec<CreditTier>,
+}
+
+/// Share of the monthly bill credited when uptime falls below a level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreditTier {
+    /// Uptime in percent below which the tier applies
+    pub below_uptime: f64,
+    pub credit_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunbookSettings {
+    /// Ops runs the matching runbook before working an incident by hand
+    pub enabled: bool,
+    /// Minutes each runbook step takes
+    pub step_minutes: u32,
+    /// Runbooks for common incidents
+    pub runbooks: Vec<RunbookDefinition>,
+}
+
+/// Ordered remediation steps for incidents sharing a keyword
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunbookDefinition {
+    pub name: String,
+    /// Lowercase words of an incident's title, description or services the
+    /// runbook applies to
+    pub keywords: Vec<String>,
+    pub steps: Vec<RunbookStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunbookStep {
+    pub action: String,
+    /// Chance the step fixes the incident
+    pub success_chance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShiftSettings {
+    /// Ops agents work in shifts and hand their open work over between them
+    pub enabled: bool,
+    /// Shifts covering the day; without any, a day shift over the working
+    /// hours and a night shift over the rest
+    pub shifts: Vec<ShiftDefinition>,
+    /// How much slower services answer while nobody is on shift
+    pub unstaffed_slowdown: f64,
+    /// Handoff notes kept
+    pub max_notes: usize,
+}
+
+/// Hours of the day a shift covers; it wraps past midnight when it ends
+/// before it starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShiftDefinition {
+    pub name: String,
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentStateSettings {
+    /// Chance an agent goes on leave on any day
+    pub leave_chance: f64,
+    /// Days of leave taken at once
+    pub leave_days: u32,
+    /// Minutes waiting on another agent before an agent counts as blocked
+    pub blocked_after_minutes: i64,
+    /// Blocked Ops agents hand their unanswered tickets to available ones
+    pub reassign_blocked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemorySettings {
+    /// Remember messages and finished work and act on them
+    pub enabled: bool,
+    /// Messages each agent remembers
+    pub max_interactions: usize,
+    /// Finished tickets and incidents each agent remembers
+    pub max_tasks: usize,
+    /// Chance a message goes to a known collaborator rather than anyone
+    pub prefer_known_chance: f64,
+    /// How alike an incident must be to one the agent fixed to recall the fix
+    pub recall_similarity: f64,
+    /// Share of the usual work an incident takes when the fix is recalled
+    pub recall_effort: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityPolicySettings {
+    /// Days backups must be kept for the backup retention policy
+    pub min_backup_retention_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatchingSettings {
+    /// Release patches nightly and apply them in maintenance windows
+    pub enabled: bool,
+    /// Chance a server gets a routine patch each day
+    pub release_probability: f64,
+    /// Servers patched in one window; the rest wait for the next
+    pub max_servers_per_window: usize,
+    /// Hours a window keeps its servers in maintenance
+    pub window_hours: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoscalingSettings {
+    /// Average CPU or memory usage of a fleet, in percent, above which it
+    /// gets another server
+    pub scale_up_percent: f64,
+    /// Usage below which a fleet counts as idle
+    pub scale_down_percent: f64,
+    /// Checks in a row a fleet must be idle before a server is removed
+    pub scale_down_after_checks: u32,
+    /// Fewest servers a fleet keeps
+    pub min_servers: usize,
+    /// Most servers a fleet grows to
+    pub max_servers: usize,
+    /// Limits for single fleets, by the hostname of their first server
+    pub fleets: BTreeMap<String, FleetLimits>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FleetLimits {
+    pub min_servers: usize,
+    pub max_servers: usize,
+}
+
+impl AutoscalingSettings {
+    pub fn limits(&self, fleet: &str) -> FleetLimits {
+        self.fleets.get(fleet).copied().unwrap_or(FleetLimits { min_servers: self.min_servers, max_servers: self.max_servers })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineSettings {
+    /// Build and test merged commits before DevOps deploys them
+    pub enabled: bool,
+    /// Pipelines building or testing at once; the rest wait in the queue
+    pub max_concurrent_builds: usize,
+    /// Simulated minutes a build usually takes
+    pub build_minutes: u32,
+    /// Simulated minutes a test run usually takes
+    pub test_minutes: u32,
+    /// Chance a test run fails for no reason
+    pub flaky_test_probability: f64,
+    /// Re-runs of failed tests before the pipeline fails
+    pub test_retries: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressionSettings {
+    /// Experience earned by each finished task, ticket, incident, ...
+    pub experience_per_task: f64,
+    /// Experience a skill point costs at skill 1; the cost grows as the
+    /// skill nears 100
+    pub experience_per_point: f64,
+    /// Days a skill may go unused before it loses a point, and between
+    /// further losses; skills never decay when unset
+    pub decay_after_days: Option<i64>,
+    /// Skill below which disuse costs nothing more
+    pub decay_floor: u8,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecisionSettings {
+    /// Policy of agents without a more specific one
+    pub default: PolicySettings,
+    /// Policies by department name, e.g. "Ops"
+    pub departments: BTreeMap<String, PolicySettings>,
+    /// Policies by agent name, ahead of department ones
+    pub agents: BTreeMap<String, PolicySettings>,
+}
+
+impl DecisionSettings {
+    /// Policy an agent is configured with
+    pub fn policy_for(&self, agent_name: &str, department: &str) -> &PolicySettings {
+        self.agents.get(agent_name)
+            .or_else(|| self.departments.get(department))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Decision policy; the LLM reads its key from the named environment variable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicySettings {
+    /// Routine work by a per-department chance, daily tasks by
+    /// `event_probabilities.daily_tasks`
+    #[default]
+    Random,
+    /// Routine work whenever the agent is not busy, daily tasks every few turns
+    RuleBased {
+        /// Open items at which the agent counts as busy
+        #[serde(default = "default_busy_backlog")]
+        busy_backlog: usize,
+        #[serde(default = "default_daily_tasks_every")]
+        daily_tasks_every: u32,
+    },
+    /// The given turns, repeated
+    Scripted { turns: Vec<TurnDecision> },
+    /// Ask a model behind an OpenAI-compatible chat completions API (`llm` feature)
+    Llm {
+        #[serde(default = "default_llm_api_url")]
+        api_url: String,
+        model: String,
+        #[serde(default = "default_llm_api_key_env")]
+        api_key_env: String,
+    },
+}
+
+fn default_busy_backlog() -> usize {
+    3
+}
+
+fn default_daily_tasks_every() -> u32 {
+    10
+}
+
+fn default_llm_api_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_llm_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonalitySettings {
+    /// Personality of agents without a more specific one
+    pub default: Personality,
+    /// Personalities by department name, e.g. "Ops"
+    pub departments: BTreeMap<String, Personality>,
+    /// Personalities by agent name, ahead of department ones
+    pub agents: BTreeMap<String, Personality>,
+}
+
+impl PersonalitySettings {
+    /// Personality an agent is configured with
+    pub fn personality_for(&self, agent_name: &str, department: &str) -> Personality {
+        self.agents.get(agent_name)
+            .or_else(|| self.departments.get(department))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptingSettings {
+    /// Script each department's agents run, by department name, e.g. "Sales";
+    /// replaces a department's built-in agents
+    pub departments: BTreeMap<String, ScriptedDepartment>,
+    /// Most operations one script call may run before it is stopped
+    pub max_operations: u64,
+}
+
+impl Default for ScriptingSettings {
+    fn default() -> Self {
+        Self {
+            departments: BTreeMap::new(),
+            max_operations: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedDepartment {
+    /// Rhai script, read at startup
+    pub script: PathBuf,
+    /// Message types the script acts on
+    #[serde(default)]
+    pub message_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReleaseNotesSettings {
+    /// Markdown status page listing every release; not written when unset
+    pub status_page_path: Option<PathBuf>,
+    /// Have Marketing rewrite each release's summary for customers
+    pub marketing_polish: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailSettings {
+    /// How emails are sent; email is off when unset
+    pub transport: Option<EmailTransportSettings>,
+    /// Sender of ticket updates
+    pub support_from: String,
+    /// Sender of campaign mailings
+    pub marketing_from: String,
+    /// Customers are mailed at `<customer id>@<customer_domain>`
+    pub customer_domain: String,
+    /// Email customers when their tickets are opened and resolved
+    pub ticket_updates: bool,
+    /// Email customers when an email campaign goes live
+    pub campaigns: bool,
+    /// Email customers the status page notices about incidents affecting them
+    pub status_updates: bool,
+    /// Most customers mailed per campaign
+    pub max_campaign_recipients: usize,
+}
+
+/// Email transport; the SMTP password is read from the named environment variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EmailTransportSettings {
+    /// Record emails without sending them
+    Mock,
+    /// Plain SMTP to a test inbox such as MailHog or Mailpit
+    Smtp {
+        host: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        /// Log in with AUTH PLAIN when set
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default = "default_smtp_password_env")]
+        password_env: String,
+        /// Name the client introduces itself with
+        #[serde(default = "default_smtp_helo")]
+        helo: String,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    1025
+}
+
+fn default_smtp_password_env() -> String {
+    "SMTP_PASSWORD".to_string()
+}
+
+fn default_smtp_helo() -> String {
+    "aivertco.local".to_string()
+}
+
+fn default_mock_ack_probability() -> f64 {
+    0.8
+}
+
+fn default_mock_ack_minutes() -> (i64, i64) {
+    (2, 10)
+}
+
+fn default_pagerduty_events_url() -> String {
+    "https://events.pagerduty.com".to_string()
+}
+
+fn default_pagerduty_api_url() -> String {
+    "https://api.pagerduty.com".to_string()
+}
+
+fn default_pagerduty_routing_key_env() -> String {
+    "PAGERDUTY_ROUTING_KEY".to_string()
+}
+
+fn default_pagerduty_token_env() -> String {
+    "PAGERDUTY_API_TOKEN".to_string()
+}
+
+fn default_opsgenie_api_url() -> String {
+    "https://api.opsgenie.com".to_string()
+}
+
+fn default_opsgenie_key_env() -> String {
+    "OPSGENIE_API_KEY".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSettings {
+    /// Template pack directory; built-in templates are used when unset
+    pub template_pack: Option<PathBuf>,
+    /// Directory end-of-day and end-of-run company reports are written to;
+    /// none are written when unset
+    pub company_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadlockSettings {
+    /// Simulated minutes an unanswered blocking request keeps its sender
+    /// waiting before the wait is dropped
+    pub max_wait_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntakeSettings {
+    /// Open work items at which a department stops taking non-critical intake
+    pub backlog_threshold: usize,
+    /// Open work items at or below which an open breaker closes again
+    pub recovery_backlog: usize,
+    /// Concurrent open Sev1 incidents that pause non-critical intake everywhere
+    pub sev1_storm_threshold: usize,
+    /// Minutes senders are told to wait before retrying deferred messages
+    pub retry_after_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyntheticSettings {
+    /// Directory generated datasets are written to
+    pub out_dir: PathBuf,
+    /// File format of generated datasets
+    pub format: ExportSchema,
+    /// Days of history the records are spread over
+    pub days: u32,
+    /// End of the covered period; unset means now, set it for datasets
+    /// that repeat exactly with the seed
+    pub end: Option<chrono::DateTime<chrono::Utc>>,
+    /// Number of support tickets
+    pub tickets: usize,
+    /// Number of incidents
+    pub incidents: usize,
+    /// Number of network flow records
+    pub network_flows: usize,
+    /// Number of security events
+    pub security_events: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            speed_multiplier: 1.0,
+            autonomous_mode: true,
+            working_hours: (9, 18), // 9 AM to 6 PM
+            max_steps: None,
+            max_wall_seconds: None,
+            max_sim_days: None,
+            headless: false,
+            step_minutes: 1,
+            snapshot_path: Some(PathBuf::from("simulation_snapshot.json")),
+            seed: None,
+            department_sizes: DepartmentSizes::default(),
+            event_probabilities: EventProbabilities::default(),
+            customers: CustomerSettings::default(),
+            daemon: DaemonSettings::default(),
+            synthetic: SyntheticSettings::default(),
+            intake: IntakeSettings::default(),
+            reports: ReportSettings::default(),
+            delivery: DeliverySettings::default(),
+            deadlocks: DeadlockSettings::default(),
+            webhooks: WebhookSettings::default(),
+            issue_sync: IssueSyncSettings::default(),
+            journal: JournalSettings::default(),
+            metrics: MetricsSettings::default(),
+            api: ApiSettings::default(),
+            paging: PagingSettings::default(),
+            email: EmailSettings::default(),
+            vcs: VcsSettings::default(),
+            supervision: SupervisionSettings::default(),
+            release_notes: ReleaseNotesSettings::default(),
+            scorecards: ScorecardSettings::default(),
+            quotas: QuotaSettings::default(),
+            alert_routing: BTreeMap::new(),
+            risks: RiskSettings::default(),
+            org: OrgSettings::default(),
+            executive: ExecutiveSettings::default(),
+            staffing: StaffingSettings::default(),
+            economy: EconomySettings::default(),
+            knowledge: KnowledgeSettings::default(),
+            deployments: DeploymentSettings::default(),
+            pipelines: PipelineSettings::default(),
+            alerting: AlertingSettings::default(),
+            infrastructure: InfraPlan::default(),
+            autoscaling: AutoscalingSettings::default(),
+            backups: BackupSettings::default(),
+            patching: PatchingSettings::default(),
+            scanning: ScanSettings::default(),
+            red_team: RedTeamSettings::default(),
+            iam: IamSettings::default(),
+            security_policies: SecurityPolicySettings::default(),
+            phishing: PhishingSettings::default(),
+            threat_intel: ThreatIntelSettings::default(),
+            dlp: DlpSettings::default(),
+            forensics: ForensicsSettings::default(),
+            traffic: TrafficSettings::default(),
+            load_balancing: LoadBalancingSettings::default(),
+            certificates: CertificateSettings::default(),
+            wan: WanSettings::default(),
+            ddos: DdosSettings::default(),
+            flow_logs: FlowLogSettings::default(),
+            tickets: TicketSettings::default(),
+            ticket_queue: TicketQueueSettings::default(),
+            changes: ChangeSettings::default(),
+            capacity: CapacitySettings::default(),
+            status_page: StatusPageSettings::default(),
+            runbooks: RunbookSettings::default(),
+            shifts: ShiftSettings::default(),
+            agent_states: AgentStateSettings::default(),
+            memory: MemorySettings::default(),
+            progression: ProgressionSettings::default(),
+            decisions: DecisionSettings::default(),
+            personalities: PersonalitySettings::default(),
+            scripting: ScriptingSettings::default(),
+            history: HistorySettings::default(),
+        }
+    }
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            health_addr: "127.0.0.1:8089".to_string(),
+            snapshot_dir: PathBuf::from("snapshots"),
+            snapshot_every_steps: 60,
+            keep_snapshots: 24,
+            report_interval_hours: 24 * 7,
+            report_dir: PathBuf::from("reports"),
+            report_webhook: None,
+            report_email: None,
+            liveness_timeout_secs: 900,
+        }
+    }
+}
+
+impl Default for DeliverySettings {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_backoff_minutes: 5,
+            dead_letter_capacity: 1_000,
+            batch_size: 20,
+            max_passed_over: 5,
+        }
+    }
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            max_retries: 5,
+            retry_backoff_minutes: 1,
+        }
+    }
+}
+
+impl Default for IssueSyncSettings {
+    fn default() -> Self {
+        Self {
+            tracker: None,
+            interval_minutes: 15,
+            label: "aivertco".to_string(),
+            max_writes_per_sync: 25,
+            state_path: PathBuf::from("issue_sync_links.json"),
+        }
+    }
+}
+
+impl Default for PagingSettings {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            min_severity: Severity::Sev2,
+            ack_timeout_minutes: 15,
+            max_escalations: 2,
+        }
+    }
+}
+
+impl Default for SupervisionSettings {
+    fn default() -> Self {
+        Self {
+            backoff_minutes: 5,
+            max_backoff_minutes: 60,
+            restart_after_failures: 3,
+            max_restarts: 3,
+            checkpoint_interval_minutes: 60,
+        }
+    }
+}
+
+impl Default for ScorecardSettings {
+    fn default() -> Self {
+        Self {
+            slo_target: 99.5,
+            cost_per_request_budget: 0.001,
+            tech_debt_per_day: 1.0,
+            min_grade: Grade::C,
+            requests_per_customer_hour: 1_000,
+            deployment_hourly_cost: 0.5,
+        }
+    }
+}
+
+impl Default for QuotaSettings {
+    fn default() -> Self {
+        Self {
+            footprint_per_feature: Resources::new(2, 4, 20),
+            project_default: Resources::new(8, 16, 100),
+            team_default: Resources::new(32, 64, 500),
+            teams: BTreeMap::new(),
+            capacity_high_water_percent: 80.0,
+            approval: vec![
+                ApprovalTier { max_increase_percent: 25.0, approver: "DevOps".to_string() },
+                ApprovalTier { max_increase_percent: 100.0, approver: "Ops".to_string() },
+            ],
+        }
+    }
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        Self {
+            owner_threshold: 9,
+            mitigation_days: 60,
+        }
+    }
+}
+
+impl Default for OrgSettings {
+    fn default() -> Self {
+        Self {
+            delegate_backlog_gap: 3,
+            manager_change_limit: 2,
+            head_change_limit: 5,
+            ceo_budget_percent: 300.0,
+            status_updates: true,
+            risk_backlog: 8,
+        }
+    }
+}
+
+impl Default for ExecutiveSettings {
+    fn default() -> Self {
+        Self {
+            goal_improvement_percent: 10.0,
+            backlog_per_agent: 5,
+            budget_share_percent: 30.0,
+            freeze_after_weeks: 2,
+        }
+    }
+}
+
+impl Default for StaffingSettings {
+    fn default() -> Self {
+        Self {
+            hr_reviews: true,
+            hire_above_backlog: 6.0,
+            offboard_below_backlog: 0.5,
+            max_department_size: 12,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl Default for EconomySettings {
+    fn default() -> Self {
+        Self {
+            starting_cash: 100_000.0,
+            project_revenue: 5_000.0,
+            agent_monthly_cost: 1_500.0,
+            server_monthly_cost: 200.0,
+            sla_penalty_percent: 10.0,
+            bankruptcy_cash: 0.0,
+            stop_on_bankruptcy: true,
+        }
+    }
+}
+
+impl Default for KnowledgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            incident_hours: 6.0,
+            min_similarity: 0.5,
+            known_fix_effort: 0.5,
+            runbook_effort: 0.25,
+            runbook_after_uses: 3,
+            post_mortem_severity: Severity::Sev2,
+        }
+    }
+}
+
+impl Default for AlertingSettings {
+    fn default() -> Self {
+        let rule = |name: &str, metric, threshold, for_checks, severity| AlertRule { name: name.to_string(), metric, threshold, for_checks, severity };
+        Self {
+            enabled: true,
+            rules: vec![
+                rule("high-cpu", AlertMetric::Cpu, 90.0, 3, Severity::Sev3),
+                rule("high-memory", AlertMetric::Memory, 90.0, 3, Severity::Sev3),
+                rule("disk-filling", AlertMetric::Disk, 85.0, 1, Severity::Sev3),
+                rule("cluster-degraded", AlertMetric::ClusterDegraded, 0.5, 1, Severity::Sev2),
+            ],
+            silences: vec![],
+        }
+    }
+}
+
+impl Default for DeploymentSettings {
+    fn default() -> Self {
+        Self {
+            strategy: DeploymentStrategy::default(),
+            environments: vec![
+                EnvironmentSettings::new("dev", 0, false),
+                EnvironmentSettings::new("staging", 240, false),
+                EnvironmentSettings::new("production", 0, true),
+            ],
+        }
+    }
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            drill_every_days: 7,
+            drill_failure_probability: 0.1,
+            restore_minutes_per_gb: 0.5,
+        }
+    }
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            catalog_size: 200,
+            severity_weights: SeverityWeights::default(),
+            exposure_probability: 0.005,
+            remediation_days: RemediationDays::default(),
+            verification_failure_probability: 0.05,
+        }
+    }
+}
+
+impl Default for SeverityWeights {
+    fn default() -> Self {
+        Self {
+            critical: 0.05,
+            high: 0.15,
+            medium: 0.4,
+            low: 0.3,
+            info: 0.1,
+        }
+    }
+}
+
+impl Default for RemediationDays {
+    fn default() -> Self {
+        Self {
+            critical: 7,
+            high: 30,
+            medium: 90,
+            low: 180,
+        }
+    }
+}
+
+impl Default for RedTeamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every_days: 7,
+        }
+    }
+}
+
+impl Default for IamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mfa_enrollment_probability: 0.9,
+            request_probability: 0.02,
+            grant_days: 30,
+            review_every_days: 30,
+        }
+    }
+}
+
+impl Default for PhishingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            every_days: 14,
+            targets_per_campaign: 10,
+            base_click_probability: 0.4,
+            initial_awareness: 0.3,
+            training_gain: 0.3,
+        }
+    }
+}
+
+impl Default for ThreatIntelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            feeds: Vec::new(),
+            observations_per_day: 500,
+            malicious_probability: 0.01,
+            advisory_days: 7,
+            detection_boost: 0.3,
+        }
+    }
+}
+
+impl Default for DlpSettings {
+    fn default() -> Self {
+        let store = |name: &str, classification, segment: &str, security_level, records| DataStore {
+            name: name.to_string(),
+            classification,
+            segment: segment.to_string(),
+            security_level,
+            records,
+            record_bytes: 2048,
+        };
+        Self {
+            enabled: true,
+            stores: vec![
+                store("customer-db", Classification::Pii, "data", SecurityLevel::Restricted, 50_000),
+                store("billing-ledger", Classification::Confidential, "data", SecurityLevel::Restricted, 20_000),
+                store("wiki", Classification::Internal, "corporate", SecurityLevel::Internal, 5_000),
+                store("marketing-site", Classification::Public, "dmz", SecurityLevel::DMZ, 500),
+            ],
+            transfers_per_day: 200,
+            misroute_probability: 0.005,
+        }
+    }
+}
+
+impl Default for ForensicsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            incident_hours: 4.0,
+            action_item_days: 14,
+        }
+    }
+}
+
+impl Default for TrafficSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            customer_mbps: 25.0,
+            project_mbps: 50.0,
+            segment_capacity_mbps: 1000,
+            hop_latency_ms: 2.0,
+            variability: 0.3,
+            upgrade_utilization: 0.8,
+        }
+    }
+}
+
+impl Default for LoadBalancingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            requests_per_second: 50.0,
+        }
+    }
+}
+
+impl Default for CertificateSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lifetime_days: 90,
+            renew_before_days: 30,
+            warn_before_days: 14,
+            renewal_failure_probability: 0.1,
+            manual_share: 0.2,
+            manual_renewal_max_days: 21,
+        }
+    }
+}
+
+impl Default for WanSettings {
+    fn default() -> Self {
+        let sites = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        Self {
+            enabled: true,
+            sites: sites(&["us-east", "us-west", "eu-west"]),
+            links: vec![
+                WanLinkSettings::new("us-east", "us-west", LinkKind::Wan, 35.0, 0.002),
+                WanLinkSettings::new("us-east", "eu-west", LinkKind::Wan, 40.0, 0.002),
+                WanLinkSettings::new("us-west", "eu-west", LinkKind::Vpn, 70.0, 0.005),
+            ],
+            services: BTreeMap::from([
+                ("web-frontend".to_string(), sites(&["us-east", "eu-west"])),
+                ("api-gateway".to_string(), sites(&["us-east", "us-west"])),
+            ]),
+            repair_minutes: 120,
+        }
+    }
+}
+
+impl Default for DdosSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            attacks_per_month: 2.0,
+            peak_multiplier: 5.0,
+            max_duration_hours: 6,
+            rate_limit_minutes: 10,
+            scrubbing_minutes: 30,
+            blackhole_minutes: 90,
+        }
+    }
+}
+
+impl Default for FlowLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            threat_hunting: true,
+            dlp: true,
+        }
+    }
+}
+
+impl Default for TicketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hours_per_complexity: 1.5,
+            pending_customer_chance: 0.3,
+            customer_reply_hours: (1, 24),
+            reopen_chance: 0.1,
+            survey_response_rate: 0.4,
+        }
+    }
+}
+
+impl Default for TicketQueueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_open_per_agent: 8,
+            routes: vec![TicketRoute { tag: None, min_priority: Some(Priority::Urgent), min_skill: 85 }],
+        }
+    }
+}
+
+impl Default for ChangeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_chances: [0.02, 0.05, 0.15, 0.3],
+            duration_minutes: (15, 120),
+            rollback_success_rate: 0.9,
+            review_after_hours: 24,
+        }
+    }
+}
+
+impl Default for CapacitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lookback_weeks: 4,
+            horizon_weeks: 8,
+            limit_percent: 85.0,
+        }
+    }
+}
+
+impl Default for StatusPageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_severity: Severity::Sev2,
+            monitoring_minutes: 60,
+            notify_customers: true,
+            credit_tiers: vec![
+                CreditTier { below_uptime: 99.9, credit_percent: 10.0 },
+                CreditTier { below_uptime: 99.0, credit_percent: 25.0 },
+                CreditTier { below_uptime: 95.0, credit_percent: 50.0 },
+            ],
+        }
+    }
+}
+
+impl Default for RunbookSettings {
+    fn default() -> Self {
+        let runbook = |name: &str, keywords: &[&str], steps: &[(&str, f64)]| RunbookDefinition {
+            name: name.to_string(),
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+            steps: steps.iter().map(|(action, success_chance)| RunbookStep { action: action.to_string(), success_chance: *success_chance }).collect(),
+        };
+        Self {
+            enabled: true,
+            step_minutes: 15,
+            runbooks: vec![
+                runbook("High CPU", &["cpu"], &[("Restart the busiest processes", 0.4), ("Scale out the deployment", 0.5), ("Fail over to a healthy pool", 0.6)]),
+                runbook("Noisy neighbour", &["noisy", "neighbour"], &[("Throttle the noisy tenant", 0.5), ("Move the noisy tenant off the pool", 0.7)]),
+                runbook("Expired certificate", &["certificate"], &[("Renew the certificate", 0.7), ("Roll the renewed certificate out to the load balancers", 0.8)]),
+                runbook("DDoS", &["ddos"], &[("Tighten the rate limits", 0.4), ("Divert traffic through scrubbing", 0.6)]),
+                runbook("Failed change", &["change"], &[("Roll the change back again", 0.5), ("Restore from the last backup", 0.6)]),
+            ],
+        }
+    }
+}
+
+impl Default for ShiftSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shifts: vec![],
+            unstaffed_slowdown: 2.0,
+            max_notes: 50,
+        }
+    }
+}
+
+impl Default for AgentStateSettings {
+    fn default() -> Self {
+        Self {
+            leave_chance: 0.01,
+            leave_days: 3,
+            blocked_after_minutes: 120,
+            reassign_blocked: true,
+        }
+    }
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_interactions: 50,
+            max_tasks: 20,
+            prefer_known_chance: 0.7,
+            recall_similarity: 0.5,
+            recall_effort: 0.6,
+        }
+    }
+}
+
+impl Default for SecurityPolicySettings {
+    fn default() -> Self {
+        Self {
+            min_backup_retention_days: 30,
+        }
+    }
+}
+
+impl Default for PatchingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            release_probability: 0.1,
+            max_servers_per_window: 5,
+            window_hours: 2,
+        }
+    }
+}
+
+impl Default for AutoscalingSettings {
+    fn default() -> Self {
+        Self {
+            scale_up_percent: 80.0,
+            scale_down_percent: 30.0,
+            scale_down_after_checks: 3,
+            min_servers: 1,
+            max_servers: 10,
+            fleets: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for PipelineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concurrent_builds: 2,
+            build_minutes: 10,
+            test_minutes: 20,
+            flaky_test_probability: 0.05,
+            test_retries: 2,
+        }
+    }
+}
+
+impl Default for ProgressionSettings {
+    fn default() -> Self {
+        Self {
+            experience_per_task: 1.0,
+            experience_per_point: 2.0,
+            decay_after_days: None,
+            decay_floor: 30,
+        }
+    }
+}
+
+impl Default for VcsSettings {
+    fn default() -> Self {
+        Self {
+            repository: "aivertco-platform".to_string(),
+            default_branch: "main".to_string(),
+            revert_window_minutes: 60,
+        }
+    }
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            transport: None,
+            support_from: "support@aivertco.example".to_string(),
+            marketing_from: "news@aivertco.example".to_string(),
+            customer_domain: "customers.example".to_string(),
+            ticket_updates: true,
+            campaigns: true,
+            status_updates: true,
+            max_campaign_recipients: 100,
+        }
+    }
+}
+
+impl Default for DeadlockSettings {
+    fn default() -> Self {
+        Self {
+            max_wait_minutes: 8 * 60,
+        }
+    }
+}
+
+impl Default for IntakeSettings {
+    fn default() -> Self {
+        Self {
+            backlog_threshold: 25,
+            recovery_backlog: 10,
+            sev1_storm_threshold: 2,
+            retry_after_minutes: 30,
+        }
+    }
+}
+
+impl Default for SyntheticSettings {
+    fn default() -> Self {
+        Self {
+            out_dir: PathBuf::from("datasets"),
+            format: ExportSchema::Jsonl,
+            days: 30,
+            end: None,
+            tickets: 1_000,
+            incidents: 100,
+            network_flows: 10_000,
+            security_events: 2_000,
+        }
+    }
+}
+
+impl Default for CustomerSettings {
+    fn default() -> Self {
+        Self {
+            initial_count: 8,
+            dedicated_share: 0.25,
+            satisfaction: SatisfactionSettings::default(),
+        }
+    }
+}
+
+impl Default for SatisfactionSettings {
+    fn default() -> Self {
+        Self {
+            initial: 75.0,
+            ticket_on_time: 2.0,
+            ticket_late: 5.0,
+            incident: 4.0,
+            project_delivered: 5.0,
+            csat_point: 1.0,
+        }
+    }
+}
+
+impl DepartmentSizes {
+    /// Starting agents of a department besides its manager
+    pub fn of(&self, department: &str) -> usize {
+        match department {
+            "Engineering" => self.engineering,
+            "DevOps" => self.devops,
+            "InfoSec" => self.infosec,
+            "Networking" => self.networking,
+            "Ops" => self.ops,
+            "Marketing" => self.marketing,
+            _ => 0,
+        }
+    }
+}
+
+impl Default for DepartmentSizes {
+    fn default() -> Self {
+        Self {
+            engineering: 3,
+            devops: 3,
+            infosec: 2,
+            networking: 2,
+            ops: 3,
+            marketing: 2,
+        }
+    }
+}
+
+impl Default for EventProbabilities {
+    fn default() -> Self {
+        Self {
+            new_project: 0.05,
+            security_incident: 0.03,
+            infrastructure_issue: 0.04,
+            customer_request: 0.06,
+            agent_message: 0.15,
+            daily_tasks: 0.10,
+            health_check: 0.10,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Load configuration from a file (if any), apply environment overrides and validate
+    ///
+    /// With no explicit path, `aivertco.toml` in the working directory is used
+    /// when present; otherwise the defaults apply.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Self::default(),
+        };
+
+        config.apply_env_overrides(|key| std::env::var(key).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse a TOML configuration file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        toml::from_str(&contents)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))
+    }
+
+    /// Apply `AIVERTCO_*` overrides using the given variable lookup
+    pub fn apply_env_overrides<F>(&mut self, lookup: F) -> Result<(), ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(value) = lookup("AIVERTCO_SPEED") {
+            self.speed_multiplier = parse_env("AIVERTCO_SPEED", &value)?;
+        }
+        if let Some(value) = lookup("AIVERTCO_AUTONOMOUS") {
+            self.autonomous_mode = parse_env("AIVERTCO_AUTONOMOUS", &value)?;
+        }
+        if let Some(value) = lookup("AIVERTCO_WORKING_HOURS") {
+            let (start, end) = value.split_once('-')
+                .ok_or_else(|| ConfigError::Env("AIVERTCO_WORKING_HOURS".to_string(), format!("expected START-END, got '{}'", value)))?;
+            self.working_hours = (
+                parse_env("AIVERTCO_WORKING_HOURS", start.trim())?,
+                parse_env("AIVERTCO_WORKING_HOURS", end.trim())?,
+            );
+        }
+        if let Some(value) = lookup("AIVERTCO_MAX_STEPS") {
+            self.max_steps = Some(parse_env("AIVERTCO_MAX_STEPS", &value)?);
+        }
+        if let Some(value) = lookup("AIVERTCO_DURATION") {
+            self.max_wall_seconds = Some(parse_duration(&value).map_err(|e| ConfigError::Env("AIVERTCO_DURATION".to_string(), e))?);
+        }
+        if let Some(value) = lookup("AIVERTCO_SIM_DAYS") {
+            self.max_sim_days = Some(parse_env("AIVERTCO_SIM_DAYS", &value)?);
+        }
+        if let Some(value) = lookup("AIVERTCO_HEADLESS") {
+            self.headless = parse_env("AIVERTCO_HEADLESS", &value)?;
+        }
+        if let Some(value) = lookup("AIVERTCO_SEED") {
+            self.seed = Some(parse_env("AIVERTCO_SEED", &value)?);
+        }
+        if let Some(value) = lookup("AIVERTCO_SNAPSHOT_PATH") {
+            self.snapshot_path = if value.is_empty() { None } else { Some(PathBuf::from(value)) };
+        }
+        if let Some(value) = lookup("AIVERTCO_METRICS_ADDR") {
+            self.metrics.addr = if value.is_empty() { None } else { Some(value) };
+        }
+        if let Some(value) = lookup("AIVERTCO_API_ADDR") {
+            self.api.addr = if value.is_empty() { None } else { Some(value) };
+        }
+        if let Some(value) = lookup("AIVERTCO_COMPANY_REPORT_DIR") {
+            self.reports.company_dir = if value.is_empty() { None } else { Some(PathBuf::from(value)) };
+        }
+        if let Some(value) = lookup("AIVERTCO_HISTORY_DB") {
+            self.history.database = if value.is_empty() { None } else { Some(PathBuf::from(value)) };
+        }
+
+        Ok(())
+    }
+
+    /// Check the configuration for values the simulation cannot run with
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if !(self.speed_multiplier > 0.0 && self.speed_multiplier.is_finite()) {
+            problems.push(format!("speed_multiplier must be positive, got {}", self.speed_multiplier));
+        }
+
+        let (start, end) = self.working_hours;
+        if start > 23 || end > 24 || start >= end {
+            problems.push(format!("working_hours must satisfy 0 <= start < end <= 24, got ({}, {})", start, end));
+        }
+
+        if self.step_minutes == 0 {
+            problems.push("step_minutes must be at least 1".to_string());
+        }
+
+        if self.max_steps == Some(0) {
+            problems.push("max_steps must be at least 1 when set".to_string());
+        }
+        if self.max_wall_seconds == Some(0) {
+            problems.push("max_wall_seconds must be at least 1 when set".to_string());
+        }
+        if self.max_sim_days == Some(0) {
+            problems.push("max_sim_days must be at least 1 when set".to_string());
+        }
+
+        let probabilities = &self.event_probabilities;
+        let named = [
+            ("new_project", probabilities.new_project),
+            ("security_incident", probabilities.security_incident),
+            ("infrastructure_issue", probabilities.infrastructure_issue),
+            ("customer_request", probabilities.customer_request),
+            ("agent_message", probabilities.agent_message),
+            ("daily_tasks", probabilities.daily_tasks),
+            ("health_check", probabilities.health_check),
+        ];
+        for (name, value) in named {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("event_probabilities.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.customers.dedicated_share) {
+            problems.push(format!("customers.dedicated_share must be between 0 and 1, got {}", self.customers.dedicated_share));
+        }
+        if !(0.0..=100.0).contains(&self.customers.satisfaction.initial) {
+            problems.push(format!("customers.satisfaction.initial must be between 0 and 100, got {}", self.customers.satisfaction.initial));
+        }
+
+        if let Some(addr) = &self.api.addr {
+            if self.api.token_env.is_none() && !is_loopback(addr) {
+                problems.push(format!("api.addr '{}' is reachable from other hosts; set api.token_env or listen on a loopback address", addr));
+            }
+        }
+
+        let daemon = &self.daemon;
+        if daemon.snapshot_every_steps == 0 {
+            problems.push("daemon.snapshot_every_steps must be at least 1".to_string());
+        }
+        if daemon.keep_snapshots == 0 {
+            problems.push("daemon.keep_snapshots must be at least 1".to_string());
+        }
+        if daemon.report_interval_hours < 1 {
+            problems.push(format!("daemon.report_interval_hours must be at least 1, got {}", daemon.report_interval_hours));
+        }
+        if let Some(url) = &daemon.report_webhook {
+            if !url.starts_with("http://") {
+                problems.push(format!("daemon.report_webhook must be an http:// URL, got '{}'", url));
+            }
+        }
+        if let Some(address) = &daemon.report_email {
+            if !address.contains('@') {
+                problems.push(format!("daemon.report_email must be an email address, got '{}'", address));
+            }
+            if self.email.transport.is_none() {
+                problems.push("daemon.report_email needs an [email.transport] to send through".to_string());
+            }
+        }
+
+        let delivery = &self.delivery;
+        if delivery.retry_backoff_minutes < 1 {
+            problems.push(format!("delivery.retry_backoff_minutes must be at least 1, got {}", delivery.retry_backoff_minutes));
+        }
+        if delivery.max_retries > 16 {
+            problems.push(format!("delivery.max_retries must be at most 16, got {}", delivery.max_retries));
+        }
+        if delivery.dead_letter_capacity == 0 {
+            problems.push("delivery.dead_letter_capacity must be at least 1".to_string());
+        }
+        if delivery.batch_size == 0 {
+            problems.push("delivery.batch_size must be at least 1".to_string());
+        }
+
+        let webhooks = &self.webhooks;
+        if webhooks.retry_backoff_minutes < 1 {
+            problems.push(format!("webhooks.retry_backoff_minutes must be at least 1, got {}", webhooks.retry_backoff_minutes));
+        }
+        if webhooks.max_retries > 16 {
+            problems.push(format!("webhooks.max_retries must be at most 16, got {}", webhooks.max_retries));
+        }
+        for subscription in &webhooks.subscriptions {
+            if !subscription.url.starts_with("http://") {
+                problems.push(format!("webhooks.subscriptions url must be an http:// URL, got '{}'", subscription.url));
+            }
+            if subscription.events.is_empty() {
+                problems.push(format!("webhooks.subscriptions for '{}' must list at least one event", subscription.url));
+            }
+        }
+
+        let sync = &self.issue_sync;
+        if sync.interval_minutes < 1 {
+            problems.push(format!("issue_sync.interval_minutes must be at least 1, got {}", sync.interval_minutes));
+        }
+        if sync.max_writes_per_sync == 0 {
+            problems.push("issue_sync.max_writes_per_sync must be at least 1".to_string());
+        }
+        if sync.label.trim().is_empty() {
+            problems.push("issue_sync.label must not be empty".to_string());
+        }
+        match &sync.tracker {
+            Some(TrackerSettings::Github { repo, .. }) if repo.split('/').filter(|part| !part.is_empty()).count() != 2 => {
+                problems.push(format!("issue_sync.tracker.repo must be 'owner/name', got '{}'", repo));
+            }
+            Some(TrackerSettings::Jira { base_url, .. }) if !base_url.starts_with("https://") && !base_url.starts_with("http://") => {
+                problems.push(format!("issue_sync.tracker.base_url must be an http(s) URL, got '{}'", base_url));
+            }
+            _ => {}
+        }
+
+        let paging = &self.paging;
+        if paging.ack_timeout_minutes < 1 {
+            problems.push(format!("paging.ack_timeout_minutes must be at least 1, got {}", paging.ack_timeout_minutes));
+        }
+        match &paging.provider {
+            Some(PagingProviderSettings::Mock { ack_probability, ack_minutes: (min, max) }) => {
+                if !(0.0..=1.0).contains(ack_probability) {
+                    problems.push(format!("paging.provider.ack_probability must be between 0 and 1, got {}", ack_probability));
+                }
+                if *min < 0 || min > max {
+                    problems.push(format!("paging.provider.ack_minutes must be [min, max] with 0 <= min <= max, got [{}, {}]", min, max));
+                }
+            }
+            Some(PagingProviderSettings::Pagerduty { events_url: url, .. } | PagingProviderSettings::Opsgenie { api_url: url, .. })
+                if !url.starts_with("https://") && !url.starts_with("http://") => {
+                problems.push(format!("paging.provider URL must be an http(s) URL, got '{}'", url));
+            }
+            _ => {}
+        }
+
+        let email = &self.email;
+        for (key, address) in [("support_from", &email.support_from), ("marketing_from", &email.marketing_from)] {
+            if !address.contains('@') {
+                problems.push(format!("email.{} must be an email address, got '{}'", key, address));
+            }
+        }
+        if email.customer_domain.trim().is_empty() || email.customer_domain.contains('@') {
+            problems.push(format!("email.customer_domain must be a domain name, got '{}'", email.customer_domain));
+        }
+        if let Some(EmailTransportSettings::Smtp { host, port, .. }) = &email.transport {
+            if host.trim().is_empty() || *port == 0 {
+                problems.push(format!("email.transport must name an SMTP host and port, got '{}:{}'", host, port));
+            }
+        }
+
+        let supervision = &self.supervision;
+        if supervision.backoff_minutes < 1 || supervision.max_backoff_minutes < supervision.backoff_minutes {
+            problems.push(format!("supervision backoff must satisfy 1 <= backoff_minutes <= max_backoff_minutes, got {} and {}",
+                                  supervision.backoff_minutes, supervision.max_backoff_minutes));
+        }
+        if supervision.restart_after_failures == 0 {
+            problems.push("supervision.restart_after_failures must be at least 1".to_string());
+        }
+        if supervision.checkpoint_interval_minutes < 1 {
+            problems.push(format!("supervision.checkpoint_interval_minutes must be at least 1, got {}", supervision.checkpoint_interval_minutes));
+        }
+
+        let vcs = &self.vcs;
+        for (key, name) in [("repository", &vcs.repository), ("default_branch", &vcs.default_branch)] {
+            if name.trim().is_empty() || name.contains(char::is_whitespace) {
+                problems.push(format!("vcs.{} must be a name without spaces, got '{}'", key, name));
+            }
+        }
+        if vcs.revert_window_minutes < 0 {
+            problems.push(format!("vcs.revert_window_minutes must not be negative, got {}", vcs.revert_window_minutes));
+        }
+
+        let scorecards = &self.scorecards;
+        if !(scorecards.slo_target > 0.0 && scorecards.slo_target <= 100.0) {
+            problems.push(format!("scorecards.slo_target must be in (0, 100], got {}", scorecards.slo_target));
+        }
+        if !(scorecards.cost_per_request_budget > 0.0 && scorecards.cost_per_request_budget.is_finite()) {
+            problems.push(format!("scorecards.cost_per_request_budget must be positive, got {}", scorecards.cost_per_request_budget));
+        }
+        if scorecards.tech_debt_per_day < 0.0 || scorecards.deployment_hourly_cost < 0.0 {
+            problems.push("scorecards.tech_debt_per_day and deployment_hourly_cost must not be negative".to_string());
+        }
+
+        let quotas = &self.quotas;
+        if quotas.footprint_per_feature == Resources::default() {
+            problems.push("quotas.footprint_per_feature must reserve some resources".to_string());
+        }
+        if !(quotas.capacity_high_water_percent > 0.0 && quotas.capacity_high_water_percent <= 100.0) {
+            problems.push(format!("quotas.capacity_high_water_percent must be in (0, 100], got {}", quotas.capacity_high_water_percent));
+        }
+        if quotas.approval.windows(2).any(|pair| pair[0].max_increase_percent >= pair[1].max_increase_percent) {
+            problems.push("quotas.approval tiers must be ordered by increasing max_increase_percent".to_string());
+        }
+        for tier in &quotas.approval {
+            if tier.approver.trim().is_empty() {
+                problems.push("quotas.approval approver must name a department".to_string());
+            }
+        }
+
+        for (service, policy) in &self.alert_routing {
+            if policy.escalation_delay_minutes.is_some_and(|minutes| minutes < 1) {
+                problems.push(format!("alert_routing.{}.escalation_delay_minutes must be at least 1", service));
+            }
+            if policy.escalation_delay_minutes.is_some() && policy.escalate_to.is_empty() {
+                problems.push(format!("alert_routing.{}.escalate_to must name a recipient when escalation_delay_minutes is set", service));
+            }
+            let routes = policy.routes.iter().chain(&policy.after_hours);
+            if routes.flat_map(|route| &route.notify).chain(&policy.escalate_to).any(|target| target.trim().is_empty()) {
+                problems.push(format!("alert_routing.{} recipients must not be empty", service));
+            }
+        }
+
+        if !(1..=25).contains(&self.risks.owner_threshold) {
+            problems.push(format!("risks.owner_threshold must be between 1 and 25, got {}", self.risks.owner_threshold));
+        }
+        if self.risks.mitigation_days < 1 {
+            problems.push(format!("risks.mitigation_days must be at least 1, got {}", self.risks.mitigation_days));
+        }
+
+        let org = &self.org;
+        if org.manager_change_limit > org.head_change_limit {
+            problems.push(format!("org.manager_change_limit ({}) must not exceed org.head_change_limit ({})",
+                                  org.manager_change_limit, org.head_change_limit));
+        }
+        let largest_tier = quotas.approval.last().map_or(0.0, |tier| tier.max_increase_percent);
+        if !(org.ceo_budget_percent >= largest_tier && org.ceo_budget_percent.is_finite()) {
+            problems.push(format!("org.ceo_budget_percent must be at least the largest quotas.approval tier ({}), got {}",
+                                  largest_tier, org.ceo_budget_percent));
+        }
+        if org.risk_backlog == 0 {
+            problems.push("org.risk_backlog must be at least 1".to_string());
+        }
+
+        let executive = &self.executive;
+        if !(0.0..100.0).contains(&executive.goal_improvement_percent) {
+            problems.push(format!("executive.goal_improvement_percent must be at least 0 and below 100, got {}", executive.goal_improvement_percent));
+        }
+        if executive.backlog_per_agent == 0 {
+            problems.push("executive.backlog_per_agent must be at least 1".to_string());
+        }
+        if !(0.0..=100.0).contains(&executive.budget_share_percent) {
+            problems.push(format!("executive.budget_share_percent must be between 0 and 100, got {}", executive.budget_share_percent));
+        }
+        if executive.freeze_after_weeks == 0 {
+            problems.push("executive.freeze_after_weeks must be at least 1".to_string());
+        }
+
+        let staffing = &self.staffing;
+        if staffing.offboard_below_backlog >= staffing.hire_above_backlog {
+            problems.push(format!("staffing.offboard_below_backlog ({}) must be below staffing.hire_above_backlog ({})",
+                                  staffing.offboard_below_backlog, staffing.hire_above_backlog));
+        }
+        if staffing.max_department_size == 0 {
+            problems.push("staffing.max_department_size must be at least 1".to_string());
+        }
+        for change in &staffing.changes {
+            if let StaffingChange::Hire { department, .. } = &change.change {
+                if department.trim().is_empty() {
+                    problems.push(format!("staffing.changes: hire on day {} needs a department", change.day));
+                }
+            }
+        }
+
+        let economy = &self.economy;
+        for (name, amount) in [("project_revenue", economy.project_revenue),
+                               ("agent_monthly_cost", economy.agent_monthly_cost),
+                               ("server_monthly_cost", economy.server_monthly_cost)] {
+            if amount < 0.0 {
+                problems.push(format!("economy.{} must not be negative, got {}", name, amount));
+            }
+        }
+        if !(0.0..=100.0).contains(&economy.sla_penalty_percent) {
+            problems.push(format!("economy.sla_penalty_percent must be between 0 and 100, got {}", economy.sla_penalty_percent));
+        }
+        if economy.starting_cash < economy.bankruptcy_cash {
+            problems.push(format!("economy.starting_cash ({}) must not be below economy.bankruptcy_cash ({})",
+                                  economy.starting_cash, economy.bankruptcy_cash));
+        }
+
+        let knowledge = &self.knowledge;
+        if knowledge.incident_hours <= 0.0 {
+            problems.push(format!("knowledge.incident_hours must be positive, got {}", knowledge.incident_hours));
+        }
+        if knowledge.min_similarity <= 0.0 || knowledge.min_similarity > 1.0 {
+            problems.push(format!("knowledge.min_similarity must be above 0 and at most 1, got {}", knowledge.min_similarity));
+        }
+        for (name, effort) in [("known_fix_effort", knowledge.known_fix_effort), ("runbook_effort", knowledge.runbook_effort)] {
+            if !(0.0..=1.0).contains(&effort) {
+                problems.push(format!("knowledge.{} must be between 0 and 1, got {}", name, effort));
+            }
+        }
+        if knowledge.runbook_after_uses == 0 {
+            problems.push("knowledge.runbook_after_uses must be at least 1".to_string());
+        }
+
+        let mut rule_names = std::collections::HashSet::new();
+        for rule in &self.alerting.rules {
+            if !rule_names.insert(rule.name.as_str()) {
+                problems.push(format!("alerting.rules has '{}' more than once", rule.name));
+            }
+            if rule.for_checks == 0 {
+                problems.push(format!("alerting rule '{}' needs for_checks of at least 1", rule.name));
+            }
+        }
+        for silence in &self.alerting.silences {
+            if silence.until <= silence.from {
+                problems.push(format!("alerting silence from {} must end after it starts, not at {}", silence.from, silence.until));
+            }
+        }
+
+        let infrastructure = &self.infrastructure;
+        let mut hostnames = std::collections::HashSet::new();
+        for server in &infrastructure.servers {
+            if !hostnames.insert(server.hostname.as_str()) {
+                problems.push(format!("infrastructure.servers has '{}' more than once", server.hostname));
+            }
+        }
+        let mut cluster_names = std::collections::HashSet::new();
+        for cluster in &infrastructure.clusters {
+            if !cluster_names.insert(cluster.name.as_str()) {
+                problems.push(format!("infrastructure.clusters has '{}' more than once", cluster.name));
+            }
+            if cluster.nodes == 0 || cluster.nodes > crate::cluster::MAX_NODES {
+                problems.push(format!("infrastructure cluster '{}' needs 1 to {} nodes, got {}", cluster.name, crate::cluster::MAX_NODES, cluster.nodes));
+            }
+        }
+        let mut load_balancer_names = std::collections::HashSet::new();
+        for load_balancer in &infrastructure.load_balancers {
+            if !load_balancer_names.insert(load_balancer.name.as_str()) {
+                problems.push(format!("infrastructure.load_balancers has '{}' more than once", load_balancer.name));
+            }
+            if load_balancer.backends.is_empty() {
+                problems.push(format!("infrastructure load balancer '{}' needs at least one backend", load_balancer.name));
+            }
+        }
+
+        let backups = &self.backups;
+        if backups.retention_days == 0 {
+            problems.push("backups.retention_days must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&backups.drill_failure_probability) {
+            problems.push(format!("backups.drill_failure_probability must be between 0 and 1, got {}", backups.drill_failure_probability));
+        }
+        if backups.restore_minutes_per_gb < 0.0 {
+            problems.push(format!("backups.restore_minutes_per_gb must not be negative, got {}", backups.restore_minutes_per_gb));
+        }
+
+        let scanning = &self.scanning;
+        if scanning.catalog_size == 0 {
+            problems.push("scanning.catalog_size must be at least 1".to_string());
+        }
+        let weights = &scanning.severity_weights;
+        let weights = [weights.critical, weights.high, weights.medium, weights.low, weights.info];
+        if weights.iter().any(|weight| *weight < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+            problems.push(format!("scanning.severity_weights must not be negative and must not all be 0, got {:?}", weights));
+        }
+        if !(0.0..=1.0).contains(&scanning.exposure_probability) {
+            problems.push(format!("scanning.exposure_probability must be between 0 and 1, got {}", scanning.exposure_probability));
+        }
+        if !(0.0..=1.0).contains(&scanning.verification_failure_probability) {
+            problems.push(format!("scanning.verification_failure_probability must be between 0 and 1, got {}", scanning.verification_failure_probability));
+        }
+
+        if self.red_team.enabled && self.red_team.every_days == 0 {
+            problems.push("red_team.every_days must be at least 1 when the red team is enabled".to_string());
+        }
+
+        let iam = &self.iam;
+        if !(0.0..=1.0).contains(&iam.request_probability) {
+            problems.push(format!("iam.request_probability must be between 0 and 1, got {}", iam.request_probability));
+        }
+        if !(0.0..=1.0).contains(&iam.mfa_enrollment_probability) {
+            problems.push(format!("iam.mfa_enrollment_probability must be between 0 and 1, got {}", iam.mfa_enrollment_probability));
+        }
+        if iam.enabled && (iam.grant_days == 0 || iam.review_every_days == 0) {
+            problems.push("iam.grant_days and iam.review_every_days must be at least 1 when IAM is enabled".to_string());
+        }
+
+        let phishing = &self.phishing;
+        for (name, value) in [
+            ("base_click_probability", phishing.base_click_probability),
+            ("initial_awareness", phishing.initial_awareness),
+            ("training_gain", phishing.training_gain),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("phishing.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if phishing.enabled && phishing.every_days == 0 {
+            problems.push("phishing.every_days must be at least 1 when phishing campaigns are enabled".to_string());
+        }
+
+        let threat_intel = &self.threat_intel;
+        for (name, value) in [
+            ("malicious_probability", threat_intel.malicious_probability),
+            ("detection_boost", threat_intel.detection_boost),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("threat_intel.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if threat_intel.enabled && threat_intel.advisory_days == 0 {
+            problems.push("threat_intel.advisory_days must be at least 1 when threat intelligence is enabled".to_string());
+        }
+
+        let dlp = &self.dlp;
+        if !(0.0..=1.0).contains(&dlp.misroute_probability) {
+            problems.push(format!("dlp.misroute_probability must be between 0 and 1, got {}", dlp.misroute_probability));
+        }
+        let mut store_names = std::collections::HashSet::new();
+        for store in &dlp.stores {
+            if !store_names.insert(&store.name) {
+                problems.push(format!("dlp.stores has '{}' more than once", store.name));
+            }
+            if store.security_level < store.classification.minimum_level() {
+                problems.push(format!("dlp store '{}' holds {} data in a {:?} segment", store.name, store.classification.as_str(), store.security_level));
+            }
+        }
+
+        if self.forensics.incident_hours < 0.0 {
+            problems.push(format!("forensics.incident_hours must not be negative, got {}", self.forensics.incident_hours));
+        }
+
+        let traffic = &self.traffic;
+        if traffic.customer_mbps < 0.0 || traffic.project_mbps < 0.0 {
+            problems.push(format!("traffic.customer_mbps and traffic.project_mbps must not be negative, got {} and {}", traffic.customer_mbps, traffic.project_mbps));
+        }
+        if traffic.segment_capacity_mbps == 0 {
+            problems.push("traffic.segment_capacity_mbps must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&traffic.variability) {
+            problems.push(format!("traffic.variability must be between 0 and 1, got {}", traffic.variability));
+        }
+        if traffic.upgrade_utilization <= 0.0 {
+            problems.push(format!("traffic.upgrade_utilization must be positive, got {}", traffic.upgrade_utilization));
+        }
+        if self.load_balancing.requests_per_second < 0.0 {
+            problems.push(format!("load_balancing.requests_per_second must not be negative, got {}", self.load_balancing.requests_per_second));
+        }
+
+        let certificates = &self.certificates;
+        if certificates.lifetime_days == 0 {
+            problems.push("certificates.lifetime_days must be at least 1".to_string());
+        }
+        if certificates.renew_before_days >= certificates.lifetime_days || certificates.warn_before_days >= certificates.lifetime_days {
+            problems.push(format!(
+                "certificates.renew_before_days and certificates.warn_before_days must be below certificates.lifetime_days ({}), got {} and {}",
+                certificates.lifetime_days, certificates.renew_before_days, certificates.warn_before_days
+            ));
+        }
+        for (name, value) in [("renewal_failure_probability", certificates.renewal_failure_probability), ("manual_share", certificates.manual_share)] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("certificates.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+
+        let wan = &self.wan;
+        for link in &wan.links {
+            if link.from == link.to || !wan.sites.contains(&link.from) || !wan.sites.contains(&link.to) {
+                problems.push(format!("wan.links must join two different sites from wan.sites, got {} and {}", link.from, link.to));
+            }
+            if link.latency_ms < 0.0 {
+                problems.push(format!("wan.links latency_ms must not be negative, got {} between {} and {}", link.latency_ms, link.from, link.to));
+            }
+            if !(0.0..=1.0).contains(&link.failure_probability) {
+                problems.push(format!("wan.links failure_probability must be between 0 and 1, got {} between {} and {}", link.failure_probability, link.from, link.to));
+            }
+        }
+        for (service, sites) in &wan.services {
+            if let Some(unknown) = sites.iter().find(|site| !wan.sites.contains(site)) {
+                problems.push(format!("wan.services.{} runs in unknown site {}", service, unknown));
+            }
+        }
+
+        let ddos = &self.ddos;
+        if ddos.attacks_per_month < 0.0 {
+            problems.push(format!("ddos.attacks_per_month must not be negative, got {}", ddos.attacks_per_month));
+        }
+        if ddos.peak_multiplier <= 1.0 {
+            problems.push(format!("ddos.peak_multiplier must be above 1, got {}", ddos.peak_multiplier));
+        }
+        if ddos.max_duration_hours == 0 {
+            problems.push("ddos.max_duration_hours must be at least 1".to_string());
+        }
+        if ddos.rate_limit_minutes > ddos.scrubbing_minutes || ddos.scrubbing_minutes > ddos.blackhole_minutes {
+            problems.push(format!(
+                "ddos mitigations must escalate: rate_limit_minutes ({}) <= scrubbing_minutes ({}) <= blackhole_minutes ({})",
+                ddos.rate_limit_minutes, ddos.scrubbing_minutes, ddos.blackhole_minutes
+            ));
+        }
+
+        let tickets = &self.tickets;
+        if tickets.hours_per_complexity <= 0.0 {
+            problems.push(format!("tickets.hours_per_complexity must be above 0, got {}", tickets.hours_per_complexity));
+        }
+        for (name, value) in [("pending_customer_chance", tickets.pending_customer_chance), ("reopen_chance", tickets.reopen_chance), ("survey_response_rate", tickets.survey_response_rate)] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("tickets.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if tickets.customer_reply_hours.0 > tickets.customer_reply_hours.1 {
+            problems.push(format!("tickets.customer_reply_hours must be (min, max), got {:?}", tickets.customer_reply_hours));
+        }
+
+        let queue = &self.ticket_queue;
+        if queue.max_open_per_agent == 0 {
+            problems.push("ticket_queue.max_open_per_agent must be at least 1".to_string());
+        }
+        for route in &queue.routes {
+            if route.tag.is_none() && route.min_priority.is_none() {
+                problems.push(format!("ticket_queue.routes need a tag or a min_priority, got a route for skill {}", route.min_skill));
+            }
+            if route.min_skill > 100 {
+                problems.push(format!("ticket_queue.routes min_skill must be at most 100, got {}", route.min_skill));
+            }
+        }
+
+        let changes = &self.changes;
+        if changes.failure_chances.iter().any(|chance| !(0.0..=1.0).contains(chance)) {
+            problems.push(format!("changes.failure_chances must be between 0 and 1, got {:?}", changes.failure_chances));
+        }
+        if changes.duration_minutes.0 > changes.duration_minutes.1 {
+            problems.push(format!("changes.duration_minutes must be (min, max), got {:?}", changes.duration_minutes));
+        }
+        if !(0.0..=1.0).contains(&changes.rollback_success_rate) {
+            problems.push(format!("changes.rollback_success_rate must be between 0 and 1, got {}", changes.rollback_success_rate));
+        }
+
+        let capacity = &self.capacity;
+        if capacity.lookback_weeks == 0 || capacity.horizon_weeks == 0 {
+            problems.push(format!("capacity.lookback_weeks and horizon_weeks must be at least 1, got {} and {}", capacity.lookback_weeks, capacity.horizon_weeks));
+        }
+        if !(capacity.limit_percent > 0.0 && capacity.limit_percent <= 100.0) {
+            problems.push(format!("capacity.limit_percent must be between 0 and 100, got {}", capacity.limit_percent));
+        }
+
+        for tier in &self.status_page.credit_tiers {
+            if !(0.0..=100.0).contains(&tier.below_uptime) || !(0.0..=100.0).contains(&tier.credit_percent) {
+                problems.push(format!("status_page.credit_tiers must be percentages between 0 and 100, got {} and {}", tier.below_uptime, tier.credit_percent));
+            }
+        }
+
+        let runbooks = &self.runbooks;
+        if runbooks.step_minutes == 0 {
+            problems.push("runbooks.step_minutes must be at least 1".to_string());
+        }
+        for runbook in &runbooks.runbooks {
+            if runbook.keywords.is_empty() || runbook.steps.is_empty() {
+                problems.push(format!("runbook '{}' needs keywords and steps", runbook.name));
+            }
+            if runbook.steps.iter().any(|step| !(0.0..=1.0).contains(&step.success_chance)) {
+                problems.push(format!("runbook '{}' step success_chance must be between 0 and 1", runbook.name));
+            }
+        }
+
+        let shifts = &self.shifts;
+        for shift in &shifts.shifts {
+            if shift.start_hour >= 24 || shift.end_hour > 24 || shift.start_hour == shift.end_hour {
+                problems.push(format!("shift '{}' must start and end on different hours of the day, got ({}, {})", shift.name, shift.start_hour, shift.end_hour));
+            }
+        }
+        if shifts.unstaffed_slowdown < 1.0 {
+            problems.push(format!("shifts.unstaffed_slowdown must be at least 1, got {}", shifts.unstaffed_slowdown));
+        }
+
+        let agent_states = &self.agent_states;
+        if !(0.0..=1.0).contains(&agent_states.leave_chance) {
+            problems.push(format!("agent_states.leave_chance must be between 0 and 1, got {}", agent_states.leave_chance));
+        }
+        if agent_states.leave_days == 0 || agent_states.blocked_after_minutes <= 0 {
+            problems.push(format!("agent_states.leave_days and blocked_after_minutes must be at least 1, got {} and {}", agent_states.leave_days, agent_states.blocked_after_minutes));
+        }
+
+        let memory = &self.memory;
+        for (name, value) in [("prefer_known_chance", memory.prefer_known_chance), ("recall_similarity", memory.recall_similarity), ("recall_effort", memory.recall_effort)] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("memory.{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if memory.max_interactions == 0 || memory.max_tasks == 0 {
+            problems.push(format!("memory.max_interactions and max_tasks must be at least 1, got {} and {}", memory.max_interactions, memory.max_tasks));
+        }
+
+        let patching = &self.patching;
+        if !(0.0..=1.0).contains(&patching.release_probability) {
+            problems.push(format!("patching.release_probability must be between 0 and 1, got {}", patching.release_probability));
+        }
+        if patching.max_servers_per_window == 0 {
+            problems.push("patching.max_servers_per_window must be at least 1".to_string());
+        }
+
+        let autoscaling = &self.autoscaling;
+        if !(0.0..=100.0).contains(&autoscaling.scale_down_percent) || autoscaling.scale_down_percent >= autoscaling.scale_up_percent {
+            problems.push(format!("autoscaling.scale_down_percent must be between 0 and scale_up_percent ({}), got {}",
+                                  autoscaling.scale_up_percent, autoscaling.scale_down_percent));
+        }
+        if autoscaling.scale_down_after_checks == 0 {
+            problems.push("autoscaling.scale_down_after_checks must be at least 1".to_string());
+        }
+        let defaults = ("autoscaling".to_string(), FleetLimits { min_servers: autoscaling.min_servers, max_servers: autoscaling.max_servers });
+        for (name, limits) in std::iter::once(defaults).chain(autoscaling.fleets.iter().map(|(fleet, limits)| (format!("autoscaling.fleets.{}", fleet), *limits))) {
+            if limits.max_servers == 0 || limits.min_servers > limits.max_servers {
+                problems.push(format!("{}.min_servers ({}) must not exceed max_servers ({}), which must be at least 1", name, limits.min_servers, limits.max_servers));
+            }
+        }
+
+        let pipelines = &self.pipelines;
+        if pipelines.max_concurrent_builds == 0 {
+            problems.push("pipelines.max_concurrent_builds must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&pipelines.flaky_test_probability) {
+            problems.push(format!("pipelines.flaky_test_probability must be between 0 and 1, got {}", pipelines.flaky_test_probability));
+        }
+
+        if let DeploymentStrategy::Canary { traffic_percent, .. } = &self.deployments.strategy {
+            if traffic_percent.is_empty() {
+                problems.push("deployments.strategy.traffic_percent must list at least one stage".to_string());
+            }
+            if traffic_percent.windows(2).any(|pair| pair[0] >= pair[1]) || traffic_percent.iter().any(|percent| *percent == 0 || *percent > 100) {
+                problems.push(format!("deployments.strategy.traffic_percent must rise from above 0 to at most 100, got {:?}", traffic_percent));
+            }
+        }
+        let mut environments = std::collections::HashSet::new();
+        for environment in &self.deployments.environments {
+            if environment.name.trim().is_empty() {
+                problems.push("deployments.environments must all have a name".to_string());
+            } else if !environments.insert(environment.name.as_str()) {
+                problems.push(format!("deployments.environments lists '{}' more than once", environment.name));
+            }
+        }
+
+        let progression = &self.progression;
+        if progression.experience_per_task < 0.0 {
+            problems.push(format!("progression.experience_per_task must not be negative, got {}", progression.experience_per_task));
+        }
+        if progression.experience_per_point <= 0.0 {
+            problems.push(format!("progression.experience_per_point must be positive, got {}", progression.experience_per_point));
+        }
+        if progression.decay_after_days.is_some_and(|days| days < 1) {
+            problems.push("progression.decay_after_days must be at least 1".to_string());
+        }
+        if !(1..=100).contains(&progression.decay_floor) {
+            problems.push(format!("progression.decay_floor must be between 1 and 100, got {}", progression.decay_floor));
+        }
+
+        let decisions = &self.decisions;
+        let policies = std::iter::once(("default".to_string(), &decisions.default))
+            .chain(decisions.departments.iter().map(|(department, policy)| (format!("departments.{}", department), policy)))
+            .chain(decisions.agents.iter().map(|(agent, policy)| (format!("agents.{}", agent), policy)));
+        for (name, policy) in policies {
+            match policy {
+                PolicySettings::RuleBased { daily_tasks_every: 0, .. } => {
+                    problems.push(format!("decisions.{}.daily_tasks_every must be at least 1", name));
+                }
+                PolicySettings::Scripted { turns } if turns.is_empty() => {
+                    problems.push(format!("decisions.{}.turns must not be empty", name));
+                }
+                _ => {}
+            }
+        }
+
+        let personalities = &self.personalities;
+        let personalities = std::iter::once(("default".to_string(), &personalities.default))
+            .chain(personalities.departments.iter().map(|(department, personality)| (format!("departments.{}", department), personality)))
+            .chain(personalities.agents.iter().map(|(agent, personality)| (format!("agents.{}", agent), personality)));
+        for (name, personality) in personalities {
+            for (trait_name, value) in personality.traits() {
+                if !(0.0..=1.0).contains(&value) {
+                    problems.push(format!("personalities.{}.{} must be between 0.0 and 1.0, got {}", name, trait_name, value));
+                }
+            }
+        }
+
+        let scripting = &self.scripting;
+        for (department, scripted) in &scripting.departments {
+            if crate::agents::Department::named(department).is_none() {
+                problems.push(format!("scripting.departments.{} is not a department", department));
+            }
+            if scripted.script.as_os_str().is_empty() {
+                problems.push(format!("scripting.departments.{}.script must not be empty", department));
+            }
+        }
+        if scripting.max_operations == 0 {
+            problems.push("scripting.max_operations must be at least 1".to_string());
+        }
+
+        let intake = &self.intake;
+        if intake.recovery_backlog >= intake.backlog_threshold {
+            problems.push(format!("intake.recovery_backlog ({}) must be below intake.backlog_threshold ({})",
+                                  intake.recovery_backlog, intake.backlog_threshold));
+        }
+        if intake.sev1_storm_threshold == 0 {
+            problems.push("intake.sev1_storm_threshold must be at least 1".to_string());
+        }
+        if intake.retry_after_minutes < 1 {
+            problems.push(format!("intake.retry_after_minutes must be at least 1, got {}", intake.retry_after_minutes));
+        }
+
+        if self.deadlocks.max_wait_minutes < 1 {
+            problems.push(format!("deadlocks.max_wait_minutes must be at least 1, got {}", self.deadlocks.max_wait_minutes));
+        }
+
+        if let Some(pack) = &self.reports.template_pack {
+            if !pack.join(crate::templates::PACK_MANIFEST).is_file() {
+                problems.push(format!("reports.template_pack '{}' has no {}", pack.display(), crate::templates::PACK_MANIFEST));
+            }
+        }
+
+        if self.synthetic.days == 0 {
+            problems.push("synthetic.days must be at least 1".to_string());
+        }
+
+        // Company events share a single roll per step; infrastructure faults are scheduled separately
+        let company_events = probabilities.new_project + probabilities.security_incident
+            + probabilities.customer_request;
+        if company_events > 1.0 {
+            problems.push(format!("company event probabilities sum to {:.2}, must not exceed 1.0", company_events));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+/// Parse a duration such as "90s", "45m", "2h" or "1d" into seconds
+pub fn parse_duration(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let invalid = || format!("expected a duration like 90s, 45m, 2h or 1d, got '{}'", value);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    amount.checked_mul(unit_seconds).ok_or_else(invalid)
+}
+
+/// Whether `addr` (`host:port`) only accepts connections from this machine
+fn is_loopback(addr: &str) -> bool {
+    if let Ok(socket) = addr.parse::<std::net::SocketAddr>() {
+        return socket.ip().is_loopback();
+    }
+    addr.rsplit_once(':').is_some_and(|(host, _)| host == "localhost")
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.parse()
+        .map_err(|_| ConfigError::Env(key.to_string(), format!("cannot parse '{}'", value)))
+}
+
+/// Configuration-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Failed to parse config file {0}: {1}")]
+    Parse(PathBuf, String),
+
+    #[error("Invalid environment override {0}: {1}")]
+    Env(String, String),
+
+    #[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(SimulationConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_partial_toml_uses_defaults() {
+        let config: SimulationConfig = toml::from_str(r#"
+            speed_multiplier = 10.0
+            seed = 7
+
+            [department_sizes]
+            ops = 5
+
+            [event_probabilities]
+            security_incident = 0.2
+
+            [[webhooks.subscriptions]]
+            url = "http://localhost:9000/hooks"
+            events = ["incident.declared", "deployment.failed"]
+            filters = { severity = ["Sev1"] }
+
+            [decisions.departments.Ops]
+            kind = "rule_based"
+
+            [decisions.agents."Sarah Chen"]
+            kind = "scripted"
+            turns = [{ activity = true }, {}]
+
+            [personalities.departments.Ops]
+            response_latency = 0.9
+
+            [scripting.departments.Sales]
+            script = "scripts/sales.rhai"
+        "#).unwrap();
+
+        assert_eq!(config.speed_multiplier, 10.0);
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(config.department_sizes.ops, 5);
+        assert_eq!(config.department_sizes.devops, 3);
+        assert_eq!(config.event_probabilities.security_incident, 0.2);
+        assert_eq!(config.working_hours, (9, 18));
+        assert_eq!(config.webhooks.subscriptions[0].events, vec![EntityEvent::IncidentDeclared, EntityEvent::DeploymentFailed]);
+        assert_eq!(config.webhooks.max_retries, 5);
+        assert!(matches!(config.decisions.policy_for("Ops Lead", "Ops"), PolicySettings::RuleBased { busy_backlog: 3, daily_tasks_every: 10 }));
+        assert!(matches!(config.decisions.policy_for("Sarah Chen", "Engineering"), PolicySettings::Scripted { turns } if turns.len() == 2));
+        assert!(matches!(config.decisions.policy_for("Alex Kim", "DevOps"), PolicySettings::Random));
+        assert_eq!(config.personalities.personality_for("Ops Lead", "Ops").response_latency, 0.9);
+        assert_eq!(config.personalities.personality_for("Ops Lead", "Ops").thoroughness, 0.5);
+        assert_eq!(config.scripting.departments["Sales"].script, PathBuf::from("scripts/sales.rhai"));
+        assert_eq!(config.scripting.max_operations, 100_000);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env = HashMap::from([
+            ("AIVERTCO_SPEED", "4.5"),
+            ("AIVERTCO_WORKING_HOURS", "0-24"),
+            ("AIVERTCO_MAX_STEPS", "100"),
+            ("AIVERTCO_DURATION", "2h"),
+        ]);
+        let mut config = SimulationConfig::default();
+        config.apply_env_overrides(|key| env.get(key).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(config.speed_multiplier, 4.5);
+        assert_eq!(config.working_hours, (0, 24));
+        assert_eq!(config.max_steps, Some(100));
+        assert_eq!(config.max_wall_seconds, Some(7200));
+
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("1d"), Ok(86_400));
+        assert!(parse_duration("2 hours").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_validation_reports_all_problems() {
+        let mut config = SimulationConfig::default();
+        config.speed_multiplier = 0.0;
+        config.working_hours = (18, 9);
+        config.event_probabilities.new_project = 0.9;
+        config.event_probabilities.customer_request = 0.9;
+
+        match config.validate() {
+            Err(ConfigError::Invalid(problems)) => assert_eq!(problems.len(), 3),
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_control_api_off_loopback_needs_a_token() {
+        let mut config = SimulationConfig::default();
+        config.api.addr = Some("127.0.0.1:8088".to_string());
+        assert!(config.validate().is_ok());
+
+        config.api.addr = Some("0.0.0.0:8088".to_string());
+        assert!(config.validate().is_err());
+        config.api.token_env = Some("AIVERTCO_API_TOKEN".to_string());
+        assert!(config.validate().is_ok());
+    }
+}
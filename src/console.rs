@@ -0,0 +1,151 @@
+//! Console Controls - Runtime Commands from the Terminal
+//!
+//! This module implements steering a running simulation by typing commands
+//! into the terminal it runs in:
+//! - `pause` and `resume`
+//! - `step [n]`: run exactly n orchestrator steps (one by default), then
+//!   stay paused, for following agent interactions step by step
+//! - `speed <multiplier>` to change the pace of the simulated clock
+//! - `status`, `agents` for every agent's workload, state and latest activity,
+//!   and `help`
+//! - `threads` for the most recently active conversation threads, and
+//!   `thread <id>` for every message of one
+//!
+//! The same controls are on the control API (`api` feature). Lines are read
+//! on a thread of their own and answered by the orchestrator between events.
+
+use std::io::{BufRead, IsTerminal};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+use uuid::Uuid;
+
+/// Commands typed before the orchestrator gets to them
+const COMMAND_QUEUE: usize = 16;
+
+const HELP: &str = "⌨️ Commands: pause (p) | resume (r) | step [n] (s) | speed <multiplier> | status | agents | threads | thread <id> | help";
+
+/// Change to how the simulation runs, from the console or the control API
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    Pause,
+    Resume,
+    /// Run this many orchestrator steps, then pause
+    Step(u32),
+    SetSpeed(f32),
+    Status,
+    /// Print every agent's status report
+    Agents,
+    /// List the most recently active conversation threads
+    Threads,
+    /// Print every message of a conversation thread
+    Thread(Uuid),
+}
+
+/// Parse a console line; `Ok(None)` for lines that need no answer from the orchestrator
+pub fn parse(line: &str) -> Result<Option<Control>, String> {
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return Ok(None);
+    };
+    let argument = words.next();
+    let control = match command.to_lowercase().as_str() {
+        "pause" | "p" => Control::Pause,
+        "resume" | "r" => Control::Resume,
+        "step" | "s" => match argument {
+            None => Control::Step(1),
+            Some(count) => match count.parse() {
+                Ok(count) if count > 0 => Control::Step(count),
+                _ => return Err(format!("step takes a positive number of steps, got '{}'", count)),
+            },
+        },
+        "speed" => match argument.map(str::parse::<f32>) {
+            Some(Ok(multiplier)) => Control::SetSpeed(multiplier),
+            _ => return Err("speed takes a multiplier, e.g. 'speed 10'".to_string()),
+        },
+        "status" => Control::Status,
+        "agents" => Control::Agents,
+        "threads" => Control::Threads,
+        "thread" => match argument.map(Uuid::parse_str) {
+            Some(Ok(thread_id)) => Control::Thread(thread_id),
+            _ => return Err("thread takes a thread id as listed by 'threads'".to_string()),
+        },
+        "help" | "?" => {
+            println!("{}", HELP);
+            return Ok(None);
+        }
+        other => return Err(format!("unknown command '{}'; type help", other)),
+    };
+    Ok(Some(control))
+}
+
+/// Orchestrator side of the console: the commands typed so far
+#[derive(Debug)]
+pub struct Console {
+    controls: mpsc::Receiver<Control>,
+    arrived: Arc<Notify>,
+}
+
+impl Console {
+    /// Read commands from stdin when it is a terminal; `None` for piped or detached runs
+    pub fn attach() -> Option<Self> {
+        let stdin = std::io::stdin();
+        if !stdin.is_terminal() {
+            return None;
+        }
+
+        let (sender, controls) = mpsc::channel(COMMAND_QUEUE);
+        let arrived = Arc::new(Notify::new());
+        let notify = arrived.clone();
+        // Blocking reads on a thread of their own, so they never hold up the runtime
+        std::thread::spawn(move || {
+            for line in stdin.lock().lines() {
+                let Ok(line) = line else { break };
+                match parse(&line) {
+                    Ok(Some(control)) => {
+                        if sender.blocking_send(control).is_err() {
+                            break;
+                        }
+                        notify.notify_one();
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!("❌ {}", e),
+                }
+            }
+        });
+        println!("{}", HELP);
+        Some(Self { controls, arrived })
+    }
+
+    /// Next command already typed, if any
+    pub fn try_next(&mut self) -> Option<Control> {
+        self.controls.try_recv().ok()
+    }
+
+    /// Resolve once a command has arrived since the last call
+    pub async fn arrived(&self) {
+        self.arrived.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse("pause"), Ok(Some(Control::Pause)));
+        assert_eq!(parse("  R "), Ok(Some(Control::Resume)));
+        assert_eq!(parse("step"), Ok(Some(Control::Step(1))));
+        assert_eq!(parse("s 5"), Ok(Some(Control::Step(5))));
+        assert_eq!(parse("speed 2.5"), Ok(Some(Control::SetSpeed(2.5))));
+        assert_eq!(parse("agents"), Ok(Some(Control::Agents)));
+        let thread_id = Uuid::new_v4();
+        assert_eq!(parse(&format!("thread {}", thread_id.simple())), Ok(Some(Control::Thread(thread_id))));
+        assert_eq!(parse(""), Ok(None));
+
+        assert!(parse("step 0").is_err());
+        assert!(parse("speed fast").is_err());
+        assert!(parse("thread").is_err());
+        assert!(parse("explode").is_err());
+    }
+}
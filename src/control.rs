@@ -0,0 +1,423 @@
+//! Simulation Control - Console and Control API Requests
+//!
+//! This module implements how a running simulation is steered from outside:
+//! - Starting the control API when `[api] addr` is set
+//! - Waiting for console commands and API requests between events
+//! - Pausing, resuming, stepping and changing speed
+//! - Answering API requests: staffing, injected work, reports and threads
+
+use super::*;
+
+impl CompanySimulation {
+    /// Start the control API when an address is configured
+    #[cfg(feature = "api")]
+    pub(crate) async fn start_api(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(addr) = &self.config.api.addr {
+            self.api = Some(api::ControlApi::serve(addr, self.config.api.token_env.as_deref()).await?);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "api"))]
+    pub(crate) async fn start_api(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.config.api.addr.is_some() {
+            println!("⚠️ api.addr is set but this build lacks the api feature; the control API is off");
+        }
+        Ok(())
+    }
+
+    /// Resolve once a console command or control request comes in
+    pub(crate) async fn control_arrived(&self) {
+        let console = async {
+            match &self.console {
+                Some(console) => console.arrived().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = console => {}
+            _ = self.api_request_arrived() => {}
+        }
+    }
+
+    /// Carry out every command typed into the console
+    pub(crate) async fn answer_console(&mut self) {
+        while let Some(control) = self.console.as_mut().and_then(Console::try_next) {
+            if control == Control::Agents {
+                match self.status_reports().await {
+                    Ok(reports) => reports.iter().for_each(|report| println!("📋 {}", report.summary())),
+                    Err(e) => println!("❌ {}", e),
+                }
+                continue;
+            }
+            if let Control::Thread(thread_id) = control {
+                match threads::history(&self.message_log, thread_id) {
+                    Some(thread) => print!("🧵 {}", thread.render(|id| self.actor_name(id))),
+                    None => println!("❌ No messages in thread {}", thread_id.simple()),
+                }
+                continue;
+            }
+            if control == Control::Threads {
+                for thread in threads::recent(&self.message_log, THREAD_LISTING) {
+                    println!("🧵 {} '{}': {} message(s), last at {}", thread.id.simple(), thread.subject, thread.messages,
+                             thread.last_at.format("%Y-%m-%d %H:%M"));
+                }
+                continue;
+            }
+            match self.apply_control(control, "the console") {
+                Ok(answer) if control == Control::Status => println!("📋 {}", answer),
+                Ok(_) => {}
+                Err(e) => println!("❌ {}", e),
+            }
+        }
+    }
+
+    /// Pause, resume, step or change speed; `Err` explains why it was refused
+    fn apply_control(&mut self, control: Control, via: &str) -> Result<serde_json::Value, String> {
+        match control {
+            Control::Pause => {
+                if !self.paused {
+                    println!("⏸️ Paused through {} at step {}", via, self.step_count);
+                }
+                self.paused = true;
+                self.pending_steps = 0;
+            }
+            Control::Resume => {
+                if self.paused {
+                    println!("▶️ Resumed through {} at step {}", via, self.step_count);
+                }
+                self.paused = false;
+                self.pending_steps = 0;
+            }
+            Control::Step(steps) => {
+                if steps == 0 {
+                    return Err("steps must be at least 1".to_string());
+                }
+                println!("⏭️ Running {} step(s) through {} from step {}", steps, via, self.step_count);
+                self.paused = true;
+                self.pending_steps = steps;
+            }
+            Control::SetSpeed(multiplier) => {
+                if !(multiplier > 0.0 && multiplier.is_finite()) {
+                    return Err(format!("speed multiplier must be positive, got {}", multiplier));
+                }
+                self.config.speed_multiplier = multiplier;
+                println!("⚙️  Simulation speed set to {:.1}x through {}", multiplier, via);
+            }
+            Control::Status | Control::Agents | Control::Threads | Control::Thread(_) => {}
+        }
+        Ok(serde_json::json!({
+            "paused": self.paused,
+            "pending_steps": self.pending_steps,
+            "step": self.step_count,
+            "speed_multiplier": self.config.speed_multiplier,
+            "simulated_time": self.scheduler.now(),
+        }))
+    }
+
+    /// Resolve once a control request comes in
+    #[cfg(feature = "api")]
+    async fn api_request_arrived(&self) {
+        match &self.api {
+            Some(api) => api.arrived().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    #[cfg(not(feature = "api"))]
+    async fn api_request_arrived(&self) {
+        std::future::pending().await
+    }
+
+    /// Answer every control request waiting
+    #[cfg(feature = "api")]
+    pub(crate) async fn answer_api_requests(&mut self) {
+        while let Some(request) = self.api.as_mut().and_then(|api| api.try_next()) {
+            let answer = self.answer_api_command(request.command).await;
+            // The client may have hung up
+            let _ = request.reply.send(answer);
+        }
+    }
+
+    #[cfg(not(feature = "api"))]
+    pub(crate) async fn answer_api_requests(&mut self) {}
+
+    /// Carry out a control request; `Err` explains why it was refused
+    #[cfg(feature = "api")]
+    async fn answer_api_command(&mut self, command: api::ApiCommand) -> Result<serde_json::Value, String> {
+        match command {
+            api::ApiCommand::Pause => self.apply_control(Control::Pause, "the control API"),
+            api::ApiCommand::Resume => self.apply_control(Control::Resume, "the control API"),
+            api::ApiCommand::Step(steps) => self.apply_control(Control::Step(steps), "the control API"),
+            api::ApiCommand::SetSpeed(multiplier) => self.apply_control(Control::SetSpeed(multiplier), "the control API"),
+            api::ApiCommand::Status => self.apply_control(Control::Status, "the control API"),
+            api::ApiCommand::ListAgents => {
+                let agents: Vec<serde_json::Value> = self.agents.infos()
+                    .map(|agent| serde_json::json!({
+                        "id": agent.id,
+                        "name": agent.name,
+                        "department": agent.department.as_str(),
+                        "manager_id": agent.manager_id,
+                    }))
+                    .collect();
+                Ok(serde_json::json!({ "agents": agents }))
+            }
+            api::ApiCommand::AgentStatus(agent) => match self.status_report(&agent).await {
+                Some(report) => serde_json::to_value(report).map_err(|e| e.to_string()),
+                None => Err(format!("no agent named or with id '{}'", agent)),
+            },
+            api::ApiCommand::Hire(request) => {
+                let agent_id = self.hire(&request.department, request.name, "the control API").await.map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "agent_id": agent_id }))
+            }
+            api::ApiCommand::Offboard(agent) => {
+                let successor = self.offboard(&agent, "the control API").await.map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "offboarded": agent, "work_to": successor }))
+            }
+            api::ApiCommand::InjectIncident(request) => {
+                let Some(assignment) = self.assign_work(WorkKind::Incident, chrono::Duration::hours(4)).await else {
+                    return Err("no Ops agent to take the incident".to_string());
+                };
+                println!("🎛️ Incident injected through the control API: {}", request.title);
+                let payload = MessagePayload::IncidentDeclared {
+                    title: request.title,
+                    severity: request.severity,
+                    affected_services: request.affected_services,
+                    affected_customers: vec![],
+                };
+                let message = payload.into_message(SystemActor::Operator.id(), assignment.agent_id, request.description, MessagePriority::High);
+                let message_id = message.id;
+                self.pending_messages.push(message);
+                Ok(serde_json::json!({ "message_id": message_id, "assigned_to": assignment.agent_id }))
+            }
+            api::ApiCommand::InjectTicket(request) => {
+                let customer_tier = request.customer_id.as_deref()
+                    .and_then(|id| self.catalog.customers.get(id))
+                    .map_or(CustomerTier::Standard, |customer| customer.tier);
+                let ticket = TicketRequest {
+                    title: request.title,
+                    description: request.description,
+                    priority: request.priority,
+                    customer_id: request.customer_id,
+                    customer_tier,
+                    tags: request.tags,
+                };
+                if self.config.ticket_queue.enabled {
+                    println!("🎛️ Ticket injected through the control API: {}", ticket.title);
+                    let queue_id = self.ticket_queue.enqueue(ticket, SystemActor::Operator.id(), self.scheduler.now());
+                    return Ok(serde_json::json!({ "queued": queue_id, "queue_depth": self.ticket_queue.depth() }));
+                }
+                let Some(assignment) = self.assign_work(WorkKind::Ticket, chrono::Duration::hours(2)).await else {
+                    return Err("no Ops agent to take the ticket".to_string());
+                };
+                println!("🎛️ Ticket injected through the control API: {}", ticket.title);
+                let message = ticket_message(SystemActor::Operator.id(), assignment.agent_id, ticket);
+                let message_id = message.id;
+                self.pending_messages.push(message);
+                Ok(serde_json::json!({ "message_id": message_id, "assigned_to": assignment.agent_id }))
+            }
+            api::ApiCommand::InjectProject => {
+                let project_id = Uuid::new_v4();
+                println!("🎛️ Project injected through the control API: {}", project_id.simple());
+                self.plan_project(project_id).map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "project_id": project_id, "tasks": self.task_graph.project_tasks(project_id).count() }))
+            }
+            api::ApiCommand::Report(name) => self.api_report(&name).await,
+            api::ApiCommand::DeploymentStatus(id) => self.deployment_status(&id).await,
+            api::ApiCommand::ListThreads => {
+                Ok(serde_json::json!({ "threads": threads::recent(&self.message_log, THREAD_LISTING) }))
+            }
+            api::ApiCommand::ThreadHistory(id) => {
+                let thread_id = Uuid::parse_str(&id).map_err(|_| format!("'{}' is not a thread id", id))?;
+                let thread = threads::history(&self.message_log, thread_id).ok_or_else(|| format!("no messages in thread {}", id))?;
+                serde_json::to_value(thread).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// Report served on `/reports/<name>`
+    #[cfg(feature = "api")]
+    async fn api_report(&self, name: &str) -> Result<serde_json::Value, String> {
+        let now = self.scheduler.now();
+        match name {
+            "summary" => {
+                let snapshot = self.snapshot().await.map_err(|e| e.to_string())?;
+                let report = daemon::SummaryReport::from_snapshot(&snapshot, now - chrono::Duration::days(7), 0);
+                serde_json::to_value(report).map_err(|e| e.to_string())
+            }
+            "insights" => serde_json::to_value(self.kpi_history.insights(now, ReportPeriod::Week)).map_err(|e| e.to_string()),
+            "risks" => Ok(serde_json::json!({
+                "open": self.risks.open().collect::<Vec<_>>(),
+                "latest_review": self.risks.latest_review(),
+            })),
+            "doctor" => {
+                let snapshot = self.snapshot().await.map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "diagnoses": doctor::diagnose(&snapshot) }))
+            }
+            "org" => Ok(serde_json::json!({
+                "chart": self.org.render(),
+                "company": self.org.company_rollup(),
+                "approvals": self.org.approvals(),
+                "escalations": self.org.escalations(),
+            })),
+            "executive" => Ok(serde_json::json!({
+                "quarter": self.executive.quarter,
+                "goals": self.executive.goals,
+                "allocations": self.executive.allocations,
+                "hiring_freeze": self.executive.hiring_freeze,
+                "latest_review": self.executive.latest_review(),
+                "department_reports": self.executive.department_reports,
+            })),
+            "staffing" => {
+                let (hires, exits) = self.staffing.totals();
+                Ok(serde_json::json!({ "hires": hires, "exits": exits, "recent": self.staffing.events() }))
+            }
+            "economy" => Ok(serde_json::json!({
+                "cash": self.economy.cash,
+                "bankrupt_at": self.economy.bankrupt_at,
+                "months": self.economy.statements(),
+            })),
+            "knowledge" => {
+                let (lookups, hits) = self.knowledge.stats();
+                let runbooks: BTreeMap<&String, serde_json::Value> = self.knowledge.runbook_stats().iter()
+                    .map(|(name, stats)| (name, serde_json::json!({
+                        "stats": stats,
+                        "success_rate": stats.success_rate(),
+                        "mean_minutes_to_resolve": stats.mean_minutes_to_resolve(),
+                    })))
+                    .collect();
+                Ok(serde_json::json!({ "lookups": lookups, "hits": hits, "articles": self.knowledge.articles(), "runbooks": runbooks }))
+            }
+            "alerts" => Ok(serde_json::json!({
+                "active": self.alerts.active(),
+                "resolved": self.alerts.resolved(),
+                "silenced": self.alerts.silenced(),
+            })),
+            "pipelines" => {
+                let (queued, running) = self.pipelines.load();
+                Ok(serde_json::json!({ "queued": queued, "running": running, "pipelines": self.pipelines.pipelines() }))
+            }
+            "environments" => Ok(serde_json::json!({
+                "versions": self.promotions.versions(),
+                "promotions": self.promotions.promotions(),
+            })),
+            "red_team" => Ok(serde_json::json!({
+                "report": self.red_team.report(),
+                "campaigns": self.red_team.campaigns(),
+            })),
+            "iam" => {
+                let active: Vec<Uuid> = self.agents.infos().map(|info| info.id).collect();
+                Ok(serde_json::json!({
+                    "findings": self.iam.findings(&active, self.scheduler.now()),
+                    "accounts": self.iam.accounts().collect::<Vec<_>>(),
+                    "requests": self.iam.requests(),
+                    "reviews": self.iam.reviews(),
+                }))
+            }
+            "phishing" => Ok(serde_json::json!({
+                "report": self.phishing.report(),
+                "people": self.phishing.people().collect::<Vec<_>>(),
+                "campaigns": self.phishing.campaigns(),
+                "trainings": self.phishing.trainings(),
+            })),
+            "dlp" => Ok(serde_json::json!({
+                "report": self.dlp.report(),
+                "stores": self.config.dlp.stores,
+                "exposures": self.dlp.exposures(),
+            })),
+            "threat_intel" => Ok(serde_json::json!({
+                "indicators": self.threat_intel.indicators().collect::<Vec<_>>(),
+                "advisories": self.threat_intel.advisories(),
+                "sightings": self.threat_intel.sightings(),
+            })),
+            "traffic" => {
+                let reports = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.traffic.clone())).await.map_err(|e| e.to_string())?;
+                serde_json::to_value(reports.into_iter().flatten().next().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+            "topology" => {
+                let graphs = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.topology_graph())).await.map_err(|e| e.to_string())?;
+                let graph = graphs.into_iter().flatten().next().unwrap_or_default();
+                Ok(serde_json::json!({ "partitions": graph.partitions(), "dot": graph.to_dot() }))
+            }
+            "dns" => {
+                let zones = self.agents.call_all(|agent| agent.as_networking().map(|networking| (networking.network_topology.dns_config.zone.clone(), networking.export_zone()))).await.map_err(|e| e.to_string())?;
+                let (zone, bind) = zones.into_iter().flatten().next().unwrap_or_default();
+                Ok(serde_json::json!({ "zone": zone, "bind": bind }))
+            }
+            "certificates" => {
+                let inventories = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.certificates.clone())).await.map_err(|e| e.to_string())?;
+                serde_json::to_value(inventories.into_iter().flatten().next().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+            "wan" => {
+                let wans = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.wan.clone())).await.map_err(|e| e.to_string())?;
+                serde_json::to_value(wans.into_iter().flatten().next().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+            "ddos" => {
+                let defenses = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.ddos.clone())).await.map_err(|e| e.to_string())?;
+                serde_json::to_value(defenses.into_iter().flatten().next().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+            "capacity" => Ok(serde_json::json!({
+                "settings": self.config.capacity,
+                "forecasts": self.capacity.forecasts().collect::<Vec<_>>(),
+            })),
+            "status_page" => Ok(serde_json::json!({
+                "status": self.status_page.overall(),
+                "components": self.status_page.components(),
+                "incidents": self.status_page.incidents.iter().rev().take(20).collect::<Vec<_>>(),
+                "notices_sent": self.status_page.notices_sent,
+                "credits": self.status_page.credits.iter().rev().take(50).collect::<Vec<_>>(),
+                "credited": self.status_page.credited,
+            })),
+            "shifts" => {
+                let shifts = shifts::shifts(&self.config.shifts, self.config.working_hours);
+                let roster: Vec<serde_json::Value> = shifts.iter()
+                    .map(|shift| serde_json::json!({
+                        "shift": shift,
+                        "staff": self.shift_roster.staff(&shift.name).iter().map(|agent_id| self.actor_name(*agent_id)).collect::<Vec<_>>(),
+                    }))
+                    .collect();
+                Ok(serde_json::json!({
+                    "current": self.shift_roster.current(),
+                    "shifts": roster,
+                    "handed_over": self.shift_roster.handed_over,
+                    "unstaffed_hours": self.shift_roster.unstaffed_hours,
+                    "handoffs": self.shift_roster.notes().rev().take(20).collect::<Vec<_>>(),
+                }))
+            }
+            "ticket_queue" => Ok(serde_json::json!({
+                "depth": self.ticket_queue.depth(),
+                "oldest_wait_minutes": self.ticket_queue.oldest_wait_minutes(self.scheduler.now()),
+                "mean_wait_minutes": self.ticket_queue.mean_wait_minutes(),
+                "routed": self.ticket_queue.routed,
+                "reassigned": self.ticket_queue.reassigned,
+                "waiting": self.ticket_queue.waiting(),
+            })),
+            "firewall" => {
+                let reports = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.analyze_firewall())).await.map_err(|e| e.to_string())?;
+                serde_json::to_value(reports.into_iter().flatten().next().unwrap_or_default()).map_err(|e| e.to_string())
+            }
+            _ => Err(format!("unknown report '{}'; expected summary, insights, risks, doctor, org, executive, staffing, economy, knowledge, pipelines, environments, red_team, iam, phishing, threat_intel, dlp, firewall, topology, traffic, dns, certificates, wan, ddos, ticket_queue, capacity, status_page, shifts or alerts", name)),
+        }
+    }
+
+    /// A deployment's status, steps and their output, for the control API
+    #[cfg(feature = "api")]
+    async fn deployment_status(&self, id: &str) -> Result<serde_json::Value, String> {
+        let deployment_id = Uuid::parse_str(id).map_err(|_| format!("'{}' is not a deployment id", id))?;
+        let found = self.agents.call_all(move |agent| {
+            let info = agent.get_agent();
+            let deployment = agent.as_devops().and_then(|devops| devops.deployment(deployment_id)).cloned()?;
+            Some((info.name.clone(), deployment))
+        }).await.map_err(|e| e.to_string())?;
+        let (agent, deployment) = found.into_iter().flatten().next().ok_or_else(|| format!("no deployment {}", deployment_id))?;
+        let (done, total) = deployment.progress();
+        Ok(serde_json::json!({
+            "agent": agent,
+            "status": deployment.status,
+            "steps_done": done,
+            "steps_total": total,
+            "current_step": deployment.running_step().map(|step| step.name.clone()),
+            "deployment": deployment,
+        }))
+    }
+}
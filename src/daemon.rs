@@ -0,0 +1,500 @@
+//! Daemon Mode - Long-Running Simulation Service
+//!
+//! This module implements running the simulation as a persistent service:
+//! - Liveness (`/healthz`) and readiness (`/readyz`) HTTP endpoints, with
+//!   how many agents were in each state at the latest rotated snapshot
+//! - Structural diagnostics of the latest rotated snapshot (`/doctor`)
+//! - Rotated snapshots so a restarted daemon resumes from the latest state
+//! - Periodic (weekly by default) summary reports written to disk,
+//!   posted to a webhook and emailed to the operator
+
+use crate::agentstate::AgentState;
+use crate::analytics::{Insight, ReportPeriod};
+use crate::config::DaemonSettings;
+use crate::doctor::{self, Diagnosis};
+use crate::email::EmailGateway;
+use crate::releasenotes::ReleaseNotes;
+use crate::snapshot::{AgentSnapshot, SimulationSnapshot};
+use crate::templates::{ReportTemplate, TemplatePack};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Prefix of rotated snapshot file names
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+/// Minutes before a failed report post is retried; doubles on every attempt
+const REPORT_RETRY_MINUTES: i64 = 5;
+
+/// Health state shared with the HTTP endpoint task
+#[derive(Debug)]
+pub struct HealthState {
+    started_at: chrono::DateTime<chrono::Utc>,
+    ready: AtomicBool,
+    /// Unix timestamp of the last main-loop heartbeat
+    last_heartbeat: AtomicI64,
+    step_count: AtomicU64,
+    liveness_timeout_secs: i64,
+    /// Findings of the doctor on the latest rotated snapshot
+    diagnoses: Mutex<Vec<Diagnosis>>,
+    /// Agents in each state at the latest rotated snapshot
+    agent_states: Mutex<BTreeMap<AgentState, usize>>,
+}
+
+impl HealthState {
+    pub fn new(liveness_timeout_secs: i64) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            started_at: now,
+            ready: AtomicBool::new(false),
+            last_heartbeat: AtomicI64::new(now.timestamp()),
+            step_count: AtomicU64::new(0),
+            liveness_timeout_secs,
+            diagnoses: Mutex::new(Vec::new()),
+            agent_states: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Build the HTTP status line and JSON body for a request path
+    pub fn respond(&self, path: &str, now: chrono::DateTime<chrono::Utc>) -> (&'static str, String) {
+        let since_heartbeat = now.timestamp() - self.last_heartbeat.load(Ordering::Relaxed);
+        let body = |status: &str| serde_json::json!({
+            "status": status,
+            "step": self.step_count.load(Ordering::Relaxed),
+            "uptime_secs": (now - self.started_at).num_seconds(),
+            "secs_since_heartbeat": since_heartbeat,
+            "agent_states": *self.agent_states.lock().expect("agent states lock poisoned"),
+        }).to_string();
+
+        match path {
+            "/healthz" if since_heartbeat <= self.liveness_timeout_secs => ("200 OK", body("alive")),
+            "/healthz" => ("503 Service Unavailable", body("stalled")),
+            "/readyz" if self.ready.load(Ordering::Relaxed) => ("200 OK", body("ready")),
+            "/readyz" => ("503 Service Unavailable", body("starting")),
+            "/doctor" => {
+                let diagnoses = self.diagnoses.lock().expect("diagnoses lock poisoned");
+                ("200 OK", serde_json::json!({
+                    "step": self.step_count.load(Ordering::Relaxed),
+                    "problems": diagnoses.len(),
+                    "diagnoses": *diagnoses,
+                }).to_string())
+            }
+            _ => ("404 Not Found", serde_json::json!({ "error": "not found" }).to_string()),
+        }
+    }
+}
+
+/// Summary of simulation activity over a reporting period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryReport {
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub steps_in_period: u64,
+    pub total_steps: u64,
+    pub total_agents: usize,
+    pub shared_customers: usize,
+    pub dedicated_customers: usize,
+    pub monthly_revenue: f64,
+    pub incidents_opened: usize,
+    pub tickets_opened: usize,
+    pub marketing_leads: u32,
+    /// Week-over-week KPI movements
+    #[serde(default)]
+    pub insights: Vec<Insight>,
+    /// Releases shipped over the last simulated week
+    #[serde(default)]
+    pub releases: Vec<ReleaseNotes>,
+}
+
+impl SummaryReport {
+    /// Values available to the summary report template
+    pub fn template_context(&self) -> serde_json::Value {
+        serde_json::json!({
+            "period_start": self.period_start.format("%Y-%m-%d").to_string(),
+            "period_end": self.period_end.format("%Y-%m-%d").to_string(),
+            "steps_in_period": self.steps_in_period,
+            "total_steps": self.total_steps,
+            "total_agents": self.total_agents,
+            "shared_customers": self.shared_customers,
+            "dedicated_customers": self.dedicated_customers,
+            "monthly_revenue": format!("{:.2}", self.monthly_revenue),
+            "incidents_opened": self.incidents_opened,
+            "tickets_opened": self.tickets_opened,
+            "marketing_leads": self.marketing_leads,
+            "has_insights": !self.insights.is_empty(),
+            "insights": self.insights.iter().map(|insight| insight.template_context()).collect::<Vec<_>>(),
+            "has_releases": !self.releases.is_empty(),
+            "releases": self.releases.iter().map(|notes| notes.template_context()).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Summarize a snapshot over the period since the previous report
+    pub fn from_snapshot(snapshot: &SimulationSnapshot, period_start: chrono::DateTime<chrono::Utc>, start_step: u64) -> Self {
+        let mut incidents_opened = 0;
+        let mut tickets_opened = 0;
+        for agent in snapshot.agents.values() {
+            if let AgentSnapshot::Ops(ops) = agent {
+                incidents_opened += ops.incidents.values()
+                    .filter(|i| i.duplicate_of.is_none() && i.created_at >= period_start)
+                    .count();
+                tickets_opened += ops.support_tickets.values()
+                    .filter(|t| t.created_at >= period_start)
+                    .count();
+            }
+        }
+        let (shared_customers, dedicated_customers) = snapshot.catalog.tenancy_counts();
+
+        Self {
+            period_start,
+            period_end: snapshot.taken_at,
+            steps_in_period: snapshot.step_count.saturating_sub(start_step),
+            total_steps: snapshot.step_count,
+            total_agents: snapshot.agents.len(),
+            shared_customers,
+            dedicated_customers,
+            monthly_revenue: snapshot.catalog.monthly_revenue(),
+            incidents_opened,
+            tickets_opened,
+            marketing_leads: snapshot.campaign_reports.values().map(|r| r.total_leads).sum(),
+            insights: snapshot.kpi_history.insights(snapshot.simulated_now(), ReportPeriod::Week),
+            releases: snapshot.release_notes.since(snapshot.simulated_now() - chrono::Duration::days(7)).cloned().collect(),
+        }
+    }
+}
+
+/// Daemon-mode bookkeeping attached to a running simulation
+#[derive(Debug)]
+pub struct Daemon {
+    settings: DaemonSettings,
+    /// Templates the summary digest is rendered with
+    templates: TemplatePack,
+    health: Arc<HealthState>,
+    last_snapshot_step: u64,
+    last_report_at: chrono::DateTime<chrono::Utc>,
+    last_report_step: u64,
+    /// Latest summary report not yet posted to the report webhook
+    pending_post: Option<PendingPost>,
+}
+
+/// Summary report waiting to be posted
+#[derive(Debug, Clone)]
+struct PendingPost {
+    json: String,
+    attempts: u32,
+    next_attempt_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Daemon {
+    pub fn new(settings: DaemonSettings, templates: TemplatePack, step_count: u64) -> Self {
+        Self {
+            health: Arc::new(HealthState::new(settings.liveness_timeout_secs)),
+            settings,
+            templates,
+            last_snapshot_step: step_count,
+            last_report_at: chrono::Utc::now(),
+            last_report_step: step_count,
+            pending_post: None,
+        }
+    }
+
+    /// Bind the health endpoints and serve them in the background
+    pub async fn start(&self) -> Result<(), DaemonError> {
+        let listener = TcpListener::bind(&self.settings.health_addr).await
+            .map_err(|e| DaemonError::Bind(self.settings.health_addr.clone(), e))?;
+        println!("🩺 Health endpoints on http://{}/healthz, /readyz and /doctor", self.settings.health_addr);
+
+        let health = self.health.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { continue };
+                tokio::spawn(serve_health_request(stream, health.clone()));
+            }
+        });
+        Ok(())
+    }
+
+    /// Mark the simulation ready to serve traffic
+    pub fn set_ready(&self, ready: bool) {
+        self.health.ready.store(ready, Ordering::Relaxed);
+    }
+
+    /// Record that the main loop is still making progress
+    pub fn heartbeat(&self, step_count: u64) {
+        self.health.last_heartbeat.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+        self.health.step_count.store(step_count, Ordering::Relaxed);
+    }
+
+    /// Whether enough steps have passed for the next rotated snapshot
+    pub fn snapshot_due(&self, step_count: u64) -> bool {
+        step_count >= self.last_snapshot_step + self.settings.snapshot_every_steps
+    }
+
+    /// Whether the next summary report is due
+    pub fn report_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        now - self.last_report_at >= chrono::Duration::hours(self.settings.report_interval_hours)
+    }
+
+    /// Write a timestamped snapshot and prune the oldest beyond the retention limit
+    pub fn rotate_snapshot(&mut self, snapshot: &SimulationSnapshot) -> Result<PathBuf, DaemonError> {
+        std::fs::create_dir_all(&self.settings.snapshot_dir)?;
+        let path = self.settings.snapshot_dir.join(format!("{}{}-step{:010}.json",
+            SNAPSHOT_PREFIX, snapshot.taken_at.format("%Y%m%dT%H%M%S"), snapshot.step_count));
+        snapshot.save(&path).map_err(|e| DaemonError::Snapshot(e.to_string()))?;
+        self.last_snapshot_step = snapshot.step_count;
+        *self.health.diagnoses.lock().expect("diagnoses lock poisoned") = doctor::diagnose(snapshot);
+        *self.health.agent_states.lock().expect("agent states lock poisoned") = snapshot.agent_states.counts();
+
+        let snapshots = list_snapshots(&self.settings.snapshot_dir)?;
+        let excess = snapshots.len().saturating_sub(self.settings.keep_snapshots);
+        for old in &snapshots[..excess] {
+            std::fs::remove_file(old)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Whether a report post that failed is due for another attempt
+    pub fn report_post_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.pending_post.as_ref().is_some_and(|post| post.next_attempt_at <= now)
+    }
+
+    /// Summarize the period since the last report and deliver it
+    ///
+    /// The report is written to disk even when posting it fails; the post is
+    /// then retried with backoff until it succeeds or the next report replaces it.
+    /// A failed email is reported and not retried.
+    pub async fn deliver_report(&mut self, snapshot: &SimulationSnapshot, email: Option<&mut EmailGateway>) -> Result<SummaryReport, DaemonError> {
+        let report = SummaryReport::from_snapshot(snapshot, self.last_report_at, self.last_report_step);
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| DaemonError::Report(e.to_string()))?;
+
+        std::fs::create_dir_all(&self.settings.report_dir)?;
+        let path = self.settings.report_dir.join(format!("summary-{}.json", report.period_end.format("%Y%m%d")));
+        std::fs::write(&path, &json)?;
+        println!("📰 Summary report written to {}", path.display());
+
+        let digest = self.templates.render(ReportTemplate::SummaryReport, &report.template_context())
+            .map_err(|e| DaemonError::Report(e.to_string()))?;
+        let digest_path = path.with_extension(self.templates.extension(ReportTemplate::SummaryReport));
+        std::fs::write(&digest_path, &digest)?;
+
+        if let (Some(to), Some(email)) = (&self.settings.report_email, email) {
+            let subject = format!("AIvertCo summary {} to {}", report.period_start.format("%Y-%m-%d"), report.period_end.format("%Y-%m-%d"));
+            match email.send_summary(to, subject, digest, report.period_end).await {
+                Ok(()) => println!("📧 Summary report emailed to {}", to),
+                Err(e) => eprintln!("📧 Could not email summary report to {}: {}", to, e),
+            }
+        }
+
+        self.last_report_at = report.period_end;
+        self.last_report_step = report.total_steps;
+
+        if self.settings.report_webhook.is_some() {
+            self.pending_post = Some(PendingPost { json, attempts: 0, next_attempt_at: chrono::Utc::now() });
+            self.post_pending_report(chrono::Utc::now()).await?;
+        }
+        Ok(report)
+    }
+
+    /// Post the pending summary report, backing off after a failure
+    pub async fn post_pending_report(&mut self, now: chrono::DateTime<chrono::Utc>) -> Result<(), DaemonError> {
+        let (Some(url), Some(post)) = (&self.settings.report_webhook, self.pending_post.as_mut()) else {
+            return Ok(());
+        };
+        if let Err(e) = crate::webhooks::post_json(url, &post.json, &[]).await {
+            post.attempts += 1;
+            let backoff = (REPORT_RETRY_MINUTES << (post.attempts - 1).min(16)).min(self.settings.report_interval_hours * 60);
+            post.next_attempt_at = now + chrono::Duration::minutes(backoff);
+            return Err(DaemonError::Webhook(format!("{}, retrying at {}", e, post.next_attempt_at.format("%Y-%m-%d %H:%M"))));
+        }
+        println!("📤 Summary report posted to {}", url);
+        self.pending_post = None;
+        Ok(())
+    }
+}
+
+/// Most recent rotated snapshot in a directory, if any
+pub fn latest_snapshot(dir: &Path) -> Option<PathBuf> {
+    list_snapshots(dir).ok()?.pop()
+}
+
+/// Rotated snapshots in a directory, oldest first
+fn list_snapshots(dir: &Path) -> Result<Vec<PathBuf>, DaemonError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(SNAPSHOT_PREFIX)))
+        .collect();
+    // Timestamped names sort chronologically
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+/// Answer one health check request
+async fn serve_health_request(mut stream: TcpStream, health: Arc<HealthState>) {
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).await.unwrap_or(0);
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, body) = health.respond(path, chrono::Utc::now());
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body);
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Daemon-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum DaemonError {
+    #[error("Failed to bind health endpoint on {0}: {1}")]
+    Bind(String, std::io::Error),
+
+    #[error("Daemon I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Snapshot rotation failed: {0}")]
+    Snapshot(String),
+
+    #[error("Report generation failed: {0}")]
+    Report(String),
+
+    #[error("Webhook delivery failed: {0}")]
+    Webhook(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::ServiceCatalog;
+    use crate::config::SimulationConfig;
+    use crate::snapshot::SNAPSHOT_VERSION;
+    use std::collections::HashMap;
+
+    fn empty_snapshot(step_count: u64) -> SimulationSnapshot {
+        SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now() + chrono::Duration::seconds(step_count as i64),
+            step_count,
+            config: SimulationConfig::default(),
+            agents: HashMap::new(),
+            projects: HashMap::new(),
+            pending_messages: vec![],
+            campaign_reports: HashMap::new(),
+            catalog: ServiceCatalog::default(),
+            message_log: vec![],
+            scheduler: None,
+            router: crate::routing::MessageRouter::new(),
+            intake: crate::intake::IntakeController::new(),
+            wait_graph: crate::waitgraph::WaitForGraph::new(),
+            kpi_history: crate::analytics::KpiHistory::new(),
+            entity_tracker: crate::webhooks::EntityTracker::new(),
+            webhooks: crate::webhooks::WebhookDispatcher::new(),
+            repository: None,
+            supervisor: crate::supervisor::Supervisor::new(),
+            release_notes: crate::releasenotes::ReleaseLog::new(),
+            scorecards: crate::scorecard::ScorecardBook::new(),
+            quotas: crate::quota::QuotaLedger::new(),
+            task_graph: crate::taskgraph::TaskGraph::new(),
+            risks: crate::risk::RiskRegister::new(),
+            progression: crate::progression::SkillProgression::new(),
+            org: crate::orgchart::OrgChart::new(),
+            executive: crate::executive::ExecutiveAgent::new(),
+            staffing: crate::staffing::StaffingLog::new(),
+            economy: None,
+            knowledge: crate::knowledge::KnowledgeBase::new(),
+            pipelines: crate::pipeline::BuildQueue::new(),
+            promotions: crate::promotion::Promotions::new(),
+            alerts: crate::alerting::AlertEngine::new(),
+            red_team: crate::redteam::AttackSimulator::new(),
+            iam: crate::iam::IdentityDirectory::new(),
+            phishing: crate::phishing::AwarenessProgram::new(),
+            threat_intel: crate::threatintel::ThreatIntel::new(),
+            dlp: crate::dlp::DlpMonitor::new(),
+            ticket_queue: crate::ticketqueue::TicketQueue::new(),
+            capacity: crate::capacity::CapacityPlanner::new(),
+            status_page: crate::statuspage::StatusPage::new(),
+            shift_roster: crate::shifts::ShiftRoster::new(),
+            agent_states: crate::agentstate::AgentStates::new(),
+            memories: crate::memory::AgentMemories::new(),
+            manager_reports: crate::deptreport::ManagerReports::new(),
+            threads: crate::threads::ConversationThreads::new(),
+        }
+    }
+
+    #[test]
+    fn test_health_endpoints() {
+        let health = HealthState::new(60);
+        let now = chrono::Utc::now();
+
+        assert_eq!(health.respond("/healthz", now).0, "200 OK");
+        assert_eq!(health.respond("/readyz", now).0, "503 Service Unavailable");
+        health.ready.store(true, Ordering::Relaxed);
+        assert_eq!(health.respond("/readyz", now).0, "200 OK");
+        assert!(health.respond("/readyz", now).1.contains("\"agent_states\":{}"));
+
+        let later = now + chrono::Duration::seconds(120);
+        assert_eq!(health.respond("/healthz", later).0, "503 Service Unavailable");
+        assert_eq!(health.respond("/nope", now).0, "404 Not Found");
+        let (status, body) = health.respond("/doctor", now);
+        assert_eq!((status, body.contains("\"problems\":0")), ("200 OK", true));
+    }
+
+    #[test]
+    fn test_snapshot_rotation_keeps_latest() {
+        let dir = std::env::temp_dir().join(format!("daemon-{}", uuid::Uuid::new_v4()));
+        let settings = DaemonSettings {
+            snapshot_dir: dir.clone(),
+            keep_snapshots: 2,
+            ..DaemonSettings::default()
+        };
+        let mut daemon = Daemon::new(settings, TemplatePack::builtin(), 0);
+
+        for step in [10, 20, 30] {
+            daemon.rotate_snapshot(&empty_snapshot(step)).unwrap();
+        }
+
+        let remaining = list_snapshots(&dir).unwrap();
+        let latest = latest_snapshot(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(remaining.len(), 2);
+        assert!(latest.to_string_lossy().ends_with("step0000000030.json"));
+        assert!(!daemon.snapshot_due(35));
+    }
+
+    #[tokio::test]
+    async fn test_failed_report_post_backs_off() {
+        // Nothing listens on a port that was just released
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let dir = std::env::temp_dir().join(format!("daemon-{}", uuid::Uuid::new_v4()));
+        let settings = DaemonSettings {
+            report_dir: dir.clone(),
+            report_webhook: Some(format!("http://127.0.0.1:{}/reports", port)),
+            ..DaemonSettings::default()
+        };
+        let mut daemon = Daemon::new(settings, TemplatePack::builtin(), 0);
+
+        let result = daemon.deliver_report(&empty_snapshot(10), None).await;
+        let written = std::fs::read_dir(&dir).map(|entries| entries.count()).unwrap_or(0);
+        std::fs::remove_dir_all(&dir).ok();
+
+        // The report is on disk and not due again; only the post is retried, later
+        assert!(matches!(result, Err(DaemonError::Webhook(_))));
+        assert_eq!(written, 2);
+        let now = chrono::Utc::now();
+        assert!(!daemon.report_due(now));
+        assert!(!daemon.report_post_due(now));
+        assert!(daemon.report_post_due(now + chrono::Duration::minutes(REPORT_RETRY_MINUTES)));
+    }
+}
@@ -0,0 +1,275 @@
+//! DDoS Simulation - Attacks on Public Segments and Their Mitigation
+//!
+//! This module implements DDoS attacks on Networking's public-facing
+//! segments and how they are fought off:
+//! - Attacks arrive at random, a few a month, on a Public or DMZ segment,
+//!   flooding it with several times its capacity for a few hours. While more
+//!   attack traffic gets through than the segment carries, the services
+//!   behind it are degraded
+//! - Mitigation escalates until the attack traffic getting through fits:
+//!   rate-limiting firewall rules first, then scrubbing at the load
+//!   balancers, and as a last resort blackholing the segment upstream, which
+//!   drops its legitimate traffic too. Each takes effect some minutes into
+//!   the attack, and scrubbing needs a load balancer that is up
+//! - Time to mitigate: from the start of an attack until the traffic getting
+//!   through fits the segment again
+//!
+//! Networking defends every simulation step; the orchestrator declares an
+//! incident for Ops, keeps InfoSec informed and samples the mean time to
+//! mitigate as a KPI.
+
+use crate::config::DdosSettings;
+use crate::departments::infosec::Severity;
+use chrono::{DateTime, Utc};
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Attacks kept once over, for the KPI and the report
+const MAX_ATTACKS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mitigation {
+    RateLimit,
+    Scrubbing,
+    Blackhole,
+}
+
+impl Mitigation {
+    /// In the order they are tried
+    pub const ESCALATION: [Mitigation; 3] = [Mitigation::RateLimit, Mitigation::Scrubbing, Mitigation::Blackhole];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mitigation::RateLimit => "rate limiting",
+            Mitigation::Scrubbing => "scrubbing",
+            Mitigation::Blackhole => "blackholing",
+        }
+    }
+
+    /// Share of the attack traffic reaching it that it filters
+    fn filtered(&self) -> f64 {
+        match self {
+            Mitigation::RateLimit => 0.5,
+            Mitigation::Scrubbing => 0.8,
+            Mitigation::Blackhole => 1.0,
+        }
+    }
+
+    /// Minutes into an attack it takes effect
+    fn delay_minutes(&self, settings: &DdosSettings) -> u32 {
+        match self {
+            Mitigation::RateLimit => settings.rate_limit_minutes,
+            Mitigation::Scrubbing => settings.scrubbing_minutes,
+            Mitigation::Blackhole => settings.blackhole_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attack {
+    pub id: Uuid,
+    pub segment: String,
+    /// Attack traffic before mitigation
+    pub bps: u64,
+    pub capacity_bps: u64,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Mitigations in place and when each took effect
+    pub mitigations: Vec<(Mitigation, DateTime<Utc>)>,
+    pub mitigated_at: Option<DateTime<Utc>>,
+    pub ended: bool,
+}
+
+impl Attack {
+    /// Attack traffic the mitigations in place let through
+    pub fn residual_bps(&self) -> u64 {
+        let passed = self.mitigations.iter().fold(1.0, |passed, (mitigation, _)| passed * (1.0 - mitigation.filtered()));
+        (self.bps as f64 * passed) as u64
+    }
+
+    /// Whether the traffic getting through fits the segment
+    pub fn contained(&self) -> bool {
+        self.residual_bps() <= self.capacity_bps
+    }
+
+    pub fn has(&self, mitigation: Mitigation) -> bool {
+        self.mitigations.iter().any(|(applied, _)| *applied == mitigation)
+    }
+
+    pub fn minutes_to_mitigate(&self) -> Option<i64> {
+        self.mitigated_at.map(|at| (at - self.started_at).num_minutes())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DdosEventKind {
+    Started { bps: u64, capacity_bps: u64 },
+    Mitigation { mitigation: Mitigation, residual_bps: u64 },
+    Mitigated { minutes: i64 },
+    Ended { mitigated: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DdosEvent {
+    pub attack: Uuid,
+    pub segment: String,
+    pub kind: DdosEventKind,
+}
+
+impl DdosEvent {
+    fn new(attack: &Attack, kind: DdosEventKind) -> Self {
+        Self { attack: attack.id, segment: attack.segment.clone(), kind }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match &self.kind {
+            DdosEventKind::Started { .. } => Severity::High,
+            DdosEventKind::Mitigation { mitigation: Mitigation::Blackhole, .. } => Severity::High,
+            DdosEventKind::Mitigation { .. } => Severity::Medium,
+            DdosEventKind::Mitigated { .. } | DdosEventKind::Ended { mitigated: true } => Severity::Low,
+            DdosEventKind::Ended { mitigated: false } => Severity::Medium,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let gbps = |bps: u64| bps as f64 / 1e9;
+        match &self.kind {
+            DdosEventKind::Started { bps, capacity_bps } => {
+                format!("DDoS attack on segment {}: {:.1} Gbps against {:.1} Gbps of capacity", self.segment, gbps(*bps), gbps(*capacity_bps))
+            }
+            DdosEventKind::Mitigation { mitigation, residual_bps } => {
+                format!("DDoS on segment {}: {} in place, {:.1} Gbps still getting through", self.segment, mitigation.name(), gbps(*residual_bps))
+            }
+            DdosEventKind::Mitigated { minutes } => format!("DDoS on segment {} mitigated after {} minutes", self.segment, minutes),
+            DdosEventKind::Ended { mitigated: true } => format!("DDoS on segment {} is over", self.segment),
+            DdosEventKind::Ended { mitigated: false } => format!("DDoS on segment {} stopped before it was mitigated", self.segment),
+        }
+    }
+}
+
+/// Attacks under way and past
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DdosDefense {
+    pub attacks: Vec<Attack>,
+}
+
+impl DdosDefense {
+    pub fn active(&self) -> impl Iterator<Item = &Attack> {
+        self.attacks.iter().filter(|attack| !attack.ended)
+    }
+
+    /// End the attacks that are over, escalate the mitigation of those still
+    /// getting through and start new ones over `elapsed_seconds`.
+    /// `segments` are the public-facing segments and their capacity in bps;
+    /// `scrubbing` is whether a load balancer can scrub traffic.
+    pub fn step(&mut self, now: DateTime<Utc>, elapsed_seconds: u64, segments: &BTreeMap<String, u64>, scrubbing: bool, settings: &DdosSettings, rng: &mut impl Rng) -> Vec<DdosEvent> {
+        let mut events = Vec::new();
+        for attack in self.attacks.iter_mut().filter(|attack| !attack.ended) {
+            if now >= attack.ends_at {
+                attack.ended = true;
+                events.push(DdosEvent::new(attack, DdosEventKind::Ended { mitigated: attack.mitigated_at.is_some() }));
+                continue;
+            }
+            if attack.mitigated_at.is_some() {
+                continue;
+            }
+            for mitigation in Mitigation::ESCALATION {
+                if attack.contained() || now < attack.started_at + chrono::Duration::minutes(i64::from(mitigation.delay_minutes(settings))) {
+                    break;
+                }
+                if attack.has(mitigation) || (mitigation == Mitigation::Scrubbing && !scrubbing) {
+                    continue;
+                }
+                attack.mitigations.push((mitigation, now));
+                events.push(DdosEvent::new(attack, DdosEventKind::Mitigation { mitigation, residual_bps: attack.residual_bps() }));
+            }
+            if attack.contained() {
+                attack.mitigated_at = Some(now);
+                let minutes = attack.minutes_to_mitigate().unwrap_or_default();
+                events.push(DdosEvent::new(attack, DdosEventKind::Mitigated { minutes }));
+            }
+        }
+
+        let hours = elapsed_seconds as f64 / 3600.0;
+        let chance = (settings.attacks_per_month.max(0.0) / (30.0 * 24.0) * hours).min(1.0);
+        let target = segments.iter().filter(|(name, _)| !self.active().any(|attack| attack.segment == **name)).choose(rng);
+        if let Some((segment, &capacity_bps)) = target.filter(|_| rng.gen_bool(chance)) {
+            let peak = settings.peak_multiplier.max(1.0);
+            let attack = Attack {
+                id: Uuid::new_v4(),
+                segment: segment.clone(),
+                bps: (capacity_bps as f64 * rng.gen_range(peak.min(1.5)..=peak)) as u64,
+                capacity_bps,
+                started_at: now,
+                ends_at: now + chrono::Duration::hours(rng.gen_range(1..=i64::from(settings.max_duration_hours.max(1)))),
+                mitigations: Vec::new(),
+                mitigated_at: None,
+                ended: false,
+            };
+            events.push(DdosEvent::new(&attack, DdosEventKind::Started { bps: attack.bps, capacity_bps }));
+            self.attacks.push(attack);
+        }
+
+        let over = self.attacks.iter().filter(|attack| attack.ended).count();
+        if over > MAX_ATTACKS {
+            let mut excess = over - MAX_ATTACKS;
+            self.attacks.retain(|attack| {
+                let drop = attack.ended && excess > 0;
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+        events
+    }
+
+    /// Mean minutes from the start of an attack until it was mitigated
+    pub fn mean_time_to_mitigate(&self) -> Option<f64> {
+        let minutes: Vec<i64> = self.attacks.iter().filter_map(Attack::minutes_to_mitigate).collect();
+        if minutes.is_empty() {
+            return None;
+        }
+        Some(minutes.iter().sum::<i64>() as f64 / minutes.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_mitigation_escalates_until_the_attack_fits() {
+        let settings = DdosSettings { attacks_per_month: 720.0 * 24.0, ..DdosSettings::default() };
+        let segments = BTreeMap::from([("dmz".to_string(), 1_000_000_000)]);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let start = Utc::now();
+        let mut defense = DdosDefense::default();
+        let events = defense.step(start, 3600, &segments, false, &settings, &mut rng);
+        assert!(matches!(events[..], [DdosEvent { kind: DdosEventKind::Started { .. }, .. }]));
+        defense.attacks[0].bps = 3_000_000_000;
+        defense.attacks[0].ends_at = start + chrono::Duration::hours(6);
+
+        // One attack per segment at a time
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        assert!(defense.step(at(5), 300, &segments, false, &settings, &mut rng).is_empty());
+        let events = defense.step(at(10), 300, &segments, false, &settings, &mut rng);
+        assert_eq!(events.len(), 1);
+        assert_eq!(defense.attacks[0].residual_bps(), 1_500_000_000);
+
+        // With no load balancer to scrub it the segment is blackholed
+        assert!(defense.step(at(60), 300, &segments, false, &settings, &mut rng).is_empty());
+        let events = defense.step(at(90), 300, &segments, false, &settings, &mut rng);
+        assert!(matches!(events[0].kind, DdosEventKind::Mitigation { mitigation: Mitigation::Blackhole, residual_bps: 0 }));
+        assert!(matches!(events[1].kind, DdosEventKind::Mitigated { minutes: 90 }));
+        assert_eq!(defense.mean_time_to_mitigate(), Some(90.0));
+
+        let events = defense.step(at(360), 300, &segments, false, &settings, &mut rng);
+        assert_eq!(events[0].kind, DdosEventKind::Ended { mitigated: true });
+    }
+}
@@ -6,17 +6,62 @@
 //! - Monitoring and alerting
 //! - High availability and fault tolerance
 //! - Customer server reliability
+//! - Server fleets scaled up under load and within budget, and scaled down
+//!   once idle for a while, between per-fleet minimum and maximum sizes
+//! - Container clusters: shipped projects run as pods bin-packed onto nodes,
+//!   rescheduled when a node fails, with nodes added or removed by auto-scaling
+//! - Deployments run step by step on the simulated clock, with retries and
+//!   timeouts
+//! - Canary and blue/green releases shifting traffic through the Networking
+//!   agent's load balancers, and rollback plans run when a release fails
+//! - Infrastructure as code: plans diffed against the running servers,
+//!   clusters and load balancers, destructive changes approved by Ops, then
+//!   applied one change at a time and checked for drift
+//! - Nightly backups of every server and cluster into a catalog pruned by
+//!   retention, restore drills that can fail, and the RPO/RTO they add up to
+//! - Patches pending per server, security fixes for InfoSec's findings
+//!   among them, applied in maintenance windows Ops approves; critical
+//!   fixes get an emergency window
+//! - Remediation tasks from InfoSec for serious findings, released as fixes
+//!   at once and released again when a re-scan finds the fix did not take
+//! - Improvement tasks for owned services after operations reviews
+//! - Small quota increases and capacity planning from quota utilization,
+//!   and servers added ahead of the CPU exhaustion Ops forecasts
 
 use crate::agents::{Agent, AgentTrait, Department};
+use crate::alerting::{AlertMetric, MetricSample};
+use crate::assignment;
+use crate::autoscale::{Autoscaler, ScalingDecision, ScalingPolicy};
+use crate::backup::{BackupCatalog, RecoveryMetrics};
+use crate::capacity::CapacityForecast;
+use crate::cluster::{self, Cluster, ClusterHealth};
 use crate::communication::{Message, MessageBus, MessagePriority};
+use crate::config::{BackupSettings, PatchingSettings};
+use crate::infra::{self, InfraChange, InfraPlan, InfraRun, InfraRunStatus};
+use crate::patching::{Advisory, PatchLedger, WindowEvent};
+use crate::payload::MessagePayload;
+use crate::pipeline::PipelineStatus;
 use crate::projects::{Project, Task};
+use crate::quota::{self, QuotaUtilization};
+use crate::scorecard;
+use crate::system::SystemActor;
 use async_trait::async_trait;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Attempts a deployment step gets before the deployment fails
+pub const MAX_STEP_ATTEMPTS: u32 = 3;
+
+/// Pods each shipped project runs
+const WORKLOAD_REPLICAS: u32 = 2;
+
+/// Chance a ready cluster node fails at a health check
+const NODE_FAILURE_PROBABILITY: f64 = 0.02;
+
 /// DevOps Agent specialized in infrastructure and deployment
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DevOpsAgent {
@@ -32,18 +77,51 @@ pub struct DevOpsAgent {
     pub infrastructure_state: InfrastructureState,
     /// Active deployments
     pub active_deployments: HashMap<Uuid, Deployment>,
+    /// Improvement tasks for owned services, oldest first
+    #[serde(default)]
+    pub improvements: Vec<Improvement>,
+    /// Latest infrastructure plan run, being applied or done
+    #[serde(default)]
+    pub infra_run: Option<InfraRun>,
+    /// Last plan applied in full; what it has and a new plan lacks is destroyed
+    #[serde(default)]
+    pub applied_plan: InfraPlan,
+    /// Limits and costs the last scale request came with
+    #[serde(default)]
+    pub scaling_policy: ScalingPolicy,
+    /// Idle streaks of the server fleets
+    #[serde(default)]
+    pub autoscaler: Autoscaler,
+    /// Patches pending on the servers and the windows applying them
+    #[serde(default)]
+    pub patches: PatchLedger,
+    /// Messages waiting to be picked up by the orchestrator
+    #[serde(default)]
+    pub outbox: Vec<Message>,
+}
+
+/// Improvement task assigned at an operations review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Improvement {
+    pub task_id: Uuid,
+    pub service: String,
+    pub title: String,
+    pub assigned_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InfrastructureState {
     /// Server instances and their status
     pub servers: HashMap<String, ServerStatus>,
-    /// Container clusters
-    pub clusters: HashMap<String, ClusterStatus>,
+    /// Container clusters, one per environment deployed to
+    pub clusters: HashMap<String, Cluster>,
     /// Monitoring systems
     pub monitoring: MonitoringStatus,
     /// Backup systems
     pub backups: BackupStatus,
+    /// Load balancers Networking was asked to run, with their backends
+    #[serde(default)]
+    pub load_balancers: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +134,9 @@ pub struct ServerStatus {
     pub disk_usage: f32,
     pub uptime: u64,
     pub last_check: chrono::DateTime<chrono::Utc>,
+    /// Size it was provisioned at; unknown for servers from older snapshots
+    #[serde(default)]
+    pub spec: Option<ServerConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -67,23 +148,6 @@ pub enum ServerState {
     Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClusterStatus {
-    pub name: String,
-    pub nodes: Vec<String>,
-    pub healthy_nodes: usize,
-    pub status: ClusterHealth,
-    pub last_health_check: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ClusterHealth {
-    Healthy,
-    Degraded,
-    Critical,
-    Offline,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoringStatus {
     pub prometheus_up: bool,
@@ -99,6 +163,9 @@ pub struct BackupStatus {
     pub backup_success: bool,
     pub retention_days: u32,
     pub total_backups: u32,
+    /// Backups per system and the restore drills run on them
+    #[serde(default)]
+    pub catalog: BackupCatalog,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +177,90 @@ pub struct Deployment {
     pub start_time: chrono::DateTime<chrono::Utc>,
     pub steps: Vec<DeploymentStep>,
     pub current_step: usize,
+    /// Causality id of the event that triggered the deployment
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+    /// Repository commit being deployed
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// When the last step finished or the deployment gave up
+    #[serde(default)]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub strategy: DeploymentStrategy,
+    /// Steps that undo the release, run when it fails
+    #[serde(default)]
+    pub rollback_plan: Vec<DeploymentStep>,
+    /// Share of traffic on the new release, in percent
+    #[serde(default)]
+    pub traffic_percent: u8,
+    /// Traffic stage of the strategy being baked
+    #[serde(default)]
+    pub traffic_stage: usize,
+    #[serde(default)]
+    pub stage_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Why the release is being rolled back, once it is
+    #[serde(default)]
+    pub rollback_reason: Option<String>,
+    /// Rollback plan step being run
+    #[serde(default)]
+    pub rollback_step: usize,
+}
+
+impl Deployment {
+    /// Step being worked on, if the deployment is still running
+    pub fn running_step(&self) -> Option<&DeploymentStep> {
+        self.steps.get(self.current_step)
+            .filter(|_| matches!(self.status, DeploymentStatus::Pending | DeploymentStatus::InProgress))
+    }
+
+    /// Steps finished successfully out of all steps
+    pub fn progress(&self) -> (usize, usize) {
+        (self.steps.iter().filter(|step| step.status == StepStatus::Success).count(), self.steps.len())
+    }
+}
+
+/// How a release takes over traffic once its steps are done
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeploymentStrategy {
+    /// The Deploy step replaces the running release outright
+    #[default]
+    AllAtOnce,
+    /// Traffic moves to the new release in stages, each baked before the next
+    Canary {
+        traffic_percent: Vec<u8>,
+        bake_minutes: u32,
+    },
+    /// All traffic switches to the idle environment at once and back again
+    /// if it turns out unhealthy
+    BlueGreen {
+        bake_minutes: u32,
+    },
+}
+
+impl DeploymentStrategy {
+    /// Traffic share of each stage, ending at 100 for strategies that shift traffic
+    pub fn traffic_stages(&self) -> Vec<u8> {
+        match self {
+            DeploymentStrategy::AllAtOnce => vec![],
+            DeploymentStrategy::Canary { traffic_percent, .. } => {
+                let mut stages: Vec<u8> = traffic_percent.iter().map(|percent| (*percent).min(100)).filter(|percent| *percent > 0).collect();
+                if stages.last() != Some(&100) {
+                    stages.push(100);
+                }
+                stages
+            }
+            DeploymentStrategy::BlueGreen { .. } => vec![100],
+        }
+    }
+
+    fn bake_time(&self) -> chrono::Duration {
+        match self {
+            DeploymentStrategy::AllAtOnce => chrono::Duration::zero(),
+            DeploymentStrategy::Canary { bake_minutes, .. } | DeploymentStrategy::BlueGreen { bake_minutes } => chrono::Duration::minutes(*bake_minutes as i64),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -129,6 +280,47 @@ pub struct DeploymentStep {
     pub status: StepStatus,
     pub output: Option<String>,
     pub error: Option<String>,
+    /// Simulated seconds the step usually takes; each run takes half to one
+    /// and a half times as long
+    #[serde(default)]
+    pub duration_seconds: u32,
+    /// Chance each run fails; set by the agent's deployment skill when unset
+    #[serde(default)]
+    pub failure_probability: Option<f64>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// How long the current run takes, drawn when it starts
+    #[serde(default)]
+    pub run_seconds: Option<u32>,
+    #[serde(default)]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DeploymentStep {
+    pub fn new(name: &str, command: &str, duration_seconds: u32, timeout_seconds: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            timeout_seconds,
+            status: StepStatus::Pending,
+            output: None,
+            error: None,
+            duration_seconds,
+            failure_probability: None,
+            attempts: 0,
+            started_at: None,
+            run_seconds: None,
+            finished_at: None,
+        }
+    }
+
+    /// When the current run ends, by finishing or by timing out
+    fn ends_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let run = self.run_seconds?.min(self.timeout_seconds);
+        Some(self.started_at? + chrono::Duration::seconds(run as i64))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -150,31 +342,44 @@ impl DevOpsAgent {
             monitoring_skill: 80,
             infrastructure_state: InfrastructureState::default(),
             active_deployments: HashMap::new(),
+            improvements: Vec::new(),
+            infra_run: None,
+            applied_plan: InfraPlan::default(),
+            scaling_policy: ScalingPolicy::default(),
+            autoscaler: Autoscaler::new(),
+            patches: PatchLedger::new(),
+            outbox: vec![],
         }
     }
 
     /// Provision a new server instance
     pub async fn provision_server(&mut self, server_config: ServerConfig) -> Result<ServerStatus, DevOpsError> {
+        let server = self.insert_server(server_config);
+
+        // Log the provisioning
+        println!("🔧 DevOps: Provisioned server {}", server.hostname);
+
+        Ok(server)
+    }
+
+    fn insert_server(&mut self, server_config: ServerConfig) -> ServerStatus {
         // Simulate server provisioning
         let server_id = format!("srv-{}", Uuid::new_v4().simple());
 
         let server = ServerStatus {
             id: server_id.clone(),
-            hostname: server_config.hostname,
+            hostname: server_config.hostname.clone(),
             status: ServerState::Online,
             cpu_usage: 0.0,
             memory_usage: 0.0,
             disk_usage: 0.0,
             uptime: 0,
             last_check: chrono::Utc::now(),
+            spec: Some(server_config),
         };
 
         self.infrastructure_state.servers.insert(server_id, server.clone());
-
-        // Log the provisioning
-        println!("🔧 DevOps: Provisioned server {}", server_config.hostname);
-
-        Ok(server)
+        server
     }
 
     /// Deploy an application to the specified environment
@@ -189,27 +394,152 @@ impl DevOpsAgent {
             start_time: chrono::Utc::now(),
             steps: deployment_config.steps,
             current_step: 0,
+            correlation_id: deployment_config.correlation_id,
+            commit: deployment_config.commit.clone(),
+            finished_at: None,
+            strategy: deployment_config.strategy,
+            rollback_plan: deployment_config.rollback_plan,
+            traffic_percent: 0,
+            traffic_stage: 0,
+            stage_started_at: None,
+            rollback_reason: None,
+            rollback_step: 0,
         };
 
         self.active_deployments.insert(deployment_id, deployment);
 
-        // Start deployment asynchronously
-        let agent_clone = self.agent.clone();
-        let deployment_id_clone = deployment_id;
-        tokio::spawn(async move {
-            Self::execute_deployment(deployment_id_clone, agent_clone).await;
+        match &deployment_config.commit {
+            Some(commit) => println!("🚀 DevOps: Started deployment {} of {} to {}",
+                                     deployment_id, crate::vcs::short(commit), deployment_config.environment),
+            None => println!("🚀 DevOps: Started deployment {} to {}", deployment_id, deployment_config.environment),
+        }
+
+        Ok(deployment_id)
+    }
+
+    /// Deployment by id, running or finished
+    pub fn deployment(&self, deployment_id: Uuid) -> Option<&Deployment> {
+        self.active_deployments.get(&deployment_id)
+    }
+
+    /// Run every unfinished deployment forward to `now`; returns the ids of
+    /// deployments that finished, shipped, failed or rolled back, on this pass
+    ///
+    /// Steps run one after another, each starting when the one before it
+    /// ended. A run fails by chance or by outlasting its timeout and is
+    /// retried up to `MAX_STEP_ATTEMPTS` times; after that the remaining
+    /// steps are skipped and the rollback plan runs. Canary and blue/green
+    /// releases then shift traffic stage by stage, asking the orchestrator
+    /// to have Networking reweigh the load balancer, and roll back when a
+    /// stage turns out unhealthy.
+    pub fn advance_deployments(&mut self, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<Uuid> {
+        let skill_failure = 1.0 - assignment::success_probability(self.deployment_skill);
+        let mut finished = Vec::new();
+        let mut shifts = Vec::new();
+        for deployment in self.active_deployments.values_mut() {
+            if !matches!(deployment.status, DeploymentStatus::Pending | DeploymentStatus::InProgress) {
+                continue;
+            }
+            if deployment.status == DeploymentStatus::Pending {
+                deployment.status = DeploymentStatus::InProgress;
+                println!("🚚 DevOps: Deployment {} to {} is running", deployment.id, deployment.environment);
+            }
+            if run_deployment(deployment, now, skill_failure, rng, &mut shifts) {
+                finished.push(deployment.id);
+            }
+        }
+        let shipped: Vec<(String, Uuid)> = finished.iter()
+            .filter_map(|id| self.active_deployments.get(id))
+            .filter(|deployment| deployment.status == DeploymentStatus::Success)
+            .map(|deployment| (deployment.environment.clone(), deployment.project_id))
+            .collect();
+        for (environment, project_id) in shipped {
+            self.place_workload(&environment, project_id);
+        }
+        for shift in shifts {
+            let MessagePayload::TrafficShift { deployment_id, environment, percent } = &shift else { continue };
+            let content = format!("Send {}% of {} traffic to deployment {}", percent, environment, deployment_id);
+            self.outbox.push(shift.into_message(self.agent.id, SystemActor::Orchestrator.id(), content, MessagePriority::High));
+        }
+        finished
+    }
+
+    /// Finish the oldest improvement task and report it to the orchestrator
+    pub fn work_on_improvements(&mut self) -> Option<Improvement> {
+        if self.improvements.is_empty() {
+            return None;
+        }
+        let improvement = self.improvements.remove(0);
+        println!("🛠️ DevOps: Finished '{}'", improvement.title);
+
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: SystemActor::Orchestrator.id(),
+            message_type: scorecard::IMPROVEMENT_DONE.to_string(),
+            content: improvement.title.clone(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                (scorecard::TASK_ID_KEY.to_string(), improvement.task_id.to_string()),
+                ("service".to_string(), improvement.service.clone()),
+            ]),
         });
+        Some(improvement)
+    }
 
-        println!("🚀 DevOps: Started deployment {} to {}", deployment_id, deployment_config.environment);
+    /// Provision a server for every quota at or above the high-water mark
+    pub async fn plan_capacity(&mut self, report: &[QuotaUtilization], high_water_percent: f64) -> Result<Vec<ServerStatus>, DevOpsError> {
+        let mut provisioned = Vec::new();
+        for utilization in report.iter().filter(|utilization| utilization.percent >= high_water_percent) {
+            println!("📊 DevOps: {} at {:.0}% of quota, adding capacity", utilization.scope, utilization.percent);
+            let server_config = ServerConfig {
+                hostname: format!("{}-capacity-{}", utilization.scope.replace(':', "-"), self.infrastructure_state.servers.len() + 1),
+                cpu_cores: utilization.limit.cpu_cores,
+                memory_gb: utilization.limit.memory_gb,
+                disk_gb: utilization.limit.storage_gb,
+                encrypted: true,
+            };
+            provisioned.push(self.provision_server(server_config).await?);
+        }
+        Ok(provisioned)
+    }
 
-        Ok(deployment_id)
+    /// Add a server ahead of the CPU exhaustion Ops forecast, sized like the
+    /// largest one running
+    pub async fn add_forecast_capacity(&mut self, forecast: &CapacityForecast) -> Result<ServerStatus, DevOpsError> {
+        println!("📊 DevOps: {}, adding a server", forecast.description());
+        let largest = self.infrastructure_state.servers.values()
+            .filter_map(|server| server.spec.as_ref())
+            .max_by_key(|spec| (spec.cpu_cores, spec.memory_gb));
+        let server_config = ServerConfig {
+            hostname: format!("capacity-{}", self.infrastructure_state.servers.len() + 1),
+            cpu_cores: largest.map_or(8, |spec| spec.cpu_cores),
+            memory_gb: largest.map_or(16, |spec| spec.memory_gb),
+            disk_gb: largest.map_or(100, |spec| spec.disk_gb),
+            encrypted: true,
+        };
+        self.provision_server(server_config).await
     }
 
-    /// Execute deployment steps
-    async fn execute_deployment(deployment_id: Uuid, agent: Agent) {
-        // This would execute actual deployment steps
-        // For simulation, we'll just mark as successful
-        println!("✅ DevOps: Deployment {} completed successfully", deployment_id);
+    /// Reply to the orchestrator with a decision on a quota increase
+    fn decide_quota_increase(&mut self, request: &Message, approved: bool, reason: String) -> Result<(), DevOpsError> {
+        let request_id = request.metadata.get(quota::REQUEST_ID_KEY)
+            .ok_or_else(|| DevOpsError::InvalidRequest("approval_request without a quota_request_id".to_string()))?;
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: SystemActor::Orchestrator.id(),
+            message_type: quota::APPROVAL_DECISION.to_string(),
+            content: reason,
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                (quota::REQUEST_ID_KEY.to_string(), request_id.clone()),
+                ("approved".to_string(), approved.to_string()),
+            ]),
+        });
+        Ok(())
     }
 
     /// Check server health and update status
@@ -241,90 +571,486 @@ impl DevOpsAgent {
     pub async fn auto_scale(&mut self) -> Result<Vec<String>, DevOpsError> {
         let mut actions = Vec::new();
 
-        // Check each server for scaling needs
-        for (server_id, server) in &self.infrastructure_state.servers.clone() {
-            if server.cpu_usage > 80.0 || server.memory_usage > 80.0 {
-                // Scale up - add more servers
-                let new_server_config = ServerConfig {
-                    hostname: format!("{}-scale-{}", server.hostname, chrono::Utc::now().timestamp()),
-                    cpu_cores: 4,
-                    memory_gb: 8,
-                    disk_gb: 100,
-                };
+        // Add nodes for pods nothing can take, then drop nodes left idle
+        for cluster in self.infrastructure_state.clusters.values_mut() {
+            while cluster.pending_pods() > 0 && cluster.nodes.len() < cluster::MAX_NODES {
+                let node = cluster.add_node();
+                let placed = cluster.schedule();
+                actions.push(format!("Scaled up: added node {} for {} pending pods", node, placed));
+            }
+            if cluster.pending_pods() == 0 {
+                for node in cluster.remove_idle_nodes(cluster::MIN_NODES) {
+                    actions.push(format!("Scaled down: removed idle node {}", node));
+                }
+            }
+        }
 
-                if let Ok(new_server) = self.provision_server(new_server_config).await {
-                    actions.push(format!("Scaled up: added server {}", new_server.hostname));
+        // Grow hot fleets and shrink idle ones
+        let decisions = self.autoscaler.decide(self.infrastructure_state.servers.values(), &self.scaling_policy);
+        for decision in decisions {
+            match decision {
+                ScalingDecision::ScaleUp { fleet, reason } => {
+                    let new_server = self.provision_server(self.fleet_server_config(&fleet)).await?;
+                    actions.push(format!("Scaled up: added server {} ({})", new_server.hostname, reason));
                 }
+                ScalingDecision::ScaleDown { hostname, reason, .. } => {
+                    self.infrastructure_state.servers.retain(|_, server| server.hostname != hostname);
+                    actions.push(format!("Scaled down: removed server {} ({})", hostname, reason));
+                }
+                ScalingDecision::Held { fleet, reason } => actions.push(format!("Held {}: {}", fleet, reason)),
             }
         }
 
         Ok(actions)
     }
 
-    /// Perform backup operations
-    pub async fn perform_backup(&mut self) -> Result<(), DevOpsError> {
-        // Simulate backup process
-        println!("💾 DevOps: Starting backup operation...");
+    /// Configuration of the next server of a fleet: the size of the fleet's
+    /// first server and the first free `-scale-<n>` name
+    fn fleet_server_config(&self, fleet: &str) -> ServerConfig {
+        let servers = &self.infrastructure_state.servers;
+        let taken = |hostname: &str| servers.values().any(|server| server.hostname == hostname);
+        let hostname = (1..).map(|n| format!("{}-scale-{}", fleet, n)).find(|hostname| !taken(hostname)).unwrap_or_default();
+        let spec = servers.values().find(|server| server.hostname == fleet).and_then(|server| server.spec.clone());
+        match spec {
+            Some(spec) => ServerConfig { hostname, ..spec },
+            None => ServerConfig { hostname, cpu_cores: 4, memory_gb: 8, disk_gb: 100, encrypted: true },
+        }
+    }
 
-        // Update backup status
-        self.infrastructure_state.backups.last_backup = chrono::Utc::now();
-        self.infrastructure_state.backups.backup_success = true;
-        self.infrastructure_state.backups.total_backups += 1;
+    /// Fail and recover cluster nodes, rescheduling the pods of failed ones;
+    /// returns what happened
+    pub fn check_clusters(&mut self, rng: &mut impl Rng) -> Vec<String> {
+        let mut events = Vec::new();
+        for cluster in self.infrastructure_state.clusters.values_mut() {
+            let nodes: Vec<(String, bool)> = cluster.nodes.iter().map(|node| (node.name.clone(), node.ready)).collect();
+            for (node, ready) in nodes {
+                if !ready {
+                    cluster.recover_node(&node);
+                    events.push(format!("Node {} recovered", node));
+                } else if rng.gen_bool(NODE_FAILURE_PROBABILITY) {
+                    let evicted = cluster.fail_node(&node);
+                    events.push(format!("Node {} failed, {} pods evicted", node, evicted));
+                }
+            }
+            let pending = cluster.pending_pods();
+            let placed = cluster.schedule();
+            if pending > 0 {
+                events.push(format!("Rescheduled {} of {} pending pods on {}", placed, pending, cluster.name));
+            }
+            cluster.last_health_check = chrono::Utc::now();
+        }
+        events
+    }
 
-        println!("✅ DevOps: Backup completed successfully");
-        Ok(())
+    /// Latest usage of every server and the health of every cluster, for
+    /// the alert rules
+    pub fn metric_samples(&self) -> Vec<MetricSample> {
+        let sample = |target: &str, metric, value: f64| MetricSample { target: target.to_string(), metric, value, owner: self.agent.id };
+        let mut samples = Vec::new();
+        for server in self.infrastructure_state.servers.values() {
+            samples.push(sample(&server.hostname, AlertMetric::Cpu, f64::from(server.cpu_usage)));
+            samples.push(sample(&server.hostname, AlertMetric::Memory, f64::from(server.memory_usage)));
+            samples.push(sample(&server.hostname, AlertMetric::Disk, f64::from(server.disk_usage)));
+        }
+        for cluster in self.infrastructure_state.clusters.values() {
+            let degraded = if cluster.status == ClusterHealth::Healthy { 0.0 } else { 1.0 };
+            samples.push(sample(&cluster.name, AlertMetric::ClusterDegraded, degraded));
+        }
+        samples
+    }
+
+    /// Run a shipped project on its environment's cluster, replacing the
+    /// release before it
+    fn place_workload(&mut self, environment: &str, project_id: Uuid) {
+        let cluster = self.infrastructure_state.clusters.entry(environment.to_string())
+            .or_insert_with(|| Cluster::new(environment, cluster::MIN_NODES));
+        let workload = format!("project-{}", &project_id.simple().to_string()[..8]);
+        cluster.remove_workload(&workload);
+        cluster.submit(&workload, cluster::POD_REQUESTS, WORKLOAD_REPLICAS);
+        cluster.schedule();
+        if cluster.pending_pods() > 0 {
+            println!("⏳ DevOps: {} pods pending on {} until it scales up", cluster.pending_pods(), cluster.name);
+        }
+    }
+
+    /// Diff a plan against the infrastructure and start a run to apply it;
+    /// a run is held for an Ops change request while it has destructive
+    /// changes. Ignored while an earlier run is still going.
+    pub fn plan_infrastructure(&mut self, plan: InfraPlan, now: chrono::DateTime<chrono::Utc>) -> Option<&InfraRun> {
+        if self.infra_run.as_ref().is_some_and(|run| matches!(run.status, InfraRunStatus::AwaitingApproval | InfraRunStatus::Applying)) {
+            return None;
+        }
+        let changes = infra::diff(&plan, &self.applied_plan, &self.infrastructure_state);
+        if changes.is_empty() {
+            println!("🗺️ DevOps: Infrastructure in line with the plan");
+            return None;
+        }
+        // The same plan again means something changed outside of it
+        if self.infra_run.as_ref().is_some_and(|run| run.status == InfraRunStatus::Applied && run.plan == plan) {
+            println!("🧭 DevOps: Drift detected: {}", changes.iter().map(InfraChange::describe).collect::<Vec<_>>().join("; "));
+        }
+        for change in &changes {
+            println!("🗺️ DevOps: Plan {}", change.describe());
+        }
+
+        let run = InfraRun::new(plan, changes, now);
+        if run.status == InfraRunStatus::AwaitingApproval {
+            let destructive: Vec<String> = run.destructive_changes().map(InfraChange::describe).collect();
+            let review = MessagePayload::ChangeReview {
+                reference: run.id,
+                title: format!("Apply infrastructure plan ({} changes)", run.changes.len()),
+                description: destructive.join("\n"),
+                impact: "Infrastructure is destroyed, shrunk or restarted".to_string(),
+                rollback_plan: "Apply the previous infrastructure plan".to_string(),
+                requested_by: self.agent.id,
+            };
+            let content = format!("{} destructive infrastructure changes need approval", destructive.len());
+            self.outbox.push(review.into_message(self.agent.id, SystemActor::Orchestrator.id(), content, MessagePriority::High));
+        }
+        self.infra_run = Some(run);
+        self.infra_run.as_ref()
+    }
+
+    /// Apply or drop the run waiting on an Ops decision
+    pub fn decide_infrastructure(&mut self, run_id: Uuid, approved: bool, reason: &str, now: chrono::DateTime<chrono::Utc>) {
+        let Some(run) = self.infra_run.as_mut().filter(|run| run.id == run_id && run.status == InfraRunStatus::AwaitingApproval) else {
+            return;
+        };
+        if approved {
+            println!("✅ DevOps: Applying infrastructure plan: {}", reason);
+            run.status = InfraRunStatus::Applying;
+        } else {
+            println!("⛔ DevOps: Infrastructure plan rejected: {}", reason);
+            run.status = InfraRunStatus::Rejected;
+            run.finished_at = Some(now);
+        }
+    }
+
+    /// Apply the next change of the running plan; once all are applied the
+    /// plan is checked for drift. Returns the change applied.
+    pub fn apply_infrastructure_step(&mut self, now: chrono::DateTime<chrono::Utc>) -> Option<String> {
+        let change = self.infra_run.as_ref()
+            .filter(|run| run.status == InfraRunStatus::Applying)
+            .and_then(|run| run.changes.get(run.applied).cloned())?;
+        self.apply_change(&change);
+        let run = self.infra_run.as_mut()?;
+        run.applied += 1;
+        if run.applied == run.changes.len() {
+            run.drift = infra::diff(&run.plan, &run.plan, &self.infrastructure_state);
+            run.status = InfraRunStatus::Applied;
+            run.finished_at = Some(now);
+            self.applied_plan = run.plan.clone();
+            if run.drift.is_empty() {
+                println!("🗺️ DevOps: Infrastructure plan applied");
+            } else {
+                println!("🧭 DevOps: Infrastructure plan applied with drift: {}", run.drift.iter().map(InfraChange::describe).collect::<Vec<_>>().join("; "));
+            }
+        }
+        Some(change.describe())
+    }
+
+    fn apply_change(&mut self, change: &InfraChange) {
+        let state = &mut self.infrastructure_state;
+        match change {
+            InfraChange::CreateServer { server } => {
+                self.insert_server(server.clone());
+            }
+            InfraChange::ResizeServer { server } => {
+                // Re-provisioning restarts it
+                if let Some(current) = state.servers.values_mut().find(|current| current.hostname == server.hostname) {
+                    current.spec = Some(server.clone());
+                    current.uptime = 0;
+                }
+            }
+            InfraChange::DestroyServer { hostname } => state.servers.retain(|_, server| server.hostname != *hostname),
+            InfraChange::CreateCluster { cluster } => {
+                state.clusters.insert(cluster.name.clone(), Cluster::new(&cluster.name, cluster.nodes));
+            }
+            InfraChange::ScaleCluster { name, to, .. } => {
+                if let Some(cluster) = state.clusters.get_mut(name) {
+                    while cluster.nodes.len() < *to {
+                        cluster.add_node();
+                    }
+                    // Nodes still running pods stay, and show up as drift
+                    cluster.remove_idle_nodes(*to);
+                    cluster.schedule();
+                }
+            }
+            InfraChange::DestroyCluster { name } => {
+                state.clusters.remove(name);
+            }
+            InfraChange::ConfigureLoadBalancer { load_balancer } => {
+                state.load_balancers.insert(load_balancer.name.clone(), load_balancer.backends.clone());
+                self.update_load_balancer(&load_balancer.name, load_balancer.backends.clone());
+            }
+            InfraChange::DestroyLoadBalancer { name } => {
+                state.load_balancers.remove(name);
+                self.update_load_balancer(name, Vec::new());
+            }
+        }
+    }
+
+    /// Ask the orchestrator to have Networking carry out a load balancer change
+    fn update_load_balancer(&mut self, name: &str, backends: Vec<String>) {
+        let content = if backends.is_empty() {
+            format!("Remove load balancer {}", name)
+        } else {
+            format!("Point load balancer {} at {}", name, backends.join(", "))
+        };
+        let update = MessagePayload::LoadBalancerUpdate { name: name.to_string(), backends };
+        self.outbox.push(update.into_message(self.agent.id, SystemActor::Orchestrator.id(), content, MessagePriority::Normal));
+    }
+
+    /// Back up every server and cluster, prune backups past retention and
+    /// run a restore drill when one is due; returns what happened
+    pub fn perform_backup(&mut self, settings: &BackupSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<String> {
+        let state = &mut self.infrastructure_state;
+        // Servers hold what their disk is filled with, clusters what their pods claim
+        let mut systems: Vec<(String, u32)> = state.servers.values()
+            .map(|server| {
+                let disk_gb = server.spec.as_ref().map_or(100, |spec| spec.disk_gb);
+                (server.hostname.clone(), ((disk_gb as f32 * server.disk_usage / 100.0).round() as u32).max(1))
+            })
+            .collect();
+        systems.extend(state.clusters.values().map(|cluster| {
+            (cluster.name.clone(), cluster.pods.iter().map(|pod| pod.requests.storage_gb).sum::<u32>().max(1))
+        }));
+
+        let backups = &mut state.backups;
+        for (system, size_gb) in &systems {
+            backups.catalog.record(system, *size_gb, now);
+        }
+        let names: Vec<String> = systems.into_iter().map(|(system, _)| system).collect();
+        let pruned = backups.catalog.prune(&names, settings.retention_days, now);
+        backups.last_backup = now;
+        backups.backup_success = true;
+        backups.retention_days = settings.retention_days;
+        backups.total_backups += names.len() as u32;
+
+        let mut events = vec![format!("Backed up {} systems, pruned {} expired backups", names.len(), pruned)];
+        if backups.catalog.drill_due(settings, now) {
+            if let Some(drill) = backups.catalog.drill(settings, now, rng) {
+                events.push(if drill.succeeded {
+                    format!("Restore drill of {} passed in {} min", drill.system, drill.restore_minutes)
+                } else {
+                    format!("Restore drill of {} failed: its newest backup could not be restored", drill.system)
+                });
+            }
+        }
+        events
+    }
+
+    /// RPO and RTO as the backups and restore drills stand at `now`
+    pub fn recovery_metrics(&self, now: chrono::DateTime<chrono::Utc>) -> RecoveryMetrics {
+        self.infrastructure_state.backups.catalog.metrics(now)
+    }
+
+    /// Take in the day's patches and, while none is open, ask Ops to approve
+    /// a maintenance window for the servers that have some
+    pub fn take_patches(&mut self, advisories: &[Advisory], settings: &PatchingSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<String> {
+        let servers: Vec<String> = self.infrastructure_state.servers.values().map(|server| server.hostname.clone()).collect();
+        let released = self.patches.release(&servers, advisories, settings, now, rng);
+        let mut events = Vec::new();
+        if released > 0 {
+            events.push(format!("{} patches released, {} pending", released, self.patches.pending_count()));
+        }
+        let Some(window) = self.patches.schedule(settings, now) else {
+            return events;
+        };
+        let kind = if window.emergency { "Emergency maintenance" } else { "Maintenance" };
+        let review = MessagePayload::ChangeReview {
+            reference: window.id,
+            title: format!("{} window for {} servers", kind, window.servers.len()),
+            description: format!("Patch {} from {} to {}", window.servers.join(", "), window.starts_at.format("%Y-%m-%d %H:%M"), window.ends_at.format("%H:%M")),
+            impact: "Servers restart and stop serving for the window".to_string(),
+            rollback_plan: "Reinstall the previous package versions".to_string(),
+            requested_by: self.agent.id,
+        };
+        let content = format!("{} window for {} servers needs approval", kind, window.servers.len());
+        events.push(content.clone());
+        self.outbox.push(review.into_message(self.agent.id, SystemActor::Orchestrator.id(), content, MessagePriority::High));
+        events
+    }
+
+    /// Schedule or drop the maintenance window waiting on an Ops decision
+    pub fn decide_maintenance(&mut self, window_id: Uuid, approved: bool, reason: &str) {
+        let Some(window) = self.patches.decide(window_id, approved) else {
+            return;
+        };
+        if approved {
+            println!("🗓️ DevOps: Maintenance window at {} approved: {}", window.starts_at.format("%Y-%m-%d %H:%M"), reason);
+        } else {
+            println!("⛔ DevOps: Maintenance window rejected: {}", reason);
+        }
+    }
+
+    /// Start or finish the approved maintenance window when it is due;
+    /// servers are in maintenance while it runs
+    pub fn run_maintenance(&mut self, now: chrono::DateTime<chrono::Utc>) -> Option<WindowEvent> {
+        let event = self.patches.advance(now)?;
+        let (servers, status) = match &event {
+            WindowEvent::Started { servers, .. } => (servers, ServerState::Maintenance),
+            WindowEvent::Finished { servers, .. } => (servers, ServerState::Online),
+        };
+        for server in self.infrastructure_state.servers.values_mut().filter(|server| servers.contains(&server.hostname)) {
+            server.status = status.clone();
+            server.last_check = now;
+        }
+        Some(event)
+    }
+
+    /// Critical patches still pending on any server
+    pub fn unpatched_critical(&self) -> BTreeSet<String> {
+        self.patches.unpatched_critical()
+    }
+
+    /// Release the fix for an InfoSec remediation task onto every server;
+    /// the next maintenance window applies it
+    pub fn take_remediation(&mut self, advisory: &Advisory, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let servers: Vec<String> = self.infrastructure_state.servers.values().map(|server| server.hostname.clone()).collect();
+        self.patches.release_fix(&servers, advisory, now)
     }
 }
 
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "deploy_request",
+    "infrastructure_alert",
+    "alert_fired",
+    "alert_resolved",
+    "pipeline_event",
+    "infra_plan",
+    "change_decision",
+    "health_check",
+    "scale_request",
+    "backup_request",
+    "patch_request",
+    "remediation_task",
+    scorecard::IMPROVEMENT_TASK,
+    "work_on_improvements",
+    quota::APPROVAL_REQUEST,
+    quota::CAPACITY_REPORT,
+    "certificate_alert",
+    "capacity_request",
+    "runbook_escalation",
+];
+
 #[async_trait]
 impl AgentTrait for DevOpsAgent {
     async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match message.message_type.as_str() {
-            "deploy_request" => {
-                // Handle deployment request
-                if let Some(project_id) = message.metadata.get("project_id") {
-                    if let Ok(project_uuid) = Uuid::parse_str(project_id) {
-                        let deployment_config = DeploymentConfig {
-                            project_id: project_uuid,
-                            environment: message.metadata.get("environment").unwrap_or(&"staging".to_string()).clone(),
-                            steps: vec![
-                                DeploymentStep {
-                                    name: "Build".to_string(),
-                                    command: "cargo build --release".to_string(),
-                                    timeout_seconds: 300,
-                                    status: StepStatus::Pending,
-                                    output: None,
-                                    error: None,
-                                },
-                                DeploymentStep {
-                                    name: "Test".to_string(),
-                                    command: "cargo test".to_string(),
-                                    timeout_seconds: 600,
-                                    status: StepStatus::Pending,
-                                    output: None,
-                                    error: None,
-                                },
-                                DeploymentStep {
-                                    name: "Deploy".to_string(),
-                                    command: "./deploy.sh".to_string(),
-                                    timeout_seconds: 300,
-                                    status: StepStatus::Pending,
-                                    output: None,
-                                    error: None,
-                                },
-                            ],
-                        };
-
-                        self.deploy_application(deployment_config).await?;
+        match MessagePayload::from_message(&message)? {
+            Some(MessagePayload::DeploymentRequest { project_id, environment, commit, strategy }) => {
+                let deployment_config = DeploymentConfig {
+                    project_id,
+                    environment,
+                    commit,
+                    correlation_id: crate::timeline::correlation_id(&message),
+                    steps: vec![
+                        DeploymentStep::new("Build", "cargo build --release", 180, 300),
+                        DeploymentStep::new("Test", "cargo test", 420, 600),
+                        DeploymentStep::new("Deploy", "./deploy.sh", 120, 300),
+                    ],
+                    strategy,
+                    rollback_plan: vec![
+                        DeploymentStep::new("Rollback", "./deploy.sh --rollback", 60, 300),
+                    ],
+                };
+
+                self.deploy_application(deployment_config).await?;
+                return Ok(());
+            }
+            Some(MessagePayload::InfrastructureAlert { deployment, tenancy, affected_customers, .. }) => {
+                println!("🚨 DevOps: Investigating alert on {} ({} tenancy, {} customers affected)",
+                        deployment, tenancy.as_str(), affected_customers.len());
+                return Ok(());
+            }
+            Some(MessagePayload::AlertFired { rule, target, severity, value, .. }) => {
+                self.infrastructure_state.monitoring.active_alerts += 1;
+                println!("🚨 DevOps: {:?} alert {} on {} ({:.1})", severity, rule, target, value);
+                // A struggling cluster may just need more nodes
+                if self.infrastructure_state.clusters.contains_key(&target) {
+                    for action in self.auto_scale().await? {
+                        println!("📈 DevOps: {}", action);
                     }
                 }
+                return Ok(());
+            }
+            Some(MessagePayload::RunbookEscalation { title, affected_services, runbook, steps_tried, .. }) => {
+                println!("🧯 DevOps: Taking over '{}' on {} after runbook '{}' tried: {}",
+                        title, affected_services.join(", "), runbook, steps_tried.join(", "));
+                return Ok(());
+            }
+            Some(MessagePayload::CertificateAlert { event }) => {
+                println!("🔐 DevOps: {} ({} {})", event.description(), event.service, event.environment);
+                return Ok(());
+            }
+            Some(MessagePayload::AlertResolved { rule, target, .. }) => {
+                let monitoring = &mut self.infrastructure_state.monitoring;
+                monitoring.active_alerts = monitoring.active_alerts.saturating_sub(1);
+                println!("✅ DevOps: Alert {} on {} resolved", rule, target);
+                return Ok(());
             }
+            Some(MessagePayload::BackupRequest { at, settings }) => {
+                for event in self.perform_backup(&settings, at, &mut rand::thread_rng()) {
+                    println!("💾 DevOps: {}", event);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::ScaleRequest { policy }) => {
+                // Later checks scale by the same policy until the next request
+                self.scaling_policy = policy;
+                for action in self.auto_scale().await? {
+                    println!("📈 DevOps: {}", action);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::CapacityRequest { forecast }) => {
+                self.add_forecast_capacity(&forecast).await?;
+                return Ok(());
+            }
+            Some(MessagePayload::InfraPlanRequest { plan }) => {
+                self.plan_infrastructure(plan, message.timestamp);
+                return Ok(());
+            }
+            Some(MessagePayload::ChangeDecision { reference, approved, reason, .. }) => {
+                self.decide_infrastructure(reference, approved, &reason, message.timestamp);
+                self.decide_maintenance(reference, approved, &reason);
+                return Ok(());
+            }
+            Some(MessagePayload::RemediationTask { advisory, asset, due_at, .. }) => {
+                let released = self.take_remediation(&advisory, message.timestamp);
+                println!("🩹 DevOps: Fix for {} on {} released to {} servers, due {}",
+                        advisory.vulnerability, asset, released, due_at.format("%Y-%m-%d"));
+                return Ok(());
+            }
+            Some(MessagePayload::PatchRequest { at, settings, advisories }) => {
+                for event in self.take_patches(&advisories, &settings, at, &mut rand::thread_rng()) {
+                    println!("🩹 DevOps: {}", event);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::PipelineUpdate { commit, status, detail, .. }) => {
+                // The deployment request follows on its own once the artifact is out
+                match status {
+                    PipelineStatus::Passed => println!("📦 DevOps: Artifact for {} ready: {}", crate::vcs::short(&commit), detail),
+                    PipelineStatus::Failed => println!("🔴 DevOps: Nothing to deploy for {}: {}", crate::vcs::short(&commit), detail),
+                    PipelineStatus::Queued | PipelineStatus::Running => {}
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        match message.message_type.as_str() {
             "health_check" => {
                 // Perform health checks on all servers
                 for server_id in self.infrastructure_state.servers.keys().cloned().collect::<Vec<_>>() {
                     self.check_server_health(&server_id).await?;
                 }
                 println!("🏥 DevOps: Health check completed for all servers");
+                for event in self.check_clusters(&mut rand::thread_rng()) {
+                    println!("☸️ DevOps: {}", event);
+                }
             }
             "scale_request" => {
                 // Handle scaling request
@@ -334,8 +1060,37 @@ impl AgentTrait for DevOpsAgent {
                 }
             }
             "backup_request" => {
-                // Handle backup request
-                self.perform_backup().await?;
+                for event in self.perform_backup(&BackupSettings::default(), message.timestamp, &mut rand::thread_rng()) {
+                    println!("💾 DevOps: {}", event);
+                }
+            }
+            scorecard::IMPROVEMENT_TASK => {
+                let task_id = message.metadata.get(scorecard::TASK_ID_KEY)
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or_else(|| DevOpsError::InvalidRequest("improvement_task without a task_id".to_string()))?;
+                let service = message.metadata.get("service").cloned().unwrap_or_default();
+                println!("📋 DevOps: Took on '{}'", message.content);
+                self.improvements.push(Improvement {
+                    task_id,
+                    service,
+                    title: message.content,
+                    assigned_at: chrono::Utc::now(),
+                });
+            }
+            "work_on_improvements" => {
+                self.work_on_improvements();
+            }
+            quota::APPROVAL_REQUEST => {
+                // The approval matrix only routes increases DevOps can absorb here
+                println!("✅ DevOps: Approved {}", message.content);
+                self.decide_quota_increase(&message, true, "Within DevOps headroom".to_string())?;
+            }
+            quota::CAPACITY_REPORT => {
+                let report: Vec<QuotaUtilization> = serde_json::from_str(&message.content)?;
+                let high_water = message.metadata.get("high_water")
+                    .and_then(|percent| percent.parse().ok())
+                    .unwrap_or(80.0);
+                self.plan_capacity(&report, high_water).await?;
             }
             _ => {
                 println!("🤷 DevOps: Unknown message type: {}", message.message_type);
@@ -361,13 +1116,13 @@ impl AgentTrait for DevOpsAgent {
             metadata: HashMap::new(),
         }).await?;
 
-        // Backups
+        // One improvement task a day
         self.process_message(Message {
             id: Uuid::new_v4(),
             from_agent: self.agent.id,
             to_agent: self.agent.id,
-            message_type: "backup_request".to_string(),
-            content: "Daily backup".to_string(),
+            message_type: "work_on_improvements".to_string(),
+            content: "Daily improvement work".to_string(),
             priority: MessagePriority::Normal,
             timestamp: chrono::Utc::now(),
             metadata: HashMap::new(),
@@ -414,18 +1169,181 @@ impl Default for InfrastructureState {
                 backup_success: true,
                 retention_days: 30,
                 total_backups: 0,
+                catalog: BackupCatalog::new(),
             },
+            load_balancers: BTreeMap::new(),
         }
     }
 }
 
+/// Where a step's runs got to by `now`
+enum StepOutcome {
+    Running,
+    Succeeded(chrono::DateTime<chrono::Utc>),
+    /// Out of attempts, with the last error
+    Failed(chrono::DateTime<chrono::Utc>, String),
+}
+
+/// Run a step's attempts forward to `now`, the first starting at `cursor`
+fn run_step(step: &mut DeploymentStep, mut cursor: chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>, skill_failure: f64, rng: &mut impl Rng) -> StepOutcome {
+    loop {
+        let Some(ends_at) = step.ends_at().filter(|_| step.status == StepStatus::Running) else {
+            // Start the next attempt
+            let factor = rng.gen_range(0.5..1.5);
+            step.status = StepStatus::Running;
+            step.attempts += 1;
+            step.started_at = Some(cursor);
+            step.run_seconds = Some(((step.duration_seconds as f64 * factor).round() as u32).max(1));
+            step.finished_at = None;
+            continue;
+        };
+        if ends_at > now {
+            return StepOutcome::Running;
+        }
+
+        let run_seconds = step.run_seconds.unwrap_or(0);
+        let failure = if run_seconds > step.timeout_seconds {
+            Some(format!("timed out after {}s", step.timeout_seconds))
+        } else if rng.gen_bool(step.failure_probability.unwrap_or(skill_failure).clamp(0.0, 1.0)) {
+            Some(format!("exited with status 1 after {}s", run_seconds))
+        } else {
+            None
+        };
+        step.finished_at = Some(ends_at);
+        cursor = ends_at;
+        let Some(error) = failure else {
+            step.status = StepStatus::Success;
+            step.error = None;
+            step.output = Some(format!("$ {}\n{} finished in {}s (attempt {})", step.command, step.name, run_seconds, step.attempts));
+            return StepOutcome::Succeeded(ends_at);
+        };
+
+        step.status = StepStatus::Failed;
+        step.output = Some(format!("$ {}\n{} {}", step.command, step.name, error));
+        step.error = Some(format!("{} {}", step.name, error));
+        if step.attempts >= MAX_STEP_ATTEMPTS {
+            return StepOutcome::Failed(ends_at, format!("{} {} on attempt {}", step.name, error, step.attempts));
+        }
+        println!("🔁 DevOps: {} step {}, retrying", step.name, error);
+    }
+}
+
+/// Start undoing a failed release: traffic goes back to the running release
+/// and the rollback plan runs; false if there is nothing to undo
+fn start_rollback(deployment: &mut Deployment, reason: String, shifts: &mut Vec<MessagePayload>) -> bool {
+    if deployment.rollback_plan.is_empty() && deployment.traffic_percent == 0 {
+        return false;
+    }
+    if deployment.traffic_percent > 0 {
+        deployment.traffic_percent = 0;
+        shifts.push(traffic_shift(deployment));
+    }
+    println!("↩️ DevOps: Rolling back deployment {} to {}: {}", deployment.id, deployment.environment, reason);
+    deployment.rollback_reason = Some(reason);
+    true
+}
+
+fn traffic_shift(deployment: &Deployment) -> MessagePayload {
+    MessagePayload::TrafficShift {
+        deployment_id: deployment.id,
+        environment: deployment.environment.clone(),
+        percent: deployment.traffic_percent,
+    }
+}
+
+/// Run a deployment forward to `now`; true once it shipped, failed or rolled back
+fn run_deployment(deployment: &mut Deployment, now: chrono::DateTime<chrono::Utc>, skill_failure: f64, rng: &mut impl Rng, shifts: &mut Vec<MessagePayload>) -> bool {
+    // The first step starts now; later ones when the step before them ended
+    let mut cursor = now;
+    loop {
+        if deployment.rollback_reason.is_some() {
+            let Some(step) = deployment.rollback_plan.get_mut(deployment.rollback_step) else {
+                deployment.status = DeploymentStatus::RolledBack;
+                deployment.finished_at = Some(cursor);
+                println!("↩️ DevOps: Deployment {} to {} rolled back", deployment.id, deployment.environment);
+                return true;
+            };
+            match run_step(step, cursor, now, skill_failure, rng) {
+                StepOutcome::Running => return false,
+                StepOutcome::Succeeded(at) => {
+                    cursor = at;
+                    deployment.rollback_step += 1;
+                }
+                StepOutcome::Failed(at, error) => {
+                    deployment.status = DeploymentStatus::Failed;
+                    deployment.finished_at = Some(at);
+                    println!("❌ DevOps: Rollback of deployment {} to {} failed: {}", deployment.id, deployment.environment, error);
+                    return true;
+                }
+            }
+            continue;
+        }
+
+        if let Some(step) = deployment.steps.get_mut(deployment.current_step) {
+            match run_step(step, cursor, now, skill_failure, rng) {
+                StepOutcome::Running => return false,
+                StepOutcome::Succeeded(at) => {
+                    cursor = at;
+                    deployment.current_step += 1;
+                }
+                StepOutcome::Failed(at, error) => {
+                    for skipped in deployment.steps.iter_mut().skip(deployment.current_step + 1) {
+                        skipped.status = StepStatus::Skipped;
+                    }
+                    cursor = at;
+                    if !start_rollback(deployment, error.clone(), shifts) {
+                        deployment.status = DeploymentStatus::Failed;
+                        deployment.finished_at = Some(at);
+                        println!("❌ DevOps: Deployment {} to {} failed: {}", deployment.id, deployment.environment, error);
+                        return true;
+                    }
+                }
+            }
+            continue;
+        }
+
+        // Steps done; shift traffic stage by stage
+        let Some(percent) = deployment.strategy.traffic_stages().get(deployment.traffic_stage).copied() else {
+            deployment.status = DeploymentStatus::Success;
+            deployment.finished_at = Some(cursor);
+            println!("✅ DevOps: Deployment {} to {} shipped", deployment.id, deployment.environment);
+            return true;
+        };
+        let Some(stage_started_at) = deployment.stage_started_at else {
+            deployment.traffic_percent = percent;
+            deployment.stage_started_at = Some(cursor);
+            shifts.push(traffic_shift(deployment));
+            println!("🔀 DevOps: Deployment {} takes {}% of {} traffic", deployment.id, percent, deployment.environment);
+            continue;
+        };
+        let baked_at = stage_started_at + deployment.strategy.bake_time();
+        if baked_at > now {
+            return false;
+        }
+        cursor = baked_at;
+        if rng.gen_bool(skill_failure.clamp(0.0, 1.0)) {
+            start_rollback(deployment, format!("unhealthy at {}% of traffic", percent), shifts);
+            continue;
+        }
+        deployment.traffic_stage += 1;
+        deployment.stage_started_at = None;
+    }
+}
+
 /// Configuration for server provisioning
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub hostname: String,
     pub cpu_cores: u32,
     pub memory_gb: u32,
     pub disk_gb: u32,
+    /// Whether the server's disks are encrypted at rest
+    #[serde(default = "default_encrypted")]
+    pub encrypted: bool,
+}
+
+fn default_encrypted() -> bool {
+    true
 }
 
 /// Configuration for application deployment
@@ -434,6 +1352,14 @@ pub struct DeploymentConfig {
     pub project_id: Uuid,
     pub environment: String,
     pub steps: Vec<DeploymentStep>,
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub strategy: DeploymentStrategy,
+    #[serde(default)]
+    pub rollback_plan: Vec<DeploymentStep>,
 }
 
 /// DevOps-specific errors
@@ -450,11 +1376,15 @@ pub enum DevOpsError {
 
     #[error("Monitoring error: {0}")]
     MonitoringError(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
 
     #[tokio::test]
     async fn test_devops_agent_creation() {
@@ -473,6 +1403,7 @@ mod tests {
             cpu_cores: 4,
             memory_gb: 8,
             disk_gb: 100,
+            encrypted: true,
         };
 
         let result = agent.provision_server(config).await;
@@ -483,6 +1414,21 @@ mod tests {
         assert_eq!(server.status, ServerState::Online);
     }
 
+    #[tokio::test]
+    async fn test_capacity_planned_for_full_quotas() {
+        let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
+        let limit = quota::Resources::new(8, 16, 100);
+        let report = vec![
+            QuotaUtilization { scope: "team:Engineering".to_string(), used: quota::Resources::new(7, 8, 40), limit, percent: 87.5 },
+            QuotaUtilization { scope: "team:Ops".to_string(), used: quota::Resources::new(2, 4, 20), limit, percent: 25.0 },
+        ];
+
+        let provisioned = agent.plan_capacity(&report, 80.0).await.unwrap();
+        assert_eq!(provisioned.len(), 1);
+        assert!(provisioned[0].hostname.starts_with("team-Engineering-capacity"));
+        assert_eq!(agent.infrastructure_state.servers.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_deployment_creation() {
         let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
@@ -490,9 +1436,143 @@ mod tests {
             project_id: Uuid::new_v4(),
             environment: "staging".to_string(),
             steps: vec![],
+            correlation_id: None,
+            commit: None,
+            strategy: DeploymentStrategy::AllAtOnce,
+            rollback_plan: vec![],
         };
 
         let result = agent.deploy_application(config).await;
         assert!(result.is_ok());
+
+        // Without steps the deployment ships on the first pass
+        let deployment_id = result.unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let now = chrono::Utc::now();
+        assert_eq!(agent.advance_deployments(now, &mut rng), vec![deployment_id]);
+        assert_eq!(agent.active_deployments[&deployment_id].status, DeploymentStatus::Success);
+        assert!(agent.advance_deployments(now, &mut rng).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deployment_steps_run_retry_and_time_out() {
+        let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let start = chrono::Utc::now();
+        let sure = |mut step: DeploymentStep| { step.failure_probability = Some(0.0); step };
+        let steps = vec![
+            sure(DeploymentStep::new("Build", "make", 100, 1_000)),
+            // Always outlasts its timeout
+            sure(DeploymentStep::new("Migrate", "./migrate.sh", 100, 10)),
+            sure(DeploymentStep::new("Deploy", "./deploy.sh", 100, 1_000)),
+        ];
+        let config = DeploymentConfig { project_id: Uuid::new_v4(), environment: "production".to_string(), steps, correlation_id: None, commit: None, strategy: DeploymentStrategy::AllAtOnce, rollback_plan: vec![] };
+        let deployment_id = agent.deploy_application(config).await.unwrap();
+
+        // Build takes 50-150s, so it is still running a moment in
+        assert!(agent.advance_deployments(start, &mut rng).is_empty());
+        let deployment = agent.deployment(deployment_id).unwrap();
+        assert_eq!(deployment.status, DeploymentStatus::InProgress);
+        assert_eq!(deployment.running_step().unwrap().status, StepStatus::Running);
+
+        // An hour on, Build shipped and Migrate timed out on every attempt
+        assert_eq!(agent.advance_deployments(start + chrono::Duration::hours(1), &mut rng), vec![deployment_id]);
+        let deployment = agent.deployment(deployment_id).unwrap();
+        assert_eq!(deployment.status, DeploymentStatus::Failed);
+        assert_eq!(deployment.progress(), (1, 3));
+        assert_eq!(deployment.steps[1].attempts, MAX_STEP_ATTEMPTS);
+        assert_eq!(deployment.steps[1].error.as_deref(), Some("Migrate timed out after 10s"));
+        assert_eq!(deployment.steps[2].status, StepStatus::Skipped);
+        assert!(deployment.steps[0].output.as_deref().unwrap().starts_with("$ make"));
+    }
+
+    #[tokio::test]
+    async fn test_canary_shifts_traffic_and_rolls_back() {
+        let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let start = chrono::Utc::now();
+        let sure = |mut step: DeploymentStep| { step.failure_probability = Some(0.0); step };
+        let canary = DeploymentStrategy::Canary { traffic_percent: vec![10, 50], bake_minutes: 30 };
+        assert_eq!(canary.traffic_stages(), vec![10, 50, 100]);
+
+        // A perfect agent never fails a health check, so the canary ships
+        agent.deployment_skill = 100;
+        let config = DeploymentConfig {
+            project_id: Uuid::new_v4(),
+            environment: "production".to_string(),
+            steps: vec![sure(DeploymentStep::new("Deploy", "./deploy.sh", 60, 1_000))],
+            correlation_id: None,
+            commit: None,
+            strategy: canary.clone(),
+            rollback_plan: vec![sure(DeploymentStep::new("Rollback", "./deploy.sh --rollback", 60, 1_000))],
+        };
+        let shipped = agent.deploy_application(config.clone()).await.unwrap();
+        assert!(agent.advance_deployments(start + chrono::Duration::minutes(10), &mut rng).is_empty());
+        assert_eq!(agent.deployment(shipped).unwrap().traffic_percent, 10);
+        assert_eq!(agent.advance_deployments(start + chrono::Duration::hours(3), &mut rng), vec![shipped]);
+        let deployment = agent.deployment(shipped).unwrap();
+        assert_eq!((deployment.status.clone(), deployment.traffic_percent), (DeploymentStatus::Success, 100));
+        let shifts: Vec<u8> = agent.outbox.iter().filter_map(|message| match MessagePayload::from_message(message) {
+            Ok(Some(MessagePayload::TrafficShift { percent, .. })) => Some(percent),
+            _ => None,
+        }).collect();
+        assert_eq!(shifts, vec![10, 50, 100]);
+
+        // A failing Deploy step runs the rollback plan instead
+        agent.outbox.clear();
+        let mut config = config;
+        config.steps = vec![DeploymentStep { failure_probability: Some(1.0), ..DeploymentStep::new("Deploy", "./deploy.sh", 60, 1_000) }];
+        let rolled_back = agent.deploy_application(config).await.unwrap();
+        assert_eq!(agent.advance_deployments(start + chrono::Duration::hours(1), &mut rng), vec![rolled_back]);
+        let deployment = agent.deployment(rolled_back).unwrap();
+        assert_eq!(deployment.status, DeploymentStatus::RolledBack);
+        assert_eq!(deployment.rollback_plan[0].status, StepStatus::Success);
+        assert!(deployment.rollback_reason.as_deref().unwrap().starts_with("Deploy exited with status 1"));
+        // No traffic had moved yet, so none moves back
+        assert!(agent.outbox.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shipped_projects_scale_cluster() {
+        let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
+        // Two 16-core nodes hold 16 two-core pods, so the ninth project waits
+        for _ in 0..9 {
+            agent.place_workload("production", Uuid::new_v4());
+        }
+        let cluster = &agent.infrastructure_state.clusters["production"];
+        assert_eq!((cluster.nodes.len(), cluster.pending_pods()), (2, 2));
+
+        let actions = agent.auto_scale().await.unwrap();
+        assert!(actions[0].starts_with("Scaled up: added node production-node-3"));
+        let cluster = &agent.infrastructure_state.clusters["production"];
+        assert_eq!((cluster.nodes.len(), cluster.pending_pods()), (3, 0));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_infra_plan_waits_for_approval_and_detects_drift() {
+        let mut agent = DevOpsAgent::new("Test Agent".to_string(), None);
+        let now = chrono::Utc::now();
+        let server = |hostname: &str| ServerConfig { hostname: hostname.to_string(), cpu_cores: 4, memory_gb: 16, disk_gb: 100, encrypted: true };
+        let plan = InfraPlan { servers: vec![server("web-1"), server("web-2")], ..InfraPlan::default() };
+
+        // Creating servers needs no approval
+        agent.plan_infrastructure(plan.clone(), now);
+        while agent.apply_infrastructure_step(now).is_some() {}
+        assert_eq!(agent.infra_run.as_ref().unwrap().status, InfraRunStatus::Applied);
+        assert_eq!(agent.infrastructure_state.servers.len(), 2);
+
+        // Dropping one is destructive and waits for Ops
+        let smaller = InfraPlan { servers: vec![server("web-1")], ..InfraPlan::default() };
+        let run_id = agent.plan_infrastructure(smaller, now).unwrap().id;
+        assert!(agent.apply_infrastructure_step(now).is_none());
+        assert_eq!(agent.outbox.last().unwrap().message_type, "change_request");
+        agent.decide_infrastructure(run_id, true, "Approved", now);
+        assert_eq!(agent.apply_infrastructure_step(now).as_deref(), Some("- server web-2"));
+        assert_eq!(agent.infrastructure_state.servers.len(), 1);
+
+        // A server removed by hand shows up on the next check
+        agent.infrastructure_state.servers.clear();
+        let run = agent.plan_infrastructure(agent.applied_plan.clone(), now).unwrap();
+        assert_eq!(run.changes, vec![InfraChange::CreateServer { server: server("web-1") }]);
+    }
+}
@@ -0,0 +1,291 @@
+//! Engineering Department - Product Development & Source Control
+//!
+//! This module implements the Engineering department responsible for:
+//! - Building customer projects on feature branches
+//! - Committing work in small steps and merging finished features
+//! - Reverting releases that caused production trouble
+//! - Fixing the tests of commits whose CI/CD pipeline failed
+//!
+//! The repository itself belongs to the orchestrator; engineers send it
+//! `VcsRequest`s through their outbox, much as Marketing sends its reports.
+
+use crate::agents::{Agent, AgentTrait, Department};
+use crate::communication::{Message, MessagePriority};
+use crate::payload::MessagePayload;
+use crate::pipeline::PipelineStatus;
+use crate::system::SystemActor;
+use crate::vcs::{self, VcsRequest};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Kinds of change an engineer commits while building a feature
+const CHANGE_VERBS: [&str; 5] = ["Add", "Refactor", "Fix", "Test", "Document"];
+
+/// Engineering Agent specialized in building product features
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineeringAgent {
+    /// Base agent properties
+    pub agent: Agent,
+    /// Feature development skill
+    pub coding_skill: u8,
+    /// Code review skill
+    pub review_skill: u8,
+    /// Features being built, keyed by project id
+    pub features: HashMap<Uuid, Feature>,
+    /// Messages waiting to be picked up by the orchestrator
+    pub outbox: Vec<Message>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feature {
+    pub project_id: Uuid,
+    pub branch: String,
+    pub commits_planned: u32,
+    pub commits_made: u32,
+    pub status: FeatureStatus,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FeatureStatus {
+    InProgress,
+    /// Merge requested from the orchestrator
+    Merged,
+}
+
+impl EngineeringAgent {
+    /// Create a new Engineering agent
+    pub fn new(name: String, manager_id: Option<Uuid>) -> Self {
+        Self {
+            agent: Agent::new(name, Department::Engineering, manager_id),
+            coding_skill: 85,
+            review_skill: 75,
+            features: HashMap::new(),
+            outbox: vec![],
+        }
+    }
+
+    /// Start a feature branch for a project with its first commit
+    pub fn start_feature(&mut self, project_id: Uuid) -> Result<&Feature, EngineeringError> {
+        if self.features.contains_key(&project_id) {
+            return Err(EngineeringError::FeatureExists(project_id));
+        }
+
+        // Stronger engineers get features done in fewer, larger commits
+        let commits_planned = 2 + rand::random::<u32>() % 3 + u32::from(self.coding_skill < 70);
+        let branch = format!("feature/{}", &project_id.simple().to_string()[..8]);
+        self.features.insert(project_id, Feature {
+            project_id,
+            branch: branch.clone(),
+            commits_planned,
+            commits_made: 0,
+            status: FeatureStatus::InProgress,
+            started_at: chrono::Utc::now(),
+        });
+
+        println!("🌱 Engineering: Started {} for project {}", branch, project_id.simple());
+        self.commit_work(project_id)?;
+        Ok(&self.features[&project_id])
+    }
+
+    /// Commit the next piece of a feature, asking to merge once it is done
+    pub fn commit_work(&mut self, project_id: Uuid) -> Result<(), EngineeringError> {
+        let feature = self.features.get_mut(&project_id)
+            .ok_or(EngineeringError::FeatureNotFound(project_id))?;
+        if feature.status != FeatureStatus::InProgress {
+            return Ok(());
+        }
+
+        let verb = CHANGE_VERBS[feature.commits_made as usize % CHANGE_VERBS.len()];
+        let commit = VcsRequest::Commit {
+            branch: feature.branch.clone(),
+            message: format!("{} project {} part {}", verb, &project_id.simple().to_string()[..8], feature.commits_made + 1),
+            project_id: Some(project_id),
+        };
+        feature.commits_made += 1;
+
+        let merge = (feature.commits_made >= feature.commits_planned).then(|| {
+            feature.status = FeatureStatus::Merged;
+            VcsRequest::Merge { branch: feature.branch.clone(), project_id: Some(project_id) }
+        });
+
+        self.queue_vcs_request(&commit)?;
+        if let Some(merge) = merge {
+            println!("🔀 Engineering: Requesting merge of project {}", project_id.simple());
+            self.queue_vcs_request(&merge)?;
+        }
+        Ok(())
+    }
+
+    /// Fix the tests that failed a commit's pipeline on a branch of its own
+    /// and merge it, which runs the pipeline again
+    fn fix_pipeline(&mut self, project_id: Uuid, commit: &str, detail: &str) -> Result<(), EngineeringError> {
+        let branch = format!("fix/{}", vcs::short(commit));
+        let project_id = Some(project_id).filter(|id| !id.is_nil());
+        println!("🔧 Engineering: Pipeline for {} failed ({}), fixing on {}", vcs::short(commit), detail, branch);
+        self.queue_vcs_request(&VcsRequest::Commit {
+            branch: branch.clone(),
+            message: format!("Fix tests failing the pipeline of {}", vcs::short(commit)),
+            project_id,
+        })?;
+        self.queue_vcs_request(&VcsRequest::Merge { branch, project_id })
+    }
+
+    /// Queue a repository change for the orchestrator
+    fn queue_vcs_request(&mut self, request: &VcsRequest) -> Result<(), EngineeringError> {
+        let content = serde_json::to_string(request)
+            .map_err(|e| EngineeringError::RequestFailed(e.to_string()))?;
+
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: SystemActor::Orchestrator.id(),
+            message_type: vcs::VCS_REQUEST.to_string(),
+            content,
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        });
+        Ok(())
+    }
+}
+
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "pipeline_event",
+    "project_assignment",
+    "revert_request",
+    "work_on_features",
+];
+
+#[async_trait]
+impl AgentTrait for EngineeringAgent {
+    async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(MessagePayload::ProjectAssignment { project_id }) = MessagePayload::from_message(&message)? {
+            // A project assigned twice keeps its existing branch
+            if !self.features.contains_key(&project_id) {
+                self.start_feature(project_id)?;
+            }
+            return Ok(());
+        }
+        if let Some(MessagePayload::PipelineUpdate { project_id, commit, status: PipelineStatus::Failed, detail, .. }) = MessagePayload::from_message(&message)? {
+            self.fix_pipeline(project_id, &commit, &detail)?;
+            return Ok(());
+        }
+
+        match message.message_type.as_str() {
+            "revert_request" => {
+                let commit = message.metadata.get("commit")
+                    .ok_or_else(|| EngineeringError::RequestFailed("revert_request without a commit".to_string()))?;
+                println!("⏪ Engineering: Reverting {}: {}", vcs::short(commit), message.content);
+                self.queue_vcs_request(&VcsRequest::Revert { commit: commit.clone(), reason: message.content.clone() })?;
+            }
+            "work_on_features" => {
+                let in_progress: Vec<Uuid> = self.features.values()
+                    .filter(|f| f.status == FeatureStatus::InProgress)
+                    .map(|f| f.project_id)
+                    .collect();
+                for project_id in in_progress {
+                    self.commit_work(project_id)?;
+                }
+            }
+            _ => {
+                println!("🤷 Engineering: Unknown message type: {}", message.message_type);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn perform_daily_tasks(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        println!("💻 Engineering: Performing daily development tasks...");
+
+        self.process_message(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: self.agent.id,
+            message_type: "work_on_features".to_string(),
+            content: "Daily feature work".to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }).await?;
+
+        Ok(())
+    }
+
+    fn get_agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    fn get_agent_mut(&mut self) -> &mut Agent {
+        &mut self.agent
+    }
+}
+
+/// Engineering-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum EngineeringError {
+    #[error("Feature already started for project {0}")]
+    FeatureExists(Uuid),
+
+    #[error("No feature for project {0}")]
+    FeatureNotFound(Uuid),
+
+    #[error("Repository request failed: {0}")]
+    RequestFailed(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::DepartmentAgent;
+
+    #[tokio::test]
+    async fn test_feature_is_committed_then_merged() {
+        let mut agent = EngineeringAgent::new("Test Engineering Agent".to_string(), None);
+        let project_id = Uuid::new_v4();
+        let assignment = MessagePayload::ProjectAssignment { project_id }
+            .into_message(SystemActor::Scheduler.id(), agent.agent.id, "Assigned".to_string(), MessagePriority::Normal);
+        agent.process_message(assignment).await.unwrap();
+
+        let planned = agent.features[&project_id].commits_planned;
+        for _ in 1..planned {
+            agent.perform_daily_tasks().await.unwrap();
+        }
+        assert_eq!(agent.features[&project_id].status, FeatureStatus::Merged);
+
+        let requests: Vec<VcsRequest> = agent.drain_outbox().iter()
+            .map(|m| serde_json::from_str(&m.content).unwrap())
+            .collect();
+        assert_eq!(requests.len() as u32, planned + 1);
+        assert!(requests[..planned as usize].iter().all(|r| matches!(r, VcsRequest::Commit { .. })));
+        assert!(matches!(requests.last(), Some(VcsRequest::Merge { project_id: Some(id), .. }) if *id == project_id));
+
+        // Finished features get no more commits
+        agent.perform_daily_tasks().await.unwrap();
+        assert!(agent.drain_outbox().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_pipeline_is_fixed_and_merged() {
+        let mut agent = EngineeringAgent::new("Test Engineering Agent".to_string(), None);
+        let failed = MessagePayload::PipelineUpdate {
+            pipeline_id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            commit: "a1b2c3d4e5f6".to_string(),
+            stage: crate::pipeline::Stage::Test,
+            status: PipelineStatus::Failed,
+            detail: "tests failed on all 3 runs".to_string(),
+        };
+        agent.process_message(failed.into_message(SystemActor::Orchestrator.id(), agent.agent.id, "Failed".to_string(), MessagePriority::High)).await.unwrap();
+
+        let requests: Vec<VcsRequest> = agent.drain_outbox().iter()
+            .map(|m| serde_json::from_str(&m.content).unwrap())
+            .collect();
+        assert!(matches!(&requests[0], VcsRequest::Commit { branch, project_id: None, .. } if branch == "fix/a1b2c3d"));
+        assert!(matches!(&requests[1], VcsRequest::Merge { branch, .. } if branch == "fix/a1b2c3d"));
+    }
+}
@@ -7,17 +7,67 @@
 //! - Compliance monitoring and reporting
 //! - Security policy enforcement
 //! - Data protection and encryption
+//! - Security fixes handed to DevOps as patches; critical ones left
+//!   unpatched lower the posture score, and patched ones close their
+//!   vulnerabilities
+//! - Weekly scans of an asset inventory of servers, network services and
+//!   applications against a vulnerability catalog, with findings tracked
+//!   per asset until they are remediated
+//! - Remediation tasks assigned to DevOps for Critical and High findings,
+//!   due by severity; a patched finding is only closed once a re-scan of
+//!   its asset no longer finds it
+//! - Compliance scores derived from security policies (MFA, encryption at
+//!   rest, firewall default-deny, backup retention, access reviews) checked
+//!   against evidence of the actual DevOps, Networking and identity state;
+//!   violated policies stay open compliance issues
+//! - Results of simulated phishing campaigns: every click is a security
+//!   event, and the agents who clicked are booked into security-awareness
+//!   training the next day
+//! - Threat advisories from the intelligence feeds: every threat sighted in
+//!   the company's traffic is recorded as suspicious activity
+//! - DLP exposures: classified data reaching a lower-security segment is a
+//!   data breach, sized by the records and bytes exposed
+//! - Post-mortems of resolved security incidents, referenced by the next
+//!   compliance audit along with their action items
+//! - Firewall analyses: each newly found shadowed, conflicting, redundant or
+//!   overly broad rule is a policy violation
+//! - Certificate alerts: a TLS certificate about to expire, failing to
+//!   renew or lapsed is a policy violation, critical on production
 
 use crate::agents::{Agent, AgentTrait, Department};
+use crate::certs::CertificateEvent;
 use crate::communication::{Message, MessageBus, MessagePriority};
+use crate::config::{SecurityPolicySettings, ScanSettings};
+use crate::ddos::{DdosEvent, DdosEventKind};
+use crate::dlp::{Classification, Exposure};
+use crate::firewall::FirewallReport;
+use crate::forensics::PostMortem;
+use crate::payload::MessagePayload;
+use crate::patching::Advisory;
+use crate::phishing::PhishingTarget;
 use crate::projects::{Project, Task};
+use crate::remediation::{RemediationTask, RemediationTracker};
+use crate::secpolicy::{self, PolicyEvaluation, PolicyEvidence, Standard};
+use crate::system::SystemActor;
+use crate::threatintel::ThreatAdvisory;
+use crate::vulnscan::{self, AgingReport, AssetInventory, AssetKind, Remediation, VulnerabilityCatalog};
 use async_trait::async_trait;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Posture points each critical fix DevOps has yet to apply costs
+const UNPATCHED_CRITICAL_PENALTY: i32 = 15;
+
+/// Days after a phishing campaign its clickers are trained
+const TRAINING_LEAD_DAYS: i64 = 1;
+
+/// Post-mortems kept; the oldest go first
+const MAX_POST_MORTEMS: usize = 100;
+
 /// InfoSec Agent specialized in cybersecurity and threat protection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InfoSecAgent {
@@ -35,6 +85,40 @@ pub struct InfoSecAgent {
     pub active_incidents: HashMap<Uuid, SecurityIncident>,
     /// Security policies and compliance status
     pub compliance_status: ComplianceStatus,
+    /// Vulnerabilities found by scans and not yet remediated
+    #[serde(default)]
+    pub open_vulnerabilities: Vec<Vulnerability>,
+    /// Servers, network services and applications scans cover
+    #[serde(default)]
+    pub inventory: AssetInventory,
+    /// Known vulnerabilities, generated on the first scan
+    #[serde(default)]
+    pub catalog: VulnerabilityCatalog,
+    /// Settings of the latest scan request
+    #[serde(default)]
+    pub scan_settings: ScanSettings,
+    /// Findings closed, oldest first
+    #[serde(default)]
+    pub remediated: Vec<Remediation>,
+    /// Tasks DevOps works on for Critical and High findings
+    #[serde(default)]
+    pub remediation_tasks: RemediationTracker,
+    /// Messages waiting to be picked up by the orchestrator
+    #[serde(default)]
+    pub outbox: Vec<Message>,
+    /// Security policies audits check
+    #[serde(default)]
+    pub policy_settings: SecurityPolicySettings,
+    /// The company's actual state as last reported, for policies to be
+    /// checked against
+    #[serde(default)]
+    pub policy_evidence: Option<PolicyEvidence>,
+    /// Post-mortems of resolved incidents, oldest first
+    #[serde(default)]
+    pub post_mortems: Vec<PostMortem>,
+    /// Latest analysis of Networking's firewall rules
+    #[serde(default)]
+    pub firewall_report: Option<FirewallReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +133,9 @@ pub struct SecurityPosture {
     pub recent_events: Vec<SecurityEvent>,
     /// Last assessment date
     pub last_assessment: chrono::DateTime<chrono::Utc>,
+    /// Critical fixes pending on DevOps servers
+    #[serde(default)]
+    pub unpatched_critical: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +195,8 @@ pub enum EventType {
     PolicyViolation,
     SuspiciousActivity,
     SystemCompromise,
+    /// An agent clicked a simulated phishing lure
+    PhishingClick,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -131,6 +220,11 @@ pub struct SecurityIncident {
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub resolution_steps: Vec<String>,
     pub affected_systems: Vec<String>,
+    /// Messages that reported the incident
+    #[serde(default)]
+    pub source_messages: Vec<Uuid>,
+    #[serde(default)]
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -177,38 +271,339 @@ impl InfoSecAgent {
             security_posture: SecurityPosture::default(),
             active_incidents: HashMap::new(),
             compliance_status: ComplianceStatus::default(),
+            open_vulnerabilities: Vec::new(),
+            inventory: AssetInventory::new(),
+            catalog: VulnerabilityCatalog::default(),
+            scan_settings: ScanSettings::default(),
+            remediated: Vec::new(),
+            remediation_tasks: RemediationTracker::new(),
+            outbox: Vec::new(),
+            policy_settings: SecurityPolicySettings::default(),
+            policy_evidence: None,
+            post_mortems: Vec::new(),
+            firewall_report: None,
         }
     }
 
-    /// Perform security vulnerability scan
-    pub async fn perform_vulnerability_scan(&mut self, target: &str) -> Result<ScanResults, InfoSecError> {
-        println!("🔍 InfoSec: Starting vulnerability scan on {}", target);
+    /// Open vulnerabilities per affected system
+    pub fn open_vulnerabilities_by_system(&self) -> BTreeMap<String, u32> {
+        let mut counts = BTreeMap::new();
+        for vulnerability in &self.open_vulnerabilities {
+            *counts.entry(vulnerability.affected_system.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Patch every open vulnerability of a system; returns how many were fixed
+    pub fn remediate_vulnerabilities(&mut self, system: &str, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let fixed = self.remediate(|vulnerability| vulnerability.affected_system == system, now);
+        println!("🩹 InfoSec: Remediated {} vulnerabilities in {}", fixed, system);
+        fixed
+    }
+
+    /// Re-scan the assets with vulnerabilities every server is now patched
+    /// for. Findings the re-scan no longer finds are closed with their
+    /// tasks; tasks whose fix did not take go back to DevOps. Returns how
+    /// many findings were closed.
+    pub fn verify_patched(&mut self, vulnerabilities: &[String], fixed_by: Uuid, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> usize {
+        self.remediation_tasks.fix_applied(vulnerabilities, fixed_by, now);
+        let patched: Vec<(String, String)> = self.open_vulnerabilities.iter()
+            .filter(|vulnerability| vulnerabilities.contains(&vulnerability.id))
+            .map(|vulnerability| (vulnerability.id.clone(), vulnerability.affected_system.clone()))
+            .collect();
+        let failure = self.scan_settings.verification_failure_probability.clamp(0.0, 1.0);
+        let mut closed = 0;
+        for (id, asset) in patched {
+            let still_present = rng.gen_bool(failure);
+            if let Some(task) = self.remediation_tasks.verify(&id, &asset, still_present, now).cloned() {
+                println!("⚠️ InfoSec: Re-scan still finds {} on {}, back to DevOps", id, asset);
+                self.assign_remediation(&task, now);
+            }
+            if still_present {
+                for vulnerability in self.open_vulnerabilities.iter_mut().filter(|vulnerability| vulnerability.id == id && vulnerability.affected_system == asset) {
+                    vulnerability.last_seen = Some(now);
+                    vulnerability.times_seen += 1;
+                }
+            } else {
+                closed += self.remediate(|vulnerability| vulnerability.id == id && vulnerability.affected_system == asset, now);
+            }
+        }
+        closed
+    }
 
-        // Simulate vulnerability scanning
-        let vulnerabilities = vec![
-            Vulnerability {
-                id: format!("CVE-2024-{}", rand::random::<u32>() % 10000),
-                title: "Sample Vulnerability".to_string(),
+    /// Open remediation tasks for new Critical and High findings and send
+    /// them to DevOps; returns how many were opened
+    pub fn open_remediation_tasks(&mut self, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let opened = self.remediation_tasks.open(&self.open_vulnerabilities, &self.scan_settings.remediation_days, now);
+        for task in &opened {
+            self.assign_remediation(task, now);
+        }
+        opened.len()
+    }
+
+    fn assign_remediation(&mut self, task: &RemediationTask, now: chrono::DateTime<chrono::Utc>) {
+        let payload = MessagePayload::RemediationTask {
+            task_id: task.id,
+            advisory: Advisory { vulnerability: task.vulnerability.clone(), title: task.title.clone(), severity: task.severity.clone() },
+            asset: task.asset.clone(),
+            due_at: task.due_at,
+        };
+        let content = format!("Patch {} on {} by {}", task.vulnerability, task.asset, task.due_at.format("%Y-%m-%d"));
+        // Resolved to a DevOps agent by the orchestrator
+        let mut message = payload.into_message(self.agent.id, Uuid::nil(), content, priority_of(&task.severity));
+        message.timestamp = now;
+        message.metadata.insert("to_department".to_string(), Department::DevOps.as_str().to_string());
+        self.outbox.push(message);
+    }
+
+    /// Record a security event for every agent who clicked a campaign's
+    /// lure and book them into training; returns the clicks
+    pub fn review_phishing(&mut self, lure: &str, targets: &[PhishingTarget], now: chrono::DateTime<chrono::Utc>) -> usize {
+        let clicked: Vec<&PhishingTarget> = targets.iter().filter(|target| target.clicked).collect();
+        for target in &clicked {
+            self.security_posture.recent_events.push(SecurityEvent {
+                id: Uuid::new_v4(),
+                event_type: EventType::PhishingClick,
                 severity: Severity::Medium,
-                cvss_score: 6.5,
-                description: "Sample vulnerability description".to_string(),
-                affected_system: target.to_string(),
-                remediation: "Apply security patch".to_string(),
-                discovered_at: chrono::Utc::now(),
+                description: format!("{} ({}) clicked simulated phishing lure '{}'", target.name, target.department, lure),
+                source: "phishing_simulation".to_string(),
+                timestamp: now,
+                resolved: false,
+            });
+        }
+        if !clicked.is_empty() {
+            let agents = clicked.iter().map(|target| target.agent_id).collect();
+            let at = now + chrono::Duration::days(TRAINING_LEAD_DAYS);
+            let content = format!("Security-awareness training for {} agents", clicked.len());
+            let mut message = MessagePayload::TrainingScheduled { agents, at }
+                .into_message(self.agent.id, SystemActor::Orchestrator.id(), content, MessagePriority::Normal);
+            message.timestamp = now;
+            self.outbox.push(message);
+        }
+        clicked.len()
+    }
+
+    /// Record an advisory's sightings as suspicious activity to watch for
+    pub fn record_advisory(&mut self, advisory: &ThreatAdvisory) {
+        self.security_posture.recent_events.push(SecurityEvent {
+            id: Uuid::new_v4(),
+            event_type: EventType::SuspiciousActivity,
+            severity: Severity::High,
+            description: format!("{} indicators ({}) sighted on {}", advisory.threat, advisory.indicators.join(", "), advisory.hosts.join(", ")),
+            source: "threat_intel".to_string(),
+            timestamp: advisory.issued_at,
+            resolved: false,
+        });
+    }
+
+    /// Record a data breach for every exposure, more severe the more
+    /// sensitive the data; returns the records exposed
+    pub fn record_exposures(&mut self, exposures: &[Exposure]) -> u64 {
+        for exposure in exposures {
+            let severity = match exposure.classification {
+                Classification::Pii => Severity::Critical,
+                Classification::Confidential => Severity::High,
+                Classification::Internal => Severity::Medium,
+                Classification::Public => Severity::Low,
+            };
+            self.security_posture.recent_events.push(SecurityEvent {
+                id: Uuid::new_v4(),
+                event_type: EventType::DataBreach,
+                severity,
+                description: format!("{} {} records (~{} KB) from {} reached {:?} segment {}", exposure.records, exposure.classification.as_str(),
+                                     exposure.bytes / 1024, exposure.store, exposure.to_level, exposure.to_segment),
+                source: "dlp".to_string(),
+                timestamp: exposure.at,
+                resolved: false,
+            });
+        }
+        exposures.iter().map(|exposure| exposure.records).sum()
+    }
+
+    /// Record the firewall findings the last analysis did not have as
+    /// policy violations and keep the analysis; returns the new findings
+    pub fn record_firewall_report(&mut self, report: FirewallReport, at: chrono::DateTime<chrono::Utc>) -> usize {
+        let previous = self.firewall_report.take().unwrap_or_default();
+        let new: Vec<_> = report.findings.iter().filter(|finding| !previous.findings.contains(finding)).collect();
+        for finding in &new {
+            self.security_posture.recent_events.push(SecurityEvent {
+                id: Uuid::new_v4(),
+                event_type: EventType::PolicyViolation,
+                severity: finding.kind.severity(),
+                description: format!("Firewall rule {}: {}", finding.kind.as_str(), finding.description),
+                source: "firewall_analysis".to_string(),
+                timestamp: at,
+                resolved: false,
+            });
+        }
+        let count = new.len();
+        self.firewall_report = Some(report);
+        count
+    }
+
+    /// Record a certificate about to expire, failing to renew or lapsed as
+    /// a policy violation
+    pub fn record_certificate_event(&mut self, event: &CertificateEvent, at: chrono::DateTime<chrono::Utc>) {
+        self.security_posture.recent_events.push(SecurityEvent {
+            id: Uuid::new_v4(),
+            event_type: EventType::PolicyViolation,
+            severity: event.severity(),
+            description: event.description(),
+            source: "certificates".to_string(),
+            timestamp: at,
+            resolved: false,
+        });
+    }
+
+    /// Record a DDoS attack starting, escalating or ending; it is resolved
+    /// once mitigated
+    pub fn record_ddos_event(&mut self, event: &DdosEvent, at: chrono::DateTime<chrono::Utc>) {
+        self.security_posture.recent_events.push(SecurityEvent {
+            id: Uuid::new_v4(),
+            event_type: EventType::DDoSAttack,
+            severity: event.severity(),
+            description: event.description(),
+            source: "ddos".to_string(),
+            timestamp: at,
+            resolved: matches!(event.kind, DdosEventKind::Mitigated { .. } | DdosEventKind::Ended { .. }),
+        });
+    }
+
+    /// Close the open findings that match, remembering when each was fixed
+    fn remediate(&mut self, fixed: impl Fn(&Vulnerability) -> bool, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let (closed, open): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open_vulnerabilities).into_iter().partition(|vulnerability| fixed(vulnerability));
+        self.open_vulnerabilities = open;
+        self.remediated.extend(closed.iter().map(|vulnerability| Remediation {
+            vulnerability: vulnerability.id.clone(),
+            asset: vulnerability.affected_system.clone(),
+            severity: vulnerability.severity.clone(),
+            discovered_at: vulnerability.discovered_at,
+            remediated_at: now,
+        }));
+        if self.remediated.len() > vulnscan::MAX_REMEDIATED {
+            let excess = self.remediated.len() - vulnscan::MAX_REMEDIATED;
+            self.remediated.drain(..excess);
+        }
+        if !closed.is_empty() {
+            self.update_security_posture(now);
+        }
+        closed.len()
+    }
+
+    /// Age of the open findings against their remediation deadlines
+    pub fn aging(&self, now: chrono::DateTime<chrono::Utc>) -> AgingReport {
+        vulnscan::aging(&self.open_vulnerabilities, &self.remediated, &self.scan_settings.remediation_days, now)
+    }
+
+    /// Sync the inventory to the assets DevOps, Networking and the service
+    /// catalog run, then scan every asset; returns the new findings
+    pub fn scan_inventory(&mut self, settings: ScanSettings, assets: [(AssetKind, &[String]); 3], now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> usize {
+        if settings != self.scan_settings {
+            // A different catalog size or distribution needs a new catalog
+            if settings.catalog_size != self.scan_settings.catalog_size || settings.severity_weights != self.scan_settings.severity_weights {
+                self.catalog = VulnerabilityCatalog::default();
             }
-        ];
+            self.scan_settings = settings;
+        }
+        for (kind, names) in assets {
+            for retired in self.inventory.sync(kind, names, now) {
+                // Findings on an asset that is gone cannot be fixed any more
+                self.open_vulnerabilities.retain(|vulnerability| vulnerability.affected_system != retired);
+                self.remediation_tasks.drop_asset(&retired, now);
+            }
+        }
+        let names: Vec<String> = self.inventory.assets().map(|asset| asset.name.clone()).collect();
+        let found = names.iter().map(|name| self.scan_asset(name, now, rng).len()).sum();
+        self.update_security_posture(now);
+        self.open_remediation_tasks(now);
+        found
+    }
+
+    /// Scan one asset against the catalog. Findings still open on it are
+    /// seen again, and vulnerabilities remediated there are not reported
+    /// anew. Returns the new findings.
+    pub fn scan_asset(&mut self, name: &str, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<Vulnerability> {
+        if self.catalog.is_empty() {
+            self.catalog = VulnerabilityCatalog::generate(&self.scan_settings, rng);
+        }
+        let asset = self.inventory.ensure(name, now);
+        asset.last_scanned = Some(now);
+        let kind = asset.kind;
+
+        for vulnerability in self.open_vulnerabilities.iter_mut().filter(|vulnerability| vulnerability.affected_system == name) {
+            vulnerability.last_seen = Some(now);
+            vulnerability.times_seen += 1;
+        }
+        let exposure = self.scan_settings.exposure_probability.clamp(0.0, 1.0);
+        let open = &self.open_vulnerabilities;
+        let remediated = &self.remediated;
+        let found: Vec<Vulnerability> = self.catalog.applicable(kind)
+            .filter(|entry| !open.iter().any(|vulnerability| vulnerability.id == entry.id && vulnerability.affected_system == name))
+            .filter(|entry| !remediated.iter().any(|fix| fix.vulnerability == entry.id && fix.asset == name))
+            .filter(|_| rng.gen_bool(exposure))
+            .map(|entry| entry.finding(name, now))
+            .collect();
+        self.open_vulnerabilities.extend(found.iter().cloned());
+        found
+    }
+
+    /// Take the critical fixes DevOps has yet to apply into the posture score
+    pub fn update_patch_status(&mut self, unpatched_critical: u32) {
+        self.security_posture.unpatched_critical = unpatched_critical;
+        self.recalculate_score();
+    }
+
+    /// Take in evidence of the company's actual state and keep every
+    /// policy it violates as an open compliance issue; the next audit
+    /// scores it
+    pub fn record_policy_evidence(&mut self, settings: SecurityPolicySettings, evidence: PolicyEvidence, now: chrono::DateTime<chrono::Utc>) -> PolicyEvaluation {
+        let evaluation = secpolicy::evaluate(&evidence, &settings);
+        let mut previous = std::mem::take(&mut self.compliance_status.open_issues);
+        let issues: Vec<ComplianceIssue> = evaluation.violated()
+            .map(|result| {
+                let id = format!("POL-{}", result.policy.id());
+                // A violation already open keeps the deadline it was given
+                let due_date = previous.iter().find(|issue| issue.id == id).map_or(now + chrono::Duration::days(30), |issue| issue.due_date);
+                ComplianceIssue {
+                    id,
+                    standard: result.policy.standards().iter().map(|standard| standard.as_str()).collect::<Vec<_>>().join(", "),
+                    requirement: format!("{}: {}", result.policy.name(), result.violations.join(", ")),
+                    severity: result.policy.severity(),
+                    status: "open".to_string(),
+                    due_date,
+                }
+            })
+            .collect();
+        previous.retain(|issue| !issue.id.starts_with("POL-"));
+        previous.extend(issues);
+        self.compliance_status.open_issues = previous;
+        self.policy_settings = settings;
+        self.policy_evidence = Some(evidence);
+        evaluation
+    }
+
+    /// Perform security vulnerability scan
+    pub async fn perform_vulnerability_scan(&mut self, target: &str, now: chrono::DateTime<chrono::Utc>) -> Result<ScanResults, InfoSecError> {
+        println!("🔍 InfoSec: Starting vulnerability scan on {}", target);
+
+        let new_findings = self.scan_asset(target, now, &mut rand::thread_rng()).len() as u32;
+        let vulnerabilities: Vec<Vulnerability> = self.open_vulnerabilities.iter()
+            .filter(|vulnerability| vulnerability.affected_system == target)
+            .cloned()
+            .collect();
 
         let results = ScanResults {
             target: target.to_string(),
-            scan_start: chrono::Utc::now(),
-            scan_end: chrono::Utc::now(),
+            scan_start: now,
+            scan_end: now,
             vulnerabilities_found: vulnerabilities.len() as u32,
+            new_findings,
             vulnerabilities,
             scan_status: ScanStatus::Completed,
         };
 
         // Update security posture
-        self.update_security_posture(&results).await?;
+        self.update_security_posture(now);
 
         println!("✅ InfoSec: Vulnerability scan completed for {}", target);
         Ok(results)
@@ -229,6 +624,8 @@ impl InfoSecAgent {
             updated_at: chrono::Utc::now(),
             resolution_steps: vec!["Initial assessment".to_string()],
             affected_systems: incident_report.affected_systems,
+            source_messages: Vec::new(),
+            resolved_at: None,
         };
 
         self.active_incidents.insert(incident_id, incident);
@@ -244,6 +641,31 @@ impl InfoSecAgent {
         Ok(incident_id)
     }
 
+    /// Oldest incident not yet resolved
+    pub fn oldest_open_incident(&self) -> Option<&SecurityIncident> {
+        self.active_incidents.values()
+            .filter(|incident| !matches!(incident.status, IncidentStatus::Resolved | IncidentStatus::Closed))
+            .min_by_key(|incident| incident.created_at)
+    }
+
+    /// Resolve an incident and take it off the active list
+    pub fn resolve_incident(&mut self, incident_id: Uuid, root_cause: &str, now: chrono::DateTime<chrono::Utc>) -> Option<SecurityIncident> {
+        let mut incident = self.active_incidents.remove(&incident_id)?;
+        incident.status = IncidentStatus::Resolved;
+        incident.resolution_steps.push(format!("Root cause: {}", root_cause));
+        incident.updated_at = now;
+        incident.resolved_at = Some(now);
+        Some(incident)
+    }
+
+    /// Keep a post-mortem for the next compliance audit to reference
+    pub fn record_post_mortem(&mut self, post_mortem: PostMortem) {
+        self.post_mortems.push(post_mortem);
+        if self.post_mortems.len() > MAX_POST_MORTEMS {
+            self.post_mortems.remove(0);
+        }
+    }
+
     /// Update security controls
     pub async fn update_security_controls(&mut self) -> Result<(), InfoSecError> {
         println!("🔒 InfoSec: Updating security controls...");
@@ -278,28 +700,40 @@ impl InfoSecAgent {
     pub async fn perform_compliance_audit(&mut self) -> Result<AuditResults, InfoSecError> {
         println!("📋 InfoSec: Performing compliance audit...");
 
-        // Simulate compliance checking
-        let gdpr_score = (rand::random::<f32>() * 20.0 + 80.0) as u8;
-        let soc2_score = (rand::random::<f32>() * 15.0 + 85.0) as u8;
-        let iso_score = (rand::random::<f32>() * 10.0 + 90.0) as u8;
-
-        self.compliance_status.gdpr_compliance = gdpr_score;
-        self.compliance_status.soc2_compliance = soc2_score;
-        self.compliance_status.iso27001_compliance = iso_score;
+        // Until evidence of the company's state arrives, the last scores stand
+        let evaluation = self.policy_evidence.as_ref().map(|evidence| secpolicy::evaluate(evidence, &self.policy_settings));
+        if let Some(evaluation) = &evaluation {
+            self.compliance_status.gdpr_compliance = evaluation.score(Standard::Gdpr);
+            self.compliance_status.soc2_compliance = evaluation.score(Standard::Soc2);
+            self.compliance_status.iso27001_compliance = evaluation.score(Standard::Iso27001);
+        }
         self.compliance_status.last_audit = chrono::Utc::now();
+        let gdpr_score = self.compliance_status.gdpr_compliance;
+        let soc2_score = self.compliance_status.soc2_compliance;
+        let iso_score = self.compliance_status.iso27001_compliance;
+
+        // Post-mortems since the last audit, and what they asked for
+        let mut referenced = Vec::new();
+        let mut action_items = Vec::new();
+        for post_mortem in self.post_mortems.iter_mut().filter(|post_mortem| !post_mortem.audited) {
+            post_mortem.audited = true;
+            referenced.push(post_mortem.article.unwrap_or(post_mortem.id));
+            action_items.extend(post_mortem.action_items.iter().map(|item| format!("{} ({}, from post-mortem '{}')", item.description, item.owner, post_mortem.title)));
+        }
 
         let results = AuditResults {
             audit_date: chrono::Utc::now(),
             gdpr_compliance: gdpr_score,
             soc2_compliance: soc2_score,
             iso27001_compliance: iso_score,
-            overall_compliance: (gdpr_score + soc2_score + iso_score) / 3,
-            issues_found: vec![],
-            recommendations: vec![
-                "Review access control policies".to_string(),
-                "Update encryption standards".to_string(),
-                "Enhance monitoring capabilities".to_string(),
-            ],
+            overall_compliance: ((u16::from(gdpr_score) + u16::from(soc2_score) + u16::from(iso_score)) / 3) as u8,
+            issues_found: self.compliance_status.open_issues.iter().map(|issue| issue.requirement.clone()).collect(),
+            recommendations: evaluation.iter()
+                .flat_map(|evaluation| evaluation.violated())
+                .map(|result| result.policy.recommendation().to_string())
+                .chain(action_items)
+                .collect(),
+            post_mortems: referenced,
         };
 
         println!("✅ InfoSec: Compliance audit completed - Overall score: {}%", results.overall_compliance);
@@ -344,36 +778,131 @@ impl InfoSecAgent {
         Ok(events)
     }
 
-    /// Update security posture based on scan results
-    async fn update_security_posture(&mut self, scan_results: &ScanResults) -> Result<(), InfoSecError> {
-        // Update vulnerability counts
+    /// Update security posture from every open finding
+    fn update_security_posture(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let open = &self.open_vulnerabilities;
+        let count = |severity: Severity| open.iter().filter(|v| v.severity == severity).count() as u32;
         self.security_posture.vulnerabilities = VulnerabilityCounts {
-            critical: scan_results.vulnerabilities.iter().filter(|v| v.severity == Severity::Critical).count() as u32,
-            high: scan_results.vulnerabilities.iter().filter(|v| v.severity == Severity::High).count() as u32,
-            medium: scan_results.vulnerabilities.iter().filter(|v| v.severity == Severity::Medium).count() as u32,
-            low: scan_results.vulnerabilities.iter().filter(|v| v.severity == Severity::Low).count() as u32,
-            info: scan_results.vulnerabilities.iter().filter(|v| v.severity == Severity::Info).count() as u32,
+            critical: count(Severity::Critical),
+            high: count(Severity::High),
+            medium: count(Severity::Medium),
+            low: count(Severity::Low),
+            info: count(Severity::Info),
         };
 
-        // Calculate overall security score
-        let vuln_penalty = (self.security_posture.vulnerabilities.critical * 20 +
-                           self.security_posture.vulnerabilities.high * 10 +
-                           self.security_posture.vulnerabilities.medium * 5) as i32;
+        self.recalculate_score();
+        self.security_posture.last_assessment = now;
+    }
 
-        self.security_posture.overall_score = (100i32 - vuln_penalty).max(0) as u8;
-        self.security_posture.last_assessment = chrono::Utc::now();
+    /// Calculate overall security score from the vulnerabilities found and
+    /// the critical fixes left unpatched
+    fn recalculate_score(&mut self) {
+        let posture = &mut self.security_posture;
+        let vuln_penalty = (posture.vulnerabilities.critical * 20 +
+                           posture.vulnerabilities.high * 10 +
+                           posture.vulnerabilities.medium * 5) as i32;
+        let patch_penalty = posture.unpatched_critical as i32 * UNPATCHED_CRITICAL_PENALTY;
 
-        Ok(())
+        posture.overall_score = (100i32 - vuln_penalty - patch_penalty).max(0) as u8;
     }
 }
 
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "vulnerability_scan",
+    "remediate_vulnerabilities",
+    "incident_report",
+    "threat_check",
+    "compliance_audit",
+    "security_update",
+    "patch_status",
+    "patches_applied",
+    "scan_request",
+    "policy_check",
+    "phishing_results",
+    "threat_advisory",
+    "data_exposure",
+    "firewall_findings",
+    "certificate_alert",
+    "ddos_alert",
+];
+
 #[async_trait]
 impl AgentTrait for InfoSecAgent {
     async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match MessagePayload::from_message(&message)? {
+            Some(MessagePayload::PatchStatus { unpatched_critical }) => {
+                self.update_patch_status(unpatched_critical);
+                if unpatched_critical > 0 {
+                    println!("⚠️ InfoSec: {} critical fixes unpatched, posture score {}", unpatched_critical, self.security_posture.overall_score);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::PatchesApplied { vulnerabilities }) => {
+                let closed = self.verify_patched(&vulnerabilities, message.from_agent, message.timestamp, &mut rand::thread_rng());
+                println!("🩹 InfoSec: Re-scan verified {} vulnerabilities patched by DevOps", closed);
+                return Ok(());
+            }
+            Some(MessagePayload::PhishingResults { at, lure, targets, .. }) => {
+                let clicked = self.review_phishing(&lure, &targets, at);
+                println!("🎣 InfoSec: {} of {} agents clicked '{}'{}", clicked, targets.len(), lure,
+                         if clicked > 0 { ", training booked" } else { "" });
+                return Ok(());
+            }
+            Some(MessagePayload::DataExposure { exposures }) => {
+                let records = self.record_exposures(&exposures);
+                println!("🚨 InfoSec: DLP caught {} exposures of classified data, {} records", exposures.len(), records);
+                return Ok(());
+            }
+            Some(MessagePayload::FirewallFindings { at, report }) => {
+                let findings = report.findings.len();
+                let new = self.record_firewall_report(report, at);
+                println!("🧱 InfoSec: Firewall analysis has {} findings, {} new", findings, new);
+                return Ok(());
+            }
+            Some(MessagePayload::CertificateAlert { event }) => {
+                self.record_certificate_event(&event, message.timestamp);
+                println!("🔐 InfoSec: {}", event.description());
+                return Ok(());
+            }
+            Some(MessagePayload::DdosAlert { event }) => {
+                self.record_ddos_event(&event, message.timestamp);
+                println!("🌊 InfoSec: {}", event.description());
+                return Ok(());
+            }
+            Some(MessagePayload::ThreatAdvisory { advisory }) => {
+                self.record_advisory(&advisory);
+                println!("📡 InfoSec: Watching for {} until {}", advisory.threat, advisory.expires_at.format("%Y-%m-%d"));
+                return Ok(());
+            }
+            Some(MessagePayload::PolicyCheck { at, settings, evidence }) => {
+                let evaluation = self.record_policy_evidence(settings, evidence, at);
+                let violated: Vec<&str> = evaluation.violated().map(|result| result.policy.name()).collect();
+                if !violated.is_empty() {
+                    println!("📜 InfoSec: Policies violated: {}", violated.join(", "));
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::ScanRequest { at, settings, servers, network_services, applications }) => {
+                let assets = [(AssetKind::Server, &servers[..]), (AssetKind::NetworkService, &network_services[..]), (AssetKind::Application, &applications[..])];
+                let found = self.scan_inventory(settings, assets, at, &mut rand::thread_rng());
+                let aging = self.aging(at);
+                println!("🔍 InfoSec: Scanned {} assets, {} new findings, {} open ({} overdue)",
+                         self.inventory.len(), found, aging.open, aging.overdue);
+                return Ok(());
+            }
+            _ => {}
+        }
+
         match message.message_type.as_str() {
             "vulnerability_scan" => {
                 if let Some(target) = message.metadata.get("target") {
-                    self.perform_vulnerability_scan(target).await?;
+                    self.perform_vulnerability_scan(target, message.timestamp).await?;
+                }
+            }
+            "remediate_vulnerabilities" => {
+                if let Some(target) = message.metadata.get("target") {
+                    self.remediate_vulnerabilities(target, message.timestamp);
                 }
             }
             "incident_report" => {
@@ -382,9 +911,17 @@ impl AgentTrait for InfoSecAgent {
                     title: message.metadata.get("title").unwrap_or(&"Security Incident".to_string()).clone(),
                     description: message.content,
                     severity: Severity::High, // Default to high for reported incidents
-                    affected_systems: vec!["unknown".to_string()], // Would parse from metadata
+                    affected_systems: message.metadata.get("affected_systems")
+                        .map(|systems| systems.split(',').map(|system| system.trim().to_string()).collect())
+                        .unwrap_or_else(|| vec!["unknown".to_string()]),
                 };
-                self.handle_incident(incident_report).await?;
+                let incident_id = self.handle_incident(incident_report).await?;
+                // Timed by the report, so forensics can line it up with the journal
+                if let Some(incident) = self.active_incidents.get_mut(&incident_id) {
+                    incident.created_at = message.timestamp;
+                    incident.updated_at = message.timestamp;
+                    incident.source_messages.push(message.id);
+                }
             }
             "threat_check" => {
                 let threats = self.monitor_threats().await?;
@@ -469,6 +1006,15 @@ impl AgentTrait for InfoSecAgent {
     }
 }
 
+/// Serious findings jump DevOps' queue
+fn priority_of(severity: &Severity) -> MessagePriority {
+    match severity {
+        Severity::Critical => MessagePriority::Critical,
+        Severity::High => MessagePriority::High,
+        _ => MessagePriority::Normal,
+    }
+}
+
 impl Default for SecurityPosture {
     fn default() -> Self {
         Self {
@@ -483,6 +1029,7 @@ impl Default for SecurityPosture {
             active_controls: vec![],
             recent_events: vec![],
             last_assessment: chrono::Utc::now(),
+            unpatched_critical: 0,
         }
     }
 }
@@ -505,7 +1052,11 @@ pub struct ScanResults {
     pub target: String,
     pub scan_start: chrono::DateTime<chrono::Utc>,
     pub scan_end: chrono::DateTime<chrono::Utc>,
+    /// Findings open on the target after the scan
     pub vulnerabilities_found: u32,
+    /// Of those, the ones this scan found first
+    #[serde(default)]
+    pub new_findings: u32,
     pub vulnerabilities: Vec<Vulnerability>,
     pub scan_status: ScanStatus,
 }
@@ -520,6 +1071,12 @@ pub struct Vulnerability {
     pub affected_system: String,
     pub remediation: String,
     pub discovered_at: chrono::DateTime<chrono::Utc>,
+    /// Latest scan that found it still open
+    #[serde(default)]
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    /// Scans that found it open
+    #[serde(default)]
+    pub times_seen: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -549,6 +1106,10 @@ pub struct AuditResults {
     pub overall_compliance: u8,
     pub issues_found: Vec<String>,
     pub recommendations: Vec<String>,
+    /// Knowledge base articles of the post-mortems filed since the last
+    /// audit
+    #[serde(default)]
+    pub post_mortems: Vec<Uuid>,
 }
 
 /// InfoSec-specific errors
@@ -583,12 +1144,87 @@ mod tests {
     #[tokio::test]
     async fn test_vulnerability_scan() {
         let mut agent = InfoSecAgent::new("Test Agent".to_string(), None);
-        let result = agent.perform_vulnerability_scan("test-system").await;
+        agent.scan_settings.exposure_probability = 1.0;
+        let now = chrono::Utc::now();
+        let result = agent.perform_vulnerability_scan("test-system", now).await;
         assert!(result.is_ok());
 
         let scan_results = result.unwrap();
         assert_eq!(scan_results.target, "test-system");
         assert_eq!(scan_results.scan_status, ScanStatus::Completed);
+        assert_eq!(scan_results.new_findings, scan_results.vulnerabilities_found);
+
+        // A second scan sees the same findings again rather than adding them
+        let rescan = agent.perform_vulnerability_scan("test-system", now).await.unwrap();
+        assert_eq!((rescan.vulnerabilities_found, rescan.new_findings), (scan_results.vulnerabilities_found, 0));
+        assert!(agent.open_vulnerabilities.iter().all(|vulnerability| vulnerability.times_seen == 2));
+
+        // Findings stay open until the system is remediated, and do not come back
+        assert_eq!(agent.open_vulnerabilities_by_system()["test-system"], scan_results.vulnerabilities_found);
+        assert_eq!(agent.remediate_vulnerabilities("test-system", now), scan_results.vulnerabilities_found as usize);
+        assert!(agent.open_vulnerabilities.is_empty());
+        assert_eq!(agent.perform_vulnerability_scan("test-system", now).await.unwrap().vulnerabilities_found, 0);
+        assert_eq!(agent.aging(now).remediated, scan_results.vulnerabilities_found as usize);
+    }
+
+    #[tokio::test]
+    async fn test_compliance_scores_derive_from_policy_evidence() {
+        let mut agent = InfoSecAgent::new("Test Agent".to_string(), None);
+        let now = chrono::Utc::now();
+        let evidence = PolicyEvidence {
+            accounts: 2,
+            servers: BTreeMap::from([("web-1".to_string(), false)]),
+            default_deny_rule: None,
+            backup_retention_days: 30,
+            ..PolicyEvidence::default()
+        };
+        let evaluation = agent.record_policy_evidence(SecurityPolicySettings::default(), evidence.clone(), now);
+        assert_eq!(evaluation.violated().count(), 2);
+        assert_eq!(agent.compliance_status.open_issues.len(), 2);
+
+        // Unencrypted disks fail half of GDPR; they and the missing
+        // default-deny fail two of SOC 2's five policies
+        let audit = agent.perform_compliance_audit().await.unwrap();
+        assert_eq!((audit.gdpr_compliance, audit.soc2_compliance), (50, 60));
+        assert_eq!(audit.recommendations.len(), 2);
+
+        // Violations still open keep their deadline; fixed ones are closed
+        let fixed = PolicyEvidence { servers: BTreeMap::from([("web-1".to_string(), true)]), ..evidence };
+        agent.record_policy_evidence(SecurityPolicySettings::default(), fixed, now + chrono::Duration::days(1));
+        assert_eq!(agent.compliance_status.open_issues.len(), 1);
+        assert_eq!(agent.compliance_status.open_issues[0].due_date, now + chrono::Duration::days(30));
+        assert_eq!(agent.perform_compliance_audit().await.unwrap().gdpr_compliance, 100);
+    }
+
+    #[test]
+    fn test_phishing_clicks_become_events_and_training() {
+        let mut agent = InfoSecAgent::new("Test Agent".to_string(), None);
+        let now = chrono::Utc::now();
+        let target = |name: &str, clicked: bool| PhishingTarget {
+            agent_id: Uuid::new_v4(),
+            name: name.to_string(),
+            department: "Sales".to_string(),
+            clicked,
+            reported: !clicked,
+        };
+        assert_eq!(agent.review_phishing("Password expires today", &[target("Alice", true), target("Bob", false)], now), 1);
+        assert_eq!(agent.security_posture.recent_events.last().unwrap().event_type, EventType::PhishingClick);
+        match MessagePayload::from_message(&agent.outbox[0]).unwrap() {
+            Some(MessagePayload::TrainingScheduled { agents, at }) => {
+                assert_eq!(agents.len(), 1);
+                assert_eq!(at, now + chrono::Duration::days(1));
+            }
+            other => panic!("expected a training booking, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unpatched_critical_fixes_lower_posture() {
+        let mut agent = InfoSecAgent::new("Test Agent".to_string(), None);
+        agent.update_patch_status(0);
+        let patched = agent.security_posture.overall_score;
+        agent.update_patch_status(2);
+        assert_eq!(agent.security_posture.overall_score, patched - 30);
     }
 
     #[tokio::test]
@@ -0,0 +1,612 @@
+//! Marketing Department - Campaigns & Lead Generation
+//!
+//! This module implements the Marketing department responsible for:
+//! - Campaign planning and budget management
+//! - Multi-channel campaign execution
+//! - Lead generation and hand-off to Sales
+//! - Campaign performance analytics and reporting
+//! - Customer-facing copy for release notes
+
+use crate::agents::{Agent, AgentTrait, Department};
+use crate::communication::{Message, MessagePriority};
+use crate::releasenotes::{self, ReleaseNotes};
+use crate::system::SystemActor;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Marketing Agent specialized in campaigns and lead generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketingAgent {
+    /// Base agent properties
+    pub agent: Agent,
+    /// Campaign planning and execution skill
+    pub campaign_skill: u8,
+    /// Content creation skill
+    pub content_skill: u8,
+    /// Marketing analytics skill
+    pub analytics_skill: u8,
+    /// Campaigns owned by this agent
+    pub campaigns: HashMap<Uuid, Campaign>,
+    /// Leads generated by campaigns
+    pub leads: Vec<Lead>,
+    /// Messages waiting to be picked up by the orchestrator
+    pub outbox: Vec<Message>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub id: Uuid,
+    pub name: String,
+    pub channel: Channel,
+    pub budget: f64,
+    pub spent: f64,
+    pub duration_days: u32,
+    pub days_run: u32,
+    pub conversion_rate: f32, // clicks that become leads (0.0-1.0)
+    pub impressions: u64,
+    pub clicks: u64,
+    pub leads_generated: u32,
+    pub status: CampaignStatus,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Search,
+    Social,
+    Email,
+    Content,
+    Events,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CampaignStatus {
+    Planned,
+    Active,
+    Paused,
+    Completed,
+    BudgetExhausted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lead {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub company: String,
+    pub estimated_value: f64,
+    pub score: u8, // 0-100 qualification score
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Channel {
+    /// Cost per thousand impressions in dollars
+    pub fn cpm(&self) -> f64 {
+        match self {
+            Channel::Search => 25.0,
+            Channel::Social => 8.0,
+            Channel::Email => 2.0,
+            Channel::Content => 5.0,
+            Channel::Events => 60.0,
+        }
+    }
+
+    /// Baseline click-through rate (0.0-1.0)
+    pub fn click_through_rate(&self) -> f32 {
+        match self {
+            Channel::Search => 0.035,
+            Channel::Social => 0.012,
+            Channel::Email => 0.025,
+            Channel::Content => 0.018,
+            Channel::Events => 0.15,
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = MarketingError;
+
+    /// Parse a channel name from message metadata
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "search" => Ok(Channel::Search),
+            "social" => Ok(Channel::Social),
+            "email" => Ok(Channel::Email),
+            "content" => Ok(Channel::Content),
+            "events" => Ok(Channel::Events),
+            _ => Err(MarketingError::UnknownChannel(value.to_string())),
+        }
+    }
+}
+
+impl MarketingAgent {
+    /// Create a new Marketing agent
+    pub fn new(name: String, manager_id: Option<Uuid>) -> Self {
+        Self {
+            agent: Agent::new(name, Department::Marketing, manager_id),
+            campaign_skill: 85,
+            content_skill: 80,
+            analytics_skill: 75,
+            campaigns: HashMap::new(),
+            leads: vec![],
+            outbox: vec![],
+        }
+    }
+
+    /// Launch a new marketing campaign
+    pub async fn launch_campaign(&mut self, config: CampaignConfig) -> Result<Uuid, MarketingError> {
+        if config.budget <= 0.0 {
+            return Err(MarketingError::InvalidBudget(config.budget));
+        }
+        if config.duration_days == 0 {
+            return Err(MarketingError::InvalidCampaign("duration must be at least one day".to_string()));
+        }
+
+        let campaign_id = Uuid::new_v4();
+
+        // Better campaign planners convert a little better
+        let skill_bonus = self.campaign_skill as f32 / 100.0;
+        let campaign = Campaign {
+            id: campaign_id,
+            name: config.name.clone(),
+            channel: config.channel,
+            budget: config.budget,
+            spent: 0.0,
+            duration_days: config.duration_days,
+            days_run: 0,
+            conversion_rate: (config.target_conversion_rate * (0.8 + 0.4 * skill_bonus)).clamp(0.0, 1.0),
+            impressions: 0,
+            clicks: 0,
+            leads_generated: 0,
+            status: CampaignStatus::Active,
+            started_at: chrono::Utc::now(),
+        };
+
+        self.campaigns.insert(campaign_id, campaign);
+
+        println!("📣 Marketing: Launched {:?} campaign '{}' with ${:.2} budget", config.channel, config.name, config.budget);
+        Ok(campaign_id)
+    }
+
+    /// Pause an active campaign
+    pub async fn pause_campaign(&mut self, campaign_id: Uuid) -> Result<(), MarketingError> {
+        if let Some(campaign) = self.campaigns.get_mut(&campaign_id) {
+            if campaign.status == CampaignStatus::Active {
+                campaign.status = CampaignStatus::Paused;
+                println!("⏸️ Marketing: Paused campaign '{}'", campaign.name);
+            }
+            Ok(())
+        } else {
+            Err(MarketingError::CampaignNotFound(campaign_id))
+        }
+    }
+
+    /// Run one simulated day of a campaign, returning the leads it produced
+    pub async fn run_campaign_day(&mut self, campaign_id: Uuid) -> Result<Vec<Lead>, MarketingError> {
+        let campaign = self.campaigns.get_mut(&campaign_id)
+            .ok_or(MarketingError::CampaignNotFound(campaign_id))?;
+
+        if campaign.status != CampaignStatus::Active {
+            return Ok(vec![]);
+        }
+
+        // Spend evenly across the remaining days
+        let remaining_budget = campaign.budget - campaign.spent;
+        let remaining_days = campaign.duration_days.saturating_sub(campaign.days_run).max(1);
+        let daily_spend = remaining_budget / remaining_days as f64;
+
+        let impressions = (daily_spend / campaign.channel.cpm() * 1000.0) as u64;
+        let expected_clicks = impressions as f32 * campaign.channel.click_through_rate() * (0.75 + rand::random::<f32>() * 0.5);
+        let clicks = expected_clicks as u64;
+        let expected_leads = clicks as f32 * campaign.conversion_rate;
+        let mut lead_count = expected_leads as u32;
+        if rand::random::<f32>() < expected_leads.fract() {
+            lead_count += 1;
+        }
+
+        campaign.spent += daily_spend;
+        campaign.days_run += 1;
+        campaign.impressions += impressions;
+        campaign.clicks += clicks;
+        campaign.leads_generated += lead_count;
+
+        if campaign.spent >= campaign.budget - f64::EPSILON {
+            campaign.status = CampaignStatus::BudgetExhausted;
+        } else if campaign.days_run >= campaign.duration_days {
+            campaign.status = CampaignStatus::Completed;
+        }
+
+        let campaign_name = campaign.name.clone();
+        let leads: Vec<Lead> = (0..lead_count)
+            .map(|_| Lead {
+                id: Uuid::new_v4(),
+                campaign_id,
+                company: format!("Prospect-{}", rand::random::<u16>()),
+                estimated_value: 2_000.0 + rand::random::<f64>() * 18_000.0,
+                score: (40.0 + rand::random::<f32>() * 60.0) as u8,
+                created_at: chrono::Utc::now(),
+            })
+            .collect();
+
+        for lead in &leads {
+            self.queue_lead_for_sales(lead);
+        }
+        self.leads.extend(leads.iter().cloned());
+
+        if !leads.is_empty() {
+            println!("🎯 Marketing: Campaign '{}' generated {} new leads", campaign_name, leads.len());
+        }
+
+        Ok(leads)
+    }
+
+    /// Build a performance report across all campaigns
+    pub fn generate_campaign_report(&self) -> CampaignReport {
+        let campaigns: Vec<CampaignMetrics> = self.campaigns.values()
+            .map(|c| CampaignMetrics {
+                campaign_id: c.id,
+                name: c.name.clone(),
+                channel: c.channel,
+                status: c.status.clone(),
+                spent: c.spent,
+                impressions: c.impressions,
+                clicks: c.clicks,
+                leads: c.leads_generated,
+                click_through_rate: if c.impressions > 0 { c.clicks as f32 / c.impressions as f32 } else { 0.0 },
+                conversion_rate: if c.clicks > 0 { c.leads_generated as f32 / c.clicks as f32 } else { 0.0 },
+                cost_per_lead: if c.leads_generated > 0 { Some(c.spent / c.leads_generated as f64) } else { None },
+            })
+            .collect();
+
+        let total_spend: f64 = campaigns.iter().map(|c| c.spent).sum();
+        let total_leads: u32 = campaigns.iter().map(|c| c.leads).sum();
+
+        CampaignReport {
+            agent_id: self.agent.id,
+            generated_at: chrono::Utc::now(),
+            active_campaigns: self.campaigns.values().filter(|c| c.status == CampaignStatus::Active).count() as u32,
+            total_spend,
+            total_leads,
+            cost_per_lead: if total_leads > 0 { Some(total_spend / total_leads as f64) } else { None },
+            campaigns,
+        }
+    }
+
+    /// Queue a lead message addressed to the Sales department, or to the
+    /// Marketing head while no Sales agent is staffed
+    fn queue_lead_for_sales(&mut self, lead: &Lead) {
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: Uuid::nil(), // Resolved to a Sales agent by the orchestrator
+            message_type: "lead".to_string(),
+            content: format!("New lead from {} (score {})", lead.company, lead.score),
+            priority: if lead.score >= 80 { MessagePriority::High } else { MessagePriority::Normal },
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                ("to_department".to_string(), "Sales".to_string()),
+                ("fallback_department".to_string(), "Marketing".to_string()),
+                ("lead_id".to_string(), lead.id.to_string()),
+                ("campaign_id".to_string(), lead.campaign_id.to_string()),
+                ("company".to_string(), lead.company.clone()),
+                ("estimated_value".to_string(), format!("{:.2}", lead.estimated_value)),
+                ("score".to_string(), lead.score.to_string()),
+            ]),
+        });
+    }
+
+    /// Turn engineering release notes into one friendly line for customers
+    pub fn polish_release_notes(&self, notes: &ReleaseNotes) -> String {
+        let mut highlights = Vec::new();
+        if !notes.features.is_empty() {
+            highlights.push(match notes.features.len() {
+                1 => "a new improvement".to_string(),
+                n => format!("{} new improvements", n),
+            });
+        }
+        if !notes.fixes.is_empty() {
+            highlights.push(match notes.fixes.len() {
+                1 => "a bug fix".to_string(),
+                n => format!("{} bug fixes", n),
+            });
+        }
+
+        let mut copy = match highlights.is_empty() {
+            true => format!("{} is live with behind-the-scenes reliability work.", notes.version),
+            false => format!("{} is live with {}.", notes.version, highlights.join(" and ")),
+        };
+        if !notes.rollbacks.is_empty() {
+            copy.push_str(" We also rolled back a recent change while we take another look at it.");
+        }
+        // Seasoned writers close with a thank-you
+        if self.content_skill >= 80 {
+            copy.push_str(" Thanks for building with us!");
+        }
+        copy
+    }
+
+    /// Queue the daily campaign report for the orchestrator
+    fn queue_campaign_report(&mut self) -> Result<(), MarketingError> {
+        let report = self.generate_campaign_report();
+        let content = serde_json::to_string(&report)
+            .map_err(|e| MarketingError::ReportingFailed(e.to_string()))?;
+
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: SystemActor::Orchestrator.id(),
+            message_type: "campaign_report".to_string(),
+            content,
+            priority: MessagePriority::Low,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        });
+
+        Ok(())
+    }
+}
+
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "launch_campaign",
+    "pause_campaign",
+    "run_campaigns",
+    "campaign_report",
+    releasenotes::POLISH_REQUEST,
+];
+
+#[async_trait]
+impl AgentTrait for MarketingAgent {
+    async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match message.message_type.as_str() {
+            "launch_campaign" => {
+                let config = CampaignConfig {
+                    name: message.metadata.get("name").unwrap_or(&"Brand Awareness".to_string()).clone(),
+                    channel: message.metadata.get("channel")
+                        .and_then(|c| c.parse().ok())
+                        .unwrap_or(Channel::Social),
+                    budget: message.metadata.get("budget")
+                        .and_then(|b| b.parse().ok())
+                        .unwrap_or(5_000.0),
+                    duration_days: message.metadata.get("duration_days")
+                        .and_then(|d| d.parse().ok())
+                        .unwrap_or(14),
+                    target_conversion_rate: 0.05,
+                };
+                self.launch_campaign(config).await?;
+            }
+            "pause_campaign" => {
+                if let Some(campaign_id) = message.metadata.get("campaign_id") {
+                    if let Ok(campaign_uuid) = Uuid::parse_str(campaign_id) {
+                        self.pause_campaign(campaign_uuid).await?;
+                    }
+                }
+            }
+            "run_campaigns" => {
+                let campaign_ids: Vec<Uuid> = self.campaigns.values()
+                    .filter(|c| c.status == CampaignStatus::Active)
+                    .map(|c| c.id)
+                    .collect();
+                for campaign_id in campaign_ids {
+                    self.run_campaign_day(campaign_id).await?;
+                }
+            }
+            "campaign_report" => {
+                self.queue_campaign_report()?;
+                let report = self.generate_campaign_report();
+                println!("📊 Marketing: {} active campaigns, {} leads, ${:.2} spent",
+                        report.active_campaigns, report.total_leads, report.total_spend);
+            }
+            releasenotes::POLISH_REQUEST => {
+                let notes: ReleaseNotes = serde_json::from_str(&message.content)
+                    .map_err(|e| MarketingError::ReportingFailed(e.to_string()))?;
+                let copy = self.polish_release_notes(&notes);
+                println!("✍️ Marketing: Polished release notes for {}", notes.version);
+
+                self.outbox.push(Message {
+                    id: Uuid::new_v4(),
+                    from_agent: self.agent.id,
+                    to_agent: SystemActor::Orchestrator.id(),
+                    message_type: releasenotes::POLISHED.to_string(),
+                    content: copy,
+                    priority: MessagePriority::Low,
+                    timestamp: chrono::Utc::now(),
+                    metadata: HashMap::from([(releasenotes::NOTES_ID_KEY.to_string(), notes.id.to_string())]),
+                });
+            }
+            "lead" => {
+                // Only reaches Marketing while no Sales agent is staffed
+                let company = message.metadata.get("company").map(String::as_str).unwrap_or("unknown company");
+                println!("🌱 Marketing: Nurturing lead from {} until Sales is staffed", company);
+            }
+            _ => {
+                println!("🤷 Marketing: Unknown message type: {}", message.message_type);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn perform_daily_tasks(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        println!("📣 Marketing: Performing daily campaign tasks...");
+
+        // Keep at least one campaign running
+        if !self.campaigns.values().any(|c| c.status == CampaignStatus::Active) {
+            let channels = [Channel::Search, Channel::Social, Channel::Email, Channel::Content, Channel::Events];
+            let channel = channels[rand::random::<usize>() % channels.len()];
+
+            self.process_message(Message {
+                id: Uuid::new_v4(),
+                from_agent: self.agent.id,
+                to_agent: self.agent.id,
+                message_type: "launch_campaign".to_string(),
+                content: "Launch new lead generation campaign".to_string(),
+                priority: MessagePriority::Normal,
+                timestamp: chrono::Utc::now(),
+                metadata: HashMap::from([
+                    ("name".to_string(), format!("{:?} Lead Gen", channel)),
+                    ("channel".to_string(), format!("{:?}", channel)),
+                    ("budget".to_string(), "5000".to_string()),
+                ]),
+            }).await?;
+        }
+
+        // Run a day of every active campaign
+        self.process_message(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: self.agent.id,
+            message_type: "run_campaigns".to_string(),
+            content: "Daily campaign execution".to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }).await?;
+
+        // Report performance to the orchestrator
+        self.process_message(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: self.agent.id,
+            message_type: "campaign_report".to_string(),
+            content: "Daily campaign report".to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }).await?;
+
+        Ok(())
+    }
+
+    fn get_agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    fn get_agent_mut(&mut self) -> &mut Agent {
+        &mut self.agent
+    }
+}
+
+/// Configuration for launching a campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignConfig {
+    pub name: String,
+    pub channel: Channel,
+    pub budget: f64,
+    pub duration_days: u32,
+    pub target_conversion_rate: f32,
+}
+
+/// Daily campaign performance report sent to the orchestrator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignReport {
+    pub agent_id: Uuid,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub active_campaigns: u32,
+    pub total_spend: f64,
+    pub total_leads: u32,
+    pub cost_per_lead: Option<f64>,
+    pub campaigns: Vec<CampaignMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignMetrics {
+    pub campaign_id: Uuid,
+    pub name: String,
+    pub channel: Channel,
+    pub status: CampaignStatus,
+    pub spent: f64,
+    pub impressions: u64,
+    pub clicks: u64,
+    pub leads: u32,
+    pub click_through_rate: f32,
+    pub conversion_rate: f32,
+    pub cost_per_lead: Option<f64>,
+}
+
+/// Marketing-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum MarketingError {
+    #[error("Campaign not found: {0}")]
+    CampaignNotFound(Uuid),
+
+    #[error("Invalid campaign budget: {0}")]
+    InvalidBudget(f64),
+
+    #[error("Invalid campaign: {0}")]
+    InvalidCampaign(String),
+
+    #[error("Reporting failed: {0}")]
+    ReportingFailed(String),
+
+    #[error("Unknown channel: {0}")]
+    UnknownChannel(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::DepartmentAgent;
+
+    fn test_campaign(budget: f64, duration_days: u32) -> CampaignConfig {
+        CampaignConfig {
+            name: "Test Campaign".to_string(),
+            channel: Channel::Search,
+            budget,
+            duration_days,
+            target_conversion_rate: 0.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_marketing_agent_creation() {
+        let agent = MarketingAgent::new("Test Marketing Agent".to_string(), None);
+        assert_eq!(agent.agent.name, "Test Marketing Agent");
+        assert_eq!(agent.agent.department, Department::Marketing);
+        assert_eq!(agent.campaign_skill, 85);
+        assert!(agent.campaigns.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_campaign_launch_rejects_invalid_budget() {
+        let mut agent = MarketingAgent::new("Test Agent".to_string(), None);
+        let result = agent.launch_campaign(test_campaign(0.0, 7)).await;
+        assert!(matches!(result, Err(MarketingError::InvalidBudget(_))));
+    }
+
+    #[tokio::test]
+    async fn test_campaign_spends_budget_and_queues_leads() {
+        let mut agent = MarketingAgent::new("Test Agent".to_string(), None);
+        let campaign_id = agent.launch_campaign(test_campaign(10_000.0, 2)).await.unwrap();
+
+        let first_day = agent.run_campaign_day(campaign_id).await.unwrap();
+        let second_day = agent.run_campaign_day(campaign_id).await.unwrap();
+
+        let campaign = &agent.campaigns[&campaign_id];
+        assert!((campaign.spent - 10_000.0).abs() < 0.01);
+        assert_ne!(campaign.status, CampaignStatus::Active);
+        assert_eq!(campaign.leads_generated as usize, first_day.len() + second_day.len());
+
+        let outbox = agent.drain_outbox();
+        assert_eq!(outbox.len(), first_day.len() + second_day.len());
+        assert!(outbox.iter().all(|m| m.message_type == "lead"
+            && m.metadata.get("to_department").map(String::as_str) == Some("Sales")
+            && m.metadata.get("fallback_department").map(String::as_str) == Some("Marketing")));
+        assert_eq!("Events".parse::<Channel>().unwrap(), Channel::Events);
+        assert!(matches!("radio".parse::<Channel>(), Err(MarketingError::UnknownChannel(_))));
+    }
+
+    #[tokio::test]
+    async fn test_campaign_report() {
+        let mut agent = MarketingAgent::new("Test Agent".to_string(), None);
+        let campaign_id = agent.launch_campaign(test_campaign(1_000.0, 10)).await.unwrap();
+        agent.run_campaign_day(campaign_id).await.unwrap();
+
+        let report = agent.generate_campaign_report();
+        assert_eq!(report.active_campaigns, 1);
+        assert_eq!(report.campaigns.len(), 1);
+        assert!((report.total_spend - 100.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,593 @@
+//! Company Departments
+//!
+//! Each department module provides a specialized agent built on top of the
+//! shared `AgentTrait`. The orchestrator stores agents as `DepartmentAgent`
+//! trait objects so it can reach the department-level hooks defined here.
+//! Whatever error an agent returns is classified as an `AgentError`, so the
+//! supervisor can tell failures worth retrying from fatal ones.
+
+pub mod devops;
+pub mod engineering;
+pub mod infosec;
+pub mod marketing;
+pub mod networking;
+pub mod ops;
+
+use crate::agents::AgentTrait;
+use crate::assignment::WorkKind;
+use crate::communication::Message;
+use crate::payload::PayloadError;
+use crate::snapshot::AgentSnapshot;
+use crate::statusreport::{ActiveItem, AgentStatusReport};
+use serde::{Deserialize, Serialize};
+
+/// Orchestrator-facing extensions implemented by every department agent
+///
+/// Agents are `Send + 'static` so the orchestrator can move each one onto a
+/// task of its own while it works through its messages.
+pub trait DepartmentAgent: AgentTrait + Send + 'static {
+    /// Take all messages the agent has queued for delivery to other agents
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        Vec::new()
+    }
+
+    /// Number of open work items the agent is carrying
+    fn backlog(&self) -> usize {
+        0
+    }
+
+    /// The agent's skill for a kind of work, if it does that work at all
+    fn skill(&self, _kind: WorkKind) -> Option<u8> {
+        None
+    }
+
+    /// Shift every skill by `delta` points, e.g. for a more or less
+    /// experienced hire
+    fn adjust_skills(&mut self, _delta: i8) {}
+
+    /// Shift the skill used for one kind of work by `delta` points
+    fn adjust_skill(&mut self, _kind: WorkKind, _delta: i8) {}
+
+    /// Number of finished pieces of a kind of work the agent still has on record
+    fn completed_work(&self, _kind: WorkKind) -> usize {
+        0
+    }
+
+    /// Tickets, incidents, deployments and the like the agent has open
+    fn active_items(&self) -> Vec<ActiveItem> {
+        Vec::new()
+    }
+
+    /// The agent's workload, open items and skills; state and recent
+    /// activity are the orchestrator's to add
+    fn status_report(&self) -> AgentStatusReport {
+        let agent = self.get_agent();
+        AgentStatusReport {
+            agent_id: agent.id,
+            name: agent.name.clone(),
+            department: agent.department.as_str().to_string(),
+            manager_id: agent.manager_id,
+            backlog: self.backlog(),
+            active_items: self.active_items(),
+            skills: WorkKind::ALL.iter().filter_map(|kind| self.skill(*kind).map(|skill| (*kind, skill))).collect(),
+            completed: WorkKind::ALL.iter().filter_map(|kind| self.skill(*kind).map(|_| (*kind, self.completed_work(*kind)))).collect(),
+            state: None,
+            recent_activity: Vec::new(),
+        }
+    }
+
+    /// Number of open Sev1 incidents the agent is handling
+    fn open_sev1_incidents(&self) -> usize {
+        0
+    }
+
+    /// The agent as an Ops agent, for orchestrator KPIs
+    fn as_ops(&self) -> Option<&ops::OpsAgent> {
+        None
+    }
+
+    /// The agent as an Ops agent, for applying edits made outside the simulation
+    fn as_ops_mut(&mut self) -> Option<&mut ops::OpsAgent> {
+        None
+    }
+
+    /// The agent as a DevOps agent, for deployment tracking
+    fn as_devops(&self) -> Option<&devops::DevOpsAgent> {
+        None
+    }
+
+    /// The agent as a DevOps agent, for running deployments on the simulated clock
+    fn as_devops_mut(&mut self) -> Option<&mut devops::DevOpsAgent> {
+        None
+    }
+
+    /// The agent as an InfoSec agent, for service scorecards
+    fn as_infosec(&self) -> Option<&infosec::InfoSecAgent> {
+        None
+    }
+
+    /// The agent as an InfoSec agent, for resolving its incidents
+    fn as_infosec_mut(&mut self) -> Option<&mut infosec::InfoSecAgent> {
+        None
+    }
+
+    /// The agent as a Networking agent, for the assets InfoSec scans
+    fn as_networking(&self) -> Option<&networking::NetworkingAgent> {
+        None
+    }
+
+    /// The agent as a Networking agent, for running its load balancers
+    fn as_networking_mut(&mut self) -> Option<&mut networking::NetworkingAgent> {
+        None
+    }
+
+    /// The agent as a Marketing agent, for campaign mailings
+    fn as_marketing(&self) -> Option<&marketing::MarketingAgent> {
+        None
+    }
+
+    /// Capture the agent's full state for a simulation snapshot
+    fn snapshot(&self) -> AgentSnapshot;
+}
+
+impl DepartmentAgent for devops::DevOpsAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        match kind {
+            WorkKind::Deployment => Some(self.deployment_skill),
+            WorkKind::Infrastructure => Some(self.infrastructure_skill),
+            _ => None,
+        }
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.infrastructure_skill, delta);
+        shift_skill(&mut self.deployment_skill, delta);
+        shift_skill(&mut self.monitoring_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        match kind {
+            WorkKind::Deployment => shift_skill(&mut self.deployment_skill, delta),
+            WorkKind::Infrastructure => shift_skill(&mut self.infrastructure_skill, delta),
+            _ => {}
+        }
+    }
+
+    fn completed_work(&self, kind: WorkKind) -> usize {
+        match kind {
+            WorkKind::Deployment => self.active_deployments.values()
+                .filter(|d| d.status == devops::DeploymentStatus::Success)
+                .count(),
+            _ => 0,
+        }
+    }
+
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn backlog(&self) -> usize {
+        self.active_deployments.values()
+            .filter(|d| matches!(d.status, devops::DeploymentStatus::Pending | devops::DeploymentStatus::InProgress))
+            .count()
+            + self.improvements.len()
+    }
+
+    fn active_items(&self) -> Vec<ActiveItem> {
+        let deployments = self.active_deployments.values()
+            .filter(|d| matches!(d.status, devops::DeploymentStatus::Pending | devops::DeploymentStatus::InProgress))
+            .map(|d| ActiveItem::new("deployment", d.id, format!("{} to {}", d.project_id, d.environment), &d.status));
+        let improvements = self.improvements.iter()
+            .map(|improvement| ActiveItem {
+                kind: "improvement".to_string(),
+                id: improvement.task_id,
+                title: improvement.title.clone(),
+                status: "Assigned".to_string(),
+            });
+        deployments.chain(improvements).collect()
+    }
+
+    fn as_devops(&self) -> Option<&devops::DevOpsAgent> {
+        Some(self)
+    }
+
+    fn as_devops_mut(&mut self) -> Option<&mut devops::DevOpsAgent> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::DevOps(self.clone())
+    }
+}
+
+impl DepartmentAgent for engineering::EngineeringAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        (kind == WorkKind::Feature).then_some(self.coding_skill)
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.coding_skill, delta);
+        shift_skill(&mut self.review_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        if kind == WorkKind::Feature {
+            shift_skill(&mut self.coding_skill, delta);
+        }
+    }
+
+    fn completed_work(&self, kind: WorkKind) -> usize {
+        match kind {
+            WorkKind::Feature => self.features.values()
+                .filter(|f| f.status == engineering::FeatureStatus::Merged)
+                .count(),
+            _ => 0,
+        }
+    }
+
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn backlog(&self) -> usize {
+        self.features.values()
+            .filter(|f| f.status == engineering::FeatureStatus::InProgress)
+            .count()
+    }
+
+    fn active_items(&self) -> Vec<ActiveItem> {
+        self.features.iter()
+            .filter(|(_, f)| f.status == engineering::FeatureStatus::InProgress)
+            .map(|(id, f)| ActiveItem::new("feature", *id, f.branch.clone(), &f.status))
+            .collect()
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::Engineering(self.clone())
+    }
+}
+
+impl DepartmentAgent for infosec::InfoSecAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        (kind == WorkKind::SecurityIncident).then_some(self.incident_response_skill)
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.security_skill, delta);
+        shift_skill(&mut self.threat_detection_skill, delta);
+        shift_skill(&mut self.incident_response_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        if kind == WorkKind::SecurityIncident {
+            shift_skill(&mut self.incident_response_skill, delta);
+        }
+    }
+
+    fn completed_work(&self, kind: WorkKind) -> usize {
+        match kind {
+            WorkKind::SecurityIncident => self.active_incidents.values()
+                .filter(|i| matches!(i.status, infosec::IncidentStatus::Resolved | infosec::IncidentStatus::Closed))
+                .count(),
+            _ => 0,
+        }
+    }
+
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn backlog(&self) -> usize {
+        self.active_incidents.values()
+            .filter(|i| matches!(i.status, infosec::IncidentStatus::Open | infosec::IncidentStatus::Investigating | infosec::IncidentStatus::Mitigating))
+            .count()
+    }
+
+    fn active_items(&self) -> Vec<ActiveItem> {
+        self.active_incidents.values()
+            .filter(|i| matches!(i.status, infosec::IncidentStatus::Open | infosec::IncidentStatus::Investigating | infosec::IncidentStatus::Mitigating))
+            .map(|i| ActiveItem::new("security incident", i.id, i.title.clone(), &i.status))
+            .collect()
+    }
+
+    fn as_infosec(&self) -> Option<&infosec::InfoSecAgent> {
+        Some(self)
+    }
+
+    fn as_infosec_mut(&mut self) -> Option<&mut infosec::InfoSecAgent> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::InfoSec(self.clone())
+    }
+}
+
+impl DepartmentAgent for networking::NetworkingAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        (kind == WorkKind::Network).then_some(self.network_skill)
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.network_skill, delta);
+        shift_skill(&mut self.security_skill, delta);
+        shift_skill(&mut self.performance_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        if kind == WorkKind::Network {
+            shift_skill(&mut self.network_skill, delta);
+        }
+    }
+
+    fn as_networking(&self) -> Option<&networking::NetworkingAgent> {
+        Some(self)
+    }
+
+    fn as_networking_mut(&mut self) -> Option<&mut networking::NetworkingAgent> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::Networking(self.clone())
+    }
+}
+
+impl DepartmentAgent for ops::OpsAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        match kind {
+            WorkKind::Ticket => Some(self.support_skill),
+            WorkKind::Incident => Some(self.incident_skill),
+            _ => None,
+        }
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.sysadmin_skill, delta);
+        shift_skill(&mut self.support_skill, delta);
+        shift_skill(&mut self.incident_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        match kind {
+            WorkKind::Ticket => shift_skill(&mut self.support_skill, delta),
+            WorkKind::Incident => shift_skill(&mut self.incident_skill, delta),
+            _ => {}
+        }
+    }
+
+    fn completed_work(&self, kind: WorkKind) -> usize {
+        match kind {
+            WorkKind::Ticket => self.support_tickets.values()
+                .filter(|t| matches!(t.status, ops::TicketStatus::Resolved | ops::TicketStatus::Closed))
+                .count(),
+            WorkKind::Incident => self.incidents.values()
+                .filter(|i| matches!(i.status, ops::IncidentStatus::Resolved | ops::IncidentStatus::PostMortem | ops::IncidentStatus::Closed))
+                .count(),
+            _ => 0,
+        }
+    }
+
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn backlog(&self) -> usize {
+        let tickets = self.support_tickets.values()
+            .filter(|t| matches!(t.status, ops::TicketStatus::Open | ops::TicketStatus::InProgress))
+            .count();
+        tickets + self.active_incidents().count()
+    }
+
+    fn active_items(&self) -> Vec<ActiveItem> {
+        let tickets = self.support_tickets.values()
+            .filter(|t| matches!(t.status, ops::TicketStatus::Open | ops::TicketStatus::InProgress))
+            .map(|t| ActiveItem::new("ticket", t.id, t.title.clone(), &t.status));
+        let incidents = self.active_incidents().map(|i| ActiveItem::new("incident", i.id, i.title.clone(), &i.status));
+        tickets.chain(incidents).collect()
+    }
+
+    fn open_sev1_incidents(&self) -> usize {
+        self.active_incidents().filter(|i| i.severity == ops::Severity::Sev1).count()
+    }
+
+    fn as_ops(&self) -> Option<&ops::OpsAgent> {
+        Some(self)
+    }
+
+    fn as_ops_mut(&mut self) -> Option<&mut ops::OpsAgent> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::Ops(self.clone())
+    }
+}
+
+impl DepartmentAgent for marketing::MarketingAgent {
+    fn skill(&self, kind: WorkKind) -> Option<u8> {
+        (kind == WorkKind::Campaign).then_some(self.campaign_skill)
+    }
+
+    fn adjust_skills(&mut self, delta: i8) {
+        shift_skill(&mut self.campaign_skill, delta);
+        shift_skill(&mut self.content_skill, delta);
+        shift_skill(&mut self.analytics_skill, delta);
+    }
+
+    fn adjust_skill(&mut self, kind: WorkKind, delta: i8) {
+        if kind == WorkKind::Campaign {
+            shift_skill(&mut self.campaign_skill, delta);
+        }
+    }
+
+    fn completed_work(&self, kind: WorkKind) -> usize {
+        match kind {
+            WorkKind::Campaign => self.campaigns.values()
+                .filter(|c| c.status == marketing::CampaignStatus::Completed)
+                .count(),
+            _ => 0,
+        }
+    }
+
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn as_marketing(&self) -> Option<&marketing::MarketingAgent> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        AgentSnapshot::Marketing(self.clone())
+    }
+}
+
+/// Shift a skill, keeping it within 1-100
+fn shift_skill(skill: &mut u8, delta: i8) {
+    *skill = (i16::from(*skill) + i16::from(delta)).clamp(1, 100) as u8;
+}
+
+/// What went wrong while an agent processed a message or ran its tasks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+pub enum AgentError {
+    /// The message was malformed or asked for something that does not exist
+    #[error("Message handling failed: {0}")]
+    MessageHandling(String),
+
+    /// The agent took the work on and it failed
+    #[error("Task execution failed: {0}")]
+    TaskExecution(String),
+
+    /// Something the agent relies on was unavailable
+    #[error("Dependency failed: {0}")]
+    Dependency(String),
+
+    /// The agent cannot carry on until it is recovered
+    #[error("Fatal agent error: {0}")]
+    Fatal(String),
+}
+
+impl AgentError {
+    /// Whether the agent may succeed if it is left to try again
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, AgentError::Fatal(_))
+    }
+
+    /// Classify a boxed error an agent returned; errors of no known type count
+    /// as failed tasks
+    pub fn from_boxed(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        error.downcast::<AgentError>().map(|error| *error)
+            .or_else(|error| error.downcast::<PayloadError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<devops::DevOpsError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<engineering::EngineeringError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<infosec::InfoSecError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<marketing::MarketingError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<networking::NetworkingError>().map(|error| AgentError::from(*error)))
+            .or_else(|error| error.downcast::<ops::OpsError>().map(|error| AgentError::from(*error)))
+            .unwrap_or_else(|error| AgentError::TaskExecution(error.to_string()))
+    }
+}
+
+impl From<PayloadError> for AgentError {
+    fn from(error: PayloadError) -> Self {
+        AgentError::MessageHandling(error.to_string())
+    }
+}
+
+impl From<devops::DevOpsError> for AgentError {
+    fn from(error: devops::DevOpsError) -> Self {
+        use devops::DevOpsError::*;
+        match error {
+            ServerNotFound(_) | InvalidRequest(_) => AgentError::MessageHandling(error.to_string()),
+            MonitoringError(_) => AgentError::Dependency(error.to_string()),
+            DeploymentFailed(_) | InfrastructureError(_) => AgentError::TaskExecution(error.to_string()),
+        }
+    }
+}
+
+impl From<engineering::EngineeringError> for AgentError {
+    fn from(error: engineering::EngineeringError) -> Self {
+        use engineering::EngineeringError::*;
+        match error {
+            FeatureExists(_) | FeatureNotFound(_) => AgentError::MessageHandling(error.to_string()),
+            RequestFailed(_) => AgentError::Dependency(error.to_string()),
+        }
+    }
+}
+
+impl From<infosec::InfoSecError> for AgentError {
+    fn from(error: infosec::InfoSecError) -> Self {
+        AgentError::TaskExecution(error.to_string())
+    }
+}
+
+impl From<marketing::MarketingError> for AgentError {
+    fn from(error: marketing::MarketingError) -> Self {
+        use marketing::MarketingError::*;
+        match error {
+            CampaignNotFound(_) | InvalidBudget(_) | InvalidCampaign(_) => AgentError::MessageHandling(error.to_string()),
+            ReportingFailed(_) => AgentError::TaskExecution(error.to_string()),
+        }
+    }
+}
+
+impl From<networking::NetworkingError> for AgentError {
+    fn from(error: networking::NetworkingError) -> Self {
+        use networking::NetworkingError::*;
+        match error {
+            ConfigurationError(_) | DeviceNotFound(_) => AgentError::MessageHandling(error.to_string()),
+            ServiceUnavailable(_) => AgentError::Dependency(error.to_string()),
+            Dns(_) | VPNError(_) | Ipam(_) => AgentError::TaskExecution(error.to_string()),
+        }
+    }
+}
+
+impl From<ops::OpsError> for AgentError {
+    fn from(error: ops::OpsError) -> Self {
+        use ops::OpsError::*;
+        match error {
+            TicketNotFound(_) | IncidentNotFound(_) | ChangeNotFound(_) | InvalidTicketTransition(_)
+            | ReopenWindowExpired(_) | InvalidMerge(_) | InvalidQuotaRequest(_) => AgentError::MessageHandling(error.to_string()),
+            SLACalculationError(_) | MaintenanceFailed(_) => AgentError::TaskExecution(error.to_string()),
+        }
+    }
+}
+
+/// Reads a recorded `AgentError`; failures recorded before they were
+/// classified, as plain messages, read as failed tasks
+pub mod agent_error {
+    use super::AgentError;
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Recorded {
+        Classified(AgentError),
+        Message(String),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AgentError, D::Error> {
+        Ok(match Recorded::deserialize(deserializer)? {
+            Recorded::Classified(error) => error,
+            Recorded::Message(message) => AgentError::TaskExecution(message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_department_errors_are_classified() {
+        let boxed = |error: Box<dyn std::error::Error + Send + Sync>| AgentError::from_boxed(error);
+        assert!(matches!(boxed(Box::new(ops::OpsError::TicketNotFound(Uuid::nil()))), AgentError::MessageHandling(_)));
+        assert!(matches!(boxed(Box::new(devops::DevOpsError::MonitoringError("no metrics".to_string()))), AgentError::Dependency(_)));
+        assert!(matches!(boxed(Box::new(PayloadError::MissingField("ticket_id"))), AgentError::MessageHandling(_)));
+        assert_eq!(boxed("boom".into()), AgentError::TaskExecution("boom".to_string()));
+        assert_eq!(boxed(Box::new(AgentError::Fatal("gone".to_string()))), AgentError::Fatal("gone".to_string()));
+        assert!(!AgentError::Fatal("gone".to_string()).is_retryable());
+    }
+}
@@ -7,18 +7,65 @@
 //! - DNS management and domain configuration
 //! - VPN and secure connectivity
 //! - Network monitoring and performance optimization
+//! - Weighted traffic shifting between the live and candidate releases of an
+//!   environment, for canary and blue/green deployments
+//! - Load balancers DevOps infrastructure plans point at servers by hostname,
+//!   resolved through DNS A records
+//! - IP address management: segment CIDRs are validated and may not
+//!   overlap, and devices and load balancer backends draw their addresses
+//!   from their segment's pool; servers live in the `servers` segment
+//! - Firewall rule analysis and packet simulation against the ordered rules
+//! - A topology graph of segments and devices for reachability, shortest
+//!   paths and partitions left by offline devices
+//! - Bandwidth, latency and packet loss from simulated service traffic, and
+//!   capacity upgrades of the segments it fills
+//! - Load balancer health checks against the DevOps servers behind the
+//!   backends, ejecting and readmitting them, and request distribution
+//! - A DNS zone with several records per name, changes that wait out the
+//!   TTL of the records they replace, and BIND export
+//! - TLS certificates of the service endpoints, renewed ahead of expiry
+//! - WAN links and site-to-site VPNs between sites, with failover routing
+//!   around the links down and the cross-site latency of multi-region
+//!   services
+//! - DDoS attacks on the public-facing segments, fought off with
+//!   rate-limiting firewall rules, scrubbing at the load balancers and
+//!   upstream blackholing
+//! - Flow logs: a NetFlow-style record of every flow each step, kept for the
+//!   evening's threat hunting and DLP monitoring
 
 use crate::agents::{Agent, AgentTrait, Department};
+use crate::balancer::{self, BackendChange, LoadBalancerStats};
+use crate::certs::{self, CertificateEvent, CertificateInventory};
 use crate::communication::{Message, MessageBus, MessagePriority};
+use crate::config::{CertificateSettings, DdosSettings, TrafficSettings, WanSettings};
+use crate::ddos::{DdosDefense, DdosEvent, DdosEventKind, Mitigation};
+use crate::departments::devops::ServerStatus;
+use crate::dns::{DnsError, DnsZone};
+use crate::firewall::{self, FirewallReport};
+use crate::flowlog::{self, FlowRecord};
+use crate::ipam::{self, AddressPool, IpamError};
+use crate::payload::MessagePayload;
 use crate::projects::{Project, Task};
+use crate::topology::TopologyGraph;
+use crate::traffic::{self, Flow, ServiceDemand, TrafficReport};
+use crate::wan::{LatencySample, LinkKind, Wan, WanEvent};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::net::IpAddr;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Segment servers and load balancer backends get their addresses from
+pub const SERVER_SEGMENT: &str = "servers";
+
+/// CIDR of the servers segment when Networking creates it
+const SERVER_CIDR: &str = "10.1.0.0/16";
+
+/// Flow records kept until they are taken; the oldest go first
+const MAX_FLOW_RECORDS: usize = 100_000;
+
 /// Networking Agent specialized in network infrastructure and connectivity
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkingAgent {
@@ -36,6 +83,29 @@ pub struct NetworkingAgent {
     pub network_services: HashMap<String, NetworkService>,
     /// Network performance metrics
     pub performance_metrics: NetworkMetrics,
+    /// Traffic the catalog services draw, as last reported
+    #[serde(default)]
+    pub traffic_demand: Vec<ServiceDemand>,
+    #[serde(default)]
+    pub traffic_settings: TrafficSettings,
+    /// The last day of traffic
+    #[serde(default)]
+    pub traffic: TrafficReport,
+    /// Flows of the last day of traffic, logged every step
+    #[serde(default)]
+    pub traffic_flows: Vec<Flow>,
+    /// Flow records captured since they were last taken
+    #[serde(default)]
+    pub flow_log: Vec<FlowRecord>,
+    /// TLS certificates of the service endpoints
+    #[serde(default)]
+    pub certificates: CertificateInventory,
+    /// Sites and the WAN between them
+    #[serde(default)]
+    pub wan: Wan,
+    /// DDoS attacks under way and past
+    #[serde(default)]
+    pub ddos: DdosDefense,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +130,27 @@ pub struct NetworkSegment {
     pub security_level: SecurityLevel,
     pub connected_segments: Vec<String>,
     pub devices: Vec<NetworkDevice>,
+    /// Addresses handed out from the CIDR; built from it when missing
+    #[serde(default)]
+    pub pool: Option<AddressPool>,
+    /// Capacity after upgrades; the traffic settings' default until then
+    #[serde(default)]
+    pub capacity_mbps: Option<u64>,
+}
+
+impl NetworkSegment {
+    /// The segment's address pool, parsed from its CIDR on first use
+    pub fn pool_mut(&mut self) -> Result<&mut AddressPool, IpamError> {
+        if self.pool.is_none() {
+            let mut pool = AddressPool::new(&self.cidr)?;
+            for device in &self.devices {
+                // Devices recorded before pools existed keep their addresses
+                let _ = pool.claim(device.ip_address);
+            }
+            self.pool = Some(pool);
+        }
+        Ok(self.pool.as_mut().expect("just built"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,7 +181,8 @@ pub enum DeviceStatus {
     Maintenance,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// From least to most secure
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SecurityLevel {
     Public,
     DMZ,
@@ -130,6 +222,8 @@ pub enum FirewallAction {
     Allow,
     Deny,
     Log,
+    /// Throttle matching traffic and let the rest through
+    RateLimit,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,6 +234,8 @@ pub struct LoadBalancer {
     pub backends: Vec<BackendServer>,
     pub health_check: HealthCheck,
     pub status: LoadBalancerStatus,
+    #[serde(default)]
+    pub stats: LoadBalancerStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -156,6 +252,14 @@ pub struct BackendServer {
     pub port: u16,
     pub weight: u32,
     pub healthy: bool,
+    /// Health checks passed or failed in a row
+    #[serde(default)]
+    pub consecutive_successes: u32,
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    /// Requests it has served
+    #[serde(default)]
+    pub requests: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -183,8 +287,9 @@ pub enum LoadBalancerStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DNSConfig {
-    /// Domain records
-    pub records: HashMap<String, DNSRecord>,
+    /// Domain records, in effect and propagating
+    #[serde(flatten)]
+    pub zone: DnsZone,
     /// Name servers
     pub name_servers: Vec<String>,
     /// DNSSEC enabled
@@ -193,7 +298,7 @@ pub struct DNSConfig {
     pub last_update: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DNSRecord {
     pub record_type: RecordType,
     pub value: String,
@@ -319,20 +424,44 @@ impl NetworkingAgent {
             network_topology: NetworkTopology::default(),
             network_services: HashMap::new(),
             performance_metrics: NetworkMetrics::default(),
+            traffic_demand: Vec::new(),
+            traffic_settings: TrafficSettings::default(),
+            traffic: TrafficReport::default(),
+            traffic_flows: Vec::new(),
+            flow_log: Vec::new(),
+            certificates: CertificateInventory::default(),
+            wan: Wan::default(),
+            ddos: DdosDefense::default(),
         }
     }
 
-    /// Configure network segment
+    /// Configure network segment; its CIDR must parse and overlap no
+    /// other segment's
     pub async fn configure_segment(&mut self, config: SegmentConfig) -> Result<String, NetworkingError> {
+        self.add_segment(config)
+    }
+
+    fn add_segment(&mut self, config: SegmentConfig) -> Result<String, NetworkingError> {
+        let pool = AddressPool::new(&config.cidr)?;
+        for other in self.network_topology.segments.values() {
+            let Ok(network) = ipam::parse_cidr(&other.cidr) else {
+                continue;
+            };
+            if ipam::overlaps(pool.network(), &network) {
+                return Err(IpamError::Overlap(format!("{} ({})", config.name, config.cidr), format!("{} ({})", other.name, other.cidr)).into());
+            }
+        }
         let segment_id = format!("seg-{}", Uuid::new_v4().simple());
 
         let segment = NetworkSegment {
             id: segment_id.clone(),
-            name: config.name,
-            cidr: config.cidr,
+            name: config.name.clone(),
+            cidr: pool.network().to_string(),
             security_level: config.security_level,
             connected_segments: vec![],
             devices: vec![],
+            pool: Some(pool),
+            capacity_mbps: None,
         };
 
         self.network_topology.segments.insert(segment_id.clone(), segment);
@@ -341,6 +470,243 @@ impl NetworkingAgent {
         Ok(segment_id)
     }
 
+    fn segment_mut(&mut self, name: &str) -> Result<&mut NetworkSegment, IpamError> {
+        self.network_topology.segments.values_mut()
+            .find(|segment| segment.name == name)
+            .ok_or_else(|| IpamError::UnknownSegment(name.to_string()))
+    }
+
+    /// Take a free address from a segment's pool
+    pub fn allocate_address(&mut self, segment: &str) -> Result<IpAddr, NetworkingError> {
+        Ok(self.segment_mut(segment)?.pool_mut()?.allocate()?)
+    }
+
+    /// Give an address back to a segment's pool; returns whether it was taken
+    pub fn release_address(&mut self, segment: &str, address: IpAddr) -> bool {
+        self.segment_mut(segment).and_then(|segment| segment.pool_mut()).is_ok_and(|pool| pool.release(address))
+    }
+
+    /// Add a device to a segment at the next free address
+    pub fn add_device(&mut self, segment: &str, device_type: DeviceType, mac_address: &str, now: chrono::DateTime<chrono::Utc>) -> Result<IpAddr, NetworkingError> {
+        let segment = self.segment_mut(segment)?;
+        let ip_address = segment.pool_mut()?.allocate()?;
+        segment.devices.push(NetworkDevice {
+            id: format!("dev-{}", Uuid::new_v4().simple()),
+            device_type,
+            ip_address,
+            mac_address: mac_address.to_string(),
+            status: DeviceStatus::Online,
+            last_seen: now,
+        });
+        Ok(ip_address)
+    }
+
+    /// Take a device off a segment, releasing its address
+    pub fn remove_device(&mut self, segment: &str, device_id: &str) -> Option<NetworkDevice> {
+        let segment = self.segment_mut(segment).ok()?;
+        let index = segment.devices.iter().position(|device| device.id == device_id)?;
+        let device = segment.devices.remove(index);
+        if let Ok(pool) = segment.pool_mut() {
+            pool.release(device.ip_address);
+        }
+        Some(device)
+    }
+
+    /// Link two segments; traffic between them goes through the first's
+    /// routers and firewalls
+    pub fn connect_segments(&mut self, from: &str, to: &str) -> Result<(), NetworkingError> {
+        self.segment_mut(to)?;
+        let segment = self.segment_mut(from)?;
+        if !segment.connected_segments.iter().any(|linked| linked == to) {
+            segment.connected_segments.push(to.to_string());
+        }
+        Ok(())
+    }
+
+    /// Set a device's status; returns whether the device exists
+    pub fn set_device_status(&mut self, device_id: &str, status: DeviceStatus) -> bool {
+        let device = self.network_topology.segments.values_mut()
+            .flat_map(|segment| segment.devices.iter_mut())
+            .find(|device| device.id == device_id);
+        match device {
+            Some(device) => {
+                device.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Segments and devices as a graph, as they are now
+    pub fn topology_graph(&self) -> TopologyGraph {
+        TopologyGraph::build(&self.network_topology)
+    }
+
+    /// Next free address in the servers segment, creating the segment if
+    /// it does not exist yet
+    fn allocate_server_address(&mut self) -> Result<IpAddr, NetworkingError> {
+        self.ensure_server_segment()?;
+        self.allocate_address(SERVER_SEGMENT)
+    }
+
+    fn ensure_server_segment(&mut self) -> Result<(), NetworkingError> {
+        if self.segment_mut(SERVER_SEGMENT).is_err() {
+            self.add_segment(SegmentConfig {
+                name: SERVER_SEGMENT.to_string(),
+                cidr: SERVER_CIDR.to_string(),
+                security_level: SecurityLevel::Internal,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Health-check every load balancer's backends over `elapsed_seconds`
+    /// against the DevOps servers their DNS records point at, then spread
+    /// the requests that arrived meanwhile; returns the backends ejected
+    /// or readmitted
+    pub fn run_load_balancers(&mut self, servers: &[ServerStatus], elapsed_seconds: u64, requests_per_second: f64, rng: &mut impl rand::Rng) -> Vec<BackendChange> {
+        let by_hostname: HashMap<&str, &ServerStatus> = servers.iter().map(|server| (server.hostname.as_str(), server)).collect();
+        let zone = &self.network_topology.dns_config.zone;
+        let by_address: HashMap<IpAddr, &ServerStatus> = zone.records.keys()
+            .filter_map(|hostname| by_hostname.get(hostname.as_str()).map(|server| (hostname, *server)))
+            .flat_map(|(hostname, server)| zone.addresses(hostname).into_iter().map(move |address| (address, server)))
+            .collect();
+        let requests = (requests_per_second.max(0.0) * elapsed_seconds as f64) as u64;
+        let mut changes = Vec::new();
+        for lb in &mut self.network_topology.load_balancers {
+            changes.extend(balancer::run_checks(lb, &by_address, elapsed_seconds, rng));
+            balancer::distribute(lb, requests, &by_address);
+        }
+        changes
+    }
+
+    /// Fail and repair WAN links over `elapsed_seconds` and route traffic
+    /// between sites around the links down; site-to-site VPNs show whether
+    /// their link is up
+    pub fn run_wan(&mut self, settings: &WanSettings, now: chrono::DateTime<chrono::Utc>, elapsed_seconds: u64, rng: &mut impl rand::Rng) -> Vec<WanEvent> {
+        self.wan.configure(settings);
+        let events = self.wan.step(now, elapsed_seconds, settings.repair_minutes, rng);
+        for link in self.wan.links.iter().filter(|link| link.kind == LinkKind::Vpn) {
+            let id = format!("vpn-{}-{}", link.from, link.to);
+            let status = if link.up { VPNStatus::Connected } else { VPNStatus::Failed };
+            match self.network_topology.vpn_configs.iter_mut().find(|vpn| vpn.id == id) {
+                Some(vpn) => vpn.status = status,
+                None => self.network_topology.vpn_configs.push(VPNConfig {
+                    id,
+                    name: link.name(),
+                    vpn_type: VPNType::IPSec,
+                    remote_endpoint: link.to.clone(),
+                    local_networks: vec![link.from.clone()],
+                    remote_networks: vec![link.to.clone()],
+                    status,
+                }),
+            }
+        }
+        events
+    }
+
+    /// Defend the public-facing segments over `elapsed_seconds`: rate-limit
+    /// attacks with firewall rules, which go once they are over, and scrub
+    /// them at the load balancers or have them blackholed upstream. Attack
+    /// traffic getting through shows in the segments' bandwidth.
+    pub fn defend_ddos(&mut self, settings: &DdosSettings, now: chrono::DateTime<chrono::Utc>, elapsed_seconds: u64, rng: &mut impl rand::Rng) -> Vec<DdosEvent> {
+        let capacities = self.segment_capacities();
+        let segments = self.network_topology.segments.values()
+            .filter(|segment| segment.security_level <= SecurityLevel::DMZ)
+            .map(|segment| (segment.name.clone(), capacities[&segment.name]))
+            .collect();
+        let scrubbing = self.network_topology.load_balancers.iter().any(|lb| lb.status != LoadBalancerStatus::Offline);
+        let events = self.ddos.step(now, elapsed_seconds, &segments, scrubbing, settings, rng);
+        for event in &events {
+            let rule = format!("ddos-rate-limit-{}", event.segment);
+            match event.kind {
+                DdosEventKind::Mitigation { mitigation: Mitigation::RateLimit, .. } => self.network_topology.firewall_rules.insert(0, FirewallRule {
+                    id: format!("fw-{}", Uuid::new_v4().simple()),
+                    name: rule,
+                    source_segment: "any".to_string(),
+                    destination_segment: event.segment.clone(),
+                    port_range: PortRange { start: 0, end: u16::MAX },
+                    protocol: Protocol::Any,
+                    action: FirewallAction::RateLimit,
+                    enabled: true,
+                }),
+                DdosEventKind::Ended { .. } => self.network_topology.firewall_rules.retain(|existing| existing.name != rule),
+                _ => {}
+            }
+        }
+        let legitimate = |segment: &str| self.traffic.segments.get(segment).map_or(0, |load| load.bps);
+        let bandwidth = &mut self.performance_metrics.bandwidth_usage;
+        for event in events.iter().filter(|event| matches!(event.kind, DdosEventKind::Ended { .. })) {
+            if let Some(metrics) = bandwidth.get_mut(&event.segment) {
+                metrics.inbound_bps = legitimate(&event.segment);
+            }
+        }
+        for attack in self.ddos.active() {
+            let metrics = bandwidth.entry(attack.segment.clone()).or_insert(BandwidthMetrics { inbound_bps: 0, outbound_bps: 0, total_bytes: 0 });
+            // Blackholing drops the segment's legitimate traffic upstream too
+            metrics.inbound_bps = if attack.has(Mitigation::Blackhole) { 0 } else { legitimate(&attack.segment) + attack.residual_bps() };
+        }
+        events
+    }
+
+    /// Round trips to each multi-region service sampled since the last call
+    pub fn take_service_latency(&mut self) -> BTreeMap<String, LatencySample> {
+        self.wan.take_latency()
+    }
+
+    /// Bring the certificate inventory in line with the services' endpoints
+    /// in every environment, then warn of expiries and run renewals
+    pub fn check_certificates(&mut self, services: &[String], environments: &[String], now: chrono::DateTime<chrono::Utc>, settings: &CertificateSettings, rng: &mut impl rand::Rng) -> Vec<CertificateEvent> {
+        let endpoints = certs::endpoints(services, environments, &self.network_topology.dns_config.zone.origin);
+        let mut events = self.certificates.sync(&endpoints, now, settings, rng);
+        events.extend(self.certificates.check(now, settings, rng));
+        events
+    }
+
+    /// Take the services' traffic demand for the next monitoring
+    pub fn set_traffic_demand(&mut self, demand: Vec<ServiceDemand>, settings: TrafficSettings) {
+        self.traffic_demand = demand;
+        self.traffic_settings = settings;
+    }
+
+    /// Capacity of every segment in bps
+    fn segment_capacities(&self) -> BTreeMap<String, u64> {
+        self.network_topology.segments.values()
+            .map(|segment| (segment.name.clone(), segment.capacity_mbps.unwrap_or(self.traffic_settings.segment_capacity_mbps).saturating_mul(1_000_000)))
+            .collect()
+    }
+
+    /// Generate a day of traffic for the demand and route it over the
+    /// topology
+    fn simulate_traffic(&mut self) -> Result<TrafficReport, NetworkingError> {
+        if !self.traffic_demand.is_empty() {
+            self.ensure_server_segment()?;
+        }
+        let segments = self.network_topology.segments.values().map(|segment| (segment.name.clone(), segment.security_level.clone())).collect();
+        let flows = traffic::generate(&self.traffic_demand, &segments, SERVER_SEGMENT, &self.traffic_settings, &mut rand::thread_rng());
+        let report = traffic::simulate(&flows, &self.topology_graph(), &self.segment_capacities(), &self.traffic_settings);
+        self.traffic_flows = flows;
+        Ok(report)
+    }
+
+    /// Log the traffic flows of the last `elapsed_seconds` as seen by the
+    /// firewall; the records are also kept until taken
+    pub fn capture_flows(&mut self, now: chrono::DateTime<chrono::Utc>, elapsed_seconds: u64, rng: &mut impl rand::Rng) -> Vec<FlowRecord> {
+        let start = now - chrono::Duration::seconds(elapsed_seconds as i64);
+        let records = flowlog::capture(&self.traffic_flows, &self.network_topology, start, now, rng);
+        self.flow_log.extend(records.iter().cloned());
+        if self.flow_log.len() > MAX_FLOW_RECORDS {
+            let excess = self.flow_log.len() - MAX_FLOW_RECORDS;
+            self.flow_log.drain(..excess);
+        }
+        records
+    }
+
+    /// Flow records captured since the last call
+    pub fn take_flow_log(&mut self) -> Vec<FlowRecord> {
+        std::mem::take(&mut self.flow_log)
+    }
+
     /// Add firewall rule
     pub async fn add_firewall_rule(&mut self, rule_config: FirewallRuleConfig) -> Result<String, NetworkingError> {
         let rule_id = format!("fw-{}", Uuid::new_v4().simple());
@@ -362,6 +728,16 @@ impl NetworkingAgent {
         Ok(rule_id)
     }
 
+    /// The firewall rule that decides a packet, if any does
+    pub fn simulate_packet(&self, source: &str, destination: &str, port: u16, protocol: &Protocol) -> Option<&FirewallRule> {
+        firewall::simulate_packet(&self.network_topology.firewall_rules, source, destination, port, protocol)
+    }
+
+    /// Conflicting, shadowed, redundant and overly broad firewall rules
+    pub fn analyze_firewall(&self) -> FirewallReport {
+        firewall::analyze(&self.network_topology.firewall_rules)
+    }
+
     /// Configure load balancer
     pub async fn configure_load_balancer(&mut self, config: LoadBalancerConfig) -> Result<String, NetworkingError> {
         let lb_id = format!("lb-{}", Uuid::new_v4().simple());
@@ -373,6 +749,7 @@ impl NetworkingAgent {
             backends: config.backends,
             health_check: config.health_check,
             status: LoadBalancerStatus::Active,
+            stats: LoadBalancerStats::default(),
         };
 
         self.network_topology.load_balancers.push(load_balancer);
@@ -381,13 +758,31 @@ impl NetworkingAgent {
         Ok(lb_id)
     }
 
-    /// Update DNS records
-    pub async fn update_dns_record(&mut self, domain: &str, record: DNSRecord) -> Result<(), NetworkingError> {
-        self.network_topology.dns_config.records.insert(domain.to_string(), record);
-        self.network_topology.dns_config.last_update = chrono::Utc::now();
+    /// Update DNS records: add a record to the name, or replace the one of
+    /// the same type and value; returns when resolvers see the change
+    pub async fn update_dns_record(&mut self, domain: &str, record: DNSRecord, now: chrono::DateTime<chrono::Utc>) -> Result<chrono::DateTime<chrono::Utc>, NetworkingError> {
+        let propagates_at = self.network_topology.dns_config.zone.add(domain, record, now)?;
+        self.network_topology.dns_config.last_update = now;
 
-        println!("🌐 Networking: Updated DNS record for {}", domain);
-        Ok(())
+        println!("🌐 Networking: Updated DNS record for {}, visible from {}", domain, propagates_at.format("%H:%M:%S"));
+        Ok(propagates_at)
+    }
+
+    /// Put DNS changes whose old records have expired from caches into
+    /// effect; returns the names changed
+    pub fn propagate_dns(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+        let names = self.network_topology.dns_config.zone.apply_due(now);
+        if !names.is_empty() {
+            self.network_topology.dns_config.last_update = now;
+            println!("🌐 Networking: DNS changes to {} propagated", names.join(", "));
+        }
+        names
+    }
+
+    /// The DNS zone as a BIND zone file
+    pub fn export_zone(&self) -> String {
+        let dns = &self.network_topology.dns_config;
+        dns.zone.to_bind(&dns.name_servers)
     }
 
     /// Configure VPN connection
@@ -401,6 +796,13 @@ impl NetworkingAgent {
 
     /// Monitor network performance
     pub async fn monitor_performance(&mut self) -> Result<(), NetworkingError> {
+        if self.traffic_settings.enabled {
+            self.traffic = self.simulate_traffic()?;
+            self.record_traffic_metrics();
+            self.warn_of_partitions();
+            return Ok(());
+        }
+
         // Simulate network monitoring
         for (segment_name, segment) in &self.network_topology.segments.clone() {
             let metrics = BandwidthMetrics {
@@ -428,9 +830,33 @@ impl NetworkingAgent {
 
         self.performance_metrics.last_update = chrono::Utc::now();
 
+        self.warn_of_partitions();
         Ok(())
     }
 
+    fn warn_of_partitions(&self) {
+        for pieces in self.topology_graph().partitions() {
+            let pieces: Vec<String> = pieces.iter().map(|piece| format!("[{}]", piece.iter().cloned().collect::<Vec<_>>().join(", "))).collect();
+            println!("⚠️ Networking: Offline devices partitioned the network into {}", pieces.join(" | "));
+        }
+    }
+
+    /// Metrics of the last day of traffic
+    fn record_traffic_metrics(&mut self) {
+        let report = &self.traffic;
+        let metrics = &mut self.performance_metrics;
+        metrics.bandwidth_usage = report.segments.iter()
+            .map(|(name, load)| (name.clone(), BandwidthMetrics { inbound_bps: load.bps, outbound_bps: load.bps, total_bytes: load.bps.saturating_mul(86_400) / 8 }))
+            .collect();
+        metrics.latency_stats = report.latency.clone().unwrap_or(LatencyStats { average_ms: 0.0, min_ms: 0.0, max_ms: 0.0, p95_ms: 0.0 });
+        metrics.packet_loss = report.packet_loss.clone().unwrap_or(PacketLossStats { percentage: 0.0, total_packets: 0, lost_packets: 0 });
+        let connections = report.flows as u32;
+        metrics.connection_counts.active_connections = connections;
+        metrics.connection_counts.total_connections += u64::from(connections);
+        metrics.connection_counts.peak_connections = metrics.connection_counts.peak_connections.max(connections);
+        metrics.last_update = chrono::Utc::now();
+    }
+
     /// Optimize network performance
     pub async fn optimize_performance(&mut self) -> Result<Vec<String>, NetworkingError> {
         let mut optimizations = Vec::new();
@@ -445,6 +871,17 @@ impl NetworkingAgent {
             optimizations.push("Packet loss detected - investigate network issues".to_string());
         }
 
+        // Double the capacity of segments the traffic fills
+        let threshold = self.traffic_settings.upgrade_utilization;
+        let default_mbps = self.traffic_settings.segment_capacity_mbps;
+        for (name, load) in self.traffic.segments.iter().filter(|(_, load)| load.utilization >= threshold) {
+            if let Some(segment) = self.network_topology.segments.values_mut().find(|segment| segment.name == *name) {
+                let capacity = segment.capacity_mbps.unwrap_or(default_mbps).saturating_mul(2);
+                segment.capacity_mbps = Some(capacity);
+                optimizations.push(format!("Segment {} ran at {:.0}% - upgraded to {} Mbps", name, load.utilization * 100.0, capacity));
+            }
+        }
+
         // Check load balancer distribution
         for lb in &self.network_topology.load_balancers {
             let healthy_backends = lb.backends.iter().filter(|b| b.healthy).count();
@@ -461,6 +898,95 @@ impl NetworkingAgent {
         Ok(optimizations)
     }
 
+    /// Send `percent` of an environment's traffic to its candidate release
+    ///
+    /// Each environment has a weighted load balancer with the live backend
+    /// first and the candidate second. At 100% the candidate is promoted to
+    /// live; at 0% all traffic is back on the live release.
+    pub fn shift_traffic(&mut self, environment: &str, percent: u8) -> &LoadBalancer {
+        let percent = u32::from(percent.min(100));
+        let index = match self.network_topology.load_balancers.iter().position(|lb| lb.name == environment) {
+            Some(index) => index,
+            None => {
+                let addresses = [self.allocate_server_address(), self.allocate_server_address()];
+                let backends = addresses.into_iter()
+                    .map(|address| backend(address.unwrap_or_else(|e| {
+                        println!("⚠️ Networking: No address for a {} backend: {}", environment, e);
+                        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+                    })))
+                    .collect();
+                self.network_topology.load_balancers.push(load_balancer(environment, LoadBalancingAlgorithm::WeightedRoundRobin, backends));
+                self.network_topology.load_balancers.len() - 1
+            }
+        };
+
+        let lb = &mut self.network_topology.load_balancers[index];
+        if percent == 100 {
+            lb.backends.swap(0, 1);
+            lb.backends[0].weight = 100;
+            lb.backends[1].weight = 0;
+            println!("🔀 Networking: Promoted the candidate release of {}", environment);
+        } else {
+            lb.backends[0].weight = 100 - percent;
+            lb.backends[1].weight = percent;
+            println!("🔀 Networking: {}% of {} traffic on the candidate release", percent, environment);
+        }
+        lb
+    }
+
+    /// Point the named load balancer at servers by hostname, creating it if
+    /// needed; no backends removes it. Hostnames without an A record get one
+    /// at an address from the servers segment.
+    pub fn configure_backends(&mut self, name: &str, hostnames: &[String]) -> Option<&LoadBalancer> {
+        if hostnames.is_empty() {
+            self.network_topology.load_balancers.retain(|lb| lb.name != name);
+            println!("🗑️ Networking: Removed load balancer {}", name);
+            return None;
+        }
+
+        let mut backends = Vec::new();
+        for hostname in hostnames {
+            if self.network_topology.dns_config.zone.latest(hostname).is_empty() {
+                let address = match self.allocate_server_address() {
+                    Ok(address) => address,
+                    Err(e) => {
+                        println!("⚠️ Networking: No address for {}: {}", hostname, e);
+                        continue;
+                    }
+                };
+                let record = DNSRecord {
+                    record_type: RecordType::A,
+                    value: address.to_string(),
+                    ttl: 300,
+                    proxied: false,
+                };
+                if let Err(e) = self.network_topology.dns_config.zone.add(hostname, record, chrono::Utc::now()) {
+                    println!("⚠️ Networking: No DNS record for {}: {}", hostname, e);
+                    self.release_address(SERVER_SEGMENT, address);
+                    continue;
+                }
+            }
+            // Every address the name resolves to is a backend
+            for ip_address in self.network_topology.dns_config.zone.addresses(hostname) {
+                backends.push(BackendServer { weight: 1, ..backend(ip_address) });
+            }
+        }
+
+        let load_balancers = &mut self.network_topology.load_balancers;
+        let index = match load_balancers.iter().position(|lb| lb.name == name) {
+            Some(index) => {
+                load_balancers[index].backends = backends;
+                index
+            }
+            None => {
+                load_balancers.push(load_balancer(name, LoadBalancingAlgorithm::RoundRobin, backends));
+                load_balancers.len() - 1
+            }
+        };
+        println!("⚖️ Networking: Load balancer {} -> {}", name, hostnames.join(", "));
+        Some(&load_balancers[index])
+    }
+
     /// Register network service
     pub async fn register_service(&mut self, service_config: ServiceConfig) -> Result<(), NetworkingError> {
         let service = NetworkService {
@@ -478,6 +1004,39 @@ impl NetworkingAgent {
     }
 }
 
+fn backend(ip_address: IpAddr) -> BackendServer {
+    BackendServer { ip_address, port: 443, weight: 0, healthy: true, consecutive_successes: 0, consecutive_failures: 0, requests: 0 }
+}
+
+fn load_balancer(name: &str, algorithm: LoadBalancingAlgorithm, backends: Vec<BackendServer>) -> LoadBalancer {
+    LoadBalancer {
+        id: format!("lb-{}", Uuid::new_v4().simple()),
+        name: name.to_string(),
+        algorithm,
+        backends,
+        health_check: HealthCheck {
+            check_type: HealthCheckType::HTTP,
+            interval_seconds: 10,
+            timeout_seconds: 5,
+            healthy_threshold: 3,
+            unhealthy_threshold: 2,
+        },
+        status: LoadBalancerStatus::Active,
+        stats: LoadBalancerStats::default(),
+    }
+}
+
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "configure_segment",
+    "add_firewall_rule",
+    "performance_monitor",
+    "register_service",
+    "shift_traffic",
+    "configure_load_balancer",
+    "traffic_demand",
+];
+
 #[async_trait]
 impl AgentTrait for NetworkingAgent {
     async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -517,6 +1076,21 @@ impl AgentTrait for NetworkingAgent {
                 };
                 self.register_service(service_config).await?;
             }
+            "traffic_demand" => {
+                if let Some(MessagePayload::TrafficDemand { demand, settings }) = MessagePayload::from_message(&message)? {
+                    self.set_traffic_demand(demand, settings);
+                }
+            }
+            "shift_traffic" => {
+                if let Some(MessagePayload::TrafficShift { environment, percent, .. }) = MessagePayload::from_message(&message)? {
+                    self.shift_traffic(&environment, percent);
+                }
+            }
+            "configure_load_balancer" => {
+                if let Some(MessagePayload::LoadBalancerUpdate { name, backends }) = MessagePayload::from_message(&message)? {
+                    self.configure_backends(&name, &backends);
+                }
+            }
             _ => {
                 println!("🤷 Networking: Unknown message type: {}", message.message_type);
             }
@@ -541,7 +1115,7 @@ impl AgentTrait for NetworkingAgent {
         }).await?;
 
         // Service health checks
-        for (service_name, service) in &mut self.network_services.clone() {
+        for (service_name, service) in &mut self.network_services {
             // Simulate health check
             service.status = if rand::random::<f32>() < 0.95 {
                 ServiceStatus::Healthy
@@ -587,7 +1161,7 @@ impl Default for NetworkTopology {
             firewall_rules: vec![],
             load_balancers: vec![],
             dns_config: DNSConfig {
-                records: HashMap::new(),
+                zone: DnsZone::default(),
                 name_servers: vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()],
                 dnssec_enabled: true,
                 last_update: chrono::Utc::now(),
@@ -670,11 +1244,14 @@ pub enum NetworkingError {
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
-    #[error("DNS error: {0}")]
-    DNSError(String),
+    #[error(transparent)]
+    Dns(#[from] DnsError),
 
     #[error("VPN error: {0}")]
     VPNError(String),
+
+    #[error(transparent)]
+    Ipam(#[from] IpamError),
 }
 
 #[cfg(test)]
@@ -704,6 +1281,29 @@ mod tests {
         assert_eq!(agent.network_topology.segments.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_segments_validate_cidrs_and_allocate_addresses() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        let segment = |name: &str, cidr: &str| SegmentConfig { name: name.to_string(), cidr: cidr.to_string(), security_level: SecurityLevel::Internal };
+        assert!(matches!(agent.configure_segment(segment("bad", "10.0.1.0/24x")).await, Err(NetworkingError::Ipam(IpamError::InvalidCidr(..)))));
+        agent.configure_segment(segment("app", "10.0.1.0/24")).await.unwrap();
+        assert!(matches!(agent.configure_segment(segment("wide", "10.0.0.0/16")).await, Err(NetworkingError::Ipam(IpamError::Overlap(..)))));
+
+        let now = chrono::Utc::now();
+        let router = agent.add_device("app", DeviceType::Router, "00:00:5e:00:53:01", now).unwrap();
+        assert_eq!(router, IpAddr::from([10, 0, 1, 1]));
+        let device_id = agent.network_topology.segments.values().next().unwrap().devices[0].id.clone();
+        assert_eq!(agent.allocate_address("app").unwrap(), IpAddr::from([10, 0, 1, 2]));
+        assert!(agent.remove_device("app", &device_id).is_some());
+        assert_eq!(agent.allocate_address("app").unwrap(), router);
+        assert!(matches!(agent.allocate_address("nowhere"), Err(NetworkingError::Ipam(IpamError::UnknownSegment(_)))));
+
+        // Backends get addresses from the servers segment
+        let lb = agent.configure_backends("web", &["web-1".to_string()]).unwrap();
+        assert_eq!(lb.backends[0].ip_address, IpAddr::from([10, 1, 0, 1]));
+        assert!(agent.release_address(SERVER_SEGMENT, IpAddr::from([10, 1, 0, 1])));
+    }
+
     #[tokio::test]
     async fn test_firewall_rule_creation() {
         let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
@@ -742,4 +1342,36 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(agent.network_services.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_traffic_shift_promotes_candidate() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        let weights = |lb: &LoadBalancer| lb.backends.iter().map(|backend| backend.weight).collect::<Vec<_>>();
+
+        let lb = agent.shift_traffic("production", 10);
+        assert_eq!(lb.algorithm, LoadBalancingAlgorithm::WeightedRoundRobin);
+        assert_eq!(weights(lb), vec![90, 10]);
+        let candidate = lb.backends[1].ip_address;
+
+        let lb = agent.shift_traffic("production", 100);
+        assert_eq!(weights(lb), vec![100, 0]);
+        assert_eq!(lb.backends[0].ip_address, candidate);
+        assert_eq!(agent.network_topology.load_balancers.len(), 1);
+    }
+
+    #[test]
+    fn test_configure_backends_by_hostname() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        let hosts = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        let lb = agent.configure_backends("web", &hosts(&["web-1", "web-2"])).unwrap();
+        assert_eq!(lb.backends.len(), 2);
+        let web_1 = lb.backends[0].ip_address;
+
+        // Known hosts keep their address
+        let lb = agent.configure_backends("web", &hosts(&["web-1"])).unwrap();
+        assert_eq!(lb.backends[0].ip_address, web_1);
+        assert!(agent.configure_backends("web", &[]).is_none());
+        assert!(agent.network_topology.load_balancers.is_empty());
+    }
+}
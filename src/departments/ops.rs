@@ -2,22 +2,64 @@
 //!
 //! This module implements the Operations department responsible for:
 //! - System administration and maintenance
-//! - Incident response and troubleshooting
-//! - Customer support and ticket management
-//! - Service level agreement (SLA) monitoring
-//! - Change management and release coordination
+//! - Incident response and troubleshooting, starting with the runbook for
+//!   common incidents and escalating to DevOps when it does not help
+//! - Customer support and ticket management: tickets are answered, worked
+//!   for as long as their complexity takes the agent, wait on customer
+//!   replies, get resolved, reopened and closed, and customers answer a CSAT
+//!   survey once they close
+//! - Service level agreement (SLA) monitoring: uptime and response times
+//!   derived from the incidents, servers and network status behind each
+//!   service, measured over the month against an error budget
+//! - Escalation of tickets and incidents that miss their response or
+//!   resolution target
+//! - Change management and release coordination: approved changes are
+//!   carried out at their scheduled time, fail more often the riskier they
+//!   are, are rolled back when they fail and reviewed once finished
 //! - Capacity planning and resource management
+//! - Quota increases too large for DevOps, approved as change requests
+//! - Destructive infrastructure changes DevOps plans, approved the same way
+//! - Promotions of builds into production, approved the same way
+//! - Response-time and availability SLAs of multi-region services, checked
+//!   against the cross-site latency Networking measures
 
 use crate::agents::{Agent, AgentTrait, Department};
 use crate::communication::{Message, MessageBus, MessagePriority};
+use crate::config::{ChangeSettings, TicketSettings};
+use crate::departments::devops::Deployment;
+use crate::departments::networking::ServiceStatus;
+use crate::knowledge::Consultation;
+use crate::payload::MessagePayload;
 use crate::projects::{Project, Task};
+use crate::quota;
+use crate::runbook::{RunbookOutcome, RunbookRun};
+use crate::system::SystemActor;
+use crate::timeline::{self, Timeline, TimelineEntry, TimelineEntryKind};
+use crate::wan::LatencySample;
 use async_trait::async_trait;
+use chrono::Datelike;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Message type of an SLA breach reported to the orchestrator
+pub const SLA_ESCALATION: &str = "sla_escalation";
+
+/// Message type of the escalation notice sent to the department manager
+pub const ESCALATION_NOTICE: &str = "escalation_notice";
+
+/// Response time of a healthy service without a measured one, in milliseconds
+const BASE_RESPONSE_MS: f64 = 150.0;
+
+/// How much slower a degraded service answers
+const DEGRADED_SLOWDOWN: f64 = 3.0;
+
+/// Minutes in the monthly measurement period error budgets are sized for
+const PERIOD_MINUTES: f64 = 30.0 * 24.0 * 60.0;
+
 /// Operations Agent specialized in system operations and support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpsAgent {
@@ -37,6 +79,16 @@ pub struct OpsAgent {
     pub sla_tracking: SLATracking,
     /// Change management queue
     pub change_queue: Vec<ChangeRequest>,
+    /// Window in which incidents on the same service are treated as duplicates
+    pub duplicate_window_minutes: i64,
+    /// Auto-close and reopen rules for support tickets
+    pub ticket_policy: TicketPolicy,
+    /// Response and resolution targets tickets and incidents escalate on
+    #[serde(default)]
+    pub escalation_policy: EscalationPolicy,
+    /// Messages waiting to be picked up by the orchestrator
+    #[serde(default)]
+    pub outbox: Vec<Message>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,14 +99,144 @@ pub struct SupportTicket {
     pub priority: Priority,
     pub status: TicketStatus,
     pub customer_id: Option<String>,
+    pub customer_tier: CustomerTier,
     pub assigned_to: Option<Uuid>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// When the fix was delivered; stops the resolution SLA clock
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the ticket was administratively closed
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Number of times the customer reopened the ticket
+    pub reopen_count: u32,
     pub resolution: Option<String>,
     pub tags: Vec<String>,
+    /// Targets missed so far, each escalated once
+    #[serde(default)]
+    pub breaches: Vec<SlaBreach>,
+    /// Progress of the work and the customer's side of it
+    #[serde(default)]
+    pub work: TicketWork,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl SupportTicket {
+    /// Start of the resolution SLA clock, moved on by the time spent waiting
+    /// on the customer
+    fn clock_start(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at + chrono::Duration::minutes(self.work.paused_minutes)
+    }
+
+    /// Back to work after the customer reopened it; the resolution SLA
+    /// clock resumes from the original creation time, and the new fix takes
+    /// half the work of the first
+    fn reopen(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        self.status = TicketStatus::InProgress;
+        self.resolved_at = None;
+        self.closed_at = None;
+        self.reopen_count += 1;
+        self.updated_at = now;
+        self.work.worked_minutes = self.work.effort_minutes / 2;
+        self.work.working_since = Some(now);
+        self.work.customer_reply_at = None;
+        self.work.surveyed = false;
+        self.work.csat = None;
+    }
+
+    /// Hours from creation to resolution, not counting the time spent
+    /// waiting on the customer
+    pub fn resolution_hours(&self) -> Option<f64> {
+        self.resolved_at.map(|resolved| (resolved - self.clock_start()).num_minutes() as f64 / 60.0)
+    }
+}
+
+/// Work on a ticket and the customer's side of it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketWork {
+    /// 1 to 5, assessed on the first response
+    pub complexity: Option<u8>,
+    /// When the customer first heard back; stops the response SLA clock
+    pub first_response_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Minutes of work the fix takes the assigned agent
+    pub effort_minutes: i64,
+    /// Minutes worked before the current stretch
+    pub worked_minutes: i64,
+    /// Start of the current stretch of work
+    pub working_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Questions for the customer still to ask before the fix
+    pub questions: u32,
+    /// When the ticket started waiting on the customer
+    pub waiting_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the customer replies, or reopens the resolved ticket
+    pub customer_reply_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Minutes spent waiting on the customer, off the resolution SLA clock
+    pub paused_minutes: i64,
+    /// Whether the customer was surveyed since the ticket last closed
+    pub surveyed: bool,
+    /// CSAT score from 1 to 5, when the customer answered the survey
+    pub csat: Option<u8>,
+}
+
+/// What happened to a ticket while it was worked
+#[derive(Debug, Clone, PartialEq)]
+pub enum TicketEventKind {
+    /// First response, `minutes` after the ticket was opened
+    Responded { minutes: i64 },
+    AskedCustomer,
+    CustomerReplied,
+    /// Resolved after `hours` on the resolution SLA clock
+    Resolved { hours: f64 },
+    Reopened,
+    Closed,
+    /// The customer answered the CSAT survey
+    Surveyed { score: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketEvent {
+    pub title: String,
+    pub customer_id: Option<String>,
+    pub kind: TicketEventKind,
+}
+
+impl TicketEvent {
+    fn new(ticket: &SupportTicket, kind: TicketEventKind) -> Self {
+        Self { title: ticket.title.clone(), customer_id: ticket.customer_id.clone(), kind }
+    }
+
+    pub fn description(&self) -> String {
+        match &self.kind {
+            TicketEventKind::Responded { minutes } => format!("answered '{}' after {} minutes", self.title, minutes),
+            TicketEventKind::AskedCustomer => format!("asked the customer for more on '{}'", self.title),
+            TicketEventKind::CustomerReplied => format!("heard back from the customer on '{}'", self.title),
+            TicketEventKind::Resolved { hours } => format!("resolved '{}' in {:.1} hours", self.title, hours),
+            TicketEventKind::Reopened => format!("had '{}' reopened by the customer", self.title),
+            TicketEventKind::Closed => format!("closed '{}'", self.title),
+            TicketEventKind::Surveyed { score } => format!("got a CSAT of {}/5 for '{}'", score, self.title),
+        }
+    }
+}
+
+/// Hours until the customer gets back
+fn reply_delay(settings: &TicketSettings, rng: &mut impl Rng) -> chrono::Duration {
+    let (min, max) = settings.customer_reply_hours;
+    chrono::Duration::hours(i64::from(rng.gen_range(min..=max.max(min))))
+}
+
+/// CSAT from 1 to 5: full marks for a clean fix, a point off for each
+/// missed SLA target and each reopen, and sometimes one more
+fn csat(ticket: &SupportTicket, rng: &mut impl Rng) -> u8 {
+    let penalty = ticket.breaches.len() as i64 + i64::from(ticket.reopen_count) + rng.gen_range(0..=1);
+    (5 - penalty).clamp(1, 5) as u8
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CustomerTier {
+    Standard,
+    Premium,
+    Enterprise,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Priority {
     Low,
     Normal,
@@ -63,6 +245,30 @@ pub enum Priority {
     Critical,
 }
 
+impl Priority {
+    /// 0 for Low up to 4 for Critical
+    pub fn rank(&self) -> u8 {
+        match self {
+            Priority::Low => 0,
+            Priority::Normal => 1,
+            Priority::High => 2,
+            Priority::Urgent => 3,
+            Priority::Critical => 4,
+        }
+    }
+
+    /// Next priority up; `None` at the top
+    pub fn raised(&self) -> Option<Priority> {
+        match self {
+            Priority::Low => Some(Priority::Normal),
+            Priority::Normal => Some(Priority::High),
+            Priority::High => Some(Priority::Urgent),
+            Priority::Urgent => Some(Priority::Critical),
+            Priority::Critical => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TicketStatus {
     Open,
@@ -72,6 +278,130 @@ pub enum TicketStatus {
     Closed,
 }
 
+/// Auto-close and reopen rules for support tickets
+///
+/// Resolved tickets wait for customer confirmation and close automatically
+/// once the auto-close delay passes. Customers may reopen a ticket within the
+/// reopen grace window measured from its resolution, even after it closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketPolicy {
+    /// Days a resolved ticket waits before auto-closing
+    pub default_auto_close_days: i64,
+    /// Per-priority auto-close delays
+    pub auto_close_days_by_priority: HashMap<Priority, i64>,
+    /// Per-tier auto-close delays, taking precedence over priority
+    pub auto_close_days_by_tier: HashMap<CustomerTier, i64>,
+    /// Days after resolution during which the customer may reopen
+    pub reopen_grace_days: i64,
+}
+
+impl TicketPolicy {
+    /// Auto-close delay that applies to a ticket
+    pub fn auto_close_after(&self, ticket: &SupportTicket) -> chrono::Duration {
+        let days = self.auto_close_days_by_tier.get(&ticket.customer_tier)
+            .or_else(|| self.auto_close_days_by_priority.get(&ticket.priority))
+            .copied()
+            .unwrap_or(self.default_auto_close_days);
+        chrono::Duration::days(days)
+    }
+
+    /// Whether the customer may still reopen a ticket
+    pub fn can_reopen(&self, ticket: &SupportTicket, now: chrono::DateTime<chrono::Utc>) -> bool {
+        matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed)
+            && ticket.resolved_at.is_some_and(|resolved| now - resolved <= chrono::Duration::days(self.reopen_grace_days))
+    }
+}
+
+/// Target a ticket or incident missed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SlaBreach {
+    /// Nobody picked it up in time
+    Response,
+    /// Not fixed in time
+    Resolution,
+}
+
+impl SlaBreach {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaBreach::Response => "response",
+            SlaBreach::Resolution => "resolution",
+        }
+    }
+}
+
+/// Minutes allowed to pick work up and to resolve it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SlaTarget {
+    pub response_minutes: i64,
+    pub resolution_minutes: i64,
+}
+
+impl SlaTarget {
+    const fn new(response_minutes: i64, resolution_minutes: i64) -> Self {
+        Self { response_minutes, resolution_minutes }
+    }
+
+    /// First target missed and not yet escalated; the clock runs from creation
+    fn breach(&self, created_at: chrono::DateTime<chrono::Utc>, unanswered: bool, escalated: &[SlaBreach], now: chrono::DateTime<chrono::Utc>) -> Option<SlaBreach> {
+        let age = now - created_at;
+        if unanswered && age > chrono::Duration::minutes(self.response_minutes) && !escalated.contains(&SlaBreach::Response) {
+            return Some(SlaBreach::Response);
+        }
+        if age > chrono::Duration::minutes(self.resolution_minutes) && !escalated.contains(&SlaBreach::Resolution) {
+            return Some(SlaBreach::Resolution);
+        }
+        None
+    }
+}
+
+/// SLA targets by ticket priority and incident severity
+///
+/// A missed target raises the priority or severity one step, and the
+/// orchestrator hands the work to a more skilled agent and tells the
+/// department manager. Work without a target never escalates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub ticket_targets: HashMap<Priority, SlaTarget>,
+    pub incident_targets: HashMap<Severity, SlaTarget>,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            ticket_targets: HashMap::from([
+                (Priority::Low, SlaTarget::new(24 * 60, 5 * 24 * 60)),
+                (Priority::Normal, SlaTarget::new(8 * 60, 3 * 24 * 60)),
+                (Priority::High, SlaTarget::new(4 * 60, 24 * 60)),
+                (Priority::Urgent, SlaTarget::new(60, 8 * 60)),
+                (Priority::Critical, SlaTarget::new(30, 4 * 60)),
+            ]),
+            incident_targets: HashMap::from([
+                (Severity::Sev4, SlaTarget::new(24 * 60, 3 * 24 * 60)),
+                (Severity::Sev3, SlaTarget::new(4 * 60, 24 * 60)),
+                (Severity::Sev2, SlaTarget::new(30, 8 * 60)),
+                (Severity::Sev1, SlaTarget::new(15, 4 * 60)),
+            ]),
+        }
+    }
+}
+
+impl Default for TicketPolicy {
+    fn default() -> Self {
+        Self {
+            default_auto_close_days: 7,
+            auto_close_days_by_priority: HashMap::from([
+                (Priority::Urgent, 3),
+                (Priority::Critical, 3),
+            ]),
+            auto_close_days_by_tier: HashMap::from([
+                (CustomerTier::Enterprise, 14),
+            ]),
+            reopen_grace_days: 14,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Incident {
     pub id: Uuid,
@@ -85,9 +415,96 @@ pub struct Incident {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
     pub assigned_team: Option<String>,
+    /// Master incident this one was merged into
+    pub duplicate_of: Option<Uuid>,
+    /// Incidents merged into this one
+    pub duplicates: Vec<Uuid>,
+    /// Causality ids of the events that raised or were linked to this incident
+    #[serde(default)]
+    pub correlation_ids: Vec<Uuid>,
+    /// Every status the incident has moved through
+    #[serde(default)]
+    pub status_history: Vec<IncidentStatusChange>,
+    /// Targets missed so far, each escalated once
+    #[serde(default)]
+    pub breaches: Vec<SlaBreach>,
+    /// Knowledge base search made when work on the incident started
+    #[serde(default)]
+    pub knowledge: Option<Consultation>,
+    /// Runbook run on the incident before it was worked by hand
+    #[serde(default)]
+    pub runbook: Option<RunbookRun>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Recorded incident status transition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentStatusChange {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub status: IncidentStatus,
+    pub note: Option<String>,
+}
+
+impl Incident {
+    /// Record a status transition
+    fn set_status(&mut self, status: IncidentStatus, note: Option<String>) {
+        self.status = status.clone();
+        self.status_history.push(IncidentStatusChange {
+            at: chrono::Utc::now(),
+            status,
+            note,
+        });
+    }
+
+    /// Whether a message belongs to this incident's chain of events
+    pub fn is_related(&self, message: &Message) -> bool {
+        let names_incident = message.metadata.get(timeline::INCIDENT_KEY)
+            .is_some_and(|id| *id == self.id.to_string());
+        let correlated = timeline::correlation_id(message)
+            .is_some_and(|id| self.correlation_ids.contains(&id));
+        names_incident || correlated
+    }
+
+    /// Reconstruct the ordered timeline of this incident
+    ///
+    /// Status changes come from the incident itself; messages, alerts and chat
+    /// entries are linked by causality id or an explicit incident reference;
+    /// deployments are included when correlated or started within the
+    /// lookback window before the incident up to its resolution.
+    pub fn timeline(&self, messages: &[Message], deployments: &[Deployment]) -> Timeline {
+        let mut timeline = Timeline::new(self.id, self.title.clone());
+
+        for change in &self.status_history {
+            timeline.entries.push(TimelineEntry {
+                at: change.at,
+                kind: TimelineEntryKind::StatusChange,
+                actor: None,
+                summary: match &change.note {
+                    Some(note) => format!("Status → {:?}: {}", change.status, note),
+                    None => format!("Status → {:?}", change.status),
+                },
+                source_id: self.id,
+            });
+        }
+
+        for message in messages.iter().filter(|message| self.is_related(message)) {
+            timeline.push_message(message);
+        }
+
+        let window_start = self.created_at - chrono::Duration::minutes(timeline::DEPLOY_LOOKBACK_MINUTES);
+        let window_end = self.resolved_at.unwrap_or_else(chrono::Utc::now);
+        for deployment in deployments {
+            let correlated = deployment.correlation_id.is_some_and(|id| self.correlation_ids.contains(&id));
+            let in_window = deployment.start_time >= window_start && deployment.start_time <= window_end;
+            if correlated || in_window {
+                timeline.push_deployment(deployment);
+            }
+        }
+
+        timeline.finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Severity {
     Sev1, // Critical - service down
     Sev2, // High - major functionality impacted
@@ -95,6 +512,36 @@ pub enum Severity {
     Sev4, // Low - cosmetic or informational
 }
 
+impl Severity {
+    /// Numeric urgency, higher is more severe
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Sev1 => 4,
+            Severity::Sev2 => 3,
+            Severity::Sev3 => 2,
+            Severity::Sev4 => 1,
+        }
+    }
+
+    /// Next severity up; `None` at Sev1
+    pub fn raised(&self) -> Option<Severity> {
+        match self {
+            Severity::Sev4 => Some(Severity::Sev3),
+            Severity::Sev3 => Some(Severity::Sev2),
+            Severity::Sev2 => Some(Severity::Sev1),
+            Severity::Sev1 => None,
+        }
+    }
+}
+
+/// Ticket or incident moving between Ops agents on escalation
+#[derive(Debug, Clone)]
+pub enum Handover {
+    Ticket(SupportTicket),
+    /// Master incident followed by the duplicates merged into it
+    Incident(Vec<Incident>),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum IncidentStatus {
     Open,
@@ -113,6 +560,93 @@ pub struct SLATracking {
     pub compliance: HashMap<String, f32>,
     /// SLA violations this month
     pub violations: Vec<SLAViolation>,
+    /// Average response times last measured, in milliseconds
+    #[serde(default)]
+    pub response_times: HashMap<String, f32>,
+    /// Uptime and response times measured this period, by service
+    #[serde(default)]
+    pub levels: HashMap<String, ServiceLevel>,
+}
+
+/// What the services' health looked like over an SLA check interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    pub service: String,
+    /// Worst active incident affecting it
+    pub incident: Option<Severity>,
+    /// Share of DevOps servers online
+    pub capacity: f64,
+    /// Networking's status of the service, when it runs one by that name
+    pub network: Option<ServiceStatus>,
+    /// How much slower it answers with no Ops agent on shift
+    #[serde(default)]
+    pub unstaffed_slowdown: Option<f64>,
+}
+
+impl ServiceHealth {
+    /// Down during a Sev1 or Sev2 incident, while Networking has it
+    /// unhealthy or offline, or with no server online
+    pub fn is_down(&self) -> bool {
+        self.incident.as_ref().is_some_and(|severity| severity.rank() >= Severity::Sev2.rank())
+            || matches!(self.network, Some(ServiceStatus::Unhealthy | ServiceStatus::Offline))
+            || self.capacity <= 0.0
+    }
+
+    /// Response time from the healthy `base_ms`: slower while degraded by a
+    /// lesser incident or the network, while nobody is on shift, and as
+    /// servers drop out
+    pub fn response_ms(&self, base_ms: f64) -> f64 {
+        let degraded = self.incident.is_some() || self.network == Some(ServiceStatus::Degraded);
+        let slowdown = if degraded { DEGRADED_SLOWDOWN } else { 1.0 };
+        base_ms * slowdown * self.unstaffed_slowdown.unwrap_or(1.0) / self.capacity.max(0.1)
+    }
+}
+
+/// Uptime and response times of a service over the current measurement period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceLevel {
+    pub period_start: Option<chrono::DateTime<chrono::Utc>>,
+    pub measured_minutes: f64,
+    pub down_minutes: f64,
+    pub checks: u32,
+    /// Checks the service answered slower than its target
+    pub slow_checks: u32,
+    /// Response time at the last check, in milliseconds
+    pub response_ms: f64,
+    /// Whether the period's error budget ran out
+    pub budget_exhausted: bool,
+}
+
+impl ServiceLevel {
+    /// Percentage of the period measured so far the service was up
+    pub fn uptime(&self) -> f64 {
+        if self.measured_minutes <= 0.0 {
+            return 100.0;
+        }
+        100.0 * (1.0 - self.down_minutes / self.measured_minutes)
+    }
+
+    /// Percentage of checks answered within the response time target
+    pub fn response_compliance(&self) -> f64 {
+        if self.checks == 0 {
+            return 100.0;
+        }
+        100.0 * f64::from(self.checks - self.slow_checks) / f64::from(self.checks)
+    }
+
+    /// Minutes of downtime the uptime target allows over a whole period
+    pub fn error_budget_minutes(sla: &SLA) -> f64 {
+        (1.0 - f64::from(sla.uptime_target) / 100.0).max(0.0) * PERIOD_MINUTES
+    }
+
+    /// Share of the period's error budget left, from 1 down to 0
+    pub fn error_budget_remaining(&self, sla: &SLA) -> f64 {
+        let budget = Self::error_budget_minutes(sla);
+        if budget <= 0.0 {
+            return if self.down_minutes > 0.0 { 0.0 } else { 1.0 };
+        }
+        (1.0 - self.down_minutes / budget).max(0.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +658,19 @@ pub struct SLA {
     pub measurement_period: String, // e.g., "monthly"
 }
 
+impl SLA {
+    /// The standard monthly SLA
+    pub fn standard(service_name: &str) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            uptime_target: 99.9,
+            response_time_target: 500,
+            resolution_time_target: 4,
+            measurement_period: "monthly".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SLAViolation {
     pub service: String,
@@ -146,6 +693,12 @@ pub struct ChangeRequest {
     pub status: ChangeStatus,
     pub requester: Uuid,
     pub approver: Option<Uuid>,
+    /// When it was carried out, once its scheduled time came
+    #[serde(default)]
+    pub execution: Option<ChangeExecution>,
+    /// Post-implementation review, some time after it finished
+    #[serde(default)]
+    pub review: Option<ChangeReview>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -164,6 +717,20 @@ pub enum RiskLevel {
     Critical,
 }
 
+impl RiskLevel {
+    /// Chance a change of this risk fails when carried out
+    pub fn failure_chance(&self, settings: &ChangeSettings) -> f64 {
+        let [low, medium, high, critical] = settings.failure_chances;
+        let chance = match self {
+            RiskLevel::Low => low,
+            RiskLevel::Medium => medium,
+            RiskLevel::High => high,
+            RiskLevel::Critical => critical,
+        };
+        chance.clamp(0.0, 1.0)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ChangeStatus {
     Draft,
@@ -172,10 +739,72 @@ pub enum ChangeStatus {
     Scheduled,
     InProgress,
     Completed,
+    /// Failed and its rollback did not restore service either
     Failed,
+    /// Failed and was rolled back
+    RolledBack,
     Cancelled,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChangeExecution {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// When the work is done and the outcome known
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ChangeExecution {
+    pub fn duration_minutes(&self) -> i64 {
+        (self.ends_at - self.started_at).num_minutes()
+    }
+}
+
+/// Post-implementation review of a finished change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChangeReview {
+    pub reviewed_at: chrono::DateTime<chrono::Utc>,
+    pub successful: bool,
+    pub findings: String,
+}
+
+/// What happened to a change while it was carried out
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEventKind {
+    /// Work started, to take `minutes`
+    Started { minutes: i64 },
+    Completed,
+    RolledBack,
+    /// Failed and could not be rolled back
+    Failed,
+    Reviewed { successful: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub change_id: Uuid,
+    pub title: String,
+    pub risk_level: RiskLevel,
+    pub kind: ChangeEventKind,
+}
+
+impl ChangeEvent {
+    fn new(change: &ChangeRequest, kind: ChangeEventKind) -> Self {
+        Self { change_id: change.id, title: change.title.clone(), risk_level: change.risk_level.clone(), kind }
+    }
+
+    pub fn description(&self) -> String {
+        match &self.kind {
+            ChangeEventKind::Started { minutes } => format!("started change '{}' ({:?} risk, {} minutes)", self.title, self.risk_level, minutes),
+            ChangeEventKind::Completed => format!("completed change '{}'", self.title),
+            ChangeEventKind::RolledBack => format!("rolled back failed change '{}'", self.title),
+            ChangeEventKind::Failed => format!("could not roll back failed change '{}'", self.title),
+            ChangeEventKind::Reviewed { successful: true } => format!("reviewed change '{}': implemented as planned", self.title),
+            ChangeEventKind::Reviewed { successful: false } => format!("reviewed failed change '{}'", self.title),
+        }
+    }
+}
+
 impl OpsAgent {
     /// Create a new Operations agent
     pub fn new(name: String, manager_id: Option<Uuid>) -> Self {
@@ -188,6 +817,10 @@ impl OpsAgent {
             incidents: HashMap::new(),
             sla_tracking: SLATracking::default(),
             change_queue: vec![],
+            duplicate_window_minutes: 30,
+            ticket_policy: TicketPolicy::default(),
+            escalation_policy: EscalationPolicy::default(),
+            outbox: vec![],
         }
     }
 
@@ -202,11 +835,17 @@ impl OpsAgent {
             priority: ticket_request.priority,
             status: TicketStatus::Open,
             customer_id: ticket_request.customer_id,
+            customer_tier: ticket_request.customer_tier,
             assigned_to: None, // Will be assigned by routing logic
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            resolved_at: None,
+            closed_at: None,
+            reopen_count: 0,
             resolution: None,
             tags: ticket_request.tags,
+            breaches: vec![],
+            work: TicketWork::default(),
         };
 
         self.support_tickets.insert(ticket_id, ticket);
@@ -218,8 +857,319 @@ impl OpsAgent {
         Ok(ticket_id)
     }
 
+    /// Mark a ticket resolved, stopping its resolution SLA clock
+    pub async fn resolve_ticket(&mut self, ticket_id: Uuid, resolution: String) -> Result<(), OpsError> {
+        let ticket = self.support_tickets.get_mut(&ticket_id)
+            .ok_or(OpsError::TicketNotFound(ticket_id))?;
+
+        if matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err(OpsError::InvalidTicketTransition(format!("ticket {} is already {:?}", ticket_id, ticket.status)));
+        }
+
+        let now = chrono::Utc::now();
+        ticket.status = TicketStatus::Resolved;
+        ticket.resolution = Some(resolution);
+        ticket.resolved_at = Some(now);
+        ticket.updated_at = now;
+        ticket.work.working_since = None;
+
+        println!("✅ Ops: Resolved ticket '{}'", ticket.title);
+        Ok(())
+    }
+
+    /// Close a resolved ticket
+    pub async fn close_ticket(&mut self, ticket_id: Uuid) -> Result<(), OpsError> {
+        let ticket = self.support_tickets.get_mut(&ticket_id)
+            .ok_or(OpsError::TicketNotFound(ticket_id))?;
+
+        if ticket.status != TicketStatus::Resolved {
+            return Err(OpsError::InvalidTicketTransition(format!("only resolved tickets can be closed, ticket {} is {:?}", ticket_id, ticket.status)));
+        }
+
+        let now = chrono::Utc::now();
+        ticket.status = TicketStatus::Closed;
+        ticket.closed_at = Some(now);
+        ticket.updated_at = now;
+
+        println!("🔒 Ops: Closed ticket '{}'", ticket.title);
+        Ok(())
+    }
+
+    /// Reopen a ticket at the customer's request within the grace window
+    pub async fn reopen_ticket(&mut self, ticket_id: Uuid, reason: &str) -> Result<(), OpsError> {
+        let ticket = self.support_tickets.get_mut(&ticket_id)
+            .ok_or(OpsError::TicketNotFound(ticket_id))?;
+
+        let now = chrono::Utc::now();
+        if !self.ticket_policy.can_reopen(ticket, now) {
+            return Err(OpsError::ReopenWindowExpired(ticket_id));
+        }
+
+        ticket.reopen(now);
+
+        println!("🔁 Ops: Customer reopened ticket '{}' ({})", ticket.title, reason);
+        Ok(())
+    }
+
+    /// Close resolved tickets whose auto-close delay has passed
+    pub async fn auto_close_tickets(&mut self) -> Result<Vec<Uuid>, OpsError> {
+        let now = chrono::Utc::now();
+        let due: Vec<Uuid> = self.support_tickets.values()
+            .filter(|ticket| ticket.status == TicketStatus::Resolved)
+            .filter(|ticket| ticket.resolved_at
+                .is_some_and(|resolved| now - resolved > self.ticket_policy.auto_close_after(ticket)))
+            .map(|ticket| ticket.id)
+            .collect();
+
+        for ticket_id in &due {
+            if let Some(ticket) = self.support_tickets.get_mut(ticket_id) {
+                ticket.status = TicketStatus::Closed;
+                ticket.closed_at = Some(now);
+                ticket.updated_at = now;
+                println!("🔒 Ops: Auto-closed resolved ticket '{}'", ticket.title);
+            }
+        }
+
+        Ok(due)
+    }
+
+    /// Work the agent's tickets up to `now` and play the customers' side
+    ///
+    /// New tickets get their first response and an estimate of the work,
+    /// from their complexity and the agent's support skill. Halfway through,
+    /// a ticket may wait on the customer for more information; the
+    /// resolution clock stops until the reply. Worked long enough, it is
+    /// resolved, and the customer may reopen it within the grace window for
+    /// another half of the work. Resolved tickets close after the auto-close
+    /// delay, and the customer may answer a CSAT survey.
+    pub fn work_tickets(&mut self, now: chrono::DateTime<chrono::Utc>, settings: &TicketSettings, rng: &mut impl Rng) -> Vec<TicketEvent> {
+        let pace = f64::from(self.support_skill.max(1)) / 100.0;
+        let mut events = Vec::new();
+        for ticket in self.support_tickets.values_mut() {
+            let reply_due = ticket.work.customer_reply_at.is_some_and(|at| at <= now);
+            match ticket.status {
+                TicketStatus::Open => {
+                    let complexity = rng.gen_range(1..=5u8);
+                    ticket.work.complexity = Some(complexity);
+                    ticket.work.effort_minutes = (settings.hours_per_complexity * f64::from(complexity) / pace * 60.0) as i64;
+                    ticket.work.questions = u32::from(rng.gen_bool(settings.pending_customer_chance.clamp(0.0, 1.0)));
+                    ticket.work.first_response_at = Some(now);
+                    ticket.work.working_since = Some(now);
+                    ticket.status = TicketStatus::InProgress;
+                    ticket.updated_at = now;
+                    events.push(TicketEvent::new(ticket, TicketEventKind::Responded { minutes: (now - ticket.created_at).num_minutes() }));
+                }
+                TicketStatus::InProgress => {
+                    // Reopened or taken over without a stretch under way
+                    let since = *ticket.work.working_since.get_or_insert(now);
+                    let worked = ticket.work.worked_minutes + (now - since).num_minutes();
+                    if ticket.work.questions > 0 && worked * 2 >= ticket.work.effort_minutes {
+                        ticket.work.questions -= 1;
+                        ticket.work.worked_minutes = worked;
+                        ticket.work.working_since = None;
+                        ticket.work.waiting_since = Some(now);
+                        ticket.work.customer_reply_at = Some(now + reply_delay(settings, rng));
+                        ticket.status = TicketStatus::PendingCustomer;
+                        ticket.updated_at = now;
+                        events.push(TicketEvent::new(ticket, TicketEventKind::AskedCustomer));
+                    } else if worked >= ticket.work.effort_minutes {
+                        ticket.work.worked_minutes = worked;
+                        ticket.work.working_since = None;
+                        ticket.status = TicketStatus::Resolved;
+                        ticket.resolution = Some(format!("Fixed after {:.1} hours of work", worked as f64 / 60.0));
+                        ticket.resolved_at = Some(now);
+                        ticket.updated_at = now;
+                        // Whether the fix holds is up to the customer
+                        if rng.gen_bool(settings.reopen_chance.clamp(0.0, 1.0)) {
+                            ticket.work.customer_reply_at = Some(now + reply_delay(settings, rng));
+                        }
+                        let hours = ticket.resolution_hours().unwrap_or_default();
+                        events.push(TicketEvent::new(ticket, TicketEventKind::Resolved { hours }));
+                    }
+                }
+                TicketStatus::PendingCustomer if reply_due => {
+                    if let Some(waiting_since) = ticket.work.waiting_since.take() {
+                        ticket.work.paused_minutes += (now - waiting_since).num_minutes();
+                    }
+                    ticket.work.customer_reply_at = None;
+                    ticket.work.working_since = Some(now);
+                    ticket.status = TicketStatus::InProgress;
+                    ticket.updated_at = now;
+                    events.push(TicketEvent::new(ticket, TicketEventKind::CustomerReplied));
+                }
+                TicketStatus::Resolved | TicketStatus::Closed if reply_due => {
+                    ticket.work.customer_reply_at = None;
+                    if self.ticket_policy.can_reopen(ticket, now) {
+                        ticket.reopen(now);
+                        events.push(TicketEvent::new(ticket, TicketEventKind::Reopened));
+                    }
+                }
+                TicketStatus::Resolved if ticket.work.customer_reply_at.is_none()
+                    && ticket.resolved_at.is_some_and(|resolved| now - resolved > self.ticket_policy.auto_close_after(ticket)) => {
+                    ticket.status = TicketStatus::Closed;
+                    ticket.closed_at = Some(now);
+                    ticket.updated_at = now;
+                    events.push(TicketEvent::new(ticket, TicketEventKind::Closed));
+                }
+                _ => {}
+            }
+
+            // Surveyed once per closure, however it closed
+            if ticket.status == TicketStatus::Closed && !ticket.work.surveyed {
+                ticket.work.surveyed = true;
+                if rng.gen_bool(settings.survey_response_rate.clamp(0.0, 1.0)) {
+                    let score = csat(ticket, rng);
+                    ticket.work.csat = Some(score);
+                    events.push(TicketEvent::new(ticket, TicketEventKind::Surveyed { score }));
+                }
+            }
+        }
+        events
+    }
+
+    /// Tickets neither resolved nor closed
+    pub fn open_tickets(&self) -> usize {
+        self.support_tickets.values()
+            .filter(|ticket| !matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed))
+            .count()
+    }
+
+    /// Tickets nobody has responded to yet
+    pub fn unanswered_tickets(&self) -> impl Iterator<Item = &SupportTicket> {
+        self.support_tickets.values().filter(|ticket| ticket.status == TicketStatus::Open)
+    }
+
+    /// Escalate tickets and incidents past an SLA target; returns how many
+    ///
+    /// Each missed target raises the priority or severity one step and is
+    /// reported to the orchestrator, which reassigns the work and notifies
+    /// the manager. Tickets waiting on the customer do not escalate.
+    pub fn escalate_breaches(&mut self, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let mut escalations = Vec::new();
+
+        for ticket in self.support_tickets.values_mut() {
+            if !matches!(ticket.status, TicketStatus::Open | TicketStatus::InProgress) {
+                continue;
+            }
+            let Some(target) = self.escalation_policy.ticket_targets.get(&ticket.priority) else {
+                continue;
+            };
+            let unanswered = ticket.work.first_response_at.is_none();
+            let Some(breach) = target.breach(ticket.clock_start(), unanswered, &ticket.breaches, now) else {
+                continue;
+            };
+            ticket.breaches.push(breach);
+            if let Some(priority) = ticket.priority.raised() {
+                ticket.priority = priority;
+            }
+            ticket.updated_at = now;
+            println!("⏫ Ops: Ticket '{}' missed its {} target, raised to {:?}", ticket.title, breach.as_str(), ticket.priority);
+            escalations.push(("ticket", ticket.id, breach, format!("Ticket '{}' missed its {} target, now {:?}", ticket.title, breach.as_str(), ticket.priority)));
+        }
+
+        let active: Vec<Uuid> = self.active_incidents().map(|incident| incident.id).collect();
+        for incident_id in active {
+            let Some(incident) = self.incidents.get_mut(&incident_id) else {
+                continue;
+            };
+            let Some(target) = self.escalation_policy.incident_targets.get(&incident.severity) else {
+                continue;
+            };
+            let unanswered = incident.status == IncidentStatus::Open;
+            let Some(breach) = target.breach(incident.created_at, unanswered, &incident.breaches, now) else {
+                continue;
+            };
+            incident.breaches.push(breach);
+            if let Some(severity) = incident.severity.raised() {
+                incident.severity = severity;
+            }
+            let note = format!("Missed its {} target, escalated to {:?}", breach.as_str(), incident.severity);
+            incident.status_history.push(IncidentStatusChange { at: now, status: incident.status.clone(), note: Some(note) });
+            println!("⏫ Ops: Incident '{}' missed its {} target, raised to {:?}", incident.title, breach.as_str(), incident.severity);
+            escalations.push(("incident", incident.id, breach, format!("Incident '{}' missed its {} target, now {:?}", incident.title, breach.as_str(), incident.severity)));
+        }
+
+        let count = escalations.len();
+        for (kind, id, breach, content) in escalations {
+            self.outbox.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: self.agent.id,
+                to_agent: SystemActor::Orchestrator.id(),
+                message_type: SLA_ESCALATION.to_string(),
+                content,
+                priority: MessagePriority::High,
+                timestamp: now,
+                metadata: HashMap::from([
+                    ("kind".to_string(), kind.to_string()),
+                    ("id".to_string(), id.to_string()),
+                    ("breach".to_string(), breach.as_str().to_string()),
+                ]),
+            });
+        }
+        count
+    }
+
+    /// Give up a ticket or incident, with its merged duplicates, to another agent
+    pub fn hand_over(&mut self, id: Uuid) -> Option<Handover> {
+        if let Some(ticket) = self.support_tickets.remove(&id) {
+            return Some(Handover::Ticket(ticket));
+        }
+        let master = self.incidents.remove(&id)?;
+        let mut incidents: Vec<Incident> = master.duplicates.iter()
+            .filter_map(|duplicate_id| self.incidents.remove(duplicate_id))
+            .collect();
+        incidents.insert(0, master);
+        Some(Handover::Incident(incidents))
+    }
+
+    /// Take over a ticket or incident handed over by another agent
+    pub fn take_over(&mut self, handover: Handover) {
+        match handover {
+            Handover::Ticket(mut ticket) => {
+                ticket.assigned_to = Some(self.agent.id);
+                self.support_tickets.insert(ticket.id, ticket);
+            }
+            Handover::Incident(incidents) => {
+                for mut incident in incidents {
+                    incident.assigned_team = Some(self.agent.name.clone());
+                    self.incidents.insert(incident.id, incident);
+                }
+            }
+        }
+    }
+
     /// Declare system incident
+    ///
+    /// If an open incident already covers one of the affected services within
+    /// the duplicate window, the report is merged into it and the master
+    /// incident's id is returned instead.
     pub async fn declare_incident(&mut self, incident_report: IncidentReport) -> Result<Uuid, OpsError> {
+        if let Some(master_id) = self.find_duplicate_incident(&incident_report) {
+            let duplicate_id = Uuid::new_v4();
+            self.incidents.insert(duplicate_id, Incident {
+                id: duplicate_id,
+                title: incident_report.title,
+                description: incident_report.description,
+                severity: incident_report.severity,
+                status: IncidentStatus::Open,
+                affected_services: incident_report.affected_services,
+                root_cause: None,
+                resolution: None,
+                created_at: chrono::Utc::now(),
+                resolved_at: None,
+                assigned_team: None,
+                duplicate_of: None,
+                duplicates: vec![],
+                correlation_ids: vec![],
+                status_history: vec![],
+                breaches: vec![],
+                knowledge: None,
+                runbook: None,
+            });
+            self.merge_incidents(master_id, duplicate_id)?;
+            return Ok(master_id);
+        }
+
         let incident_id = Uuid::new_v4();
 
         let incident = Incident {
@@ -234,6 +1184,17 @@ impl OpsAgent {
             created_at: chrono::Utc::now(),
             resolved_at: None,
             assigned_team: None,
+            duplicate_of: None,
+            duplicates: vec![],
+            correlation_ids: vec![],
+            status_history: vec![IncidentStatusChange {
+                at: chrono::Utc::now(),
+                status: IncidentStatus::Open,
+                note: Some("Incident declared".to_string()),
+            }],
+            breaches: vec![],
+            knowledge: None,
+            runbook: None,
         };
 
         self.incidents.insert(incident_id, incident);
@@ -255,10 +1216,150 @@ impl OpsAgent {
         Ok(incident_id)
     }
 
+    /// Master incidents still being worked on
+    pub fn active_incidents(&self) -> impl Iterator<Item = &Incident> {
+        self.incidents.values()
+            .filter(|incident| incident.duplicate_of.is_none())
+            .filter(|incident| matches!(incident.status, IncidentStatus::Open | IncidentStatus::Investigating | IncidentStatus::Mitigating))
+    }
+
+    /// Find an open master incident that the report duplicates
+    fn find_duplicate_incident(&self, report: &IncidentReport) -> Option<Uuid> {
+        let now = chrono::Utc::now();
+        let window = chrono::Duration::minutes(self.duplicate_window_minutes);
+
+        self.incidents.values()
+            .filter(|incident| incident.duplicate_of.is_none())
+            // Still active, or resolved recently enough that the windows overlap
+            .filter(|incident| match incident.resolved_at {
+                Some(resolved_at) => now - resolved_at <= window,
+                None => incident.status != IncidentStatus::Closed,
+            })
+            .filter(|incident| report.affected_services.iter()
+                .filter(|service| service.as_str() != "unknown")
+                .any(|service| incident.affected_services.contains(service)))
+            .min_by_key(|incident| incident.created_at)
+            .map(|incident| incident.id)
+    }
+
+    /// Merge a duplicate incident into a master incident
+    pub fn merge_incidents(&mut self, master_id: Uuid, duplicate_id: Uuid) -> Result<(), OpsError> {
+        if master_id == duplicate_id {
+            return Err(OpsError::InvalidMerge("cannot merge an incident into itself".to_string()));
+        }
+
+        let duplicate = self.incidents.get_mut(&duplicate_id)
+            .ok_or(OpsError::IncidentNotFound(duplicate_id))?;
+        if !duplicate.duplicates.is_empty() {
+            return Err(OpsError::InvalidMerge(format!("incident {} already has linked duplicates", duplicate_id)));
+        }
+        duplicate.duplicate_of = Some(master_id);
+        duplicate.set_status(IncidentStatus::Closed, Some(format!("Duplicate of incident {}", master_id)));
+        duplicate.resolution = Some(format!("Duplicate of incident {}", master_id));
+        duplicate.resolved_at = Some(chrono::Utc::now());
+        let duplicate = duplicate.clone();
+
+        let master = match self.incidents.get_mut(&master_id) {
+            Some(master) => master,
+            None => {
+                // Undo the link so the duplicate is not orphaned
+                if let Some(orphan) = self.incidents.get_mut(&duplicate_id) {
+                    orphan.duplicate_of = None;
+                    orphan.status = IncidentStatus::Open;
+                    orphan.status_history.pop();
+                    orphan.resolution = None;
+                    orphan.resolved_at = None;
+                }
+                return Err(OpsError::IncidentNotFound(master_id));
+            }
+        };
+
+        master.duplicates.push(duplicate_id);
+        master.status_history.push(IncidentStatusChange {
+            at: chrono::Utc::now(),
+            status: master.status.clone(),
+            note: Some(format!("Merged duplicate report '{}'", duplicate.title)),
+        });
+        for correlation_id in duplicate.correlation_ids {
+            if !master.correlation_ids.contains(&correlation_id) {
+                master.correlation_ids.push(correlation_id);
+            }
+        }
+        for service in duplicate.affected_services {
+            if service != "unknown" && !master.affected_services.contains(&service) {
+                master.affected_services.push(service);
+            }
+        }
+        if duplicate.severity.rank() > master.severity.rank() {
+            println!("⬆️ Ops: Raising incident '{}' to {:?} based on duplicate report", master.title, duplicate.severity);
+            master.severity = duplicate.severity;
+        }
+
+        println!("🔗 Ops: Merged duplicate incident '{}' into '{}' ({} linked)",
+                duplicate.title, master.title, master.duplicates.len());
+        Ok(())
+    }
+
+    /// Link a causality id to an incident so related events join its timeline
+    pub fn link_correlation(&mut self, incident_id: Uuid, correlation_id: Uuid) {
+        if let Some(incident) = self.incidents.get_mut(&incident_id) {
+            if !incident.correlation_ids.contains(&correlation_id) {
+                incident.correlation_ids.push(correlation_id);
+            }
+        }
+    }
+
+    /// Start work on an incident with what the knowledge base turned up
+    pub fn consult_knowledge(&mut self, incident_id: Uuid, consultation: Consultation) {
+        let Some(incident) = self.incidents.get_mut(&incident_id) else {
+            return;
+        };
+        let note = match consultation.article {
+            Some(article) => format!("Known issue, following article {}", article.simple()),
+            None => "No known fix, investigating from scratch".to_string(),
+        };
+        let status = match incident.status {
+            IncidentStatus::Open => IncidentStatus::Investigating,
+            ref status => status.clone(),
+        };
+        incident.set_status(status, Some(note));
+        incident.knowledge = Some(consultation);
+    }
+
+    /// Keep track of the runbook run on an incident: it is being mitigated
+    /// while the runbook runs, and investigated again once it gave up
+    pub fn track_runbook(&mut self, incident_id: Uuid, run: RunbookRun) {
+        let Some(incident) = self.incidents.get_mut(&incident_id) else {
+            return;
+        };
+        match run.outcome {
+            None if incident.runbook.is_none() => {
+                incident.set_status(IncidentStatus::Mitigating, Some(format!("Running runbook '{}'", run.runbook)));
+            }
+            Some(RunbookOutcome::Escalated) => {
+                incident.set_status(IncidentStatus::Investigating, Some(format!("Runbook '{}' did not fix it, escalated to DevOps", run.runbook)));
+            }
+            _ => {}
+        }
+        incident.runbook = Some(run);
+    }
+
+    /// Resolve an active incident with its root cause and fix
+    pub fn resolve_incident(&mut self, incident_id: Uuid, root_cause: String, resolution: String) -> Option<Incident> {
+        let incident = self.incidents.get_mut(&incident_id)
+            .filter(|incident| !matches!(incident.status, IncidentStatus::Resolved | IncidentStatus::PostMortem | IncidentStatus::Closed))?;
+        incident.set_status(IncidentStatus::Resolved, Some(resolution.clone()));
+        incident.root_cause = Some(root_cause);
+        incident.resolution = Some(resolution);
+        incident.resolved_at = Some(chrono::Utc::now());
+        println!("✅ Ops: Resolved incident {}", incident.title);
+        Some(incident.clone())
+    }
+
     /// Update incident status
     pub async fn update_incident(&mut self, incident_id: Uuid, update: IncidentUpdate) -> Result<(), OpsError> {
         if let Some(incident) = self.incidents.get_mut(&incident_id) {
-            incident.status = update.status;
+            incident.set_status(update.status, update.resolution.clone().or_else(|| update.root_cause.clone()));
 
             if let Some(root_cause) = update.root_cause {
                 incident.root_cause = Some(root_cause);
@@ -298,28 +1399,225 @@ impl OpsAgent {
         }
     }
 
-    /// Monitor SLA compliance
-    pub async fn monitor_sla(&mut self) -> Result<(), OpsError> {
-        for (service_name, sla) in &self.sla_tracking.slas.clone() {
-            // Simulate SLA calculation
-            let compliance = 99.0 + rand::random::<f32>() * 2.0; // 99.0-101.0%
-            self.sla_tracking.compliance.insert(service_name.clone(), compliance);
+    /// Decide on a quota increase through change management; increases are
+    /// frozen while a Sev1 incident is open. Returns whether it was approved.
+    pub async fn review_quota_increase(&mut self, request: &Message) -> Result<bool, OpsError> {
+        let request_id = request.metadata.get(quota::REQUEST_ID_KEY)
+            .cloned()
+            .ok_or_else(|| OpsError::InvalidQuotaRequest("approval_request without a quota_request_id".to_string()))?;
+        let change_id = self.submit_change_request(ChangeRequest {
+            id: Uuid::new_v4(),
+            title: request.content.clone(),
+            description: format!("Quota increase of {}%", request.metadata.get("increase_percent").map_or("?", String::as_str)),
+            change_type: ChangeType::Normal,
+            risk_level: RiskLevel::Medium,
+            impact: "More shared cluster capacity for the requesting scope".to_string(),
+            rollback_plan: "Lower the quota back to its previous limit".to_string(),
+            scheduled_time: chrono::Utc::now(),
+            status: ChangeStatus::PendingApproval,
+            requester: request.from_agent,
+            approver: None,
+            execution: None,
+            review: None,
+        }).await?;
+
+        let (approved, reason) = self.decide_change(change_id, &request.content).await?;
+
+        self.outbox.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: self.agent.id,
+            to_agent: SystemActor::Orchestrator.id(),
+            message_type: quota::APPROVAL_DECISION.to_string(),
+            content: reason,
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                (quota::REQUEST_ID_KEY.to_string(), request_id),
+                ("approved".to_string(), approved.to_string()),
+            ]),
+        });
+        Ok(approved)
+    }
+
+    /// Decide on a change request from DevOps or the promotion pipeline,
+    /// frozen like quota increases while a Sev1 incident is open, and tell
+    /// the requester through the orchestrator. Returns whether it was
+    /// approved.
+    pub async fn review_change(&mut self, reference: Uuid, title: String, description: String, impact: String, rollback_plan: String, requested_by: Uuid) -> Result<bool, OpsError> {
+        let change_id = self.submit_change_request(ChangeRequest {
+            id: Uuid::new_v4(),
+            title: title.clone(),
+            description,
+            change_type: ChangeType::Normal,
+            risk_level: RiskLevel::High,
+            impact,
+            rollback_plan,
+            scheduled_time: chrono::Utc::now(),
+            status: ChangeStatus::PendingApproval,
+            requester: requested_by,
+            approver: None,
+            execution: None,
+            review: None,
+        }).await?;
+
+        let (approved, reason) = self.decide_change(change_id, &title).await?;
+        let decision = MessagePayload::ChangeDecision { reference, requested_by, approved, reason: reason.clone() };
+        self.outbox.push(decision.into_message(self.agent.id, SystemActor::Orchestrator.id(), reason, MessagePriority::High));
+        Ok(approved)
+    }
+
+    /// Approve a pending change, or cancel it during a Sev1 change freeze
+    async fn decide_change(&mut self, change_id: Uuid, title: &str) -> Result<(bool, String), OpsError> {
+        let sev1 = self.active_incidents().filter(|incident| incident.severity == Severity::Sev1).count();
+        if sev1 > 0 {
+            if let Some(change) = self.change_queue.iter_mut().find(|change| change.id == change_id) {
+                change.status = ChangeStatus::Cancelled;
+            }
+            println!("⛔ Ops: Denied '{}' during a change freeze", title);
+            return Ok((false, format!("Change freeze: {} Sev1 incidents open", sev1)));
+        }
+        self.approve_change(change_id, self.agent.id).await?;
+        Ok((true, format!("Approved as change {}", change_id.simple())))
+    }
+
+    /// Carry out approved changes once their scheduled time comes. A change
+    /// of higher risk fails more often once its work is done, and is then
+    /// rolled back, which may not restore service either. Finished changes
+    /// get a post-implementation review after the review delay.
+    pub fn execute_changes(&mut self, now: chrono::DateTime<chrono::Utc>, settings: &ChangeSettings, rng: &mut impl Rng) -> Vec<ChangeEvent> {
+        let mut events = Vec::new();
+        for change in &mut self.change_queue {
+            match change.status {
+                ChangeStatus::Approved | ChangeStatus::Scheduled if change.scheduled_time <= now => {
+                    let (min, max) = settings.duration_minutes;
+                    let minutes = i64::from(rng.gen_range(min..=max.max(min)));
+                    change.execution = Some(ChangeExecution { started_at: now, ends_at: now + chrono::Duration::minutes(minutes), finished_at: None });
+                    change.status = ChangeStatus::InProgress;
+                    events.push(ChangeEvent::new(change, ChangeEventKind::Started { minutes }));
+                }
+                ChangeStatus::InProgress => {
+                    let Some(execution) = change.execution.as_mut().filter(|execution| now >= execution.ends_at) else {
+                        continue;
+                    };
+                    execution.finished_at = Some(now);
+                    let kind = if !rng.gen_bool(change.risk_level.failure_chance(settings)) {
+                        change.status = ChangeStatus::Completed;
+                        ChangeEventKind::Completed
+                    } else if rng.gen_bool(settings.rollback_success_rate.clamp(0.0, 1.0)) {
+                        change.status = ChangeStatus::RolledBack;
+                        ChangeEventKind::RolledBack
+                    } else {
+                        change.status = ChangeStatus::Failed;
+                        ChangeEventKind::Failed
+                    };
+                    events.push(ChangeEvent::new(change, kind));
+                }
+                ChangeStatus::Completed | ChangeStatus::RolledBack | ChangeStatus::Failed if change.review.is_none() => {
+                    let review_at = chrono::Duration::hours(i64::from(settings.review_after_hours));
+                    let Some(execution) = change.execution.as_ref().filter(|execution| execution.finished_at.is_some_and(|at| now >= at + review_at)) else {
+                        continue;
+                    };
+                    let minutes = execution.duration_minutes();
+                    let successful = change.status == ChangeStatus::Completed;
+                    let findings = match change.status {
+                        ChangeStatus::Completed => format!("Implemented as planned in {} minutes", minutes),
+                        ChangeStatus::RolledBack => format!("Failed after {} minutes and was rolled back: {}", minutes, change.rollback_plan),
+                        _ => format!("Failed after {} minutes and the rollback did not restore service; handled as an incident", minutes),
+                    };
+                    change.review = Some(ChangeReview { reviewed_at: now, successful, findings });
+                    events.push(ChangeEvent::new(change, ChangeEventKind::Reviewed { successful }));
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Measure each service's health over the last `minutes` against its
+    /// SLA, which services without one get the standard one for. The
+    /// measurements restart every month. Returns the violations: the error
+    /// budget running out, once a period, and the service turning slower
+    /// than its response time target.
+    pub fn monitor_sla(&mut self, health: &[ServiceHealth], minutes: f64, at: chrono::DateTime<chrono::Utc>) -> Vec<SLAViolation> {
+        let mut violations = Vec::new();
+        for observed in health {
+            let service = &observed.service;
+            let sla = self.sla_tracking.slas.entry(service.clone()).or_insert_with(|| SLA::standard(service)).clone();
+            let base_ms = self.sla_tracking.response_times.get(service).map_or(BASE_RESPONSE_MS, |ms| f64::from(*ms));
+            let level = self.sla_tracking.levels.entry(service.clone()).or_default();
+            let same_period = level.period_start.is_some_and(|start| start.year() == at.year() && start.month() == at.month());
+            if !same_period {
+                *level = ServiceLevel { period_start: Some(at), ..ServiceLevel::default() };
+            }
 
-            if compliance < sla.uptime_target {
-                let violation = SLAViolation {
-                    service: service_name.clone(),
+            let was_slow = level.response_ms > f64::from(sla.response_time_target);
+            level.measured_minutes += minutes;
+            if observed.is_down() {
+                level.down_minutes += minutes;
+            }
+            level.checks += 1;
+            level.response_ms = observed.response_ms(base_ms);
+            let slow = level.response_ms > f64::from(sla.response_time_target);
+            if slow {
+                level.slow_checks += 1;
+            }
+            let uptime = level.uptime();
+            self.sla_tracking.compliance.insert(service.clone(), uptime as f32);
+
+            if slow && !was_slow {
+                violations.push(SLAViolation {
+                    service: service.clone(),
+                    violation_type: "Response Time".to_string(),
+                    timestamp: at,
+                    impact: format!("Answering in {:.0} ms, above the {} ms target", level.response_ms, sla.response_time_target),
+                    resolution: None,
+                });
+            }
+            if !level.budget_exhausted && level.error_budget_remaining(&sla) <= 0.0 {
+                level.budget_exhausted = true;
+                violations.push(SLAViolation {
+                    service: service.clone(),
                     violation_type: "Uptime Target".to_string(),
-                    timestamp: chrono::Utc::now(),
-                    impact: format!("Uptime {:.2}% below target {:.2}%", compliance, sla.uptime_target),
+                    timestamp: at,
+                    impact: format!("Error budget of {:.0} minutes spent: {:.0} minutes down, {:.2}% uptime against {:.2}%",
+                                    ServiceLevel::error_budget_minutes(&sla), level.down_minutes, uptime, sla.uptime_target),
                     resolution: None,
-                };
-                self.sla_tracking.violations.push(violation);
-
-                println!("⚠️ Ops: SLA violation for {} - {:.2}% uptime", service_name, compliance);
+                });
             }
         }
+        self.sla_tracking.violations.extend(violations.iter().cloned());
+        violations
+    }
 
-        Ok(())
+    /// Check the day's round trips to multi-region services against their
+    /// SLAs, which services without one get the standard one for; returns
+    /// the violations
+    pub fn record_service_latency(&mut self, latency: &BTreeMap<String, LatencySample>, at: chrono::DateTime<chrono::Utc>) -> Vec<SLAViolation> {
+        let mut violations = Vec::new();
+        for (service, sample) in latency {
+            let sla = self.sla_tracking.slas.entry(service.clone()).or_insert_with(|| SLA::standard(service)).clone();
+            self.sla_tracking.response_times.insert(service.clone(), sample.average_ms() as f32);
+            if sample.worst_ms > f64::from(sla.response_time_target) {
+                violations.push(SLAViolation {
+                    service: service.clone(),
+                    violation_type: "Response Time".to_string(),
+                    timestamp: at,
+                    impact: format!("Cross-site round trips up to {:.0} ms, above the {} ms target", sample.worst_ms, sla.response_time_target),
+                    resolution: None,
+                });
+            }
+            if sample.unreachable > 0 {
+                violations.push(SLAViolation {
+                    service: service.clone(),
+                    violation_type: "Availability".to_string(),
+                    timestamp: at,
+                    impact: format!("Unreachable from a site in {} of {} checks", sample.unreachable, sample.samples + sample.unreachable),
+                    resolution: None,
+                });
+            }
+        }
+        self.sla_tracking.violations.extend(violations.iter().cloned());
+        violations
     }
 
     /// Perform system maintenance
@@ -348,66 +1646,179 @@ impl OpsAgent {
 
     /// Generate operations report
     pub async fn generate_report(&self) -> Result<OpsReport, OpsError> {
+        let now = chrono::Utc::now();
+        let resolution_hours: Vec<f32> = self.support_tickets.values()
+            .filter_map(|t| t.resolution_hours().map(|hours| hours as f32))
+            .collect();
+        let response_minutes: Vec<f32> = self.support_tickets.values()
+            .filter_map(|t| t.work.first_response_at.map(|responded| (responded - t.created_at).num_minutes() as f32))
+            .collect();
+        let scores: Vec<f32> = self.support_tickets.values().filter_map(|t| t.work.csat.map(f32::from)).collect();
+        let mean = |values: &[f32]| if values.is_empty() { 0.0 } else { values.iter().sum::<f32>() / values.len() as f32 };
+        let ever_resolved = self.support_tickets.values()
+            .filter(|t| t.resolved_at.is_some() || t.reopen_count > 0)
+            .count();
+        let reopened = self.support_tickets.values().filter(|t| t.reopen_count > 0).count();
+        let count_changes = |status: ChangeStatus| self.change_queue.iter().filter(|c| c.status == status).count() as u32;
+        let completed = count_changes(ChangeStatus::Completed);
+        let finished = completed + count_changes(ChangeStatus::RolledBack) + count_changes(ChangeStatus::Failed);
+
         let report = OpsReport {
             generated_at: chrono::Utc::now(),
             ticket_summary: TicketSummary {
                 total_tickets: self.support_tickets.len() as u32,
                 open_tickets: self.support_tickets.values().filter(|t| t.status == TicketStatus::Open).count() as u32,
-                resolved_today: 0, // Would calculate from timestamps
-                average_resolution_time: 4.2, // hours
+                resolved_today: self.support_tickets.values()
+                    .filter(|t| t.resolved_at.is_some_and(|resolved| resolved.date_naive() == now.date_naive()))
+                    .count() as u32,
+                closed_tickets: self.support_tickets.values().filter(|t| t.status == TicketStatus::Closed).count() as u32,
+                pending_customer: self.support_tickets.values().filter(|t| t.status == TicketStatus::PendingCustomer).count() as u32,
+                average_first_response: mean(&response_minutes),
+                average_resolution_time: mean(&resolution_hours),
+                reopened_tickets: reopened as u32,
+                reopen_rate: if ever_resolved > 0 { reopened as f32 / ever_resolved as f32 } else { 0.0 },
+                average_csat: mean(&scores),
+                surveys_answered: scores.len() as u32,
             },
             incident_summary: IncidentSummary {
-                total_incidents: self.incidents.len() as u32,
-                active_incidents: self.incidents.values().filter(|i| i.status != IncidentStatus::Closed).count() as u32,
-                sev1_incidents: self.incidents.values().filter(|i| i.severity == Severity::Sev1).count() as u32,
+                total_incidents: self.incidents.values().filter(|i| i.duplicate_of.is_none()).count() as u32,
+                active_incidents: self.incidents.values().filter(|i| i.duplicate_of.is_none() && i.status != IncidentStatus::Closed).count() as u32,
+                sev1_incidents: self.incidents.values().filter(|i| i.duplicate_of.is_none() && i.severity == Severity::Sev1).count() as u32,
+                merged_duplicates: self.incidents.values().filter(|i| i.duplicate_of.is_some()).count() as u32,
                 mttr: 2.5, // hours
             },
             sla_compliance: self.sla_tracking.compliance.clone(),
+            error_budgets: self.sla_tracking.levels.iter()
+                .filter_map(|(service, level)| {
+                    let sla = self.sla_tracking.slas.get(service)?;
+                    Some((service.clone(), level.error_budget_remaining(sla) as f32))
+                })
+                .collect(),
             upcoming_changes: self.change_queue.iter()
                 .filter(|c| c.status == ChangeStatus::Approved)
                 .map(|c| c.title.clone())
                 .collect(),
+            change_summary: ChangeSummary {
+                in_progress: count_changes(ChangeStatus::InProgress),
+                completed,
+                rolled_back: count_changes(ChangeStatus::RolledBack),
+                failed: count_changes(ChangeStatus::Failed),
+                success_rate: if finished > 0 { completed as f32 / finished as f32 } else { 0.0 },
+                reviewed: self.change_queue.iter().filter(|c| c.review.is_some()).count() as u32,
+            },
         };
 
         Ok(report)
     }
 
-    /// Auto-assign ticket based on priority and agent workload
+    /// Auto-assign ticket based on priority and agent workload; it stays
+    /// open until the agent first responds
     async fn assign_ticket(&mut self, ticket_id: Uuid) -> Result<(), OpsError> {
         if let Some(ticket) = self.support_tickets.get_mut(&ticket_id) {
             // Simple assignment logic - in real system would consider agent skills and workload
             ticket.assigned_to = Some(self.agent.id);
-            ticket.status = TicketStatus::InProgress;
         }
         Ok(())
     }
 }
 
+/// Message types `process_message` acts on; any other is logged and dropped
+pub const HANDLED_MESSAGE_TYPES: &[&str] = &[
+    "create_ticket",
+    "declare_incident",
+    "infrastructure_alert",
+    "resolve_ticket",
+    "reopen_ticket",
+    "sla_check",
+    "maintenance_task",
+    quota::APPROVAL_REQUEST,
+    "change_request",
+    "generate_report",
+    ESCALATION_NOTICE,
+    "service_latency",
+];
+
 #[async_trait]
 impl AgentTrait for OpsAgent {
     async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match message.message_type.as_str() {
-            "create_ticket" => {
+        // Structured payloads first; everything else is dispatched on the message type
+        match MessagePayload::from_message(&message)? {
+            Some(MessagePayload::TicketRequest { title, priority, customer_id, customer_tier, tags }) => {
                 let ticket_request = TicketRequest {
-                    title: message.metadata.get("title").unwrap_or(&"Support Request".to_string()).clone(),
+                    title,
                     description: message.content,
-                    priority: Priority::Normal,
-                    customer_id: message.metadata.get("customer_id").cloned(),
-                    tags: vec![],
+                    priority,
+                    customer_id,
+                    customer_tier,
+                    tags,
                 };
                 self.create_ticket(ticket_request).await?;
+                return Ok(());
             }
-            "declare_incident" => {
+            Some(MessagePayload::IncidentDeclared { title, severity, affected_services, .. }) => {
+                let correlation_id = timeline::correlation_id(&message);
                 let incident_report = IncidentReport {
-                    title: message.metadata.get("title").unwrap_or(&"System Incident".to_string()).clone(),
+                    title,
                     description: message.content,
-                    severity: Severity::Sev3,
-                    affected_services: vec!["unknown".to_string()],
+                    severity,
+                    affected_services,
                 };
-                self.declare_incident(incident_report).await?;
+                let incident_id = self.declare_incident(incident_report).await?;
+                if let Some(correlation_id) = correlation_id {
+                    self.link_correlation(incident_id, correlation_id);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::ChangeReview { reference, title, description, impact, rollback_plan, requested_by }) => {
+                self.review_change(reference, title, description, impact, rollback_plan, requested_by).await?;
+                return Ok(());
+            }
+            Some(MessagePayload::SlaCheck { at, minutes, health }) => {
+                for violation in self.monitor_sla(&health, minutes, at) {
+                    println!("⚠️ Ops: {} SLA violation for {} - {}", violation.violation_type, violation.service, violation.impact);
+                }
+                self.escalate_breaches(chrono::Utc::now());
+                return Ok(());
+            }
+            Some(MessagePayload::ServiceLatency { at, latency }) => {
+                for violation in self.record_service_latency(&latency, at) {
+                    println!("⚠️ Ops: {} SLA violation for {} - {}", violation.violation_type, violation.service, violation.impact);
+                }
+                return Ok(());
+            }
+            Some(MessagePayload::InfrastructureAlert { deployment, affected_customers, .. }) => {
+                // An alert routed to Ops starts the investigation of its incident
+                println!("🚨 Ops: Alerted about {} ({} customers affected)", deployment, affected_customers.len());
+                let correlated = timeline::correlation_id(&message).and_then(|correlation_id| {
+                    self.active_incidents()
+                        .find(|incident| incident.status == IncidentStatus::Open && incident.correlation_ids.contains(&correlation_id))
+                        .map(|incident| incident.id)
+                });
+                if let Some(incident_id) = correlated {
+                    self.update_incident(incident_id, IncidentUpdate {
+                        status: IncidentStatus::Investigating,
+                        root_cause: None,
+                        resolution: None,
+                    }).await?;
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        match message.message_type.as_str() {
+            "resolve_ticket" => {
+                if let Some(ticket_id) = message.metadata.get("ticket_id").and_then(|id| Uuid::parse_str(id).ok()) {
+                    self.resolve_ticket(ticket_id, message.content).await?;
+                }
+            }
+            "reopen_ticket" => {
+                if let Some(ticket_id) = message.metadata.get("ticket_id").and_then(|id| Uuid::parse_str(id).ok()) {
+                    self.reopen_ticket(ticket_id, &message.content).await?;
+                }
             }
             "sla_check" => {
-                self.monitor_sla().await?;
+                self.escalate_breaches(chrono::Utc::now());
             }
             "maintenance_task" => {
                 let maintenance_task = MaintenanceTask {
@@ -418,11 +1829,17 @@ impl AgentTrait for OpsAgent {
                 };
                 self.perform_maintenance(maintenance_task).await?;
             }
+            quota::APPROVAL_REQUEST => {
+                self.review_quota_increase(&message).await?;
+            }
             "generate_report" => {
                 let report = self.generate_report().await?;
                 println!("📊 Ops: Generated operations report - {} tickets, {} incidents",
                         report.ticket_summary.total_tickets, report.incident_summary.total_incidents);
             }
+            ESCALATION_NOTICE => {
+                println!("📣 Ops: {} notified of escalation: {}", self.agent.name, message.content);
+            }
             _ => {
                 println!("🤷 Ops: Unknown message type: {}", message.message_type);
             }
@@ -470,21 +1887,8 @@ impl AgentTrait for OpsAgent {
             metadata: HashMap::new(),
         }).await?;
 
-        // Close old tickets (simulate automated closure)
-        let old_tickets: Vec<Uuid> = self.support_tickets.iter()
-            .filter(|(_, ticket)| {
-                ticket.status == TicketStatus::Resolved &&
-                chrono::Utc::now().signed_duration_since(ticket.updated_at).num_days() > 7
-            })
-            .map(|(id, _)| *id)
-            .collect();
-
-        for ticket_id in old_tickets {
-            if let Some(ticket) = self.support_tickets.get_mut(&ticket_id) {
-                ticket.status = TicketStatus::Closed;
-                println!("🔒 Ops: Auto-closed old ticket '{}'", ticket.title);
-            }
-        }
+        // Close resolved tickets per the auto-close policy
+        self.auto_close_tickets().await?;
 
         Ok(())
     }
@@ -501,18 +1905,13 @@ impl AgentTrait for OpsAgent {
 impl Default for SLATracking {
     fn default() -> Self {
         let mut slas = HashMap::new();
-        slas.insert("web-service".to_string(), SLA {
-            service_name: "web-service".to_string(),
-            uptime_target: 99.9,
-            response_time_target: 500,
-            resolution_time_target: 4,
-            measurement_period: "monthly".to_string(),
-        });
+        slas.insert("web-service".to_string(), SLA::standard("web-service"));
 
         Self {
             slas,
             compliance: HashMap::new(),
             violations: vec![],
+            response_times: HashMap::new(),
         }
     }
 }
@@ -524,6 +1923,7 @@ pub struct TicketRequest {
     pub description: String,
     pub priority: Priority,
     pub customer_id: Option<String>,
+    pub customer_tier: CustomerTier,
     pub tags: Vec<String>,
 }
 
@@ -569,7 +1969,22 @@ pub struct OpsReport {
     pub ticket_summary: TicketSummary,
     pub incident_summary: IncidentSummary,
     pub sla_compliance: HashMap<String, f32>,
+    /// Share of each service's error budget left this period
+    #[serde(default)]
+    pub error_budgets: HashMap<String, f32>,
     pub upcoming_changes: Vec<String>,
+    #[serde(default)]
+    pub change_summary: ChangeSummary,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeSummary {
+    pub in_progress: u32,
+    pub completed: u32,
+    pub rolled_back: u32,
+    pub failed: u32,
+    pub success_rate: f32, // share of finished changes that completed
+    pub reviewed: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -577,7 +1992,14 @@ pub struct TicketSummary {
     pub total_tickets: u32,
     pub open_tickets: u32,
     pub resolved_today: u32,
-    pub average_resolution_time: f32,
+    pub closed_tickets: u32,
+    pub pending_customer: u32,
+    pub average_first_response: f32, // minutes from creation to first response
+    pub average_resolution_time: f32, // hours from creation to resolution, not waiting on the customer
+    pub reopened_tickets: u32,
+    pub reopen_rate: f32, // share of resolved tickets the customer reopened
+    pub average_csat: f32, // 1-5, over the surveys answered
+    pub surveys_answered: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -585,6 +2007,7 @@ pub struct IncidentSummary {
     pub total_incidents: u32,
     pub active_incidents: u32,
     pub sev1_incidents: u32,
+    pub merged_duplicates: u32,
     pub mttr: f32, // Mean Time To Resolution in hours
 }
 
@@ -597,19 +2020,32 @@ pub enum OpsError {
     #[error("Incident not found: {0}")]
     IncidentNotFound(Uuid),
 
+    #[error("Invalid ticket transition: {0}")]
+    InvalidTicketTransition(String),
+
+    #[error("Reopen window expired for ticket: {0}")]
+    ReopenWindowExpired(Uuid),
+
     #[error("Change request not found: {0}")]
     ChangeNotFound(Uuid),
 
+    #[error("Invalid incident merge: {0}")]
+    InvalidMerge(String),
+
     #[error("SLA calculation error: {0}")]
     SLACalculationError(String),
 
     #[error("Maintenance task failed: {0}")]
     MaintenanceFailed(String),
+
+    #[error("Invalid quota request: {0}")]
+    InvalidQuotaRequest(String),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
 
     #[tokio::test]
     async fn test_ops_agent_creation() {
@@ -628,6 +2064,7 @@ mod tests {
             description: "Test ticket description".to_string(),
             priority: Priority::Normal,
             customer_id: Some("customer123".to_string()),
+            customer_tier: CustomerTier::Standard,
             tags: vec!["test".to_string()],
         };
 
@@ -651,12 +2088,316 @@ mod tests {
         assert_eq!(agent.incidents.len(), 1);
     }
 
+    fn test_ticket_request(priority: Priority, customer_tier: CustomerTier) -> TicketRequest {
+        TicketRequest {
+            title: "Login broken".to_string(),
+            description: "Customer cannot log in".to_string(),
+            priority,
+            customer_id: Some("customer123".to_string()),
+            customer_tier,
+            tags: vec![],
+        }
+    }
+
     #[tokio::test]
-    async fn test_sla_monitoring() {
+    async fn test_auto_close_policy_by_priority_and_tier() {
         let mut agent = OpsAgent::new("Test Agent".to_string(), None);
-        let result = agent.monitor_sla().await;
-        assert!(result.is_ok());
-        assert!(!agent.sla_tracking.compliance.is_empty());
+        let urgent = agent.create_ticket(test_ticket_request(Priority::Urgent, CustomerTier::Standard)).await.unwrap();
+        let enterprise = agent.create_ticket(test_ticket_request(Priority::Urgent, CustomerTier::Enterprise)).await.unwrap();
+
+        for ticket_id in [urgent, enterprise] {
+            agent.resolve_ticket(ticket_id, "Fixed".to_string()).await.unwrap();
+            let ticket = agent.support_tickets.get_mut(&ticket_id).unwrap();
+            ticket.resolved_at = Some(chrono::Utc::now() - chrono::Duration::days(5));
+        }
+
+        let closed = agent.auto_close_tickets().await.unwrap();
+        assert_eq!(closed, vec![urgent]);
+        assert_eq!(agent.support_tickets[&urgent].status, TicketStatus::Closed);
+        assert_eq!(agent.support_tickets[&enterprise].status, TicketStatus::Resolved);
+    }
+
+    #[tokio::test]
+    async fn test_reopen_within_grace_window() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let ticket_id = agent.create_ticket(test_ticket_request(Priority::Normal, CustomerTier::Standard)).await.unwrap();
+        agent.resolve_ticket(ticket_id, "Fixed".to_string()).await.unwrap();
+        agent.close_ticket(ticket_id).await.unwrap();
+
+        agent.reopen_ticket(ticket_id, "Still broken").await.unwrap();
+        let ticket = &agent.support_tickets[&ticket_id];
+        assert_eq!(ticket.status, TicketStatus::InProgress);
+        assert_eq!(ticket.reopen_count, 1);
+        assert!(ticket.resolved_at.is_none());
+
+        agent.resolve_ticket(ticket_id, "Fixed for real".to_string()).await.unwrap();
+        let report = agent.generate_report().await.unwrap();
+        assert_eq!(report.ticket_summary.reopened_tickets, 1);
+        assert_eq!(report.ticket_summary.reopen_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_ticket_waits_on_the_customer_and_is_surveyed_on_closing() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        agent.support_skill = 100;
+        let ticket_id = agent.create_ticket(test_ticket_request(Priority::Normal, CustomerTier::Standard)).await.unwrap();
+        assert_eq!(agent.support_tickets[&ticket_id].status, TicketStatus::Open);
+        let settings = TicketSettings {
+            hours_per_complexity: 1.0,
+            pending_customer_chance: 1.0,
+            customer_reply_hours: (2, 2),
+            reopen_chance: 1.0,
+            survey_response_rate: 1.0,
+            ..TicketSettings::default()
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+        let start = agent.support_tickets[&ticket_id].created_at;
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let kinds = |events: Vec<TicketEvent>| events.into_iter().map(|event| event.kind).collect::<Vec<_>>();
+
+        assert_eq!(kinds(agent.work_tickets(at(0), &settings, &mut rng)), vec![TicketEventKind::Responded { minutes: 0 }]);
+        let effort = agent.support_tickets[&ticket_id].work.effort_minutes;
+        assert_eq!(effort, i64::from(agent.support_tickets[&ticket_id].work.complexity.unwrap()) * 60);
+
+        // Halfway through the customer is asked, and the clock stops until they reply
+        assert_eq!(kinds(agent.work_tickets(at(effort / 2), &settings, &mut rng)), vec![TicketEventKind::AskedCustomer]);
+        assert_eq!(agent.escalate_breaches(at(3 * 24 * 60 - 1)), 0);
+        let replied = effort / 2 + 120;
+        assert_eq!(kinds(agent.work_tickets(at(replied), &settings, &mut rng)), vec![TicketEventKind::CustomerReplied]);
+        let hours = effort as f64 / 60.0;
+        assert_eq!(kinds(agent.work_tickets(at(replied + effort / 2), &settings, &mut rng)), vec![TicketEventKind::Resolved { hours }]);
+
+        // The fix does not hold, and the second one takes half the work
+        let reopened = replied + effort / 2 + 120;
+        assert_eq!(kinds(agent.work_tickets(at(reopened), &settings, &mut rng)), vec![TicketEventKind::Reopened]);
+        let settings = TicketSettings { reopen_chance: 0.0, ..settings };
+        let resolved = reopened + effort / 2;
+        agent.work_tickets(at(resolved), &settings, &mut rng);
+        assert_eq!(agent.support_tickets[&ticket_id].status, TicketStatus::Resolved);
+
+        let events = kinds(agent.work_tickets(at(resolved + 7 * 24 * 60 + 1), &settings, &mut rng));
+        assert_eq!(events[0], TicketEventKind::Closed);
+        assert!(matches!(events[1], TicketEventKind::Surveyed { score: 3..=4 }));
+        assert!(kinds(agent.work_tickets(at(resolved + 8 * 24 * 60), &settings, &mut rng)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reopen_after_grace_window_fails() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let ticket_id = agent.create_ticket(test_ticket_request(Priority::Normal, CustomerTier::Standard)).await.unwrap();
+        agent.resolve_ticket(ticket_id, "Fixed".to_string()).await.unwrap();
+        agent.support_tickets.get_mut(&ticket_id).unwrap().resolved_at = Some(chrono::Utc::now() - chrono::Duration::days(30));
+
+        let result = agent.reopen_ticket(ticket_id, "Late complaint").await;
+        assert!(matches!(result, Err(OpsError::ReopenWindowExpired(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sla_breaches_escalate_once_and_hand_over() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let ticket_id = agent.create_ticket(test_ticket_request(Priority::Normal, CustomerTier::Standard)).await.unwrap();
+        let incident_id = agent.declare_incident(IncidentReport {
+            title: "Checkout down".to_string(),
+            description: "All requests failing".to_string(),
+            severity: Severity::Sev2,
+            affected_services: vec!["checkout".to_string()],
+        }).await.unwrap();
+
+        // The ticket is answered at once, so only its resolution clock runs;
+        // the incident was never investigated
+        let now = chrono::Utc::now();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        agent.work_tickets(now, &TicketSettings::default(), &mut rng);
+        assert_eq!(agent.escalate_breaches(now + chrono::Duration::hours(1)), 1);
+        assert_eq!(agent.incidents[&incident_id].severity, Severity::Sev1);
+        assert_eq!(agent.escalate_breaches(now + chrono::Duration::hours(5)), 1);
+        assert_eq!(agent.support_tickets[&ticket_id].priority, Priority::Normal);
+        assert_eq!(agent.escalate_breaches(now + chrono::Duration::days(4)), 1);
+        assert_eq!(agent.support_tickets[&ticket_id].priority, Priority::High);
+        assert_eq!(agent.escalate_breaches(now + chrono::Duration::days(5)), 0);
+        assert_eq!(agent.incidents[&incident_id].breaches, vec![SlaBreach::Response, SlaBreach::Resolution]);
+        assert!(agent.outbox.iter().all(|message| message.message_type == SLA_ESCALATION));
+
+        let mut senior = OpsAgent::new("Senior Agent".to_string(), None);
+        senior.take_over(agent.hand_over(ticket_id).unwrap());
+        assert!(agent.support_tickets.is_empty());
+        assert_eq!(senior.support_tickets[&ticket_id].assigned_to, Some(senior.agent.id));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_incidents_are_merged() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let first = agent.declare_incident(IncidentReport {
+            title: "Web service errors".to_string(),
+            description: "Elevated 5xx rate".to_string(),
+            severity: Severity::Sev3,
+            affected_services: vec!["web-service".to_string()],
+        }).await.unwrap();
+        let second = agent.declare_incident(IncidentReport {
+            title: "Web service down".to_string(),
+            description: "Health checks failing".to_string(),
+            severity: Severity::Sev1,
+            affected_services: vec!["web-service".to_string(), "api".to_string()],
+        }).await.unwrap();
+
+        assert_eq!(first, second);
+        let master = &agent.incidents[&first];
+        assert_eq!(master.duplicates.len(), 1);
+        assert_eq!(master.severity, Severity::Sev1);
+        assert!(master.affected_services.contains(&"api".to_string()));
+
+        let report = agent.generate_report().await.unwrap();
+        assert_eq!(report.incident_summary.total_incidents, 1);
+        assert_eq!(report.incident_summary.merged_duplicates, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unrelated_incidents_are_not_merged() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        for service in ["web-service", "database"] {
+            agent.declare_incident(IncidentReport {
+                title: format!("{} degraded", service),
+                description: "Degraded".to_string(),
+                severity: Severity::Sev3,
+                affected_services: vec![service.to_string()],
+            }).await.unwrap();
+        }
+
+        assert_eq!(agent.incidents.len(), 2);
+        assert!(agent.incidents.values().all(|i| i.duplicate_of.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_incident_timeline_links_correlated_events() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let correlation_id = Uuid::new_v4();
+        let correlated = |message_type: &str, content: &str| Message {
+            id: Uuid::new_v4(),
+            from_agent: Uuid::new_v4(),
+            to_agent: agent.agent.id,
+            message_type: message_type.to_string(),
+            content: content.to_string(),
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                (timeline::CORRELATION_KEY.to_string(), correlation_id.to_string()),
+                ("title".to_string(), "Database degraded".to_string()),
+                ("affected_services".to_string(), "database".to_string()),
+            ]),
+        };
+        let alert = correlated("infrastructure_alert", "High CPU on database-shared");
+        let declare = correlated("declare_incident", "Customers report timeouts");
+        let unrelated = Message {
+            metadata: HashMap::new(),
+            ..correlated("status_update", "Unrelated chatter")
+        };
+
+        agent.process_message(declare.clone()).await.unwrap();
+        let incident_id = *agent.incidents.keys().next().unwrap();
+        agent.update_incident(incident_id, IncidentUpdate {
+            status: IncidentStatus::Resolved,
+            root_cause: Some("Runaway query".to_string()),
+            resolution: Some("Query killed".to_string()),
+        }).await.unwrap();
+
+        let timeline = agent.incidents[&incident_id]
+            .timeline(&[alert.clone(), declare.clone(), unrelated], &[]);
+
+        let kinds: Vec<TimelineEntryKind> = timeline.entries.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![
+            TimelineEntryKind::Alert,
+            TimelineEntryKind::Message,
+            TimelineEntryKind::StatusChange,
+            TimelineEntryKind::StatusChange,
+        ]);
+        assert!(timeline.entries.windows(2).all(|pair| pair[0].at <= pair[1].at));
+        assert!(timeline.render().contains("Query killed"));
+    }
+
+    #[test]
+    fn test_sla_monitoring() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let health = |incident: Option<Severity>, capacity: f64| vec![ServiceHealth { service: "web-service".to_string(), incident, capacity, network: None, unstaffed_slowdown: None }];
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let at = |hours: i64| start + chrono::Duration::hours(hours);
+
+        assert!(agent.monitor_sla(&health(None, 1.0), 60.0, at(1)).is_empty());
+        assert_eq!(agent.sla_tracking.compliance["web-service"], 100.0);
+
+        // An hour of Sev1 spends the 43 minute budget of 99.9%, reported once
+        let violations = agent.monitor_sla(&health(Some(Severity::Sev1), 1.0), 60.0, at(2));
+        assert_eq!(violations.iter().map(|v| v.violation_type.as_str()).collect::<Vec<_>>(), vec!["Uptime Target"]);
+        assert!(agent.monitor_sla(&health(Some(Severity::Sev1), 1.0), 60.0, at(3)).is_empty());
+        let level = &agent.sla_tracking.levels["web-service"];
+        assert_eq!((level.down_minutes, level.error_budget_remaining(&SLA::standard("web-service"))), (120.0, 0.0));
+
+        // Too few servers online slows it past 500 ms
+        let violations = agent.monitor_sla(&health(None, 0.25), 60.0, at(4));
+        assert_eq!(violations[0].violation_type, "Response Time");
+        assert_eq!(agent.sla_tracking.compliance["web-service"], 50.0);
+
+        // Measurements restart with the month
+        agent.monitor_sla(&health(None, 1.0), 60.0, at(31 * 24));
+        assert_eq!(agent.sla_tracking.levels["web-service"].uptime(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_quota_increase_frozen_during_sev1() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        let request = Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: agent.agent.id,
+            message_type: quota::APPROVAL_REQUEST.to_string(),
+            content: "Raise quota team:Engineering".to_string(),
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([(quota::REQUEST_ID_KEY.to_string(), Uuid::new_v4().to_string())]),
+        };
+
+        assert!(agent.review_quota_increase(&request).await.unwrap());
+        agent.declare_incident(IncidentReport {
+            title: "Database down".to_string(),
+            description: "Primary unreachable".to_string(),
+            severity: Severity::Sev1,
+            affected_services: vec!["db".to_string()],
+        }).await.unwrap();
+        assert!(!agent.review_quota_increase(&request).await.unwrap());
+
+        let decisions: Vec<&str> = agent.outbox.iter().map(|m| m.metadata["approved"].as_str()).collect();
+        assert_eq!(decisions, vec!["true", "false"]);
+        assert_eq!(agent.change_queue[0].status, ChangeStatus::Approved);
+        assert_eq!(agent.change_queue[1].status, ChangeStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_carried_out_and_reviewed() {
+        let mut agent = OpsAgent::new("Test Agent".to_string(), None);
+        for title in ["Rotate logs", "Migrate database"] {
+            agent.review_change(Uuid::new_v4(), title.to_string(), String::new(), String::new(), "Restore the snapshot".to_string(), Uuid::nil()).await.unwrap();
+        }
+        agent.change_queue[0].risk_level = RiskLevel::Low;
+        agent.change_queue[1].risk_level = RiskLevel::Critical;
+        let settings = ChangeSettings {
+            failure_chances: [0.0, 0.0, 0.0, 1.0],
+            duration_minutes: (30, 30),
+            rollback_success_rate: 0.0,
+            ..ChangeSettings::default()
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let start = chrono::Utc::now();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+
+        let kinds = |events: Vec<ChangeEvent>| events.into_iter().map(|event| event.kind).collect::<Vec<_>>();
+        assert_eq!(kinds(agent.execute_changes(at(0), &settings, &mut rng)), vec![ChangeEventKind::Started { minutes: 30 }; 2]);
+        assert!(agent.execute_changes(at(20), &settings, &mut rng).is_empty());
+        assert_eq!(kinds(agent.execute_changes(at(30), &settings, &mut rng)), vec![ChangeEventKind::Completed, ChangeEventKind::Failed]);
+        assert!(agent.execute_changes(at(60), &settings, &mut rng).is_empty());
+        let reviews = kinds(agent.execute_changes(at(30 + 24 * 60), &settings, &mut rng));
+        assert_eq!(reviews, vec![ChangeEventKind::Reviewed { successful: true }, ChangeEventKind::Reviewed { successful: false }]);
+
+        let summary = agent.generate_report().await.unwrap().change_summary;
+        assert_eq!((summary.completed, summary.failed, summary.reviewed, summary.success_rate), (1, 1, 2, 0.5));
     }
 
     #[tokio::test]
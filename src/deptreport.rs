@@ -0,0 +1,238 @@
+//! Department Reports - Status Updates Rolled Up by Managers
+//!
+//! This module implements the status reporting that travels up the reporting
+//! lines every day:
+//! - Each agent sends its manager a `status_update`: its open items, the work
+//!   it finished since its last update, what it is blocked on and its risk
+//!   items, such as open incidents or an outsized backlog
+//! - Once all of its direct reports have answered, a manager adds its own
+//!   status and aggregates the updates into a department report: staff,
+//!   backlog, throughput, blockers and risks
+//! - Team managers pass their report up as a status update of their own;
+//!   department heads forward a summary to the CEO
+//! - A manager still missing updates when the next round starts reports with
+//!   what it has, naming who did not answer
+//!
+//! The executive keeps the latest report of each department.
+
+use crate::statusreport::AgentStatusReport;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// An agent's status, or a team's, as sent to its manager
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusUpdate {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub at: DateTime<Utc>,
+    /// Agents the update covers: the sender, and its team for managers
+    pub staff: usize,
+    pub backlog: usize,
+    /// Work finished since the previous update
+    pub throughput: usize,
+    /// Each prefixed with the agent it blocks
+    pub blockers: Vec<String>,
+    /// Each prefixed with the agent it concerns
+    pub risks: Vec<String>,
+}
+
+impl StatusUpdate {
+    /// An agent's own update from its status report; `blockers` name what it
+    /// is waiting on, and a backlog of `risk_backlog` or more is a risk
+    pub fn from_report(report: &AgentStatusReport, throughput: usize, blockers: Vec<String>, risk_backlog: usize, at: DateTime<Utc>) -> Self {
+        let mut risks: Vec<String> = report.active_items.iter()
+            .filter(|item| item.kind.contains("incident"))
+            .map(|item| format!("{}: {} '{}' ({})", report.name, item.kind, item.title, item.status))
+            .collect();
+        if report.backlog >= risk_backlog {
+            risks.push(format!("{}: {} open items", report.name, report.backlog));
+        }
+        Self {
+            agent_id: report.agent_id,
+            name: report.name.clone(),
+            at,
+            staff: 1,
+            backlog: report.backlog,
+            throughput,
+            blockers: blockers.into_iter().map(|blocker| format!("{}: {}", report.name, blocker)).collect(),
+            risks,
+        }
+    }
+}
+
+/// What a manager reports of everyone below it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepartmentReport {
+    pub manager_id: Uuid,
+    pub manager: String,
+    pub department: String,
+    pub at: DateTime<Utc>,
+    pub staff: usize,
+    pub backlog: usize,
+    pub throughput: usize,
+    pub blockers: Vec<String>,
+    pub risks: Vec<String>,
+    /// Direct reports that sent no update
+    pub missing: Vec<String>,
+}
+
+impl DepartmentReport {
+    /// The report as its manager's status update to the next level up
+    pub fn as_update(&self) -> StatusUpdate {
+        StatusUpdate {
+            agent_id: self.manager_id,
+            name: self.manager.clone(),
+            at: self.at,
+            staff: self.staff,
+            backlog: self.backlog,
+            throughput: self.throughput,
+            blockers: self.blockers.clone(),
+            risks: self.risks.clone(),
+        }
+    }
+
+    /// One line for the log and the CEO
+    pub fn summary(&self) -> String {
+        let mut line = format!("{} ({}): {} staff, {} open, {} done; {} blocker(s), {} risk(s)",
+                               self.department, self.manager, self.staff, self.backlog, self.throughput,
+                               self.blockers.len(), self.risks.len());
+        if !self.missing.is_empty() {
+            line.push_str(&format!("; no update from {}", self.missing.join(", ")));
+        }
+        line
+    }
+}
+
+/// Status updates managers are collecting, and their latest reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManagerReports {
+    /// Updates each manager received this round, by sender
+    inbox: BTreeMap<Uuid, BTreeMap<Uuid, StatusUpdate>>,
+    /// Finished work each agent last reported, for throughput
+    completed: BTreeMap<Uuid, usize>,
+    /// Latest report of each manager
+    reports: BTreeMap<Uuid, DepartmentReport>,
+}
+
+impl ManagerReports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Work an agent finished since its last update, given all it has on
+    /// record now
+    pub fn throughput(&mut self, agent_id: Uuid, completed: usize) -> usize {
+        let last = self.completed.insert(agent_id, completed).unwrap_or(0);
+        completed.saturating_sub(last)
+    }
+
+    /// File an update with its manager; true once every one of `reports`
+    /// has sent one
+    pub fn receive(&mut self, manager_id: Uuid, update: StatusUpdate, reports: &[Uuid]) -> bool {
+        let inbox = self.inbox.entry(manager_id).or_default();
+        inbox.insert(update.agent_id, update);
+        reports.iter().all(|report| inbox.contains_key(report))
+    }
+
+    /// Managers holding updates they have not reported yet
+    pub fn waiting(&self) -> Vec<Uuid> {
+        self.inbox.keys().copied().collect()
+    }
+
+    /// Aggregate a manager's own update and those it received into its
+    /// report; `reports` are its direct reports by id and name
+    pub fn compile(&mut self, own: StatusUpdate, department: &str, reports: &[(Uuid, String)]) -> &DepartmentReport {
+        let inbox = self.inbox.remove(&own.agent_id).unwrap_or_default();
+        let missing = reports.iter()
+            .filter(|(id, _)| !inbox.contains_key(id))
+            .map(|(_, name)| name.clone())
+            .collect();
+        let mut report = DepartmentReport {
+            manager_id: own.agent_id,
+            manager: own.name,
+            department: department.to_string(),
+            at: own.at,
+            staff: own.staff,
+            backlog: own.backlog,
+            throughput: own.throughput,
+            blockers: own.blockers,
+            risks: own.risks,
+            missing,
+        };
+        for update in inbox.into_values() {
+            report.staff += update.staff;
+            report.backlog += update.backlog;
+            report.throughput += update.throughput;
+            report.blockers.extend(update.blockers);
+            report.risks.extend(update.risks);
+        }
+        self.reports.insert(report.manager_id, report);
+        &self.reports[&own.agent_id]
+    }
+
+    /// Latest report of every manager
+    pub fn reports(&self) -> impl Iterator<Item = &DepartmentReport> {
+        self.reports.values()
+    }
+
+    /// Forget agents no longer in the company
+    pub fn retain(&mut self, agents: &[Uuid]) {
+        self.inbox.retain(|manager, _| agents.contains(manager));
+        self.completed.retain(|agent, _| agents.contains(agent));
+        self.reports.retain(|manager, _| agents.contains(manager));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusreport::ActiveItem;
+
+    fn report(name: &str, backlog: usize, active_items: Vec<ActiveItem>) -> AgentStatusReport {
+        AgentStatusReport {
+            agent_id: Uuid::new_v4(),
+            name: name.to_string(),
+            department: "Ops".to_string(),
+            manager_id: None,
+            backlog,
+            active_items,
+            skills: BTreeMap::new(),
+            completed: BTreeMap::new(),
+            state: None,
+            recent_activity: vec![],
+        }
+    }
+
+    #[test]
+    fn test_managers_aggregate_updates_from_their_reports() {
+        let now = Utc::now();
+        let mut reports = ManagerReports::new();
+        let (head, alice, bob) = (report("Head", 1, vec![]), report("Alice", 9, vec![]), report("Bob", 2, vec![]));
+        let incident = ActiveItem { kind: "incident".to_string(), id: Uuid::new_v4(), title: "Checkout down".to_string(), status: "Investigating".to_string() };
+        let carol = report("Carol", 1, vec![incident]);
+
+        assert_eq!(reports.throughput(alice.agent_id, 4), 4);
+        assert_eq!(reports.throughput(alice.agent_id, 6), 2);
+        let team = [alice.agent_id, bob.agent_id, carol.agent_id];
+        let alice_update = StatusUpdate::from_report(&alice, 2, vec!["waiting on Bob for resource_request".to_string()], 8, now);
+        assert_eq!(alice_update.risks, vec!["Alice: 9 open items"]);
+        assert!(!reports.receive(head.agent_id, alice_update, &team));
+        assert!(!reports.receive(head.agent_id, StatusUpdate::from_report(&bob, 1, vec![], 8, now), &team));
+        assert_eq!(reports.waiting(), vec![head.agent_id]);
+
+        // Carol never answers
+        let names: Vec<(Uuid, String)> = [&alice, &bob, &carol].iter().map(|r| (r.agent_id, r.name.clone())).collect();
+        let compiled = reports.compile(StatusUpdate::from_report(&head, 0, vec![], 8, now), "Ops", &names).clone();
+        assert_eq!((compiled.staff, compiled.backlog, compiled.throughput), (3, 12, 3));
+        assert_eq!(compiled.blockers, vec!["Alice: waiting on Bob for resource_request"]);
+        assert_eq!(compiled.missing, vec!["Carol"]);
+        assert_eq!(compiled.summary(), "Ops (Head): 3 staff, 12 open, 3 done; 1 blocker(s), 1 risk(s); no update from Carol");
+        assert!(reports.waiting().is_empty());
+
+        let carol_update = StatusUpdate::from_report(&carol, 0, vec![], 8, now);
+        assert_eq!(carol_update.risks, vec!["Carol: incident 'Checkout down' (Investigating)"]);
+        assert!(reports.receive(bob.agent_id, carol_update, &[carol.agent_id]));
+    }
+}
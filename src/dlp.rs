@@ -0,0 +1,260 @@
+//! Data Loss Prevention - Classified Data Stores and Where Their Data Goes
+//!
+//! This module implements what InfoSec knows about the company's data:
+//! - Data stores, each classified as public, internal, confidential or PII
+//!   and living in a network segment of some security level
+//! - Simulated transfers out of the stores to network segments: Networking's
+//!   segments, the stores' own and the internet. Most go where their data
+//!   may go; a few are misrouted to a segment below the level the data's
+//!   classification needs
+//! - DLP monitoring of every transfer: classified data moving to a
+//!   lower-security segment is an exposure, estimated in records and bytes
+//! - The same monitoring of Networking's flow logs: every accepted flow out
+//!   of a store's segment is a transfer of the most classified data there
+//!
+//! The orchestrator monitors a day of transfers every evening and hands the
+//! exposures to InfoSec, which records each as a data breach.
+
+use crate::config::DlpSettings;
+use crate::departments::networking::SecurityLevel;
+use crate::flowlog::{FlowAction, FlowRecord};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Exposures kept; the oldest go first
+const MAX_EXPOSURES: usize = 500;
+
+/// Segment data reaches when it leaves the company
+const INTERNET: &str = "internet";
+
+/// Largest share of a store's records one transfer moves
+const MAX_TRANSFER_SHARE: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Classification {
+    Public,
+    Internal,
+    Confidential,
+    Pii,
+}
+
+impl Classification {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Classification::Public => "public",
+            Classification::Internal => "internal",
+            Classification::Confidential => "confidential",
+            Classification::Pii => "PII",
+        }
+    }
+
+    /// Lowest security level a segment must have to hold the data
+    pub fn minimum_level(&self) -> SecurityLevel {
+        match self {
+            Classification::Public => SecurityLevel::Public,
+            Classification::Internal => SecurityLevel::Internal,
+            Classification::Confidential | Classification::Pii => SecurityLevel::Restricted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataStore {
+    pub name: String,
+    pub classification: Classification,
+    /// Network segment the store lives in, and its security level
+    pub segment: String,
+    pub security_level: SecurityLevel,
+    pub records: u64,
+    /// Average size of a record
+    pub record_bytes: u64,
+}
+
+/// Classified data that reached a segment below its classification's level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    pub id: Uuid,
+    pub store: String,
+    pub classification: Classification,
+    pub from_segment: String,
+    pub to_segment: String,
+    pub to_level: SecurityLevel,
+    /// Estimated size of what was exposed
+    pub records: u64,
+    pub bytes: u64,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Exposed records and bytes of one classification
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassificationExposure {
+    pub exposures: usize,
+    pub records: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DlpReport {
+    pub transfers: u64,
+    pub exposures: usize,
+    pub by_classification: BTreeMap<Classification, ClassificationExposure>,
+}
+
+/// Every transfer monitored, and the ones that exposed classified data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DlpMonitor {
+    transfers: u64,
+    exposures: Vec<Exposure>,
+}
+
+impl DlpMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn transfers(&self) -> u64 {
+        self.transfers
+    }
+
+    pub fn exposures(&self) -> &[Exposure] {
+        &self.exposures
+    }
+
+    /// A day of `transfers_per_day` transfers out of the stores to
+    /// `segments`, the stores' own segments or the internet, each misrouted
+    /// below its data's level with `misroute_probability`; returns the
+    /// exposures found
+    pub fn monitor(&mut self, segments: &BTreeMap<String, SecurityLevel>, settings: &DlpSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<Exposure> {
+        let stores: Vec<&DataStore> = settings.stores.iter().filter(|store| store.records > 0).collect();
+        if stores.is_empty() {
+            return Vec::new();
+        }
+        let mut destinations = segments.clone();
+        destinations.insert(INTERNET.to_string(), SecurityLevel::Public);
+        for store in &stores {
+            destinations.insert(store.segment.clone(), store.security_level.clone());
+        }
+
+        let mut found = Vec::new();
+        for _ in 0..settings.transfers_per_day {
+            let store = stores[rng.gen_range(0..stores.len())];
+            let minimum = store.classification.minimum_level();
+            let misrouted = rng.gen_bool(settings.misroute_probability.clamp(0.0, 1.0));
+            let candidates: Vec<(&String, &SecurityLevel)> = destinations.iter()
+                .filter(|(_, level)| (**level < minimum) == misrouted)
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            let (segment, level) = candidates[rng.gen_range(0..candidates.len())];
+            self.transfers += 1;
+            if *level >= minimum {
+                continue;
+            }
+            let most = ((store.records as f64 * MAX_TRANSFER_SHARE) as u64).max(1);
+            let records = rng.gen_range(1..=most);
+            found.push(Exposure {
+                id: Uuid::new_v4(),
+                store: store.name.clone(),
+                classification: store.classification,
+                from_segment: store.segment.clone(),
+                to_segment: segment.clone(),
+                to_level: level.clone(),
+                records,
+                bytes: records.saturating_mul(store.record_bytes),
+                at: now - chrono::Duration::minutes(rng.gen_range(0..24 * 60)),
+            });
+        }
+        self.record(&found);
+        found
+    }
+
+    /// Watch accepted flow records out of the stores' segments for data
+    /// reaching a segment below its level; `segments` are the levels of
+    /// Networking's segments. Returns the exposures found.
+    pub fn monitor_flows(&mut self, records: &[FlowRecord], segments: &BTreeMap<String, SecurityLevel>, settings: &DlpSettings) -> Vec<Exposure> {
+        let mut found = Vec::new();
+        for record in records.iter().filter(|record| record.action == FlowAction::Accept) {
+            let store = settings.stores.iter().filter(|store| store.segment == record.src_segment).max_by_key(|store| store.classification);
+            let Some(store) = store else {
+                continue;
+            };
+            self.transfers += 1;
+            let Some(level) = segments.get(&record.dst_segment).filter(|level| **level < store.classification.minimum_level()) else {
+                continue;
+            };
+            let exposed = (record.bytes / store.record_bytes.max(1)).clamp(1, store.records.max(1));
+            found.push(Exposure {
+                id: Uuid::new_v4(),
+                store: store.name.clone(),
+                classification: store.classification,
+                from_segment: record.src_segment.clone(),
+                to_segment: record.dst_segment.clone(),
+                to_level: level.clone(),
+                records: exposed,
+                bytes: record.bytes,
+                at: record.first_switched,
+            });
+        }
+        self.record(&found);
+        found
+    }
+
+    fn record(&mut self, found: &[Exposure]) {
+        self.exposures.extend(found.iter().cloned());
+        if self.exposures.len() > MAX_EXPOSURES {
+            let excess = self.exposures.len() - MAX_EXPOSURES;
+            self.exposures.drain(..excess);
+        }
+    }
+
+    pub fn report(&self) -> DlpReport {
+        let mut by_classification: BTreeMap<Classification, ClassificationExposure> = BTreeMap::new();
+        for exposure in &self.exposures {
+            let total = by_classification.entry(exposure.classification).or_default();
+            total.exposures += 1;
+            total.records += exposure.records;
+            total.bytes += exposure.bytes;
+        }
+        DlpReport { transfers: self.transfers, exposures: self.exposures.len(), by_classification }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn store(name: &str, classification: Classification, security_level: SecurityLevel) -> DataStore {
+        DataStore { name: name.to_string(), classification, segment: format!("{}-net", name), security_level, records: 1000, record_bytes: 100 }
+    }
+
+    #[test]
+    fn test_only_classified_data_below_its_level_is_exposed() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let now = chrono::Utc::now();
+        let segments = BTreeMap::from([("dmz".to_string(), SecurityLevel::DMZ)]);
+        let mut settings = DlpSettings {
+            stores: vec![store("customers", Classification::Pii, SecurityLevel::Restricted), store("site", Classification::Public, SecurityLevel::Public)],
+            transfers_per_day: 50,
+            misroute_probability: 0.0,
+            ..DlpSettings::default()
+        };
+        let mut monitor = DlpMonitor::new();
+        assert!(monitor.monitor(&segments, &settings, now, &mut rng).is_empty());
+
+        // Public data has nowhere lower to go, so only the PII store leaks
+        settings.misroute_probability = 1.0;
+        let exposures = monitor.monitor(&segments, &settings, now, &mut rng);
+        assert!(!exposures.is_empty());
+        assert!(exposures.iter().all(|exposure| exposure.store == "customers" && exposure.to_level < SecurityLevel::Restricted));
+        assert!(exposures.iter().all(|exposure| (1..=50).contains(&exposure.records) && exposure.bytes == exposure.records * 100));
+
+        let report = monitor.report();
+        assert_eq!(report.exposures, exposures.len());
+        assert_eq!(report.by_classification[&Classification::Pii].records, exposures.iter().map(|exposure| exposure.records).sum::<u64>());
+    }
+}
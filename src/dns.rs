@@ -0,0 +1,389 @@
+//! DNS Zones - Record Sets, TTL Propagation and BIND Export
+//!
+//! This module implements the zone Networking serves its names from:
+//! - Any number of records per name, validated on the way in: A and AAAA
+//!   values must be addresses of their family, CNAME targets hostnames, MX
+//!   values a preference and a host, SRV values priority, weight, port and
+//!   target; a CNAME stands alone at its name, and only A, AAAA and CNAME
+//!   records can be proxied
+//! - Propagation: a change to a name takes effect once resolvers drop the
+//!   records they cached, i.e. after the longest TTL of the records it
+//!   replaces; new names take effect at once
+//! - Resolution that follows CNAMEs to the records of the type asked for
+//! - Export as a BIND zone file with SOA and NS records; name servers given
+//!   by address get `ns<N>` names and glue A records
+//!
+//! The orchestrator applies the changes that have propagated every
+//! simulation step, before load balancers look their backends up.
+
+use crate::departments::networking::{DNSRecord, RecordType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Origin of the zone unless configured otherwise
+pub const DEFAULT_ORIGIN: &str = "aivertco.internal.";
+
+/// Largest TTL resolvers honour (RFC 2181)
+const MAX_TTL: u32 = i32::MAX as u32;
+
+/// CNAMEs followed at most when resolving
+const MAX_CNAME_CHAIN: usize = 8;
+
+/// SOA refresh, retry, expire and negative-caching minimum, in seconds
+const SOA_TIMERS: (u32, u32, u32, u32) = (3600, 600, 604_800, 300);
+
+/// A name's new records, waiting for the old ones to expire from caches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub name: String,
+    /// Records once propagated; none deletes the name
+    pub records: Vec<DNSRecord>,
+    pub propagates_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DnsZone {
+    #[serde(default = "default_origin")]
+    pub origin: String,
+    /// Bumped every time changes take effect
+    #[serde(default = "default_serial")]
+    pub serial: u32,
+    /// Records in effect, by name relative to the origin
+    #[serde(default, deserialize_with = "record_sets::deserialize")]
+    pub records: BTreeMap<String, Vec<DNSRecord>>,
+    #[serde(default)]
+    pub pending: Vec<PendingChange>,
+}
+
+fn default_origin() -> String {
+    DEFAULT_ORIGIN.to_string()
+}
+
+fn default_serial() -> u32 {
+    1
+}
+
+impl Default for DnsZone {
+    fn default() -> Self {
+        Self { origin: default_origin(), serial: default_serial(), records: BTreeMap::new(), pending: Vec::new() }
+    }
+}
+
+impl DnsZone {
+    /// `name` relative to the origin: `@` for the origin itself, absolute
+    /// names outside the zone unchanged
+    pub fn relative(&self, name: &str) -> String {
+        if name.eq_ignore_ascii_case(&self.origin) || name.eq_ignore_ascii_case(self.origin.trim_end_matches('.')) {
+            return "@".to_string();
+        }
+        let suffix = format!(".{}", self.origin);
+        let lowered = name.to_ascii_lowercase();
+        match lowered.strip_suffix(&suffix.to_ascii_lowercase()) {
+            Some(label) => name[..label.len()].to_string(),
+            None => name.to_string(),
+        }
+    }
+
+    /// Records of a name as they will be once its pending change propagates
+    pub fn latest(&self, name: &str) -> &[DNSRecord] {
+        let name = self.relative(name);
+        match self.pending.iter().find(|change| change.name == name) {
+            Some(change) => &change.records,
+            None => self.records.get(&name).map(Vec::as_slice).unwrap_or(&[]),
+        }
+    }
+
+    /// Replace a name's records; returns when the change takes effect
+    pub fn set(&mut self, name: &str, records: Vec<DNSRecord>, now: DateTime<Utc>) -> Result<DateTime<Utc>, DnsError> {
+        let name = self.relative(name);
+        validate_name(&name)?;
+        for record in &records {
+            validate(&name, record)?;
+        }
+        if records.iter().any(|record| record.record_type == RecordType::CNAME) && records.len() > 1 {
+            return Err(DnsError::CnameConflict(name));
+        }
+
+        if let Some(change) = self.pending.iter_mut().find(|change| change.name == name) {
+            // Caches still expire when they would have for the first change
+            change.records = records;
+            return Ok(change.propagates_at);
+        }
+        let cached = self.records.get(&name).and_then(|current| current.iter().map(|record| record.ttl).max());
+        match cached {
+            Some(ttl) => {
+                let propagates_at = now + chrono::Duration::seconds(i64::from(ttl));
+                self.pending.push(PendingChange { name, records, propagates_at });
+                Ok(propagates_at)
+            }
+            None => {
+                if !records.is_empty() {
+                    self.records.insert(name, records);
+                    self.serial = self.serial.wrapping_add(1);
+                }
+                Ok(now)
+            }
+        }
+    }
+
+    /// Add a record to a name, replacing one of the same type and value
+    pub fn add(&mut self, name: &str, record: DNSRecord, now: DateTime<Utc>) -> Result<DateTime<Utc>, DnsError> {
+        let mut records: Vec<DNSRecord> = self.latest(name).iter()
+            .filter(|existing| existing.record_type != record.record_type || existing.value != record.value)
+            .cloned()
+            .collect();
+        records.push(record);
+        self.set(name, records, now)
+    }
+
+    /// Delete every record of a name
+    pub fn remove(&mut self, name: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, DnsError> {
+        self.set(name, Vec::new(), now)
+    }
+
+    /// Put the changes whose old records expired by `now` into effect;
+    /// returns the names changed
+    pub fn apply_due(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let (due, waiting): (Vec<PendingChange>, Vec<PendingChange>) = self.pending.drain(..).partition(|change| change.propagates_at <= now);
+        self.pending = waiting;
+        if !due.is_empty() {
+            self.serial = self.serial.wrapping_add(1);
+        }
+        due.into_iter()
+            .map(|change| {
+                if change.records.is_empty() {
+                    self.records.remove(&change.name);
+                } else {
+                    self.records.insert(change.name.clone(), change.records);
+                }
+                change.name
+            })
+            .collect()
+    }
+
+    /// Records of `record_type` a lookup of `name` answers with, following
+    /// CNAMEs within the zone
+    pub fn resolve(&self, name: &str, record_type: &RecordType) -> Vec<&DNSRecord> {
+        let mut name = self.relative(name);
+        for _ in 0..MAX_CNAME_CHAIN {
+            let Some(records) = self.records.get(&name) else {
+                return Vec::new();
+            };
+            match records.iter().find(|record| record.record_type == RecordType::CNAME) {
+                Some(cname) if *record_type != RecordType::CNAME => name = self.relative(&cname.value),
+                _ => return records.iter().filter(|record| record.record_type == *record_type).collect(),
+            }
+        }
+        Vec::new()
+    }
+
+    /// Addresses a lookup of `name` answers with
+    pub fn addresses(&self, name: &str) -> Vec<IpAddr> {
+        self.resolve(name, &RecordType::A).into_iter()
+            .chain(self.resolve(name, &RecordType::AAAA))
+            .filter_map(|record| record.value.parse().ok())
+            .collect()
+    }
+
+    /// The records in effect as a BIND zone file
+    pub fn to_bind(&self, name_servers: &[String]) -> String {
+        let default_ttl = SOA_TIMERS.3;
+        let mut ns_names = Vec::new();
+        let mut glue = Vec::new();
+        for (index, server) in name_servers.iter().enumerate() {
+            match server.parse::<IpAddr>() {
+                Ok(address) => {
+                    let name = format!("ns{}", index + 1);
+                    ns_names.push(format!("{}.{}", name, self.origin));
+                    glue.push((name, address));
+                }
+                Err(_) => ns_names.push(server.clone()),
+            }
+        }
+        let primary = ns_names.first().cloned().unwrap_or_else(|| format!("ns1.{}", self.origin));
+        let (refresh, retry, expire, minimum) = SOA_TIMERS;
+
+        let mut output = format!("$ORIGIN {}\n$TTL {}\n", self.origin, default_ttl);
+        output.push_str(&format!(
+            "@\t{}\tIN\tSOA\t{} hostmaster.{} ( {} {} {} {} {} )\n",
+            default_ttl, primary, self.origin, self.serial, refresh, retry, expire, minimum
+        ));
+        for ns in &ns_names {
+            output.push_str(&format!("@\t{}\tIN\tNS\t{}\n", default_ttl, ns));
+        }
+        for (name, address) in &glue {
+            let record_type = if address.is_ipv4() { "A" } else { "AAAA" };
+            output.push_str(&format!("{}\t{}\tIN\t{}\t{}\n", name, default_ttl, record_type, address));
+        }
+        for (name, records) in &self.records {
+            for record in records {
+                let value = match record.record_type {
+                    RecordType::TXT => format!("\"{}\"", record.value.replace('\\', "\\\\").replace('"', "\\\"")),
+                    _ => record.value.clone(),
+                };
+                output.push_str(&format!("{}\t{}\tIN\t{:?}\t{}\n", name, record.ttl, record.record_type, value));
+            }
+        }
+        output
+    }
+}
+
+fn is_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+}
+
+/// Whether `name` is a hostname, relative or absolute
+fn is_hostname(name: &str) -> bool {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    !name.is_empty() && name.len() <= 253 && name.split('.').all(is_label)
+}
+
+/// Owner names may also be `@` or start with a `*` wildcard label
+fn validate_name(name: &str) -> Result<(), DnsError> {
+    let host = name.strip_prefix("*.").unwrap_or(name);
+    if name == "@" || name == "*" || is_hostname(host) {
+        Ok(())
+    } else {
+        Err(DnsError::InvalidName(name.to_string()))
+    }
+}
+
+/// Check a record's TTL, value and proxying against its type
+pub fn validate(name: &str, record: &DNSRecord) -> Result<(), DnsError> {
+    let invalid = |reason: &str| DnsError::InvalidValue(name.to_string(), record.record_type.clone(), record.value.clone(), reason.to_string());
+    if record.ttl == 0 || record.ttl > MAX_TTL {
+        return Err(DnsError::InvalidTtl(name.to_string(), record.ttl));
+    }
+    if record.proxied && !matches!(record.record_type, RecordType::A | RecordType::AAAA | RecordType::CNAME) {
+        return Err(DnsError::NotProxiable(name.to_string(), record.record_type.clone()));
+    }
+    let fields: Vec<&str> = record.value.split_whitespace().collect();
+    match record.record_type {
+        RecordType::A => {
+            record.value.parse::<Ipv4Addr>().map_err(|_| invalid("not an IPv4 address"))?;
+        }
+        RecordType::AAAA => {
+            record.value.parse::<Ipv6Addr>().map_err(|_| invalid("not an IPv6 address"))?;
+        }
+        RecordType::CNAME => {
+            if !is_hostname(&record.value) {
+                return Err(invalid("not a hostname"));
+            }
+        }
+        RecordType::MX => {
+            let [preference, host] = fields[..] else {
+                return Err(invalid("expected '<preference> <host>'"));
+            };
+            preference.parse::<u16>().map_err(|_| invalid("preference is not a number from 0 to 65535"))?;
+            if !is_hostname(host) {
+                return Err(invalid("mail exchanger is not a hostname"));
+            }
+        }
+        RecordType::TXT => {
+            if record.value.is_empty() || record.value.chars().any(|c| c.is_control()) {
+                return Err(invalid("text must be non-empty and printable"));
+            }
+        }
+        RecordType::SRV => {
+            let [priority, weight, port, target] = fields[..] else {
+                return Err(invalid("expected '<priority> <weight> <port> <target>'"));
+            };
+            for number in [priority, weight, port] {
+                number.parse::<u16>().map_err(|_| invalid("priority, weight and port must be numbers from 0 to 65535"))?;
+            }
+            if target != "." && !is_hostname(target) {
+                return Err(invalid("target is not a hostname"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record sets, also read from snapshots that kept one record per name
+mod record_sets {
+    use crate::departments::networking::DNSRecord;
+    use serde::{Deserialize, Deserializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        Many(Vec<DNSRecord>),
+        One(DNSRecord),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, Vec<DNSRecord>>, D::Error> {
+        let sets = BTreeMap::<String, OneOrMany>::deserialize(deserializer)?;
+        Ok(sets.into_iter()
+            .map(|(name, set)| match set {
+                OneOrMany::Many(records) => (name, records),
+                OneOrMany::One(record) => (name, vec![record]),
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DnsError {
+    #[error("Invalid DNS name '{0}'")]
+    InvalidName(String),
+
+    #[error("Invalid TTL {1} for {0}")]
+    InvalidTtl(String, u32),
+
+    #[error("Invalid {1:?} record for {0} '{2}': {3}")]
+    InvalidValue(String, RecordType, String, String),
+
+    #[error("A CNAME at {0} must be its only record")]
+    CnameConflict(String),
+
+    #[error("{1:?} records at {0} cannot be proxied")]
+    NotProxiable(String, RecordType),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(record_type: RecordType, value: &str, ttl: u32) -> DNSRecord {
+        DNSRecord { record_type, value: value.to_string(), ttl, proxied: false }
+    }
+
+    #[test]
+    fn test_changes_propagate_after_ttl_and_export_as_bind() {
+        let mut zone = DnsZone::default();
+        let now = Utc::now();
+        assert!(matches!(zone.add("web", record(RecordType::A, "10.1.0.300", 60), now), Err(DnsError::InvalidValue(..))));
+        assert!(matches!(zone.add("web", record(RecordType::MX, "mail", 60), now), Err(DnsError::InvalidValue(..))));
+        assert!(matches!(zone.add("bad name", record(RecordType::A, "10.1.0.1", 60), now), Err(DnsError::InvalidName(_))));
+        assert!(matches!(zone.add("web", record(RecordType::A, "10.1.0.1", 0), now), Err(DnsError::InvalidTtl(..))));
+
+        // New names answer at once, with every record
+        assert_eq!(zone.add("web", record(RecordType::A, "10.1.0.1", 300), now).unwrap(), now);
+        assert!(matches!(zone.add("web", record(RecordType::CNAME, "app", 300), now), Err(DnsError::CnameConflict(_))));
+        let later = zone.add("web.aivertco.internal.", record(RecordType::A, "10.1.0.2", 60), now).unwrap();
+        assert_eq!(later, now + chrono::Duration::seconds(300));
+        zone.add("www", record(RecordType::CNAME, "web", 3600), now).unwrap();
+        assert_eq!(zone.addresses("www"), vec!["10.1.0.1".parse::<IpAddr>().unwrap()]);
+
+        // The second address shows once the cached record expires
+        assert!(zone.apply_due(later - chrono::Duration::seconds(1)).is_empty());
+        assert_eq!(zone.apply_due(later), vec!["web".to_string()]);
+        assert_eq!(zone.addresses("www").len(), 2);
+        zone.add("@", record(RecordType::TXT, "v=spf1 -all", 300), now).unwrap();
+
+        let bind = zone.to_bind(&["8.8.8.8".to_string()]);
+        assert!(bind.starts_with("$ORIGIN aivertco.internal.\n"));
+        assert!(bind.contains(&format!("SOA\tns1.aivertco.internal. hostmaster.aivertco.internal. ( {} ", zone.serial)));
+        assert!(bind.contains("ns1\t300\tIN\tA\t8.8.8.8\n"));
+        assert!(bind.contains("web\t60\tIN\tA\t10.1.0.2\n"));
+        assert!(bind.contains("www\t3600\tIN\tCNAME\tweb\n"));
+        assert!(bind.contains("@\t300\tIN\tTXT\t\"v=spf1 -all\"\n"));
+    }
+}
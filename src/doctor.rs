@@ -0,0 +1,278 @@
+//! Simulation Doctor - Structural Self-Diagnostics
+//!
+//! This module implements health checks on the structure of a simulation,
+//! run against a snapshot:
+//! - Orphaned assignments: tasks, tickets, improvement tasks and risks held
+//!   by agents who are no longer in the company
+//! - Agents whose chain of managers does not reach an executive (an agent
+//!   without a manager), through a missing manager or a loop
+//! - Services in the catalog that nobody owns
+//! - Unroutable messages: recipients that do not exist, and message types
+//!   the recipient's department does not act on
+//!
+//! Every finding comes with the fix to apply. `aivertco doctor` checks a
+//! saved snapshot; the daemon checks each snapshot it rotates and serves the
+//! result on `/doctor`.
+
+use crate::departments::ops::TicketStatus;
+use crate::snapshot::SimulationSnapshot;
+use crate::system::SystemActor;
+use crate::taskgraph::TaskState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// Kind of structural problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    OrphanedAssignment,
+    BrokenManagerChain,
+    UnownedService,
+    UnroutableMessage,
+}
+
+impl Check {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Check::OrphanedAssignment => "orphaned assignment",
+            Check::BrokenManagerChain => "broken manager chain",
+            Check::UnownedService => "unowned service",
+            Check::UnroutableMessage => "unroutable message",
+        }
+    }
+}
+
+/// One problem found, and what to do about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnosis {
+    pub check: Check,
+    pub problem: String,
+    pub fix: String,
+}
+
+/// Run every check against a snapshot
+pub fn diagnose(snapshot: &SimulationSnapshot) -> Vec<Diagnosis> {
+    let mut diagnoses = Vec::new();
+    orphaned_assignments(snapshot, &mut diagnoses);
+    broken_manager_chains(snapshot, &mut diagnoses);
+    unowned_services(snapshot, &mut diagnoses);
+    unroutable_messages(snapshot, &mut diagnoses);
+    diagnoses
+}
+
+/// Diagnoses as printed by `aivertco doctor`
+pub fn render(diagnoses: &[Diagnosis]) -> String {
+    if diagnoses.is_empty() {
+        return "✅ No structural problems found\n".to_string();
+    }
+    let mut out = format!("🩻 {} structural problem(s) found\n", diagnoses.len());
+    for diagnosis in diagnoses {
+        out.push_str(&format!("\n❌ [{}] {}\n   → {}\n", diagnosis.check.as_str(), diagnosis.problem, diagnosis.fix));
+    }
+    out
+}
+
+fn orphaned_assignments(snapshot: &SimulationSnapshot, diagnoses: &mut Vec<Diagnosis>) {
+    let gone = |id: &Uuid| !snapshot.agents.contains_key(id);
+    let mut orphan = |problem: String, fix: &str| diagnoses.push(Diagnosis {
+        check: Check::OrphanedAssignment,
+        problem,
+        fix: fix.to_string(),
+    });
+
+    for task in snapshot.task_graph.tasks().filter(|task| task.state != TaskState::Done) {
+        if let Some(assignee) = task.assignee.filter(|assignee| gone(assignee)) {
+            orphan(format!("Task '{}' of project {} is assigned to departed agent {}", task.title, task.project_id, assignee),
+                   "Resume the simulation; the task is reassigned when it is next started, or mark it pending again");
+        }
+    }
+    for agent in snapshot.agents.values() {
+        let crate::snapshot::AgentSnapshot::Ops(ops) = agent else {
+            continue;
+        };
+        let open = ops.support_tickets.values().filter(|ticket| matches!(ticket.status, TicketStatus::Open | TicketStatus::InProgress));
+        for ticket in open {
+            if let Some(assignee) = ticket.assigned_to.filter(|assignee| gone(assignee)) {
+                orphan(format!("Ticket '{}' is assigned to departed agent {}", ticket.title, assignee),
+                       "Clear the ticket's assignee so Ops picks it up again");
+            }
+        }
+    }
+    for task in snapshot.scorecards.open_tasks().filter(|task| gone(&task.owner)) {
+        orphan(format!("Improvement task '{}' is owned by departed agent {}", task.title, task.owner),
+               "The next operations review assigns the service a new owner; re-open the task there");
+    }
+    for risk in snapshot.risks.open().filter(|risk| risk.owner.is_some_and(|owner| gone(&owner))) {
+        orphan(format!("Risk '{}' is owned by departed agent {}", risk.title, risk.owner.expect("filtered on owner")),
+               "The next quarterly risk review hands it to the department's manager");
+    }
+}
+
+fn broken_manager_chains(snapshot: &SimulationSnapshot, diagnoses: &mut Vec<Diagnosis>) {
+    let managers: BTreeMap<Uuid, Option<Uuid>> = snapshot.agents.iter()
+        .map(|(id, agent)| (*id, agent.agent().manager_id))
+        .collect();
+
+    for (id, agent) in &snapshot.agents {
+        let agent = agent.agent();
+        let mut seen = HashSet::from([*id]);
+        let mut current = agent.manager_id;
+        let problem = loop {
+            let Some(manager) = current else {
+                break None;
+            };
+            match managers.get(&manager) {
+                None => break Some(format!("{} ({}) reports to {}, who is not in the company", agent.name, agent.department.as_str(), manager)),
+                Some(_) if !seen.insert(manager) => break Some(format!("{} ({}) is in a management loop", agent.name, agent.department.as_str())),
+                Some(next) => current = *next,
+            }
+        };
+        if let Some(problem) = problem {
+            diagnoses.push(Diagnosis {
+                check: Check::BrokenManagerChain,
+                problem,
+                fix: format!("Set the agent's manager_id to the {} manager", agent.department.as_str()),
+            });
+        }
+    }
+}
+
+fn unowned_services(snapshot: &SimulationSnapshot, diagnoses: &mut Vec<Diagnosis>) {
+    for service in &snapshot.catalog.services {
+        let owned = snapshot.scorecards.owner(&service.name).is_some_and(|owner| snapshot.agents.contains_key(&owner));
+        if !owned {
+            diagnoses.push(Diagnosis {
+                check: Check::UnownedService,
+                problem: format!("Service {} has no owner", service.name),
+                fix: "Hire a DevOps agent if there is none; the next operations review assigns owners".to_string(),
+            });
+        }
+    }
+}
+
+fn unroutable_messages(snapshot: &SimulationSnapshot, diagnoses: &mut Vec<Diagnosis>) {
+    // Count each (recipient department or id, type) once, however often it was sent
+    let mut missing: BTreeMap<(Uuid, String), usize> = BTreeMap::new();
+    let mut unhandled: BTreeMap<(&str, String), usize> = BTreeMap::new();
+    for message in snapshot.message_log.iter().chain(&snapshot.pending_messages) {
+        if message.to_agent.is_nil() || SystemActor::is_system(message.to_agent) {
+            continue;
+        }
+        match snapshot.agents.get(&message.to_agent) {
+            None => *missing.entry((message.to_agent, message.message_type.clone())).or_default() += 1,
+            Some(agent) if !agent.handled_message_types().contains(&message.message_type.as_str()) => {
+                *unhandled.entry((agent.agent().department.as_str(), message.message_type.clone())).or_default() += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    for ((recipient, message_type), count) in missing {
+        diagnoses.push(Diagnosis {
+            check: Check::UnroutableMessage,
+            problem: format!("{} {} message(s) addressed to {}, who is not in the company", count, message_type, recipient),
+            fix: "Address the sender's messages to the department manager instead of a fixed agent".to_string(),
+        });
+    }
+    for ((department, message_type), count) in unhandled {
+        diagnoses.push(Diagnosis {
+            check: Check::UnroutableMessage,
+            problem: format!("{} {} message(s) sent to {}, which does not handle that type", count, message_type, department),
+            fix: format!("Send {} to a department that handles it, or add it to {}'s message handling", message_type, department),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::ServiceCatalog;
+    use crate::config::SimulationConfig;
+    use crate::departments::engineering::EngineeringAgent;
+    use crate::departments::ops::OpsAgent;
+    use crate::departments::DepartmentAgent;
+    use crate::snapshot::SNAPSHOT_VERSION;
+    use crate::taskgraph::{ProjectTask, TaskGraph};
+    use crate::testsupport::test_message;
+    use std::collections::HashMap;
+
+
+    #[test]
+    fn test_doctor_finds_orphans_broken_chains_and_unroutable_messages() {
+        let departed = Uuid::new_v4();
+        let manager = OpsAgent::new("Ops Manager".to_string(), None);
+        let engineer = EngineeringAgent::new("Engineer".to_string(), Some(departed));
+        let (manager_id, engineer_id) = (manager.get_agent().id, engineer.get_agent().id);
+
+        let mut task_graph = TaskGraph::new();
+        let task = ProjectTask::new(Uuid::new_v4(), "Build feature", "Engineering", 8, vec![]);
+        let task_id = task.id;
+        task_graph.add(task).unwrap();
+        task_graph.start(task_id, chrono::Utc::now()).unwrap();
+        task_graph.assign(task_id, departed, 8);
+
+        let snapshot = SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now(),
+            step_count: 0,
+            config: SimulationConfig::default(),
+            agents: HashMap::from([(manager_id, manager.snapshot()), (engineer_id, engineer.snapshot())]),
+            projects: HashMap::new(),
+            pending_messages: vec![],
+            campaign_reports: HashMap::new(),
+            catalog: ServiceCatalog::new(vec![]),
+            message_log: vec![
+                test_message(SystemActor::Orchestrator.id(), manager_id, "sla_check"),
+                test_message(SystemActor::Orchestrator.id(), engineer_id, "declare_incident"),
+                test_message(SystemActor::Orchestrator.id(), engineer_id, "declare_incident"),
+                test_message(SystemActor::Orchestrator.id(), departed, "work_on_features"),
+            ],
+            scheduler: None,
+            router: crate::routing::MessageRouter::new(),
+            intake: crate::intake::IntakeController::new(),
+            wait_graph: crate::waitgraph::WaitForGraph::new(),
+            kpi_history: crate::analytics::KpiHistory::new(),
+            entity_tracker: crate::webhooks::EntityTracker::new(),
+            webhooks: crate::webhooks::WebhookDispatcher::new(),
+            repository: None,
+            supervisor: crate::supervisor::Supervisor::new(),
+            release_notes: crate::releasenotes::ReleaseLog::new(),
+            scorecards: crate::scorecard::ScorecardBook::new(),
+            quotas: crate::quota::QuotaLedger::new(),
+            task_graph,
+            risks: crate::risk::RiskRegister::new(),
+            progression: crate::progression::SkillProgression::new(),
+            org: crate::orgchart::OrgChart::new(),
+            executive: crate::executive::ExecutiveAgent::new(),
+            staffing: crate::staffing::StaffingLog::new(),
+            economy: None,
+            knowledge: crate::knowledge::KnowledgeBase::new(),
+            pipelines: crate::pipeline::BuildQueue::new(),
+            promotions: crate::promotion::Promotions::new(),
+            alerts: crate::alerting::AlertEngine::new(),
+            red_team: crate::redteam::AttackSimulator::new(),
+            iam: crate::iam::IdentityDirectory::new(),
+            phishing: crate::phishing::AwarenessProgram::new(),
+            threat_intel: crate::threatintel::ThreatIntel::new(),
+            dlp: crate::dlp::DlpMonitor::new(),
+            ticket_queue: crate::ticketqueue::TicketQueue::new(),
+            capacity: crate::capacity::CapacityPlanner::new(),
+            status_page: crate::statuspage::StatusPage::new(),
+            shift_roster: crate::shifts::ShiftRoster::new(),
+            agent_states: crate::agentstate::AgentStates::new(),
+            memories: crate::memory::AgentMemories::new(),
+            manager_reports: crate::deptreport::ManagerReports::new(),
+            threads: crate::threads::ConversationThreads::new(),
+        };
+
+        let checks: Vec<Check> = diagnose(&snapshot).into_iter().map(|diagnosis| diagnosis.check).collect();
+        assert_eq!(checks, vec![
+            Check::OrphanedAssignment,
+            Check::BrokenManagerChain,
+            Check::UnroutableMessage,
+            Check::UnroutableMessage,
+        ]);
+        assert!(render(&diagnose(&snapshot)).contains("2 declare_incident message(s) sent to Engineering"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Economy - Revenue, Costs and the Company P&L
+//!
+//! This module implements the money side of the company:
+//! - Revenue from customer contracts, accrued daily, and from projects, paid
+//!   on delivery
+//! - Costs of the agents on payroll and the servers running deployments,
+//!   accrued daily
+//! - Penalties for SLA violations, a share of the customer's monthly contract
+//! - A P&L statement per simulated month and the cash balance
+//! - Bankruptcy once cash falls below the configured floor, which ends the run
+//!
+//! The orchestrator books every entry; the economy only keeps the accounts.
+
+use serde::{Deserialize, Serialize};
+
+/// Monthly statements kept, two years' worth
+const MAX_STATEMENTS: usize = 24;
+
+/// Line of the P&L an amount is booked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Entry {
+    ContractRevenue,
+    ProjectRevenue,
+    Payroll,
+    Infrastructure,
+    SlaPenalty,
+}
+
+impl Entry {
+    pub fn is_revenue(&self) -> bool {
+        matches!(self, Entry::ContractRevenue | Entry::ProjectRevenue)
+    }
+}
+
+/// P&L of one simulated month
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlyStatement {
+    /// e.g. "2024-03"
+    pub month: String,
+    pub contract_revenue: f64,
+    pub project_revenue: f64,
+    pub payroll: f64,
+    pub infrastructure: f64,
+    pub sla_penalties: f64,
+    pub projects_delivered: u32,
+    pub sla_violations: u32,
+}
+
+impl MonthlyStatement {
+    pub fn revenue(&self) -> f64 {
+        self.contract_revenue + self.project_revenue
+    }
+
+    pub fn costs(&self) -> f64 {
+        self.payroll + self.infrastructure + self.sla_penalties
+    }
+
+    pub fn profit(&self) -> f64 {
+        self.revenue() - self.costs()
+    }
+
+    fn line(&mut self, entry: Entry) -> &mut f64 {
+        match entry {
+            Entry::ContractRevenue => &mut self.contract_revenue,
+            Entry::ProjectRevenue => &mut self.project_revenue,
+            Entry::Payroll => &mut self.payroll,
+            Entry::Infrastructure => &mut self.infrastructure,
+            Entry::SlaPenalty => &mut self.sla_penalties,
+        }
+    }
+}
+
+/// The company's accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Economy {
+    pub cash: f64,
+    statements: Vec<MonthlyStatement>,
+    /// When cash first fell below the floor
+    pub bankrupt_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Economy {
+    pub fn new(starting_cash: f64) -> Self {
+        Self {
+            cash: starting_cash,
+            statements: Vec::new(),
+            bankrupt_at: None,
+        }
+    }
+
+    /// Book an amount, always positive, to a line of the month it falls in
+    pub fn book(&mut self, entry: Entry, amount: f64, at: chrono::DateTime<chrono::Utc>) {
+        let statement = self.statement_for(at);
+        *statement.line(entry) += amount;
+        match entry {
+            Entry::ProjectRevenue => statement.projects_delivered += 1,
+            Entry::SlaPenalty => statement.sla_violations += 1,
+            _ => {}
+        }
+        if entry.is_revenue() {
+            self.cash += amount;
+        } else {
+            self.cash -= amount;
+        }
+    }
+
+    /// Declare bankruptcy the first time cash is below `floor`; true if the
+    /// company is bankrupt
+    pub fn check_solvency(&mut self, floor: f64, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.bankrupt_at.is_none() && self.cash < floor {
+            self.bankrupt_at = Some(now);
+        }
+        self.bankrupt_at.is_some()
+    }
+
+    /// Statements, oldest month first
+    pub fn statements(&self) -> &[MonthlyStatement] {
+        &self.statements
+    }
+
+    /// Statement of the month `at` falls in, if anything was booked then
+    pub fn statement(&self, at: chrono::DateTime<chrono::Utc>) -> Option<&MonthlyStatement> {
+        let month = at.format("%Y-%m").to_string();
+        self.statements.iter().rev().find(|statement| statement.month == month)
+    }
+
+    fn statement_for(&mut self, at: chrono::DateTime<chrono::Utc>) -> &mut MonthlyStatement {
+        let month = at.format("%Y-%m").to_string();
+        if let Some(index) = self.statements.iter().rposition(|statement| statement.month == month) {
+            return &mut self.statements[index];
+        }
+        self.statements.push(MonthlyStatement { month, ..MonthlyStatement::default() });
+        if self.statements.len() > MAX_STATEMENTS {
+            self.statements.remove(0);
+        }
+        self.statements.last_mut().expect("pushed above")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_monthly_pnl_and_bankruptcy() {
+        let march = chrono::Utc.with_ymd_and_hms(2024, 3, 31, 0, 0, 0).unwrap();
+        let april = chrono::Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        let mut economy = Economy::new(1_000.0);
+
+        economy.book(Entry::ContractRevenue, 500.0, march);
+        economy.book(Entry::ProjectRevenue, 2_000.0, march);
+        economy.book(Entry::Payroll, 1_200.0, march);
+        economy.book(Entry::SlaPenalty, 300.0, march);
+        let statement = economy.statement(march).unwrap();
+        assert_eq!(statement.profit(), 1_000.0);
+        assert_eq!((statement.projects_delivered, statement.sla_violations), (1, 1));
+        assert!(!economy.check_solvency(0.0, march));
+
+        economy.book(Entry::Infrastructure, 2_500.0, april);
+        assert_eq!(economy.statements().len(), 2);
+        assert_eq!(economy.cash, -500.0);
+        assert!(economy.check_solvency(0.0, april));
+        // Bankruptcy sticks once declared
+        economy.book(Entry::ProjectRevenue, 5_000.0, april);
+        assert!(economy.check_solvency(0.0, april));
+        assert_eq!(economy.bankrupt_at, Some(april));
+    }
+}
@@ -0,0 +1,561 @@
+//! Email Gateway - Customer Communication
+//!
+//! This module implements the emails the company sends its customers:
+//! - Ticket updates when a customer's support ticket is opened or resolved
+//! - Campaign mailings when an email-channel marketing campaign goes live
+//! - Status page notices about incidents affecting the customer
+//! - Summary reports mailed to the operator when the daemon writes one
+//! - Transports: a minimal SMTP client for delivering to a real test inbox
+//!   (MailHog, Mailpit, Mailtrap, ...) or a built-in mock that only records
+//!
+//! Every email sent is kept in a bounded log on the gateway, whichever
+//! transport is used, so runs can be checked and reported on.
+
+use crate::catalog::CustomerAccount;
+use crate::config::{EmailSettings, EmailTransportSettings};
+use crate::departments::marketing::{Campaign, CampaignStatus, Channel};
+use crate::statuspage::CustomerNotice;
+use crate::webhooks::{EntityChange, EntityEvent};
+use async_trait::async_trait;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+/// Sent emails kept in the gateway log
+const LOG_CAPACITY: usize = 1_000;
+
+/// Longest an SMTP conversation may take
+const SMTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why an email was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailKind {
+    TicketUpdate,
+    Campaign,
+    StatusUpdate,
+    SummaryReport,
+}
+
+/// Email sent to a customer
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub id: Uuid,
+    pub kind: EmailKind,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    /// Simulated time the email was sent
+    pub sent_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Sending counters
+#[derive(Debug, Clone, Default)]
+pub struct EmailStats {
+    pub ticket_updates: u64,
+    pub campaign_emails: u64,
+    pub status_updates: u64,
+    pub summary_reports: u64,
+    pub failed: u64,
+}
+
+/// Way emails leave the simulation
+#[async_trait]
+pub trait EmailTransport: Send + Sync + std::fmt::Debug {
+    /// Transport name for logs, e.g. "SMTP localhost:1025"
+    fn name(&self) -> String;
+
+    async fn send(&mut self, email: &Email) -> Result<(), EmailError>;
+}
+
+/// Sends ticket updates, campaign mailings and status page notices
+#[derive(Debug)]
+pub struct EmailGateway {
+    transport: Box<dyn EmailTransport>,
+    settings: EmailSettings,
+    log: VecDeque<Email>,
+    pub stats: EmailStats,
+    /// Campaigns already mailed, or live before the gateway started
+    mailed_campaigns: HashSet<Uuid>,
+    /// Whether campaigns live at startup have been recorded
+    primed: bool,
+}
+
+impl EmailGateway {
+    /// Build the gateway for the configured transport, or `None` when email is off
+    pub fn from_settings(settings: &EmailSettings) -> Result<Option<Self>, EmailError> {
+        let transport: Box<dyn EmailTransport> = match &settings.transport {
+            None => return Ok(None),
+            Some(EmailTransportSettings::Mock) => Box::new(MockMailbox),
+            Some(EmailTransportSettings::Smtp { host, port, username, password_env, helo }) => {
+                let credentials = match username {
+                    Some(username) => {
+                        let password = std::env::var(password_env).map_err(|_| EmailError::MissingPassword(password_env.clone()))?;
+                        Some((username.clone(), password))
+                    }
+                    None => None,
+                };
+                Box::new(SmtpTransport { host: host.clone(), port: *port, helo: helo.clone(), credentials })
+            }
+        };
+        Ok(Some(Self::with_transport(transport, settings.clone())))
+    }
+
+    pub fn with_transport(transport: Box<dyn EmailTransport>, settings: EmailSettings) -> Self {
+        Self {
+            transport,
+            settings,
+            log: VecDeque::new(),
+            stats: EmailStats::default(),
+            mailed_campaigns: HashSet::new(),
+            primed: false,
+        }
+    }
+
+    pub fn transport_name(&self) -> String {
+        self.transport.name()
+    }
+
+    /// Most recent emails sent, oldest first
+    pub fn sent(&self) -> impl Iterator<Item = &Email> {
+        self.log.iter()
+    }
+
+    /// Email customers about ticket changes and newly live campaigns
+    ///
+    /// Returns the emails that could not be sent; they are not retried.
+    pub async fn run(&mut self, changes: &[EntityChange], campaigns: &[&Campaign], customers: &[&CustomerAccount], now: chrono::DateTime<chrono::Utc>) -> Vec<(Email, EmailError)> {
+        let mut outgoing = Vec::new();
+        if self.settings.ticket_updates {
+            outgoing.extend(changes.iter().filter_map(|change| self.ticket_email(change, customers, now)));
+        }
+
+        let live: Vec<&Campaign> = campaigns.iter().copied()
+            .filter(|campaign| campaign.channel == Channel::Email && campaign.status == CampaignStatus::Active)
+            .collect();
+        if !self.primed {
+            self.primed = true;
+            self.mailed_campaigns.extend(live.iter().map(|campaign| campaign.id));
+        }
+        for campaign in live {
+            if self.mailed_campaigns.insert(campaign.id) && self.settings.campaigns {
+                outgoing.extend(self.campaign_emails(campaign, customers, now));
+            }
+        }
+
+        self.deliver(outgoing).await
+    }
+
+    /// Email customers the status page's notices; returns the emails that
+    /// could not be sent
+    pub async fn notify(&mut self, notices: &[CustomerNotice]) -> Vec<(Email, EmailError)> {
+        if !self.settings.status_updates {
+            return Vec::new();
+        }
+        let outgoing = notices.iter()
+            .map(|notice| Email {
+                id: Uuid::new_v4(),
+                kind: EmailKind::StatusUpdate,
+                from: self.settings.support_from.clone(),
+                to: self.customer_address(&notice.customer_id),
+                subject: notice.subject.clone(),
+                body: notice.body.clone(),
+                sent_at: notice.at,
+            })
+            .collect();
+        self.deliver(outgoing).await
+    }
+
+    /// Email a summary report to `to`
+    pub async fn send_summary(&mut self, to: &str, subject: String, body: String, now: chrono::DateTime<chrono::Utc>) -> Result<(), EmailError> {
+        let email = Email {
+            id: Uuid::new_v4(),
+            kind: EmailKind::SummaryReport,
+            from: self.settings.support_from.clone(),
+            to: to.to_string(),
+            subject,
+            body,
+            sent_at: now,
+        };
+        match self.deliver(vec![email]).await.pop() {
+            Some((_, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Send `outgoing` in order; once the server cannot be reached, the
+    /// rest fail without another attempt so a down server costs one timeout
+    async fn deliver(&mut self, outgoing: Vec<Email>) -> Vec<(Email, EmailError)> {
+        let mut failures = Vec::new();
+        let mut unreachable = false;
+        for email in outgoing {
+            let result = if unreachable { Err(EmailError::Skipped) } else { self.transport.send(&email).await };
+            if let Err(e) = result {
+                unreachable |= matches!(e, EmailError::Connect(..) | EmailError::Timeout(_));
+                self.stats.failed += 1;
+                failures.push((email, e));
+                continue;
+            }
+            match email.kind {
+                EmailKind::TicketUpdate => self.stats.ticket_updates += 1,
+                EmailKind::Campaign => self.stats.campaign_emails += 1,
+                EmailKind::StatusUpdate => self.stats.status_updates += 1,
+                EmailKind::SummaryReport => self.stats.summary_reports += 1,
+            }
+            if self.log.len() == LOG_CAPACITY {
+                self.log.pop_front();
+            }
+            self.log.push_back(email);
+        }
+        failures
+    }
+
+    fn ticket_email(&self, change: &EntityChange, customers: &[&CustomerAccount], now: chrono::DateTime<chrono::Utc>) -> Option<Email> {
+        let ticket = &change.entity;
+        let customer_id = ticket["customer_id"].as_str()?;
+        let title = ticket["title"].as_str().unwrap_or("your request");
+        let reference = &change.entity_id.simple().to_string()[..8];
+        let greeting = format!("Hello {},", customer_name(customers, customer_id));
+
+        let (subject, body) = match change.event {
+            EntityEvent::TicketCreated => (
+                format!("[Ticket {}] We received your request: {}", reference, title),
+                format!("{}\n\nThanks for contacting AIvertCo support. Your request \"{}\" has been logged\nwith priority {} and an engineer will pick it up shortly.\n\nAIvertCo Support\n",
+                        greeting, title, ticket["priority"].as_str().unwrap_or("Medium")),
+            ),
+            EntityEvent::TicketResolved => (
+                format!("[Ticket {}] Resolved: {}", reference, title),
+                format!("{}\n\nYour request \"{}\" has been resolved.\n\nResolution: {}\n\nReply to this email within a week to reopen it.\n\nAIvertCo Support\n",
+                        greeting, title, ticket["resolution"].as_str().unwrap_or("see the ticket for details")),
+            ),
+            _ => return None,
+        };
+        Some(Email {
+            id: Uuid::new_v4(),
+            kind: EmailKind::TicketUpdate,
+            from: self.settings.support_from.clone(),
+            to: self.customer_address(customer_id),
+            subject,
+            body,
+            sent_at: now,
+        })
+    }
+
+    fn campaign_emails(&self, campaign: &Campaign, customers: &[&CustomerAccount], now: chrono::DateTime<chrono::Utc>) -> Vec<Email> {
+        let mut recipients = customers.to_vec();
+        recipients.sort_by(|a, b| a.id.cmp(&b.id));
+        recipients.into_iter()
+            .take(self.settings.max_campaign_recipients)
+            .map(|customer| Email {
+                id: Uuid::new_v4(),
+                kind: EmailKind::Campaign,
+                from: self.settings.marketing_from.clone(),
+                to: self.customer_address(&customer.id),
+                subject: campaign.name.clone(),
+                body: format!("Hello {},\n\n{} is live! As a {:?} customer you are among the first to hear about it.\n\nThe AIvertCo team\n",
+                              customer.name, campaign.name, customer.tier),
+                sent_at: now,
+            })
+            .collect()
+    }
+
+    fn customer_address(&self, customer_id: &str) -> String {
+        let local: String = customer_id.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        format!("{}@{}", local, self.settings.customer_domain)
+    }
+}
+
+fn customer_name(customers: &[&CustomerAccount], customer_id: &str) -> String {
+    customers.iter()
+        .find(|customer| customer.id == customer_id)
+        .map_or_else(|| customer_id.to_string(), |customer| customer.name.clone())
+}
+
+/// Accepts every email without sending it anywhere; the gateway log is the mailbox
+#[derive(Debug)]
+pub struct MockMailbox;
+
+#[async_trait]
+impl EmailTransport for MockMailbox {
+    fn name(&self) -> String {
+        "mock mailbox".to_string()
+    }
+
+    async fn send(&mut self, _email: &Email) -> Result<(), EmailError> {
+        Ok(())
+    }
+}
+
+/// Plain SMTP submission, one connection per email
+///
+/// Meant for test inboxes: there is no STARTTLS, so credentials are only
+/// sent with AUTH PLAIN over the unencrypted connection.
+#[derive(Debug)]
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+    helo: String,
+    credentials: Option<(String, String)>,
+}
+
+impl SmtpTransport {
+    async fn deliver(&self, email: &Email) -> Result<(), EmailError> {
+        let address = format!("{}:{}", self.host, self.port);
+        let stream = TcpStream::connect(&address).await
+            .map_err(|e| EmailError::Connect(address.clone(), e))?;
+        let mut stream = BufReader::new(stream);
+
+        expect_reply(&mut stream, 2).await?;
+        command(&mut stream, &format!("EHLO {}", self.helo), 2).await?;
+        if let Some((username, password)) = &self.credentials {
+            let token = base64(format!("\0{}\0{}", username, password).as_bytes());
+            command(&mut stream, &format!("AUTH PLAIN {}", token), 2).await?;
+        }
+        command(&mut stream, &format!("MAIL FROM:<{}>", header_value(&email.from)), 2).await?;
+        command(&mut stream, &format!("RCPT TO:<{}>", header_value(&email.to)), 2).await?;
+        command(&mut stream, "DATA", 3).await?;
+        stream.get_mut().write_all(format_message(email).as_bytes()).await?;
+        command(&mut stream, ".", 2).await?;
+        // The email is accepted at this point; a failed goodbye does not matter
+        let _ = command(&mut stream, "QUIT", 2).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    fn name(&self) -> String {
+        format!("SMTP {}:{}", self.host, self.port)
+    }
+
+    async fn send(&mut self, email: &Email) -> Result<(), EmailError> {
+        tokio::time::timeout(SMTP_TIMEOUT, self.deliver(email)).await
+            .map_err(|_| EmailError::Timeout(format!("{}:{}", self.host, self.port)))?
+    }
+}
+
+/// Send one SMTP command and check the class of the reply
+async fn command(stream: &mut BufReader<TcpStream>, line: &str, class: u16) -> Result<(), EmailError> {
+    stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await?;
+    expect_reply(stream, class).await
+}
+
+/// Read a possibly multi-line reply and check its code is `class`xx
+async fn expect_reply(stream: &mut BufReader<TcpStream>, class: u16) -> Result<(), EmailError> {
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Err(EmailError::Rejected("connection closed".to_string()));
+        }
+        let code: u16 = line.get(..3).and_then(|code| code.parse().ok())
+            .ok_or_else(|| EmailError::Rejected(line.trim_end().to_string()))?;
+        // "250-..." continues the reply, "250 ..." ends it
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        return if code / 100 == class { Ok(()) } else { Err(EmailError::Rejected(line.trim_end().to_string())) };
+    }
+}
+
+/// Headers and body as sent after DATA, with CRLF line ends and dot-stuffing
+fn format_message(email: &Email) -> String {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@aivertco>\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        header_value(&email.from), header_value(&email.to), header_value(&email.subject), email.sent_at.to_rfc2822(), email.id.simple());
+    for line in email.body.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message
+}
+
+/// A header value or address on one line: CR and LF become spaces, so
+/// customer-supplied text cannot add headers or SMTP commands
+fn header_value(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Standard base64 with padding (RFC 4648)
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> shift) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Email sending errors
+#[derive(Debug, thiserror::Error)]
+pub enum EmailError {
+    #[error("Environment variable {0} with the SMTP password is not set")]
+    MissingPassword(String),
+
+    #[error("Cannot connect to SMTP server {0}: {1}")]
+    Connect(String, std::io::Error),
+
+    #[error("SMTP I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("SMTP server rejected the email: {0}")]
+    Rejected(String),
+
+    #[error("SMTP server {0} timed out")]
+    Timeout(String),
+
+    #[error("Not sent, the SMTP server could not be reached for an earlier email")]
+    Skipped,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Tenancy;
+    use crate::departments::ops::CustomerTier;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use tokio::io::AsyncReadExt;
+
+    fn customer(id: &str) -> CustomerAccount {
+        CustomerAccount::new(id, &format!("{} Ltd", id), CustomerTier::Enterprise, Tenancy::Shared, chrono::Utc::now())
+    }
+
+    fn email_campaign(name: &str) -> Campaign {
+        Campaign {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            channel: Channel::Email,
+            budget: 1000.0,
+            spent: 0.0,
+            duration_days: 7,
+            days_run: 0,
+            conversion_rate: 0.05,
+            impressions: 0,
+            clicks: 0,
+            leads_generated: 0,
+            status: CampaignStatus::Active,
+            started_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ticket_updates_and_new_campaigns_are_mailed() {
+        let settings = EmailSettings { max_campaign_recipients: 2, ..EmailSettings::default() };
+        let mut gateway = EmailGateway::with_transport(Box::new(MockMailbox), settings);
+        let customers = [customer("acme"), customer("globex"), customer("initech")];
+        let customers: Vec<&CustomerAccount> = customers.iter().collect();
+        let already_live = email_campaign("Spring sale");
+        let now = chrono::Utc::now();
+
+        let opened = EntityChange {
+            event: EntityEvent::TicketCreated,
+            entity_id: Uuid::new_v4(),
+            occurred_at: now,
+            attributes: BTreeMap::new(),
+            entity: json!({ "title": "Login fails", "customer_id": "acme", "priority": "High" }),
+        };
+        assert!(gateway.run(&[opened], &[&already_live], &customers, now).await.is_empty());
+        let sent: Vec<&Email> = gateway.sent().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "acme@customers.example");
+        assert!(sent[0].body.starts_with("Hello acme Ltd,"));
+
+        let launch = email_campaign("Summer launch");
+        gateway.run(&[], &[&already_live, &launch], &customers, now).await;
+        gateway.run(&[], &[&already_live, &launch], &customers, now).await;
+        assert_eq!(gateway.stats.campaign_emails, 2);
+        assert!(gateway.sent().skip(1).all(|email| email.subject == "Summer launch"));
+    }
+
+    #[tokio::test]
+    async fn test_smtp_conversation() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let replies = ["220 ready", "250-inbox\r\n250 AUTH PLAIN", "235 ok", "250 ok", "250 ok", "354 go", "250 queued", "221 bye"];
+            let mut received = String::new();
+            for reply in replies {
+                socket.write_all(format!("{}\r\n", reply).as_bytes()).await.unwrap();
+                if reply.starts_with("221") {
+                    break;
+                }
+                // Read until the client's command (or the whole message after DATA) is in
+                let terminator = if reply.starts_with("354") { "\r\n.\r\n" } else { "\r\n" };
+                let start = received.len();
+                while !received[start..].ends_with(terminator) {
+                    let mut buffer = [0u8; 1024];
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    received.push_str(std::str::from_utf8(&buffer[..read]).unwrap());
+                }
+            }
+            received
+        });
+
+        let mut transport = SmtpTransport {
+            host: "127.0.0.1".to_string(),
+            port,
+            helo: "test".to_string(),
+            credentials: Some(("user".to_string(), "secret".to_string())),
+        };
+        let email = Email {
+            id: Uuid::new_v4(),
+            kind: EmailKind::TicketUpdate,
+            from: "support@aivertco.example".to_string(),
+            to: "acme@customers.example".to_string(),
+            subject: "Resolved\r\nBcc: everyone@customers.example".to_string(),
+            body: "Fixed.\n.hidden line\n".to_string(),
+            sent_at: chrono::Utc::now(),
+        };
+        transport.send(&email).await.unwrap();
+
+        let received = server.await.unwrap();
+        assert!(received.starts_with("EHLO test\r\nAUTH PLAIN AHVzZXIAc2VjcmV0\r\nMAIL FROM:<support@aivertco.example>\r\n"));
+        assert!(received.contains("\r\n\r\nFixed.\r\n..hidden line\r\n.\r\n"));
+        assert!(received.contains("\r\nSubject: Resolved  Bcc: everyone@customers.example\r\n"));
+        assert!(!received.contains("\r\nBcc:"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_tried_once() {
+        // Nothing listens on a port that was just released
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let transport = SmtpTransport { host: "127.0.0.1".to_string(), port, helo: "test".to_string(), credentials: None };
+        let mut gateway = EmailGateway::with_transport(Box::new(transport), EmailSettings::default());
+        let outgoing: Vec<Email> = ["acme", "globex", "initech"].iter()
+            .map(|customer| Email {
+                id: Uuid::new_v4(),
+                kind: EmailKind::Campaign,
+                from: "marketing@aivertco.example".to_string(),
+                to: format!("{}@customers.example", customer),
+                subject: "Summer launch".to_string(),
+                body: "Hello".to_string(),
+                sent_at: chrono::Utc::now(),
+            })
+            .collect();
+
+        let failures = gateway.deliver(outgoing).await;
+        assert_eq!(failures.len(), 3);
+        assert!(matches!(failures[0].1, EmailError::Connect(..)));
+        assert!(failures[1..].iter().all(|(_, e)| matches!(e, EmailError::Skipped)));
+        assert_eq!(gateway.stats.failed, 3);
+    }
+}
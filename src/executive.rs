@@ -0,0 +1,259 @@
+//! Executive - Company Strategy Set by the CEO
+//!
+//! This module implements the CEO as an executive agent steering the company:
+//! - Quarterly goals for the company KPIs, set from where each KPI stands
+//!   when the quarter opens
+//! - Headcount and budget allocated across departments by their share of the
+//!   open work, out of the monthly revenue
+//! - A weekly review of the aggregated KPIs against the goals
+//! - Reorganizations when performance calls for them: a hiring freeze while
+//!   revenue keeps missing its goal, lifted once it recovers, and new
+//!   departments opened for work nobody in the company can take
+//! - The latest report each department head sends up: throughput, blockers
+//!   and risk items
+//!
+//! The executive only decides; the orchestrator gathers the KPIs and carries
+//! out the plans.
+
+use crate::analytics::Kpi;
+use crate::config::ExecutiveSettings;
+use crate::deptreport::DepartmentReport;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Weekly reviews kept, about a quarter's worth
+const MAX_REVIEWS: usize = 13;
+
+/// Target for one KPI this quarter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Goal {
+    pub kpi: Kpi,
+    /// Value when the goal was set
+    pub baseline: f64,
+    pub target: f64,
+}
+
+impl Goal {
+    pub fn met(&self, value: f64) -> bool {
+        if self.kpi.higher_is_better() {
+            value >= self.target
+        } else {
+            value <= self.target
+        }
+    }
+}
+
+/// Staff and money a department is given
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub headcount: usize,
+    /// Dollars per month
+    pub budget: f64,
+}
+
+/// Change to how the company is organized
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reorganization {
+    HiringFreeze,
+    FreezeLifted,
+    /// Department opened for work nobody could take
+    NewDepartment(String),
+}
+
+impl Reorganization {
+    pub fn describe(&self) -> String {
+        match self {
+            Reorganization::HiringFreeze => "Hiring freeze until revenue is back on target".to_string(),
+            Reorganization::FreezeLifted => "Hiring freeze lifted".to_string(),
+            Reorganization::NewDepartment(department) => format!("{} department opened", department),
+        }
+    }
+}
+
+/// Outcome of one weekly review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyReview {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub kpis: BTreeMap<Kpi, f64>,
+    pub goals_met: usize,
+    pub goals_missed: Vec<Kpi>,
+    pub reorganizations: Vec<Reorganization>,
+}
+
+/// What the company looks like to the CEO at a review
+#[derive(Debug, Clone, Default)]
+pub struct CompanyView {
+    /// Latest value of every sampled KPI
+    pub kpis: BTreeMap<Kpi, f64>,
+    /// Agents per department
+    pub staff: BTreeMap<String, usize>,
+    /// Open work items per department
+    pub backlog: BTreeMap<String, usize>,
+    /// Departments with work waiting and nobody to do it
+    pub unstaffed: Vec<String>,
+    pub monthly_revenue: f64,
+}
+
+/// The CEO's goals, plans and reviews
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutiveAgent {
+    /// Year and quarter (1-4) the goals are for
+    pub quarter: Option<(i32, u32)>,
+    pub goals: Vec<Goal>,
+    /// By department name
+    pub allocations: BTreeMap<String, Allocation>,
+    pub hiring_freeze: bool,
+    /// Weeks in a row the revenue goal was missed
+    missed_weeks: u32,
+    reviews: Vec<WeeklyReview>,
+    /// Latest report from each department head, by department name
+    #[serde(default)]
+    pub department_reports: BTreeMap<String, DepartmentReport>,
+}
+
+fn quarter_of(at: chrono::DateTime<chrono::Utc>) -> (i32, u32) {
+    (at.year(), (at.month() - 1) / 3 + 1)
+}
+
+impl ExecutiveAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn latest_review(&self) -> Option<&WeeklyReview> {
+        self.reviews.last()
+    }
+
+    /// Take a department head's report, replacing the department's last one
+    pub fn receive_report(&mut self, report: DepartmentReport) {
+        self.department_reports.insert(report.department.clone(), report);
+    }
+
+    /// Hold the weekly review, setting new goals first when a quarter opened
+    pub fn review(&mut self, view: &CompanyView, settings: &ExecutiveSettings, now: chrono::DateTime<chrono::Utc>) -> &WeeklyReview {
+        if self.quarter != Some(quarter_of(now)) {
+            self.set_goals(&view.kpis, settings, now);
+        }
+
+        let mut goals_missed = Vec::new();
+        for goal in &self.goals {
+            match view.kpis.get(&goal.kpi) {
+                Some(value) if goal.met(*value) => {}
+                Some(_) => goals_missed.push(goal.kpi),
+                None => {}
+            }
+        }
+
+        let mut reorganizations = Vec::new();
+        let revenue_missed = goals_missed.contains(&Kpi::MonthlyRevenue);
+        self.missed_weeks = if revenue_missed { self.missed_weeks + 1 } else { 0 };
+        if !self.hiring_freeze && self.missed_weeks >= settings.freeze_after_weeks.max(1) {
+            self.hiring_freeze = true;
+            reorganizations.push(Reorganization::HiringFreeze);
+        } else if self.hiring_freeze && !revenue_missed {
+            self.hiring_freeze = false;
+            reorganizations.push(Reorganization::FreezeLifted);
+        }
+        for department in &view.unstaffed {
+            if self.allocations.get(department).is_none_or(|allocation| allocation.headcount == 0) {
+                reorganizations.push(Reorganization::NewDepartment(department.clone()));
+            }
+        }
+
+        self.allocate(view, settings);
+        for reorganization in &reorganizations {
+            if let Reorganization::NewDepartment(department) = reorganization {
+                let allocation = self.allocations.entry(department.clone()).or_default();
+                allocation.headcount = allocation.headcount.max(1);
+            }
+        }
+
+        self.reviews.push(WeeklyReview {
+            at: now,
+            kpis: view.kpis.clone(),
+            goals_met: self.goals.len() - goals_missed.len(),
+            goals_missed,
+            reorganizations,
+        });
+        if self.reviews.len() > MAX_REVIEWS {
+            self.reviews.remove(0);
+        }
+        self.reviews.last().expect("pushed above")
+    }
+
+    /// Goals for the quarter: every KPI improved on where it stands now
+    fn set_goals(&mut self, kpis: &BTreeMap<Kpi, f64>, settings: &ExecutiveSettings, now: chrono::DateTime<chrono::Utc>) {
+        let improvement = settings.goal_improvement_percent / 100.0;
+        self.goals = kpis.iter()
+            .map(|(&kpi, &baseline)| {
+                let target = if kpi.higher_is_better() { baseline * (1.0 + improvement) } else { baseline * (1.0 - improvement) };
+                Goal { kpi, baseline, target }
+            })
+            .collect();
+        self.quarter = Some(quarter_of(now));
+    }
+
+    /// Headcount to keep every department's backlog per agent in bounds, at
+    /// least one agent each and no change during a freeze, and a share of
+    /// revenue in proportion
+    fn allocate(&mut self, view: &CompanyView, settings: &ExecutiveSettings) {
+        let per_agent = settings.backlog_per_agent.max(1);
+        // Departments opened earlier keep their seats until someone fills them
+        let mut headcount: BTreeMap<String, usize> = self.allocations.iter()
+            .filter(|(department, _)| !view.staff.contains_key(*department))
+            .map(|(department, allocation)| (department.clone(), allocation.headcount))
+            .collect();
+        for (department, staff) in &view.staff {
+            let backlog = view.backlog.get(department).copied().unwrap_or(0);
+            let needed = if self.hiring_freeze { *staff } else { backlog.div_ceil(per_agent).max(1) };
+            headcount.insert(department.clone(), needed);
+        }
+        let total = headcount.values().sum::<usize>().max(1);
+        let budget = view.monthly_revenue * settings.budget_share_percent / 100.0;
+        self.allocations = headcount.into_iter()
+            .map(|(department, headcount)| {
+                let share = budget * headcount as f64 / total as f64;
+                (department, Allocation { headcount, budget: share })
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_goals_freeze_and_allocation() {
+        let settings = ExecutiveSettings { freeze_after_weeks: 2, backlog_per_agent: 5, ..ExecutiveSettings::default() };
+        let mut view = CompanyView {
+            kpis: BTreeMap::from([(Kpi::MonthlyRevenue, 10_000.0), (Kpi::OpenTickets, 20.0)]),
+            staff: BTreeMap::from([("Ops".to_string(), 2), ("DevOps".to_string(), 2)]),
+            backlog: BTreeMap::from([("Ops".to_string(), 21)]),
+            unstaffed: vec![],
+            monthly_revenue: 10_000.0,
+        };
+        let mut executive = ExecutiveAgent::new();
+        let week = |n: i64| chrono::Utc.with_ymd_and_hms(2024, 4, 1, 8, 0, 0).unwrap() + chrono::Duration::weeks(n);
+
+        // Goals are set from the first review's KPIs, which then miss them
+        let review = executive.review(&view, &settings, week(0));
+        assert_eq!(review.goals_missed.len(), 2);
+        assert_eq!(executive.quarter, Some((2024, 2)));
+        assert_eq!(executive.allocations["Ops"].headcount, 5);
+        assert_eq!(executive.allocations["DevOps"].headcount, 1);
+
+        let review = executive.review(&view, &settings, week(1));
+        assert_eq!(review.reorganizations, vec![Reorganization::HiringFreeze]);
+        // Frozen departments keep their staff
+        assert_eq!(executive.allocations["Ops"].headcount, 2);
+
+        view.kpis.insert(Kpi::MonthlyRevenue, 12_000.0);
+        view.unstaffed = vec!["Sales".to_string()];
+        let review = executive.review(&view, &settings, week(2));
+        assert_eq!(review.reorganizations, vec![Reorganization::FreezeLifted, Reorganization::NewDepartment("Sales".to_string())]);
+        assert_eq!(executive.allocations["Sales"].headcount, 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Firewall Analysis - Rule Conflicts, Shadowing and Packet Simulation
+//!
+//! This module implements the review of Networking's ordered firewall rules:
+//! - Packet simulation: the first enabled allow or deny rule matching a
+//!   packet's source and destination segment, port and protocol decides it;
+//!   log and rate-limit rules let the packet through to the next rule
+//! - Shadowed rules, which an earlier rule with the opposite action fully
+//!   covers, so they never take effect
+//! - Redundant rules, which an earlier rule with the same action fully
+//!   covers, so removing them changes nothing
+//! - Conflicts, where two rules with opposite actions partly overlap and
+//!   their order alone decides the packets in between
+//! - Overly broad allows from any segment to any segment
+//!
+//! The orchestrator analyzes the firewall every evening and hands the
+//! findings to InfoSec.
+
+use crate::departments::infosec::Severity;
+use crate::departments::networking::{FirewallAction, FirewallRule, PortRange, Protocol};
+use serde::{Deserialize, Serialize};
+
+/// Segment names that match every segment
+const ANY_SEGMENTS: &[&str] = &["any", "*"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    Shadowed,
+    Conflict,
+    OverlyBroad,
+    Redundant,
+}
+
+impl FindingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FindingKind::Shadowed => "shadowed",
+            FindingKind::Conflict => "conflict",
+            FindingKind::OverlyBroad => "overly broad",
+            FindingKind::Redundant => "redundant",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::Shadowed | FindingKind::OverlyBroad => Severity::High,
+            FindingKind::Conflict => Severity::Medium,
+            FindingKind::Redundant => Severity::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FirewallFinding {
+    pub kind: FindingKind,
+    /// Name of the rule at fault
+    pub rule: String,
+    /// Earlier rule it clashes with, if any
+    pub other: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FirewallReport {
+    /// Enabled rules analyzed
+    pub rules: usize,
+    pub findings: Vec<FirewallFinding>,
+}
+
+impl FirewallReport {
+    pub fn count(&self, kind: FindingKind) -> usize {
+        self.findings.iter().filter(|finding| finding.kind == kind).count()
+    }
+}
+
+fn is_any(segment: &str) -> bool {
+    ANY_SEGMENTS.iter().any(|any| segment.eq_ignore_ascii_case(any))
+}
+
+fn segment_matches(rule: &str, segment: &str) -> bool {
+    is_any(rule) || rule == segment
+}
+
+/// Whether every segment `b` matches, `a` matches too
+fn segment_covers(a: &str, b: &str) -> bool {
+    is_any(a) || a == b
+}
+
+fn segments_overlap(a: &str, b: &str) -> bool {
+    is_any(a) || is_any(b) || a == b
+}
+
+fn protocol_covers(a: &Protocol, b: &Protocol) -> bool {
+    *a == Protocol::Any || a == b
+}
+
+fn protocols_overlap(a: &Protocol, b: &Protocol) -> bool {
+    *a == Protocol::Any || *b == Protocol::Any || a == b
+}
+
+fn ports_cover(a: &PortRange, b: &PortRange) -> bool {
+    a.start <= b.start && a.end >= b.end
+}
+
+fn ports_overlap(a: &PortRange, b: &PortRange) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Whether every packet `b` matches, `a` matches too
+fn covers(a: &FirewallRule, b: &FirewallRule) -> bool {
+    segment_covers(&a.source_segment, &b.source_segment) && segment_covers(&a.destination_segment, &b.destination_segment)
+        && protocol_covers(&a.protocol, &b.protocol) && ports_cover(&a.port_range, &b.port_range)
+}
+
+/// Whether some packet matches both rules
+fn overlap(a: &FirewallRule, b: &FirewallRule) -> bool {
+    segments_overlap(&a.source_segment, &b.source_segment) && segments_overlap(&a.destination_segment, &b.destination_segment)
+        && protocols_overlap(&a.protocol, &b.protocol) && ports_overlap(&a.port_range, &b.port_range)
+}
+
+/// Enabled rules that decide packets, in order
+fn deciding(rules: &[FirewallRule]) -> impl Iterator<Item = &FirewallRule> {
+    rules.iter().filter(|rule| rule.enabled && !matches!(rule.action, FirewallAction::Log | FirewallAction::RateLimit))
+}
+
+/// The rule that decides a packet from `source` to `destination`, if any
+/// does; without one the packet goes through
+pub fn simulate_packet<'a>(rules: &'a [FirewallRule], source: &str, destination: &str, port: u16, protocol: &Protocol) -> Option<&'a FirewallRule> {
+    deciding(rules).find(|rule| {
+        segment_matches(&rule.source_segment, source) && segment_matches(&rule.destination_segment, destination)
+            && protocol_covers(&rule.protocol, protocol) && rule.port_range.start <= port && port <= rule.port_range.end
+    })
+}
+
+/// Analyze the rules in the order the firewall applies them
+pub fn analyze(rules: &[FirewallRule]) -> FirewallReport {
+    let rules: Vec<&FirewallRule> = deciding(rules).collect();
+    let mut findings = Vec::new();
+    for (index, rule) in rules.iter().enumerate() {
+        if rule.action == FirewallAction::Allow && is_any(&rule.source_segment) && is_any(&rule.destination_segment) {
+            findings.push(FirewallFinding {
+                kind: FindingKind::OverlyBroad,
+                rule: rule.name.clone(),
+                other: None,
+                description: format!("{} allows {:?} ports {}-{} from any segment to any segment", rule.name, rule.protocol, rule.port_range.start, rule.port_range.end),
+            });
+        }
+        // The first earlier rule covering it explains why it never fires
+        if let Some(earlier) = rules[..index].iter().find(|earlier| covers(earlier, rule)) {
+            let (kind, effect) = if earlier.action == rule.action {
+                (FindingKind::Redundant, "already does the same")
+            } else {
+                (FindingKind::Shadowed, "decides every packet it matches first, so it never takes effect")
+            };
+            findings.push(FirewallFinding {
+                kind,
+                rule: rule.name.clone(),
+                other: Some(earlier.name.clone()),
+                description: format!("{} ({:?}): earlier rule {} ({:?}) {}", rule.name, rule.action, earlier.name, earlier.action, effect),
+            });
+            continue;
+        }
+        for earlier in rules[..index].iter().filter(|earlier| earlier.action != rule.action && overlap(earlier, rule)) {
+            findings.push(FirewallFinding {
+                kind: FindingKind::Conflict,
+                rule: rule.name.clone(),
+                other: Some(earlier.name.clone()),
+                description: format!("{} ({:?}) partly overlaps earlier rule {} ({:?}); their order decides the packets both match", rule.name, rule.action, earlier.name, earlier.action),
+            });
+        }
+    }
+    FirewallReport { rules: rules.len(), findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, source: &str, destination: &str, ports: (u16, u16), protocol: Protocol, action: FirewallAction) -> FirewallRule {
+        FirewallRule {
+            id: format!("fw-{}", name),
+            name: name.to_string(),
+            source_segment: source.to_string(),
+            destination_segment: destination.to_string(),
+            port_range: PortRange { start: ports.0, end: ports.1 },
+            protocol,
+            action,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_analysis_finds_shadowed_redundant_conflicting_and_broad_rules() {
+        let rules = vec![
+            rule("log-all", "any", "any", (0, u16::MAX), Protocol::Any, FirewallAction::Log),
+            rule("deny-db", "dmz", "db", (0, u16::MAX), Protocol::Any, FirewallAction::Deny),
+            rule("allow-postgres", "dmz", "db", (5432, 5432), Protocol::TCP, FirewallAction::Allow),
+            rule("deny-db-tcp", "dmz", "db", (1, 1024), Protocol::TCP, FirewallAction::Deny),
+            rule("allow-web", "any", "web", (80, 443), Protocol::TCP, FirewallAction::Allow),
+            rule("deny-admin", "office", "web", (443, 8443), Protocol::TCP, FirewallAction::Deny),
+            rule("allow-all", "any", "any", (0, u16::MAX), Protocol::Any, FirewallAction::Allow),
+        ];
+        let report = analyze(&rules);
+        assert_eq!(report.rules, 6);
+        let find = |name: &str, kind: FindingKind| report.findings.iter().find(|finding| finding.rule == name && finding.kind == kind);
+        assert_eq!(find("allow-postgres", FindingKind::Shadowed).unwrap().other.as_deref(), Some("deny-db"));
+        assert_eq!(find("deny-db-tcp", FindingKind::Redundant).unwrap().other.as_deref(), Some("deny-db"));
+        assert_eq!(find("deny-admin", FindingKind::Conflict).unwrap().other.as_deref(), Some("allow-web"));
+        assert!(find("allow-all", FindingKind::OverlyBroad).is_some());
+        assert!(find("allow-web", FindingKind::OverlyBroad).is_none());
+
+        // The first deciding rule wins; log rules and unmatched packets pass
+        assert_eq!(simulate_packet(&rules, "dmz", "db", 5432, &Protocol::TCP).unwrap().name, "deny-db");
+        assert_eq!(simulate_packet(&rules, "office", "web", 443, &Protocol::TCP).unwrap().name, "allow-web");
+        assert_eq!(simulate_packet(&rules, "office", "web", 8000, &Protocol::TCP).unwrap().name, "deny-admin");
+        assert!(simulate_packet(&rules[..6], "office", "web", 22, &Protocol::TCP).is_none());
+    }
+}
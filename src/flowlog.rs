@@ -0,0 +1,224 @@
+//! Flow Logs - NetFlow-Style Records of the Traffic Crossing Segments
+//!
+//! This module implements the flow logs Networking captures every step:
+//! - A record per traffic flow and direction: source and destination address
+//!   and port, protocol, packets, bytes, when it was seen and whether the
+//!   firewall accepted or rejected it. Requests go to the service's port
+//!   and responses, which carry most of the bytes, come back
+//! - Addresses of the devices in a flow's segments, or hosts of their CIDRs;
+//!   customer traffic entering at a public-facing segment comes from client
+//!   addresses on the internet
+//! - Export as JSON lines in a NetFlow-like format, appended to a file
+//! - Observations of the addresses for InfoSec's threat hunting, which
+//!   correlates them with the indicator feeds
+//!
+//! The orchestrator writes each step's records to the configured file and
+//! hands the day's records to threat hunting and DLP monitoring.
+
+use crate::departments::networking::{FirewallAction, NetworkSegment, NetworkTopology, Protocol, SecurityLevel};
+use crate::firewall;
+use crate::ipam;
+use crate::threatintel::{IndicatorKind, Observation};
+use crate::traffic::Flow;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+
+/// Port services answer on
+const SERVICE_PORT: u16 = 443;
+
+/// Share of a flow's bytes in its responses
+const RESPONSE_SHARE: f64 = 0.8;
+
+/// Average packet size
+const PACKET_BYTES: u64 = 1500;
+
+/// Source named in observations taken from flow records
+pub const SOURCE: &str = "flow log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FlowAction {
+    Accept,
+    Reject,
+}
+
+/// One direction of a flow over one step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowRecord {
+    pub first_switched: chrono::DateTime<chrono::Utc>,
+    pub last_switched: chrono::DateTime<chrono::Utc>,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub src_port: u16,
+    pub dst_port: u16,
+    /// IANA protocol number
+    pub protocol: u8,
+    pub packets: u64,
+    pub bytes: u64,
+    pub action: FlowAction,
+    pub src_segment: String,
+    pub dst_segment: String,
+    pub service: String,
+}
+
+impl FlowRecord {
+    /// Both addresses as seen from the other end
+    pub fn observations(&self) -> [Observation; 2] {
+        let observation = |value: IpAddr, host: IpAddr| Observation {
+            kind: IndicatorKind::IpAddress,
+            value: value.to_string(),
+            host: host.to_string(),
+            source: SOURCE.to_string(),
+            at: self.first_switched,
+        };
+        [observation(self.src_addr, self.dst_addr), observation(self.dst_addr, self.src_addr)]
+    }
+}
+
+fn protocol_number(protocol: &Protocol) -> u8 {
+    match protocol {
+        Protocol::TCP => 6,
+        Protocol::UDP => 17,
+        Protocol::ICMP => 1,
+        Protocol::Any => 0,
+    }
+}
+
+/// A device of the segment, or a host of its CIDR when it has none
+fn address(segment: &NetworkSegment, rng: &mut impl Rng) -> IpAddr {
+    if !segment.devices.is_empty() {
+        return segment.devices[rng.gen_range(0..segment.devices.len())].ip_address;
+    }
+    let Ok(network) = ipam::parse_cidr(&segment.cidr) else {
+        return IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    };
+    network.hosts().nth(rng.gen_range(0..16)).unwrap_or_else(|| network.network())
+}
+
+/// A client somewhere on the internet
+fn client_address(rng: &mut impl Rng) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(rng.gen_range(11..=126), rng.gen(), rng.gen(), rng.gen_range(1..=254)))
+}
+
+/// Records of the flows between `start` and `end`, each decided by the
+/// firewall rules of the topology
+pub fn capture(flows: &[Flow], topology: &NetworkTopology, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<FlowRecord> {
+    let seconds = (end - start).num_seconds().max(0) as u64;
+    let segment = |name: &str| topology.segments.values().find(|segment| segment.name == name);
+    let mut records = Vec::new();
+    for flow in flows {
+        let (Some(source), Some(destination)) = (segment(&flow.source), segment(&flow.destination)) else {
+            continue;
+        };
+        let client = if source.security_level <= SecurityLevel::DMZ { client_address(rng) } else { address(source, rng) };
+        let server = address(destination, rng);
+        let src_port = rng.gen_range(49152..=u16::MAX);
+        let action = match firewall::simulate_packet(&topology.firewall_rules, &flow.source, &flow.destination, SERVICE_PORT, &Protocol::TCP) {
+            Some(rule) if rule.action == FirewallAction::Deny => FlowAction::Reject,
+            _ => FlowAction::Accept,
+        };
+        let bytes = flow.bps.saturating_mul(seconds) / 8;
+        let response_bytes = (bytes as f64 * RESPONSE_SHARE) as u64;
+        let record = |src_addr, dst_addr, src_port, dst_port, src_segment: &str, dst_segment: &str, bytes: u64| FlowRecord {
+            first_switched: start,
+            last_switched: end,
+            src_addr,
+            dst_addr,
+            src_port,
+            dst_port,
+            protocol: protocol_number(&Protocol::TCP),
+            packets: bytes.div_ceil(PACKET_BYTES),
+            bytes,
+            action,
+            src_segment: src_segment.to_string(),
+            dst_segment: dst_segment.to_string(),
+            service: flow.service.clone(),
+        };
+        records.push(record(client, server, src_port, SERVICE_PORT, &flow.source, &flow.destination, bytes - response_bytes));
+        // A rejected request gets no response
+        if action == FlowAction::Accept {
+            records.push(record(server, client, SERVICE_PORT, src_port, &flow.destination, &flow.source, response_bytes));
+        }
+    }
+    records
+}
+
+/// Appends flow records to a JSON lines file
+#[derive(Debug)]
+pub struct FlowLogWriter {
+    path: PathBuf,
+    file: std::io::BufWriter<std::fs::File>,
+    written: u64,
+}
+
+impl FlowLogWriter {
+    pub fn open(path: &Path) -> Result<Self, FlowLogError> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| FlowLogError::Io(dir.to_path_buf(), e))?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| FlowLogError::Io(path.to_path_buf(), e))?;
+        Ok(Self { path: path.to_path_buf(), file: std::io::BufWriter::new(file), written: 0 })
+    }
+
+    /// Append the records and push them to disk
+    pub fn write(&mut self, records: &[FlowRecord]) -> Result<(), FlowLogError> {
+        for record in records {
+            let line = serde_json::to_string(record).expect("flow records always serialize");
+            writeln!(self.file, "{}", line).map_err(|e| FlowLogError::Io(self.path.clone(), e))?;
+        }
+        self.written += records.len() as u64;
+        self.file.flush().map_err(|e| FlowLogError::Io(self.path.clone(), e))
+    }
+
+    /// Records written since the file was opened
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlowLogError {
+    #[error("Flow log I/O error on {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::networking::{FirewallRuleConfig, NetworkingAgent, PortRange, SegmentConfig};
+    use rand::SeedableRng;
+
+    #[tokio::test]
+    async fn test_denied_flows_are_rejected_without_a_response() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        for (name, cidr, security_level) in [("dmz", "10.0.1.0/24", SecurityLevel::DMZ), ("office", "10.0.2.0/24", SecurityLevel::Internal), ("servers", "10.1.0.0/16", SecurityLevel::Internal)] {
+            agent.configure_segment(SegmentConfig { name: name.to_string(), cidr: cidr.to_string(), security_level }).await.unwrap();
+        }
+        agent.add_firewall_rule(FirewallRuleConfig {
+            name: "deny-office".to_string(),
+            source_segment: "office".to_string(),
+            destination_segment: "servers".to_string(),
+            port_range: PortRange { start: 0, end: u16::MAX },
+            protocol: Protocol::Any,
+            action: FirewallAction::Deny,
+        }).await.unwrap();
+        let flow = |source: &str| Flow { service: "api".to_string(), source: source.to_string(), destination: "servers".to_string(), bps: 8_000 };
+
+        let start = chrono::Utc::now();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let records = capture(&[flow("dmz"), flow("office")], &agent.network_topology, start, start + chrono::Duration::seconds(100), &mut rng);
+        assert_eq!(records.len(), 3);
+        assert_eq!((records[0].bytes, records[1].bytes), (20_000, 80_000));
+        assert_eq!((records[1].src_port, records[1].dst_port), (SERVICE_PORT, records[0].src_port));
+        assert!(!records[0].src_addr.to_string().starts_with("10."));
+        assert_eq!((records[2].src_segment.as_str(), records[2].action), ("office", FlowAction::Reject));
+        assert_eq!(records[2].observations()[0].value, records[2].src_addr.to_string());
+
+        let line = serde_json::to_value(&records[2]).unwrap();
+        assert_eq!((line["action"].as_str(), line["protocol"].as_u64(), line["packets"].as_u64()), (Some("REJECT"), Some(6), Some(14)));
+    }
+}
@@ -0,0 +1,251 @@
+//! Incident Forensics - Post-Mortems of Security Incidents
+//!
+//! This module implements what InfoSec writes up once a security incident
+//! is resolved:
+//! - A timeline from the message journal: the messages that reported the
+//!   incident or refer to it, and those mentioning its affected systems in
+//!   the day before it was opened, between its opening and resolution
+//! - A root cause read from what the incident says happened: phishing,
+//!   lateral movement, data leaving the company, malware or threat activity
+//! - Action items for the departments that can keep it from happening
+//!   again, each due a set number of days after the resolution
+//!
+//! The orchestrator resolves InfoSec's incidents, files each post-mortem in
+//! the knowledge base and hands it to InfoSec, whose next compliance audit
+//! references it.
+
+use crate::communication::Message;
+use crate::departments::infosec::{SecurityIncident, Severity};
+use crate::timeline::{self, Timeline, TimelineEntry, TimelineEntryKind};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long before an incident was opened messages about its systems count
+const LOOKBACK_HOURS: i64 = 24;
+
+/// What went wrong, as read from an incident's title and description
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RootCause {
+    PhishedCredentials,
+    LateralMovement,
+    DataExfiltration,
+    Malware,
+    ThreatActivity,
+    Undetermined,
+}
+
+impl RootCause {
+    /// The first cause whose words the text mentions
+    pub fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| text.contains(word));
+        if mentions(&["phishing", "credential", "password"]) {
+            RootCause::PhishedCredentials
+        } else if mentions(&["lateral", "privilege"]) {
+            RootCause::LateralMovement
+        } else if mentions(&["exfiltrat", "export", "data breach", "exposure"]) {
+            RootCause::DataExfiltration
+        } else if mentions(&["malware", "ransomware", "trojan"]) {
+            RootCause::Malware
+        } else if mentions(&["suspicious", "indicator", "advisory", "threat"]) {
+            RootCause::ThreatActivity
+        } else {
+            RootCause::Undetermined
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RootCause::PhishedCredentials => "An employee's credentials were phished",
+            RootCause::LateralMovement => "An attacker moved laterally between network segments",
+            RootCause::DataExfiltration => "Data left the company without being stopped",
+            RootCause::Malware => "Malware ran on a company host",
+            RootCause::ThreatActivity => "Known threat activity reached company hosts",
+            RootCause::Undetermined => "Undetermined; the investigation found no single cause",
+        }
+    }
+
+    /// What each department should do about it
+    fn actions(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            RootCause::PhishedCredentials => &[
+                ("InfoSec", "Book security-awareness training for the phished employees"),
+                ("InfoSec", "Enforce MFA on every account"),
+            ],
+            RootCause::LateralMovement => &[
+                ("Networking", "Add firewall deny rules between network segments"),
+                ("DevOps", "Rotate credentials on the affected servers"),
+            ],
+            RootCause::DataExfiltration => &[
+                ("Networking", "Log egress traffic at the firewall"),
+                ("InfoSec", "Review data store classifications and DLP coverage"),
+            ],
+            RootCause::Malware => &[
+                ("DevOps", "Rebuild the affected hosts from clean images"),
+                ("InfoSec", "Deploy endpoint protection to every host"),
+            ],
+            RootCause::ThreatActivity => &[
+                ("Networking", "Block the threat's indicators at the firewall"),
+            ],
+            RootCause::Undetermined => &[
+                ("InfoSec", "Extend monitoring to the affected systems"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub description: String,
+    /// Department that owns it
+    pub owner: String,
+    pub due_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Structured write-up of a resolved security incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostMortem {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub title: String,
+    pub severity: Severity,
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+    pub resolved_at: chrono::DateTime<chrono::Utc>,
+    pub root_cause: RootCause,
+    pub affected_systems: Vec<String>,
+    pub timeline: Timeline,
+    pub action_items: Vec<ActionItem>,
+    /// Knowledge base article it was filed as
+    #[serde(default)]
+    pub article: Option<Uuid>,
+    /// Whether a compliance audit has referenced it yet
+    #[serde(default)]
+    pub audited: bool,
+}
+
+impl PostMortem {
+    /// Render as plain text for the knowledge base
+    pub fn render(&self) -> String {
+        let hours = (self.resolved_at - self.opened_at).num_minutes() as f64 / 60.0;
+        let mut output = format!("{:?} security incident resolved after {:.1}h.\nRoot cause: {}.\nAffected systems: {}.\n\n",
+                                 self.severity, hours, self.root_cause.description(), self.affected_systems.join(", "));
+        output.push_str(&self.timeline.render());
+        output.push_str("\nAction items:\n");
+        for item in &self.action_items {
+            output.push_str(&format!("  - [{}] {} (due {})\n", item.owner, item.description, item.due_at.format("%Y-%m-%d")));
+        }
+        output
+    }
+}
+
+/// Known systems an incident names, leaving out placeholders
+fn known_systems(incident: &SecurityIncident) -> Vec<String> {
+    incident.affected_systems.iter().filter(|system| !system.is_empty() && *system != "unknown").cloned().collect()
+}
+
+/// Whether a journal message belongs on an incident's timeline
+fn is_related(incident: &SecurityIncident, systems: &[String], message: &Message, resolved_at: chrono::DateTime<chrono::Utc>) -> bool {
+    if incident.source_messages.contains(&message.id) {
+        return true;
+    }
+    if message.metadata.get(timeline::INCIDENT_KEY).is_some_and(|id| *id == incident.id.to_string()) {
+        return true;
+    }
+    let in_window = message.timestamp >= incident.created_at - chrono::Duration::hours(LOOKBACK_HOURS) && message.timestamp <= resolved_at;
+    in_window && systems.iter().any(|system| message.content.contains(system.as_str()))
+}
+
+/// Investigate a resolved incident: rebuild its timeline from the journal,
+/// name the root cause and assign action items due `action_item_days` out
+pub fn investigate(incident: &SecurityIncident, messages: &[Message], action_item_days: u32, resolved_at: chrono::DateTime<chrono::Utc>) -> PostMortem {
+    let systems = known_systems(incident);
+    let root_cause = RootCause::from_text(&format!("{} {}", incident.title, incident.description));
+
+    let mut timeline = Timeline::new(incident.id, incident.title.clone());
+    timeline.entries.push(TimelineEntry {
+        at: incident.created_at,
+        kind: TimelineEntryKind::StatusChange,
+        actor: incident.assigned_to,
+        summary: format!("Status → Open: {}", incident.description),
+        source_id: incident.id,
+    });
+    for message in messages.iter().filter(|message| is_related(incident, &systems, message, resolved_at)) {
+        timeline.push_message(message);
+    }
+    timeline.entries.push(TimelineEntry {
+        at: resolved_at,
+        kind: TimelineEntryKind::StatusChange,
+        actor: incident.assigned_to,
+        summary: format!("Status → Resolved: {}", root_cause.description()),
+        source_id: incident.id,
+    });
+
+    let due_at = resolved_at + chrono::Duration::days(i64::from(action_item_days));
+    PostMortem {
+        id: Uuid::new_v4(),
+        incident_id: incident.id,
+        title: incident.title.clone(),
+        severity: incident.severity.clone(),
+        opened_at: incident.created_at,
+        resolved_at,
+        root_cause,
+        affected_systems: systems,
+        timeline: timeline.finish(),
+        action_items: root_cause.actions().iter()
+            .map(|(owner, description)| ActionItem { description: description.to_string(), owner: owner.to_string(), due_at })
+            .collect(),
+        article: None,
+        audited: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::MessagePriority;
+    use crate::testsupport::test_message;
+    use crate::departments::infosec::IncidentStatus;
+
+    fn message(content: &str, at: chrono::DateTime<chrono::Utc>) -> Message {
+        Message {
+            content: content.to_string(),
+            priority: MessagePriority::High,
+            timestamp: at,
+            ..test_message(Uuid::new_v4(), Uuid::new_v4(), "incident_report")
+        }
+    }
+
+    #[test]
+    fn test_post_mortem_rebuilds_timeline_and_assigns_actions() {
+        let opened = chrono::Utc::now();
+        let report = message("Credential phishing email to Alice (red team exercise)", opened);
+        let earlier = message("Unusual logins on web-1", opened - chrono::Duration::hours(2));
+        let stale = message("web-1 patched", opened - chrono::Duration::days(3));
+        let unrelated = message("Deploy of api to staging", opened + chrono::Duration::hours(1));
+        let incident = SecurityIncident {
+            id: Uuid::new_v4(),
+            title: "Red team phishing detected".to_string(),
+            description: report.content.clone(),
+            severity: Severity::High,
+            status: IncidentStatus::Resolved,
+            assigned_to: None,
+            created_at: opened,
+            updated_at: opened,
+            resolution_steps: vec![],
+            affected_systems: vec!["web-1".to_string(), "unknown".to_string()],
+            source_messages: vec![report.id],
+            resolved_at: None,
+        };
+
+        let resolved_at = opened + chrono::Duration::hours(4);
+        let post_mortem = investigate(&incident, &[stale, earlier.clone(), report.clone(), unrelated], 14, resolved_at);
+        assert_eq!(post_mortem.root_cause, RootCause::PhishedCredentials);
+        assert_eq!(post_mortem.affected_systems, vec!["web-1".to_string()]);
+        let sources: Vec<Uuid> = post_mortem.timeline.entries.iter().map(|entry| entry.source_id).collect();
+        assert_eq!(sources, vec![earlier.id, incident.id, report.id, incident.id]);
+        assert_eq!(post_mortem.action_items.len(), 2);
+        assert!(post_mortem.action_items.iter().all(|item| item.due_at == resolved_at + chrono::Duration::days(14)));
+        assert!(post_mortem.render().contains("Enforce MFA on every account"));
+    }
+}
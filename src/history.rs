@@ -0,0 +1,325 @@
+//! History Store - SQLite Persistence of Tickets, Incidents & Projects
+//!
+//! This module implements an optional SQLite database (built with the
+//! `sqlite` feature) that keeps the company's work history beyond a run:
+//! - Support tickets and incidents, Ops and InfoSec alike, written through
+//!   after every step they change in
+//! - Project tasks, which the task graph drops once a project is delivered
+//! - The queries behind `aivertco report --history`: counts by status and
+//!   the incidents still open
+//!
+//! Agents keep working from their in-memory maps and snapshots remain how a
+//! run resumes; the store is what survives restarts, snapshot rotation and
+//! pruning, and what can be queried with SQL.
+
+use crate::departments::infosec::SecurityIncident;
+use crate::departments::ops::{Incident, SupportTicket};
+use crate::taskgraph::ProjectTask;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Tables, created on first use
+const SCHEMA: [&str; 3] = [
+    "CREATE TABLE IF NOT EXISTS tickets (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        priority TEXT NOT NULL,
+        status TEXT NOT NULL,
+        customer_id TEXT,
+        created_at TEXT NOT NULL,
+        resolved_at TEXT,
+        data TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS incidents (
+        id TEXT PRIMARY KEY,
+        department TEXT NOT NULL,
+        title TEXT NOT NULL,
+        severity TEXT NOT NULL,
+        status TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        resolved_at TEXT,
+        data TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS project_tasks (
+        id TEXT PRIMARY KEY,
+        project_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        department TEXT NOT NULL,
+        state TEXT NOT NULL,
+        started_at TEXT,
+        completed_at TEXT,
+        data TEXT NOT NULL
+    )",
+];
+
+/// Incident statuses that no longer need attention, in either department
+const FINISHED_INCIDENT_STATUSES: &str = "('Resolved', 'PostMortem', 'Closed')";
+
+/// Entity to write to the store
+#[derive(Debug, Clone, Copy)]
+pub enum HistoryRecord<'a> {
+    Ticket(&'a SupportTicket),
+    Incident(&'a Incident),
+    SecurityIncident(&'a SecurityIncident),
+    ProjectTask(&'a ProjectTask),
+}
+
+impl HistoryRecord<'_> {
+    fn id(&self) -> Uuid {
+        match self {
+            HistoryRecord::Ticket(ticket) => ticket.id,
+            HistoryRecord::Incident(incident) => incident.id,
+            HistoryRecord::SecurityIncident(incident) => incident.id,
+            HistoryRecord::ProjectTask(task) => task.id,
+        }
+    }
+
+    fn to_json(self) -> Result<String, serde_json::Error> {
+        match self {
+            HistoryRecord::Ticket(ticket) => serde_json::to_string(ticket),
+            HistoryRecord::Incident(incident) => serde_json::to_string(incident),
+            HistoryRecord::SecurityIncident(incident) => serde_json::to_string(incident),
+            HistoryRecord::ProjectTask(task) => serde_json::to_string(task),
+        }
+    }
+}
+
+/// SQLite database of tickets, incidents and project tasks
+#[derive(Debug)]
+pub struct HistoryStore {
+    pool: SqlitePool,
+    /// Digest of each entity as last written, so unchanged ones are skipped
+    written: HashMap<Uuid, u64>,
+}
+
+impl HistoryStore {
+    /// Open the database, creating it and its tables if needed
+    pub async fn open(path: &Path) -> Result<Self, HistoryError> {
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await
+            .map_err(|e| HistoryError::Open(path.display().to_string(), e))?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self { pool, written: HashMap::new() })
+    }
+
+    /// Write the entities that changed since they were last written; returns how many were
+    pub async fn record<'a>(&mut self, records: impl IntoIterator<Item = HistoryRecord<'a>>) -> Result<usize, HistoryError> {
+        let mut changed = Vec::new();
+        for record in records {
+            let json = record.to_json()?;
+            let mut hasher = DefaultHasher::new();
+            json.hash(&mut hasher);
+            let digest = hasher.finish();
+            if self.written.get(&record.id()) != Some(&digest) {
+                changed.push((record, json, digest));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        let mut transaction = self.pool.begin().await?;
+        for (record, json, _) in &changed {
+            upsert(*record, json).execute(&mut *transaction).await?;
+        }
+        transaction.commit().await?;
+
+        for (record, _, digest) in &changed {
+            self.written.insert(record.id(), *digest);
+        }
+        Ok(changed.len())
+    }
+
+    /// Counts by status of everything created at or after `since`, and the incidents still open
+    pub async fn summary(&self, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<HistorySummary, HistoryError> {
+        let since = since.map_or_else(String::new, |since| timestamp(&since));
+        Ok(HistorySummary {
+            tickets: self.counts("SELECT status, COUNT(*) FROM tickets WHERE created_at >= ? GROUP BY status", &since).await?,
+            incidents: self.counts("SELECT department || ' ' || status, COUNT(*) FROM incidents WHERE created_at >= ? GROUP BY department, status", &since).await?,
+            project_tasks: self.counts("SELECT state, COUNT(*) FROM project_tasks WHERE COALESCE(started_at, '') >= ? GROUP BY state", &since).await?,
+            open_incidents: sqlx::query(&format!(
+                "SELECT department, severity, title, created_at FROM incidents \
+                 WHERE created_at >= ? AND status NOT IN {} ORDER BY created_at", FINISHED_INCIDENT_STATUSES))
+                .bind(&since)
+                .fetch_all(&self.pool).await?
+                .into_iter()
+                .map(|row| OpenIncident {
+                    department: row.get(0),
+                    severity: row.get(1),
+                    title: row.get(2),
+                    created_at: row.get(3),
+                })
+                .collect(),
+        })
+    }
+
+    async fn counts(&self, query: &str, since: &str) -> Result<BTreeMap<String, i64>, HistoryError> {
+        Ok(sqlx::query(query).bind(since).fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect())
+    }
+}
+
+fn upsert<'q>(record: HistoryRecord<'q>, json: &'q str) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match record {
+        HistoryRecord::Ticket(ticket) => sqlx::query(
+            "INSERT OR REPLACE INTO tickets (id, title, priority, status, customer_id, created_at, resolved_at, data) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(ticket.id.to_string())
+            .bind(&ticket.title)
+            .bind(format!("{:?}", ticket.priority))
+            .bind(format!("{:?}", ticket.status))
+            .bind(ticket.customer_id.as_deref())
+            .bind(timestamp(&ticket.created_at))
+            .bind(ticket.resolved_at.as_ref().map(timestamp))
+            .bind(json),
+        HistoryRecord::Incident(incident) => sqlx::query(
+            "INSERT OR REPLACE INTO incidents (id, department, title, severity, status, created_at, resolved_at, data) \
+             VALUES (?, 'Ops', ?, ?, ?, ?, ?, ?)")
+            .bind(incident.id.to_string())
+            .bind(&incident.title)
+            .bind(format!("{:?}", incident.severity))
+            .bind(format!("{:?}", incident.status))
+            .bind(timestamp(&incident.created_at))
+            .bind(incident.resolved_at.as_ref().map(timestamp))
+            .bind(json),
+        HistoryRecord::SecurityIncident(incident) => sqlx::query(
+            "INSERT OR REPLACE INTO incidents (id, department, title, severity, status, created_at, resolved_at, data) \
+             VALUES (?, 'InfoSec', ?, ?, ?, ?, NULL, ?)")
+            .bind(incident.id.to_string())
+            .bind(&incident.title)
+            .bind(format!("{:?}", incident.severity))
+            .bind(format!("{:?}", incident.status))
+            .bind(timestamp(&incident.created_at))
+            .bind(json),
+        HistoryRecord::ProjectTask(task) => sqlx::query(
+            "INSERT OR REPLACE INTO project_tasks (id, project_id, title, department, state, started_at, completed_at, data) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(task.id.to_string())
+            .bind(task.project_id.to_string())
+            .bind(&task.title)
+            .bind(&task.department)
+            .bind(format!("{:?}", task.state))
+            .bind(task.started_at.as_ref().map(timestamp))
+            .bind(task.completed_at.as_ref().map(timestamp))
+            .bind(json),
+    }
+}
+
+/// Fixed-width UTC timestamps, so text comparison orders them
+fn timestamp(at: &chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Result of a history query
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySummary {
+    pub tickets: BTreeMap<String, i64>,
+    /// Incidents by "department status", e.g. "Ops Open"
+    pub incidents: BTreeMap<String, i64>,
+    pub project_tasks: BTreeMap<String, i64>,
+    pub open_incidents: Vec<OpenIncident>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenIncident {
+    pub department: String,
+    pub severity: String,
+    pub title: String,
+    pub created_at: String,
+}
+
+impl HistorySummary {
+    /// Summary as printed by `aivertco report --history`
+    pub fn render(&self) -> String {
+        let mut out = String::from("🗄️ Company history\n");
+        for (heading, counts) in [("Tickets", &self.tickets), ("Incidents", &self.incidents), ("Project tasks", &self.project_tasks)] {
+            out.push_str(&format!("\n{}: {}\n", heading, counts.values().sum::<i64>()));
+            for (status, count) in counts {
+                out.push_str(&format!("   {}: {}\n", status, count));
+            }
+        }
+        if !self.open_incidents.is_empty() {
+            out.push_str("\nOpen incidents:\n");
+            for incident in &self.open_incidents {
+                out.push_str(&format!("   {} {} {} '{}'\n", incident.created_at, incident.department, incident.severity, incident.title));
+            }
+        }
+        out
+    }
+}
+
+/// History store errors
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("Failed to open history database {0}: {1}")]
+    Open(String, sqlx::Error),
+
+    #[error("History database query failed: {0}")]
+    Query(#[from] sqlx::Error),
+
+    #[error("Failed to serialize history record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::ops::{CustomerTier, Priority, TicketStatus, TicketWork};
+    use crate::taskgraph::TaskState;
+
+    fn ticket(title: &str, status: TicketStatus) -> SupportTicket {
+        let now = chrono::Utc::now();
+        SupportTicket {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: String::new(),
+            priority: Priority::Normal,
+            status,
+            customer_id: None,
+            customer_tier: CustomerTier::Standard,
+            assigned_to: None,
+            created_at: now,
+            updated_at: now,
+            resolved_at: None,
+            closed_at: None,
+            reopen_count: 0,
+            resolution: None,
+            tags: vec![],
+            breaches: vec![],
+            work: TicketWork::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_survives_reopening_and_skips_unchanged() {
+        let path = std::env::temp_dir().join(format!("aivertco-history-{}.db", Uuid::new_v4()));
+        let mut open = ticket("Cannot log in", TicketStatus::Open);
+        let closed = ticket("Slow dashboard", TicketStatus::Closed);
+        let mut task = ProjectTask::new(Uuid::new_v4(), "Build feature", "Engineering", 8, vec![]);
+        task.state = TaskState::Done;
+
+        let mut store = HistoryStore::open(&path).await.unwrap();
+        let records = [HistoryRecord::Ticket(&open), HistoryRecord::Ticket(&closed), HistoryRecord::ProjectTask(&task)];
+        assert_eq!(store.record(records).await.unwrap(), 3);
+        assert_eq!(store.record(records).await.unwrap(), 0);
+        open.status = TicketStatus::Resolved;
+        assert_eq!(store.record([HistoryRecord::Ticket(&open)]).await.unwrap(), 1);
+        drop(store);
+
+        let store = HistoryStore::open(&path).await.unwrap();
+        let summary = store.summary(None).await.unwrap();
+        assert_eq!(summary.tickets, BTreeMap::from([("Closed".to_string(), 1), ("Resolved".to_string(), 1)]));
+        assert_eq!(summary.project_tasks.get("Done"), Some(&1));
+        assert!(store.summary(Some(chrono::Utc::now() + chrono::Duration::days(1))).await.unwrap().tickets.is_empty());
+        std::fs::remove_file(path).ok();
+    }
+}
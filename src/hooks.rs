@@ -0,0 +1,1049 @@
+//! Step Hooks - Subsystems Run Every Step
+//!
+//! This module implements the subsystem work the orchestrator drives as
+//! simulated time passes:
+//! - Entity change fan-out: webhooks, issue sync, satisfaction, the status
+//!   page, customer email and paging
+//! - Intake breakers, KPI sampling and deadlock detection
+//! - Infrastructure, network and security routines run on agents' turns
+
+use super::*;
+
+impl CompanySimulation {
+    /// Run a sync round with the issue tracker when one is due
+    #[cfg(feature = "issue-sync")]
+    pub(crate) async fn sync_issues(&mut self) {
+        let now = self.scheduler.now();
+        let Some(sync) = self.issue_sync.as_mut().filter(|sync| sync.due(now)) else {
+            return;
+        };
+        let tracker = sync.tracker_name();
+
+        let tickets = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| ops.support_tickets.values().cloned().collect::<Vec<_>>()).unwrap_or_default()
+        }).await;
+        let mut tickets: Vec<departments::ops::SupportTicket> = match tickets {
+            Ok(tickets) => tickets.into_iter().flatten().collect(),
+            Err(e) => {
+                println!("⚠️ Issue sync skipped: {}", e);
+                return;
+            }
+        };
+        tickets.sort_by_key(|ticket| ticket.created_at);
+        let tickets: Vec<&departments::ops::SupportTicket> = tickets.iter().collect();
+
+        let report = match sync.sync(&tickets, now).await {
+            Ok(report) => report,
+            Err(e) => {
+                println!("⚠️ Issue sync with {} failed: {}", tracker, e);
+                return;
+            }
+        };
+        if report.created + report.updated > 0 {
+            println!("🔗 {}: {} issues created, {} updated", tracker, report.created, report.updated);
+        }
+        for (ticket_id, e) in &report.failures {
+            println!("⚠️ Could not push ticket {} to {}, retrying next round: {}", ticket_id, tracker, e);
+        }
+        for edit in report.edits {
+            self.apply_issue_edit(tracker, edit).await;
+        }
+    }
+
+    /// Apply a change a person made on the issue tracker to its ticket
+    #[cfg(feature = "issue-sync")]
+    async fn apply_issue_edit(&mut self, tracker: &str, edit: issuesync::RemoteEdit) {
+        let ticket_id = edit.ticket_id;
+        let Ok(Some(owner)) = self.agents.find(move |agent| agent.as_ops().is_some_and(|ops| ops.support_tickets.contains_key(&ticket_id))).await else {
+            return;
+        };
+
+        if let Some(title) = edit.title {
+            let renamed = title.clone();
+            let previous = self.agents.call(owner, move |agent| {
+                let ticket = agent.as_ops_mut()?.support_tickets.get_mut(&ticket_id)?;
+                ticket.updated_at = chrono::Utc::now();
+                Some(std::mem::replace(&mut ticket.title, title))
+            }).await;
+            if let Ok(Some(previous)) = previous {
+                println!("🔗 Ticket '{}' renamed to '{}' in {} issue {}", previous, renamed, tracker, edit.issue_key);
+            }
+        }
+
+        // State changes go through the agent's own ticket workflow
+        let (message_type, content) = match edit.state {
+            Some(issuesync::IssueState::Closed) => ("resolve_ticket", format!("Closed in {} issue {}", tracker, edit.issue_key)),
+            Some(issuesync::IssueState::Open) => ("reopen_ticket", format!("reopened in {} issue {}", tracker, edit.issue_key)),
+            None => return,
+        };
+        let message = Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: owner,
+            message_type: message_type.to_string(),
+            content,
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([("ticket_id".to_string(), ticket_id.to_string())]),
+        };
+        let failure = self.agents.deliver(vec![(owner, vec![message])]).await
+            .into_iter()
+            .find_map(|(_, _, status)| match status {
+                DeliveryStatus::Failed(e) => Some(e),
+                _ => None,
+            });
+        if let Some(e) = failure {
+            println!("⚠️ Could not apply {} edit to ticket {}: {}", tracker, ticket_id, e);
+        }
+    }
+
+    /// Detect ticket, incident and deployment changes, keep the status page
+    /// up to date, let customers react to them and post them to subscribers
+    pub(crate) async fn publish_entity_changes(&mut self) {
+        let now = self.scheduler.now();
+        let states = self.agents.call_all(|agent| {
+            let tickets: Vec<_> = agent.as_ops().into_iter().flat_map(|ops| ops.support_tickets.values().cloned()).collect();
+            let incidents: Vec<_> = agent.as_ops().into_iter().flat_map(|ops| ops.incidents.values().cloned()).collect();
+            let deployments: Vec<_> = agent.as_devops().into_iter().flat_map(|devops| devops.active_deployments.values().cloned()).collect();
+            (tickets, incidents, deployments)
+        }).await;
+        let states = match states {
+            Ok(states) => states,
+            Err(e) => {
+                println!("⚠️ Entity changes skipped: {}", e);
+                return;
+            }
+        };
+        let entities = states.iter().flat_map(|(tickets, incidents, deployments)| {
+            tickets.iter().map(TrackedEntity::Ticket)
+                .chain(incidents.iter().map(TrackedEntity::Incident))
+                .chain(deployments.iter().map(TrackedEntity::Deployment))
+        });
+        let changes = self.entity_tracker.observe(entities, now);
+        // Before satisfaction forgets the customers of resolved incidents
+        let incidents: Vec<departments::ops::Incident> = states.into_iter().flat_map(|(_, incidents, _)| incidents).collect();
+        for incident in &incidents {
+            self.threads.link(incident.id, &incident.correlation_ids);
+        }
+        self.update_status_page(&incidents).await;
+        self.update_satisfaction(&changes);
+        self.page_on_call(&changes).await;
+        self.email_customers(&changes).await;
+
+        let settings = &self.config.webhooks;
+        if settings.subscriptions.is_empty() {
+            return;
+        }
+        self.webhooks.enqueue(settings, &changes);
+        for failure in self.webhooks.dispatch(settings, now).await {
+            println!("🪝 Gave up on webhook {} for {} to {} after {} attempts: {}",
+                     failure.delivery.change.event.as_str(), failure.delivery.change.entity_id,
+                     failure.delivery.url, failure.delivery.attempts + 1, failure.error);
+        }
+    }
+
+    /// Move customers' satisfaction with their resolved tickets and incidents,
+    /// and pay a penalty for each one resolved past the customer's SLA
+    fn update_satisfaction(&mut self, changes: &[EntityChange]) {
+        let settings = &self.config.customers.satisfaction;
+        let mut violated: Vec<String> = Vec::new();
+        for change in changes {
+            match change.event {
+                webhooks::EntityEvent::TicketResolved => {
+                    let Ok(ticket) = serde_json::from_value::<departments::ops::SupportTicket>(change.entity.clone()) else {
+                        continue;
+                    };
+                    let customer = ticket.customer_id.as_ref().and_then(|id| self.catalog.customers.get_mut(id));
+                    // Time waiting on the customer does not count against the contract
+                    if let (Some(customer), Some(hours)) = (customer, ticket.resolution_hours()) {
+                        customer.ticket_resolved(hours, settings);
+                        if hours > customer.contract().ticket_resolution_hours as f64 {
+                            violated.push(customer.id.clone());
+                        }
+                    }
+                }
+                webhooks::EntityEvent::IncidentResolved => {
+                    let Ok(incident) = serde_json::from_value::<departments::ops::Incident>(change.entity.clone()) else {
+                        continue;
+                    };
+                    let hours = (incident.resolved_at.unwrap_or(change.occurred_at) - incident.created_at).num_minutes() as f64 / 60.0;
+                    for customer in self.catalog.customers.values_mut() {
+                        let mut affected = false;
+                        for correlation_id in &incident.correlation_ids {
+                            affected |= customer.incident_resolved(*correlation_id, hours, settings);
+                        }
+                        if affected && hours > customer.contract().incident_resolution_hours as f64 {
+                            violated.push(customer.id.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let now = self.scheduler.now();
+        for customer_id in violated {
+            let penalty = self.catalog.monthly_cost(&customer_id).unwrap_or(0.0) * self.config.economy.sla_penalty_percent / 100.0;
+            if penalty > 0.0 {
+                println!("💸 SLA penalty of ${:.2} owed to {}", penalty, customer_id);
+            }
+            self.economy.book(Entry::SlaPenalty, penalty, now);
+        }
+    }
+
+    /// Post status page updates for the incidents Ops moved on, send their
+    /// notices to the affected customers and book the SLA credits owed
+    async fn update_status_page(&mut self, incidents: &[departments::ops::Incident]) {
+        if !self.config.status_page.enabled {
+            return;
+        }
+        let now = self.scheduler.now();
+        let posted = self.status_page.observe(incidents, &self.catalog, &self.config.status_page, now);
+        for (title, update) in &posted.updates {
+            println!("📣 Status page, {}: {}", update.phase.name(), title);
+        }
+        for credit in &posted.credits {
+            println!("💸 SLA credit of ${:.2} ({:.0}%) owed to {} at {:.2}% uptime for {}",
+                     credit.amount, credit.percent, credit.customer_id, credit.uptime, credit.month);
+            self.economy.book(Entry::SlaPenalty, credit.amount, now);
+        }
+        if let Some(email) = self.email.as_mut() {
+            for (failed, e) in email.notify(&posted.notices).await {
+                println!("📧 Could not email '{}' to {}: {}", failed.subject, failed.to, e);
+            }
+        }
+    }
+
+    /// Email customers about their tickets and about newly live campaigns
+    async fn email_customers(&mut self, changes: &[EntityChange]) {
+        let Some(email) = self.email.as_mut() else {
+            return;
+        };
+        let campaigns = self.agents.call_all(|agent| {
+            agent.as_marketing().map(|marketing| marketing.campaigns.values().cloned().collect::<Vec<_>>()).unwrap_or_default()
+        }).await.unwrap_or_default();
+        let campaigns: Vec<&departments::marketing::Campaign> = campaigns.iter().flatten().collect();
+        let customers: Vec<&catalog::CustomerAccount> = self.catalog.customers.values().collect();
+
+        for (failed, e) in email.run(changes, &campaigns, &customers, self.scheduler.now()).await {
+            println!("📧 Could not email '{}' to {}: {}", failed.subject, failed.to, e);
+        }
+    }
+
+    /// Page on-call responders for severe incidents and pass on their acknowledgements
+    async fn page_on_call(&mut self, changes: &[EntityChange]) {
+        let now = self.scheduler.now();
+        let Some(pager) = self.pager.as_mut() else {
+            return;
+        };
+        let provider = pager.provider_name();
+        let round = pager.run(changes, now).await;
+
+        for e in &round.errors {
+            println!("⚠️ Paging via {} failed: {}", provider, e);
+        }
+        for page in round.triggered.iter().chain(&round.escalated) {
+            let escalation = if page.escalation_level > 0 { format!(" (escalation {})", page.escalation_level) } else { String::new() };
+            println!("📟 Paged {} for {:?} incident '{}' via {}{}",
+                     self.actor_name(page.responder), page.severity, page.summary, provider, escalation);
+            let content = format!("Page: {:?} incident '{}'", page.severity, page.summary);
+            self.pending_messages.push(page_message(page, SystemActor::Orchestrator.id(), page.responder, "page", content));
+        }
+        for page in &round.acknowledged {
+            let by = page.acknowledged_by.clone().unwrap_or_else(|| self.actor_name(page.responder));
+            let minutes = (now - page.triggered_at).num_minutes();
+            println!("📟 {} acknowledged the page for '{}' after {} min", by, page.summary, minutes);
+
+            // The incident's owner hears that someone is on it
+            let incident_id = page.incident_id;
+            let owner = self.agents.find(move |agent| agent.as_ops().is_some_and(|ops| ops.incidents.contains_key(&incident_id))).await;
+            if let Some(owner) = owner.ok().flatten().filter(|owner| *owner != page.responder) {
+                let content = format!("{} acknowledged the page for '{}' after {} min", by, page.summary, minutes);
+                self.pending_messages.push(page_message(page, page.responder, owner, "page_acknowledged", content));
+            }
+        }
+        for page in &round.unanswered {
+            println!("📵 Nobody acknowledged the page for '{}' after {} escalations", page.summary, page.escalation_level);
+        }
+    }
+
+    /// Re-evaluate department breakers and release intake they no longer hold back
+    pub(crate) async fn update_intake_breakers(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let loads = self.agents.call_all(|agent| {
+            (agent.get_agent().department.as_str().to_string(), agent.backlog(), agent.open_sev1_incidents())
+        }).await?;
+        let mut backlogs: HashMap<String, usize> = HashMap::new();
+        let mut open_sev1 = 0;
+        for (department, backlog, sev1) in loads {
+            *backlogs.entry(department).or_insert(0) += backlog;
+            open_sev1 += sev1;
+        }
+
+        let now = self.scheduler.now();
+        for transition in self.intake.evaluate(&self.config.intake, &backlogs, open_sev1, now) {
+            match (transition.state, transition.reason) {
+                (BreakerState::Open, Some(TripReason::Backlog(backlog))) => {
+                    println!("🚧 {} intake paused: backlog of {} open items", transition.department, backlog);
+                }
+                (BreakerState::Open, Some(TripReason::Sev1Storm(count))) => {
+                    println!("🚧 {} intake paused: {} Sev1 incidents open", transition.department, count);
+                }
+                (BreakerState::Open, None) => {}
+                (BreakerState::Closed, _) => println!("✅ {} intake resumed", transition.department),
+            }
+        }
+
+        for message in self.intake.release() {
+            self.router.enqueue(message);
+        }
+        Ok(())
+    }
+
+    /// Sample company KPIs at the current simulated time
+    pub(crate) async fn record_kpis(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let now = self.scheduler.now();
+        let ops_samples = self.agents.call_all(|agent| {
+            let Some(ops) = agent.as_ops() else {
+                return (Vec::new(), 0, 0);
+            };
+            let resolution_hours: Vec<f64> = ops.incidents.values()
+                .filter(|incident| incident.duplicate_of.is_none())
+                .filter_map(|incident| incident.resolved_at.map(|resolved| (resolved - incident.created_at).num_minutes() as f64 / 60.0))
+                .collect();
+            let open_tickets = ops.support_tickets.values()
+                .filter(|ticket| matches!(ticket.status, departments::ops::TicketStatus::Open | departments::ops::TicketStatus::InProgress))
+                .count();
+            (resolution_hours, ops.active_incidents().count(), open_tickets)
+        }).await?;
+
+        let resolution_hours: Vec<f64> = ops_samples.iter().flat_map(|(hours, _, _)| hours.iter().copied()).collect();
+        let open_incidents = ops_samples.iter().map(|(_, incidents, _)| incidents).sum::<usize>();
+        let open_tickets = ops_samples.iter().map(|(_, _, tickets)| tickets).sum::<usize>();
+
+        self.kpi_history.record(Kpi::OpenIncidents, now, open_incidents as f64);
+        self.kpi_history.record(Kpi::OpenTickets, now, open_tickets as f64);
+        self.kpi_history.record(Kpi::MonthlyRevenue, now, self.catalog.monthly_revenue());
+
+        // Rates and means cover only what happened since the previous sample
+        let deliveries = &self.router.stats;
+        self.kpi_history.record_counters(now, KpiCounters {
+            resolved_incidents: resolution_hours.len() as u64,
+            resolution_hours: resolution_hours.iter().sum(),
+            deliveries: deliveries.delivered + deliveries.failed + deliveries.undeliverable,
+            failed_deliveries: deliveries.failed + deliveries.undeliverable,
+            observed_steps: self.intake.observed_steps,
+            overloaded_steps: self.intake.overloaded_steps,
+        });
+
+        if let Some(minutes) = self.ticket_queue.mean_wait_minutes() {
+            self.kpi_history.record(Kpi::TicketQueueWait, now, minutes);
+        }
+
+        let mitigation_minutes = self.agents.call_all(|agent| agent.as_networking().and_then(|networking| networking.ddos.mean_time_to_mitigate())).await?;
+        if let Some(minutes) = mitigation_minutes.into_iter().flatten().next() {
+            self.kpi_history.record(Kpi::TimeToMitigate, now, minutes);
+        }
+        Ok(())
+    }
+
+    /// Drop waits nobody answered in time, then break wait-for cycles and
+    /// escalate each one to management
+    pub(crate) fn detect_deadlocks(&mut self) {
+        let max_wait = chrono::Duration::minutes(self.config.deadlocks.max_wait_minutes);
+        self.wait_graph.expire(max_wait, self.scheduler.now());
+        for deadlock in self.wait_graph.break_deadlocks(self.scheduler.now()) {
+            let chain: Vec<String> = deadlock.cycle.iter()
+                .chain(deadlock.cycle.first())
+                .map(|&id| self.actor_label(id))
+                .collect();
+            let chain = chain.join(" → ");
+            println!("🔁 Deadlock broken: {}", chain);
+
+            let waiter = deadlock.broken.waiter;
+            let escalate_to = self.agents.info(&waiter)
+                .and_then(|agent| agent.manager_id)
+                .or_else(|| self.agents.info(&deadlock.broken.holder).and_then(|agent| agent.manager_id))
+                .unwrap_or(waiter);
+
+            self.pending_messages.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: SystemActor::Orchestrator.id(),
+                to_agent: escalate_to,
+                message_type: "deadlock_escalation".to_string(),
+                content: format!("Deadlock between {}; dropped {}'s wait on {} ({})",
+                               chain, self.actor_name(waiter), self.actor_name(deadlock.broken.holder), deadlock.broken.message_type),
+                priority: MessagePriority::High,
+                timestamp: chrono::Utc::now(),
+                metadata: HashMap::from([
+                    ("deadlock_id".to_string(), deadlock.id.to_string()),
+                    ("released_request_id".to_string(), deadlock.broken.request_id.to_string()),
+                ]),
+            });
+        }
+    }
+
+    /// Run a DevOps agent's deployments forward to the simulated now
+    pub(crate) async fn run_deployments(&mut self, agent_id: Uuid) {
+        let now = self.scheduler.now();
+        let mut rng = StdRng::seed_from_u64(self.rng.gen());
+        let finished = self.agents.call(agent_id, move |agent| {
+            agent.as_devops_mut().map(|devops| devops.advance_deployments(now, &mut rng)).unwrap_or_default()
+        }).await;
+        if let Err(e) = finished {
+            println!("⚠️ Deployments of {} did not run: {}", self.actor_name(agent_id), e);
+        }
+    }
+
+    /// Apply the next change of a DevOps agent's infrastructure plan
+    pub(crate) async fn apply_infrastructure(&mut self, agent_id: Uuid) {
+        let now = self.scheduler.now();
+        let applied = self.agents.call(agent_id, move |agent| {
+            agent.as_devops_mut().and_then(|devops| devops.apply_infrastructure_step(now))
+        }).await;
+        if let Ok(Some(change)) = applied {
+            println!("🏗️ {} applied {}", self.actor_name(agent_id), change);
+        }
+    }
+
+    /// Have every DevOps agent scale its server fleets, at the server cost
+    /// of the economy and within its share of the DevOps budget
+    pub(crate) fn request_scaling(&mut self) {
+        let devops = self.department_agents(Department::DevOps);
+        // No budget before the first executive review
+        let budget = self.executive.allocations.get(Department::DevOps.as_str()).map(|allocation| allocation.budget / devops.len().max(1) as f64);
+        for devops_id in devops {
+            let policy = ScalingPolicy {
+                settings: self.config.autoscaling.clone(),
+                server_monthly_cost: self.config.economy.server_monthly_cost,
+                monthly_budget: budget,
+            };
+            let content = match budget {
+                Some(budget) => format!("Daily scaling check within ${:.0}/month", budget),
+                None => "Daily scaling check".to_string(),
+            };
+            let request = MessagePayload::ScaleRequest { policy };
+            self.pending_messages.push(request.into_message(SystemActor::Scheduler.id(), devops_id, content, MessagePriority::Normal));
+        }
+    }
+
+    /// Have every DevOps agent take the nightly backups, prune expired ones
+    /// and run a restore drill when one is due
+    pub(crate) fn request_backups(&mut self) {
+        let now = self.scheduler.now();
+        for devops_id in self.department_agents(Department::DevOps) {
+            let request = MessagePayload::BackupRequest { at: now, settings: self.config.backups.clone() };
+            self.pending_messages.push(request.into_message(SystemActor::Scheduler.id(), devops_id, "Nightly backup".to_string(), MessagePriority::Normal));
+        }
+    }
+
+    /// Release the day's patches to every DevOps agent, with security fixes
+    /// for what InfoSec's scans found, and tell InfoSec how many critical
+    /// fixes are still unpatched
+    pub(crate) async fn request_patching(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config.patching.enabled {
+            return Ok(());
+        }
+        let found = self.agents.call_all(|agent| {
+            agent.as_infosec().map(|infosec| infosec.open_vulnerabilities.iter()
+                .map(|vulnerability| Advisory {
+                    vulnerability: vulnerability.id.clone(),
+                    title: vulnerability.title.clone(),
+                    severity: vulnerability.severity.clone(),
+                })
+                .collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let mut advisories: Vec<Advisory> = Vec::new();
+        for advisory in found.into_iter().flatten() {
+            if !advisories.iter().any(|known| known.vulnerability == advisory.vulnerability) {
+                advisories.push(advisory);
+            }
+        }
+        let unpatched = self.agents.call_all(|agent| agent.as_devops().map(|devops| devops.unpatched_critical()).unwrap_or_default()).await?;
+        let unpatched_critical = unpatched.into_iter().flatten().collect::<BTreeSet<_>>().len() as u32;
+
+        let now = self.scheduler.now();
+        for devops_id in self.department_agents(Department::DevOps) {
+            let request = MessagePayload::PatchRequest { at: now, settings: self.config.patching.clone(), advisories: advisories.clone() };
+            let content = format!("Nightly patches, {} security advisories", advisories.len());
+            self.pending_messages.push(request.into_message(SystemActor::Scheduler.id(), devops_id, content, MessagePriority::Normal));
+        }
+        for infosec_id in self.department_agents(Department::InfoSec) {
+            let status = MessagePayload::PatchStatus { unpatched_critical };
+            let content = format!("{} critical fixes unpatched", unpatched_critical);
+            self.pending_messages.push(status.into_message(SystemActor::Scheduler.id(), infosec_id, content, MessagePriority::Normal));
+        }
+        Ok(())
+    }
+
+    /// Run a red team campaign when one is due, against InfoSec's controls
+    /// and Networking's firewall rules; InfoSec gets an incident for every
+    /// campaign its defenses caught
+    pub(crate) async fn run_red_team(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let now = self.scheduler.now();
+        if !self.config.red_team.enabled || !self.red_team.due(self.config.red_team.every_days, now) {
+            return Ok(());
+        }
+        let controls = self.agents.call_all(|agent| {
+            agent.as_infosec().map(|infosec| infosec.security_posture.active_controls.clone()).unwrap_or_default()
+        }).await?;
+        let rules = self.agents.call_all(|agent| {
+            agent.as_networking().map(|networking| networking.network_topology.firewall_rules.clone()).unwrap_or_default()
+        }).await?;
+        let hostnames = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values().map(|server| server.hostname.clone()).collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let defenses = Defenses {
+            controls: controls.into_iter().flatten().collect(),
+            firewall_rules: rules.into_iter().flatten().collect(),
+            advisories: self.threat_intel.active_advisories(now).cloned().collect(),
+        };
+        let employees: Vec<String> = self.agents.infos().map(|info| info.name.clone()).collect();
+        let servers: Vec<String> = hostnames.into_iter().flatten().collect();
+        let services: Vec<String> = self.catalog.services.iter().map(|service| service.name.clone()).collect();
+        let phished = pick(&employees, &mut self.rng, "an employee");
+        let server = pick(&servers, &mut self.rng, "the internal network");
+        let service = pick(&services, &mut self.rng, "customer");
+
+        let campaign = self.red_team.run(&phished, &server, &service, &defenses, now, &mut self.rng).clone();
+        let Some(caught) = campaign.detected_at() else {
+            println!("🕵️ Red team exfiltrated {} data undetected, in through {} and {}", service, phished, server);
+            return Ok(());
+        };
+        let detected_by = caught.detected_by.clone().unwrap_or_default();
+        println!("🛡️ Red team caught at {} by {}", caught.stage.as_str(), detected_by);
+        if let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) {
+            self.pending_messages.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: SystemActor::EventGenerator.id(),
+                to_agent: infosec_id,
+                message_type: "incident_report".to_string(),
+                content: format!("{} (red team exercise, detected by {})", caught.technique, detected_by),
+                priority: MessagePriority::High,
+                timestamp: now,
+                metadata: HashMap::from([
+                    ("title".to_string(), format!("Red team {} detected", caught.stage.as_str())),
+                    ("affected_systems".to_string(), format!("{},{}", server, service)),
+                ]),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reload the threat feeds, hunt for their indicators in the day's
+    /// traffic on DevOps servers and hand InfoSec an advisory for every
+    /// threat sighted
+    pub(crate) async fn hunt_threats(&mut self, flows: &[FlowRecord]) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.threat_intel.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        for feed in &settings.feeds {
+            if let Err(e) = self.threat_intel.ingest_file(feed) {
+                eprintln!("⚠️ {}", e);
+            }
+        }
+        if self.threat_intel.indicators().next().is_none() {
+            return Ok(());
+        }
+        let hostnames = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values().map(|server| server.hostname.clone()).collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let hosts: Vec<String> = hostnames.into_iter().flatten().collect();
+        let mut observations = self.threat_intel.observe(&hosts, &settings, now, &mut self.rng);
+        if self.config.flow_logs.threat_hunting {
+            observations.extend(flows.iter().flat_map(FlowRecord::observations));
+        }
+        let infosec_id = self.find_department_agent(Department::InfoSec.as_str());
+        for advisory in self.threat_intel.hunt(observations, &settings, now) {
+            println!("📡 Threat intel: {} sighted on {}, advisory issued for {}", advisory.threat, advisory.hosts.join(", "),
+                     advisory.stages.iter().map(|stage| stage.as_str()).collect::<Vec<_>>().join(", "));
+            if let Some(infosec_id) = infosec_id {
+                let content = format!("Threat advisory: {} seen in {} indicators", advisory.threat, advisory.indicators.len());
+                let payload = MessagePayload::ThreatAdvisory { advisory };
+                self.pending_messages.push(payload.into_message(SystemActor::EventGenerator.id(), infosec_id, content, MessagePriority::High));
+            }
+        }
+        Ok(())
+    }
+
+    /// Monitor a day of transfers out of the data stores to Networking's
+    /// segments and hand InfoSec the classified data exposed
+    pub(crate) async fn monitor_data(&mut self, flows: &[FlowRecord]) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.dlp.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let segments = self.agents.call_all(|agent| {
+            agent.as_networking().map(|networking| {
+                networking.network_topology.segments.values().map(|segment| (segment.name.clone(), segment.security_level.clone())).collect::<Vec<_>>()
+            }).unwrap_or_default()
+        }).await?;
+        let segments = segments.into_iter().flatten().collect();
+        let mut exposures = self.dlp.monitor(&segments, &settings, now, &mut self.rng);
+        if self.config.flow_logs.dlp {
+            exposures.extend(self.dlp.monitor_flows(flows, &segments, &settings));
+        }
+        if exposures.is_empty() {
+            return Ok(());
+        }
+        let records: u64 = exposures.iter().map(|exposure| exposure.records).sum();
+        println!("🚨 DLP: {} classified records left for lower-security segments in {} transfers", records, exposures.len());
+        if let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) {
+            let content = format!("{} DLP exposures, {} records", exposures.len(), records);
+            let payload = MessagePayload::DataExposure { exposures };
+            self.pending_messages.push(payload.into_message(SystemActor::EventGenerator.id(), infosec_id, content, MessagePriority::High));
+        }
+        Ok(())
+    }
+
+    /// Have Networking log the traffic flows of the last step and append
+    /// the records to the flow log file, if one is configured
+    pub(crate) async fn capture_flows(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config.flow_logs.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let elapsed = self.step_interval().num_seconds().max(0) as u64;
+        let seed = self.rng.gen();
+        let records = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_networking_mut().map(|networking| networking.capture_flows(now, elapsed, &mut rng)).unwrap_or_default()
+        }).await?;
+        let Some(path) = self.config.flow_logs.path.clone() else {
+            return Ok(());
+        };
+        if self.flow_log.is_none() {
+            self.flow_log = Some(FlowLogWriter::open(&path)?);
+        }
+        if let Some(writer) = &mut self.flow_log {
+            for records in records {
+                writer.write(&records)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The flow records Networking captured since the last evening
+    pub(crate) async fn take_flow_log(&mut self) -> Result<Vec<FlowRecord>, Box<dyn std::error::Error>> {
+        let records = self.agents.call_all(|agent| agent.as_networking_mut().map(|networking| networking.take_flow_log()).unwrap_or_default()).await?;
+        Ok(records.into_iter().flatten().collect())
+    }
+
+    /// Have Networking put the DNS changes whose TTL ran out into effect
+    pub(crate) async fn propagate_dns(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let now = self.scheduler.now();
+        self.agents.call_all(move |agent| {
+            if let Some(networking) = agent.as_networking_mut() {
+                networking.propagate_dns(now);
+            }
+        }).await?;
+        Ok(())
+    }
+
+    /// Have Networking health-check its load balancers against the DevOps
+    /// servers behind them over the last step
+    pub(crate) async fn run_load_balancers(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.load_balancing.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let servers = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values().cloned().collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let servers: Vec<ServerStatus> = servers.into_iter().flatten().collect();
+        let elapsed = self.step_interval().num_seconds().max(0) as u64;
+        let seed = self.rng.gen();
+        let changes = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_networking_mut().map(|networking| networking.run_load_balancers(&servers, elapsed, settings.requests_per_second, &mut rng)).unwrap_or_default()
+        }).await?;
+        for change in changes.into_iter().flatten() {
+            let backend = change.hostname.unwrap_or_else(|| change.backend.to_string());
+            if change.healthy {
+                println!("⚖️ Load balancer {}: {} passed its health checks and is back in rotation", change.load_balancer, backend);
+            } else {
+                println!("⚖️ Load balancer {}: {} failed its health checks and was ejected", change.load_balancer, backend);
+            }
+        }
+        Ok(())
+    }
+
+    /// Have Networking fail and repair its WAN links over the last step and
+    /// route between sites around the ones down
+    pub(crate) async fn run_wan(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.wan.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let elapsed = self.step_interval().num_seconds().max(0) as u64;
+        let seed = self.rng.gen();
+        let events = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_networking_mut().map(|networking| networking.run_wan(&settings, now, elapsed, &mut rng)).unwrap_or_default()
+        }).await?;
+        for event in events.into_iter().flatten() {
+            match event {
+                wan::WanEvent::LinkDown { link, kind } => println!("🌍 WAN {:?} link {} failed", kind, link),
+                wan::WanEvent::LinkUp { link, kind } => println!("🌍 WAN {:?} link {} repaired", kind, link),
+                wan::WanEvent::Failover { sites, from, to: Some(to) } => println!("🔀 WAN traffic {} failed over from {} to {}", sites, from.join(" > "), to.join(" > ")),
+                wan::WanEvent::Failover { sites, .. } => println!("🚫 WAN sites {} are cut off from each other", sites),
+                wan::WanEvent::Restored { sites, path } => println!("🌍 WAN sites {} reach each other again over {}", sites, path.join(" > ")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Have Networking fight off DDoS attacks on the public segments. InfoSec
+    /// hears how each attack goes, and one starting degrades every service
+    /// with customers until it is mitigated, which is an incident for Ops.
+    pub(crate) async fn defend_ddos(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.ddos.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let elapsed = self.step_interval().num_seconds().max(0) as u64;
+        let seed = self.rng.gen();
+        let events = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_networking_mut().map(|networking| networking.defend_ddos(&settings, now, elapsed, &mut rng)).unwrap_or_default()
+        }).await?;
+        let infosec_id = self.find_department_agent(Department::InfoSec.as_str());
+        for event in events.into_iter().flatten() {
+            println!("🌊 {}", event.description());
+            if let Some(infosec_id) = infosec_id {
+                let priority = if matches!(event.kind, ddos::DdosEventKind::Started { .. }) { MessagePriority::High } else { MessagePriority::Normal };
+                let alert = MessagePayload::DdosAlert { event: event.clone() };
+                self.pending_messages.push(alert.into_message(SystemActor::Scheduler.id(), infosec_id, event.description(), priority));
+            }
+            if matches!(event.kind, ddos::DdosEventKind::Started { .. }) {
+                self.declare_ddos_incident(&event).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// A DDoS attack started: customers of every service see it slow down
+    /// until it is mitigated. The attack's id correlates the incident.
+    async fn declare_ddos_incident(&mut self, event: &ddos::DdosEvent) {
+        let mut affected_services: Vec<String> = self.catalog.deployments.values()
+            .filter(|deployment| !deployment.customers.is_empty())
+            .map(|deployment| deployment.service.clone())
+            .collect();
+        affected_services.sort();
+        affected_services.dedup();
+        let mut affected_customers: Vec<String> = self.catalog.deployments.values()
+            .flat_map(|deployment| deployment.customers.iter().cloned())
+            .collect();
+        affected_customers.sort();
+        affected_customers.dedup();
+        for service in &affected_services {
+            self.scorecards.record_incident(service);
+        }
+        for customer_id in &affected_customers {
+            if let Some(customer) = self.catalog.customers.get_mut(customer_id) {
+                customer.incident_declared(event.attack, Severity::Sev2, &self.config.customers.satisfaction);
+            }
+        }
+        let Some(ops_id) = self.assign_work(WorkKind::Incident, chrono::Duration::hours(4)).await.map(|assignment| assignment.agent_id) else {
+            return;
+        };
+        let payload = MessagePayload::IncidentDeclared {
+            title: format!("DDoS attack on segment {}", event.segment),
+            severity: Severity::Sev2,
+            affected_services,
+            affected_customers,
+        };
+        let mut incident = payload.into_message(SystemActor::EventGenerator.id(), ops_id, event.description(), MessagePriority::High);
+        incident.metadata.insert(timeline::CORRELATION_KEY.to_string(), event.attack.to_string());
+        self.pending_messages.push(incident);
+    }
+
+    /// Hand Ops the day's round trips to the multi-region services for
+    /// their SLAs
+    pub(crate) async fn report_service_latency(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config.wan.enabled {
+            return Ok(());
+        }
+        let samples = self.agents.call_all(|agent| agent.as_networking_mut().map(|networking| networking.take_service_latency()).unwrap_or_default()).await?;
+        let mut latency = BTreeMap::new();
+        for sample in samples {
+            latency.extend(sample);
+        }
+        let Some(ops_id) = self.find_department_agent(Department::Ops.as_str()) else {
+            return Ok(());
+        };
+        if latency.is_empty() {
+            return Ok(());
+        }
+        let content = format!("Cross-site latency of {} multi-region services", latency.len());
+        let payload = MessagePayload::ServiceLatency { at: self.scheduler.now(), latency };
+        self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), ops_id, content, MessagePriority::Low));
+        Ok(())
+    }
+
+    /// Tell Networking the traffic each catalog service draws from the
+    /// customers it serves and the projects being built for them
+    pub(crate) fn report_traffic_demand(&mut self) {
+        let settings = self.config.traffic.clone();
+        if !settings.enabled {
+            return;
+        }
+        let Some(networking_id) = self.find_department_agent(Department::Networking.as_str()) else {
+            return;
+        };
+        let demand: Vec<ServiceDemand> = self.catalog.services.iter()
+            .map(|service| {
+                let customers: BTreeSet<&String> = self.catalog.deployments.values()
+                    .filter(|deployment| deployment.service == service.name)
+                    .flat_map(|deployment| deployment.customers.iter())
+                    .collect();
+                let projects = customers.iter().filter_map(|id| self.catalog.customers.get(*id)).map(|customer| customer.active_projects.len()).sum();
+                ServiceDemand { service: service.name.clone(), customers: customers.len(), projects }
+            })
+            .collect();
+        let content = format!("Traffic demand of {} services", demand.len());
+        let payload = MessagePayload::TrafficDemand { demand, settings };
+        self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), networking_id, content, MessagePriority::Low));
+    }
+
+    /// Keep an account for every agent, decide the day's access requests
+    /// along each requester's reporting line and review access when due
+    pub(crate) fn manage_access(&mut self) {
+        let settings = self.config.iam.clone();
+        if !settings.enabled {
+            return;
+        }
+        let now = self.scheduler.now();
+        let roster: Vec<AgentInfo> = self.agents.infos().cloned().collect();
+        self.iam.sync(&roster, settings.mfa_enrollment_probability, now, &mut self.rng);
+        for info in &roster {
+            if !self.rng.gen_bool(settings.request_probability) {
+                continue;
+            }
+            let role = iam::ROLES[self.rng.gen_range(0..iam::ROLES.len())].name;
+            let reason = format!("{} work needs {} access", info.department.as_str(), role);
+            let Some(request_id) = self.iam.request(info.id, role, &reason, now).map(|request| request.id) else {
+                continue;
+            };
+            let approver = self.org.chain(info.id).first().map_or(Approver::Ceo, |manager| Approver::Manager(*manager));
+            let approved = self.iam.decide(request_id, approver, settings.grant_days, now)
+                .is_some_and(|request| request.status == iam::RequestStatus::Approved);
+            let name = match approver {
+                Approver::Manager(manager) => self.actor_name(manager),
+                Approver::Ceo => SystemActor::Ceo.name().to_string(),
+            };
+            println!("🔑 {} {} {} access for {}", name, if approved { "granted" } else { "denied" }, role, info.name);
+            self.org.record_approval(Approval {
+                kind: ApprovalKind::Access,
+                requested_by: info.id,
+                subject: role.to_string(),
+                amount: f64::from(settings.grant_days),
+                approver,
+                approved,
+                decided_at: now,
+            });
+        }
+
+        let active: Vec<Uuid> = roster.iter().map(|info| info.id).collect();
+        if self.iam.review_due(settings.review_every_days, now) {
+            let review = self.iam.review(&active, now);
+            println!("🔐 Access review of {} accounts: {} orphaned disabled, {} roles revoked from {} over-privileged, {} enrolled in MFA",
+                     review.accounts, review.orphaned.len(), review.revoked, review.over_privileged.len(), review.enrolled);
+        }
+    }
+
+    /// Gather evidence of the identity directory, DevOps servers, the
+    /// firewall and backups for InfoSec to check its security policies
+    /// against at the next compliance audit
+    pub(crate) async fn check_policies(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) else {
+            return Ok(());
+        };
+        let now = self.scheduler.now();
+        let servers = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| {
+                devops.infrastructure_state.servers.values()
+                    // A server of unknown spec cannot show its disks are encrypted
+                    .map(|server| (server.hostname.clone(), server.spec.as_ref().is_some_and(|spec| spec.encrypted)))
+                    .collect::<Vec<_>>()
+            }).unwrap_or_default()
+        }).await?;
+        let default_deny = self.agents.call_all(|agent| {
+            agent.as_networking().and_then(|networking| secpolicy::default_deny(&networking.network_topology.firewall_rules).map(|rule| rule.name.clone()))
+        }).await?;
+        let active: Vec<Uuid> = self.agents.infos().map(|info| info.id).collect();
+        let evidence = PolicyEvidence {
+            accounts: self.iam.enabled_accounts(),
+            access: self.iam.findings(&active, now),
+            servers: servers.into_iter().flatten().collect(),
+            default_deny_rule: default_deny.into_iter().flatten().next(),
+            backup_retention_days: self.config.backups.retention_days,
+        };
+        let content = format!("Policy evidence for {} accounts and {} servers", evidence.accounts, evidence.servers.len());
+        let payload = MessagePayload::PolicyCheck { at: now, settings: self.config.security_policies.clone(), evidence };
+        self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), infosec_id, content, MessagePriority::Low));
+        Ok(())
+    }
+
+    /// Analyze Networking's firewall rules and hand InfoSec the findings
+    pub(crate) async fn analyze_firewall(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) else {
+            return Ok(());
+        };
+        let reports = self.agents.call_all(|agent| agent.as_networking().map(|networking| networking.analyze_firewall())).await?;
+        let Some(report) = reports.into_iter().flatten().next() else {
+            return Ok(());
+        };
+        if report.rules == 0 {
+            return Ok(());
+        }
+        let content = format!("Firewall analysis of {} rules: {} findings", report.rules, report.findings.len());
+        let payload = MessagePayload::FirewallFindings { at: self.scheduler.now(), report };
+        self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), infosec_id, content, MessagePriority::Low));
+        Ok(())
+    }
+
+    /// Have Networking check the TLS certificates of every service in every
+    /// environment; DevOps and InfoSec hear of expiring, failed and lapsed
+    /// certificates, and a lapse on production is an outage for Ops
+    pub(crate) async fn check_certificates(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.certificates.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let services: Vec<String> = self.catalog.services.iter().map(|service| service.name.clone()).collect();
+        let production = self.production_environment().to_string();
+        let environments: Vec<String> = self.config.deployments.environments.iter()
+            .map(|environment| environment.name.clone())
+            .filter(|name| *name != production)
+            .chain([certs::PRODUCTION.to_string()])
+            .collect();
+        let seed = self.rng.gen();
+        let events = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_networking_mut().map(|networking| networking.check_certificates(&services, &environments, now, &settings, &mut rng)).unwrap_or_default()
+        }).await?;
+
+        let recipients: Vec<Uuid> = [Department::DevOps, Department::InfoSec].iter().filter_map(|department| self.find_department_agent(department.as_str())).collect();
+        for event in events.into_iter().flatten() {
+            println!("🔐 {}", event.description());
+            if !event.alerts() {
+                continue;
+            }
+            for &recipient in &recipients {
+                let priority = if event.production() { MessagePriority::High } else { MessagePriority::Normal };
+                let alert = MessagePayload::CertificateAlert { event: event.clone() };
+                self.pending_messages.push(alert.into_message(SystemActor::Scheduler.id(), recipient, event.description(), priority));
+            }
+            if event.kind == certs::CertificateEventKind::Lapsed && event.production() {
+                self.declare_certificate_outage(&event).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// A production certificate lapsed: every customer of the service gets
+    /// browser errors until it is renewed
+    async fn declare_certificate_outage(&mut self, event: &certs::CertificateEvent) {
+        let correlation_id = Uuid::new_v4();
+        let mut affected_customers: Vec<String> = self.catalog.deployments.values()
+            .filter(|deployment| deployment.service == event.service)
+            .flat_map(|deployment| deployment.customers.iter().cloned())
+            .collect();
+        affected_customers.sort();
+        affected_customers.dedup();
+        self.scorecards.record_incident(&event.service);
+        for customer_id in &affected_customers {
+            if let Some(customer) = self.catalog.customers.get_mut(customer_id) {
+                customer.incident_declared(correlation_id, Severity::Sev1, &self.config.customers.satisfaction);
+            }
+        }
+        let Some(ops_id) = self.assign_work(WorkKind::Incident, chrono::Duration::hours(4)).await.map(|assignment| assignment.agent_id) else {
+            return;
+        };
+        let payload = MessagePayload::IncidentDeclared {
+            title: format!("Expired TLS certificate on {}", event.domain),
+            severity: Severity::Sev1,
+            affected_services: vec![event.service.clone()],
+            affected_customers,
+        };
+        let content = format!("{}: {} is down for its customers", event.description(), event.service);
+        let mut incident = payload.into_message(SystemActor::EventGenerator.id(), ops_id, content, MessagePriority::Critical);
+        incident.metadata.insert(timeline::CORRELATION_KEY.to_string(), correlation_id.to_string());
+        self.pending_messages.push(incident);
+    }
+
+    /// Hold the training sessions due, then phish agents across departments
+    /// when a campaign is due and hand the results to InfoSec
+    pub(crate) fn run_phishing(&mut self) {
+        let settings = self.config.phishing.clone();
+        if !settings.enabled {
+            return;
+        }
+        let now = self.scheduler.now();
+        let roster: Vec<AgentInfo> = self.agents.infos().cloned().collect();
+        self.phishing.enroll(&roster, &settings);
+        for session in self.phishing.hold_trainings(settings.training_gain, now) {
+            println!("🎓 {} agents completed security-awareness training", session.agents.len());
+        }
+        if !self.phishing.due(settings.every_days, now) {
+            return;
+        }
+        let active: Vec<Uuid> = roster.iter().map(|info| info.id).collect();
+        let campaign = self.phishing.run(&active, &settings, now, &mut self.rng).clone();
+        let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) else {
+            return;
+        };
+        let content = format!("Phishing campaign '{}' against {} agents", campaign.lure, campaign.targets.len());
+        let results = MessagePayload::PhishingResults { campaign_id: campaign.id, at: now, lure: campaign.lure, targets: campaign.targets };
+        self.pending_messages.push(results.into_message(SystemActor::EventGenerator.id(), infosec_id, content, MessagePriority::Normal));
+    }
+
+    /// Start or finish a DevOps agent's approved maintenance window; what
+    /// it patched is closed with InfoSec
+    pub(crate) async fn run_maintenance(&mut self, agent_id: Uuid) {
+        let now = self.scheduler.now();
+        let event = self.agents.call(agent_id, move |agent| {
+            agent.as_devops_mut().and_then(|devops| devops.run_maintenance(now))
+        }).await;
+        match event {
+            Ok(Some(WindowEvent::Started { servers, .. })) => {
+                println!("🛠️ {} opened a maintenance window for {}", self.actor_name(agent_id), servers.join(", "));
+            }
+            Ok(Some(WindowEvent::Finished { servers, patched, fixed, .. })) => {
+                println!("🩹 {} applied {} patches to {}", self.actor_name(agent_id), patched, servers.join(", "));
+                if fixed.is_empty() {
+                    return;
+                }
+                let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) else {
+                    return;
+                };
+                let content = format!("Patched {}", fixed.join(", "));
+                let applied = MessagePayload::PatchesApplied { vulnerabilities: fixed };
+                self.pending_messages.push(applied.into_message(agent_id, infosec_id, content, MessagePriority::Normal));
+            }
+            Ok(None) | Err(_) => {}
+        }
+    }
+
+    /// Hand the configured infrastructure plan to the DevOps head; sent
+    /// daily, so a plan already applied finds whatever drifted since
+    pub(crate) fn check_infrastructure(&mut self) {
+        let plan = &self.config.infrastructure;
+        if *plan == InfraPlan::default() {
+            return;
+        }
+        let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) else {
+            return;
+        };
+        let content = format!("Infrastructure plan: {} servers, {} clusters, {} load balancers",
+                              plan.servers.len(), plan.clusters.len(), plan.load_balancers.len());
+        let request = MessagePayload::InfraPlanRequest { plan: plan.clone() };
+        self.pending_messages.push(request.into_message(SystemActor::Scheduler.id(), devops_id, content, MessagePriority::Normal));
+    }
+}
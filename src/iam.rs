@@ -0,0 +1,399 @@
+//! Identity and Access - Accounts, Roles and Access Reviews
+//!
+//! This module implements who in the company may do what:
+//! - An account for every agent, created with its department's baseline
+//!   role the first time the agent is seen; most enroll in MFA by themselves
+//! - Roles as named sets of permissions, from a developer's code access to
+//!   an administrator's everything
+//! - Access requests for roles beyond the baseline, decided by the
+//!   requester's manager; administrator access is never granted on request,
+//!   and granted roles expire after a set number of days
+//! - Periodic access reviews that find orphaned accounts, left behind by
+//!   agents who were offboarded, and over-privileged ones, holding an
+//!   expired grant or administrator access; orphaned accounts are disabled,
+//!   extra roles revoked and accounts without MFA enrolled
+//!
+//! The orchestrator keeps the directory and hands what is still wrong with
+//! it to InfoSec every day as evidence for the MFA and access review
+//! security policies.
+
+use crate::agents::Department;
+use crate::orgchart::Approver;
+use crate::runtime::AgentInfo;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Decided requests and reviews kept; the oldest go first
+const MAX_HISTORY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ReadCode,
+    WriteCode,
+    Deploy,
+    ProductionAccess,
+    ManageInfrastructure,
+    ManageNetwork,
+    ManageSecurity,
+    CustomerData,
+    Billing,
+    Admin,
+}
+
+/// A named set of permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Role {
+    pub name: &'static str,
+    pub permissions: &'static [Permission],
+}
+
+pub const ADMIN_ROLE: &str = "admin";
+
+pub const ROLES: &[Role] = &[
+    Role { name: "employee", permissions: &[] },
+    Role { name: "developer", permissions: &[Permission::ReadCode, Permission::WriteCode] },
+    Role {
+        name: "operator",
+        permissions: &[Permission::ReadCode, Permission::Deploy, Permission::ProductionAccess, Permission::ManageInfrastructure],
+    },
+    Role { name: "network_admin", permissions: &[Permission::ManageNetwork, Permission::ProductionAccess] },
+    Role { name: "security_analyst", permissions: &[Permission::ManageSecurity, Permission::ReadCode, Permission::CustomerData] },
+    Role { name: "support", permissions: &[Permission::CustomerData] },
+    Role { name: "billing", permissions: &[Permission::Billing, Permission::CustomerData] },
+    Role {
+        name: ADMIN_ROLE,
+        permissions: &[
+            Permission::ReadCode,
+            Permission::WriteCode,
+            Permission::Deploy,
+            Permission::ProductionAccess,
+            Permission::ManageInfrastructure,
+            Permission::ManageNetwork,
+            Permission::ManageSecurity,
+            Permission::CustomerData,
+            Permission::Billing,
+            Permission::Admin,
+        ],
+    },
+];
+
+pub fn role(name: &str) -> Option<&'static Role> {
+    ROLES.iter().find(|role| role.name == name)
+}
+
+/// Role every member of a department holds from their first day
+pub fn baseline_role(department: &Department) -> &'static str {
+    match department {
+        Department::Engineering => "developer",
+        Department::DevOps | Department::Ops => "operator",
+        Department::Networking => "network_admin",
+        Department::InfoSec => "security_analyst",
+        Department::Marketing | Department::Sales => "support",
+        Department::Finance => "billing",
+        _ => "employee",
+    }
+}
+
+/// A role held by an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grant {
+    pub role: String,
+    pub granted_at: chrono::DateTime<chrono::Utc>,
+    /// When a requested role runs out; the baseline role never does
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Grant {
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub grants: Vec<Grant>,
+    pub disabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub mfa_enrolled: bool,
+}
+
+impl Account {
+    pub fn is_enabled(&self) -> bool {
+        self.disabled_at.is_none()
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.grants.iter().any(|grant| grant.role == role)
+    }
+
+    /// Everything the account's roles allow
+    pub fn permissions(&self) -> BTreeSet<Permission> {
+        self.grants.iter()
+            .filter_map(|grant| role(&grant.role))
+            .flat_map(|role| role.permissions.iter().copied())
+            .collect()
+    }
+
+    /// Grants a review would revoke: expired ones and administrator access
+    fn excess(&self, now: chrono::DateTime<chrono::Utc>) -> impl Iterator<Item = &Grant> {
+        self.grants.iter().filter(move |grant| grant.is_expired(now) || grant.role == ADMIN_ROLE)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RequestStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub role: String,
+    pub reason: String,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    pub status: RequestStatus,
+    pub decided_by: Option<Approver>,
+    pub decided_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// What an access review found and fixed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessReview {
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
+    pub accounts: usize,
+    /// Names of the accounts disabled because their agent left
+    pub orphaned: Vec<String>,
+    /// Names of the accounts that lost an expired or administrator role
+    pub over_privileged: Vec<String>,
+    pub revoked: usize,
+    /// Accounts enrolled in MFA by the review
+    #[serde(default)]
+    pub enrolled: usize,
+}
+
+/// Accounts that need an access review's attention
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessFindings {
+    pub orphaned: Vec<String>,
+    pub over_privileged: Vec<String>,
+    #[serde(default)]
+    pub without_mfa: Vec<String>,
+}
+
+/// Every account, access request and access review
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityDirectory {
+    accounts: BTreeMap<Uuid, Account>,
+    requests: Vec<AccessRequest>,
+    reviews: Vec<AccessReview>,
+}
+
+impl IdentityDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.values()
+    }
+
+    pub fn account(&self, agent_id: Uuid) -> Option<&Account> {
+        self.accounts.get(&agent_id)
+    }
+
+    pub fn requests(&self) -> &[AccessRequest] {
+        &self.requests
+    }
+
+    pub fn reviews(&self) -> &[AccessReview] {
+        &self.reviews
+    }
+
+    /// Open an account for every agent on the roster without one, enrolled
+    /// in MFA with `mfa_probability`. Agents who left keep theirs until a
+    /// review finds it orphaned.
+    pub fn sync<'a>(&mut self, roster: impl IntoIterator<Item = &'a AgentInfo>, mfa_probability: f64, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> usize {
+        let mut opened = 0;
+        for info in roster {
+            self.accounts.entry(info.id).or_insert_with(|| {
+                opened += 1;
+                Account {
+                    agent_id: info.id,
+                    name: info.name.clone(),
+                    department: info.department.as_str().to_string(),
+                    created_at: now,
+                    grants: vec![Grant { role: baseline_role(&info.department).to_string(), granted_at: now, expires_at: None }],
+                    disabled_at: None,
+                    mfa_enrolled: rng.gen_bool(mfa_probability.clamp(0.0, 1.0)),
+                }
+            });
+        }
+        opened
+    }
+
+    /// Ask for a role; `None` when there is no such role or the account
+    /// already holds it
+    pub fn request(&mut self, agent_id: Uuid, role_name: &str, reason: &str, now: chrono::DateTime<chrono::Utc>) -> Option<&AccessRequest> {
+        let account = self.accounts.get(&agent_id).filter(|account| account.is_enabled())?;
+        if role(role_name).is_none() || account.has_role(role_name) {
+            return None;
+        }
+        self.requests.push(AccessRequest {
+            id: Uuid::new_v4(),
+            agent_id,
+            role: role_name.to_string(),
+            reason: reason.to_string(),
+            requested_at: now,
+            status: RequestStatus::Pending,
+            decided_by: None,
+            decided_at: None,
+        });
+        self.requests.last()
+    }
+
+    /// Decide a pending request: approved roles are granted for
+    /// `grant_days`, administrator access is always denied
+    pub fn decide(&mut self, request_id: Uuid, approver: Approver, grant_days: u32, now: chrono::DateTime<chrono::Utc>) -> Option<&AccessRequest> {
+        let request = self.requests.iter_mut().find(|request| request.id == request_id && request.status == RequestStatus::Pending)?;
+        let account = self.accounts.get_mut(&request.agent_id).filter(|account| account.is_enabled());
+        request.status = match account {
+            Some(account) if request.role != ADMIN_ROLE => {
+                account.grants.push(Grant {
+                    role: request.role.clone(),
+                    granted_at: now,
+                    expires_at: Some(now + chrono::Duration::days(i64::from(grant_days))),
+                });
+                RequestStatus::Approved
+            }
+            _ => RequestStatus::Denied,
+        };
+        request.decided_by = Some(approver);
+        request.decided_at = Some(now);
+        let id = request.id;
+        self.trim();
+        self.requests.iter().find(|request| request.id == id)
+    }
+
+    /// Enabled accounts, orphaned ones included
+    pub fn enabled_accounts(&self) -> usize {
+        self.accounts.values().filter(|account| account.is_enabled()).count()
+    }
+
+    /// Orphaned, over-privileged and MFA-less accounts as they stand at `now`
+    pub fn findings(&self, active: &[Uuid], now: chrono::DateTime<chrono::Utc>) -> AccessFindings {
+        let enabled = || self.accounts.values().filter(|account| account.is_enabled());
+        AccessFindings {
+            orphaned: enabled().filter(|account| !active.contains(&account.agent_id)).map(|account| account.name.clone()).collect(),
+            over_privileged: enabled()
+                .filter(|account| active.contains(&account.agent_id) && account.excess(now).next().is_some())
+                .map(|account| account.name.clone())
+                .collect(),
+            without_mfa: enabled().filter(|account| !account.mfa_enrolled).map(|account| account.name.clone()).collect(),
+        }
+    }
+
+    /// Whether a review is due, `every_days` after the last one
+    pub fn review_due(&self, every_days: u32, now: chrono::DateTime<chrono::Utc>) -> bool {
+        every_days > 0 && self.reviews.last()
+            .and_then(|review| review.at)
+            .iter().all(|at| now - *at >= chrono::Duration::days(i64::from(every_days)))
+    }
+
+    /// Disable orphaned accounts, revoke expired and administrator roles
+    /// and enroll the rest in MFA
+    pub fn review(&mut self, active: &[Uuid], now: chrono::DateTime<chrono::Utc>) -> &AccessReview {
+        let findings = self.findings(active, now);
+        let (mut revoked, mut enrolled) = (0, 0);
+        for account in self.accounts.values_mut().filter(|account| account.is_enabled()) {
+            if !active.contains(&account.agent_id) {
+                account.disabled_at = Some(now);
+                continue;
+            }
+            let before = account.grants.len();
+            account.grants.retain(|grant| !grant.is_expired(now) && grant.role != ADMIN_ROLE);
+            revoked += before - account.grants.len();
+            if !account.mfa_enrolled {
+                account.mfa_enrolled = true;
+                enrolled += 1;
+            }
+        }
+        self.reviews.push(AccessReview {
+            at: Some(now),
+            accounts: self.accounts.len(),
+            orphaned: findings.orphaned,
+            over_privileged: findings.over_privileged,
+            revoked,
+            enrolled,
+        });
+        self.trim();
+        self.reviews.last().expect("just pushed")
+    }
+
+    fn trim(&mut self) {
+        if self.reviews.len() > MAX_HISTORY {
+            self.reviews.remove(0);
+        }
+        let decided = self.requests.iter().filter(|request| request.status != RequestStatus::Pending).count();
+        if decided > MAX_HISTORY {
+            let mut excess = decided - MAX_HISTORY;
+            self.requests.retain(|request| {
+                let drop = excess > 0 && request.status != RequestStatus::Pending;
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn info(name: &str, department: Department) -> AgentInfo {
+        AgentInfo { id: Uuid::new_v4(), name: name.to_string(), department, manager_id: None }
+    }
+
+    #[test]
+    fn test_review_disables_orphans_and_revokes_excess_roles() {
+        let now = chrono::Utc::now();
+        let alice = info("Alice", Department::Engineering);
+        let bob = info("Bob", Department::DevOps);
+        let mut directory = IdentityDirectory::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        assert_eq!(directory.sync([&alice, &bob], 0.0, now, &mut rng), 2);
+        assert!(directory.account(alice.id).unwrap().permissions().contains(&Permission::WriteCode));
+
+        // Production access is granted for 30 days; admin is never granted
+        let production = directory.request(alice.id, "operator", "on-call rotation", now).unwrap().id;
+        assert_eq!(directory.decide(production, Approver::Ceo, 30, now).unwrap().status, RequestStatus::Approved);
+        let admin = directory.request(alice.id, ADMIN_ROLE, "convenience", now).unwrap().id;
+        assert_eq!(directory.decide(admin, Approver::Ceo, 30, now).unwrap().status, RequestStatus::Denied);
+        assert!(directory.request(alice.id, "operator", "again", now).is_none());
+
+        // Bob leaves and Alice's grant runs out
+        let later = now + chrono::Duration::days(31);
+        let findings = directory.findings(&[alice.id], later);
+        assert_eq!(findings.orphaned, vec!["Bob".to_string()]);
+        assert_eq!(findings.over_privileged, vec!["Alice".to_string()]);
+        assert_eq!(findings.without_mfa.len(), 2);
+        assert!(directory.review_due(30, later));
+        let review = directory.review(&[alice.id], later);
+        assert_eq!((review.revoked, review.enrolled), (1, 1));
+        assert_eq!(directory.findings(&[alice.id], later), AccessFindings::default());
+        assert!(!directory.account(bob.id).unwrap().is_enabled());
+        assert!(!directory.review_due(30, later));
+    }
+}
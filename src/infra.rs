@@ -0,0 +1,229 @@
+//! Infrastructure as Code - Plans, Diffs and Drift
+//!
+//! This module implements the declarative side of DevOps infrastructure:
+//! - An `InfraPlan` describing the servers, clusters and load balancers the
+//!   company should run
+//! - A diff of a plan against the current infrastructure state, as ordered
+//!   changes that create, update or destroy resources; only resources an
+//!   earlier plan brought about are destroyed, so servers and clusters made
+//!   by auto-scaling or deployments are left alone
+//! - Destructive changes (destroying, shrinking or resizing) flagged for an
+//!   Ops change-request approval before anything is applied
+//! - Drift: whatever still differs from the plan once it has been applied,
+//!   or has changed since
+//!
+//! DevOps agents hold the plan run and apply its changes one at a time; load
+//! balancer changes are carried out by Networking.
+
+use crate::departments::devops::{InfrastructureState, ServerConfig};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Desired state of the company's infrastructure
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InfraPlan {
+    pub servers: Vec<ServerConfig>,
+    pub clusters: Vec<ClusterSpec>,
+    pub load_balancers: Vec<LoadBalancerSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterSpec {
+    pub name: String,
+    pub nodes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadBalancerSpec {
+    pub name: String,
+    /// Hostnames of the servers behind it
+    pub backends: Vec<String>,
+}
+
+/// One step of bringing the infrastructure in line with a plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InfraChange {
+    CreateServer { server: ServerConfig },
+    /// Re-provision a server at a new size, which restarts it
+    ResizeServer { server: ServerConfig },
+    DestroyServer { hostname: String },
+    CreateCluster { cluster: ClusterSpec },
+    ScaleCluster { name: String, from: usize, to: usize },
+    DestroyCluster { name: String },
+    /// Create or re-point a load balancer
+    ConfigureLoadBalancer { load_balancer: LoadBalancerSpec },
+    DestroyLoadBalancer { name: String },
+}
+
+impl InfraChange {
+    /// Whether the change takes capacity away or restarts something, and so
+    /// needs an approved change request
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            InfraChange::ResizeServer { .. } | InfraChange::DestroyServer { .. } | InfraChange::DestroyCluster { .. } | InfraChange::DestroyLoadBalancer { .. } => true,
+            InfraChange::ScaleCluster { from, to, .. } => to < from,
+            InfraChange::CreateServer { .. } | InfraChange::CreateCluster { .. } | InfraChange::ConfigureLoadBalancer { .. } => false,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            InfraChange::CreateServer { server } => format!("+ server {} ({} cores, {} GB)", server.hostname, server.cpu_cores, server.memory_gb),
+            InfraChange::ResizeServer { server } => format!("~ server {} to {} cores, {} GB", server.hostname, server.cpu_cores, server.memory_gb),
+            InfraChange::DestroyServer { hostname } => format!("- server {}", hostname),
+            InfraChange::CreateCluster { cluster } => format!("+ cluster {} ({} nodes)", cluster.name, cluster.nodes),
+            InfraChange::ScaleCluster { name, from, to } => format!("~ cluster {} from {} to {} nodes", name, from, to),
+            InfraChange::DestroyCluster { name } => format!("- cluster {}", name),
+            InfraChange::ConfigureLoadBalancer { load_balancer } => format!("~ load balancer {} -> {}", load_balancer.name, load_balancer.backends.join(", ")),
+            InfraChange::DestroyLoadBalancer { name } => format!("- load balancer {}", name),
+        }
+    }
+}
+
+/// Changes that turn `state` into `plan`: creations and updates first, in
+/// plan order, then what the `applied` plan had and `plan` no longer has is
+/// destroyed
+pub fn diff(plan: &InfraPlan, applied: &InfraPlan, state: &InfrastructureState) -> Vec<InfraChange> {
+    let mut changes = Vec::new();
+    for server in &plan.servers {
+        match state.servers.values().find(|current| current.hostname == server.hostname) {
+            None => changes.push(InfraChange::CreateServer { server: server.clone() }),
+            // Servers provisioned before plans recorded no size; leave them be
+            Some(current) if current.spec.as_ref().is_some_and(|spec| spec != server) => {
+                changes.push(InfraChange::ResizeServer { server: server.clone() });
+            }
+            Some(_) => {}
+        }
+    }
+    for cluster in &plan.clusters {
+        match state.clusters.get(&cluster.name) {
+            None => changes.push(InfraChange::CreateCluster { cluster: cluster.clone() }),
+            Some(current) if current.nodes.len() != cluster.nodes => changes.push(InfraChange::ScaleCluster {
+                name: cluster.name.clone(),
+                from: current.nodes.len(),
+                to: cluster.nodes,
+            }),
+            Some(_) => {}
+        }
+    }
+    for load_balancer in &plan.load_balancers {
+        if state.load_balancers.get(&load_balancer.name) != Some(&load_balancer.backends) {
+            changes.push(InfraChange::ConfigureLoadBalancer { load_balancer: load_balancer.clone() });
+        }
+    }
+
+    for server in applied.servers.iter().filter(|old| !plan.servers.iter().any(|server| server.hostname == old.hostname)) {
+        if state.servers.values().any(|current| current.hostname == server.hostname) {
+            changes.push(InfraChange::DestroyServer { hostname: server.hostname.clone() });
+        }
+    }
+    for cluster in applied.clusters.iter().filter(|old| !plan.clusters.iter().any(|cluster| cluster.name == old.name)) {
+        if state.clusters.contains_key(&cluster.name) {
+            changes.push(InfraChange::DestroyCluster { name: cluster.name.clone() });
+        }
+    }
+    for load_balancer in applied.load_balancers.iter().filter(|old| !plan.load_balancers.iter().any(|lb| lb.name == old.name)) {
+        if state.load_balancers.contains_key(&load_balancer.name) {
+            changes.push(InfraChange::DestroyLoadBalancer { name: load_balancer.name.clone() });
+        }
+    }
+    changes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InfraRunStatus {
+    /// Waiting for Ops to decide on the destructive changes
+    AwaitingApproval,
+    Applying,
+    Applied,
+    Rejected,
+}
+
+/// A plan being brought about by a DevOps agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfraRun {
+    pub id: Uuid,
+    pub plan: InfraPlan,
+    pub changes: Vec<InfraChange>,
+    /// Changes applied so far
+    pub applied: usize,
+    pub status: InfraRunStatus,
+    pub planned_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Differences from the plan left after applying it
+    pub drift: Vec<InfraChange>,
+}
+
+impl InfraRun {
+    pub fn new(plan: InfraPlan, changes: Vec<InfraChange>, now: chrono::DateTime<chrono::Utc>) -> Self {
+        let status = if changes.iter().any(InfraChange::is_destructive) {
+            InfraRunStatus::AwaitingApproval
+        } else {
+            InfraRunStatus::Applying
+        };
+        Self {
+            id: Uuid::new_v4(),
+            plan,
+            changes,
+            applied: 0,
+            status,
+            planned_at: now,
+            finished_at: None,
+            drift: Vec::new(),
+        }
+    }
+
+    pub fn destructive_changes(&self) -> impl Iterator<Item = &InfraChange> {
+        self.changes.iter().filter(|change| change.is_destructive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::devops::{ServerState, ServerStatus};
+
+    fn server(hostname: &str, cpu_cores: u32) -> ServerConfig {
+        ServerConfig { hostname: hostname.to_string(), cpu_cores, memory_gb: 16, disk_gb: 100, encrypted: true }
+    }
+
+    #[test]
+    fn test_diff_orders_changes_and_flags_destructive_ones() {
+        let mut state = InfrastructureState::default();
+        for spec in [server("web-1", 4), server("legacy-1", 2)] {
+            state.servers.insert(spec.hostname.clone(), ServerStatus {
+                id: spec.hostname.clone(),
+                hostname: spec.hostname.clone(),
+                status: ServerState::Online,
+                cpu_usage: 0.0,
+                memory_usage: 0.0,
+                disk_usage: 0.0,
+                uptime: 0,
+                last_check: chrono::Utc::now(),
+                spec: Some(spec),
+            });
+        }
+        let plan = InfraPlan {
+            servers: vec![server("web-1", 8), server("web-2", 4)],
+            clusters: vec![ClusterSpec { name: "production".to_string(), nodes: 3 }],
+            load_balancers: vec![LoadBalancerSpec { name: "web".to_string(), backends: vec!["web-1".to_string(), "web-2".to_string()] }],
+        };
+
+        // legacy-1 came from the last plan; nothing else has been applied
+        let applied = InfraPlan { servers: vec![server("web-1", 4), server("legacy-1", 2)], ..InfraPlan::default() };
+        let changes = diff(&plan, &applied, &state);
+        let described: Vec<String> = changes.iter().map(InfraChange::describe).collect();
+        assert_eq!(described, vec![
+            "~ server web-1 to 8 cores, 16 GB",
+            "+ server web-2 (4 cores, 16 GB)",
+            "+ cluster production (3 nodes)",
+            "~ load balancer web -> web-1, web-2",
+            "- server legacy-1",
+        ]);
+        let run = InfraRun::new(plan, changes, chrono::Utc::now());
+        assert_eq!(run.status, InfraRunStatus::AwaitingApproval);
+        assert_eq!(run.destructive_changes().count(), 2);
+    }
+}
@@ -0,0 +1,256 @@
+//! Intake Control - Department Circuit Breakers
+//!
+//! This module implements shedding load from overwhelmed departments:
+//! - One breaker per department that opens when its backlog crosses a
+//!   threshold, or for every department during a storm of Sev1 incidents
+//! - Non-critical messages for an open department are deferred and the
+//!   sender is told when to retry
+//! - Deferred messages are released once the breaker closes again
+//! - Counters of how often each department, and the company, ran overloaded
+//!
+//! Breakers close with hysteresis: the backlog has to drop to the recovery
+//! level, not just below the threshold, so a department does not flap.
+
+use crate::communication::{Message, MessagePriority};
+use crate::config::IntakeSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Metadata key carrying the retry-after hint on an `intake_deferred` message
+pub const RETRY_AFTER_KEY: &str = "retry_after";
+
+/// Message types that are admitted even while a breaker is open
+const ALWAYS_ADMITTED: &[&str] = &[
+    "declare_incident",
+    "infrastructure_alert",
+    "security_alert",
+    "incident_update",
+    "delivery_ack",
+    "intake_deferred",
+];
+
+/// Whether a breaker lets all intake through
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum BreakerState {
+    #[default]
+    Closed,
+    Open,
+}
+
+/// Why a breaker opened
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TripReason {
+    /// The department's open work reached the threshold
+    Backlog(usize),
+    /// This many Sev1 incidents were open at once
+    Sev1Storm(usize),
+}
+
+/// Breaker guarding one department's intake
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DepartmentBreaker {
+    pub state: BreakerState,
+    pub reason: Option<TripReason>,
+    pub opened_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Times the breaker has opened
+    pub trips: u64,
+    /// Messages deferred while open
+    pub deferred: u64,
+    /// Evaluations during which the breaker was open
+    pub overloaded_steps: u64,
+}
+
+/// Change of a breaker's state during an evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakerTransition {
+    pub department: String,
+    pub state: BreakerState,
+    pub reason: Option<TripReason>,
+}
+
+/// Decision on an incoming message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    Accept,
+    /// Held back; the sender should retry after this time
+    Defer { retry_after: chrono::DateTime<chrono::Utc> },
+}
+
+/// Circuit breakers for every department and the messages they hold back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntakeController {
+    breakers: BTreeMap<String, DepartmentBreaker>,
+    /// Deferred messages with the department they were addressed to
+    deferred: VecDeque<(String, Message)>,
+    /// Evaluations run so far
+    pub observed_steps: u64,
+    /// Evaluations during which any breaker was open
+    pub overloaded_steps: u64,
+}
+
+impl IntakeController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Re-evaluate every breaker against current backlogs and open Sev1 incidents
+    pub fn evaluate(&mut self, settings: &IntakeSettings, backlogs: &HashMap<String, usize>, open_sev1: usize, now: chrono::DateTime<chrono::Utc>) -> Vec<BreakerTransition> {
+        let storm = open_sev1 >= settings.sev1_storm_threshold;
+        let mut transitions = Vec::new();
+
+        for (department, &backlog) in backlogs {
+            let breaker = self.breakers.entry(department.clone()).or_default();
+            match breaker.state {
+                BreakerState::Closed => {
+                    let reason = if backlog >= settings.backlog_threshold {
+                        Some(TripReason::Backlog(backlog))
+                    } else if storm {
+                        Some(TripReason::Sev1Storm(open_sev1))
+                    } else {
+                        None
+                    };
+                    if let Some(reason) = reason {
+                        breaker.state = BreakerState::Open;
+                        breaker.reason = Some(reason.clone());
+                        breaker.opened_at = Some(now);
+                        breaker.trips += 1;
+                        transitions.push(BreakerTransition { department: department.clone(), state: BreakerState::Open, reason: Some(reason) });
+                    }
+                }
+                BreakerState::Open => {
+                    if backlog <= settings.recovery_backlog && !storm {
+                        breaker.state = BreakerState::Closed;
+                        breaker.reason = None;
+                        breaker.opened_at = None;
+                        transitions.push(BreakerTransition { department: department.clone(), state: BreakerState::Closed, reason: None });
+                    }
+                }
+            }
+            if breaker.state == BreakerState::Open {
+                breaker.overloaded_steps += 1;
+            }
+        }
+
+        self.observed_steps += 1;
+        if !self.open_departments().is_empty() {
+            self.overloaded_steps += 1;
+        }
+        transitions
+    }
+
+    /// Admit a message for a department or defer it while the breaker is open
+    pub fn admit(&mut self, settings: &IntakeSettings, department: &str, message: &Message, now: chrono::DateTime<chrono::Utc>) -> Admission {
+        let Some(breaker) = self.breakers.get_mut(department) else {
+            return Admission::Accept;
+        };
+        if breaker.state == BreakerState::Closed || is_critical(message) {
+            return Admission::Accept;
+        }
+
+        breaker.deferred += 1;
+        self.deferred.push_back((department.to_string(), message.clone()));
+        Admission::Defer { retry_after: now + chrono::Duration::minutes(settings.retry_after_minutes) }
+    }
+
+    /// Take deferred messages whose department is accepting intake again
+    pub fn release(&mut self) -> Vec<Message> {
+        let (released, held): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut self.deferred)
+            .into_iter()
+            .partition(|(department, _)| !self.is_open(department));
+        self.deferred = held;
+        released.into_iter().map(|(_, message)| message).collect()
+    }
+
+    /// Whether a department's breaker is open
+    pub fn is_open(&self, department: &str) -> bool {
+        self.breakers.get(department).is_some_and(|b| b.state == BreakerState::Open)
+    }
+
+    /// Departments currently shedding intake
+    pub fn open_departments(&self) -> Vec<&str> {
+        self.breakers.iter()
+            .filter(|(_, breaker)| breaker.state == BreakerState::Open)
+            .map(|(department, _)| department.as_str())
+            .collect()
+    }
+
+    /// Breaker of every department seen so far
+    pub fn breakers(&self) -> &BTreeMap<String, DepartmentBreaker> {
+        &self.breakers
+    }
+
+    /// Number of messages currently held back
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Fraction of evaluations during which the company ran in overload
+    pub fn overload_share(&self) -> f64 {
+        if self.observed_steps == 0 {
+            0.0
+        } else {
+            self.overloaded_steps as f64 / self.observed_steps as f64
+        }
+    }
+}
+
+/// Critical messages bypass open breakers
+fn is_critical(message: &Message) -> bool {
+    !matches!(message.priority, MessagePriority::Low | MessagePriority::Normal)
+        || ALWAYS_ADMITTED.contains(&message.message_type.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::test_message;
+    use uuid::Uuid;
+
+    fn message(message_type: &str, priority: MessagePriority) -> Message {
+        Message {
+            content: "Please build a dashboard".to_string(),
+            priority,
+            ..test_message(Uuid::new_v4(), Uuid::new_v4(), message_type)
+        }
+    }
+
+    #[test]
+    fn test_backlog_trips_and_recovers_with_hysteresis() {
+        let settings = IntakeSettings::default();
+        let mut intake = IntakeController::new();
+        let now = chrono::Utc::now();
+        let backlog = |n: usize| HashMap::from([("Ops".to_string(), n)]);
+
+        let opened = intake.evaluate(&settings, &backlog(settings.backlog_threshold), 0, now);
+        assert_eq!(opened[0].reason, Some(TripReason::Backlog(settings.backlog_threshold)));
+
+        let request = message("feature_request", MessagePriority::Normal);
+        assert!(matches!(intake.admit(&settings, "Ops", &request, now), Admission::Defer { .. }));
+        assert_eq!(intake.admit(&settings, "Ops", &message("declare_incident", MessagePriority::Normal), now), Admission::Accept);
+        assert_eq!(intake.admit(&settings, "Ops", &message("status_update", MessagePriority::High), now), Admission::Accept);
+
+        // Below the threshold but above recovery keeps shedding
+        assert!(intake.evaluate(&settings, &backlog(settings.recovery_backlog + 1), 0, now).is_empty());
+        assert!(intake.release().is_empty());
+
+        let closed = intake.evaluate(&settings, &backlog(settings.recovery_backlog), 0, now);
+        assert_eq!(closed[0].state, BreakerState::Closed);
+        assert_eq!(intake.release().iter().map(|m| m.id).collect::<Vec<_>>(), vec![request.id]);
+        assert_eq!(intake.overloaded_steps, 2);
+        assert!((intake.overload_share() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sev1_storm_opens_every_department() {
+        let settings = IntakeSettings::default();
+        let mut intake = IntakeController::new();
+        let backlogs = HashMap::from([("DevOps".to_string(), 0), ("Marketing".to_string(), 1)]);
+
+        let transitions = intake.evaluate(&settings, &backlogs, settings.sev1_storm_threshold, chrono::Utc::now());
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(intake.open_departments(), vec!["DevOps", "Marketing"]);
+
+        intake.evaluate(&settings, &backlogs, 0, chrono::Utc::now());
+        assert!(intake.open_departments().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! IP Address Management - CIDR Pools for Network Segments
+//!
+//! This module implements the addresses Networking hands out:
+//! - CIDR parsing and validation; a CIDR with host bits set is rejected
+//!   rather than silently truncated
+//! - Overlap detection between the pools of different segments
+//! - Allocation of the lowest free host address in a pool, of a specific
+//!   address, and release of addresses no longer used
+//!
+//! Every network segment has a pool; devices and load balancer backends
+//! take their addresses from the pool of the segment they sit in.
+
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+/// Parse a CIDR such as `10.0.1.0/24`
+pub fn parse_cidr(cidr: &str) -> Result<IpNet, IpamError> {
+    let network: IpNet = cidr.trim().parse().map_err(|e: ipnet::AddrParseError| IpamError::InvalidCidr(cidr.to_string(), e.to_string()))?;
+    if network.trunc() != network {
+        return Err(IpamError::InvalidCidr(cidr.to_string(), format!("host bits set; did you mean {}?", network.trunc())));
+    }
+    Ok(network)
+}
+
+/// Whether two networks share any address
+pub fn overlaps(a: &IpNet, b: &IpNet) -> bool {
+    a.contains(&b.network()) || b.contains(&a.network())
+}
+
+/// Addresses of one CIDR and which of them are taken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressPool {
+    #[serde(with = "cidr_string")]
+    network: IpNet,
+    allocated: BTreeSet<IpAddr>,
+}
+
+impl AddressPool {
+    pub fn new(cidr: &str) -> Result<Self, IpamError> {
+        Ok(Self { network: parse_cidr(cidr)?, allocated: BTreeSet::new() })
+    }
+
+    pub fn network(&self) -> &IpNet {
+        &self.network
+    }
+
+    pub fn allocated(&self) -> impl Iterator<Item = &IpAddr> {
+        self.allocated.iter()
+    }
+
+    /// Host addresses still free; the network and broadcast addresses of an
+    /// IPv4 pool are never handed out
+    pub fn available(&self) -> u128 {
+        let hosts: u128 = match self.network {
+            IpNet::V4(network) => match network.prefix_len() {
+                32 => 1,
+                31 => 2,
+                prefix => (1u128 << (32 - prefix)) - 2,
+            },
+            IpNet::V6(network) => 1u128.checked_shl(128 - u32::from(network.prefix_len())).unwrap_or(u128::MAX),
+        };
+        hosts.saturating_sub(self.allocated.len() as u128)
+    }
+
+    /// Take the lowest free host address
+    pub fn allocate(&mut self) -> Result<IpAddr, IpamError> {
+        let address = self.network.hosts()
+            .find(|address| !self.allocated.contains(address))
+            .ok_or_else(|| IpamError::Exhausted(self.network.to_string()))?;
+        self.allocated.insert(address);
+        Ok(address)
+    }
+
+    /// Take a specific address
+    pub fn claim(&mut self, address: IpAddr) -> Result<(), IpamError> {
+        if !self.network.contains(&address) {
+            return Err(IpamError::OutsidePool(address, self.network.to_string()));
+        }
+        if !self.allocated.insert(address) {
+            return Err(IpamError::InUse(address));
+        }
+        Ok(())
+    }
+
+    /// Give an address back; returns whether it was taken
+    pub fn release(&mut self, address: IpAddr) -> bool {
+        self.allocated.remove(&address)
+    }
+}
+
+/// A CIDR as its text form
+mod cidr_string {
+    use ipnet::IpNet;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(network: &IpNet, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(network)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpNet, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IpamError {
+    #[error("Invalid CIDR '{0}': {1}")]
+    InvalidCidr(String, String),
+
+    #[error("Segment {0} overlaps segment {1}")]
+    Overlap(String, String),
+
+    #[error("No free addresses left in {0}")]
+    Exhausted(String),
+
+    #[error("Address {0} is outside {1}")]
+    OutsidePool(IpAddr, String),
+
+    #[error("Address {0} is already allocated")]
+    InUse(IpAddr),
+
+    #[error("No segment named {0}")]
+    UnknownSegment(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr_validation_overlap_and_allocation() {
+        assert!(matches!(parse_cidr("10.0.1.0/33"), Err(IpamError::InvalidCidr(..))));
+        assert!(matches!(parse_cidr("web-tier"), Err(IpamError::InvalidCidr(..))));
+        assert!(matches!(parse_cidr("10.0.1.7/24"), Err(IpamError::InvalidCidr(..))));
+        let wide = parse_cidr("10.0.0.0/16").unwrap();
+        assert!(overlaps(&wide, &parse_cidr("10.0.1.0/24").unwrap()));
+        assert!(!overlaps(&parse_cidr("10.0.1.0/24").unwrap(), &parse_cidr("10.0.2.0/24").unwrap()));
+
+        let mut pool = AddressPool::new("192.168.0.0/30").unwrap();
+        assert_eq!(pool.available(), 2);
+        let first = pool.allocate().unwrap();
+        assert_eq!(first, "192.168.0.1".parse::<IpAddr>().unwrap());
+        assert!(matches!(pool.claim(first), Err(IpamError::InUse(_))));
+        assert!(matches!(pool.claim("10.0.0.1".parse().unwrap()), Err(IpamError::OutsidePool(..))));
+        assert_eq!(pool.allocate().unwrap(), "192.168.0.2".parse::<IpAddr>().unwrap());
+        assert!(matches!(pool.allocate(), Err(IpamError::Exhausted(_))));
+
+        // A released address is handed out again
+        assert!(pool.release(first));
+        assert!(!pool.release(first));
+        assert_eq!(pool.allocate().unwrap(), first);
+    }
+}
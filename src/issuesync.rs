@@ -0,0 +1,602 @@
+//! Issue Sync - Two-Way Mirroring with Jira or GitHub Issues
+//!
+//! This module implements keeping Ops support tickets in step with an
+//! external issue tracker, so humans can work the same queue as the agents:
+//! - Tickets are created as issues and their title and open/closed state
+//!   pushed whenever the simulation changes them
+//! - Issues edited by people (retitled, closed, reopened) are reported back
+//!   as edits for the orchestrator to apply to the ticket
+//! - Ticket-to-issue links are kept in a small state file across restarts
+//!
+//! Built only with the `issue-sync` feature, which pulls in the TLS-capable
+//! HTTP client the tracker APIs need.
+
+use crate::config::{IssueSyncSettings, TrackerSettings};
+use crate::departments::ops::{SupportTicket, TicketStatus};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// Longest a tracker API request may take, so a hung tracker cannot stall the step
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Longest connecting to the tracker may take
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Whether an issue is still being worked on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IssueState {
+    Open,
+    Closed,
+}
+
+/// Fields the simulation owns on a mirrored issue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IssueDraft {
+    pub title: String,
+    pub body: String,
+    pub state: IssueState,
+}
+
+impl IssueDraft {
+    /// Issue content mirroring a ticket
+    pub fn from_ticket(ticket: &SupportTicket) -> Self {
+        let state = match ticket.status {
+            TicketStatus::Resolved | TicketStatus::Closed => IssueState::Closed,
+            TicketStatus::Open | TicketStatus::InProgress | TicketStatus::PendingCustomer => IssueState::Open,
+        };
+        let body = format!("{}\n\nPriority: {:?}\nCustomer: {} ({:?})\nSimulation ticket: {}",
+                           ticket.description, ticket.priority,
+                           ticket.customer_id.as_deref().unwrap_or("none"), ticket.customer_tier, ticket.id);
+        Self { title: ticket.title.clone(), body, state }
+    }
+}
+
+/// An issue as the tracker reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalIssue {
+    /// Issue number on GitHub, issue key on Jira
+    pub key: String,
+    pub title: String,
+    pub state: IssueState,
+}
+
+/// Ticket mirrored as an issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueLink {
+    pub key: String,
+    /// Content last written to, or read from, the issue
+    pub synced: IssueDraft,
+}
+
+/// Change a person made to a mirrored issue
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteEdit {
+    pub ticket_id: Uuid,
+    pub issue_key: String,
+    /// New title, when it was changed
+    pub title: Option<String>,
+    /// New state, when the issue was closed or reopened
+    pub state: Option<IssueState>,
+}
+
+/// Outcome of one sync round
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    pub created: usize,
+    pub updated: usize,
+    pub edits: Vec<RemoteEdit>,
+    /// Tickets whose push failed, with the error; they are retried next round
+    pub failures: Vec<(Uuid, String)>,
+}
+
+/// Issue tracker API used by the sync
+#[async_trait]
+pub trait IssueTracker: Send + Sync + std::fmt::Debug {
+    /// Tracker name for logs, e.g. "GitHub"
+    fn name(&self) -> &'static str;
+
+    async fn create_issue(&self, draft: &IssueDraft) -> Result<ExternalIssue, SyncError>;
+
+    async fn update_issue(&self, key: &str, draft: &IssueDraft) -> Result<(), SyncError>;
+
+    /// Labeled issues updated in the last `minutes` minutes of real time
+    async fn updated_issues(&self, minutes: i64) -> Result<Vec<ExternalIssue>, SyncError>;
+}
+
+/// Keeps tickets and their issues in step
+#[derive(Debug)]
+pub struct IssueSync {
+    tracker: Box<dyn IssueTracker>,
+    settings: IssueSyncSettings,
+    links: BTreeMap<Uuid, IssueLink>,
+    /// Simulated time of the last round
+    last_sync_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Real time the tracker was last polled
+    last_poll_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl IssueSync {
+    /// Build the sync for the configured tracker, or `None` when sync is off
+    pub fn from_settings(settings: &IssueSyncSettings) -> Result<Option<Self>, SyncError> {
+        let client = || reqwest::Client::builder()
+            .user_agent(concat!("aivertco/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(SyncError::Http);
+        let tracker: Box<dyn IssueTracker> = match &settings.tracker {
+            None => return Ok(None),
+            Some(TrackerSettings::Github { repo, api_url, token_env }) => Box::new(GitHubIssues {
+                client: client()?,
+                api_url: api_url.trim_end_matches('/').to_string(),
+                repo: repo.clone(),
+                token: read_token(token_env)?,
+                label: settings.label.clone(),
+            }),
+            Some(TrackerSettings::Jira { base_url, project_key, email, issue_type, token_env }) => Box::new(JiraProject {
+                client: client()?,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                project_key: project_key.clone(),
+                issue_type: issue_type.clone(),
+                email: email.clone(),
+                token: read_token(token_env)?,
+                label: settings.label.clone(),
+            }),
+        };
+        Self::with_tracker(tracker, settings.clone()).map(Some)
+    }
+
+    /// Build the sync around a tracker, loading links saved by an earlier run
+    pub fn with_tracker(tracker: Box<dyn IssueTracker>, settings: IssueSyncSettings) -> Result<Self, SyncError> {
+        let links = match std::fs::read_to_string(&settings.state_path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(SyncError::Io(e)),
+        };
+        Ok(Self { tracker, settings, links, last_sync_at: None, last_poll_at: None })
+    }
+
+    /// Name of the tracker being synced with
+    pub fn tracker_name(&self) -> &'static str {
+        self.tracker.name()
+    }
+
+    /// Whether the next round is due at simulated time `now`
+    pub fn due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        !self.last_sync_at.is_some_and(|last| now - last < chrono::Duration::minutes(self.settings.interval_minutes))
+    }
+
+    /// Pull edits people made to issues, then push ticket changes
+    ///
+    /// Tickets edited remotely are not pushed in the same round, so the
+    /// returned edits win over whatever the simulation did meanwhile.
+    pub async fn sync(&mut self, tickets: &[&SupportTicket], now: chrono::DateTime<chrono::Utc>) -> Result<SyncReport, SyncError> {
+        let mut report = SyncReport::default();
+        let polled_at = chrono::Utc::now();
+
+        // Poll with a minute of overlap; unchanged issues are ignored below
+        let minutes = self.last_poll_at.map_or(24 * 60, |last| (polled_at - last).num_minutes() + 1);
+        let updated = self.tracker.updated_issues(minutes).await?;
+        let mut edited = HashSet::new();
+        for issue in updated {
+            let Some((&ticket_id, link)) = self.links.iter_mut().find(|(_, link)| link.key == issue.key) else {
+                continue;
+            };
+            let edit = RemoteEdit {
+                ticket_id,
+                issue_key: issue.key.clone(),
+                title: (issue.title != link.synced.title).then(|| issue.title.clone()),
+                state: (issue.state != link.synced.state).then_some(issue.state),
+            };
+            if edit.title.is_some() || edit.state.is_some() {
+                link.synced.title = issue.title;
+                link.synced.state = issue.state;
+                edited.insert(ticket_id);
+                report.edits.push(edit);
+            }
+        }
+        self.last_poll_at = Some(polled_at);
+
+        let mut writes = 0;
+        for ticket in tickets {
+            if writes == self.settings.max_writes_per_sync {
+                break;
+            }
+            if edited.contains(&ticket.id) {
+                continue;
+            }
+            let draft = IssueDraft::from_ticket(ticket);
+            let pushed = match self.links.get(&ticket.id) {
+                None => self.create_linked(ticket.id, draft).await.map(|()| report.created += 1),
+                Some(link) if link.synced != draft => self.update_linked(ticket.id, draft).await.map(|()| report.updated += 1),
+                Some(_) => continue,
+            };
+            if let Err(e) = pushed {
+                report.failures.push((ticket.id, e.to_string()));
+            }
+            writes += 1;
+        }
+
+        // Links of every issue created are kept, even when later pushes failed
+        self.last_sync_at = Some(now);
+        self.save_links()?;
+        Ok(report)
+    }
+
+    /// Create the ticket's issue and link it right away, so a failure
+    /// closing it cannot lead to a duplicate next round
+    async fn create_linked(&mut self, ticket_id: Uuid, draft: IssueDraft) -> Result<(), SyncError> {
+        let issue = self.tracker.create_issue(&draft).await?;
+        let synced = IssueDraft { state: issue.state, ..draft.clone() };
+        self.links.insert(ticket_id, IssueLink { key: issue.key, synced });
+        // New issues start open; close them if the ticket already is
+        if draft.state != issue.state {
+            self.update_linked(ticket_id, draft).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_linked(&mut self, ticket_id: Uuid, draft: IssueDraft) -> Result<(), SyncError> {
+        let link = self.links.get_mut(&ticket_id).expect("linked ticket");
+        self.tracker.update_issue(&link.key, &draft).await?;
+        link.synced = draft;
+        Ok(())
+    }
+
+    /// Issue key a ticket is mirrored as
+    pub fn issue_key(&self, ticket_id: Uuid) -> Option<&str> {
+        self.links.get(&ticket_id).map(|link| link.key.as_str())
+    }
+
+    fn save_links(&self) -> Result<(), SyncError> {
+        if let Some(dir) = self.settings.state_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.settings.state_path, serde_json::to_string_pretty(&self.links)?)?;
+        Ok(())
+    }
+}
+
+fn read_token(env: &str) -> Result<String, SyncError> {
+    std::env::var(env).map_err(|_| SyncError::MissingToken(env.to_string()))
+}
+
+/// Fail on non-success responses, keeping the body for the error
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, SyncError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        Err(SyncError::Api { status: status.as_u16(), body })
+    }
+}
+
+/// Issues of a GitHub repository
+#[derive(Debug)]
+pub struct GitHubIssues {
+    client: reqwest::Client,
+    api_url: String,
+    repo: String,
+    token: String,
+    label: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubIssue {
+    number: u64,
+    title: String,
+    state: String,
+    /// Set when the "issue" is really a pull request
+    pull_request: Option<serde_json::Value>,
+}
+
+impl GitHubIssue {
+    fn into_issue(self) -> ExternalIssue {
+        ExternalIssue {
+            key: self.number.to_string(),
+            title: self.title,
+            state: if self.state == "closed" { IssueState::Closed } else { IssueState::Open },
+        }
+    }
+}
+
+impl GitHubIssues {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client.request(method, format!("{}/repos/{}{}", self.api_url, self.repo, path))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+    }
+}
+
+#[async_trait]
+impl IssueTracker for GitHubIssues {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    async fn create_issue(&self, draft: &IssueDraft) -> Result<ExternalIssue, SyncError> {
+        let response = self.request(reqwest::Method::POST, "/issues")
+            .json(&json!({ "title": draft.title, "body": draft.body, "labels": [self.label] }))
+            .send().await?;
+        Ok(check(response).await?.json::<GitHubIssue>().await?.into_issue())
+    }
+
+    async fn update_issue(&self, key: &str, draft: &IssueDraft) -> Result<(), SyncError> {
+        let state = match draft.state {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+        };
+        let response = self.request(reqwest::Method::PATCH, &format!("/issues/{}", key))
+            .json(&json!({ "title": draft.title, "body": draft.body, "state": state }))
+            .send().await?;
+        check(response).await?;
+        Ok(())
+    }
+
+    async fn updated_issues(&self, minutes: i64) -> Result<Vec<ExternalIssue>, SyncError> {
+        let since = (chrono::Utc::now() - chrono::Duration::minutes(minutes)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let response = self.request(reqwest::Method::GET, "/issues")
+                .query(&[("labels", self.label.as_str()), ("state", "all"), ("since", since.as_str()), ("per_page", "100")])
+                .query(&[("page", page)])
+                .send().await?;
+            let batch: Vec<GitHubIssue> = check(response).await?.json().await?;
+            let last_page = batch.len() < 100;
+            issues.extend(batch.into_iter().filter(|issue| issue.pull_request.is_none()).map(GitHubIssue::into_issue));
+            if last_page {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+}
+
+/// Issues of a Jira Cloud project
+#[derive(Debug)]
+pub struct JiraProject {
+    client: reqwest::Client,
+    base_url: String,
+    project_key: String,
+    issue_type: String,
+    email: String,
+    token: String,
+    label: String,
+}
+
+impl JiraProject {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client.request(method, format!("{}/rest/api/2{}", self.base_url, path))
+            .basic_auth(&self.email, Some(&self.token))
+    }
+
+    /// Move an issue into the first status of the wanted category
+    ///
+    /// Jira workflows differ per project, so the transition is looked up
+    /// by where it leads rather than by name.
+    async fn transition(&self, key: &str, state: IssueState) -> Result<(), SyncError> {
+        let category = match state {
+            IssueState::Open => "new",
+            IssueState::Closed => "done",
+        };
+        let response = self.request(reqwest::Method::GET, &format!("/issue/{}/transitions", key)).send().await?;
+        let transitions: serde_json::Value = check(response).await?.json().await?;
+        let transition = transitions["transitions"].as_array().into_iter().flatten()
+            .find(|t| t["to"]["statusCategory"]["key"] == category)
+            .and_then(|t| t["id"].as_str())
+            .ok_or_else(|| SyncError::NoTransition(key.to_string(), category))?;
+
+        let response = self.request(reqwest::Method::POST, &format!("/issue/{}/transitions", key))
+            .json(&json!({ "transition": { "id": transition } }))
+            .send().await?;
+        check(response).await?;
+        Ok(())
+    }
+}
+
+fn jira_issue(issue: &serde_json::Value) -> Option<ExternalIssue> {
+    Some(ExternalIssue {
+        key: issue["key"].as_str()?.to_string(),
+        title: issue["fields"]["summary"].as_str()?.to_string(),
+        state: if issue["fields"]["status"]["statusCategory"]["key"] == "done" { IssueState::Closed } else { IssueState::Open },
+    })
+}
+
+#[async_trait]
+impl IssueTracker for JiraProject {
+    fn name(&self) -> &'static str {
+        "Jira"
+    }
+
+    async fn create_issue(&self, draft: &IssueDraft) -> Result<ExternalIssue, SyncError> {
+        let response = self.request(reqwest::Method::POST, "/issue")
+            .json(&json!({ "fields": {
+                "project": { "key": self.project_key },
+                "issuetype": { "name": self.issue_type },
+                "summary": draft.title,
+                "description": draft.body,
+                "labels": [self.label],
+            }}))
+            .send().await?;
+        let created: serde_json::Value = check(response).await?.json().await?;
+        let key = created["key"].as_str().ok_or_else(|| SyncError::Api { status: 201, body: created.to_string() })?;
+        Ok(ExternalIssue { key: key.to_string(), title: draft.title.clone(), state: IssueState::Open })
+    }
+
+    async fn update_issue(&self, key: &str, draft: &IssueDraft) -> Result<(), SyncError> {
+        let response = self.request(reqwest::Method::PUT, &format!("/issue/{}", key))
+            .json(&json!({ "fields": { "summary": draft.title, "description": draft.body } }))
+            .send().await?;
+        check(response).await?;
+
+        let response = self.request(reqwest::Method::GET, &format!("/issue/{}", key))
+            .query(&[("fields", "summary,status")])
+            .send().await?;
+        let current: serde_json::Value = check(response).await?.json().await?;
+        if jira_issue(&current).is_some_and(|issue| issue.state != draft.state) {
+            self.transition(key, draft.state).await?;
+        }
+        Ok(())
+    }
+
+    async fn updated_issues(&self, minutes: i64) -> Result<Vec<ExternalIssue>, SyncError> {
+        let jql = format!("project = \"{}\" AND labels = \"{}\" AND updated >= \"-{}m\"", self.project_key, self.label, minutes);
+        let mut issues = Vec::new();
+        loop {
+            let response = self.request(reqwest::Method::POST, "/search")
+                .json(&json!({ "jql": jql, "fields": ["summary", "status"], "startAt": issues.len(), "maxResults": 100 }))
+                .send().await?;
+            let page: serde_json::Value = check(response).await?.json().await?;
+            let batch: Vec<ExternalIssue> = page["issues"].as_array().into_iter().flatten().filter_map(jira_issue).collect();
+            let total = page["total"].as_u64().unwrap_or(0) as usize;
+            let empty = batch.is_empty();
+            issues.extend(batch);
+            if empty || issues.len() >= total {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+}
+
+/// Issue sync errors
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("Environment variable {0} with the tracker API token is not set")]
+    MissingToken(String),
+
+    #[error("Tracker request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Tracker answered {status}: {body}")]
+    Api { status: u16, body: String },
+
+    #[error("Issue {0} has no transition into a '{1}' status")]
+    NoTransition(String, &'static str),
+
+    #[error("Sync state I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Sync state is not valid JSON: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::ops::{CustomerTier, Priority, TicketWork};
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory tracker whose issues the test edits like a person would
+    #[derive(Debug, Clone, Default)]
+    struct FakeTracker {
+        issues: Arc<Mutex<Vec<ExternalIssue>>>,
+        updated: Arc<Mutex<HashSet<String>>>,
+        /// Updates fail while set, like a tracker having an outage
+        failing: Arc<Mutex<bool>>,
+    }
+
+    #[async_trait]
+    impl IssueTracker for FakeTracker {
+        fn name(&self) -> &'static str {
+            "Fake"
+        }
+
+        async fn create_issue(&self, draft: &IssueDraft) -> Result<ExternalIssue, SyncError> {
+            let mut issues = self.issues.lock().unwrap();
+            let issue = ExternalIssue { key: (issues.len() + 1).to_string(), title: draft.title.clone(), state: IssueState::Open };
+            issues.push(issue.clone());
+            Ok(issue)
+        }
+
+        async fn update_issue(&self, key: &str, draft: &IssueDraft) -> Result<(), SyncError> {
+            if *self.failing.lock().unwrap() {
+                return Err(SyncError::Api { status: 503, body: "unavailable".to_string() });
+            }
+            let mut issues = self.issues.lock().unwrap();
+            let issue = issues.iter_mut().find(|issue| issue.key == key).unwrap();
+            issue.title = draft.title.clone();
+            issue.state = draft.state;
+            self.updated.lock().unwrap().insert(key.to_string());
+            Ok(())
+        }
+
+        async fn updated_issues(&self, _minutes: i64) -> Result<Vec<ExternalIssue>, SyncError> {
+            let updated = std::mem::take(&mut *self.updated.lock().unwrap());
+            Ok(self.issues.lock().unwrap().iter().filter(|issue| updated.contains(&issue.key)).cloned().collect())
+        }
+    }
+
+    fn ticket(title: &str) -> SupportTicket {
+        let now = chrono::Utc::now();
+        SupportTicket {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: "Customer cannot log in".to_string(),
+            priority: Priority::High,
+            status: TicketStatus::Open,
+            customer_id: Some("cust-7".to_string()),
+            customer_tier: CustomerTier::Enterprise,
+            assigned_to: None,
+            created_at: now,
+            updated_at: now,
+            resolved_at: None,
+            closed_at: None,
+            reopen_count: 0,
+            resolution: None,
+            tags: vec![],
+            breaches: vec![],
+            work: TicketWork::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pushes_tickets_and_reports_human_edits() {
+        let state_path = std::env::temp_dir().join(format!("issue-sync-{}.json", Uuid::new_v4()));
+        let settings = IssueSyncSettings { state_path: state_path.clone(), ..IssueSyncSettings::default() };
+        let tracker = FakeTracker::default();
+        let mut sync = IssueSync::with_tracker(Box::new(tracker.clone()), settings.clone()).unwrap();
+        let now = chrono::Utc::now();
+
+        let (mut login, export) = (ticket("Login fails"), ticket("CSV export empty"));
+        let report = sync.sync(&[&login, &export], now).await.unwrap();
+        assert_eq!(report.created, 2);
+
+        // Our own push is not mistaken for a human edit
+        login.status = TicketStatus::Resolved;
+        assert_eq!(sync.sync(&[&login, &export], now).await.unwrap().updated, 1);
+        assert!(sync.sync(&[&login, &export], now).await.unwrap().edits.is_empty());
+
+        // Someone reopens the issue and renames it on the tracker
+        let key = sync.issue_key(login.id).unwrap().to_string();
+        tracker.update_issue(&key, &IssueDraft { title: "Login fails for SSO users".to_string(), body: String::new(), state: IssueState::Open }).await.unwrap();
+        let report = sync.sync(&[&login, &export], now).await.unwrap();
+        assert_eq!(report.edits, vec![RemoteEdit {
+            ticket_id: login.id,
+            issue_key: key.clone(),
+            title: Some("Login fails for SSO users".to_string()),
+            state: Some(IssueState::Open),
+        }]);
+        assert_eq!(report.updated, 0, "the human edit must not be overwritten");
+
+        // An issue created while closing it fails is linked, not created again
+        let mut outage = ticket("Invoice missing");
+        outage.status = TicketStatus::Resolved;
+        *tracker.failing.lock().unwrap() = true;
+        let report = sync.sync(&[&outage], now).await.unwrap();
+        assert_eq!((report.created, report.failures.len()), (1, 1));
+        *tracker.failing.lock().unwrap() = false;
+        let report = sync.sync(&[&outage], now).await.unwrap();
+        assert_eq!((report.created, report.updated), (0, 1));
+        assert_eq!(tracker.issues.lock().unwrap().len(), 3);
+
+        // Links survive a restart
+        let restarted = IssueSync::with_tracker(Box::new(tracker), settings).unwrap();
+        std::fs::remove_file(&state_path).ok();
+        assert_eq!(restarted.issue_key(login.id), Some(key.as_str()));
+    }
+}
@@ -0,0 +1,426 @@
+//! Message Journal - Capture & Replay
+//!
+//! This module implements recording bus traffic and feeding it back:
+//! - An append-only JSONL journal: a header with the configuration and the
+//!   agent roster, then one line per dispatched message with its step and
+//!   simulated time
+//! - Rotation for long runs: the journal is moved aside under a timestamped
+//!   name, a fresh one is started and only the newest rotated ones are kept
+//! - Loading a journal and mapping its agent ids onto the agents of a fresh
+//!   simulation by department and name
+//! - Comparing what agents send during a replay with what they sent when
+//!   the journal was captured, for debugging and regression testing
+
+use crate::communication::Message;
+use crate::config::SimulationConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Current journal format version
+pub const JOURNAL_VERSION: u32 = 1;
+
+/// Divergences kept in a replay report
+const DIVERGENCE_CAPACITY: usize = 100;
+
+/// One line of a journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    /// First line: what is needed to rebuild the simulation
+    Header {
+        version: u32,
+        started_at: chrono::DateTime<chrono::Utc>,
+        config: SimulationConfig,
+        roster: Vec<RosterEntry>,
+    },
+    /// A message dispatched through the bus
+    Message(RecordedMessage),
+}
+
+/// Agent present when the journal was started
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RosterEntry {
+    pub id: Uuid,
+    pub name: String,
+    pub department: String,
+}
+
+/// Dispatched message with when it was dispatched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    /// Position in the journal, counting from 1
+    pub seq: u64,
+    pub step: u64,
+    /// Simulated time of the dispatch
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub message: Message,
+}
+
+/// Appends dispatched messages to a journal file
+#[derive(Debug)]
+pub struct JournalWriter {
+    path: PathBuf,
+    file: std::io::BufWriter<std::fs::File>,
+    seq: u64,
+}
+
+impl JournalWriter {
+    /// Open a journal for appending, writing the header if the file is new
+    ///
+    /// Appending to an existing journal continues its numbering, so a
+    /// resumed run keeps recording into the same file.
+    pub fn open(path: &Path, config: &SimulationConfig, roster: Vec<RosterEntry>, now: chrono::DateTime<chrono::Utc>) -> Result<Self, JournalError> {
+        let existing = match std::fs::File::open(path) {
+            Ok(file) => Some(std::io::BufReader::new(file).lines().count() as u64),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(JournalError::Io(path.to_path_buf(), e)),
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| JournalError::Io(dir.to_path_buf(), e))?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| JournalError::Io(path.to_path_buf(), e))?;
+
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            file: std::io::BufWriter::new(file),
+            // The header takes the first line
+            seq: existing.map_or(0, |lines| lines.saturating_sub(1)),
+        };
+        if existing.unwrap_or(0) == 0 {
+            writer.write(&JournalEntry::Header { version: JOURNAL_VERSION, started_at: now, config: config.clone(), roster })?;
+            writer.flush()?;
+        }
+        Ok(writer)
+    }
+
+    /// Record a dispatched message
+    pub fn append(&mut self, step: u64, at: chrono::DateTime<chrono::Utc>, message: &Message) -> Result<(), JournalError> {
+        self.seq += 1;
+        self.write(&JournalEntry::Message(RecordedMessage { seq: self.seq, step, at, message: message.clone() }))
+    }
+
+    /// Push buffered entries to disk
+    pub fn flush(&mut self) -> Result<(), JournalError> {
+        self.file.flush().map_err(|e| JournalError::Io(self.path.clone(), e))
+    }
+
+    /// Messages recorded so far
+    pub fn recorded(&self) -> u64 {
+        self.seq
+    }
+
+    /// Move the journal aside as `<stem>-<time>-step<step>.<ext>` and start a
+    /// new one, keeping the newest `keep` rotated journals; returns the
+    /// rotated file
+    pub fn rotate(&mut self, config: &SimulationConfig, roster: Vec<RosterEntry>, step: u64, now: chrono::DateTime<chrono::Utc>, keep: usize) -> Result<PathBuf, JournalError> {
+        self.flush()?;
+        let path = self.path.clone();
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("journal");
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("jsonl");
+        let prefix = format!("{}-", stem);
+        let rotated = path.with_file_name(format!("{}{}-step{:010}.{}", prefix, now.format("%Y%m%dT%H%M%S"), step, extension));
+        std::fs::rename(&path, &rotated).map_err(|e| JournalError::Io(path.clone(), e))?;
+        *self = Self::open(&path, config, roster, now)?;
+
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut rotated_journals: Vec<PathBuf> = std::fs::read_dir(dir).map_err(|e| JournalError::Io(dir.to_path_buf(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.file_name().and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.contains("-step") && name.ends_with(extension)))
+            .collect();
+        // Timestamped names sort chronologically
+        rotated_journals.sort();
+        let excess = rotated_journals.len().saturating_sub(keep);
+        for old in &rotated_journals[..excess] {
+            std::fs::remove_file(old).map_err(|e| JournalError::Io(old.clone(), e))?;
+        }
+        Ok(rotated)
+    }
+
+    fn write(&mut self, entry: &JournalEntry) -> Result<(), JournalError> {
+        let line = serde_json::to_string(entry).expect("journal entries always serialize");
+        writeln!(self.file, "{}", line).map_err(|e| JournalError::Io(self.path.clone(), e))
+    }
+}
+
+/// A journal read back from disk
+#[derive(Debug, Clone)]
+pub struct Journal {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub config: SimulationConfig,
+    pub roster: Vec<RosterEntry>,
+    pub messages: Vec<RecordedMessage>,
+}
+
+impl Journal {
+    /// Load a journal, reporting the line of the first bad entry
+    pub fn load(path: &Path) -> Result<Self, JournalError> {
+        let file = std::fs::File::open(path).map_err(|e| JournalError::Io(path.to_path_buf(), e))?;
+        let mut header = None;
+        let mut messages = Vec::new();
+
+        for (index, line) in std::io::BufReader::new(file).lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.map_err(|e| JournalError::Io(path.to_path_buf(), e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalEntry = serde_json::from_str(&line)
+                .map_err(|e| JournalError::Parse(path.to_path_buf(), line_number, e.to_string()))?;
+            match (entry, &header) {
+                (JournalEntry::Header { version, started_at, config, roster }, None) => {
+                    if version > JOURNAL_VERSION {
+                        return Err(JournalError::UnsupportedVersion(version));
+                    }
+                    header = Some((started_at, config, roster));
+                }
+                (JournalEntry::Message(recorded), Some(_)) => messages.push(recorded),
+                _ => return Err(JournalError::Parse(path.to_path_buf(), line_number, "header must be the first and only header line".to_string())),
+            }
+        }
+
+        let (started_at, config, roster) = header.ok_or_else(|| JournalError::Parse(path.to_path_buf(), 1, "missing header".to_string()))?;
+        Ok(Self { started_at, config, roster, messages })
+    }
+}
+
+/// Agent that sent more or fewer messages of a type than the journal recorded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub step: u64,
+    /// Agent in the replaying simulation
+    pub agent: Uuid,
+    pub message_type: String,
+    /// Messages sent during the replay minus those in the journal
+    pub difference: i64,
+}
+
+/// Outcome of a replay
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    pub steps: u64,
+    /// Journaled messages fed back into the simulation
+    pub injected: u64,
+    /// Agent messages sent again exactly as recorded
+    pub reproduced: u64,
+    /// Recorded agent messages not sent during the replay
+    pub missing: u64,
+    /// Agent messages sent during the replay but not recorded
+    pub unexpected: u64,
+    /// First divergences, in step order
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Whether agents behaved exactly as recorded
+    pub fn is_faithful(&self) -> bool {
+        self.missing == 0 && self.unexpected == 0
+    }
+}
+
+/// Feeds a journal back into a simulation and checks agents' responses
+#[derive(Debug)]
+pub struct Replay {
+    /// Journal agent id to the id of the same agent in the replay
+    ids: HashMap<Uuid, Uuid>,
+    steps: BTreeMap<u64, Vec<RecordedMessage>>,
+    pub report: ReplayReport,
+}
+
+impl Replay {
+    /// Prepare a replay onto agents matched by department and name
+    pub fn new(journal: Journal, roster: &[RosterEntry]) -> Result<Self, JournalError> {
+        let mut ids = HashMap::new();
+        for recorded in &journal.roster {
+            let replaying = roster.iter()
+                .find(|agent| agent.department == recorded.department && agent.name == recorded.name)
+                .ok_or_else(|| JournalError::UnknownAgent(recorded.name.clone(), recorded.department.clone()))?;
+            ids.insert(recorded.id, replaying.id);
+        }
+
+        let mut steps: BTreeMap<u64, Vec<RecordedMessage>> = BTreeMap::new();
+        for recorded in journal.messages {
+            steps.entry(recorded.step).or_default().push(recorded);
+        }
+        Ok(Self { ids, steps, report: ReplayReport::default() })
+    }
+
+    /// Recorded steps, in order
+    pub fn steps(&self) -> Vec<u64> {
+        self.steps.keys().copied().collect()
+    }
+
+    /// Simulated time a step was recorded at
+    pub fn step_time(&self, step: u64) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.steps.get(&step)?.first().map(|recorded| recorded.at)
+    }
+
+    /// Messages dispatched during a step, addressed to the replaying agents
+    pub fn messages(&mut self, step: u64) -> Vec<Message> {
+        let messages: Vec<Message> = self.steps.get(&step).into_iter().flatten()
+            .map(|recorded| {
+                let mut message = recorded.message.clone();
+                message.from_agent = self.map(message.from_agent);
+                message.to_agent = self.map(message.to_agent);
+                message
+            })
+            .collect();
+        self.report.steps += 1;
+        self.report.injected += messages.len() as u64;
+        messages
+    }
+
+    /// Compare the messages agents sent during a step with the recording
+    ///
+    /// Messages are matched by sender and type; system actors are ignored.
+    pub fn compare(&mut self, step: u64, sent: &[Message]) {
+        let mut counts: BTreeMap<(Uuid, String), i64> = BTreeMap::new();
+        let agents: Vec<Uuid> = self.ids.values().copied().collect();
+        for recorded in self.steps.get(&step).into_iter().flatten() {
+            let sender = self.map(recorded.message.from_agent);
+            if agents.contains(&sender) {
+                *counts.entry((sender, recorded.message.message_type.clone())).or_insert(0) -= 1;
+            }
+        }
+        for message in sent.iter().filter(|message| agents.contains(&message.from_agent)) {
+            *counts.entry((message.from_agent, message.message_type.clone())).or_insert(0) += 1;
+        }
+
+        let recorded = self.steps.get(&step).into_iter().flatten()
+            .filter(|recorded| agents.contains(&self.map(recorded.message.from_agent)))
+            .count() as u64;
+        let mut missing = 0;
+        for ((agent, message_type), difference) in counts {
+            if difference == 0 {
+                continue;
+            }
+            if difference < 0 {
+                missing += difference.unsigned_abs();
+            } else {
+                self.report.unexpected += difference as u64;
+            }
+            if self.report.divergences.len() < DIVERGENCE_CAPACITY {
+                self.report.divergences.push(Divergence { step, agent, message_type, difference });
+            }
+        }
+        self.report.missing += missing;
+        self.report.reproduced += recorded - missing;
+    }
+
+    fn map(&self, id: Uuid) -> Uuid {
+        // System actors keep their well-known ids
+        self.ids.get(&id).copied().unwrap_or(id)
+    }
+}
+
+/// Journal errors
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("Journal I/O error on {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Invalid journal {0} at line {1}: {2}")]
+    Parse(PathBuf, usize, String),
+
+    #[error("Journal version {0} is newer than this build supports")]
+    UnsupportedVersion(u32),
+
+    #[error("Journaled agent {0} ({1}) does not exist in the replaying simulation")]
+    UnknownAgent(String, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::test_message;
+
+
+    fn roster(names: &[&str]) -> Vec<RosterEntry> {
+        names.iter()
+            .map(|name| RosterEntry { id: Uuid::new_v4(), name: name.to_string(), department: "Ops".to_string() })
+            .collect()
+    }
+
+    #[test]
+    fn test_append_and_reopen_continue_numbering() {
+        let path = std::env::temp_dir().join(format!("journal-{}.jsonl", Uuid::new_v4()));
+        let agents = roster(&["Ops Agent 1", "Ops Agent 2"]);
+        let now = chrono::Utc::now();
+
+        let mut writer = JournalWriter::open(&path, &SimulationConfig::default(), agents.clone(), now).unwrap();
+        writer.append(1, now, &test_message(agents[0].id, agents[1].id, "status_update")).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let mut writer = JournalWriter::open(&path, &SimulationConfig::default(), agents.clone(), now).unwrap();
+        writer.append(2, now, &test_message(agents[1].id, agents[0].id, "status_update")).unwrap();
+        writer.flush().unwrap();
+
+        let journal = Journal::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(journal.roster, agents);
+        assert_eq!(journal.messages.iter().map(|m| (m.seq, m.step)).collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_rotation_starts_a_new_journal_and_keeps_the_newest() {
+        let dir = std::env::temp_dir().join(format!("journal-{}", Uuid::new_v4()));
+        let path = dir.join("run.jsonl");
+        let agents = roster(&["Ops Agent 1", "Ops Agent 2"]);
+        let config = SimulationConfig::default();
+        let now = chrono::Utc::now();
+
+        let mut writer = JournalWriter::open(&path, &config, agents.clone(), now).unwrap();
+        let mut rotated = Vec::new();
+        for step in [10, 20, 30] {
+            writer.append(step, now, &test_message(agents[0].id, agents[1].id, "status_update")).unwrap();
+            rotated.push(writer.rotate(&config, agents.clone(), step, now, 2).unwrap());
+        }
+        let current = Journal::load(&path).unwrap();
+        let last_rotated = Journal::load(&rotated[2]).unwrap();
+        let remaining = std::fs::read_dir(&dir).unwrap().count();
+        let oldest_kept = rotated[0].exists();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(current.messages.is_empty());
+        assert_eq!(writer.recorded(), 0);
+        assert_eq!(last_rotated.messages.len(), 1);
+        assert!(!oldest_kept);
+        // The live journal and the two newest rotated ones
+        assert_eq!(remaining, 3);
+    }
+
+    #[test]
+    fn test_replay_maps_agents_and_reports_divergence() {
+        let recorded = roster(&["Ops Agent 1", "Ops Agent 2"]);
+        let replaying = roster(&["Ops Agent 2", "Ops Agent 1"]);
+        let portal = Uuid::new_v4();
+        let now = chrono::Utc::now();
+        let entry = |seq, message| RecordedMessage { seq, step: 3, at: now, message };
+
+        let journal = Journal {
+            started_at: now,
+            config: SimulationConfig::default(),
+            roster: recorded.clone(),
+            messages: vec![
+                entry(1, test_message(portal, recorded[0].id, "create_ticket")),
+                entry(2, test_message(recorded[0].id, recorded[1].id, "escalation")),
+            ],
+        };
+        let mut replay = Replay::new(journal, &replaying).unwrap();
+
+        let injected = replay.messages(3);
+        assert_eq!(injected[0].to_agent, replaying[1].id);
+        assert_eq!(injected[0].from_agent, portal);
+
+        replay.compare(3, &[test_message(replaying[1].id, replaying[0].id, "status_update")]);
+        assert_eq!((replay.report.missing, replay.report.unexpected, replay.report.reproduced), (1, 1, 0));
+        assert_eq!(replay.report.divergences[0], Divergence { step: 3, agent: replaying[1].id, message_type: "escalation".to_string(), difference: -1 });
+        assert!(!replay.report.is_faithful());
+    }
+}
@@ -0,0 +1,358 @@
+//! Knowledge Base - Organizational Memory Shared by Agents
+//!
+//! This module implements what the company remembers from its incidents:
+//! - Resolution articles, written when an incident is resolved: what it
+//!   looked like, the root cause and the fix
+//! - Runbooks, promoted from resolutions that have fixed the same issue
+//!   often enough
+//! - Post-mortems of severe incidents, and of every resolved security
+//!   incident
+//! - Search by keyword similarity, which agents run before working an
+//!   incident
+//! - Effectiveness of the automated runbooks: how often each resolved its
+//!   incident or had to escalate, and how often each step fixed it
+//!
+//! An incident a known fix covers takes a fraction of the usual work; the
+//! orchestrator carries out the lookups and files the articles.
+
+use crate::config::KnowledgeSettings;
+use crate::departments::ops::Incident;
+use crate::forensics::PostMortem;
+use crate::runbook::{RunbookOutcome, RunbookRun};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Articles kept; the oldest go first
+const MAX_ARTICLES: usize = 500;
+
+/// Words too common to tell issues apart
+const STOP_WORDS: &[&str] = &["and", "are", "for", "from", "has", "into", "the", "was", "with"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArticleKind {
+    Resolution,
+    Runbook,
+    PostMortem,
+}
+
+impl ArticleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArticleKind::Resolution => "resolution",
+            ArticleKind::Runbook => "runbook",
+            ArticleKind::PostMortem => "post-mortem",
+        }
+    }
+}
+
+/// Something the company learned from an incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Article {
+    pub id: Uuid,
+    pub kind: ArticleKind,
+    pub title: String,
+    pub services: Vec<String>,
+    /// Words the article is found by
+    pub keywords: BTreeSet<String>,
+    pub root_cause: Option<String>,
+    /// The fix, or the post-mortem write-up
+    pub body: String,
+    pub author: Uuid,
+    pub written_at: chrono::DateTime<chrono::Utc>,
+    /// Incident the article was written from
+    pub incident_id: Uuid,
+    /// Incidents the article has helped resolve since
+    pub uses: u32,
+}
+
+/// An agent's search of the knowledge base before working an incident
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Consultation {
+    pub at: chrono::DateTime<chrono::Utc>,
+    /// Closest resolution or runbook, if any was close enough
+    pub article: Option<Uuid>,
+    /// Share of the usual work the incident now takes
+    pub effort: f64,
+}
+
+/// Words of an incident's title, description and services, lowercased
+pub fn keywords(incident: &Incident) -> BTreeSet<String> {
+    words(&format!("{} {} {}", incident.title, incident.description, incident.affected_services.join(" ")))
+}
+
+/// Words of a text, lowercased, as keywords are taken
+pub fn words(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.len() >= 3 && !word.chars().all(|c| c.is_ascii_digit()) && !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Shared keywords over all keywords, from 0 to 1
+pub fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// How an automated runbook has done so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunbookStats {
+    pub runs: u32,
+    pub resolved: u32,
+    pub escalated: u32,
+    /// Times each step was tried, in runbook order
+    pub step_attempts: Vec<u32>,
+    /// Times each step fixed the incident
+    pub step_fixes: Vec<u32>,
+    /// Minutes the runs that resolved their incident took in all
+    pub minutes_to_resolve: i64,
+}
+
+impl RunbookStats {
+    /// Share of finished runs that resolved their incident
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.runs > 0).then(|| f64::from(self.resolved) / f64::from(self.runs))
+    }
+
+    pub fn mean_minutes_to_resolve(&self) -> Option<f64> {
+        (self.resolved > 0).then(|| self.minutes_to_resolve as f64 / f64::from(self.resolved))
+    }
+}
+
+/// Articles every agent can search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeBase {
+    articles: Vec<Article>,
+    lookups: u32,
+    hits: u32,
+    /// Effectiveness of each automated runbook, by name
+    #[serde(default)]
+    runbooks: BTreeMap<String, RunbookStats>,
+}
+
+impl KnowledgeBase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn articles(&self) -> &[Article] {
+        &self.articles
+    }
+
+    pub fn article(&self, id: Uuid) -> Option<&Article> {
+        self.articles.iter().find(|article| article.id == id)
+    }
+
+    /// Lookups made and how many found a known fix
+    pub fn stats(&self) -> (u32, u32) {
+        (self.lookups, self.hits)
+    }
+
+    /// Closest resolution or runbook at least `min_similarity` alike
+    pub fn search(&self, keywords: &BTreeSet<String>, min_similarity: f64) -> Option<&Article> {
+        self.articles.iter()
+            .filter(|article| article.kind != ArticleKind::PostMortem)
+            .map(|article| (article, similarity(keywords, &article.keywords)))
+            .filter(|(_, score)| *score >= min_similarity)
+            // Runbooks win ties; they are the better-proven fix
+            .max_by(|(a, a_score), (b, b_score)| a_score.total_cmp(b_score).then((a.kind == ArticleKind::Runbook).cmp(&(b.kind == ArticleKind::Runbook))))
+            .map(|(article, _)| article)
+    }
+
+    /// Search before working an incident; a resolution used often enough
+    /// becomes a runbook
+    pub fn consult(&mut self, incident: &Incident, settings: &KnowledgeSettings, now: chrono::DateTime<chrono::Utc>) -> Consultation {
+        self.lookups += 1;
+        let found = self.search(&keywords(incident), settings.min_similarity).map(|article| article.id);
+        let Some(article) = found.and_then(|id| self.articles.iter_mut().find(|article| article.id == id)) else {
+            return Consultation { at: now, article: None, effort: 1.0 };
+        };
+        self.hits += 1;
+        article.uses += 1;
+        if article.kind == ArticleKind::Resolution && article.uses >= settings.runbook_after_uses {
+            article.kind = ArticleKind::Runbook;
+            println!("📘 '{}' became a runbook after {} uses", article.title, article.uses);
+        }
+        let effort = match article.kind {
+            ArticleKind::Runbook => settings.runbook_effort,
+            _ => settings.known_fix_effort,
+        };
+        Consultation { at: now, article: Some(article.id), effort }
+    }
+
+    /// File how a resolved incident was fixed, unless a known fix already
+    /// covers it
+    pub fn record_resolution(&mut self, incident: &Incident, author: Uuid, now: chrono::DateTime<chrono::Utc>, min_similarity: f64) -> Option<Uuid> {
+        let keywords = keywords(incident);
+        if self.search(&keywords, min_similarity).is_some() {
+            return None;
+        }
+        Some(self.file(Article {
+            id: Uuid::new_v4(),
+            kind: ArticleKind::Resolution,
+            title: incident.title.clone(),
+            services: incident.affected_services.clone(),
+            keywords,
+            root_cause: incident.root_cause.clone(),
+            body: incident.resolution.clone().unwrap_or_default(),
+            author,
+            written_at: now,
+            incident_id: incident.id,
+            uses: 0,
+        }))
+    }
+
+    /// File the post-mortem of an incident resolved after `hours`
+    pub fn record_post_mortem(&mut self, incident: &Incident, hours: f64, author: Uuid, now: chrono::DateTime<chrono::Utc>) -> Uuid {
+        let body = format!("{:?} incident resolved after {:.1}h with {} SLA target(s) missed. Root cause: {}. Fix: {}.",
+                           incident.severity, hours, incident.breaches.len(),
+                           incident.root_cause.as_deref().unwrap_or("unknown"),
+                           incident.resolution.as_deref().unwrap_or("none recorded"));
+        self.file(Article {
+            id: Uuid::new_v4(),
+            kind: ArticleKind::PostMortem,
+            title: format!("Post-mortem: {}", incident.title),
+            services: incident.affected_services.clone(),
+            keywords: keywords(incident),
+            root_cause: incident.root_cause.clone(),
+            body,
+            author,
+            written_at: now,
+            incident_id: incident.id,
+            uses: 0,
+        })
+    }
+
+    /// File the post-mortem of a resolved security incident
+    pub fn record_security_post_mortem(&mut self, post_mortem: &PostMortem, author: Uuid, now: chrono::DateTime<chrono::Utc>) -> Uuid {
+        self.file(Article {
+            id: Uuid::new_v4(),
+            kind: ArticleKind::PostMortem,
+            title: format!("Post-mortem: {}", post_mortem.title),
+            services: post_mortem.affected_systems.clone(),
+            keywords: words(&format!("{} {}", post_mortem.title, post_mortem.affected_systems.join(" "))),
+            root_cause: Some(post_mortem.root_cause.description().to_string()),
+            body: post_mortem.render(),
+            author,
+            written_at: now,
+            incident_id: post_mortem.incident_id,
+            uses: 0,
+        })
+    }
+
+    /// Record how a finished runbook run went
+    pub fn record_runbook(&mut self, run: &RunbookRun, now: chrono::DateTime<chrono::Utc>) {
+        let Some(outcome) = run.outcome else {
+            return;
+        };
+        let stats = self.runbooks.entry(run.runbook.clone()).or_default();
+        stats.runs += 1;
+        if stats.step_attempts.len() < run.steps_tried {
+            stats.step_attempts.resize(run.steps_tried, 0);
+            stats.step_fixes.resize(run.steps_tried, 0);
+        }
+        for attempts in &mut stats.step_attempts[..run.steps_tried] {
+            *attempts += 1;
+        }
+        match outcome {
+            RunbookOutcome::Resolved => {
+                stats.resolved += 1;
+                stats.step_fixes[run.steps_tried - 1] += 1;
+                stats.minutes_to_resolve += run.minutes(now);
+            }
+            RunbookOutcome::Escalated => stats.escalated += 1,
+        }
+    }
+
+    pub fn runbook_stats(&self) -> &BTreeMap<String, RunbookStats> {
+        &self.runbooks
+    }
+
+    fn file(&mut self, article: Article) -> Uuid {
+        let id = article.id;
+        self.articles.push(article);
+        if self.articles.len() > MAX_ARTICLES {
+            self.articles.remove(0);
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::ops::{IncidentStatus, Severity};
+
+    fn incident(title: &str, description: &str) -> Incident {
+        Incident {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: description.to_string(),
+            severity: Severity::Sev2,
+            status: IncidentStatus::Resolved,
+            affected_services: vec!["api-gateway".to_string()],
+            root_cause: Some("Connection pool exhausted".to_string()),
+            resolution: Some("Raised the pool size and restarted".to_string()),
+            created_at: chrono::Utc::now(),
+            resolved_at: Some(chrono::Utc::now()),
+            assigned_team: None,
+            duplicate_of: None,
+            duplicates: vec![],
+            correlation_ids: vec![],
+            status_history: vec![],
+            breaches: vec![],
+            knowledge: None,
+            runbook: None,
+        }
+    }
+
+    #[test]
+    fn test_recurring_issue_finds_fix_and_becomes_runbook() {
+        let settings = KnowledgeSettings { min_similarity: 0.5, runbook_after_uses: 2, known_fix_effort: 0.5, runbook_effort: 0.25, ..KnowledgeSettings::default() };
+        let now = chrono::Utc::now();
+        let author = Uuid::new_v4();
+        let mut knowledge = KnowledgeBase::new();
+
+        let first = incident("Degraded api-gateway-1", "High CPU usage detected on api-gateway-1");
+        assert!(knowledge.record_resolution(&first, author, now, settings.min_similarity).is_some());
+        // The same issue again is already covered
+        assert!(knowledge.record_resolution(&first, author, now, settings.min_similarity).is_none());
+
+        let unrelated = incident("Security Incident - Suspicious Activity", "Brute force logins on the customer portal");
+        assert_eq!(knowledge.consult(&unrelated, &settings, now).effort, 1.0);
+
+        let recurrence = incident("Degraded api-gateway-1", "High CPU usage detected on api-gateway-1 again");
+        assert_eq!(knowledge.consult(&recurrence, &settings, now).effort, 0.5);
+        assert_eq!(knowledge.consult(&recurrence, &settings, now).effort, 0.25);
+        assert_eq!(knowledge.articles()[0].kind, ArticleKind::Runbook);
+        assert_eq!(knowledge.stats(), (3, 2));
+
+        knowledge.record_post_mortem(&first, 3.0, author, now);
+        assert!(knowledge.articles()[1].body.contains("Connection pool exhausted"));
+    }
+
+    #[test]
+    fn test_runbook_runs_accumulate_effectiveness() {
+        let now = chrono::Utc::now();
+        let run = |steps_tried: usize, outcome: RunbookOutcome| RunbookRun {
+            runbook: "High CPU".to_string(),
+            started_at: now - chrono::Duration::minutes(15 * steps_tried as i64),
+            next_step_at: now,
+            steps_tried,
+            outcome: Some(outcome),
+        };
+        let mut knowledge = KnowledgeBase::new();
+        knowledge.record_runbook(&run(1, RunbookOutcome::Resolved), now);
+        knowledge.record_runbook(&run(2, RunbookOutcome::Resolved), now);
+        knowledge.record_runbook(&run(3, RunbookOutcome::Escalated), now);
+
+        let stats = &knowledge.runbook_stats()["High CPU"];
+        assert_eq!((stats.runs, stats.resolved, stats.escalated), (3, 2, 1));
+        assert_eq!((stats.step_attempts.as_slice(), stats.step_fixes.as_slice()), (&[3, 2, 1][..], &[1, 1, 0][..]));
+        assert_eq!(stats.mean_minutes_to_resolve(), Some(22.5));
+    }
+}
@@ -5,114 +5,593 @@
 //! company simulation where AI agents work together to deliver projects,
 //! maintain infrastructure, ensure security, and provide customer support.
 
-use std::collections::HashMap;
+use chrono::Datelike;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 mod agents;
+mod agentstate;
+mod alerting;
+mod analytics;
+#[cfg(feature = "api")]
+mod api;
+mod assignment;
+mod autoscale;
+mod backup;
+mod balancer;
+mod capacity;
+mod catalog;
+mod certs;
+mod cli;
+mod cluster;
 mod communication;
+mod config;
+mod console;
+mod control;
+mod daemon;
+mod ddos;
 mod departments;
+mod deptreport;
+mod dns;
+mod dlp;
+mod doctor;
+mod economy;
+mod email;
+mod executive;
+mod firewall;
+mod flowlog;
+mod forensics;
+#[cfg(feature = "sqlite")]
+mod history;
+mod hooks;
+mod iam;
+mod infra;
+mod intake;
+mod ipam;
+#[cfg(feature = "issue-sync")]
+mod issuesync;
+mod journal;
+mod knowledge;
+mod memory;
+mod metrics;
+mod orgchart;
+mod paging;
+mod patching;
+mod personality;
+mod phishing;
+mod pipeline;
+mod payload;
+mod persistence;
+mod policy;
+mod progression;
 mod projects;
+mod promotion;
+mod quota;
+mod redteam;
+mod registry;
+mod releasenotes;
+mod remediation;
+mod report;
+mod risk;
+mod routing;
+mod runbook;
+mod runtime;
+mod scheduler;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod scorecard;
+mod secpolicy;
+mod shifts;
+mod snapshot;
+mod staffing;
+mod statuspage;
+mod statusreport;
+mod supervisor;
+mod synthetic;
+mod system;
+mod taskgraph;
+mod templates;
+#[cfg(test)]
+mod testsupport;
+mod threads;
+mod threatintel;
+mod ticketqueue;
+mod timeline;
+mod topology;
+mod traffic;
+mod transcripts;
+mod vcs;
+mod vulnscan;
+mod waitgraph;
+mod wan;
+mod webhooks;
 
 use agents::{Agent, AgentTrait, Department};
+use agentstate::{AgentState, AgentStates, Signals};
+use alerting::{AlertChange, AlertEngine};
+use analytics::{Kpi, KpiCounters, KpiHistory, ReportPeriod};
+use assignment::{Assignment, Candidate, WorkKind};
+use autoscale::ScalingPolicy;
+use capacity::{CapacityPlanner, Resource};
+use catalog::{FaultKind, ServiceCatalog};
+use clap::Parser;
+use cli::{Cli, Command, RunArgs};
 use communication::{Message, MessageBus, MessagePriority};
-use departments::devops::DevOpsAgent;
-use departments::infosec::InfoSecAgent;
-use departments::networking::NetworkingAgent;
-use departments::ops::OpsAgent;
+use config::SimulationConfig;
+use console::{Console, Control};
+use daemon::Daemon;
+use departments::devops::{DeploymentStatus, DeploymentStrategy, ServerState, ServerStatus};
+use departments::marketing::CampaignReport;
+use departments::networking::ServiceStatus;
+use departments::ops::{ChangeEventKind, CustomerTier, IncidentStatus, Priority, ServiceHealth, Severity, TicketEventKind, TicketRequest, TicketStatus};
+use departments::{AgentError, DepartmentAgent};
+use deptreport::{ManagerReports, StatusUpdate};
+use dlp::DlpMonitor;
+use economy::{Economy, Entry};
+use email::EmailGateway;
+use executive::{CompanyView, ExecutiveAgent};
+use flowlog::{FlowLogWriter, FlowRecord};
+use iam::IdentityDirectory;
+use infra::InfraPlan;
+use intake::{Admission, BreakerState, IntakeController, TripReason};
+use journal::{Journal, JournalWriter, Replay, ReplayReport, RosterEntry};
+use knowledge::{Consultation, KnowledgeBase};
+use memory::{AgentMemories, CompletedTask};
+use metrics::{Metrics, MetricsSample};
+use orgchart::{Approval, ApprovalKind, Approver, OrgChart, WorkStatus};
+use paging::{Page, Pager};
+use payload::MessagePayload;
+use patching::{Advisory, WindowEvent};
+use personality::Personality;
+use phishing::AwarenessProgram;
+use pipeline::{BuildQueue, PipelineStatus, Stage};
+use policy::{AgentTurn, DecisionPolicy, PolicyError, RandomPolicy};
+use progression::SkillProgression;
+use promotion::{PromotionAction, PromotionStatus, Promotions};
+use quota::{BlockedDeployment, QuotaError, QuotaLedger, QuotaScope};
+use redteam::{AttackSimulator, Defenses};
+use registry::AgentRegistry;
+use releasenotes::{ReleaseLog, ReleaseNotes};
+use report::{CompanyReport, ReportKind};
+use risk::{RiskCategory, RiskFinding, RiskRegister};
+use routing::{DeliveryStatus, FailureOutcome, MessageRouter};
+use runbook::{RunbookOutcome, RunbookRun};
+use runtime::{AgentInfo, AgentRuntime, RuntimeError};
+use scheduler::{EventKind, Scheduler};
+use scorecard::{Dimension, ScorecardBook};
+use secpolicy::PolicyEvidence;
+use snapshot::{SimulationSnapshot, SNAPSHOT_VERSION};
+use shifts::{HandoffItem, HandoffKind, HandoffNote, ShiftChange, ShiftRoster};
+use staffing::{DepartmentLoad, StaffingChange, StaffingError, StaffingEvent, StaffingLog};
+use statuspage::StatusPage;
+use statusreport::AgentStatusReport;
+use supervisor::{Recovery, Supervisor};
+use system::SystemActor;
+use taskgraph::TaskGraph;
+use templates::{ReportTemplate, TemplatePack};
+use threads::ConversationThreads;
+use threatintel::ThreatIntel;
+use ticketqueue::{QueueAgent, TicketQueue};
+use traffic::ServiceDemand;
+use vcs::{Applied, Repository, Signature, VcsRequest};
+use waitgraph::WaitForGraph;
+use webhooks::{EntityChange, EntityTracker, TrackedEntity, WebhookDispatcher};
+
+/// Why a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopReason {
+    /// `max_steps` reached
+    MaxSteps(u64),
+    /// `max_wall_seconds` of real time used up
+    Duration(u64),
+    /// `max_sim_days` of simulated time covered
+    SimDays(u32),
+    Signal(&'static str),
+    /// Nothing left on the scheduler
+    NoEvents,
+    /// Cash fell below `economy.bankruptcy_cash`
+    Bankrupt,
+}
+
+impl StopReason {
+    fn describe(&self) -> String {
+        match self {
+            StopReason::MaxSteps(steps) => format!("reached the step limit of {}", steps),
+            StopReason::Duration(seconds) => format!("reached the time limit of {}", format_duration(*seconds)),
+            StopReason::SimDays(days) => format!("covered the limit of {} simulated day(s)", days),
+            StopReason::Signal(signal) => format!("stopped by {}", signal),
+            StopReason::NoEvents => "no more scheduled events".to_string(),
+            StopReason::Bankrupt => "the company went bankrupt".to_string(),
+        }
+    }
+}
+
+/// Seconds as the largest unit that divides them, e.g. "2h" or "90s"
+fn format_duration(seconds: u64) -> String {
+    match seconds {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Number of dispatched messages kept for incident timelines
+const MESSAGE_LOG_CAPACITY: usize = 5_000;
+
+/// Conversation threads listed by `threads`, most recently active first
+const THREAD_LISTING: usize = 10;
+
+/// Longest real-time sleep between scheduler checks
+const MAX_REAL_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Compliance score in percent below which a standard is a risk
+const COMPLIANCE_TARGET: u8 = 90;
 
 /// Main simulation orchestrator
 #[derive(Debug)]
 struct CompanySimulation {
-    /// All agents in the company
-    agents: HashMap<Uuid, Box<dyn AgentTrait>>,
+    /// All agents in the company, each running as an actor
+    agents: AgentRuntime,
+    /// Agent health registry, restarting agents that keep failing
+    supervisor: Supervisor,
     /// Message bus for inter-agent communication
     message_bus: Arc<MessageBus>,
     /// Active projects
     projects: HashMap<Uuid, projects::Project>,
+    /// Tasks of active projects and their dependencies
+    task_graph: TaskGraph,
+    /// Latest campaign report from each Marketing agent
+    campaign_reports: HashMap<Uuid, CampaignReport>,
+    /// Services, customers and their tenancy
+    catalog: ServiceCatalog,
+    /// Messages queued this step, dispatched at the end of the step
+    pending_messages: Vec<Message>,
+    /// Recently dispatched messages kept for incident timelines
+    message_log: VecDeque<Message>,
+    /// Which conversation thread each incident and chain of events is in
+    threads: ConversationThreads,
+    /// Per-agent inboxes of dispatched messages awaiting delivery
+    router: MessageRouter,
+    /// Department circuit breakers and the intake they deferred
+    intake: IntakeController,
+    /// Agents blocked on each other, for deadlock detection
+    wait_graph: WaitForGraph,
+    /// Hourly KPI samples for trend analysis
+    kpi_history: KpiHistory,
+    /// Last seen state of entities, for change notifications
+    entity_tracker: EntityTracker,
+    /// Change notifications queued for webhook subscribers
+    webhooks: WebhookDispatcher,
+    /// Product source code, committed to by Engineering agents
+    repository: Repository,
+    /// Release notes of every shipped deployment
+    release_notes: ReleaseLog,
+    /// Per-service measurements graded at the monthly operations review
+    scorecards: ScorecardBook,
+    /// Resource quotas and reservations on the shared clusters
+    quotas: QuotaLedger,
+    /// Company risks reviewed quarterly by the department managers
+    risks: RiskRegister,
+    /// Reporting lines up to the CEO, with rollups, approvals and escalations
+    org: OrgChart,
+    /// Quarterly goals and plans the CEO sets at the weekly executive review
+    executive: ExecutiveAgent,
+    /// Agents hired and offboarded during the run
+    staffing: StaffingLog,
+    /// Cash and the monthly P&L
+    economy: Economy,
+    /// Resolutions, runbooks and post-mortems from past incidents
+    knowledge: KnowledgeBase,
+    /// CI/CD pipelines of merged commits, waiting for or holding build slots
+    pipelines: BuildQueue,
+    /// Builds on their way from the first environment to production
+    promotions: Promotions,
+    /// Active and resolved alerts on DevOps servers and clusters
+    alerts: AlertEngine,
+    /// Attack campaigns run in red team mode
+    red_team: AttackSimulator,
+    /// Accounts, access requests and access reviews
+    iam: IdentityDirectory,
+    /// Phishing campaigns, awareness and training
+    phishing: AwarenessProgram,
+    /// Threat indicators from the feeds, their sightings and advisories
+    threat_intel: ThreatIntel,
+    /// Transfers out of the data stores and the exposures DLP found
+    dlp: DlpMonitor,
+    /// New support tickets waiting for an Ops agent with room
+    ticket_queue: TicketQueue,
+    /// Utilization history and the capacity forecasts made from it
+    capacity: CapacityPlanner,
+    /// Incidents posted for customers, their notices and SLA credits
+    status_page: StatusPage,
+    /// Which shift each Ops agent works and the handoffs between them
+    shift_roster: ShiftRoster,
+    /// What every agent is up to and who is on leave
+    agent_states: AgentStates,
+    /// What each agent remembers of its recent messages and work
+    memories: AgentMemories,
+    /// Status updates managers are collecting, and their latest reports
+    manager_reports: ManagerReports,
+    /// Experience agents have built up in each kind of work
+    progression: SkillProgression,
+    /// What each agent decides to do on its turns
+    policies: HashMap<Uuid, Box<dyn DecisionPolicy>>,
+    /// Agent implementations each department is staffed with
+    registry: AgentRegistry,
+    /// Number of simulation steps executed so far
+    step_count: u64,
+    /// Timed events and the simulated clock
+    scheduler: Scheduler,
+    /// Random number generator driving orchestrator events
+    rng: StdRng,
     /// Simulation configuration
     config: SimulationConfig,
-}
-
-#[derive(Debug)]
-struct SimulationConfig {
-    /// Simulation speed multiplier (1.0 = real-time)
-    speed_multiplier: f32,
-    /// Enable autonomous operation
-    autonomous_mode: bool,
-    /// Working hours (start, end)
-    working_hours: (u8, u8),
-    /// Maximum simulation steps
-    max_steps: Option<u64>,
+    /// Service bookkeeping when running in daemon mode
+    daemon: Option<Daemon>,
+    /// Journal dispatched messages are recorded to
+    journal: Option<JournalWriter>,
+    /// File flow records are exported to, opened on the first capture
+    flow_log: Option<FlowLogWriter>,
+    /// Prometheus endpoint fed after every step
+    metrics: Option<Metrics>,
+    /// REST control API requests are taken from
+    #[cfg(feature = "api")]
+    api: Option<api::ControlApi>,
+    /// Held between events by the console or the control API
+    paused: bool,
+    /// Steps still to run before pausing again, requested with `step`
+    pending_steps: u32,
+    /// Agents whose turn took up their daily tasks, run together next step
+    due_daily_tasks: Vec<Uuid>,
+    /// Commands typed into the terminal
+    console: Option<Console>,
+    /// Templates company reports are rendered with, once `[reports] company_dir` is set
+    report_templates: Option<TemplatePack>,
+    /// SQLite history of tickets, incidents and project tasks
+    #[cfg(feature = "sqlite")]
+    history: Option<history::HistoryStore>,
+    /// Tasks of delivered projects, pruned from the task graph before history records them
+    #[cfg(feature = "sqlite")]
+    delivered_tasks: Vec<taskgraph::ProjectTask>,
+    /// On-call paging for severe incidents
+    pager: Option<Pager>,
+    /// Customer emails about tickets and campaigns
+    email: Option<EmailGateway>,
+    /// Two-way ticket sync with an external issue tracker
+    #[cfg(feature = "issue-sync")]
+    issue_sync: Option<issuesync::IssueSync>,
 }
 
 impl CompanySimulation {
     /// Create a new company simulation
-    async fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    async fn new(config: SimulationConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_registry(config, AgentRegistry::builtin()).await
+    }
+
+    /// Create a new company simulation staffed from `registry` and the
+    /// configured department scripts
+    async fn with_registry(config: SimulationConfig, registry: AgentRegistry) -> Result<Self, Box<dyn std::error::Error>> {
+        let registry = load_agent_scripts(registry, &config.scripting)?;
         let message_bus = Arc::new(MessageBus::new().await?);
+        let now = chrono::Utc::now();
+        let repository = Repository::new(&config.vcs.repository, &config.vcs.default_branch, now);
+        let economy = Economy::new(config.economy.starting_cash);
 
         let mut simulation = Self {
-            agents: HashMap::new(),
+            agents: AgentRuntime::new(),
+            supervisor: Supervisor::new(),
             message_bus: message_bus.clone(),
             projects: HashMap::new(),
-            config: SimulationConfig {
-                speed_multiplier: 1.0,
-                autonomous_mode: true,
-                working_hours: (9, 18), // 9 AM to 6 PM
-                max_steps: None,
-            },
+            task_graph: TaskGraph::new(),
+            campaign_reports: HashMap::new(),
+            catalog: ServiceCatalog::default(),
+            pending_messages: Vec::new(),
+            message_log: VecDeque::new(),
+            threads: ConversationThreads::new(),
+            router: MessageRouter::new(),
+            intake: IntakeController::new(),
+            wait_graph: WaitForGraph::new(),
+            kpi_history: KpiHistory::new(),
+            entity_tracker: EntityTracker::new(),
+            webhooks: WebhookDispatcher::new(),
+            repository,
+            release_notes: ReleaseLog::new(),
+            scorecards: ScorecardBook::new(),
+            quotas: QuotaLedger::new(),
+            risks: RiskRegister::new(),
+            org: OrgChart::new(),
+            executive: ExecutiveAgent::new(),
+            staffing: StaffingLog::new(),
+            economy,
+            knowledge: KnowledgeBase::new(),
+            pipelines: BuildQueue::new(),
+            promotions: Promotions::new(),
+            alerts: AlertEngine::new(),
+            red_team: AttackSimulator::new(),
+            iam: IdentityDirectory::new(),
+            phishing: AwarenessProgram::new(),
+            threat_intel: ThreatIntel::new(),
+            dlp: DlpMonitor::new(),
+            ticket_queue: TicketQueue::new(),
+            capacity: CapacityPlanner::new(),
+            status_page: StatusPage::new(),
+            shift_roster: ShiftRoster::new(),
+            agent_states: AgentStates::new(),
+            memories: AgentMemories::new(),
+            manager_reports: ManagerReports::new(),
+            progression: SkillProgression::new(),
+            policies: HashMap::new(),
+            registry,
+            step_count: 0,
+            scheduler: Scheduler::new(now),
+            rng: Self::seeded_rng(config.seed, 0),
+            config,
+            daemon: None,
+            journal: None,
+            flow_log: None,
+            metrics: None,
+            #[cfg(feature = "api")]
+            api: None,
+            paused: false,
+            pending_steps: 0,
+            due_daily_tasks: Vec::new(),
+            console: None,
+            report_templates: None,
+            #[cfg(feature = "sqlite")]
+            history: None,
+            #[cfg(feature = "sqlite")]
+            delivered_tasks: Vec::new(),
+            pager: None,
+            email: None,
+            #[cfg(feature = "issue-sync")]
+            issue_sync: None,
         };
 
         // Initialize all departments
         simulation.initialize_departments().await?;
+        simulation.org.rebuild(simulation.agents.infos());
+        simulation.onboard_initial_customers();
+        simulation.schedule_initial_events();
+        simulation.load_alert_routing();
+        simulation.connect_issue_tracker()?;
+        simulation.connect_pager()?;
+        simulation.connect_email()?;
+        simulation.assign_policies()?;
 
         Ok(simulation)
     }
 
+    /// Connect the configured issue tracker for two-way ticket sync
+    #[cfg(feature = "issue-sync")]
+    fn connect_issue_tracker(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.issue_sync = issuesync::IssueSync::from_settings(&self.config.issue_sync)?;
+        if let Some(sync) = &self.issue_sync {
+            println!("🔗 Syncing support tickets with {}", sync.tracker_name());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "issue-sync"))]
+    fn connect_issue_tracker(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.config.issue_sync.tracker.is_some() {
+            println!("⚠️ issue_sync.tracker is set but this build lacks the issue-sync feature; tickets are not synced");
+        }
+        Ok(())
+    }
+
+    /// Store the configured alert routing policies in the service catalog
+    fn load_alert_routing(&mut self) {
+        for (service, policy) in &self.config.alert_routing {
+            if !self.catalog.services.iter().any(|known| known.name == *service) {
+                println!("⚠️ Alert routing policy for unknown service '{}'", service);
+            }
+            self.catalog.set_routing_policy(service, policy.clone());
+        }
+    }
+
+    /// Connect the configured paging service, with the Ops agents on call
+    fn connect_pager(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.pager = Pager::from_settings(&self.config.paging, self.config.seed)?;
+        let responders = self.refresh_rotation();
+        if let Some(pager) = &self.pager {
+            println!("📟 Paging {} Ops responders via {}", responders, pager.provider_name());
+        }
+        Ok(())
+    }
+
+    /// Put the current Ops agents on the on-call rotation; returns how many
+    fn refresh_rotation(&mut self) -> usize {
+        let Some(pager) = &mut self.pager else {
+            return 0;
+        };
+        let mut responders: Vec<&AgentInfo> = self.agents.infos()
+            .filter(|agent| agent.department == Department::Ops)
+            .collect();
+        responders.sort_by(|a, b| a.name.cmp(&b.name));
+        pager.set_rotation(responders.iter().map(|agent| agent.id).collect());
+        responders.len()
+    }
+
+    /// Connect the configured email transport
+    fn connect_email(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.email = EmailGateway::from_settings(&self.config.email)?;
+        if let Some(email) = &self.email {
+            println!("📧 Emailing customers via {}", email.transport_name());
+        }
+        Ok(())
+    }
+
+    /// Build the orchestrator RNG, offsetting seeded runs by the step count on resume
+    fn seeded_rng(seed: Option<u64>, step_count: u64) -> StdRng {
+        match seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(step_count)),
+            None => StdRng::from_entropy(),
+        }
+    }
+
     /// Initialize all company departments and agents
     async fn initialize_departments(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         println!("🏢 Initializing AI Company Departments...");
 
         // Create department managers
         let engineering_manager = self.create_agent(Department::Engineering, "Sarah Chen", None).await?;
-        let sales_manager = self.create_agent(Department::Sales, "Mike Rodriguez", None).await?;
+        // Sales has no built-in agents; it is staffed once an implementation is registered
+        if self.registry.staffs(Department::Sales) {
+            self.create_agent(Department::Sales, "Mike Rodriguez", None).await?;
+        }
         let devops_manager = self.create_agent(Department::DevOps, "Jordan Smith", None).await?;
         let infosec_manager = self.create_agent(Department::InfoSec, "Alex Thompson", None).await?;
         let networking_manager = self.create_agent(Department::Networking, "Lisa Park", None).await?;
         let ops_manager = self.create_agent(Department::Ops, "David Wilson", None).await?;
+        let marketing_manager = self.create_agent(Department::Marketing, "Emma Garcia", None).await?;
 
         // Create specialized agents for each department
-        self.create_department_agents(Department::DevOps, devops_manager, 3).await?;
-        self.create_department_agents(Department::InfoSec, infosec_manager, 2).await?;
-        self.create_department_agents(Department::Networking, networking_manager, 2).await?;
-        self.create_department_agents(Department::Ops, ops_manager, 3).await?;
+        let sizes = self.config.department_sizes.clone();
+        self.create_department_agents(Department::Engineering, engineering_manager, sizes.engineering).await?;
+        self.create_department_agents(Department::DevOps, devops_manager, sizes.devops).await?;
+        self.create_department_agents(Department::InfoSec, infosec_manager, sizes.infosec).await?;
+        self.create_department_agents(Department::Networking, networking_manager, sizes.networking).await?;
+        self.create_department_agents(Department::Ops, ops_manager, sizes.ops).await?;
+        self.create_department_agents(Department::Marketing, marketing_manager, sizes.marketing).await?;
 
         println!("✅ All departments initialized with {} agents", self.agents.len());
         Ok(())
     }
 
-    /// Create an agent for a specific department
-    async fn create_agent(&mut self, department: Department, name: &str, manager_id: Option<Uuid>) -> Result<Uuid, Box<dyn std::error::Error>> {
-        let agent_id = Uuid::new_v4();
+    /// Sign up the configured number of starting customers
+    fn onboard_initial_customers(&mut self) {
+        self.catalog.onboard_random_customers(&self.config.customers, &mut self.rng);
 
-        let agent: Box<dyn AgentTrait> = match department {
-            Department::DevOps => Box::new(DevOpsAgent::new(name.to_string(), manager_id)),
-            Department::InfoSec => Box::new(InfoSecAgent::new(name.to_string(), manager_id)),
-            Department::Networking => Box::new(NetworkingAgent::new(name.to_string(), manager_id)),
-            Department::Ops => Box::new(OpsAgent::new(name.to_string(), manager_id)),
-            _ => {
-                // For other departments, create a basic agent (would be expanded)
-                Box::new(DevOpsAgent::new(name.to_string(), manager_id)) // Placeholder
-            }
-        };
+        let (shared, dedicated) = self.catalog.tenancy_counts();
+        println!("🏬 Onboarded {} customers ({} shared, {} dedicated)", shared + dedicated, shared, dedicated);
+    }
 
-        self.agents.insert(agent_id, agent);
+    /// Create an agent for a specific department
+    async fn create_agent(&mut self, department: Department, name: &str, manager_id: Option<Uuid>) -> Result<Uuid, Box<dyn std::error::Error>> {
+        let agent = self.registry.create(department, name, manager_id)?;
+        let agent_id = self.start_agent(agent);
         println!("👤 Created {} agent: {}", department.as_str(), name);
 
         Ok(agent_id)
     }
 
+    /// Spawn a new agent's actor
+    fn start_agent(&mut self, mut agent: Box<dyn DepartmentAgent>) -> Uuid {
+        // Managers keep the department's baseline; hires range around it
+        if agent.get_agent().manager_id.is_some() {
+            agent.adjust_skills(self.rng.gen_range(-20..=10));
+        }
+
+        // Keyed by the agent's own id so messages addressed to it can be routed
+        self.agents.spawn(agent)
+    }
+
     /// Create multiple agents for a department
     async fn create_department_agents(&mut self, department: Department, manager_id: Uuid, count: usize) -> Result<(), Box<dyn std::error::Error>> {
         for i in 1..=count {
@@ -123,159 +602,3056 @@ impl CompanySimulation {
     }
 
     /// Run the company simulation
+    ///
+    /// Events are taken from the scheduler in simulated-time order. Outside
+    /// headless mode the loop waits the real time between events, scaled by
+    /// the speed multiplier (1.0 = one simulated minute per real minute).
     async fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         println!("🚀 Starting AI Company Simulation...");
-        println!("📊 {} agents across {} departments", self.agents.len(), 6);
+        println!("📊 {} agents across {} departments", self.agents.len(), 7);
         println!("⚙️  Simulation speed: {:.1}x", self.config.speed_multiplier);
         println!("🤖 Autonomous mode: {}", if self.config.autonomous_mode { "ENABLED" } else { "DISABLED" });
+        println!("🕒 Simulated clock: {}", self.scheduler.now().format("%Y-%m-%d %H:%M UTC"));
+        if self.config.headless {
+            println!("🖥️  Headless: events run back-to-back");
+        }
+
+        // Set by SIGINT or SIGTERM so the loop can stop between events; a
+        // second signal exits at once
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(None);
+        tokio::spawn(async move {
+            let signal = shutdown_signal().await;
+            let _ = shutdown_tx.send(Some(signal));
+            shutdown_signal().await;
+            eprintln!("💥 Second signal received, exiting without saving");
+            std::process::exit(130);
+        });
+
+        self.open_journal()?;
+        if let Some(addr) = &self.config.metrics.addr {
+            let metrics = Metrics::new();
+            metrics.serve(addr).await?;
+            self.metrics = Some(metrics);
+        }
+        self.start_api().await?;
+        self.console = Console::attach();
+        self.open_history().await?;
+        if self.config.reports.company_dir.is_some() {
+            self.report_templates = Some(TemplatePack::from_config(self.config.reports.template_pack.as_deref())?);
+        }
+        if let Some(daemon) = &self.daemon {
+            daemon.start().await?;
+            daemon.set_ready(true);
+        }
+
+        let run_started = std::time::Instant::now();
+        let wall_limit = self.config.max_wall_seconds.map(std::time::Duration::from_secs);
+        let sim_limit = self.config.max_sim_days.map(|days| self.scheduler.now() + chrono::Duration::days(days as i64));
+
+        let stop = loop {
+            if let Some(signal) = *shutdown_rx.borrow() {
+                println!("🛑 {} received, stopping after step {}; signal again to exit without saving", signal, self.step_count);
+                break StopReason::Signal(signal);
+            }
+            if let Some(limit) = wall_limit.filter(|limit| run_started.elapsed() >= *limit) {
+                break StopReason::Duration(limit.as_secs());
+            }
+
+            if let Some(daemon) = &self.daemon {
+                daemon.heartbeat(self.step_count);
+            }
+
+            // A paused simulation only answers controls, unless it was asked to step
+            self.answer_api_requests().await;
+            self.answer_console().await;
+            if self.paused && self.pending_steps == 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(MAX_REAL_WAIT) => {}
+                    _ = shutdown_rx.changed() => {}
+                    _ = self.control_arrived() => {}
+                }
+                continue;
+            }
+
+            let Some(next_at) = self.scheduler.peek_time() else {
+                break StopReason::NoEvents;
+            };
+            if sim_limit.is_some_and(|limit| next_at > limit) {
+                break StopReason::SimDays(self.config.max_sim_days.expect("limit is set"));
+            }
+
+            // Requested steps run without waiting on the clock
+            if self.config.headless || self.pending_steps > 0 {
+                // Let the signal task run between events
+                tokio::task::yield_now().await;
+            } else {
+                let sim_wait = (next_at - self.scheduler.now()).to_std().unwrap_or_default();
+                if !sim_wait.is_zero() {
+                    // Wake at least once a minute so heartbeats keep flowing during long gaps,
+                    // and in time to stop at the wall-clock limit
+                    let real_wait = sim_wait.div_f32(self.config.speed_multiplier)
+                        .min(MAX_REAL_WAIT)
+                        .min(wall_limit.map_or(MAX_REAL_WAIT, |limit| limit.saturating_sub(run_started.elapsed())));
+                    let waiting_since = std::time::Instant::now();
+                    tokio::select! {
+                        _ = tokio::time::sleep(real_wait) => {}
+                        _ = shutdown_rx.changed() => continue,
+                        // Controls are answered without sitting out the gap
+                        _ = self.control_arrived() => {}
+                    }
+                    let waited = waiting_since.elapsed().min(real_wait);
+                    let sim_elapsed = chrono::Duration::from_std(waited.mul_f32(self.config.speed_multiplier))?;
+                    self.scheduler.advance_to(self.scheduler.now() + sim_elapsed);
+                    if self.scheduler.now() < next_at {
+                        continue;
+                    }
+                }
+            }
+
+            let event = self.scheduler.pop().expect("peeked event");
+            let steps_before = self.step_count;
+            if !self.handle_event(event.kind).await? {
+                break StopReason::MaxSteps(self.step_count);
+            }
+            if self.config.economy.stop_on_bankruptcy && self.economy.bankrupt_at.is_some() {
+                break StopReason::Bankrupt;
+            }
+            if self.pending_steps > 0 && self.step_count > steps_before {
+                self.pending_steps -= 1;
+                if self.pending_steps == 0 {
+                    println!("⏯️ Paused after step {} ({})", self.step_count, self.scheduler.now().format("%Y-%m-%d %H:%M UTC"));
+                }
+            }
+        };
+
+        self.shut_down(stop).await
+    }
+
+    /// Stop taking requests, flush and save everything, and print a final summary
+    ///
+    /// Runs once the loop is left between events, so no step is cut short.
+    /// A step that fails is reported without skipping the ones after it; the
+    /// first failure is returned.
+    async fn shut_down(&mut self, stop: StopReason) -> Result<(), Box<dyn std::error::Error>> {
+        // Dropping the API makes further requests answer that the simulation is not running
+        #[cfg(feature = "api")]
+        {
+            self.api = None;
+        }
+        if let Some(daemon) = &self.daemon {
+            daemon.set_ready(false);
+        }
+
+        let mut failure: Option<Box<dyn std::error::Error>> = None;
+        let mut keep_first = |result: Result<(), Box<dyn std::error::Error>>| {
+            if let Err(e) = result {
+                eprintln!("⚠️ Shutdown: {}", e);
+                failure.get_or_insert(e);
+            }
+        };
+        if let Some(journal) = &mut self.journal {
+            keep_first(journal.flush().map_err(Into::into));
+        }
+        #[cfg(feature = "sqlite")]
+        self.record_history().await;
+        keep_first(self.write_company_report(ReportKind::EndOfRun).await);
+        keep_first(self.save_snapshot().await);
+
+        self.print_final_summary(stop).await;
+        match failure {
+            Some(e) => Err(e),
+            // Bankruptcy is a failed run even when everything saved
+            None if stop == StopReason::Bankrupt => Err("the company went bankrupt".into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Closing summary of the run
+    async fn print_final_summary(&self, stop: StopReason) {
+        println!("🏁 Simulation completed after {} steps ({}): {}",
+               self.step_count, self.scheduler.now().format("%Y-%m-%d %H:%M UTC"), stop.describe());
+        match self.metrics_sample().await {
+            Ok(sample) => {
+                println!("   Agents: {} | Open tickets: {} | Active incidents: {} | Active deployments: {}",
+                       sample.agents.values().sum::<usize>(), sample.tickets_open,
+                       sample.incidents_active.values().sum::<usize>(), sample.deployments_active);
+                println!("   Messages: {} delivered, {} failed, {} dead-lettered, {} still queued",
+                       sample.messages_delivered, sample.messages_failed, sample.messages_dead_lettered, sample.messages_queued);
+            }
+            Err(e) => println!("   ⚠️ Final state unavailable: {}", e),
+        }
+        let (revenue, costs) = self.economy.statements().iter()
+            .fold((0.0, 0.0), |(revenue, costs), statement| (revenue + statement.revenue(), costs + statement.costs()));
+        println!("   Cash: ${:.2} | Revenue: ${:.2} | Costs: ${:.2}{}", self.economy.cash, revenue, costs,
+                 if self.economy.bankrupt_at.is_some() { " | Bankrupt" } else { "" });
+        let (lookups, hits) = self.knowledge.stats();
+        if lookups > 0 {
+            println!("📚 Knowledge base: {} articles, {} of {} lookups found a known fix",
+                     self.knowledge.articles().len(), hits, lookups);
+        }
+        for (name, stats) in self.knowledge.runbook_stats() {
+            println!("📗 Runbook '{}': {} of {} runs resolved their incident, {} escalated",
+                     name, stats.resolved, stats.runs, stats.escalated);
+        }
+        if self.config.shifts.enabled {
+            println!("🕘 Shifts: {} tickets and incidents handed over, {} hours with nobody on shift",
+                     self.shift_roster.handed_over, self.shift_roster.unstaffed_hours);
+        }
+        if !self.pipelines.pipelines().is_empty() {
+            let passed = self.pipelines.pipelines().iter().filter(|pipeline| pipeline.status == PipelineStatus::Passed).count();
+            let failed = self.pipelines.pipelines().iter().filter(|pipeline| pipeline.status == PipelineStatus::Failed).count();
+            let (queued, running) = self.pipelines.load();
+            println!("🏗️ Pipelines: {} passed, {} failed, {} running, {} queued", passed, failed, running, queued);
+        }
+        if !self.red_team.campaigns().is_empty() {
+            let report = self.red_team.report();
+            println!("🟣 Purple team: {} red team campaigns, {} detected, {} exfiltrated data",
+                     report.campaigns, report.detected, report.exfiltrated);
+            for coverage in &report.stages {
+                println!("   {}: caught {} of {} attempts", coverage.stage.as_str(), coverage.detected, coverage.attempted);
+            }
+            for gap in &report.gaps {
+                println!("   ⚠️ Gap: {}", gap);
+            }
+        }
+        if !self.phishing.campaigns().is_empty() {
+            let report = self.phishing.report();
+            println!("🎣 Phishing: {} of {} phished agents clicked across {} campaigns, {} trainings held",
+                     report.clicked, report.targeted, report.campaigns, report.trainings_completed);
+        }
+        if !self.threat_intel.advisories().is_empty() {
+            println!("📡 Threat intel: {} indicators, {} sightings, {} advisories issued",
+                     self.threat_intel.indicators().count(), self.threat_intel.sightings().len(), self.threat_intel.advisories().len());
+        }
+        if self.dlp.transfers() > 0 {
+            let report = self.dlp.report();
+            let records: u64 = report.by_classification.values().map(|total| total.records).sum();
+            println!("🚨 DLP: {} of {} transfers exposed classified data, {} records", report.exposures, report.transfers, records);
+        }
+        if let Some(journal) = &self.journal {
+            println!("📼 Journal: {} messages recorded", journal.recorded());
+        }
+        if let (Some(writer), Some(path)) = (&self.flow_log, &self.config.flow_logs.path) {
+            println!("🌐 Flow logs: {} records written to {}", writer.written(), path.display());
+        }
+        if let Some(path) = &self.config.snapshot_path {
+            println!("💾 State saved to {}", path.display());
+        }
+    }
+
+    /// Seed the scheduler with the recurring events of a fresh simulation
+    fn schedule_initial_events(&mut self) {
+        let now = self.scheduler.now();
+        let step = self.step_interval();
+
+        self.scheduler.schedule_at(now, EventKind::Step);
+        self.schedule_agent_turns();
+        self.scheduler.schedule_in(chrono::Duration::hours(1), EventKind::SlaCheck);
+        self.scheduler.schedule_at(scheduler::next_maintenance_window(now), EventKind::MaintenanceWindow);
+        self.scheduler.schedule_at(scheduler::next_operations_review(now), EventKind::OperationsReview);
+        self.scheduler.schedule_at(scheduler::next_risk_review(now), EventKind::RiskReview);
+        self.scheduler.schedule_at(scheduler::next_executive_review(now), EventKind::ExecutiveReview);
+        self.scheduler.schedule_at(scheduler::next_end_of_day(now), EventKind::EndOfDay);
+        for scripted in &self.config.staffing.changes {
+            self.scheduler.schedule_at(now + chrono::Duration::days(scripted.day as i64), EventKind::Staffing(scripted.change.clone()));
+        }
+        self.schedule_next_fault();
+    }
+
+    /// Schedule the first turn of every agent
+    fn schedule_agent_turns(&mut self) {
+        let now = self.scheduler.now();
+        let step = self.step_interval();
+        for agent_id in self.agents.ids() {
+            // Spread agent turns across the step so they do not all fire together
+            let jitter = chrono::Duration::seconds(self.rng.gen_range(0..step.num_seconds().max(1)));
+            self.scheduler.schedule_at(now + jitter, EventKind::AgentTurn(agent_id));
+        }
+    }
+
+    /// Simulated time between orchestrator steps and agent turns
+    fn step_interval(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.config.step_minutes as i64)
+    }
+
+    /// Schedule the next infrastructure fault
+    ///
+    /// Fault arrivals are a Poisson process whose rate per step is the
+    /// configured `infrastructure_issue` probability.
+    fn schedule_next_fault(&mut self) {
+        let rate = self.config.event_probabilities.infrastructure_issue as f64;
+        if rate <= 0.0 {
+            return;
+        }
+        let steps_until = -(1.0 - self.rng.gen::<f64>()).ln() / rate;
+        let delay = chrono::Duration::seconds((steps_until * self.step_interval().num_seconds() as f64) as i64);
+        self.scheduler.schedule_in(delay, EventKind::InfrastructureFault);
+    }
+
+    /// Run one scheduled event; returns false when the simulation should stop
+    async fn handle_event(&mut self, kind: EventKind) -> Result<bool, Box<dyn std::error::Error>> {
+        let now = self.scheduler.now();
+        let working_hours = self.config.working_hours;
+
+        match kind {
+            EventKind::Step => {
+                // Check working hours on the simulated clock
+                if !scheduler::within_working_hours(now, working_hours) {
+                    let resume_at = scheduler::next_working_start(now, working_hours);
+                    if !self.config.headless {
+                        println!("😴 Outside working hours ({}-{}). Agents resting until {}...",
+                               working_hours.0, working_hours.1, resume_at.format("%Y-%m-%d %H:%M"));
+                    }
+                    self.scheduler.schedule_at(resume_at, EventKind::Step);
+                    return Ok(true);
+                }
+
+                // Check if we've reached max steps
+                if let Some(max) = self.config.max_steps {
+                    if self.step_count >= max {
+                        return Ok(false);
+                    }
+                }
+
+                self.step_count += 1;
+                if !self.config.headless {
+                    println!("\n--- Simulation Step {} ({}) ---", self.step_count, now.format("%Y-%m-%d %H:%M"));
+                }
+
+                let started = std::time::Instant::now();
+                self.run_simulation_step().await?;
+                self.record_metrics(started.elapsed()).await;
+                self.run_daemon_tasks().await?;
+                self.scheduler.schedule_in(self.step_interval(), EventKind::Step);
+            }
+            EventKind::AgentTurn(agent_id) => {
+                // Agents removed since the turn was scheduled simply drop out
+                if !self.agents.contains(&agent_id) {
+                    return Ok(true);
+                }
+                if self.on_duty(&agent_id, now) {
+                    self.process_agent_turn(agent_id).await?;
+                    self.scheduler.schedule_in(self.step_interval(), EventKind::AgentTurn(agent_id));
+                } else {
+                    let resume_at = self.next_duty_start(&agent_id, now);
+                    let jitter = chrono::Duration::seconds(self.rng.gen_range(0..self.step_interval().num_seconds().max(1)));
+                    self.scheduler.schedule_at(resume_at + jitter, EventKind::AgentTurn(agent_id));
+                }
+            }
+            EventKind::SlaCheck => {
+                self.change_shifts().await?;
+                self.update_agent_states().await?;
+                self.check_slas().await?;
+                self.record_kpis().await?;
+                self.sample_service_health().await?;
+                self.evaluate_alerts().await?;
+                self.progress_skills().await?;
+                self.scheduler.schedule_in(chrono::Duration::hours(1), EventKind::SlaCheck);
+            }
+            EventKind::MaintenanceWindow => {
+                println!("🛠️ Weekly maintenance window opened");
+                self.send_ops_request("maintenance_task", "Weekly maintenance window", HashMap::from([
+                    ("title".to_string(), "Weekly Maintenance Window".to_string()),
+                ]));
+                self.request_vulnerability_scans().await?;
+                self.send_capacity_report()?;
+                self.refresh_risks().await?;
+                self.scheduler.schedule_at(scheduler::next_maintenance_window(now), EventKind::MaintenanceWindow);
+            }
+            EventKind::InfrastructureFault => {
+                println!("⚠️ Infrastructure issue detected!");
+                self.handle_infrastructure_issue().await?;
+                self.schedule_next_fault();
+            }
+            EventKind::OperationsReview => {
+                self.hold_operations_review().await?;
+                self.scheduler.schedule_at(scheduler::next_operations_review(now), EventKind::OperationsReview);
+            }
+            EventKind::AlertEscalation { correlation_id, deployment_id } => {
+                self.escalate_alert(correlation_id, deployment_id).await;
+            }
+            EventKind::RiskReview => {
+                self.hold_risk_review().await?;
+                self.scheduler.schedule_at(scheduler::next_risk_review(now), EventKind::RiskReview);
+            }
+            EventKind::ExecutiveReview => {
+                self.hold_executive_review().await?;
+                self.scheduler.schedule_at(scheduler::next_executive_review(now), EventKind::ExecutiveReview);
+            }
+            EventKind::Staffing(change) => {
+                self.apply_staffing_change(change, "the scenario").await;
+            }
+            EventKind::EndOfDay => {
+                self.close_books();
+                self.check_infrastructure();
+                self.request_scaling();
+                self.forecast_capacity().await?;
+                self.plan_leave();
+                self.request_backups();
+                self.request_patching().await?;
+                let flows = self.take_flow_log().await?;
+                self.hunt_threats(&flows).await?;
+                self.monitor_data(&flows).await?;
+                self.report_traffic_demand();
+                self.report_service_latency().await?;
+                self.run_red_team().await?;
+                self.run_phishing();
+                self.manage_access();
+                self.check_policies().await?;
+                self.analyze_firewall().await?;
+                self.check_certificates().await?;
+                self.roll_up_status().await?;
+                self.threads.retain(&self.message_log);
+                if self.config.org.status_updates {
+                    self.request_status_updates().await?;
+                }
+                if self.config.staffing.hr_reviews {
+                    self.review_staffing().await?;
+                }
+                self.write_company_report(ReportKind::EndOfDay).await?;
+                self.scheduler.schedule_at(scheduler::next_end_of_day(now), EventKind::EndOfDay);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Book a day of contract revenue, payroll and hosting, report last
+    /// month's P&L when a month opens, and check the company can still pay
+    fn close_books(&mut self) {
+        let now = self.scheduler.now();
+        let settings = &self.config.economy;
+        // Midnight closes the day before
+        let day = now - chrono::Duration::minutes(1);
+        self.economy.book(Entry::ContractRevenue, self.catalog.monthly_revenue() / 30.0, day);
+        self.economy.book(Entry::Payroll, self.agents.len() as f64 * settings.agent_monthly_cost / 30.0, day);
+        self.economy.book(Entry::Infrastructure, self.catalog.deployments.len() as f64 * settings.server_monthly_cost / 30.0, day);
+
+        if now.month() != day.month() {
+            if let Some(statement) = self.economy.statement(day) {
+                println!("📒 P&L for {}: ${:.2} revenue, ${:.2} costs, ${:.2} profit; ${:.2} in the bank",
+                         statement.month, statement.revenue(), statement.costs(), statement.profit(), self.economy.cash);
+            }
+        }
+        let was_solvent = self.economy.bankrupt_at.is_none();
+        if self.economy.check_solvency(settings.bankruptcy_cash, now) && was_solvent {
+            println!("💸 Bankrupt: cash fell to ${:.2}, below ${:.2}", self.economy.cash, settings.bankruptcy_cash);
+        }
+    }
+
+    /// Roll every agent's open and finished work up the reporting lines to
+    /// the CEO, and have managers delegate away from overloaded reports
+    async fn roll_up_status(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let work: BTreeMap<Uuid, WorkStatus> = self.status_reports().await?.iter()
+            .map(|report| (report.agent_id, report.work()))
+            .collect();
+        self.org.roll_up(&work, self.scheduler.now());
+
+        if let Some(company) = self.org.company_rollup() {
+            println!("👔 CEO rollup: {} staff, {} open items, {} done", company.staff, company.work.backlog, company.work.completed);
+            for head in &company.reports {
+                println!("   {} ({} staff): {} open, {} done", head.name, head.staff, head.work.backlog, head.work.completed);
+            }
+        }
+        for (manager, from, to) in self.org.delegations(self.config.org.delegate_backlog_gap) {
+            self.delegate(manager, from, to).await;
+        }
+        Ok(())
+    }
+
+    /// Have every agent without reports send its manager a status update;
+    /// managers still missing updates from the last round report with what
+    /// they have, and heads without reports go straight to the CEO
+    async fn request_status_updates(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        for manager_id in self.manager_reports.waiting() {
+            self.forward_department_report(manager_id).await?;
+        }
+
+        let now = self.scheduler.now();
+        for report in self.status_reports().await? {
+            let (has_reports, manager_id) = match self.org.member(&report.agent_id) {
+                Some(member) => (!member.reports.is_empty(), member.manager_id),
+                None => continue,
+            };
+            if has_reports {
+                continue;
+            }
+            match manager_id {
+                Some(manager_id) => {
+                    let update = self.status_update(&report, now);
+                    let content = format!("Status: {} open, {} done since the last update", update.backlog, update.throughput);
+                    let message = MessagePayload::StatusUpdate { update }.into_message(report.agent_id, manager_id, content, MessagePriority::Low);
+                    self.pending_messages.push(message);
+                }
+                None => self.forward_department_report(report.agent_id).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// An agent's own status update: what it finished since its last one, and
+    /// the answers it has waited on for `blocked_after_minutes` or longer
+    fn status_update(&mut self, report: &AgentStatusReport, now: chrono::DateTime<chrono::Utc>) -> StatusUpdate {
+        let blocked_after = chrono::Duration::minutes(self.config.agent_states.blocked_after_minutes);
+        let blockers: Vec<String> = self.wait_graph.edges().iter()
+            .filter(|edge| edge.waiter == report.agent_id && now - edge.since >= blocked_after)
+            .map(|edge| format!("waiting on {} for {}", self.actor_name(edge.holder), edge.message_type))
+            .collect();
+        let throughput = self.manager_reports.throughput(report.agent_id, report.work().completed);
+        StatusUpdate::from_report(report, throughput, blockers, self.config.org.risk_backlog, now)
+    }
+
+    /// File a status update with the manager it reached; the manager reports
+    /// once all of its reports have sent one
+    async fn receive_status_update(&mut self, manager_id: Uuid, message: &Message) -> Result<(), Box<dyn std::error::Error>> {
+        // Chatter between agents carries no update
+        let Ok(Some(MessagePayload::StatusUpdate { update })) = MessagePayload::from_message(message) else {
+            return Ok(());
+        };
+        let Some(reports) = self.org.member(&manager_id).map(|member| member.reports.clone()) else {
+            return Ok(());
+        };
+        if self.manager_reports.receive(manager_id, update, &reports) {
+            self.forward_department_report(manager_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Aggregate the updates a manager received into its report; team
+    /// managers pass it up as their own update, heads send it to the CEO
+    async fn forward_department_report(&mut self, manager_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(member) = self.org.member(&manager_id).cloned() else {
+            return Ok(());
+        };
+        let Ok(own) = self.agents.call(manager_id, |agent| agent.status_report()).await else {
+            return Ok(());
+        };
+        let own = self.status_update(&own, self.scheduler.now());
+        let reports: Vec<(Uuid, String)> = member.reports.iter().map(|id| (*id, self.actor_name(*id))).collect();
+        let report = self.manager_reports.compile(own, &member.department, &reports).clone();
+
+        let content = report.summary();
+        let message = match member.manager_id {
+            Some(above) => MessagePayload::StatusUpdate { update: report.as_update() }.into_message(manager_id, above, content, MessagePriority::Low),
+            None => MessagePayload::DepartmentReport { report }.into_message(manager_id, SystemActor::Ceo.id(), content, MessagePriority::Low),
+        };
+        self.pending_messages.push(message);
+        Ok(())
+    }
+
+    /// Every agent's status report, with its state and recent activity
+    async fn status_reports(&self) -> Result<Vec<AgentStatusReport>, Box<dyn std::error::Error>> {
+        let mut reports = self.agents.call_all(|agent| agent.status_report()).await?;
+        for report in &mut reports {
+            let agent_id = report.agent_id;
+            report.observe(self.agent_states.state(&agent_id), self.memories.get(&agent_id), statusreport::RECENT_ACTIVITY, |id| self.actor_name(id));
+        }
+        Ok(reports)
+    }
+
+    /// Status report of the agent with this name or id
+    async fn status_report(&self, agent: &str) -> Option<AgentStatusReport> {
+        let id = Uuid::parse_str(agent).ok();
+        let agent_id = self.agents.infos().find(|info| Some(info.id) == id || info.name == agent)?.id;
+        let mut report = self.agents.call(agent_id, |agent| agent.status_report()).await.ok()?;
+        report.observe(self.agent_states.state(&agent_id), self.memories.get(&agent_id), statusreport::RECENT_ACTIVITY, |id| self.actor_name(id));
+        Some(report)
+    }
+
+    /// Move one open ticket or incident from a manager's busiest report to
+    /// the least busy one; only Ops work can change hands
+    async fn delegate(&mut self, manager: Uuid, from: Uuid, to: Uuid) {
+        let handover = self.agents.call(from, |agent| {
+            let ops = agent.as_ops_mut()?;
+            let id = ops.support_tickets.values()
+                .find(|ticket| matches!(ticket.status, departments::ops::TicketStatus::Open | departments::ops::TicketStatus::InProgress))
+                .map(|ticket| ticket.id)?;
+            ops.hand_over(id)
+        }).await;
+        let Ok(Some(handover)) = handover else {
+            return;
+        };
+        let title = match &handover {
+            departments::ops::Handover::Ticket(ticket) => ticket.title.clone(),
+            departments::ops::Handover::Incident(incidents) => incidents.first().map(|incident| incident.title.clone()).unwrap_or_default(),
+        };
+        let taken = self.agents.call(to, move |agent| {
+            if let Some(ops) = agent.as_ops_mut() {
+                ops.take_over(handover);
+            }
+        }).await;
+        if taken.is_ok() {
+            println!("🔀 {} moved '{}' from {} to {}", self.actor_name(manager), title, self.actor_name(from), self.actor_name(to));
+        }
+    }
+
+    /// Hire an agent into a department mid-run, reporting to its head
+    ///
+    /// The first hire into a department nobody works in becomes its head.
+    async fn hire(&mut self, department: &str, name: Option<String>, by: &str) -> Result<Uuid, StaffingError> {
+        let department = Department::named(department).ok_or_else(|| StaffingError::UnknownDepartment(department.to_string()))?;
+        let name = match name {
+            Some(name) if self.agents.infos().any(|agent| agent.name == name) => return Err(StaffingError::NameTaken(name)),
+            Some(name) => name,
+            None => staffing::next_agent_name(department.as_str(), self.agents.infos().map(|agent| agent.name.as_str())),
+        };
+        let head = self.agents.infos()
+            .find(|agent| agent.department == department && agent.manager_id.is_none())
+            .map(|agent| agent.id);
+        let agent = self.registry.create(department, &name, head)
+            .map_err(|_| StaffingError::NoAgentType(department.as_str().to_string()))?;
+
+        let agent_id = self.start_agent(agent);
+        let jitter = chrono::Duration::seconds(self.rng.gen_range(0..self.step_interval().num_seconds().max(1)));
+        self.scheduler.schedule_in(jitter, EventKind::AgentTurn(agent_id));
+        self.org.rebuild(self.agents.infos());
+        if department == Department::Ops {
+            self.refresh_rotation();
+        }
+        println!("🤝 {} joined {} (hired by {})", name, department.as_str(), by);
+        self.staffing.record(StaffingEvent {
+            at: self.scheduler.now(),
+            agent_id,
+            name,
+            department: department.as_str().to_string(),
+            hired: true,
+            by: by.to_string(),
+        });
+        Ok(agent_id)
+    }
+
+    /// Offboard an agent by name or id, handing its tickets, incidents and
+    /// project tasks to the least busy colleague; returns the colleague
+    async fn offboard(&mut self, agent: &str, by: &str) -> Result<Uuid, StaffingError> {
+        let id = Uuid::parse_str(agent).ok();
+        let info = self.agents.infos()
+            .find(|info| Some(info.id) == id || info.name == agent)
+            .cloned()
+            .ok_or_else(|| StaffingError::UnknownAgent(agent.to_string()))?;
+        if self.agents.infos().any(|other| other.manager_id == Some(info.id)) {
+            return Err(StaffingError::HasReports(info.name));
+        }
+        let colleagues: Vec<Uuid> = self.agents.infos()
+            .filter(|other| other.department == info.department && other.id != info.id)
+            .map(|other| other.id)
+            .collect();
+        let loads = self.agents.call_all(|agent| (agent.get_agent().id, agent.backlog())).await?;
+        let successor = loads.into_iter()
+            .filter(|(id, _)| colleagues.contains(id))
+            .min_by_key(|(_, backlog)| *backlog)
+            .map(|(id, _)| id)
+            .ok_or_else(|| StaffingError::LastInDepartment(info.name.clone()))?;
+
+        let handovers = self.agents.call(info.id, |agent| {
+            let mut handovers = Vec::new();
+            if let Some(ops) = agent.as_ops_mut() {
+                let ids: Vec<Uuid> = ops.support_tickets.keys().chain(ops.incidents.keys()).copied().collect();
+                // Merged duplicates go with their master incident, so some ids are gone by their turn
+                handovers.extend(ids.into_iter().filter_map(|id| ops.hand_over(id)));
+            }
+            handovers
+        }).await?;
+        let handed_over = handovers.len();
+        self.agents.call(successor, move |agent| {
+            if let Some(ops) = agent.as_ops_mut() {
+                for handover in handovers {
+                    ops.take_over(handover);
+                }
+            }
+        }).await?;
+        let tasks: Vec<(Uuid, Uuid, i64)> = self.task_graph.tasks()
+            .filter(|task| task.assignee == Some(info.id) && task.state == taskgraph::TaskState::InProgress)
+            .map(|task| (task.id, task.project_id, task.estimate_hours))
+            .collect();
+        for (task_id, project_id, estimate_hours) in &tasks {
+            self.task_graph.assign(*task_id, successor, *estimate_hours);
+            self.assign_project_task(*project_id, successor);
+        }
+        for message in self.pending_messages.iter_mut().filter(|message| message.to_agent == info.id) {
+            message.to_agent = successor;
+        }
+
+        self.agents.stop(info.id).await?;
+        self.policies.remove(&info.id);
+        self.org.rebuild(self.agents.infos());
+        if info.department == Department::Ops {
+            self.refresh_rotation();
+        }
+        println!("👋 {} left {} (offboarded by {}); {} open items and {} project tasks went to {}",
+               info.name, info.department.as_str(), by, handed_over, tasks.len(), self.actor_name(successor));
+        self.staffing.record(StaffingEvent {
+            at: self.scheduler.now(),
+            agent_id: info.id,
+            name: info.name,
+            department: info.department.as_str().to_string(),
+            hired: false,
+            by: by.to_string(),
+        });
+        Ok(successor)
+    }
+
+    /// Carry out a hire or offboarding asked for outside the simulation
+    async fn apply_staffing_change(&mut self, change: StaffingChange, by: &str) {
+        let outcome = match change {
+            StaffingChange::Hire { department, name } => self.hire(&department, name, by).await.map(|_| ()),
+            StaffingChange::Offboard { agent } => self.offboard(&agent, by).await.map(|_| ()),
+        };
+        if let Err(e) = outcome {
+            println!("⚠️ Staffing change from {} not made: {}", by, e);
+        }
+    }
+
+    /// HR's daily look at each department's workload: hire where work piles
+    /// up or the CEO allocated more headcount, offboard where agents sit idle
+    async fn review_staffing(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let agents = self.agents.call_all(|agent| {
+            let info = agent.get_agent();
+            (info.id, info.department.as_str().to_string(), info.manager_id.is_some(), agent.backlog())
+        }).await?;
+        let managers: Vec<Uuid> = self.agents.infos().filter_map(|agent| agent.manager_id).collect();
+
+        let mut loads: BTreeMap<String, DepartmentLoad> = BTreeMap::new();
+        let mut idlest: BTreeMap<String, usize> = BTreeMap::new();
+        for (id, department, managed, backlog) in agents {
+            let load = loads.entry(department.clone()).or_default();
+            load.staff += 1;
+            load.backlog += backlog;
+            if managed && !managers.contains(&id) && idlest.get(&department).is_none_or(|least| backlog < *least) {
+                load.idlest = Some(id);
+                idlest.insert(department, backlog);
+            }
+        }
+        for department in self.executive.allocations.keys() {
+            loads.entry(department.clone()).or_default();
+        }
+        for (department, load) in &mut loads {
+            load.floor = self.config.department_sizes.of(department) + 1;
+            load.allocated = self.executive.allocations.get(department).map(|allocation| allocation.headcount);
+        }
+
+        let hr = SystemActor::Hr.name();
+        for decision in staffing::plan(&loads, self.executive.hiring_freeze, &self.config.staffing) {
+            let outcome = match decision {
+                staffing::Decision::Hire(department) => self.hire(&department, None, hr).await.map(|_| ()),
+                staffing::Decision::Offboard(agent_id) => self.offboard(&agent_id.to_string(), hr).await.map(|_| ()),
+            };
+            if let Err(e) = outcome {
+                println!("⚠️ HR staffing change not made: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sign a merge off along the author's reporting line before it ships
+    ///
+    /// Managers approve changes within their limit; the CEO approves larger
+    /// ones, except while a department's intake breaker is open.
+    fn approve_change(&mut self, author_id: Uuid, project_id: Option<Uuid>, hash: &str) -> bool {
+        let features = project_id.map_or(1, |project_id| self.repository.merge_count(project_id).max(1)) as u32;
+        let approver = self.org.change_approver(author_id, features, &self.config.org);
+        let frozen = self.intake.open_departments();
+        let approved = approver != Approver::Ceo || frozen.is_empty();
+        let name = match approver {
+            Approver::Manager(manager) => self.actor_name(manager),
+            Approver::Ceo => SystemActor::Ceo.name().to_string(),
+        };
+        if approved {
+            println!("✍️ {} approved shipping {} ({} features)", name, vcs::short(hash), features);
+        } else {
+            println!("⛔ {} held {} ({} features) while {} is overloaded", name, vcs::short(hash), features, frozen.join(", "));
+        }
+        self.org.record_approval(Approval {
+            kind: ApprovalKind::ChangeRequest,
+            requested_by: author_id,
+            subject: hash.to_string(),
+            amount: f64::from(features),
+            approver,
+            approved,
+            decided_at: self.scheduler.now(),
+        });
+        approved
+    }
+
+    /// Write a company report when `[reports] company_dir` is set
+    async fn write_company_report(&self, kind: ReportKind) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(dir), Some(templates)) = (&self.config.reports.company_dir, &self.report_templates) else {
+            return Ok(());
+        };
+        let report = CompanyReport::from_snapshot(&self.snapshot().await?, kind).await?;
+        // A report that cannot be written is not worth stopping the company for
+        match report.write(dir, templates) {
+            Ok(path) => println!("🗂️ Company report {} written to {}", report.title(), path.display()),
+            Err(e) => eprintln!("⚠️ {}", e),
+        }
+        Ok(())
+    }
+
+    /// Queue a scheduler request for the Ops department
+    fn send_ops_request(&mut self, message_type: &str, content: &str, metadata: HashMap<String, String>) {
+        if let Some(ops_id) = self.find_department_agent(Department::Ops.as_str()) {
+            self.pending_messages.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: SystemActor::Scheduler.id(),
+                to_agent: ops_id,
+                message_type: message_type.to_string(),
+                content: content.to_string(),
+                priority: MessagePriority::Normal,
+                timestamp: chrono::Utc::now(),
+                metadata,
+            });
+        }
+    }
+
+    /// Whether an agent works at `now`: Ops agents on the roster while their
+    /// shift is on, everyone else within the working hours
+    fn on_duty(&self, agent_id: &Uuid, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let shifts = shifts::shifts(&self.config.shifts, self.config.working_hours);
+        match self.shift_roster.shift_of(agent_id) {
+            Some(shift) if self.config.shifts.enabled => shifts::current(&shifts, now).is_some_and(|current| current.name == shift),
+            _ => scheduler::within_working_hours(now, self.config.working_hours),
+        }
+    }
+
+    /// When an agent off duty is next back at work
+    fn next_duty_start(&self, agent_id: &Uuid, now: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        let shifts = shifts::shifts(&self.config.shifts, self.config.working_hours);
+        let shift = self.shift_roster.shift_of(agent_id)
+            .filter(|_| self.config.shifts.enabled)
+            .and_then(|name| shifts.iter().find(|shift| shift.name == name));
+        match shift {
+            Some(shift) => shifts::next_start(shift, now),
+            None => scheduler::next_working_start(now, self.config.working_hours),
+        }
+    }
+
+    /// Move an agent to the state its working hours, leave, failures, waits
+    /// and open work put it in; returns the state
+    fn update_agent_state(&mut self, agent_id: Uuid, backlog: usize) -> AgentState {
+        let now = self.scheduler.now();
+        let signals = Signals {
+            on_duty: self.on_duty(&agent_id, now),
+            failing: !self.supervisor.can_run(&agent_id, now),
+            backlog,
+            waiting_since: self.wait_graph.edges().iter().filter(|edge| edge.waiter == agent_id).map(|edge| edge.since).min(),
+        };
+        let transition = self.agent_states.update(agent_id, &signals, &self.config.agent_states, now);
+        if transition.is_some_and(|transition| transition.to == AgentState::Blocked) {
+            println!("⛔ {} is blocked waiting on another agent", self.actor_name(agent_id));
+        }
+        self.agent_states.state(&agent_id)
+    }
+
+    /// Update every agent's state and have blocked Ops agents hand their
+    /// unanswered tickets to available colleagues
+    async fn update_agent_states(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let backlogs = self.agents.call_all(|agent| (agent.get_agent().id, agent.backlog())).await?;
+        let agent_ids: Vec<Uuid> = backlogs.iter().map(|(agent_id, _)| *agent_id).collect();
+        self.agent_states.retain(&agent_ids);
+        self.memories.retain(&agent_ids);
+        self.manager_reports.retain(&agent_ids);
+        let mut blocked = Vec::new();
+        for (agent_id, backlog) in backlogs {
+            if self.update_agent_state(agent_id, backlog) == AgentState::Blocked {
+                blocked.push(agent_id);
+            }
+        }
+        if self.config.agent_states.reassign_blocked {
+            for agent_id in blocked {
+                self.reassign_blocked(agent_id).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hand a blocked Ops agent's unanswered tickets to the available Ops
+    /// agents with the fewest open tickets
+    async fn reassign_blocked(&mut self, owner: Uuid) {
+        let tickets = self.agents.call(owner, |agent| {
+            agent.as_ops().into_iter().flat_map(|ops| ops.unanswered_tickets())
+                .map(|ticket| (ticket.id, ticket.title.clone()))
+                .collect::<Vec<_>>()
+        }).await.unwrap_or_default();
+        if tickets.is_empty() {
+            return;
+        }
+        let agents = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| QueueAgent { agent_id: ops.agent.id, support_skill: ops.support_skill, open_tickets: ops.open_tickets() })
+        }).await.unwrap_or_default();
+        let mut available: Vec<QueueAgent> = agents.into_iter().flatten()
+            .filter(|agent| matches!(self.agent_states.state(&agent.agent_id), AgentState::Idle | AgentState::Working))
+            .collect();
+        for (ticket_id, title) in tickets {
+            let Some(to) = ticketqueue::pick(&available, 0, usize::MAX, Some(owner)) else {
+                break;
+            };
+            let handover = self.agents.call(owner, move |agent| agent.as_ops_mut().and_then(|ops| ops.hand_over(ticket_id))).await;
+            let Ok(Some(handover)) = handover else {
+                continue;
+            };
+            let taken = self.agents.call(to, move |agent| {
+                if let Some(ops) = agent.as_ops_mut() {
+                    ops.take_over(handover);
+                }
+            }).await;
+            if taken.is_err() {
+                continue;
+            }
+            if let Some(agent) = available.iter_mut().find(|agent| agent.agent_id == to) {
+                agent.open_tickets += 1;
+            }
+            println!("🔀 Moved '{}' from {}, blocked, to {}", title, self.actor_name(owner), self.actor_name(to));
+        }
+    }
+
+    /// Send agents on leave by chance, keeping someone in every department
+    fn plan_leave(&mut self) {
+        let agents: Vec<(Uuid, String)> = self.agents.infos().map(|agent| (agent.id, agent.department.as_str().to_string())).collect();
+        let now = self.scheduler.now();
+        for (agent_id, until) in self.agent_states.plan_leave(&agents, &self.config.agent_states, now, &mut self.rng) {
+            println!("🏖️ {} is on leave until {}", self.actor_name(agent_id), until.format("%Y-%m-%d"));
+        }
+    }
+
+    /// Put new Ops agents on a shift and, when a shift has just started, have
+    /// the outgoing agents hand their open work to the incoming ones
+    async fn change_shifts(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config.shifts.enabled {
+            return Ok(());
+        }
+        let shifts = shifts::shifts(&self.config.shifts, self.config.working_hours);
+        let ops = self.agents.call_all(|agent| agent.as_ops().map(|ops| ops.agent.id)).await?;
+        let ops: Vec<Uuid> = ops.into_iter().flatten().collect();
+        for (agent_id, shift) in self.shift_roster.sync(&ops, &shifts) {
+            println!("🕘 {} joins the {} shift", self.actor_name(agent_id), shift);
+        }
+        if let Some(change) = self.shift_roster.advance(&shifts, self.scheduler.now()) {
+            self.hand_off_shift(change).await;
+        }
+        Ok(())
+    }
+
+    /// Hand each outgoing Ops agent's open tickets and active incidents to
+    /// the incoming agents with the fewest tickets, and note what changed
+    /// hands; with nobody incoming the work waits
+    async fn hand_off_shift(&mut self, change: ShiftChange) {
+        let staff = self.shift_roster.staff(&change.to);
+        if staff.is_empty() {
+            println!("🌙 The {} shift starts with nobody on it; open work waits for the next one", change.to);
+            return;
+        }
+        let agents = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| QueueAgent { agent_id: ops.agent.id, support_skill: ops.support_skill, open_tickets: ops.open_tickets() })
+        }).await.unwrap_or_default();
+        let mut incoming: Vec<QueueAgent> = agents.into_iter().flatten().filter(|agent| staff.contains(&agent.agent_id)).collect();
+        let now = self.scheduler.now();
+        for from in self.shift_roster.staff(&change.from) {
+            let work = self.agents.call(from, |agent| {
+                agent.as_ops().map(|ops| {
+                    let tickets = ops.support_tickets.values()
+                        .filter(|ticket| !matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed))
+                        .map(|ticket| (HandoffKind::Ticket, ticket.id, ticket.title.clone(), format!("{:?}", ticket.status)));
+                    let incidents = ops.active_incidents()
+                        .map(|incident| (HandoffKind::Incident, incident.id, incident.title.clone(), format!("{:?}", incident.status)));
+                    tickets.chain(incidents).collect::<Vec<_>>()
+                }).unwrap_or_default()
+            }).await.unwrap_or_default();
+            let mut items = Vec::new();
+            for (kind, id, title, status) in work {
+                let Some(to) = ticketqueue::pick(&incoming, 0, usize::MAX, None) else {
+                    break;
+                };
+                let handover = self.agents.call(from, move |agent| agent.as_ops_mut().and_then(|ops| ops.hand_over(id))).await;
+                let Ok(Some(handover)) = handover else {
+                    continue;
+                };
+                let taken = self.agents.call(to, move |agent| {
+                    if let Some(ops) = agent.as_ops_mut() {
+                        ops.take_over(handover);
+                    }
+                }).await;
+                if taken.is_err() {
+                    continue;
+                }
+                if let Some(agent) = incoming.iter_mut().find(|agent| agent.agent_id == to) {
+                    agent.open_tickets += 1;
+                }
+                items.push(HandoffItem { kind, id, title, status, to });
+            }
+            if items.is_empty() {
+                continue;
+            }
+            let note = HandoffNote { at: now, from_shift: change.from.clone(), to_shift: change.to.clone(), from, items };
+            println!("📝 {}: {}", self.actor_name(from), note.summary());
+            self.shift_roster.record(note, self.config.shifts.max_notes);
+        }
+    }
+
+    /// Have Ops check the last hour of every catalog service against its SLA:
+    /// the worst incident affecting it, the share of DevOps servers online,
+    /// Networking's status of it and whether anyone is on shift
+    async fn check_slas(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(ops_id) = self.find_department_agent(Department::Ops.as_str()) else {
+            return Ok(());
+        };
+        let incidents = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| ops.active_incidents()
+                .flat_map(|i| i.affected_services.iter().map(|service| (service.clone(), i.severity.clone())))
+                .collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let mut worst: HashMap<String, Severity> = HashMap::new();
+        for (service, severity) in incidents.into_iter().flatten() {
+            let current = worst.entry(service).or_insert_with(|| severity.clone());
+            if severity.rank() > current.rank() {
+                *current = severity;
+            }
+        }
+        let servers = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values().map(|server| server.status.clone()).collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let servers: Vec<ServerState> = servers.into_iter().flatten().collect();
+        let capacity = if servers.is_empty() {
+            1.0
+        } else {
+            servers.iter().filter(|state| **state == ServerState::Online).count() as f64 / servers.len() as f64
+        };
+        let network = self.agents.call_all(|agent| {
+            agent.as_networking().map(|networking| networking.network_services.values().map(|service| (service.name.clone(), service.status.clone())).collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let network: HashMap<String, ServiceStatus> = network.into_iter().flatten().collect();
+
+        let shifts = shifts::shifts(&self.config.shifts, self.config.working_hours);
+        let unstaffed = self.config.shifts.enabled && !self.shift_roster.staffed(&shifts, self.scheduler.now());
+        let unstaffed_slowdown = unstaffed.then_some(self.config.shifts.unstaffed_slowdown);
+        let health: Vec<ServiceHealth> = self.catalog.services.iter()
+            .map(|service| ServiceHealth {
+                service: service.name.clone(),
+                incident: worst.get(&service.name).cloned(),
+                capacity,
+                network: network.get(&service.name).cloned(),
+                unstaffed_slowdown,
+            })
+            .collect();
+        let payload = MessagePayload::SlaCheck { at: self.scheduler.now(), minutes: 60.0, health };
+        self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), ops_id, "Scheduled SLA compliance check".to_string(), MessagePriority::Normal));
+        Ok(())
+    }
+
+    /// Sample every service's availability and traffic for its scorecard
+    async fn sample_service_health(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let degraded = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| ops.incidents.values()
+                .filter(|i| i.duplicate_of.is_none() && matches!(i.status, IncidentStatus::Open | IncidentStatus::Investigating | IncidentStatus::Mitigating))
+                .flat_map(|i| i.affected_services.clone())
+                .collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let degraded: HashSet<String> = degraded.into_iter().flatten().collect();
+
+        let now = self.scheduler.now();
+        let settings = &self.config.scorecards;
+        for service in &self.catalog.services {
+            let (deployments, customers) = self.catalog.service_footprint(&service.name);
+            let load = self.rng.gen_range(0.8..1.2);
+            let requests = (customers as u64 * settings.requests_per_customer_hour) as f64 * load;
+            let cost = deployments as f64 * settings.deployment_hourly_cost;
+            self.scorecards.record_hour(&service.name, !degraded.contains(&service.name), requests as u64, cost, settings, now);
+        }
+        Ok(())
+    }
+
+    /// Check the alert rules against DevOps metrics and tell each target's
+    /// owner what fired or resolved
+    async fn evaluate_alerts(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config.alerting.enabled {
+            return Ok(());
+        }
+        let samples = self.agents.call_all(|agent| agent.as_devops().map(|devops| devops.metric_samples()).unwrap_or_default()).await?;
+        let samples: Vec<_> = samples.into_iter().flatten().collect();
+        for change in self.alerts.evaluate(&samples, &self.config.alerting, self.scheduler.now()) {
+            let (alert, payload, content) = match change {
+                AlertChange::Fired(alert) => {
+                    let content = format!("{} on {} at {:.1}", alert.rule, alert.target, alert.value);
+                    let payload = MessagePayload::AlertFired {
+                        alert_id: alert.id,
+                        rule: alert.rule.clone(),
+                        target: alert.target.clone(),
+                        severity: alert.severity.clone(),
+                        value: alert.value,
+                    };
+                    (alert, payload, content)
+                }
+                AlertChange::Resolved(alert) => {
+                    let content = format!("{} on {} resolved after {} checks", alert.rule, alert.target, alert.repeats + 1);
+                    let payload = MessagePayload::AlertResolved { alert_id: alert.id, rule: alert.rule.clone(), target: alert.target.clone() };
+                    (alert, payload, content)
+                }
+            };
+            // The owner may have been offboarded since the sample
+            if !self.agents.contains(&alert.owner) {
+                continue;
+            }
+            let priority = if alert.resolved_at.is_none() && alert.severity.rank() >= Severity::Sev2.rank() { MessagePriority::High } else { MessagePriority::Normal };
+            self.pending_messages.push(payload.into_message(SystemActor::Orchestrator.id(), alert.owner, content, priority));
+        }
+        Ok(())
+    }
+
+    /// Have InfoSec scan every server, network service and catalog service
+    /// for vulnerabilities
+    async fn request_vulnerability_scans(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) else {
+            return Ok(());
+        };
+        let hostnames = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values().map(|server| server.hostname.clone()).collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let services = self.agents.call_all(|agent| {
+            agent.as_networking().map(|networking| networking.network_services.keys().cloned().collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let servers: BTreeSet<String> = hostnames.into_iter().flatten().collect();
+        let network_services: BTreeSet<String> = services.into_iter().flatten().collect();
+        let applications: Vec<String> = self.catalog.services.iter().map(|service| service.name.clone()).collect();
+
+        let content = format!("Weekly vulnerability scan of {} assets", servers.len() + network_services.len() + applications.len());
+        let request = MessagePayload::ScanRequest {
+            at: self.scheduler.now(),
+            settings: self.config.scanning.clone(),
+            servers: servers.into_iter().collect(),
+            network_services: network_services.into_iter().collect(),
+            applications,
+        };
+        self.pending_messages.push(request.into_message(SystemActor::Scheduler.id(), infosec_id, content, MessagePriority::Normal));
+        Ok(())
+    }
+
+    /// Grade every service and give owners of low-scoring ones an improvement task
+    async fn hold_operations_review(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Services without an owner go to DevOps agents in turn
+        let devops = self.department_agents(Department::DevOps);
+        if !devops.is_empty() {
+            for (i, service) in self.catalog.services.iter().enumerate() {
+                let owned = self.scorecards.owner(&service.name).is_some_and(|owner| self.agents.contains(&owner));
+                if !owned {
+                    self.scorecards.assign_owner(&service.name, devops[i % devops.len()]);
+                }
+            }
+        }
+
+        let counts = self.agents.call_all(|agent| agent.as_infosec().map(|infosec| infosec.open_vulnerabilities_by_system())).await?;
+        let mut vulnerabilities = BTreeMap::new();
+        for (system, count) in counts.into_iter().flatten().flatten() {
+            *vulnerabilities.entry(system).or_insert(0) += count;
+        }
+
+        let now = self.scheduler.now();
+        let review = self.scorecards.review(&vulnerabilities, &self.config.scorecards, now).clone();
+        println!("📋 Operations review for {} – {}", review.period_start.format("%Y-%m-%d"), now.format("%Y-%m-%d"));
+        for card in &review.scorecards {
+            println!("   {} {}: {:.0} (SLO {:.2}%, {} vulnerabilities, {} incidents, tech debt {:.0})",
+                   card.grade.as_str(), card.service, card.score, card.slo_compliance,
+                   card.open_vulnerabilities, card.incidents, card.tech_debt);
+        }
+
+        for task_id in &review.tasks {
+            let Some(task) = self.scorecards.task(task_id).cloned() else {
+                continue;
+            };
+            println!("   📌 {} assigned to {}", task.title, self.actor_name(task.owner));
+            self.pending_messages.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: SystemActor::Orchestrator.id(),
+                to_agent: task.owner,
+                message_type: scorecard::IMPROVEMENT_TASK.to_string(),
+                content: task.title.clone(),
+                priority: MessagePriority::Normal,
+                timestamp: chrono::Utc::now(),
+                metadata: HashMap::from([
+                    (scorecard::TASK_ID_KEY.to_string(), task.id.to_string()),
+                    ("service".to_string(), task.service.clone()),
+                    ("focus".to_string(), task.focus.as_str().to_string()),
+                ]),
+            });
+        }
+        Ok(())
+    }
+
+    /// Turn work agents finished since the last check into experience, and
+    /// let unused skills decay
+    async fn progress_skills(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let reports = self.agents.call_all(|agent| {
+            let id = agent.get_agent().id;
+            WorkKind::ALL.iter()
+                .filter_map(|kind| agent.skill(*kind).map(|skill| (id, *kind, skill, agent.completed_work(*kind))))
+                .collect::<Vec<_>>()
+        }).await?;
+
+        let now = self.scheduler.now();
+        self.progression.retain(&self.agents.ids());
+        for (agent_id, kind, skill, completed) in reports.into_iter().flatten() {
+            let delta = self.progression.observe(agent_id, kind, completed, skill, &self.config.progression, now);
+            if delta == 0 {
+                continue;
+            }
+            self.agents.call(agent_id, move |agent| agent.adjust_skill(kind, delta)).await?;
+            if delta > 0 {
+                println!("🎓 {} improved at {:?} work: {} → {}", self.actor_name(agent_id), kind, skill, skill.saturating_add(delta as u8).min(100));
+            }
+        }
+        Ok(())
+    }
+
+    /// Risks currently reported across the company, one finding per source
+    async fn collect_risks(&self) -> Result<Vec<RiskFinding>, Box<dyn std::error::Error>> {
+        let now = self.scheduler.now();
+        let mut findings = Vec::new();
+
+        for task in self.task_graph.overdue(now) {
+            let late = now - task.started_at.expect("overdue tasks have started") - chrono::Duration::hours(task.estimate_hours);
+            findings.push(RiskFinding {
+                key: format!("project:{}", task.project_id),
+                category: RiskCategory::Project,
+                title: format!("Project {} late on {}", task.project_id, task.title),
+                department: task.department.clone(),
+                likelihood: (1 + late.num_days() / 2).clamp(1, 5) as u8,
+                impact: 3,
+            });
+        }
+
+        // Every InfoSec agent scans and audits on its own; the worst view counts
+        let infosec = self.agents.call_all(|agent| agent.as_infosec()
+            .map(|infosec| (infosec.open_vulnerabilities.clone(), infosec.compliance_status.clone()))).await?;
+        let mut systems: BTreeMap<String, (u8, f32)> = BTreeMap::new();
+        let mut standards: BTreeMap<String, (u8, HashSet<String>)> = BTreeMap::new();
+        for (vulnerabilities, compliance) in infosec.into_iter().flatten() {
+            for vulnerability in vulnerabilities {
+                let system = systems.entry(vulnerability.affected_system).or_insert((0, 0.0));
+                system.0 = system.0.saturating_add(1);
+                system.1 = system.1.max(vulnerability.cvss_score);
+            }
+            for (standard, score) in [("GDPR", compliance.gdpr_compliance), ("SOC 2", compliance.soc2_compliance), ("ISO 27001", compliance.iso27001_compliance)] {
+                let entry = standards.entry(standard.to_string()).or_insert((100, HashSet::new()));
+                entry.0 = entry.0.min(score);
+            }
+            for issue in compliance.open_issues {
+                standards.entry(issue.standard).or_insert((100, HashSet::new())).1.insert(issue.id);
+            }
+        }
+        for (system, (count, cvss)) in systems {
+            findings.push(RiskFinding {
+                key: format!("security:{}", system),
+                category: RiskCategory::Security,
+                title: format!("{} open vulnerabilities on {} (CVSS up to {:.1})", count, system, cvss),
+                department: Department::InfoSec.as_str().to_string(),
+                likelihood: count.clamp(1, 5),
+                impact: match cvss {
+                    c if c >= 9.0 => 5,
+                    c if c >= 7.0 => 4,
+                    c if c >= 4.0 => 3,
+                    _ => 2,
+                },
+            });
+        }
+        for (standard, (score, issues)) in standards {
+            if score >= COMPLIANCE_TARGET && issues.is_empty() {
+                continue;
+            }
+            findings.push(RiskFinding {
+                key: format!("compliance:{}", standard),
+                category: RiskCategory::Compliance,
+                title: format!("{} at {}% with {} open issues", standard, score, issues.len()),
+                department: Department::InfoSec.as_str().to_string(),
+                likelihood: (usize::from(COMPLIANCE_TARGET.saturating_sub(score) / 10) + issues.len()).clamp(1, 5) as u8,
+                impact: 4,
+            });
+        }
+
+        let mut vendors = Vec::new();
+        if let Some(email) = &self.email {
+            vendors.push(("email", email.transport_name(), email.stats.failed, Department::Ops));
+        }
+        vendors.push(("webhooks", "webhook subscribers".to_string(), self.webhooks.stats.failed + self.webhooks.stats.dropped, Department::DevOps));
+        for (vendor, name, failed, department) in vendors.into_iter().filter(|vendor| vendor.2 > 0) {
+            findings.push(RiskFinding {
+                key: format!("vendor:{}", vendor),
+                category: RiskCategory::Vendor,
+                title: format!("{} failed {} deliveries", name, failed),
+                department: department.as_str().to_string(),
+                likelihood: failed.clamp(1, 5) as u8,
+                impact: 2,
+            });
+        }
+
+        for (task, dependents) in self.task_graph.bottlenecks(2) {
+            findings.push(RiskFinding {
+                key: format!("spof:task:{}", task.id),
+                category: RiskCategory::SinglePointOfFailure,
+                title: format!("{} tasks of project {} wait on {}", dependents, task.project_id, task.title),
+                department: task.department.clone(),
+                likelihood: 2,
+                impact: (1 + dependents).min(5) as u8,
+            });
+        }
+        let mut staff: BTreeMap<&str, usize> = BTreeMap::new();
+        for agent in self.agents.infos() {
+            *staff.entry(agent.department.as_str()).or_default() += 1;
+        }
+        for (department, _) in staff.into_iter().filter(|(_, count)| *count == 1) {
+            findings.push(RiskFinding {
+                key: format!("spof:department:{}", department),
+                category: RiskCategory::SinglePointOfFailure,
+                title: format!("{} rests on a single agent", department),
+                department: department.to_string(),
+                likelihood: 2,
+                impact: 4,
+            });
+        }
+        Ok(findings)
+    }
+
+    /// Update the risk register between reviews
+    async fn refresh_risks(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let findings = self.collect_risks().await?;
+        let now = self.scheduler.now();
+        for key in self.risks.refresh(findings, now) {
+            if let Some(risk) = self.risks.risk(&key) {
+                println!("🧯 New {} risk (score {}): {}", risk.category.as_str(), risk.score(), risk.title);
+            }
+        }
+        Ok(())
+    }
+
+    /// Quarterly executive review: department managers take ownership of the
+    /// risks above the bar
+    async fn hold_risk_review(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let findings = self.collect_risks().await?;
+        let executives: BTreeMap<String, Uuid> = self.agents.infos()
+            .filter(|agent| agent.manager_id.is_none())
+            .map(|agent| (agent.department.as_str().to_string(), agent.id))
+            .collect();
+        let now = self.scheduler.now();
+        let review = self.risks.review(findings, &executives, &self.config.risks, now).clone();
+
+        let open: Vec<String> = review.open_by_category.iter().map(|(category, count)| format!("{} {}", count, category)).collect();
+        println!("🧯 Quarterly risk review: {} open ({}), {} mitigated since last review",
+               review.open_by_category.values().sum::<usize>(), open.join(", "), review.mitigated);
+        for key in &review.top_risks {
+            let Some(risk) = self.risks.risk(key) else {
+                continue;
+            };
+            let owner = risk.owner.map_or("unowned".to_string(), |owner| self.actor_name(owner));
+            println!("   {:>2} {} ({})", risk.score(), risk.title, owner);
+        }
+        for key in &review.assigned {
+            if let Some((mitigation, Some(owner))) = self.risks.risk(key).map(|risk| (risk.mitigation.clone(), risk.owner)) {
+                println!("   📌 {} owns: {}", self.actor_name(owner), mitigation.unwrap_or_default());
+            }
+        }
+        for key in &review.overdue {
+            if let Some(risk) = self.risks.risk(key) {
+                println!("   ⏰ Mitigation overdue: {}", risk.title);
+            }
+        }
+        self.risks.prune_mitigated(now - chrono::Duration::days(365));
+        Ok(())
+    }
+
+    /// Weekly CEO review: KPIs against the quarter's goals, then headcount,
+    /// budget and any reorganization for the week ahead
+    async fn hold_executive_review(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let departments = self.agents.call_all(|agent| (agent.get_agent().department.as_str().to_string(), agent.backlog())).await?;
+        let mut view = CompanyView {
+            kpis: Kpi::ALL.iter().filter_map(|kpi| self.kpi_history.latest(*kpi).map(|value| (*kpi, value))).collect(),
+            monthly_revenue: self.catalog.monthly_revenue(),
+            ..CompanyView::default()
+        };
+        for (department, backlog) in departments {
+            *view.staff.entry(department.clone()).or_default() += 1;
+            *view.backlog.entry(department).or_default() += backlog;
+        }
+        for task in self.task_graph.ready() {
+            if !view.staff.contains_key(&task.department) && !view.unstaffed.contains(&task.department) {
+                view.unstaffed.push(task.department.clone());
+            }
+        }
+
+        let review = self.executive.review(&view, &self.config.executive, self.scheduler.now()).clone();
+        println!("🏛️ Executive review: {} of {} quarterly goals on track",
+               review.goals_met, review.goals_met + review.goals_missed.len());
+        for kpi in &review.goals_missed {
+            if let (Some(goal), Some(value)) = (self.executive.goals.iter().find(|goal| goal.kpi == *kpi), review.kpis.get(kpi)) {
+                println!("   🎯 {} at {:.1}, goal {:.1}", kpi.name(), value, goal.target);
+            }
+        }
+        for reorganization in &review.reorganizations {
+            println!("   🏗️ {}", reorganization.describe());
+        }
+        for (department, allocation) in &self.executive.allocations {
+            println!("   {}: {} headcount, ${:.0}/month", department, allocation.headcount, allocation.budget);
+        }
+        Ok(())
+    }
+
+    /// Feed the metrics endpoint the state a step left behind
+    async fn record_metrics(&self, duration: std::time::Duration) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        match self.metrics_sample().await {
+            Ok(sample) => metrics.record_step(duration, sample),
+            Err(e) => println!("⚠️ Metrics sample skipped: {}", e),
+        }
+    }
+
+    /// Current message, ticket, incident, deployment and agent counts
+    async fn metrics_sample(&self) -> Result<MetricsSample, Box<dyn std::error::Error>> {
+        let states = self.agents.call_all(|agent| {
+            let tickets = agent.as_ops().map_or(0, |ops| ops.support_tickets.values()
+                .filter(|t| matches!(t.status, departments::ops::TicketStatus::Open | departments::ops::TicketStatus::InProgress))
+                .count());
+            let incidents: Vec<Severity> = agent.as_ops().into_iter()
+                .flat_map(|ops| ops.active_incidents().map(|incident| incident.severity.clone()))
+                .collect();
+            let deployments = agent.as_devops().map_or(0, |devops| devops.active_deployments.values()
+                .filter(|d| matches!(d.status, departments::devops::DeploymentStatus::Pending | departments::devops::DeploymentStatus::InProgress))
+                .count());
+            (tickets, incidents, deployments)
+        }).await?;
+
+        let mut sample = MetricsSample {
+            step_count: self.step_count,
+            messages_delivered: self.router.stats.delivered,
+            messages_failed: self.router.stats.failed,
+            messages_dead_lettered: self.router.stats.dead_lettered,
+            messages_queued: self.router.queued() + self.router.pending_retries(),
+            ticket_queue_depth: self.ticket_queue.depth(),
+            ticket_queue_oldest_wait_minutes: self.ticket_queue.oldest_wait_minutes(self.scheduler.now()),
+            ticket_queue_mean_wait_minutes: self.ticket_queue.mean_wait_minutes().unwrap_or_default(),
+            ..MetricsSample::default()
+        };
+        for (tickets, incidents, deployments) in states {
+            sample.tickets_open += tickets;
+            sample.deployments_active += deployments;
+            for severity in incidents {
+                *sample.incidents_active.entry(format!("{:?}", severity)).or_default() += 1;
+            }
+        }
+        for agent in self.agents.infos() {
+            *sample.agents.entry(agent.department.as_str().to_string()).or_default() += 1;
+        }
+        Ok(sample)
+    }
+
+    /// Open the history database when one is configured
+    #[cfg(feature = "sqlite")]
+    async fn open_history(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.config.history.database {
+            self.history = Some(history::HistoryStore::open(path).await?);
+            println!("🗄️ Recording history to {}", path.display());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    async fn open_history(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.config.history.database.is_some() {
+            println!("⚠️ history.database is set but this build lacks the sqlite feature; history is not recorded");
+        }
+        Ok(())
+    }
+
+    /// Write tickets, incidents and project tasks that changed to the history database
+    #[cfg(feature = "sqlite")]
+    async fn record_history(&mut self) {
+        if self.history.is_none() {
+            return;
+        }
+        let states = self.agents.call_all(|agent| {
+            let tickets: Vec<_> = agent.as_ops().into_iter().flat_map(|ops| ops.support_tickets.values().cloned()).collect();
+            let incidents: Vec<_> = agent.as_ops().into_iter().flat_map(|ops| ops.incidents.values().cloned()).collect();
+            let security_incidents: Vec<_> = agent.as_infosec().into_iter().flat_map(|infosec| infosec.active_incidents.values().cloned()).collect();
+            (tickets, incidents, security_incidents)
+        }).await;
+        let states = match states {
+            Ok(states) => states,
+            Err(e) => {
+                println!("⚠️ History not recorded: {}", e);
+                return;
+            }
+        };
+        let records = states.iter()
+            .flat_map(|(tickets, incidents, security_incidents)| {
+                tickets.iter().map(history::HistoryRecord::Ticket)
+                    .chain(incidents.iter().map(history::HistoryRecord::Incident))
+                    .chain(security_incidents.iter().map(history::HistoryRecord::SecurityIncident))
+            })
+            .chain(self.task_graph.tasks().chain(&self.delivered_tasks).map(history::HistoryRecord::ProjectTask));
+
+        let store = self.history.as_mut().expect("history checked above");
+        match store.record(records).await {
+            Ok(_) => self.delivered_tasks.clear(),
+            Err(e) => println!("⚠️ History not recorded: {}", e),
+        }
+    }
+
+    /// Execute one simulation step
+    ///
+    /// Agents work in their own scheduled turns; the step gathers what they
+    /// produced and generates company-level events.
+    async fn run_simulation_step(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Bring back crashed agents before handing them work
+        self.supervise_agents().await;
+
+        // Deliver messages dispatched in earlier steps
+        self.deliver_messages().await?;
+
+        // Run the daily tasks agents took up on their turns, all at once
+        self.run_daily_tasks().await;
+
+        // Collect messages agents queued for others
+        self.collect_outgoing_messages().await?;
+
+        // Handle inter-agent communication
+        self.process_messages().await?;
+
+        // Move merged commits through build, test and artifact
+        self.run_pipelines();
+
+        // Promote builds that shipped to an environment and soaked there
+        self.advance_promotions().await;
+
+        // Write up deployments that shipped
+        self.publish_release_notes().await;
+
+        // Check for new projects or tasks
+        self.generate_company_activities().await?;
+
+        // Start project tasks whose dependencies are done
+        self.start_ready_tasks().await;
+
+        // Monitor system health
+        self.monitor_system_health().await?;
+
+        // DNS changes take effect once their old records expire
+        self.propagate_dns().await?;
+
+        // Health-check load balancer backends and spread their requests
+        self.run_load_balancers().await?;
+
+        // Fail and repair WAN links and reroute between sites
+        self.run_wan().await?;
+
+        // Start DDoS attacks on public segments and escalate their mitigation
+        self.defend_ddos().await?;
+
+        // Log the step's traffic flows
+        self.capture_flows().await?;
+
+        // Hand queued tickets to Ops agents with room
+        self.route_tickets().await?;
+
+        // Carry out approved changes that are due
+        self.execute_changes().await?;
+
+        // Open or close department breakers before admitting new intake
+        self.update_intake_breakers().await?;
+
+        // Break cycles of agents waiting on each other
+        self.detect_deadlocks();
+
+        // Dispatch everything queued during the step
+        self.flush_pending_messages().await?;
+
+        // Notify webhook subscribers of entity changes
+        self.publish_entity_changes().await;
+
+        // Mirror tickets to the issue tracker and apply edits made there
+        #[cfg(feature = "issue-sync")]
+        self.sync_issues().await;
+
+        #[cfg(feature = "sqlite")]
+        self.record_history().await;
+
+        Ok(())
+    }
+
+    /// Send all queued messages through the message bus
+    async fn flush_pending_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_sent(self.pending_messages.len() as u64);
+        }
+        for mut message in std::mem::take(&mut self.pending_messages) {
+            self.threads.assign(&mut message);
+            if self.message_log.len() == MESSAGE_LOG_CAPACITY {
+                self.message_log.pop_front();
+            }
+            self.message_log.push_back(message.clone());
+            if let Some(journal) = &mut self.journal {
+                journal.append(self.step_count, self.scheduler.now(), &message)?;
+            }
+            self.wait_graph.observe(&message, self.scheduler.now());
+            self.message_bus.send_message(message.clone()).await?;
+
+            match SystemActor::from_id(message.to_agent) {
+                Some(actor) => {
+                    self.router.acknowledge(&message, DeliveryStatus::Delivered);
+                    self.handle_system_message(actor, message)?;
+                }
+                None => self.admit_or_defer(message),
+            }
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Name and department of an agent, for chains of agents
+    fn actor_label(&self, id: Uuid) -> String {
+        match self.agents.info(&id) {
+            Some(agent) => format!("{} ({})", agent.name, agent.department.as_str()),
+            None => self.actor_name(id),
+        }
+    }
+
+    /// Queue a message for its recipient unless the recipient's department is shedding intake
+    fn admit_or_defer(&mut self, message: Message) {
+        let Some(department) = self.agents.info(&message.to_agent).map(|agent| agent.department.as_str()) else {
+            self.router.enqueue(message);
+            return;
+        };
+
+        match self.intake.admit(&self.config.intake, department, &message, self.scheduler.now()) {
+            Admission::Accept => self.router.enqueue(message),
+            Admission::Defer { retry_after } => {
+                println!("⏸️ Deferred {} message for {} until {}", message.message_type, department, retry_after.format("%Y-%m-%d %H:%M"));
+                if !SystemActor::is_system(message.from_agent) {
+                    self.pending_messages.push(Message {
+                        id: Uuid::new_v4(),
+                        from_agent: SystemActor::Orchestrator.id(),
+                        to_agent: message.from_agent,
+                        message_type: "intake_deferred".to_string(),
+                        content: format!("{} is overloaded; {} deferred, retry after {}",
+                                       department, message.message_type, retry_after.format("%Y-%m-%d %H:%M")),
+                        priority: MessagePriority::Low,
+                        timestamp: chrono::Utc::now(),
+                        metadata: HashMap::from([
+                            ("deferred_message_id".to_string(), message.id.to_string()),
+                            (intake::RETRY_AFTER_KEY.to_string(), retry_after.to_rfc3339()),
+                        ]),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Hand every queued message to its recipient's `process_message`, agents in parallel
+    async fn deliver_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Failed deliveries whose backoff has elapsed get another attempt
+        self.router.requeue_due_retries(self.scheduler.now());
+
+        // Each recipient's actor works through its batch; all actors run at once
+        let now = self.scheduler.now();
+        let mut outcomes = Vec::new();
+        let mut batches = Vec::new();
+        for recipient in self.router.recipients() {
+            // Agents backing off after failures keep their messages queued
+            if !self.supervisor.can_run(&recipient, now) {
+                continue;
+            }
+            // Slow responders leave their inbox for a later round now and then
+            let personality = self.personality_of(&recipient);
+            if personality.defers(&mut self.rng) {
+                continue;
+            }
+            let batch = self.router.take_batch(recipient, &self.config.delivery);
+            if !self.agents.contains(&recipient) {
+                for message in batch {
+                    println!("📭 Undeliverable {} message for {}", message.message_type, recipient.simple());
+                    outcomes.push((recipient, message, DeliveryStatus::Undeliverable));
+                }
+                continue;
+            }
+            batches.push((recipient, batch));
+        }
+
+        let mut batch_errors: BTreeMap<Uuid, Option<AgentError>> = BTreeMap::new();
+        for (recipient, message, status) in self.agents.deliver(batches).await {
+            let error = batch_errors.entry(recipient).or_insert(None);
+            if let DeliveryStatus::Failed(e) = &status {
+                println!("⚠️ {} failed to process {} message: {}", self.actor_name(recipient), message.message_type, e);
+                error.get_or_insert_with(|| e.clone());
+            }
+            outcomes.push((recipient, message, status));
+        }
+        for (recipient, error) in batch_errors {
+            self.supervise_outcome(recipient, error.map_or(Ok(()), Err));
+        }
+
+        for (recipient, message, status) in outcomes {
+            let status = match status {
+                DeliveryStatus::Delivered => {
+                    self.router.delivered(&message);
+                    if message.message_type == "status_update" {
+                        self.receive_status_update(recipient, &message).await?;
+                    }
+                    if self.config.memory.enabled && self.agents.contains(&message.from_agent) {
+                        self.memories.remember_message(message.from_agent, recipient, &message.message_type, now, &self.config.memory);
+                    }
+                    DeliveryStatus::Delivered
+                }
+                failure => match self.router.fail(message.clone(), failure, &self.config.delivery, self.scheduler.now()) {
+                    FailureOutcome::Retry { .. } => continue,
+                    FailureOutcome::DeadLettered => {
+                        println!("💀 Dead-lettered {} message for {} after {} retries",
+                               message.message_type, self.actor_name(recipient), self.config.delivery.max_retries);
+                        DeliveryStatus::DeadLettered
+                    }
+                },
+            };
+
+            // Senders hear about the final outcome only, not every retry
+            if message.metadata.get(routing::ACK_REQUESTED_KEY).is_some_and(|v| v == "true") {
+                self.queue_delivery_ack(&message, &status);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run every due agent's daily tasks concurrently; an agent that fails
+    /// or crashes is supervised on its own while the others carry on
+    async fn run_daily_tasks(&mut self) {
+        let due = std::mem::take(&mut self.due_daily_tasks);
+        for (agent_id, outcome) in self.agents.daily_tasks(&due).await {
+            let outcome = match outcome {
+                Ok(outcome) => outcome,
+                // Agents removed since their turn simply drop out
+                Err(RuntimeError::UnknownAgent(_)) => continue,
+                Err(e) => Err(e.into()),
+            };
+            self.supervise_outcome(agent_id, outcome);
+        }
+    }
+
+    /// Tell the sender of a message how its delivery went
+    fn queue_delivery_ack(&mut self, message: &Message, status: &DeliveryStatus) {
+        self.pending_messages.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: message.from_agent,
+            message_type: "delivery_ack".to_string(),
+            content: format!("{} message {:?}", message.message_type, status),
+            priority: MessagePriority::Low,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                ("acked_message_id".to_string(), message.id.to_string()),
+            ]),
+        });
+    }
+
+    /// Process one agent's scheduled turn
+    async fn process_agent_turn(&mut self, agent_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        // Offline agents, e.g. backing off after failures, and agents on
+        // leave sit their turn out
+        let backlog = self.agents.call(agent_id, |agent| agent.backlog()).await.unwrap_or(0);
+        if !self.update_agent_state(agent_id, backlog).is_available() {
+            return Ok(());
+        }
+
+        if let Some(agent) = self.agents.info(&agent_id).cloned() {
+            let agent_name = agent.name.clone();
+
+            // The agent's policy decides its turn
+            let mut policy = match self.policies.remove(&agent_id) {
+                Some(policy) => policy,
+                None => self.policy_for(&agent).unwrap_or_else(|e| {
+                    println!("⚠️ {} falls back to the random policy: {}", agent_name, e);
+                    Box::new(RandomPolicy { daily_tasks: self.config.event_probabilities.daily_tasks })
+                }),
+            };
+            let now = self.scheduler.now();
+            let turn = AgentTurn {
+                agent_id,
+                name: agent_name.clone(),
+                department: agent.department.clone(),
+                backlog,
+                now,
+                working_hours: self.on_duty(&agent_id, now),
+                personality: self.personality_of(&agent_id),
+            };
+            let decision = policy.decide(&turn, &mut self.rng).await;
+            self.policies.insert(agent_id, policy);
+
+            if decision.activity {
+                match agent.department {
+                    // Engineers push their features along
+                    Department::Engineering => println!("💻 {} (Engineering): Reviewing pull requests", agent_name),
+                    // DevOps agents perform infrastructure tasks and run their deployments
+                    Department::DevOps => {
+                        println!("🔧 {} (DevOps): Performing infrastructure maintenance", agent_name);
+                        self.run_deployments(agent_id).await;
+                        self.apply_infrastructure(agent_id).await;
+                        self.run_maintenance(agent_id).await;
+                    }
+                    // InfoSec agents monitor security and work their incidents
+                    Department::InfoSec => {
+                        println!("🔒 {} (InfoSec): Conducting security scan", agent_name);
+                        self.work_on_security_incident(agent_id).await;
+                    }
+                    // Networking agents optimize network
+                    Department::Networking => println!("🌐 {} (Networking): Optimizing network performance", agent_name),
+                    // Ops agents handle support and work their incidents
+                    Department::Ops => {
+                        println!("🎫 {} (Ops): Processing support tickets", agent_name);
+                        self.work_on_tickets(agent_id).await;
+                        self.work_on_incident(agent_id).await;
+                    }
+                    // Marketing agents tend their campaigns
+                    Department::Marketing => println!("📣 {} (Marketing): Reviewing campaign performance", agent_name),
+                    _ => {}
+                }
+            }
+
+            // Daily tasks run with every other agent's at the next step
+            if decision.daily_tasks && !self.due_daily_tasks.contains(&agent_id) {
+                self.due_daily_tasks.push(agent_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move unanswered tickets off Ops agents over the open-ticket limit,
+    /// then hand queued tickets, most urgent first, to agents with room
+    async fn route_tickets(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.ticket_queue.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let agents = self.agents.call_all(|agent| {
+            agent.as_ops().map(|ops| QueueAgent { agent_id: ops.agent.id, support_skill: ops.support_skill, open_tickets: ops.open_tickets() })
+        }).await?;
+        let mut agents: Vec<QueueAgent> = agents.into_iter().flatten().collect();
+        self.rebalance_tickets(&mut agents, &settings).await;
+
+        for routed in self.ticket_queue.route(&mut agents, &settings, self.scheduler.now()) {
+            println!("📥 '{}' went to {} after {} minutes in the queue", routed.ticket.request.title, self.actor_name(routed.agent_id), routed.waited_minutes);
+            self.pending_messages.push(ticket_message(routed.ticket.from, routed.agent_id, routed.ticket.request));
+        }
+        Ok(())
+    }
+
+    /// Give the newest unanswered tickets of agents over the open-ticket
+    /// limit to agents with room
+    async fn rebalance_tickets(&mut self, agents: &mut [QueueAgent], settings: &config::TicketQueueSettings) {
+        let overloaded: Vec<(Uuid, usize)> = agents.iter()
+            .filter(|agent| agent.open_tickets > settings.max_open_per_agent)
+            .map(|agent| (agent.agent_id, agent.open_tickets - settings.max_open_per_agent))
+            .collect();
+        for (owner, excess) in overloaded {
+            let tickets = self.agents.call(owner, |agent| {
+                let mut tickets: Vec<_> = agent.as_ops().into_iter().flat_map(|ops| ops.unanswered_tickets())
+                    .map(|ticket| (ticket.id, ticket.title.clone(), ticket.tags.clone(), ticket.priority.clone(), ticket.created_at))
+                    .collect();
+                tickets.sort_by(|a, b| b.4.cmp(&a.4));
+                tickets
+            }).await.unwrap_or_default();
+            for (ticket_id, title, tags, priority, _) in tickets.into_iter().take(excess) {
+                let skill = ticketqueue::required_skill(&tags, &priority, &settings.routes);
+                let Some(to) = ticketqueue::pick(agents, skill, settings.max_open_per_agent, Some(owner)) else {
+                    break;
+                };
+                let handover = self.agents.call(owner, move |agent| agent.as_ops_mut().and_then(|ops| ops.hand_over(ticket_id))).await;
+                let Ok(Some(handover)) = handover else {
+                    continue;
+                };
+                let taken = self.agents.call(to, move |agent| {
+                    if let Some(ops) = agent.as_ops_mut() {
+                        ops.take_over(handover);
+                    }
+                }).await;
+                if taken.is_err() {
+                    continue;
+                }
+                for agent in agents.iter_mut() {
+                    if agent.agent_id == owner {
+                        agent.open_tickets -= 1;
+                    } else if agent.agent_id == to {
+                        agent.open_tickets += 1;
+                    }
+                }
+                self.ticket_queue.reassigned += 1;
+                println!("🔀 Moved '{}' from {}, over its ticket limit, to {}", title, self.actor_name(owner), self.actor_name(to));
+            }
+        }
+    }
+
+    /// Carry out each Ops agent's approved changes at their scheduled time;
+    /// a failed change its rollback could not undo becomes an incident for
+    /// that agent
+    async fn execute_changes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.changes.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let seed = self.rng.gen();
+        let events = self.agents.call_all(move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_ops_mut().map(|ops| (ops.agent.id, ops.execute_changes(now, &settings, &mut rng)))
+        }).await?;
+        for (agent_id, events) in events.into_iter().flatten() {
+            for event in events {
+                println!("🛠️ {} {}", self.actor_name(agent_id), event.description());
+                if event.kind != ChangeEventKind::Failed {
+                    continue;
+                }
+                let payload = MessagePayload::IncidentDeclared {
+                    title: format!("Failed change: {}", event.title),
+                    severity: Severity::Sev2,
+                    affected_services: vec![],
+                    affected_customers: vec![],
+                };
+                let content = format!("Change '{}' failed and its rollback did not restore service", event.title);
+                self.pending_messages.push(payload.into_message(SystemActor::Scheduler.id(), agent_id, content, MessagePriority::High));
+            }
+        }
+        Ok(())
+    }
+
+    /// Work an Ops agent's support tickets along and let the customers'
+    /// CSAT answers move their satisfaction
+    async fn work_on_tickets(&mut self, agent_id: Uuid) {
+        let settings = self.config.tickets.clone();
+        if !settings.enabled {
+            return;
+        }
+        let now = self.scheduler.now();
+        let seed = self.rng.gen();
+        let events = self.agents.call(agent_id, move |agent| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            agent.as_ops_mut().map(|ops| ops.work_tickets(now, &settings, &mut rng)).unwrap_or_default()
+        }).await.unwrap_or_default();
+        for event in events {
+            println!("🎫 {} {}", self.actor_name(agent_id), event.description());
+            if matches!(event.kind, TicketEventKind::Resolved { .. }) && self.config.memory.enabled {
+                self.memories.remember_task(agent_id, CompletedTask::ticket(&event.title, now), &self.config.memory);
+            }
+            let TicketEventKind::Surveyed { score } = event.kind else {
+                continue;
+            };
+            if let Some(customer) = event.customer_id.as_ref().and_then(|id| self.catalog.customers.get_mut(id)) {
+                customer.ticket_rated(score, &self.config.customers.satisfaction);
+            }
+        }
+    }
+
+    /// Work an Ops agent's oldest active incident: run the matching runbook
+    /// first, otherwise search the knowledge base when starting, then resolve
+    /// it once the work it takes is done and file what was learned
+    async fn work_on_incident(&mut self, agent_id: Uuid) {
+        let now = self.scheduler.now();
+        let incident = self.agents.call(agent_id, |agent| {
+            agent.as_ops().and_then(|ops| ops.active_incidents().min_by_key(|incident| incident.created_at).cloned())
+        }).await.ok().flatten();
+        let Some(incident) = incident else {
+            return;
+        };
+        if self.run_runbook(agent_id, &incident).await {
+            return;
+        }
+        let settings = &self.config.knowledge;
+        let incident_id = incident.id;
+
+        let Some(consultation) = incident.knowledge.clone() else {
+            let mut consultation = if settings.enabled {
+                self.knowledge.consult(&incident, settings, now)
+            } else {
+                Consultation { at: now, article: None, effort: 1.0 }
+            };
+            // An agent that fixed one like it before knows what to do
+            if let Some(fixed) = self.recall_fix(agent_id, &incident) {
+                println!("🧠 {} remembers fixing '{}' and applies the same fix to '{}'", self.actor_name(agent_id), fixed.title, incident.title);
+                consultation.effort = consultation.effort.min(self.config.memory.recall_effort);
+            }
+            // Thorough agents take longer over an incident, careless ones vary more
+            let personality = self.personality_of(&agent_id);
+            consultation.effort *= personality.duration_factor(&mut self.rng);
+            if let Some(article) = consultation.article.and_then(|id| self.knowledge.article(id)) {
+                println!("📚 {} found the {} '{}' for '{}'", self.actor_name(agent_id), article.kind.as_str(), article.title, incident.title);
+            }
+            let _ = self.agents.call(agent_id, move |agent| {
+                if let Some(ops) = agent.as_ops_mut() {
+                    ops.consult_knowledge(incident_id, consultation);
+                }
+            }).await;
+            return;
+        };
+        let worked = now - consultation.at;
+        if worked.num_minutes() < (settings.incident_hours * consultation.effort * 60.0) as i64 {
+            return;
+        }
+
+        let article = consultation.article.and_then(|id| self.knowledge.article(id));
+        let recalled = self.recall_fix(agent_id, &incident).and_then(|fixed| fixed.resolution.clone());
+        let root_cause = article.and_then(|article| article.root_cause.clone()).unwrap_or_else(|| incident.description.clone());
+        let resolution = article.map(|article| article.body.clone()).filter(|body| !body.is_empty())
+            .or(recalled)
+            .unwrap_or_else(|| format!("Restored {} after investigation", incident.affected_services.join(", ")));
+        let resolved = self.agents.call(agent_id, move |agent| {
+            agent.as_ops_mut().and_then(|ops| ops.resolve_incident(incident_id, root_cause, resolution))
+        }).await.ok().flatten();
+        let Some(resolved) = resolved else {
+            return;
+        };
+        if self.config.memory.enabled {
+            self.memories.remember_task(agent_id, CompletedTask::incident(&resolved, now), &self.config.memory);
+        }
+        let settings = &self.config.knowledge;
+        if !settings.enabled {
+            return;
+        }
+
+        if self.knowledge.record_resolution(&resolved, agent_id, now, settings.min_similarity).is_some() {
+            println!("📚 {} wrote up how '{}' was fixed", self.actor_name(agent_id), resolved.title);
+        }
+        if resolved.severity.rank() >= settings.post_mortem_severity.rank() {
+            let hours = worked.num_minutes() as f64 / 60.0;
+            self.knowledge.record_post_mortem(&resolved, hours, agent_id, now);
+            println!("📝 {} filed a post-mortem for '{}'", self.actor_name(agent_id), resolved.title);
+        }
+    }
+
+    /// An incident the agent fixed before that is like this one
+    fn recall_fix(&self, agent_id: Uuid, incident: &departments::ops::Incident) -> Option<&CompletedTask> {
+        let settings = &self.config.memory;
+        if !settings.enabled {
+            return None;
+        }
+        self.memories.get(&agent_id)?.recall(&knowledge::keywords(incident), settings.recall_similarity)
+    }
+
+    /// Run the step of an incident's runbook that is due, starting the
+    /// matching runbook first; resolves the incident when a step fixes it and
+    /// escalates to DevOps once every step failed. False when no runbook
+    /// applies or it gave up, and the incident is worked by hand
+    async fn run_runbook(&mut self, agent_id: Uuid, incident: &departments::ops::Incident) -> bool {
+        let settings = self.config.runbooks.clone();
+        if !settings.enabled {
+            return false;
+        }
+        let now = self.scheduler.now();
+        let incident_id = incident.id;
+        let mut run = match &incident.runbook {
+            Some(run) if run.outcome.is_some() => return false,
+            Some(run) => run.clone(),
+            None => {
+                let Some(runbook) = runbook::find(incident, &settings.runbooks) else {
+                    return false;
+                };
+                let run = RunbookRun::start(runbook, &settings, now);
+                println!("📗 {} started runbook '{}' on '{}'", self.actor_name(agent_id), runbook.name, incident.title);
+                let _ = self.agents.call(agent_id, move |agent| {
+                    if let Some(ops) = agent.as_ops_mut() {
+                        ops.track_runbook(incident_id, run);
+                    }
+                }).await;
+                return true;
+            }
+        };
+        // A runbook dropped from the config is given up on
+        let steps = settings.runbooks.iter().find(|runbook| runbook.name == run.runbook).cloned()
+            .unwrap_or_else(|| config::RunbookDefinition { name: run.runbook.clone(), keywords: Vec::new(), steps: Vec::new() });
+        if let Some(step) = run.step(&steps, &settings, now, &mut self.rng) {
+            println!("📗 {} {} '{}' (runbook '{}', step {}): {}", self.actor_name(agent_id),
+                     if step.fixed { "fixed" } else { "did not fix" }, incident.title, run.runbook, step.step + 1, step.action);
+        }
+        let Some(outcome) = run.outcome else {
+            let _ = self.agents.call(agent_id, move |agent| {
+                if let Some(ops) = agent.as_ops_mut() {
+                    ops.track_runbook(incident_id, run);
+                }
+            }).await;
+            return true;
+        };
+        self.knowledge.record_runbook(&run, now);
+
+        let tried: Vec<String> = steps.steps.iter().take(run.steps_tried).map(|step| step.action.clone()).collect();
+        if outcome == RunbookOutcome::Escalated {
+            if let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) {
+                let content = format!("Runbook '{}' did not fix '{}'", run.runbook, incident.title);
+                let escalation = MessagePayload::RunbookEscalation {
+                    incident_id,
+                    title: incident.title.clone(),
+                    affected_services: incident.affected_services.clone(),
+                    runbook: run.runbook.clone(),
+                    steps_tried: tried,
+                };
+                self.pending_messages.push(escalation.into_message(agent_id, devops_id, content, MessagePriority::High));
+            }
+            let _ = self.agents.call(agent_id, move |agent| {
+                if let Some(ops) = agent.as_ops_mut() {
+                    ops.track_runbook(incident_id, run);
+                }
+            }).await;
+            return false;
+        }
+
+        let root_cause = format!("Known issue covered by runbook '{}'", run.runbook);
+        let resolution = format!("Runbook '{}': {}", run.runbook, tried.last().cloned().unwrap_or_default());
+        let resolved = self.agents.call(agent_id, move |agent| {
+            agent.as_ops_mut().and_then(|ops| {
+                ops.track_runbook(incident_id, run);
+                ops.resolve_incident(incident_id, root_cause, resolution)
+            })
+        }).await.ok().flatten();
+        let knowledge = &self.config.knowledge;
+        if let Some(resolved) = resolved.filter(|resolved| knowledge.enabled && resolved.severity.rank() >= knowledge.post_mortem_severity.rank()) {
+            let hours = (now - resolved.created_at).num_minutes() as f64 / 60.0;
+            self.knowledge.record_post_mortem(&resolved, hours, agent_id, now);
+            println!("📝 {} filed a post-mortem for '{}'", self.actor_name(agent_id), resolved.title);
+        }
+        true
+    }
+
+    /// Resolve an InfoSec agent's oldest security incident once it has
+    /// been worked long enough, then file its post-mortem, built from the
+    /// message journal, in the knowledge base and with InfoSec
+    async fn work_on_security_incident(&mut self, agent_id: Uuid) {
+        let settings = self.config.forensics.clone();
+        if !settings.enabled {
+            return;
+        }
+        let now = self.scheduler.now();
+        let incident = self.agents.call(agent_id, |agent| {
+            agent.as_infosec().and_then(|infosec| infosec.oldest_open_incident().cloned())
+        }).await.ok().flatten();
+        let Some(incident) = incident.filter(|incident| (now - incident.created_at).num_minutes() >= (settings.incident_hours * 60.0) as i64) else {
+            return;
+        };
+
+        let messages: Vec<Message> = self.message_log.iter().cloned().collect();
+        let mut post_mortem = forensics::investigate(&incident, &messages, settings.action_item_days, now);
+        let root_cause = post_mortem.root_cause.description().to_string();
+        let incident_id = incident.id;
+        let resolved = self.agents.call(agent_id, move |agent| {
+            agent.as_infosec_mut().and_then(|infosec| infosec.resolve_incident(incident_id, &root_cause, now))
+        }).await.ok().flatten();
+        if resolved.is_none() {
+            return;
+        }
+
+        post_mortem.article = Some(self.knowledge.record_security_post_mortem(&post_mortem, agent_id, now));
+        println!("📝 {} filed a post-mortem for '{}': {}, {} action items", self.actor_name(agent_id), post_mortem.title,
+                 post_mortem.root_cause.description(), post_mortem.action_items.len());
+        let _ = self.agents.call(agent_id, move |agent| {
+            if let Some(infosec) = agent.as_infosec_mut() {
+                infosec.record_post_mortem(post_mortem);
+            }
+        }).await;
+    }
+
+    /// Build the decision policy an agent is configured with
+    fn policy_for(&self, agent: &AgentInfo) -> Result<Box<dyn DecisionPolicy>, PolicyError> {
+        let settings = self.config.decisions.policy_for(&agent.name, agent.department.as_str());
+        policy::build(settings, &self.config)
+    }
+
+    /// Personality an agent is configured with; neutral for system actors
+    fn personality_of(&self, agent_id: &Uuid) -> Personality {
+        self.agents.info(agent_id)
+            .map(|agent| self.config.personalities.personality_for(&agent.name, agent.department.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// Give every agent without one its configured decision policy
+    fn assign_policies(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let agents: Vec<AgentInfo> = self.agents.infos().filter(|agent| !self.policies.contains_key(&agent.id)).cloned().collect();
+        for agent in agents {
+            let policy = self.policy_for(&agent)?;
+            self.policies.insert(agent.id, policy);
+        }
+        Ok(())
+    }
+
+    /// Record how an agent's work went, backing it off or recovering it on failure
+    fn supervise_outcome(&mut self, agent_id: Uuid, outcome: Result<(), AgentError>) {
+        let error = match outcome {
+            Ok(()) => {
+                self.supervisor.record_success(agent_id);
+                return;
+            }
+            Err(error) => error,
+        };
+
+        let now = self.scheduler.now();
+        println!("🩺 {} failed: {}", self.actor_name(agent_id), error);
+        if let Some(recovery) = self.supervisor.record_failure(agent_id, error, &self.config.supervision, now) {
+            self.recover_agent(agent_id, recovery);
+        }
+    }
+
+    /// Restart crashed agents and checkpoint healthy ones when due
+    async fn supervise_agents(&mut self) {
+        let now = self.scheduler.now();
+        for agent_id in self.agents.crashed() {
+            println!("💥 {} crashed", self.actor_name(agent_id));
+            let recovery = self.supervisor.record_crash(agent_id, &self.config.supervision, now);
+            self.recover_agent(agent_id, recovery);
+        }
+
+        if self.supervisor.checkpoint_due(&self.config.supervision, now) {
+            match self.agents.snapshot().await {
+                Ok(snapshots) => self.supervisor.store_checkpoints(snapshots, now),
+                Err(e) => println!("⚠️ Agent checkpoint skipped: {}", e),
+            }
+        }
+    }
+
+    /// Respawn an agent from its checkpoint or as a fresh agent with the same identity
+    fn recover_agent(&mut self, agent_id: Uuid, recovery: Recovery) {
+        let Some(info) = self.agents.info(&agent_id).cloned() else {
+            return;
+        };
+        let restored = match recovery {
+            Recovery::Restart => self.supervisor.checkpoint(&agent_id).cloned()
+                .and_then(|checkpoint| checkpoint.into_agent(&self.registry).ok()),
+            Recovery::Replace => None,
+        };
+        let (agent, recovery) = match restored {
+            Some(agent) => (agent, Recovery::Restart),
+            None => {
+                let Ok(mut agent) = self.registry.create(info.department, &info.name, info.manager_id) else {
+                    println!("⚠️ No agent implementation to replace {} with", info.name);
+                    return;
+                };
+                agent.get_agent_mut().id = agent_id;
+                (agent, Recovery::Replace)
+            }
+        };
+
+        // Spawning under the same id stops the old actor; its inbox is untouched
+        self.agents.spawn(agent);
+        self.supervisor.recovered(agent_id, recovery);
+        match recovery {
+            Recovery::Restart => println!("🔄 Restarted {} from its last checkpoint", info.name),
+            Recovery::Replace => println!("🆕 Replaced {} with a fresh agent", info.name),
+        }
+    }
+
+    /// Collect messages from agent outboxes and send them on
+    async fn collect_outgoing_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let outgoing = self.agents.drain_outboxes().await?;
+
+        for mut message in outgoing {
+            // Messages addressed to the simulation itself
+            if let Some(actor) = SystemActor::from_id(message.to_agent) {
+                if message.message_type == departments::ops::SLA_ESCALATION {
+                    self.escalate_breach(message).await;
+                    continue;
+                }
+                self.handle_system_message(actor, message)?;
+                continue;
+            }
+
+            // Resolve department-addressed messages to a concrete agent, trying
+            // the fallback department when the addressed one has no agents
+            if message.to_agent.is_nil() {
+                let target = ["to_department", "fallback_department"].into_iter()
+                    .filter_map(|key| message.metadata.get(key))
+                    .find_map(|dept| self.known_department_agent(message.from_agent, dept).or_else(|| self.find_department_agent(dept)));
+                match target {
+                    Some(agent_id) => message.to_agent = agent_id,
+                    None => {
+                        println!("📭 No recipient for {} message from {}", message.message_type, self.actor_name(message.from_agent));
+                        continue;
+                    }
+                }
+            }
+
+            self.pending_messages.push(message);
+        }
+
+        Ok(())
+    }
+
+    /// Hand a ticket or incident that missed its SLA to a more skilled Ops
+    /// agent, when there is one, and notify the department manager
+    async fn escalate_breach(&mut self, message: Message) {
+        let Some(id) = message.metadata.get("id").and_then(|id| Uuid::parse_str(id).ok()) else {
+            return;
+        };
+        let kind = match message.metadata.get("kind").map(String::as_str) {
+            Some("incident") => WorkKind::Incident,
+            _ => WorkKind::Ticket,
+        };
+        let owner = message.from_agent;
+        let owner_skill = self.agents.call(owner, move |agent| agent.skill(kind)).await.ok().flatten().unwrap_or(0);
+
+        let candidates = self.agents.call_all(move |agent| {
+            let info = agent.get_agent();
+            if info.id == owner || info.department != Department::Ops {
+                return None;
+            }
+            agent.skill(kind)
+                .filter(|skill| *skill > owner_skill)
+                .map(|skill| Candidate { agent_id: info.id, skill, load: agent.backlog() })
+        }).await;
+        let candidates: Vec<Candidate> = candidates.map(|candidates| candidates.into_iter().flatten().collect()).unwrap_or_default();
+        let mut handled_by = owner;
+        if let Some(assignment) = assignment::assign(&candidates, chrono::Duration::hours(1)) {
+            let handover = self.agents.call(owner, move |agent| agent.as_ops_mut().and_then(|ops| ops.hand_over(id))).await;
+            if let Ok(Some(handover)) = handover {
+                let taken = self.agents.call(assignment.agent_id, move |agent| {
+                    if let Some(ops) = agent.as_ops_mut() {
+                        ops.take_over(handover);
+                    }
+                }).await;
+                if taken.is_ok() {
+                    handled_by = assignment.agent_id;
+                }
+            }
+        }
+
+        let manager = self.agents.info(&owner)
+            .and_then(|agent| agent.manager_id)
+            .or_else(|| self.find_department_agent(Department::Ops.as_str()));
+        let content = if handled_by == owner {
+            format!("{} (stays with {})", message.content, self.actor_name(owner))
+        } else {
+            format!("{} (reassigned from {} to {})", message.content, self.actor_name(owner), self.actor_name(handled_by))
+        };
+        println!("⏫ {}", content);
+        // Nobody in Ops could take it; the department head passes it up
+        let head = manager.filter(|manager| self.org.member(manager).is_some_and(|member| member.is_head()));
+        if let Some(head) = head.filter(|_| handled_by == owner) {
+            println!("👔 {} escalated to the CEO: {}", self.actor_name(head), content);
+            self.org.escalate(head, content.clone(), self.scheduler.now());
+        }
+        if let Some(manager) = manager {
+            let mut metadata = message.metadata;
+            metadata.insert("assigned_to".to_string(), handled_by.to_string());
+            self.pending_messages.push(Message {
+                id: Uuid::new_v4(),
+                from_agent: SystemActor::Orchestrator.id(),
+                to_agent: manager,
+                message_type: departments::ops::ESCALATION_NOTICE.to_string(),
+                content,
+                priority: MessagePriority::High,
+                timestamp: chrono::Utc::now(),
+                metadata,
+            });
+        }
+    }
+
+    /// Handle a message addressed to one of the system actors
+    fn handle_system_message(&mut self, actor: SystemActor, message: Message) -> Result<(), Box<dyn std::error::Error>> {
+        match message.message_type.as_str() {
+            "department_report" => {
+                let Some(MessagePayload::DepartmentReport { report }) = MessagePayload::from_message(&message)? else {
+                    return Ok(());
+                };
+                println!("📋 Department report for the CEO: {}", report.summary());
+                self.executive.receive_report(report);
+            }
+            "campaign_report" => {
+                let report: CampaignReport = serde_json::from_str(&message.content)?;
+                println!("📣 Marketing report: {} leads from {} active campaigns (${:.2} spent)",
+                       report.total_leads, report.active_campaigns, report.total_spend);
+                self.campaign_reports.insert(report.agent_id, report);
+            }
+            // DevOps moves release traffic through Networking's load balancers
+            "shift_traffic" => {
+                let Some(networking_id) = self.find_department_agent(Department::Networking.as_str()) else {
+                    println!("⚠️ No Networking agent to shift traffic for {}", self.actor_name(message.from_agent));
+                    return Ok(());
+                };
+                self.pending_messages.push(Message { id: Uuid::new_v4(), to_agent: networking_id, ..message });
+            }
+            // Destructive infrastructure changes go through Ops change management
+            "change_request" => {
+                let Some(ops_id) = self.find_department_agent(Department::Ops.as_str()) else {
+                    println!("⚠️ No Ops agent to review a change for {}", self.actor_name(message.from_agent));
+                    return Ok(());
+                };
+                self.pending_messages.push(Message { id: Uuid::new_v4(), to_agent: ops_id, ..message });
+            }
+            "change_decision" => {
+                let Some(MessagePayload::ChangeDecision { requested_by, .. }) = MessagePayload::from_message(&message)? else {
+                    return Ok(());
+                };
+                if requested_by == SystemActor::Orchestrator.id() {
+                    self.decide_promotion(&message);
+                } else if self.agents.contains(&requested_by) {
+                    self.pending_messages.push(Message { id: Uuid::new_v4(), to_agent: requested_by, ..message });
+                }
+            }
+            "configure_load_balancer" => {
+                let Some(networking_id) = self.find_department_agent(Department::Networking.as_str()) else {
+                    println!("⚠️ No Networking agent to configure load balancers for {}", self.actor_name(message.from_agent));
+                    return Ok(());
+                };
+                self.pending_messages.push(Message { id: Uuid::new_v4(), to_agent: networking_id, ..message });
+            }
+            vcs::VCS_REQUEST => {
+                let request: VcsRequest = serde_json::from_str(&message.content)?;
+                self.apply_vcs_request(message.from_agent, request);
+            }
+            scorecard::IMPROVEMENT_DONE => {
+                let task_id = message.metadata.get(scorecard::TASK_ID_KEY)
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or("improvement report without a task_id")?;
+                let Some(task) = self.scorecards.complete_task(task_id, self.scheduler.now()).cloned() else {
+                    return Ok(());
+                };
+                println!("✅ {} completed '{}'", self.actor_name(message.from_agent), task.title);
+                // Fixing vulnerabilities is InfoSec's job; the owner only asks for it
+                if task.focus == Dimension::Security {
+                    if let Some(infosec_id) = self.find_department_agent(Department::InfoSec.as_str()) {
+                        self.pending_messages.push(Message {
+                            id: Uuid::new_v4(),
+                            from_agent: message.from_agent,
+                            to_agent: infosec_id,
+                            message_type: "remediate_vulnerabilities".to_string(),
+                            content: task.title.clone(),
+                            priority: MessagePriority::Normal,
+                            timestamp: chrono::Utc::now(),
+                            metadata: HashMap::from([("target".to_string(), task.service.clone())]),
+                        });
+                    }
+                }
+            }
+            quota::APPROVAL_DECISION => {
+                let request_id = message.metadata.get(quota::REQUEST_ID_KEY)
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or("quota decision without a quota_request_id")?;
+                let approved = message.metadata.get("approved").is_some_and(|approved| approved == "true");
+                let request = self.quotas.decide(request_id, approved, message.content, self.scheduler.now())?.clone();
+                let approver = self.actor_name(message.from_agent);
+                if !approved {
+                    println!("⛔ {} denied quota increase for {}: {}", approver, request.scope.label(), request.reason.unwrap_or_default());
+                    return Ok(());
+                }
+                println!("🧮 {} raised quota {} to {}", approver, request.scope.label(), request.requested);
+                if let Some(blocked) = request.blocked {
+                    self.request_deployment(blocked.requested_by, blocked.project_id, blocked.commit);
+                }
+            }
+            "training_scheduled" => {
+                let Some(MessagePayload::TrainingScheduled { agents, at }) = MessagePayload::from_message(&message)? else {
+                    return Ok(());
+                };
+                let attendees = agents.len();
+                self.phishing.schedule_training(agents, at);
+                println!("📚 {} booked security-awareness training for {} agents on {}", self.actor_name(message.from_agent), attendees, at.format("%Y-%m-%d"));
+            }
+            releasenotes::POLISHED => {
+                let notes_id = message.metadata.get(releasenotes::NOTES_ID_KEY)
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .ok_or("release notes copy without a release_notes_id")?;
+                if self.release_notes.polish(notes_id, message.content) {
+                    println!("✍️ {} polished release notes {}", self.actor_name(message.from_agent), notes_id.simple());
+                    self.write_status_page();
+                }
+            }
+            _ => {
+                println!("📨 {} → {}: {}", self.actor_name(message.from_agent), actor.name(), message.content);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply an Engineering agent's repository change; merges are released and deployed
+    fn apply_vcs_request(&mut self, author_id: Uuid, request: VcsRequest) {
+        let author = Signature { id: author_id, name: self.actor_name(author_id) };
+        let now = self.scheduler.now();
+        let applied = match self.repository.apply(&request, author.clone(), now) {
+            Ok(applied) => applied,
+            Err(e) => {
+                println!("⚠️ Repository rejected change from {}: {}", author.name, e);
+                return;
+            }
+        };
+
+        match applied {
+            Applied::Committed { hash, branch } => {
+                let subject = self.repository.get(&hash).map(|commit| commit.message.clone()).unwrap_or_default();
+                println!("🌿 {} committed {} on {}: {}", author.name, vcs::short(&hash), branch, subject);
+            }
+            Applied::Merged { hash, branch, project_id } => {
+                let release = match self.repository.release(&hash, now) {
+                    Ok(tag) => tag.name.clone(),
+                    Err(e) => {
+                        println!("⚠️ Could not tag release: {}", e);
+                        vcs::short(&hash).to_string()
+                    }
+                };
+                println!("🔀 {} merged {} into {} as {} ({})",
+                       author.name, branch, self.repository.default_branch, vcs::short(&hash), release);
+                if let Some(project_id) = project_id {
+                    self.complete_project_task(project_id, Department::Engineering);
+                }
+                if self.approve_change(author_id, project_id, &hash) {
+                    self.start_pipeline(author_id, project_id.unwrap_or_else(Uuid::nil), hash);
+                }
+            }
+            Applied::Reverted { hash, reverted } => {
+                println!("⏪ {} reverted {} with {}", author.name, vcs::short(&reverted), vcs::short(&hash));
+                let project_id = self.repository.get(&reverted).and_then(|commit| commit.project_id);
+                self.start_pipeline(author_id, project_id.unwrap_or_else(Uuid::nil), hash);
+            }
+        }
+    }
+
+    /// Queue a commit's pipeline, or hand it straight to DevOps when
+    /// pipelines are off
+    fn start_pipeline(&mut self, from: Uuid, project_id: Uuid, commit: String) {
+        if !self.config.pipelines.enabled {
+            self.request_deployment(from, project_id, commit);
+            return;
+        }
+        let event = self.pipelines.enqueue(project_id, commit, from, self.scheduler.now());
+        println!("🏗️ Pipeline for {} {}", vcs::short(&event.commit), event.detail);
+        self.publish_pipeline_event(&event);
+    }
+
+    /// Run the build queue forward; pipelines that pass are deployed
+    fn run_pipelines(&mut self) {
+        let now = self.scheduler.now();
+        for event in self.pipelines.advance(now, &self.config.pipelines, &mut self.rng) {
+            match event.status {
+                PipelineStatus::Failed => println!("🔴 Pipeline for {} failed at {}: {}", vcs::short(&event.commit), event.stage.as_str(), event.detail),
+                _ => println!("🏗️ Pipeline for {}: {}", vcs::short(&event.commit), event.detail),
+            }
+            self.publish_pipeline_event(&event);
+            if event.status == PipelineStatus::Passed && event.stage == Stage::Deploy {
+                self.request_deployment(event.requested_by, event.project_id, event.commit);
+            }
+        }
+    }
+
+    /// Tell the commit's author and DevOps how a pipeline is getting on
+    fn publish_pipeline_event(&mut self, event: &pipeline::PipelineEvent) {
+        let devops_id = self.find_department_agent(Department::DevOps.as_str());
+        let content = format!("Pipeline for {} at {}: {}", vcs::short(&event.commit), event.stage.as_str(), event.detail);
+        let recipients = [Some(event.requested_by), devops_id];
+        for to in recipients.into_iter().flatten().filter(|id| self.agents.contains(id)) {
+            let priority = if event.status == PipelineStatus::Failed { MessagePriority::High } else { MessagePriority::Low };
+            let payload = MessagePayload::pipeline_update(event);
+            self.pending_messages.push(payload.into_message(SystemActor::Orchestrator.id(), to, content.clone(), priority));
+        }
+    }
+
+    /// Start a commit on its way to production, once its footprint fits the
+    /// quotas; without environments to promote through it goes there directly
+    fn request_deployment(&mut self, from: Uuid, project_id: Uuid, commit: String) {
+        let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) else {
+            return;
+        };
+        let team = self.agents.info(&from)
+            .map_or(Department::Engineering.as_str(), |agent| agent.department.as_str())
+            .to_string();
+        let features = if project_id.is_nil() { 1 } else { self.repository.merge_count(project_id).max(1) };
+        let footprint = self.config.quotas.footprint_per_feature.scaled(features as u32);
+        match self.quotas.reserve(&team, project_id, footprint, &self.config.quotas) {
+            Ok(()) => {}
+            Err(QuotaError::Exceeded { scope, needed, limit }) => {
+                println!("🚫 Deployment of {} blocked: {} needs {}, quota is {}", vcs::short(&commit), scope.label(), needed, limit);
+                let blocked = BlockedDeployment { requested_by: from, team, project_id, commit, footprint };
+                self.request_quota_increase(&scope, needed, blocked);
+                return;
+            }
+            Err(e) => {
+                println!("⚠️ Deployment of {} not reserved: {}", vcs::short(&commit), e);
+                return;
+            }
+        }
+        let now = self.scheduler.now();
+        match self.promotions.start(project_id, commit.clone(), from, &self.config.deployments.environments, now) {
+            Some(action) => self.run_promotion_action(action),
+            None => self.send_deployment(devops_id, from, project_id, commit, "production", None),
+        }
+    }
+
+    /// Environment customers use: the last one builds are promoted through
+    fn production_environment(&self) -> &str {
+        self.config.deployments.environments.last().map_or("production", |last| last.name.as_str())
+    }
+
+    /// Ask DevOps to deploy a commit to an environment; production gets the
+    /// configured release strategy, the environments before it all at once
+    fn send_deployment(&mut self, devops_id: Uuid, from: Uuid, project_id: Uuid, commit: String, environment: &str, promotion_id: Option<Uuid>) {
+        let strategy = if environment == self.production_environment() { self.config.deployments.strategy.clone() } else { DeploymentStrategy::AllAtOnce };
+        let content = format!("Deploy {} to {}", vcs::short(&commit), environment);
+        let payload = MessagePayload::DeploymentRequest {
+            project_id,
+            environment: environment.to_string(),
+            commit: Some(commit),
+            strategy,
+        };
+        let mut message = payload.into_message(from, devops_id, content, MessagePriority::High);
+        // DevOps keeps the promotion as the deployment's correlation id
+        if let Some(promotion_id) = promotion_id {
+            message.metadata.insert(timeline::CORRELATION_KEY.to_string(), promotion_id.to_string());
+        }
+        self.pending_messages.push(message);
+    }
+
+    /// Deploy a promoted build, or ask Ops to approve its next environment
+    fn run_promotion_action(&mut self, action: PromotionAction) {
+        match action {
+            PromotionAction::Deploy { promotion_id, project_id, commit, requested_by, environment } => {
+                let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) else {
+                    return;
+                };
+                println!("🚦 Promoting {} to {}", vcs::short(&commit), environment);
+                self.send_deployment(devops_id, requested_by, project_id, commit, &environment, Some(promotion_id));
+            }
+            PromotionAction::RequestApproval { promotion_id, commit, from, to } => {
+                let Some(ops_id) = self.find_department_agent(Department::Ops.as_str()) else {
+                    println!("⚠️ No Ops agent to approve promoting {} to {}", vcs::short(&commit), to);
+                    return;
+                };
+                let review = MessagePayload::ChangeReview {
+                    reference: promotion_id,
+                    title: format!("Promote {} from {} to {}", vcs::short(&commit), from, to),
+                    description: format!("Build {} has soaked in {}", commit, from),
+                    impact: format!("{} runs the new build", to),
+                    rollback_plan: "Roll the deployment back to the previous build".to_string(),
+                    requested_by: SystemActor::Orchestrator.id(),
+                };
+                let content = format!("Approve promoting {} to {}", vcs::short(&commit), to);
+                self.pending_messages.push(review.into_message(SystemActor::Orchestrator.id(), ops_id, content, MessagePriority::High));
+            }
+        }
+    }
+
+    /// Record how promoted builds' deployments ended, then move on every
+    /// build whose soak time is over
+    async fn advance_promotions(&mut self) {
+        let deploying: Vec<Uuid> = self.promotions.promotions().iter()
+            .filter(|promotion| promotion.status == PromotionStatus::Deploying)
+            .map(|promotion| promotion.id)
+            .collect();
+        let finished = if deploying.is_empty() {
+            Ok(Vec::new())
+        } else {
+            self.agents.call_all(move |agent| {
+                agent.as_devops().map(|devops| devops.active_deployments.values()
+                    .filter(|deployment| matches!(deployment.status, DeploymentStatus::Success | DeploymentStatus::Failed | DeploymentStatus::RolledBack))
+                    .filter_map(|deployment| {
+                        let promotion_id = deployment.correlation_id.filter(|id| deploying.contains(id))?;
+                        let detail = deployment.rollback_reason.clone().unwrap_or_else(|| format!("{:?}", deployment.status));
+                        Some((promotion_id, deployment.environment.clone(), deployment.status == DeploymentStatus::Success, detail))
+                    })
+                    .collect::<Vec<_>>()).unwrap_or_default()
+            }).await.map(|finished| finished.into_iter().flatten().collect())
+        };
+        let finished = match finished {
+            Ok(finished) => finished,
+            Err(e) => {
+                println!("⚠️ Promotions not advanced: {}", e);
+                return;
+            }
+        };
+
+        let now = self.scheduler.now();
+        let environments = self.config.deployments.environments.clone();
+        for (promotion_id, environment, shipped, detail) in finished {
+            // Deployments to environments the build already left are done with
+            if !self.promotions.promotion(promotion_id).is_some_and(|promotion| promotion.environment == environment) {
+                continue;
+            }
+            self.promotions.deployment_finished(promotion_id, shipped, &detail, &environments, now);
+            if let Some(promotion) = self.promotions.promotion(promotion_id) {
+                match promotion.status {
+                    PromotionStatus::Promoted => println!("🏁 {} promoted to {}", vcs::short(&promotion.commit), promotion.environment),
+                    PromotionStatus::Failed => println!("🔴 Promotion of {} stopped: {}", vcs::short(&promotion.commit), promotion.reason.as_deref().unwrap_or_default()),
+                    _ => println!("🚦 {} running in {}", vcs::short(&promotion.commit), promotion.environment),
+                }
+            }
+        }
+        for action in self.promotions.advance(&environments, now) {
+            self.run_promotion_action(action);
+        }
+    }
+
+    /// Carry out Ops' decision on promoting a build to its next environment
+    fn decide_promotion(&mut self, message: &Message) {
+        let Ok(Some(MessagePayload::ChangeDecision { reference, approved, reason, .. })) = MessagePayload::from_message(message) else {
+            return;
+        };
+        let now = self.scheduler.now();
+        let environments = self.config.deployments.environments.clone();
+        match self.promotions.decide(reference, approved, &reason, &environments, now) {
+            Some(action) => self.run_promotion_action(action),
+            None => {
+                if let Some(reason) = self.promotions.promotion(reference).and_then(|promotion| promotion.reason.clone()) {
+                    println!("⛔ Promotion stopped: {}", reason);
+                }
+            }
+        }
+    }
+
+    /// Send a quota-increase request to the approver the matrix names
+    fn request_quota_increase(&mut self, scope: &QuotaScope, needed: quota::Resources, blocked: BlockedDeployment) {
+        let already_pending = self.quotas.pending_requests().any(|request| request.scope == *scope);
+        // Increases beyond the matrix go up to the CEO
+        let mut matrix = self.config.quotas.approval.clone();
+        matrix.push(config::ApprovalTier {
+            max_increase_percent: self.config.org.ceo_budget_percent,
+            approver: SystemActor::Ceo.name().to_string(),
+        });
+        let request = match self.quotas.request_increase(scope, needed, blocked, &matrix, self.scheduler.now()) {
+            Ok(request) => request.clone(),
+            Err(e) => {
+                println!("⛔ Deployment denied: {}", e);
+                return;
+            }
+        };
+        // A request already pending just waits for its decision
+        if already_pending {
+            return;
+        }
+        if request.approver == SystemActor::Ceo.name() {
+            self.approve_budget(request);
+            return;
+        }
+        let Some(approver_id) = self.find_department_agent(&request.approver) else {
+            println!("⚠️ No {} agent to approve quota increase for {}", request.approver, scope.label());
+            return;
+        };
+        println!("📝 Quota increase of {:.0}% for {} sent to {}", request.increase_percent, scope.label(), self.actor_name(approver_id));
+        self.pending_messages.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: approver_id,
+            message_type: quota::APPROVAL_REQUEST.to_string(),
+            content: format!("Raise quota {} from {} to {}", scope.label(), request.current, request.requested),
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                (quota::REQUEST_ID_KEY.to_string(), request.id.to_string()),
+                ("scope".to_string(), scope.label()),
+                ("increase_percent".to_string(), format!("{:.1}", request.increase_percent)),
+            ]),
+        });
+    }
 
-        let mut step_count = 0u64;
+    /// The CEO's decision on a quota increase beyond the approval matrix
+    fn approve_budget(&mut self, request: quota::QuotaRequest) {
+        let now = self.scheduler.now();
+        let reason = format!("Approved by the CEO at {:.0}% over the current quota", request.increase_percent);
+        let decided = match self.quotas.decide(request.id, true, reason, now) {
+            Ok(decided) => decided.clone(),
+            Err(e) => {
+                println!("⚠️ {}", e);
+                return;
+            }
+        };
+        println!("👔 CEO raised quota {} to {}", decided.scope.label(), decided.requested);
+        if let Some(blocked) = decided.blocked {
+            self.org.record_approval(Approval {
+                kind: ApprovalKind::Budget,
+                requested_by: blocked.requested_by,
+                subject: decided.scope.label(),
+                amount: decided.increase_percent,
+                approver: Approver::Ceo,
+                approved: true,
+                decided_at: now,
+            });
+            self.request_deployment(blocked.requested_by, blocked.project_id, blocked.commit);
+        }
+    }
 
-        loop {
-            step_count += 1;
-            println!("\n--- Simulation Step {} ---", step_count);
+    /// Sample how full the servers, Ops' ticket capacity and the network are,
+    /// and ask ahead for what is forecast to run out: servers from DevOps,
+    /// and budget from Finance
+    async fn forecast_capacity(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings = self.config.capacity.clone();
+        if !settings.enabled {
+            return Ok(());
+        }
+        let now = self.scheduler.now();
+        let cpu = self.agents.call_all(|agent| {
+            agent.as_devops().map(|devops| devops.infrastructure_state.servers.values()
+                .filter(|server| server.status != ServerState::Offline)
+                .map(|server| f64::from(server.cpu_usage))
+                .collect::<Vec<_>>()).unwrap_or_default()
+        }).await?;
+        let cpu: Vec<f64> = cpu.into_iter().flatten().collect();
+        if !cpu.is_empty() {
+            self.capacity.record(Resource::ServerCpu, now, cpu.iter().sum::<f64>() / cpu.len() as f64);
+        }
+        let tickets = self.agents.call_all(|agent| agent.as_ops().map(|ops| ops.open_tickets())).await?;
+        let tickets: Vec<usize> = tickets.into_iter().flatten().collect();
+        if !tickets.is_empty() {
+            let room = tickets.len() * self.config.ticket_queue.max_open_per_agent.max(1);
+            self.capacity.record(Resource::Tickets, now, tickets.iter().sum::<usize>() as f64 / room as f64 * 100.0);
+        }
+        let traffic = self.agents.call_all(|agent| {
+            agent.as_networking().and_then(|networking| networking.traffic.segments.values().map(|load| load.utilization).reduce(f64::max))
+        }).await?;
+        if let Some(busiest) = traffic.into_iter().flatten().flatten().reduce(f64::max) {
+            self.capacity.record(Resource::Traffic, now, busiest * 100.0);
+        }
 
-            // Check if we've reached max steps
-            if let Some(max) = self.config.max_steps {
-                if step_count >= max {
-                    println!("🏁 Reached maximum simulation steps ({})", max);
-                    break;
+        let from = self.find_department_agent(Department::Ops.as_str()).unwrap_or(SystemActor::Scheduler.id());
+        let (server_cost, agent_cost) = (self.config.economy.server_monthly_cost, self.config.economy.agent_monthly_cost);
+        for forecast in self.capacity.plan(&settings, now) {
+            println!("📐 Capacity forecast: {}", forecast.description());
+            let monthly_cost = match forecast.resource {
+                Resource::ServerCpu => Some(server_cost),
+                Resource::Tickets => Some(agent_cost),
+                Resource::Traffic => None,
+            };
+            if forecast.resource == Resource::ServerCpu {
+                if let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) {
+                    let request = MessagePayload::CapacityRequest { forecast: forecast.clone() };
+                    self.pending_messages.push(request.into_message(from, devops_id, forecast.description(), MessagePriority::Normal));
                 }
             }
+            if let Some(finance_id) = self.find_department_agent(Department::Finance.as_str()) {
+                let content = format!("Budget for more {}: {}", forecast.resource.name(), forecast.description());
+                let request = MessagePayload::BudgetRequest { forecast, monthly_cost };
+                self.pending_messages.push(request.into_message(from, finance_id, content, MessagePriority::Normal));
+            }
+        }
+        Ok(())
+    }
 
-            // Check working hours
-            let current_hour = chrono::Utc::now().hour() as u8;
-            let (start_hour, end_hour) = self.config.working_hours;
+    /// Send utilization against every quota to DevOps for capacity planning
+    fn send_capacity_report(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let report = self.quotas.utilization();
+        if report.is_empty() {
+            return Ok(());
+        }
+        let Some(devops_id) = self.find_department_agent(Department::DevOps.as_str()) else {
+            return Ok(());
+        };
+        self.pending_messages.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: devops_id,
+            message_type: quota::CAPACITY_REPORT.to_string(),
+            content: serde_json::to_string(&report)?,
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([
+                ("high_water".to_string(), self.config.quotas.capacity_high_water_percent.to_string()),
+            ]),
+        });
+        Ok(())
+    }
 
-            if current_hour < start_hour || current_hour >= end_hour {
-                println!("😴 Outside working hours ({}-{}). Agents resting...", start_hour, end_hour);
-                tokio::time::sleep(tokio::time::Duration::from_secs(300)).await; // Sleep 5 minutes
-                continue;
+    /// Compile release notes for every production deployment that shipped
+    /// since the last step
+    async fn publish_release_notes(&mut self) {
+        let production = self.production_environment().to_string();
+        let shipped = self.agents.call_all(move |agent| {
+            agent.as_devops().map(|devops| devops.active_deployments.values()
+                .filter(|deployment| deployment.status == DeploymentStatus::Success && deployment.environment == production)
+                .filter_map(|deployment| Some((deployment.start_time, deployment.id, deployment.commit.clone()?, deployment.environment.clone())))
+                .collect::<Vec<_>>()).unwrap_or_default()
+        }).await;
+        let mut shipped: Vec<_> = match shipped {
+            Ok(shipped) => shipped.into_iter().flatten()
+                .filter(|(_, deployment_id, _, _)| !self.release_notes.is_shipped(deployment_id))
+                .collect(),
+            Err(e) => {
+                println!("⚠️ Release notes skipped: {}", e);
+                return;
             }
+        };
+        if shipped.is_empty() {
+            return;
+        }
+        // Oldest first, so each release covers what the one before it did not
+        shipped.sort_by_key(|(start_time, ..)| *start_time);
 
-            // Run simulation step
-            self.run_simulation_step().await?;
+        let now = self.scheduler.now();
+        for (_, deployment_id, commit, environment) in shipped {
+            let notes = ReleaseNotes::compile(&self.repository, deployment_id, &commit, &environment, self.release_notes.last_commit(), now);
+            let notes = match notes {
+                Ok(notes) if !notes.is_empty() => notes,
+                Ok(_) => {
+                    self.release_notes.skip(deployment_id);
+                    continue;
+                }
+                Err(e) => {
+                    println!("⚠️ No release notes for deployment {}: {}", deployment_id, e);
+                    self.release_notes.skip(deployment_id);
+                    continue;
+                }
+            };
 
-            // Sleep between steps (scaled by speed multiplier)
-            let sleep_duration = (60.0 / self.config.speed_multiplier) as u64; // Base 1 minute
-            tokio::time::sleep(tokio::time::Duration::from_secs(sleep_duration)).await;
+            println!("📝 Released {}: {}", notes.version, notes.summary());
+            if self.config.release_notes.marketing_polish {
+                self.request_release_polish(&notes);
+            }
+            // Shipping a project's work is the end of its rollout
+            for project_id in notes.projects.clone() {
+                self.complete_project_task(project_id, Department::Ops);
+            }
+            self.release_notes.record(notes);
         }
-
-        println!("🏁 Simulation completed after {} steps", step_count);
-        Ok(())
+        self.write_status_page();
     }
 
-    /// Execute one simulation step
-    async fn run_simulation_step(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Process agent activities
-        self.process_agent_activities().await?;
+    /// Ask Marketing to rewrite release notes for customers
+    fn request_release_polish(&mut self, notes: &ReleaseNotes) {
+        let Some(marketer) = self.find_department_agent(Department::Marketing.as_str()) else {
+            return;
+        };
+        let content = match serde_json::to_string(notes) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("⚠️ Could not send release notes to Marketing: {}", e);
+                return;
+            }
+        };
+        self.pending_messages.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: marketer,
+            message_type: releasenotes::POLISH_REQUEST.to_string(),
+            content,
+            priority: MessagePriority::Low,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([(releasenotes::NOTES_ID_KEY.to_string(), notes.id.to_string())]),
+        });
+    }
 
-        // Handle inter-agent communication
-        self.process_messages().await?;
+    /// Rewrite the status page, if one is configured
+    fn write_status_page(&self) {
+        let Some(path) = &self.config.release_notes.status_page_path else {
+            return;
+        };
+        if let Err(e) = self.release_notes.write_status_page(&self.repository.name, path) {
+            println!("⚠️ Could not write status page {}: {}", path.display(), e);
+        }
+    }
 
-        // Check for new projects or tasks
-        self.generate_company_activities().await?;
+    /// Have Engineering revert the latest release if it went out within the revert window
+    fn request_release_revert(&mut self, reason: &str) {
+        let now = self.scheduler.now();
+        let window = chrono::Duration::minutes(self.config.vcs.revert_window_minutes);
+        let Some(release) = self.repository.latest_tag().filter(|tag| now - tag.created_at <= window) else {
+            return;
+        };
+        if self.repository.is_reverted(&release.commit) {
+            return;
+        }
+        let Some(engineer) = self.find_department_agent(Department::Engineering.as_str()) else {
+            return;
+        };
 
-        // Monitor system health
-        self.monitor_system_health().await?;
+        println!("   ⏪ Release {} went out {} minutes ago, asking Engineering to revert it",
+               release.name, (now - release.created_at).num_minutes());
+        self.pending_messages.push(Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::Orchestrator.id(),
+            to_agent: engineer,
+            message_type: "revert_request".to_string(),
+            content: format!("Release {} suspected: {}", release.name, reason),
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::from([("commit".to_string(), release.commit.clone())]),
+        });
+    }
 
-        Ok(())
+    /// Display name for an agent or system actor id
+    fn actor_name(&self, id: Uuid) -> String {
+        if let Some(actor) = SystemActor::from_id(id) {
+            return actor.name().to_string();
+        }
+        self.agents.info(&id)
+            .map(|agent| agent.name.clone())
+            .unwrap_or_else(|| format!("unknown ({})", id.simple()))
     }
 
-    /// Process activities for all agents
-    async fn process_agent_activities(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let agent_ids: Vec<Uuid> = self.agents.keys().cloned().collect();
+    /// Every agent in a department
+    fn department_agents(&self, department: Department) -> Vec<Uuid> {
+        self.agents.infos()
+            .filter(|agent| agent.department == department)
+            .map(|agent| agent.id)
+            .collect()
+    }
 
-        for agent_id in agent_ids {
-            if let Some(agent) = self.agents.get_mut(&agent_id) {
-                let agent_name = agent.get_agent().name.clone();
-                let department = agent.get_agent().department.as_str();
+    /// Find an agent in the named department, preferring its manager
+    fn find_department_agent(&self, department: &str) -> Option<Uuid> {
+        let candidates: Vec<&AgentInfo> = self.agents.infos()
+            .filter(|agent| agent.department.as_str() == department)
+            .collect();
 
-                // Simulate agent activity
-                match agent.get_agent().department {
-                    Department::DevOps => {
-                        // DevOps agents perform infrastructure tasks
-                        if rand::random::<f32>() < 0.3 { // 30% chance
-                            println!("🔧 {} (DevOps): Performing infrastructure maintenance", agent_name);
-                        }
-                    }
-                    Department::InfoSec => {
-                        // InfoSec agents monitor security
-                        if rand::random::<f32>() < 0.2 { // 20% chance
-                            println!("🔒 {} (InfoSec): Conducting security scan", agent_name);
-                        }
-                    }
-                    Department::Networking => {
-                        // Networking agents optimize network
-                        if rand::random::<f32>() < 0.25 { // 25% chance
-                            println!("🌐 {} (Networking): Optimizing network performance", agent_name);
-                        }
-                    }
-                    Department::Ops => {
-                        // Ops agents handle support
-                        if rand::random::<f32>() < 0.4 { // 40% chance
-                            println!("🎫 {} (Ops): Processing support tickets", agent_name);
-                        }
-                    }
-                    _ => {}
-                }
+        candidates.iter()
+            .find(|agent| agent.manager_id.is_none())
+            .or_else(|| candidates.first())
+            .map(|agent| agent.id)
+    }
 
-                // Run daily tasks (simplified - would run less frequently in real system)
-                if rand::random::<f32>() < 0.1 { // 10% chance per step
-                    agent.perform_daily_tasks().await?;
-                }
-            }
+    /// A known collaborator of the sender in the named department, by chance
+    fn known_department_agent(&mut self, sender: Uuid, department: &str) -> Option<Uuid> {
+        if !self.config.memory.enabled {
+            return None;
         }
-
-        Ok(())
+        let candidates: Vec<Uuid> = self.agents.infos()
+            .filter(|agent| agent.department.as_str() == department)
+            .map(|agent| agent.id)
+            .collect();
+        self.memories.favourite(&sender, &candidates)
+            .filter(|_| self.rng.gen_bool(self.config.memory.prefer_known_chance))
     }
 
     /// Process inter-agent messages
     async fn process_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Simulate occasional inter-agent communication
-        if rand::random::<f32>() < 0.15 { // 15% chance per step
-            let agent_ids: Vec<Uuid> = self.agents.keys().cloned().collect();
-            if agent_ids.len() >= 2 {
-                let sender_idx = rand::random::<usize>() % agent_ids.len();
-                let mut receiver_idx = rand::random::<usize>() % agent_ids.len();
-                while receiver_idx == sender_idx {
-                    receiver_idx = rand::random::<usize>() % agent_ids.len();
-                }
-
-                let sender_id = agent_ids[sender_idx];
-                let receiver_id = agent_ids[receiver_idx];
-
-                if let Some(sender) = self.agents.get(&sender_id) {
-                    let message_types = vec![
-                        "status_update",
-                        "collaboration_request",
-                        "issue_report",
-                        "resource_request",
-                    ];
-
-                    let message_type = message_types[rand::random::<usize>() % message_types.len()];
-
-                    let message = Message {
-                        id: Uuid::new_v4(),
-                        from_agent: sender_id,
-                        to_agent: receiver_id,
-                        message_type: message_type.to_string(),
-                        content: format!("Automated {} from {} department",
-                                       message_type.replace("_", " "),
-                                       sender.get_agent().department.as_str()),
-                        priority: MessagePriority::Normal,
-                        timestamp: chrono::Utc::now(),
-                        metadata: HashMap::new(),
-                    };
+        // Simulate occasional inter-agent communication, more of it the
+        // chattier the team; chatty agents send a bigger share
+        let agent_ids = self.agents.ids();
+        let volumes: Vec<f64> = agent_ids.iter().map(|id| self.personality_of(id).message_volume()).collect();
+        let team_volume = volumes.iter().sum::<f64>() / volumes.len().max(1) as f64;
+        let chance = f64::from(self.config.event_probabilities.agent_message) * team_volume;
+        if agent_ids.len() >= 2 && self.rng.gen::<f64>() < chance {
+            let mut pick = self.rng.gen::<f64>() * volumes.iter().sum::<f64>();
+            let sender_idx = volumes.iter().position(|volume| {
+                pick -= volume;
+                pick < 0.0
+            }).unwrap_or(agent_ids.len() - 1);
+            let sender_id = agent_ids[sender_idx];
+            // Agents mostly write to colleagues they already work with
+            let others: Vec<Uuid> = agent_ids.iter().copied().filter(|id| *id != sender_id).collect();
+            let known = self.memories.favourite(&sender_id, &others)
+                .filter(|_| self.config.memory.enabled && self.rng.gen_bool(self.config.memory.prefer_known_chance));
+            let receiver_id = known.unwrap_or_else(|| others[self.rng.gen_range(0..others.len())]);
 
-                    // Send message through bus
-                    self.message_bus.send_message(message.clone()).await?;
+            if let Some(sender) = self.agents.info(&sender_id) {
+                let message_types = vec![
+                    "status_update",
+                    "collaboration_request",
+                    "issue_report",
+                    "resource_request",
+                ];
 
-                    println!("💬 {} → {}: {}",
-                           sender.get_agent().department.as_str(),
-                           self.agents.get(&receiver_id).unwrap().get_agent().department.as_str(),
-                           message.content);
-                }
+                let message_type = message_types[self.rng.gen_range(0..message_types.len())];
+
+                let message = Message {
+                    id: Uuid::new_v4(),
+                    from_agent: sender_id,
+                    to_agent: receiver_id,
+                    message_type: message_type.to_string(),
+                    content: format!("Automated {} from {} department",
+                                   message_type.replace("_", " "),
+                                   sender.department.as_str()),
+                    priority: MessagePriority::Normal,
+                    timestamp: chrono::Utc::now(),
+                    metadata: HashMap::new(),
+                };
+
+                // Queue message for the bus
+                self.pending_messages.push(message.clone());
+
+                println!("💬 {} → {}: {}",
+                       sender.department.as_str(),
+                       self.agents.info(&receiver_id).unwrap().department.as_str(),
+                       message.content);
             }
         }
 
@@ -285,25 +3661,24 @@ impl CompanySimulation {
     /// Generate company activities (projects, incidents, etc.)
     async fn generate_company_activities(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Simulate random company events
-        let event_roll = rand::random::<f32>();
+        let event_roll = self.rng.gen::<f32>();
+        let probabilities = self.config.event_probabilities.clone();
+        let project_threshold = probabilities.new_project;
+        let security_threshold = project_threshold + probabilities.security_incident;
+        // Infrastructure faults are scheduled separately, see `schedule_next_fault`
+        let customer_threshold = security_threshold + probabilities.customer_request;
 
-        if event_roll < 0.05 { // 5% chance - new customer project
+        if event_roll < project_threshold { // New customer project
             let project_id = Uuid::new_v4();
             println!("📋 New customer project received: {}", project_id.simple());
 
-            // Assign to engineering and ops
-            self.assign_project_task(project_id, Department::Engineering).await?;
-            self.assign_project_task(project_id, Department::Ops).await?;
+            self.plan_project(project_id)?;
 
-        } else if event_roll < 0.08 { // 3% chance - security incident
+        } else if event_roll < security_threshold { // Security incident
             println!("🚨 Security incident detected!");
             self.handle_security_incident().await?;
 
-        } else if event_roll < 0.12 { // 4% chance - infrastructure issue
-            println!("⚠️ Infrastructure issue detected!");
-            self.handle_infrastructure_issue().await?;
-
-        } else if event_roll < 0.18 { // 6% chance - customer support request
+        } else if event_roll < customer_threshold { // Customer support request
             println!("🎫 Customer support request received!");
             self.handle_customer_request().await?;
         }
@@ -311,106 +3686,257 @@ impl CompanySimulation {
         Ok(())
     }
 
-    /// Assign project task to department
-    async fn assign_project_task(&mut self, project_id: Uuid, department: Department) -> Result<(), Box<dyn std::error::Error>> {
-        // Find an agent in the department
-        for agent in self.agents.values() {
-            if agent.get_agent().department == department {
-                let message = Message {
-                    id: Uuid::new_v4(),
-                    from_agent: Uuid::nil(), // System message
-                    to_agent: agent.get_agent().id,
-                    message_type: "project_assignment".to_string(),
-                    content: format!("Assigned to project {}", project_id.simple()),
-                    priority: MessagePriority::Normal,
-                    timestamp: chrono::Utc::now(),
-                    metadata: HashMap::from([
-                        ("project_id".to_string(), project_id.to_string()),
-                    ]),
-                };
+    /// Plan a new project's tasks; the step starts those with nothing to wait on
+    fn plan_project(&mut self, project_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        self.task_graph.plan_project(project_id)?;
+        if let Some(customer_id) = self.catalog.assign_project(project_id, &mut self.rng) {
+            println!("🧾 Project {} is for {}", project_id.simple(), customer_id);
+        }
+        if let Some(path) = self.task_graph.critical_path(project_id, self.scheduler.now())? {
+            let steps: Vec<&str> = path.tasks.iter()
+                .filter_map(|id| self.task_graph.task(id))
+                .map(|task| task.title.as_str())
+                .collect();
+            println!("🗺️ Project {} critical path: {} (est. done {})",
+                   project_id.simple(), steps.join(" → "), path.estimated_completion.format("%Y-%m-%d %H:%M"));
+        }
+        Ok(())
+    }
 
-                self.message_bus.send_message(message).await?;
-                break;
+    /// Assign every task whose dependencies are done to the best-suited agent
+    async fn start_ready_tasks(&mut self) {
+        let now = self.scheduler.now();
+        let ready: Vec<(Uuid, Uuid, String, String, i64)> = self.task_graph.ready()
+            .map(|task| (task.id, task.project_id, task.department.clone(), task.title.clone(), task.estimate_hours))
+            .collect();
+        for (task_id, project_id, department, title, estimate_hours) in ready {
+            // Without anyone in the department the task waits for a hire
+            let Some(kind) = WorkKind::for_department(&department) else {
+                continue;
+            };
+            let Some(assignment) = self.assign_work(kind, chrono::Duration::hours(estimate_hours)).await else {
+                continue;
+            };
+            if let Err(e) = self.task_graph.start(task_id, now) {
+                println!("⚠️ Could not start task {}: {}", task_id.simple(), e);
+                continue;
             }
+            let hours = (assignment.duration.num_minutes() as f64 / 60.0).ceil() as i64;
+            self.task_graph.assign(task_id, assignment.agent_id, hours);
+            println!("👷 {} (skill {}) took '{}' for project {}, est. {}h",
+                   self.actor_name(assignment.agent_id), assignment.skill, title, project_id.simple(), hours);
+            self.assign_project_task(project_id, assignment.agent_id);
         }
+    }
 
-        Ok(())
+    /// Best-suited, least-loaded agent of the department doing a kind of work
+    async fn assign_work(&self, kind: WorkKind, base_duration: chrono::Duration) -> Option<Assignment> {
+        let department = kind.department();
+        let candidates = self.agents.call_all(move |agent| {
+            let info = agent.get_agent();
+            if info.department.as_str() != department {
+                return None;
+            }
+            agent.skill(kind).map(|skill| Candidate { agent_id: info.id, skill, load: agent.backlog() })
+        }).await;
+        match candidates {
+            Ok(candidates) => assignment::assign(&candidates.into_iter().flatten().collect::<Vec<_>>(), base_duration),
+            Err(e) => {
+                println!("⚠️ Could not rank {} agents: {}", department, e);
+                None
+            }
+        }
+    }
+
+    /// Finish a department's task on a project; the next step starts whatever it unblocks
+    fn complete_project_task(&mut self, project_id: Uuid, department: Department) {
+        let Some(title) = self.task_graph.complete(project_id, department.as_str(), self.scheduler.now()).map(|task| task.title.clone()) else {
+            return;
+        };
+        println!("☑️ {} finished '{}' for project {}", department.as_str(), title, project_id.simple());
+        if self.task_graph.project_tasks(project_id).all(|task| task.state == taskgraph::TaskState::Done) {
+            match self.catalog.deliver_project(project_id, &self.config.customers.satisfaction) {
+                Some(customer_id) => println!("🏁 Project {} delivered to {}", project_id.simple(), customer_id),
+                None => println!("🏁 Project {} delivered", project_id.simple()),
+            }
+            self.economy.book(Entry::ProjectRevenue, self.config.economy.project_revenue, self.scheduler.now());
+            #[cfg(feature = "sqlite")]
+            if self.history.is_some() {
+                self.delivered_tasks.extend(self.task_graph.project_tasks(project_id).cloned());
+            }
+            self.task_graph.prune_finished();
+        }
+    }
+
+    /// Assign project task to an agent
+    fn assign_project_task(&mut self, project_id: Uuid, agent_id: Uuid) {
+        let message = MessagePayload::ProjectAssignment { project_id }.into_message(
+            SystemActor::Scheduler.id(),
+            agent_id,
+            format!("Assigned to project {}", project_id.simple()),
+            MessagePriority::Normal,
+        );
+        self.pending_messages.push(message);
     }
 
     /// Handle security incident
     async fn handle_security_incident(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Notify InfoSec agents
-        for agent in self.agents.values() {
-            if agent.get_agent().department == Department::InfoSec {
-                let message = Message {
-                    id: Uuid::new_v4(),
-                    from_agent: Uuid::nil(),
-                    to_agent: agent.get_agent().id,
-                    message_type: "declare_incident".to_string(),
-                    content: "Security incident: Suspicious activity detected on customer portal",
-                    priority: MessagePriority::High,
-                    timestamp: chrono::Utc::now(),
-                    metadata: HashMap::from([
-                        ("title".to_string(), "Security Incident - Suspicious Activity".to_string()),
-                        ("severity".to_string(), "Sev2".to_string()),
-                    ]),
-                };
+        // Notify the InfoSec agent best suited to respond
+        if let Some(assignment) = self.assign_work(WorkKind::SecurityIncident, chrono::Duration::hours(4)).await {
+            let payload = MessagePayload::IncidentDeclared {
+                title: "Security Incident - Suspicious Activity".to_string(),
+                severity: Severity::Sev2,
+                affected_services: vec!["customer-portal".to_string()],
+                affected_customers: vec![],
+            };
+            let message = payload.into_message(
+                SystemActor::EventGenerator.id(),
+                assignment.agent_id,
+                "Security incident: Suspicious activity detected on customer portal".to_string(),
+                MessagePriority::High,
+            );
 
-                self.message_bus.send_message(message).await?;
-                break; // Notify first InfoSec agent
-            }
+            self.pending_messages.push(message);
         }
 
         Ok(())
     }
 
     /// Handle infrastructure issue
+    ///
+    /// The fault lands on a deployment from the service catalog, so its blast
+    /// radius depends on tenancy: a shared pool affects every tenant on it,
+    /// a dedicated stack only its owner.
     async fn handle_infrastructure_issue(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Notify DevOps agents
-        for agent in self.agents.values() {
-            if agent.get_agent().department == Department::DevOps {
-                let message = Message {
-                    id: Uuid::new_v4(),
-                    from_agent: Uuid::nil(),
-                    to_agent: agent.get_agent().id,
-                    message_type: "infrastructure_alert".to_string(),
-                    content: "High CPU usage detected on web servers",
-                    priority: MessagePriority::High,
-                    timestamp: chrono::Utc::now(),
-                    metadata: HashMap::new(),
-                };
+        let Some(fault) = self.catalog.inject_fault(&mut self.rng) else {
+            return Ok(());
+        };
+        self.scorecards.record_incident(&fault.service);
+
+        let content = match (&fault.kind, &fault.noisy_customer) {
+            (FaultKind::NoisyNeighbor, Some(noisy)) => format!(
+                "Noisy neighbour on {}: {} is saturating the pool, {} customers degraded",
+                fault.deployment, noisy, fault.affected_customers.len()),
+            _ => format!("High CPU usage detected on {}", fault.deployment),
+        };
+        println!("   🧨 {} ({} tenancy, affects: {})",
+               content, fault.tenancy.as_str(), fault.affected_customers.join(", "));
 
-                self.message_bus.send_message(message).await?;
-                break; // Notify first DevOps agent
+        // Shared by the alert and the incident so both land on one timeline
+        let correlation_id = Uuid::new_v4();
+        // Shared pools hit harder
+        let severity = if fault.affected_customers.len() > 1 { Severity::Sev2 } else { Severity::Sev3 };
+        for customer_id in &fault.affected_customers {
+            if let Some(customer) = self.catalog.customers.get_mut(customer_id) {
+                customer.incident_declared(correlation_id, severity.clone(), &self.config.customers.satisfaction);
             }
         }
 
+        // A fault shortly after a release is blamed on it
+        self.request_release_revert(&content);
+
+        // Alert whoever the service's routing policy names
+        let now = self.scheduler.now();
+        let policy = self.catalog.routing_policy(&fault.service);
+        let recipients = policy.recipients(&severity, scheduler::within_working_hours(now, self.config.working_hours));
+        let alert = MessagePayload::InfrastructureAlert {
+            service: fault.service.clone(),
+            deployment: fault.deployment.clone(),
+            tenancy: fault.tenancy,
+            affected_customers: fault.affected_customers.clone(),
+        };
+        self.send_alert(&alert, &recipients, &content, correlation_id);
+        if let Some(delay) = policy.escalation_delay() {
+            self.scheduler.schedule_at(now + delay, EventKind::AlertEscalation { correlation_id, deployment_id: fault.deployment_id });
+        }
+
+        // Customer-facing impact becomes an incident for the best-suited Ops agent
+        if let Some(ops_id) = self.assign_work(WorkKind::Incident, chrono::Duration::hours(4)).await.map(|assignment| assignment.agent_id) {
+            let payload = MessagePayload::IncidentDeclared {
+                title: format!("Degraded {}", fault.deployment),
+                severity,
+                affected_services: vec![fault.service.clone()],
+                affected_customers: fault.affected_customers.clone(),
+            };
+            let mut incident = payload.into_message(SystemActor::ChaosEngine.id(), ops_id, content, MessagePriority::High);
+            incident.metadata.insert(timeline::CORRELATION_KEY.to_string(), correlation_id.to_string());
+            self.pending_messages.push(incident);
+        }
+
         Ok(())
     }
 
+    /// Send an infrastructure alert to each routing recipient
+    fn send_alert(&mut self, alert: &MessagePayload, recipients: &[String], content: &str, correlation_id: Uuid) {
+        let mut notified = HashSet::new();
+        for target in recipients {
+            let recipient = if target == catalog::ON_CALL {
+                // Without a pager the Ops lead stands in for the rotation
+                let now = self.scheduler.now();
+                self.pager.as_ref().and_then(|pager| pager.on_call(now, 0))
+                    .or_else(|| self.find_department_agent(Department::Ops.as_str()))
+            } else {
+                self.find_department_agent(target)
+            };
+            let Some(recipient) = recipient else {
+                println!("⚠️ No {} agent to alert: {}", target, content);
+                continue;
+            };
+            if !notified.insert(recipient) {
+                continue;
+            }
+            let mut message = alert.clone().into_message(SystemActor::ChaosEngine.id(), recipient, content.to_string(), MessagePriority::High);
+            message.metadata.insert(timeline::CORRELATION_KEY.to_string(), correlation_id.to_string());
+            self.pending_messages.push(message);
+        }
+    }
+
+    /// Alert the escalation recipients if the alert's incident is still open
+    async fn escalate_alert(&mut self, correlation_id: Uuid, deployment_id: Uuid) {
+        let open = self.agents.find(move |agent| agent.as_ops().is_some_and(|ops| {
+            ops.active_incidents().any(|incident| incident.correlation_ids.contains(&correlation_id))
+        })).await;
+        if !matches!(open, Ok(Some(_))) {
+            return;
+        }
+        let Some(deployment) = self.catalog.deployments.get(&deployment_id).cloned() else {
+            return;
+        };
+        let policy = self.catalog.routing_policy(&deployment.service);
+        let minutes = policy.escalation_delay_minutes.unwrap_or_default();
+        let label = deployment.label();
+        let content = format!("Escalation: {} still degraded after {} min", label, minutes);
+        println!("⏫ {} (to {})", content, policy.escalate_to.join(", "));
+        let alert = MessagePayload::InfrastructureAlert {
+            service: deployment.service.clone(),
+            deployment: label,
+            tenancy: deployment.tenancy,
+            affected_customers: deployment.customers.clone(),
+        };
+        self.send_alert(&alert, &policy.escalate_to, &content, correlation_id);
+    }
+
     /// Handle customer request
     async fn handle_customer_request(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Notify Ops agents
-        for agent in self.agents.values() {
-            if agent.get_agent().department == Department::Ops {
-                let message = Message {
-                    id: Uuid::new_v4(),
-                    from_agent: Uuid::nil(),
-                    to_agent: agent.get_agent().id,
-                    message_type: "create_ticket".to_string(),
-                    content: "Customer reports website loading slowly",
-                    priority: MessagePriority::Normal,
-                    timestamp: chrono::Utc::now(),
-                    metadata: HashMap::from([
-                        ("title".to_string(), "Website Performance Issue".to_string()),
-                        ("priority".to_string(), "Normal".to_string()),
-                        ("customer_id".to_string(), format!("cust-{}", rand::random::<u32>())),
-                    ]),
-                };
+        let (customer_id, customer_tier) = match self.catalog.random_customer(&mut self.rng) {
+            Some(customer) => (customer.id.clone(), customer.tier),
+            None => (format!("cust-{}", self.rng.gen::<u32>()), CustomerTier::Standard),
+        };
 
-                self.message_bus.send_message(message).await?;
-                break; // Notify first Ops agent
-            }
+        let ticket = TicketRequest {
+            title: "Website Performance Issue".to_string(),
+            description: "Customer reports website loading slowly".to_string(),
+            priority: Priority::Normal,
+            customer_id: Some(customer_id),
+            customer_tier,
+            tags: vec!["performance".to_string()],
+        };
+
+        // Queue it for routing, or notify the Ops agent best suited to support the customer
+        if self.config.ticket_queue.enabled {
+            self.ticket_queue.enqueue(ticket, SystemActor::EventGenerator.id(), self.scheduler.now());
+        } else if let Some(assignment) = self.assign_work(WorkKind::Ticket, chrono::Duration::hours(2)).await {
+            self.pending_messages.push(ticket_message(SystemActor::EventGenerator.id(), assignment.agent_id, ticket));
         }
 
         Ok(())
@@ -419,19 +3945,95 @@ impl CompanySimulation {
     /// Monitor overall system health
     async fn monitor_system_health(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Periodic health check
-        if rand::random::<f32>() < 0.1 { // 10% chance per step
+        if self.rng.gen::<f32>() < self.config.event_probabilities.health_check {
             let total_agents = self.agents.len();
             let active_projects = self.projects.len();
 
             println!("🏥 System Health Check:");
             println!("   👥 Total Agents: {}", total_agents);
             println!("   📋 Active Projects: {}", active_projects);
+            if !self.campaign_reports.is_empty() {
+                let total_leads: u32 = self.campaign_reports.values().map(|r| r.total_leads).sum();
+                let total_spend: f64 = self.campaign_reports.values().map(|r| r.total_spend).sum();
+                println!("   📣 Marketing Leads: {} (${:.2} spent)", total_leads, total_spend);
+            }
+            let (shared, dedicated) = self.catalog.tenancy_counts();
+            println!("   🏬 Customers: {} shared, {} dedicated (${:.2}/month)", shared, dedicated, self.catalog.monthly_revenue());
+            let deliveries = &self.router.stats;
+            println!("   📬 Messages: {} delivered, {} failed, {} undeliverable, {} queued",
+                   deliveries.delivered, deliveries.failed, deliveries.undeliverable, self.router.queued());
+            let dead_letters = self.router.dead_letters();
+            println!("   💀 Dead letters: {} ({} awaiting retry)", dead_letters.len(), self.router.pending_retries());
+            for dead in dead_letters.iter().rev().take(3) {
+                println!("      {} for {}: {:?} after {} attempts",
+                       dead.message.message_type, self.actor_name(dead.message.to_agent), dead.reason, dead.attempts);
+            }
+            let open_breakers = self.intake.open_departments();
+            if !open_breakers.is_empty() {
+                println!("   🚧 Intake paused: {} ({} deferred)", open_breakers.join(", "), self.intake.deferred_count());
+            }
+            println!("   📈 Overloaded {:.1}% of steps", self.intake.overload_share() * 100.0);
+            println!("   🔁 Waits: {} open, {} deadlocks broken ({:.2}/day)",
+                   self.wait_graph.edges().len(), self.wait_graph.detected, self.wait_graph.deadlocks_per_day(self.scheduler.now()));
+            if !self.config.webhooks.subscriptions.is_empty() {
+                let hooks = &self.webhooks.stats;
+                println!("   🪝 Webhooks: {} delivered, {} retried, {} failed, {} pending",
+                       hooks.delivered, hooks.retried, hooks.failed, self.webhooks.pending());
+            }
+            let (restarts, replacements) = self.supervisor.recoveries();
+            println!("   🩺 Supervision: {} agents failing, {} restarts, {} replacements",
+                   self.supervisor.unhealthy().count(), restarts, replacements);
+            let states: Vec<String> = self.agent_states.counts().iter()
+                .map(|(state, count)| format!("{} {}", count, state.as_str()))
+                .collect();
+            println!("   👥 Agent states: {}", states.join(", "));
+            let release = self.repository.latest_tag().map_or("none", |tag| tag.name.as_str());
+            println!("   🌿 Repository: {} commits, {} branches, latest release {}",
+                   self.repository.commit_count(), self.repository.branches().count(), release);
+            if let Some(review) = self.scorecards.latest_review() {
+                let grades: Vec<String> = review.scorecards.iter()
+                    .map(|card| format!("{} {}", card.service, card.grade.as_str()))
+                    .collect();
+                println!("   📋 Scorecards: {} ({} improvement tasks open)", grades.join(", "), self.scorecards.open_tasks().count());
+            }
+            let in_progress = self.task_graph.tasks().filter(|task| task.state == taskgraph::TaskState::InProgress).count();
+            let blocked = self.task_graph.blocked().count();
+            if in_progress + blocked > 0 {
+                println!("   🗺️ Project tasks: {} in progress, {} waiting on dependencies", in_progress, blocked);
+            }
+            if let Some(fullest) = self.quotas.utilization().first() {
+                println!("   🧮 Quotas: fullest {} at {:.0}%, {} increase requests pending",
+                       fullest.scope, fullest.percent, self.quotas.pending_requests().count());
+            }
+            if self.progression.gained + self.progression.lost > 0 {
+                println!("   🎓 Skills: {} points gained from experience, {} lost to disuse",
+                       self.progression.gained, self.progression.lost);
+            }
+            if let Some(review) = self.risks.latest_review() {
+                let now = self.scheduler.now();
+                let open: Vec<_> = self.risks.open().collect();
+                println!("   🧯 Risks: {} open, {} unowned, {} mitigations overdue (reviewed {})",
+                       open.len(), open.iter().filter(|risk| risk.owner.is_none()).count(),
+                       open.iter().filter(|risk| risk.is_overdue(now)).count(), review.held_at.format("%Y-%m-%d"));
+            }
+            if let Some(notes) = self.release_notes.notes().last() {
+                println!("   📝 Release notes: {} shipped, latest {} ({})",
+                       self.release_notes.notes().len(), notes.version, notes.summary());
+            }
+            if let Some(email) = &self.email {
+                println!("   📧 Email: {} ticket updates, {} campaign emails, {} status updates, {} summary reports, {} failed",
+                       email.stats.ticket_updates, email.stats.campaign_emails, email.stats.status_updates,
+                       email.stats.summary_reports, email.stats.failed);
+            }
+            if let Some(pager) = &self.pager {
+                println!("   📟 Paging: {} pages awaiting acknowledgement via {}", pager.open_pages(), pager.provider_name());
+            }
             println!("   ✅ All systems operational");
 
             // Check agent status
             let mut department_counts = HashMap::new();
-            for agent in self.agents.values() {
-                let dept = agent.get_agent().department.as_str();
+            for agent in self.agents.infos() {
+                let dept = agent.department.as_str();
                 *department_counts.entry(dept).or_insert(0) += 1;
             }
 
@@ -446,6 +4048,24 @@ impl CompanySimulation {
 }
 
 impl Department {
+    const ALL: [Department; 10] = [
+        Department::Engineering,
+        Department::Sales,
+        Department::DevOps,
+        Department::InfoSec,
+        Department::Networking,
+        Department::Ops,
+        Department::Marketing,
+        Department::Finance,
+        Department::HR,
+        Department::Legal,
+    ];
+
+    /// Department with the given name, ignoring case
+    fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|department| department.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
     /// Convert department to string
     fn as_str(&self) -> &'static str {
         match self {
@@ -465,27 +4085,346 @@ impl Department {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🤖 AI Company Simulation v0.1.0");
-    println!("==================================");
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Run(RunArgs::default()));
+
+    match command {
+        Command::Run(args) => {
+            let mut config = load_config_or_exit(cli.config.as_deref());
+            args.apply(&mut config);
+            validate_or_exit(&config);
+
+            print_banner();
+            let mut simulation = CompanySimulation::new(config).await?;
+            simulation.run().await?;
+        }
+        Command::Resume { snapshot, overrides } => {
+            print_banner();
+            let mut simulation = CompanySimulation::from_snapshot(&snapshot).await?;
+            overrides.apply(&mut simulation.config);
+            validate_or_exit(&simulation.config);
+            simulation.run().await?;
+        }
+        Command::Daemon(args) => {
+            print_banner();
+            let config = load_config_or_exit(cli.config.as_deref());
+            let mut simulation = match daemon::latest_snapshot(&config.daemon.snapshot_dir) {
+                Some(path) => CompanySimulation::from_snapshot(&path).await?,
+                None => CompanySimulation::new(config.clone()).await?,
+            };
+            // Settings from the current config win over those stored in the snapshot
+            simulation.config.daemon = config.daemon;
+            args.apply(&mut simulation.config);
+            validate_or_exit(&simulation.config);
+
+            let templates = load_templates_or_exit(&simulation.config);
+            simulation.daemon = Some(Daemon::new(simulation.config.daemon.clone(), templates, simulation.step_count));
+            simulation.run().await?;
+        }
+        Command::Replay { journal, strict } => {
+            let journal = Journal::load(&journal)?;
+            let mut config = journal.config.clone();
+            config.journal.path = None;
+            let mut simulation = CompanySimulation::new(config).await?;
+            let report = simulation.replay(journal).await?;
 
-    // Initialize the company simulation
-    let mut simulation = CompanySimulation::new().await?;
+            println!("📼 Replayed {} steps, {} messages injected", report.steps, report.injected);
+            println!("   Reproduced: {} | Missing: {} | Unexpected: {}", report.reproduced, report.missing, report.unexpected);
+            for divergence in report.divergences.iter().take(10) {
+                println!("   ⚠️  Step {}: {} sent {:+} {} message(s)", divergence.step,
+                       simulation.actor_name(divergence.agent), divergence.difference, divergence.message_type);
+            }
+            if strict && !report.is_faithful() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Command::ExportChat { snapshot, format, out } => {
+            let snapshot = SimulationSnapshot::load(&snapshot)?;
+            let agents: Vec<&Agent> = snapshot.agents.values().map(|agent| agent.agent()).collect();
+            let transcript = transcripts::Transcript::from_messages(&snapshot.message_log, &agents);
+            transcript.export(format, &out)?;
+            println!("💬 Exported {} channels from {} messages to {}",
+                   transcript.channels.len(), snapshot.message_log.len(), out.display());
+            return Ok(());
+        }
+        Command::Generate(args) => {
+            let config = load_config_or_exit(cli.config.as_deref());
+            let settings = &config.synthetic;
+            let seed = args.seed.or(config.seed).unwrap_or_else(rand::random);
+            let datasets = if args.datasets.is_empty() { synthetic::Dataset::ALL.to_vec() } else { args.datasets };
+            let schema = args.format.unwrap_or(settings.format);
+            let out = args.out.unwrap_or_else(|| settings.out_dir.clone());
 
-    // Run the simulation
-    simulation.run().await?;
+            let end = args.end.or(settings.end).unwrap_or_else(chrono::Utc::now);
+            let mut generator = synthetic::SyntheticGenerator::new(seed, &config.customers, settings.days, end);
+            for dataset in datasets {
+                let count = args.count.unwrap_or_else(|| synthetic::configured_volume(settings, dataset));
+                let manifest = generator.export(dataset, count, schema, &out)?;
+                println!("🧪 {}: {} records → {}", dataset.as_str(), manifest.records, manifest.file.display());
+            }
+            println!("🌱 Seed: {}, period ending {}", seed, end.to_rfc3339());
+            return Ok(());
+        }
+        Command::Doctor { snapshot, json } => {
+            let snapshot = SimulationSnapshot::load(&snapshot)?;
+            let diagnoses = doctor::diagnose(&snapshot);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diagnoses)?);
+            } else {
+                print!("{}", doctor::render(&diagnoses));
+            }
+            if !diagnoses.is_empty() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Command::ValidateConfig => {
+            let config = load_config_or_exit(cli.config.as_deref());
+            println!("✅ Configuration is valid");
+            println!("{}", toml::to_string_pretty(&config)?);
+            return Ok(());
+        }
+        Command::Report { snapshot, incident, thread, history, since } => {
+            if let Some(database) = history {
+                let since = since.map(|date| date.and_hms_opt(0, 0, 0).expect("valid time").and_utc());
+                print!("{}", history_report(&database, since).await?);
+                return Ok(());
+            }
+            let snapshot = SimulationSnapshot::load(&snapshot)?;
+            match (incident, thread) {
+                (Some(incident_id), _) => match snapshot.incident_timeline(incident_id) {
+                    Some(timeline) => print!("{}", timeline.render()),
+                    None => {
+                        eprintln!("❌ No incident {} in snapshot", incident_id);
+                        std::process::exit(1);
+                    }
+                },
+                (None, Some(thread_id)) => match snapshot.thread_history(thread_id) {
+                    Some(thread) => print!("{}", thread.render(|id| snapshot_actor_name(&snapshot, id))),
+                    None => {
+                        eprintln!("❌ No messages in thread {} in snapshot", thread_id);
+                        std::process::exit(1);
+                    }
+                },
+                (None, None) => {
+                    let config = load_config_or_exit(cli.config.as_deref());
+                    let templates = load_templates_or_exit(&config);
+                    print!("{}", templates.render(ReportTemplate::SnapshotReport, &snapshot_report_context(&snapshot))?);
+                }
+            }
+            return Ok(());
+        }
+    }
 
     println!("👋 Simulation ended. Thank you for running the AI Company!");
 
     Ok(())
 }
 
+fn print_banner() {
+    println!("🤖 AI Company Simulation v{}", env!("CARGO_PKG_VERSION"));
+    println!("==================================");
+}
+
+/// Load the configuration, exiting with a readable error if it is invalid
+fn load_config_or_exit(path: Option<&Path>) -> SimulationConfig {
+    match SimulationConfig::load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Load the configured report template pack, exiting with a readable error if it is broken
+fn load_templates_or_exit(config: &SimulationConfig) -> TemplatePack {
+    match TemplatePack::from_config(config.reports.template_pack.as_deref()) {
+        Ok(templates) => templates,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Summary of a history database for `report --history`
+#[cfg(feature = "sqlite")]
+async fn history_report(database: &Path, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<String, Box<dyn std::error::Error>> {
+    let store = history::HistoryStore::open(database).await?;
+    Ok(store.summary(since).await?.render())
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn history_report(_database: &Path, _since: Option<chrono::DateTime<chrono::Utc>>) -> Result<String, Box<dyn std::error::Error>> {
+    eprintln!("❌ report --history needs a build with the sqlite feature");
+    std::process::exit(2);
+}
+
+/// Resolve on the first SIGINT (Ctrl-C) or, on Unix, SIGTERM; names the signal
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                eprintln!("⚠️ Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
+/// Re-validate after command-line overrides were applied
+fn validate_or_exit(config: &SimulationConfig) {
+    if let Err(e) = config.validate() {
+        eprintln!("❌ {}", e);
+        std::process::exit(2);
+    }
+}
+
+/// Message about a page, tied to its incident so it shows in the incident timeline
+fn page_message(page: &Page, from_agent: Uuid, to_agent: Uuid, message_type: &str, content: String) -> Message {
+    Message {
+        id: Uuid::new_v4(),
+        from_agent,
+        to_agent,
+        message_type: message_type.to_string(),
+        content,
+        priority: MessagePriority::High,
+        timestamp: chrono::Utc::now(),
+        metadata: HashMap::from([
+            (timeline::INCIDENT_KEY.to_string(), page.incident_id.to_string()),
+            ("page_id".to_string(), page.id.to_string()),
+        ]),
+    }
+}
+
+/// Message asking an Ops agent to open a support ticket
+fn ticket_message(from_agent: Uuid, to_agent: Uuid, request: TicketRequest) -> Message {
+    let payload = MessagePayload::TicketRequest {
+        title: request.title,
+        priority: request.priority,
+        customer_id: request.customer_id,
+        customer_tier: request.customer_tier,
+        tags: request.tags,
+    };
+    payload.into_message(from_agent, to_agent, request.description, MessagePriority::Normal)
+}
+
+/// One of the names at random, or the fallback when there are none
+fn pick(names: &[String], rng: &mut StdRng, fallback: &str) -> String {
+    if names.is_empty() {
+        return fallback.to_string();
+    }
+    names[rng.gen_range(0..names.len())].clone()
+}
+
+/// Register the configured department scripts, replacing those departments'
+/// built-in agents
+#[cfg(feature = "scripting")]
+fn load_agent_scripts(mut registry: AgentRegistry, settings: &config::ScriptingSettings) -> Result<AgentRegistry, Box<dyn std::error::Error>> {
+    for (name, scripted) in &settings.departments {
+        let department = Department::named(name).ok_or_else(|| format!("Unknown department '{}' in [scripting]", name))?;
+        let script = scripting::Script::load(&scripted.script, &scripted.message_types, settings)?;
+        registry = registry.register(department, scripting::ScriptFactory::new(department, script));
+        println!("📜 {} agents run {}", department.as_str(), scripted.script.display());
+    }
+    Ok(registry)
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_agent_scripts(registry: AgentRegistry, settings: &config::ScriptingSettings) -> Result<AgentRegistry, Box<dyn std::error::Error>> {
+    if !settings.departments.is_empty() {
+        println!("⚠️ scripting.departments is set but this build lacks the scripting feature; departments keep their built-in agents");
+    }
+    Ok(registry)
+}
+
+/// Name of an agent or system actor in a snapshot
+fn snapshot_actor_name(snapshot: &SimulationSnapshot, id: Uuid) -> String {
+    if let Some(actor) = SystemActor::from_id(id) {
+        return actor.name().to_string();
+    }
+    snapshot.agents.get(&id)
+        .map(|agent| agent.agent().name.clone())
+        .unwrap_or_else(|| format!("unknown ({})", id.simple()))
+}
+
+/// Values available to the snapshot report template
+fn snapshot_report_context(snapshot: &SimulationSnapshot) -> serde_json::Value {
+    let mut department_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for agent in snapshot.agents.values() {
+        *department_counts.entry(agent.agent().department.as_str()).or_insert(0) += 1;
+    }
+
+    let marketing = (!snapshot.campaign_reports.is_empty()).then(|| serde_json::json!({
+        "leads": snapshot.campaign_reports.values().map(|r| r.total_leads).sum::<u32>(),
+        "spend": format!("{:.2}", snapshot.campaign_reports.values().map(|r| r.total_spend).sum::<f64>()),
+    }));
+
+    let (shared, dedicated) = snapshot.catalog.tenancy_counts();
+    let clock = snapshot.simulated_now();
+    let trends: Vec<serde_json::Value> = [ReportPeriod::Week, ReportPeriod::Quarter].iter()
+        .flat_map(|&period| snapshot.kpi_history.insights(clock, period))
+        .map(|insight| insight.template_context())
+        .collect();
+
+    serde_json::json!({
+        "taken_at": snapshot.taken_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        "step_count": snapshot.step_count,
+        "total_agents": snapshot.agents.len(),
+        "active_projects": snapshot.projects.len(),
+        "pending_messages": snapshot.pending_messages.len(),
+        "departments": department_counts.iter()
+            .map(|(name, agents)| serde_json::json!({ "name": name, "agents": agents }))
+            .collect::<Vec<_>>(),
+        "marketing": marketing,
+        "customers": {
+            "shared": shared,
+            "dedicated": dedicated,
+            "monthly_revenue": format!("{:.2}", snapshot.catalog.monthly_revenue()),
+        },
+        "intake": {
+            "overload_pct": format!("{:.1}", snapshot.intake.overload_share() * 100.0),
+            "breakers": snapshot.intake.breakers().iter()
+                .filter(|(_, breaker)| breaker.trips > 0)
+                .map(|(department, breaker)| serde_json::json!({
+                    "department": department,
+                    "trips": breaker.trips,
+                    "deferred": breaker.deferred,
+                    "open_steps": breaker.overloaded_steps,
+                }))
+                .collect::<Vec<_>>(),
+        },
+        "deadlocks": {
+            "broken": snapshot.wait_graph.detected,
+            "per_day": format!("{:.2}", snapshot.wait_graph.deadlocks_per_day(clock)),
+        },
+        "has_trends": !trends.is_empty(),
+        "trends": trends,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::AgentSnapshot;
 
     #[tokio::test]
     async fn test_simulation_initialization() {
-        let simulation = CompanySimulation::new().await;
+        let simulation = CompanySimulation::new(SimulationConfig::default()).await;
         assert!(simulation.is_ok());
 
         let sim = simulation.unwrap();
@@ -495,18 +4434,112 @@ mod tests {
 
     #[tokio::test]
     async fn test_department_creation() {
-        let simulation = CompanySimulation::new().await.unwrap();
+        let simulation = CompanySimulation::new(SimulationConfig::default()).await.unwrap();
 
         // Check that we have agents from all departments
         let mut departments_found = std::collections::HashSet::new();
-        for agent in simulation.agents.values() {
-            departments_found.insert(agent.get_agent().department.as_str());
+        for agent in simulation.agents.infos() {
+            departments_found.insert(agent.department.as_str());
         }
 
         assert!(departments_found.contains("DevOps"));
         assert!(departments_found.contains("InfoSec"));
         assert!(departments_found.contains("Networking"));
         assert!(departments_found.contains("Ops"));
+        assert!(departments_found.contains("Marketing"));
+    }
+
+    #[tokio::test]
+    async fn test_system_actor_attribution() {
+        let simulation = CompanySimulation::new(SimulationConfig::default()).await.unwrap();
+        assert_eq!(simulation.actor_name(SystemActor::ChaosEngine.id()), "Chaos Engine");
+
+        let agent = simulation.agents.infos().next().unwrap();
+        assert_eq!(simulation.actor_name(agent.id), agent.name);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_resume() {
+        let mut simulation = CompanySimulation::new(SimulationConfig::default()).await.unwrap();
+        simulation.step_count = 17;
+        let path = std::env::temp_dir().join(format!("resume-{}.json", Uuid::new_v4()));
+        simulation.config.snapshot_path = Some(path.clone());
+        simulation.save_snapshot().await.unwrap();
+
+        let resumed = CompanySimulation::from_snapshot(&path).await.unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(resumed.step_count, 17);
+        assert_eq!(resumed.agents.len(), simulation.agents.len());
+        assert!(resumed.agents.ids().iter().all(|agent_id| simulation.agents.contains(agent_id)));
+        assert_eq!(resumed.catalog.customers.len(), simulation.catalog.customers.len());
+        assert_eq!(resumed.scheduler.now(), simulation.scheduler.now());
+        assert_eq!(resumed.scheduler.len(), simulation.scheduler.len());
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_delivered_to_recipient() {
+        let mut simulation = CompanySimulation::new(SimulationConfig::default()).await.unwrap();
+        let ops_id = simulation.find_department_agent("Ops").unwrap();
+        let message = Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::EventGenerator.id(),
+            to_agent: ops_id,
+            message_type: "create_ticket".to_string(),
+            content: "Printer on fire".to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        };
+        simulation.pending_messages.push(message.clone());
+
+        simulation.flush_pending_messages().await.unwrap();
+        assert_eq!(simulation.router.status(message.id), Some(&DeliveryStatus::Queued));
+        simulation.deliver_messages().await.unwrap();
+
+        assert_eq!(simulation.router.status(message.id), Some(&DeliveryStatus::Delivered));
+        match simulation.agents.call(ops_id, |agent| agent.snapshot()).await.unwrap() {
+            AgentSnapshot::Ops(ops) => assert_eq!(ops.support_tickets.len(), 1),
+            _ => panic!("expected the Ops manager"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_headless_run_advances_simulated_clock() {
+        let mut config = SimulationConfig::default();
+        config.headless = true;
+        config.max_steps = Some(600);
+        config.seed = Some(11);
+        config.snapshot_path = None;
+        let mut simulation = CompanySimulation::new(config).await.unwrap();
+        let started_at = simulation.scheduler.now();
+
+        simulation.run().await.unwrap();
+
+        assert_eq!(simulation.step_count, 600);
+        // Steps only run in working hours, so 600 one-minute steps span at least 10 hours
+        assert!(simulation.scheduler.now() - started_at >= chrono::Duration::hours(10));
+    }
+
+    #[tokio::test]
+    async fn test_shared_fault_raises_wider_incident() {
+        let mut config = SimulationConfig::default();
+        config.seed = Some(3);
+        config.customers.dedicated_share = 0.0;
+        let mut simulation = CompanySimulation::new(config).await.unwrap();
+
+        simulation.handle_infrastructure_issue().await.unwrap();
+
+        let payloads: Vec<MessagePayload> = simulation.pending_messages.iter()
+            .filter_map(|m| MessagePayload::from_message(m).unwrap())
+            .collect();
+        let Some(MessagePayload::IncidentDeclared { severity, affected_customers, .. }) = payloads.iter()
+            .find(|p| matches!(p, MessagePayload::IncidentDeclared { .. })) else {
+            panic!("fault should raise an Ops incident");
+        };
+        assert_eq!(*severity, Severity::Sev2);
+        assert_eq!(affected_customers.len(), 8);
+        assert!(payloads.iter().any(|p| matches!(p, MessagePayload::InfrastructureAlert { tenancy: catalog::Tenancy::Shared, .. })));
     }
 }
 
@@ -0,0 +1,204 @@
+//! Agent Memory - What Each Agent Remembers of Its Recent Work
+//!
+//! This module implements a bounded memory per agent:
+//! - Recent messages the agent sent and received, and with whom; the
+//!   colleagues it exchanged the most with are its known collaborators
+//! - Recent tickets and incidents it finished, with the words they are
+//!   recognized by and how each was fixed
+//! - The oldest messages and tasks are forgotten once past the configured
+//!   limits
+//!
+//! The orchestrator routes agents' messages to known collaborators when it
+//! can, and an agent working an incident like one it fixed before recalls
+//! the fix and needs less time. Memories are part of simulation snapshots.
+
+use crate::config::MemorySettings;
+use crate::departments::ops::Incident;
+use crate::knowledge;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use uuid::Uuid;
+
+/// A message the agent sent or received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub at: DateTime<Utc>,
+    /// The other agent
+    pub with: Uuid,
+    pub message_type: String,
+    /// Sent by the agent rather than to it
+    pub sent: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Ticket,
+    Incident,
+}
+
+/// A ticket or incident the agent finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedTask {
+    pub at: DateTime<Utc>,
+    pub kind: TaskKind,
+    pub title: String,
+    /// Words the task is recognized by
+    pub keywords: BTreeSet<String>,
+    /// How it was fixed, for incidents
+    pub resolution: Option<String>,
+}
+
+impl CompletedTask {
+    pub fn ticket(title: &str, at: DateTime<Utc>) -> Self {
+        Self { at, kind: TaskKind::Ticket, title: title.to_string(), keywords: knowledge::words(title), resolution: None }
+    }
+
+    pub fn incident(incident: &Incident, at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            kind: TaskKind::Incident,
+            title: incident.title.clone(),
+            keywords: knowledge::keywords(incident),
+            resolution: incident.resolution.clone(),
+        }
+    }
+}
+
+/// One agent's recent messages and finished work
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentMemory {
+    interactions: VecDeque<Interaction>,
+    tasks: VecDeque<CompletedTask>,
+}
+
+impl AgentMemory {
+    pub fn interactions(&self) -> impl DoubleEndedIterator<Item = &Interaction> {
+        self.interactions.iter()
+    }
+
+    pub fn tasks(&self) -> impl DoubleEndedIterator<Item = &CompletedTask> {
+        self.tasks.iter()
+    }
+
+    /// Messages exchanged with each remembered colleague
+    pub fn collaborators(&self) -> BTreeMap<Uuid, usize> {
+        let mut counts = BTreeMap::new();
+        for interaction in &self.interactions {
+            *counts.entry(interaction.with).or_default() += 1;
+        }
+        counts
+    }
+
+    /// The candidate the agent exchanged the most with, the most recent one
+    /// on ties; `None` when it remembers none of them
+    pub fn favourite(&self, candidates: &[Uuid]) -> Option<Uuid> {
+        let counts = self.collaborators();
+        let last_seen = |agent_id: &Uuid| self.interactions.iter().rposition(|interaction| interaction.with == *agent_id);
+        candidates.iter()
+            .filter(|agent_id| counts.contains_key(agent_id))
+            .max_by_key(|agent_id| (counts[agent_id], last_seen(agent_id)))
+            .copied()
+    }
+
+    /// Most alike incident the agent fixed, at least `min_similarity` alike
+    pub fn recall(&self, keywords: &BTreeSet<String>, min_similarity: f64) -> Option<&CompletedTask> {
+        self.tasks.iter()
+            .filter(|task| task.kind == TaskKind::Incident)
+            .map(|task| (task, knowledge::similarity(keywords, &task.keywords)))
+            .filter(|(_, score)| *score >= min_similarity)
+            // Later fixes win ties
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(task, _)| task)
+    }
+}
+
+/// Every agent's memory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentMemories {
+    memories: BTreeMap<Uuid, AgentMemory>,
+}
+
+impl AgentMemories {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, agent_id: &Uuid) -> Option<&AgentMemory> {
+        self.memories.get(agent_id)
+    }
+
+    /// Remember a message on both ends, forgetting the oldest past
+    /// `max_interactions`
+    pub fn remember_message(&mut self, from: Uuid, to: Uuid, message_type: &str, at: DateTime<Utc>, settings: &MemorySettings) {
+        for (agent_id, with, sent) in [(from, to, true), (to, from, false)] {
+            let interactions = &mut self.memories.entry(agent_id).or_default().interactions;
+            interactions.push_back(Interaction { at, with, message_type: message_type.to_string(), sent });
+            while interactions.len() > settings.max_interactions {
+                interactions.pop_front();
+            }
+        }
+    }
+
+    /// Remember a finished task, forgetting the oldest past `max_tasks`
+    pub fn remember_task(&mut self, agent_id: Uuid, task: CompletedTask, settings: &MemorySettings) {
+        let tasks = &mut self.memories.entry(agent_id).or_default().tasks;
+        tasks.push_back(task);
+        while tasks.len() > settings.max_tasks {
+            tasks.pop_front();
+        }
+    }
+
+    /// Favourite collaborator of an agent among the candidates
+    pub fn favourite(&self, agent_id: &Uuid, candidates: &[Uuid]) -> Option<Uuid> {
+        self.memories.get(agent_id)?.favourite(candidates)
+    }
+
+    /// Forget agents no longer in the company, and what others remember of
+    /// them as collaborators
+    pub fn retain(&mut self, agents: &[Uuid]) {
+        self.memories.retain(|agent_id, _| agents.contains(agent_id));
+        for memory in self.memories.values_mut() {
+            memory.interactions.retain(|interaction| agents.contains(&interaction.with));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_is_bounded_and_recalls_collaborators_and_fixes() {
+        let settings = MemorySettings { max_interactions: 3, max_tasks: 2, ..MemorySettings::default() };
+        let now: DateTime<Utc> = "2024-03-04T10:00:00Z".parse().unwrap();
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut memories = AgentMemories::new();
+
+        memories.remember_message(alice, carol, "status_update", now, &settings);
+        memories.remember_message(alice, bob, "issue_report", now, &settings);
+        memories.remember_message(bob, alice, "collaboration_request", now, &settings);
+        assert_eq!(memories.favourite(&alice, &[bob, carol]), Some(bob));
+        assert_eq!(memories.favourite(&bob, &[carol]), None);
+        // The oldest message is forgotten, and Carol with it
+        memories.remember_message(alice, bob, "status_update", now, &settings);
+        assert_eq!(memories.get(&alice).unwrap().collaborators(), BTreeMap::from([(bob, 3)]));
+        assert!(!memories.get(&alice).unwrap().interactions().any(|interaction| interaction.with == carol));
+
+        memories.remember_task(alice, CompletedTask::ticket("Cannot reset password", now), &settings);
+        let mut fixed = CompletedTask::ticket("High CPU usage on web-pool", now);
+        fixed.kind = TaskKind::Incident;
+        fixed.resolution = Some("Restarted the workers".to_string());
+        memories.remember_task(alice, fixed, &settings);
+        let keywords = knowledge::words("CPU usage high again on web-pool");
+        assert_eq!(memories.get(&alice).unwrap().recall(&keywords, 0.5).unwrap().resolution.as_deref(), Some("Restarted the workers"));
+        memories.remember_task(alice, CompletedTask::ticket("Invoice missing", now), &settings);
+        memories.remember_task(alice, CompletedTask::ticket("Slow dashboard", now), &settings);
+        assert!(memories.get(&alice).unwrap().recall(&keywords, 0.5).is_none());
+
+        memories.retain(&[alice]);
+        assert!(memories.get(&bob).is_none());
+        assert_eq!(memories.get(&alice).unwrap().collaborators().len(), 0);
+    }
+}
@@ -0,0 +1,194 @@
+//! Metrics Export - Prometheus Endpoint for Simulation Internals
+//!
+//! This module implements an optional `/metrics` HTTP endpoint in the
+//! Prometheus text exposition format:
+//! - Counters of messages sent, delivered, failed and dead-lettered
+//! - Gauges of open tickets, active incidents by severity, active deployments
+//!   and agents per department
+//! - Gauges of the Ops ticket queue: its depth, the oldest ticket's wait and
+//!   the mean wait of recently routed tickets
+//! - A summary of how long orchestrator steps take in real time
+//!
+//! The orchestrator records a fresh sample after every step; scrapes read the
+//! latest one, so a scrape never waits on the simulation.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Content type of the text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// State of the simulation after a step
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSample {
+    pub step_count: u64,
+    pub messages_delivered: u64,
+    pub messages_failed: u64,
+    pub messages_dead_lettered: u64,
+    /// Messages queued in inboxes or waiting for a retry
+    pub messages_queued: usize,
+    pub tickets_open: usize,
+    pub ticket_queue_depth: usize,
+    pub ticket_queue_oldest_wait_minutes: i64,
+    pub ticket_queue_mean_wait_minutes: f64,
+    /// Active incidents by severity name
+    pub incidents_active: BTreeMap<String, usize>,
+    pub deployments_active: usize,
+    /// Agents by department name
+    pub agents: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    sample: MetricsSample,
+    messages_sent: u64,
+    step_seconds_sum: f64,
+    step_seconds_count: u64,
+}
+
+/// Latest sample and running totals, shared with the endpoint task
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count messages handed to the message bus
+    pub fn record_sent(&self, count: u64) {
+        self.state.lock().expect("metrics lock poisoned").messages_sent += count;
+    }
+
+    /// Record a finished step and the state it left behind
+    pub fn record_step(&self, duration: std::time::Duration, sample: MetricsSample) {
+        let mut state = self.state.lock().expect("metrics lock poisoned");
+        state.step_seconds_sum += duration.as_secs_f64();
+        state.step_seconds_count += 1;
+        state.sample = sample;
+    }
+
+    /// Everything in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().expect("metrics lock poisoned");
+        let sample = &state.sample;
+        let mut out = String::new();
+
+        metric(&mut out, "aivertco_steps_total", "counter", "Orchestrator steps run", &[("", sample.step_count as f64)]);
+        metric(&mut out, "aivertco_messages_sent_total", "counter", "Messages handed to the message bus", &[("", state.messages_sent as f64)]);
+        metric(&mut out, "aivertco_messages_delivered_total", "counter", "Messages delivered to agents", &[("", sample.messages_delivered as f64)]);
+        metric(&mut out, "aivertco_messages_failed_total", "counter", "Failed delivery attempts", &[("", sample.messages_failed as f64)]);
+        metric(&mut out, "aivertco_messages_dead_lettered_total", "counter", "Messages given up on after all retries", &[("", sample.messages_dead_lettered as f64)]);
+        metric(&mut out, "aivertco_messages_queued", "gauge", "Messages waiting for delivery", &[("", sample.messages_queued as f64)]);
+        metric(&mut out, "aivertco_tickets_open", "gauge", "Support tickets open or in progress", &[("", sample.tickets_open as f64)]);
+        metric(&mut out, "aivertco_ticket_queue_depth", "gauge", "Support tickets waiting for an Ops agent", &[("", sample.ticket_queue_depth as f64)]);
+        metric(&mut out, "aivertco_ticket_queue_oldest_wait_minutes", "gauge", "Simulated minutes the longest-waiting queued ticket has waited", &[("", sample.ticket_queue_oldest_wait_minutes as f64)]);
+        metric(&mut out, "aivertco_ticket_queue_mean_wait_minutes", "gauge", "Mean simulated minutes recently routed tickets waited", &[("", sample.ticket_queue_mean_wait_minutes)]);
+
+        let incidents: Vec<(String, f64)> = sample.incidents_active.iter()
+            .map(|(severity, count)| (format!("severity=\"{}\"", severity), *count as f64))
+            .collect();
+        metric(&mut out, "aivertco_incidents_active", "gauge", "Active incidents by severity", &labelled(&incidents));
+        metric(&mut out, "aivertco_deployments_active", "gauge", "Deployments pending or in progress", &[("", sample.deployments_active as f64)]);
+        let agents: Vec<(String, f64)> = sample.agents.iter()
+            .map(|(department, count)| (format!("department=\"{}\"", department), *count as f64))
+            .collect();
+        metric(&mut out, "aivertco_agents", "gauge", "Agents by department", &labelled(&agents));
+
+        out.push_str("# HELP aivertco_step_duration_seconds Real time spent running an orchestrator step\n");
+        out.push_str("# TYPE aivertco_step_duration_seconds summary\n");
+        out.push_str(&format!("aivertco_step_duration_seconds_sum {}\n", state.step_seconds_sum));
+        out.push_str(&format!("aivertco_step_duration_seconds_count {}\n", state.step_seconds_count));
+        out
+    }
+
+    /// Bind the endpoint and serve it in the background
+    pub async fn serve(&self, addr: &str) -> Result<(), MetricsError> {
+        let listener = TcpListener::bind(addr).await
+            .map_err(|e| MetricsError::Bind(addr.to_string(), e))?;
+        println!("📈 Prometheus metrics on http://{}/metrics", addr);
+
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { continue };
+                tokio::spawn(serve_metrics_request(stream, metrics.clone()));
+            }
+        });
+        Ok(())
+    }
+}
+
+fn labelled(series: &[(String, f64)]) -> Vec<(&str, f64)> {
+    series.iter().map(|(labels, value)| (labels.as_str(), *value)).collect()
+}
+
+/// Append one metric family; an empty label set writes the bare name
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, series: &[(&str, f64)]) {
+    out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+    for (labels, value) in series {
+        if labels.is_empty() {
+            out.push_str(&format!("{} {}\n", name, value));
+        } else {
+            out.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+        }
+    }
+}
+
+/// Answer one scrape
+async fn serve_metrics_request(mut stream: TcpStream, metrics: Metrics) {
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).await.unwrap_or(0);
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = match path {
+        "/metrics" => ("200 OK", CONTENT_TYPE, metrics.render()),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body);
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Metrics endpoint errors
+#[derive(Debug, thiserror::Error)]
+pub enum MetricsError {
+    #[error("Failed to bind metrics endpoint on {0}: {1}")]
+    Bind(String, std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_exposition_format() {
+        let metrics = Metrics::new();
+        metrics.record_sent(7);
+        metrics.record_step(std::time::Duration::from_millis(250), MetricsSample {
+            step_count: 3,
+            messages_delivered: 5,
+            tickets_open: 2,
+            incidents_active: BTreeMap::from([("Sev1".to_string(), 1), ("Sev3".to_string(), 4)]),
+            agents: BTreeMap::from([("Ops".to_string(), 3)]),
+            ..MetricsSample::default()
+        });
+        let text = metrics.render();
+        assert!(text.contains("# TYPE aivertco_messages_sent_total counter\naivertco_messages_sent_total 7\n"));
+        assert!(text.contains("aivertco_incidents_active{severity=\"Sev1\"} 1\naivertco_incidents_active{severity=\"Sev3\"} 4\n"));
+        assert!(text.contains("aivertco_agents{department=\"Ops\"} 3\n"));
+
+        metrics.record_step(std::time::Duration::from_millis(250), MetricsSample { step_count: 4, ..MetricsSample::default() });
+        let text = metrics.render();
+        assert!(text.contains("aivertco_steps_total 4\n"));
+        assert!(text.contains("aivertco_step_duration_seconds_sum 0.5\naivertco_step_duration_seconds_count 2\n"));
+        // Only the latest sample is exported
+        assert!(!text.contains("severity=\"Sev1\""));
+    }
+}
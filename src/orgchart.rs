@@ -0,0 +1,375 @@
+//! Org Chart - Reporting Lines, Rollups & Delegation
+//!
+//! This module implements the company's management structure, built from the
+//! manager every agent reports to:
+//! - Reporting lines: each agent's manager and direct reports, with the
+//!   department heads reporting to the CEO at the top
+//! - Status rollups: a daily summary of the open and finished work below
+//!   every manager, rolled up through the department heads to the CEO
+//! - Delegation: a manager moves open work from their busiest report to the
+//!   least busy one once the gap between them grows too wide
+//! - Approvals: change requests go to the nearest manager in the requester's
+//!   chain whose limit covers them, and to the CEO above every limit, as do
+//!   budget increases beyond the quota approval matrix
+//! - Escalations that reach a department head without a resolution go on to
+//!   the CEO
+//!
+//! The chart is rebuilt from the roster whenever it changes; rollups,
+//! approvals and escalations are kept with it for reports.
+
+use crate::config::OrgSettings;
+use crate::runtime::AgentInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Approvals and escalations kept in the chart
+const MAX_RECORDS: usize = 200;
+
+/// One agent's place in the chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Member {
+    pub id: Uuid,
+    pub name: String,
+    pub department: String,
+    pub manager_id: Option<Uuid>,
+    /// Direct reports, in id order
+    pub reports: Vec<Uuid>,
+}
+
+impl Member {
+    /// Department heads report to the CEO
+    pub fn is_head(&self) -> bool {
+        self.manager_id.is_none()
+    }
+}
+
+/// Open and finished work of one agent, as reported for a rollup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkStatus {
+    pub backlog: usize,
+    pub completed: usize,
+}
+
+/// A manager's view of a direct report, with everything below them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportStatus {
+    pub agent_id: Uuid,
+    pub name: String,
+    /// Agents in the report's part of the chart, the report included
+    pub staff: usize,
+    pub work: WorkStatus,
+}
+
+/// Work below one manager, or below the CEO for the whole company
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusRollup {
+    /// `None` for the CEO's company-wide rollup
+    pub manager_id: Option<Uuid>,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub staff: usize,
+    pub work: WorkStatus,
+    pub reports: Vec<ReportStatus>,
+}
+
+impl StatusRollup {
+    /// Direct reports with the most and the least open work
+    pub fn busiest_and_idlest(&self) -> Option<(&ReportStatus, &ReportStatus)> {
+        let busiest = self.reports.iter().max_by_key(|report| (report.work.backlog, report.agent_id))?;
+        let idlest = self.reports.iter().min_by_key(|report| (report.work.backlog, report.agent_id))?;
+        Some((busiest, idlest))
+    }
+}
+
+/// Who decides a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Approver {
+    Manager(Uuid),
+    Ceo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalKind {
+    /// Shipping a merge to production, sized in merged features
+    ChangeRequest,
+    /// Raising a quota beyond the approval matrix, sized in percent
+    Budget,
+    /// Granting a role beyond the baseline, sized in days granted
+    Access,
+}
+
+impl ApprovalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalKind::ChangeRequest => "change request",
+            ApprovalKind::Budget => "budget",
+            ApprovalKind::Access => "access",
+        }
+    }
+}
+
+/// A decided request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub kind: ApprovalKind,
+    pub requested_by: Uuid,
+    pub subject: String,
+    pub amount: f64,
+    pub approver: Approver,
+    pub approved: bool,
+    pub decided_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Work a department head could not resolve, handed to the CEO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub from: Uuid,
+    pub subject: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Reporting lines of the company and what flows along them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrgChart {
+    /// Rebuilt from the roster, so never persisted
+    #[serde(skip)]
+    members: BTreeMap<Uuid, Member>,
+    /// Latest rollup of every manager
+    rollups: BTreeMap<Uuid, StatusRollup>,
+    /// Latest company-wide rollup for the CEO
+    company: Option<StatusRollup>,
+    approvals: Vec<Approval>,
+    escalations: Vec<Escalation>,
+}
+
+impl OrgChart {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild the reporting lines from the roster
+    ///
+    /// Agents whose manager is not in the company are treated as department
+    /// heads, so every agent stays reachable from the CEO.
+    pub fn rebuild<'a>(&mut self, agents: impl IntoIterator<Item = &'a AgentInfo>) {
+        self.members = agents.into_iter()
+            .map(|agent| (agent.id, Member {
+                id: agent.id,
+                name: agent.name.clone(),
+                department: agent.department.as_str().to_string(),
+                manager_id: agent.manager_id,
+                reports: Vec::new(),
+            }))
+            .collect();
+        let lines: Vec<(Uuid, Option<Uuid>)> = self.members.values().map(|member| (member.id, member.manager_id)).collect();
+        for (id, manager_id) in lines {
+            match manager_id.filter(|manager| *manager != id && self.members.contains_key(manager)) {
+                Some(manager) => self.members.get_mut(&manager).expect("checked above").reports.push(id),
+                None => self.members.get_mut(&id).expect("built above").manager_id = None,
+            }
+        }
+        self.rollups.retain(|manager, _| self.members.contains_key(manager));
+    }
+
+    pub fn member(&self, id: &Uuid) -> Option<&Member> {
+        self.members.get(id)
+    }
+
+    /// Department heads, in id order
+    pub fn heads(&self) -> impl Iterator<Item = &Member> {
+        self.members.values().filter(|member| member.is_head())
+    }
+
+    /// Agents with at least one report, in id order
+    pub fn managers(&self) -> impl Iterator<Item = &Member> {
+        self.members.values().filter(|member| !member.reports.is_empty())
+    }
+
+    /// Managers above an agent, nearest first, ending with the department head
+    pub fn chain(&self, id: Uuid) -> Vec<Uuid> {
+        let mut chain = Vec::new();
+        let mut current = self.members.get(&id).and_then(|member| member.manager_id);
+        while let Some(manager) = current {
+            // Stop at a management loop rather than walk it forever
+            if chain.contains(&manager) || manager == id {
+                break;
+            }
+            chain.push(manager);
+            current = self.members.get(&manager).and_then(|member| member.manager_id);
+        }
+        chain
+    }
+
+    /// Everyone below a member, the member included
+    fn subtree(&self, id: Uuid) -> Vec<Uuid> {
+        let mut below = vec![id];
+        let mut next = 0;
+        while next < below.len() {
+            if let Some(member) = self.members.get(&below[next]) {
+                let new: Vec<Uuid> = member.reports.iter().filter(|report| !below.contains(report)).copied().collect();
+                below.extend(new);
+            }
+            next += 1;
+        }
+        below
+    }
+
+    /// Roll reported work up to every manager and to the CEO
+    pub fn roll_up(&mut self, work: &BTreeMap<Uuid, WorkStatus>, now: chrono::DateTime<chrono::Utc>) {
+        let total = |ids: &[Uuid]| ids.iter().fold(WorkStatus::default(), |sum, id| {
+            let status = work.get(id).copied().unwrap_or_default();
+            WorkStatus { backlog: sum.backlog + status.backlog, completed: sum.completed + status.completed }
+        });
+        let report_status = |chart: &Self, id: Uuid| {
+            let below = chart.subtree(id);
+            ReportStatus {
+                agent_id: id,
+                name: chart.members.get(&id).map_or_else(String::new, |member| member.name.clone()),
+                staff: below.len(),
+                work: total(&below),
+            }
+        };
+
+        let mut rollups = BTreeMap::new();
+        for manager in self.managers() {
+            let below = self.subtree(manager.id);
+            rollups.insert(manager.id, StatusRollup {
+                manager_id: Some(manager.id),
+                at: now,
+                staff: below.len(),
+                work: total(&below),
+                reports: manager.reports.iter().map(|report| report_status(self, *report)).collect(),
+            });
+        }
+        let heads: Vec<ReportStatus> = self.heads().map(|head| report_status(self, head.id)).collect();
+        let everyone: Vec<Uuid> = self.members.keys().copied().collect();
+        self.company = Some(StatusRollup {
+            manager_id: None,
+            at: now,
+            staff: everyone.len(),
+            work: total(&everyone),
+            reports: heads,
+        });
+        self.rollups = rollups;
+    }
+
+    pub fn rollup(&self, manager_id: &Uuid) -> Option<&StatusRollup> {
+        self.rollups.get(manager_id)
+    }
+
+    /// The CEO's latest rollup of the whole company
+    pub fn company_rollup(&self) -> Option<&StatusRollup> {
+        self.company.as_ref()
+    }
+
+    /// Managers whose busiest report carries at least `gap` more open items
+    /// than their least busy one, with the two reports
+    pub fn delegations(&self, gap: usize) -> Vec<(Uuid, Uuid, Uuid)> {
+        self.rollups.values()
+            .filter_map(|rollup| {
+                let (busiest, idlest) = rollup.busiest_and_idlest()?;
+                let manager = rollup.manager_id?;
+                (gap > 0 && busiest.work.backlog >= idlest.work.backlog + gap)
+                    .then_some((manager, busiest.agent_id, idlest.agent_id))
+            })
+            .collect()
+    }
+
+    /// Who approves a change of `features` merged features from `requester`:
+    /// the nearest manager whose limit covers it, or the CEO above every limit
+    pub fn change_approver(&self, requester: Uuid, features: u32, settings: &OrgSettings) -> Approver {
+        for manager in self.chain(requester) {
+            let head = self.members.get(&manager).is_some_and(Member::is_head);
+            let limit = if head { settings.head_change_limit } else { settings.manager_change_limit };
+            if features <= limit {
+                return Approver::Manager(manager);
+            }
+        }
+        Approver::Ceo
+    }
+
+    /// Record a decided request
+    pub fn record_approval(&mut self, approval: Approval) {
+        self.approvals.push(approval);
+        if self.approvals.len() > MAX_RECORDS {
+            self.approvals.remove(0);
+        }
+    }
+
+    pub fn approvals(&self) -> &[Approval] {
+        &self.approvals
+    }
+
+    /// Record work a department head handed to the CEO
+    pub fn escalate(&mut self, from: Uuid, subject: String, now: chrono::DateTime<chrono::Utc>) {
+        self.escalations.push(Escalation { from, subject, at: now });
+        if self.escalations.len() > MAX_RECORDS {
+            self.escalations.remove(0);
+        }
+    }
+
+    pub fn escalations(&self) -> &[Escalation] {
+        &self.escalations
+    }
+
+    /// The chart as an indented tree under the CEO
+    pub fn render(&self) -> String {
+        let mut out = String::from("CEO\n");
+        let mut stack: Vec<(Uuid, usize)> = self.heads().map(|head| (head.id, 1)).collect();
+        stack.reverse();
+        while let Some((id, depth)) = stack.pop() {
+            let Some(member) = self.members.get(&id) else {
+                continue;
+            };
+            let open = self.rollups.get(&id)
+                .map_or_else(String::new, |rollup| format!(", {} open below", rollup.work.backlog));
+            out.push_str(&format!("{}{} ({}{})\n", "  ".repeat(depth), member.name, member.department, open));
+            stack.extend(member.reports.iter().rev().map(|report| (*report, depth + 1)));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Department;
+
+    fn info(name: &str, manager_id: Option<Uuid>) -> AgentInfo {
+        AgentInfo { id: Uuid::new_v4(), name: name.to_string(), department: Department::Ops, manager_id }
+    }
+
+    #[test]
+    fn test_rollups_delegation_and_approvals() {
+        let head = info("Head", None);
+        let lead = info("Lead", Some(head.id));
+        let busy = info("Busy", Some(lead.id));
+        let idle = info("Idle", Some(lead.id));
+        // Reports to someone who left, so counts as a head
+        let orphan = info("Orphan", Some(Uuid::new_v4()));
+        let mut chart = OrgChart::new();
+        chart.rebuild([&head, &lead, &busy, &idle, &orphan]);
+
+        assert_eq!(chart.chain(busy.id), vec![lead.id, head.id]);
+        assert_eq!(chart.heads().count(), 2);
+
+        let now = chrono::Utc::now();
+        let work = BTreeMap::from([
+            (busy.id, WorkStatus { backlog: 5, completed: 1 }),
+            (idle.id, WorkStatus { backlog: 1, completed: 3 }),
+            (orphan.id, WorkStatus { backlog: 2, completed: 0 }),
+        ]);
+        chart.roll_up(&work, now);
+        assert_eq!(chart.rollup(&head.id).unwrap().work, WorkStatus { backlog: 6, completed: 4 });
+        assert_eq!(chart.company_rollup().unwrap().work.backlog, 8);
+        assert_eq!(chart.delegations(3), vec![(lead.id, busy.id, idle.id)]);
+        assert!(chart.delegations(5).is_empty());
+
+        let settings = OrgSettings { manager_change_limit: 1, head_change_limit: 3, ..OrgSettings::default() };
+        assert_eq!(chart.change_approver(busy.id, 1, &settings), Approver::Manager(lead.id));
+        assert_eq!(chart.change_approver(busy.id, 3, &settings), Approver::Manager(head.id));
+        assert_eq!(chart.change_approver(busy.id, 4, &settings), Approver::Ceo);
+        // Heads have nobody but the CEO above them
+        assert_eq!(chart.change_approver(head.id, 1, &settings), Approver::Ceo);
+    }
+}
@@ -0,0 +1,560 @@
+//! Paging - On-Call Rotation & Paging Drills
+//!
+//! This module implements paging the on-call responder for severe incidents:
+//! - An on-call rotation over the Ops agents that hands over once per
+//!   simulated day, escalating to the next responder when a page goes
+//!   unacknowledged
+//! - Paging services pages are sent through: PagerDuty (Events API v2) or
+//!   Opsgenie sandbox accounts, or a built-in mock that acknowledges like a
+//!   responder would
+//! - Acknowledgements read back from the service, so people answering a
+//!   drill on their phone are reflected in the simulation
+//!
+//! The PagerDuty and Opsgenie services need the `paging` feature, which
+//! pulls in the TLS-capable HTTP client; the mock works in every build.
+
+use crate::config::{PagingProviderSettings, PagingSettings};
+use crate::departments::ops::Severity;
+use crate::webhooks::{EntityChange, EntityEvent};
+use async_trait::async_trait;
+use chrono::Datelike;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Longest a provider request may take, so a hung provider cannot stall the step
+#[cfg(feature = "paging")]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Where a page stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageStatus {
+    /// Waiting for the current responder to acknowledge
+    Paging,
+    Acknowledged,
+    /// Every responder in the escalation chain let it time out
+    Unanswered,
+}
+
+/// Page raised for an incident
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub summary: String,
+    pub severity: Severity,
+    /// Agent currently paged
+    pub responder: Uuid,
+    /// 0 for the primary on-call, one more per escalation
+    pub escalation_level: u32,
+    pub status: PageStatus,
+    pub triggered_at: chrono::DateTime<chrono::Utc>,
+    /// When the current responder was paged
+    pub paged_at: chrono::DateTime<chrono::Utc>,
+    pub acknowledged_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Who acknowledged, as the paging service reports it
+    pub acknowledged_by: Option<String>,
+}
+
+/// Acknowledgement read back from a paging service
+#[derive(Debug, Clone, PartialEq)]
+pub struct Acknowledgement {
+    pub page_id: Uuid,
+    /// Person who acknowledged; `None` when the service does not say
+    pub by: Option<String>,
+}
+
+/// What happened to pages during one round
+#[derive(Debug, Default)]
+pub struct PageRound {
+    pub triggered: Vec<Page>,
+    pub escalated: Vec<Page>,
+    pub acknowledged: Vec<Page>,
+    pub unanswered: Vec<Page>,
+    pub errors: Vec<PagingError>,
+}
+
+/// Service pages are sent through
+#[async_trait]
+pub trait PagingProvider: Send + Sync + std::fmt::Debug {
+    /// Service name for logs, e.g. "PagerDuty"
+    fn name(&self) -> &'static str;
+
+    /// Send a page, or send it again to a new responder after an escalation
+    async fn trigger(&mut self, page: &Page) -> Result<(), PagingError>;
+
+    /// Close a page once its incident is resolved
+    async fn resolve(&mut self, page: &Page) -> Result<(), PagingError>;
+
+    /// Acknowledgements received for the given unanswered pages
+    async fn acknowledgements(&mut self, pages: &[&Page], now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Acknowledgement>, PagingError>;
+}
+
+/// Pages the on-call responder and follows pages up until they are answered
+#[derive(Debug)]
+pub struct Pager {
+    provider: Box<dyn PagingProvider>,
+    settings: PagingSettings,
+    /// Responders in rotation order
+    rotation: Vec<Uuid>,
+    /// Pages of unresolved incidents, by incident id
+    pages: BTreeMap<Uuid, Page>,
+}
+
+impl Pager {
+    /// Build the pager for the configured service, or `None` when paging is off
+    pub fn from_settings(settings: &PagingSettings, seed: Option<u64>) -> Result<Option<Self>, PagingError> {
+        let provider: Box<dyn PagingProvider> = match &settings.provider {
+            None => return Ok(None),
+            Some(PagingProviderSettings::Mock { ack_probability, ack_minutes }) => {
+                Box::new(MockPager::new(seed, *ack_probability, *ack_minutes))
+            }
+            #[cfg(feature = "paging")]
+            Some(PagingProviderSettings::Pagerduty { events_url, api_url, routing_key_env, token_env }) => Box::new(PagerDuty {
+                client: http_client()?,
+                events_url: events_url.trim_end_matches('/').to_string(),
+                api_url: api_url.trim_end_matches('/').to_string(),
+                routing_key: read_secret(routing_key_env)?,
+                token: read_secret(token_env)?,
+            }),
+            #[cfg(feature = "paging")]
+            Some(PagingProviderSettings::Opsgenie { api_url, api_key_env }) => Box::new(Opsgenie {
+                client: http_client()?,
+                api_url: api_url.trim_end_matches('/').to_string(),
+                api_key: read_secret(api_key_env)?,
+            }),
+            #[cfg(not(feature = "paging"))]
+            Some(PagingProviderSettings::Pagerduty { .. } | PagingProviderSettings::Opsgenie { .. }) => {
+                return Err(PagingError::FeatureDisabled);
+            }
+        };
+        Ok(Some(Self::with_provider(provider, settings.clone())))
+    }
+
+    pub fn with_provider(provider: Box<dyn PagingProvider>, settings: PagingSettings) -> Self {
+        Self { provider, settings, rotation: Vec::new(), pages: BTreeMap::new() }
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Set who takes part in the on-call rotation, in rotation order
+    pub fn set_rotation(&mut self, responders: Vec<Uuid>) {
+        self.rotation = responders;
+    }
+
+    /// Responder at an escalation level; the rotation hands over every simulated day
+    pub fn on_call(&self, now: chrono::DateTime<chrono::Utc>, escalation_level: u32) -> Option<Uuid> {
+        if self.rotation.is_empty() {
+            return None;
+        }
+        let day = now.date_naive().num_days_from_ce() as usize;
+        Some(self.rotation[(day + escalation_level as usize) % self.rotation.len()])
+    }
+
+    /// Pages still waiting for an acknowledgement
+    pub fn open_pages(&self) -> usize {
+        self.pages.values().filter(|page| page.status == PageStatus::Paging).count()
+    }
+
+    /// Page for newly declared incidents, close pages of resolved ones, then
+    /// pick up acknowledgements and escalate pages that timed out
+    pub async fn run(&mut self, changes: &[EntityChange], now: chrono::DateTime<chrono::Utc>) -> PageRound {
+        let mut round = PageRound::default();
+
+        for change in changes {
+            match change.event {
+                EntityEvent::IncidentDeclared => {
+                    let Ok(severity) = serde_json::from_value::<Severity>(change.entity["severity"].clone()) else {
+                        continue;
+                    };
+                    if severity.rank() < self.settings.min_severity.rank() || self.pages.contains_key(&change.entity_id) {
+                        continue;
+                    }
+                    let Some(responder) = self.on_call(now, 0) else {
+                        continue;
+                    };
+                    let page = Page {
+                        id: Uuid::new_v4(),
+                        incident_id: change.entity_id,
+                        summary: change.entity["title"].as_str().unwrap_or("Untitled incident").to_string(),
+                        severity,
+                        responder,
+                        escalation_level: 0,
+                        status: PageStatus::Paging,
+                        triggered_at: now,
+                        paged_at: now,
+                        acknowledged_at: None,
+                        acknowledged_by: None,
+                    };
+                    if let Err(e) = self.provider.trigger(&page).await {
+                        round.errors.push(e);
+                    }
+                    round.triggered.push(page.clone());
+                    self.pages.insert(page.incident_id, page);
+                }
+                EntityEvent::IncidentResolved => {
+                    if let Some(page) = self.pages.remove(&change.entity_id) {
+                        if let Err(e) = self.provider.resolve(&page).await {
+                            round.errors.push(e);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let open: Vec<&Page> = self.pages.values().filter(|page| page.status == PageStatus::Paging).collect();
+        let acknowledgements = if open.is_empty() {
+            Vec::new()
+        } else {
+            self.provider.acknowledgements(&open, now).await.unwrap_or_else(|e| {
+                round.errors.push(e);
+                Vec::new()
+            })
+        };
+        for acknowledgement in acknowledgements {
+            let Some(page) = self.pages.values_mut().find(|page| page.id == acknowledgement.page_id && page.status == PageStatus::Paging) else {
+                continue;
+            };
+            page.status = PageStatus::Acknowledged;
+            page.acknowledged_at = Some(now);
+            page.acknowledged_by = acknowledgement.by;
+            round.acknowledged.push(page.clone());
+        }
+
+        let timeout = chrono::Duration::minutes(self.settings.ack_timeout_minutes);
+        let timed_out: Vec<Uuid> = self.pages.values()
+            .filter(|page| page.status == PageStatus::Paging && now - page.paged_at >= timeout)
+            .map(|page| page.incident_id)
+            .collect();
+        for incident_id in timed_out {
+            let level = self.pages[&incident_id].escalation_level + 1;
+            let next = self.on_call(now, level).filter(|_| level <= self.settings.max_escalations);
+            let page = self.pages.get_mut(&incident_id).expect("timed out page exists");
+            match next {
+                Some(responder) => {
+                    page.responder = responder;
+                    page.escalation_level = level;
+                    page.paged_at = now;
+                    if let Err(e) = self.provider.trigger(page).await {
+                        round.errors.push(e);
+                    }
+                    round.escalated.push(page.clone());
+                }
+                None => {
+                    page.status = PageStatus::Unanswered;
+                    round.unanswered.push(page.clone());
+                }
+            }
+        }
+
+        round
+    }
+}
+
+/// In-process stand-in for a paging service
+///
+/// Each page is acknowledged after a random delay or missed altogether, as
+/// a responder might. Seeded runs page the same way every time.
+#[derive(Debug)]
+pub struct MockPager {
+    rng: StdRng,
+    ack_probability: f64,
+    ack_minutes: (i64, i64),
+    /// When each outstanding page will be acknowledged; `None` if it never will
+    due: HashMap<Uuid, Option<chrono::DateTime<chrono::Utc>>>,
+}
+
+impl MockPager {
+    pub fn new(seed: Option<u64>, ack_probability: f64, ack_minutes: (i64, i64)) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self { rng, ack_probability, ack_minutes, due: HashMap::new() }
+    }
+}
+
+#[async_trait]
+impl PagingProvider for MockPager {
+    fn name(&self) -> &'static str {
+        "mock pager"
+    }
+
+    async fn trigger(&mut self, page: &Page) -> Result<(), PagingError> {
+        let due = self.rng.gen_bool(self.ack_probability)
+            .then(|| page.paged_at + chrono::Duration::minutes(self.rng.gen_range(self.ack_minutes.0..=self.ack_minutes.1)));
+        self.due.insert(page.id, due);
+        Ok(())
+    }
+
+    async fn resolve(&mut self, page: &Page) -> Result<(), PagingError> {
+        self.due.remove(&page.id);
+        Ok(())
+    }
+
+    async fn acknowledgements(&mut self, pages: &[&Page], now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Acknowledgement>, PagingError> {
+        Ok(pages.iter()
+            .filter(|page| self.due.get(&page.id).copied().flatten().is_some_and(|due| due <= now))
+            .map(|page| Acknowledgement { page_id: page.id, by: None })
+            .collect())
+    }
+}
+
+#[cfg(feature = "paging")]
+fn http_client() -> Result<reqwest::Client, PagingError> {
+    reqwest::Client::builder()
+        .user_agent(concat!("aivertco/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(REQUEST_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(PagingError::Http)
+}
+
+#[cfg(feature = "paging")]
+fn read_secret(env: &str) -> Result<String, PagingError> {
+    std::env::var(env).map_err(|_| PagingError::MissingKey(env.to_string()))
+}
+
+/// Fail on non-success responses, keeping the body for the error
+#[cfg(feature = "paging")]
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, PagingError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        Err(PagingError::Api { status: status.as_u16(), body })
+    }
+}
+
+/// Service on a PagerDuty account
+///
+/// Pages go through the Events API with the page id as dedup key, so an
+/// escalation updates the same PagerDuty incident. Acknowledgements are
+/// read from the REST API.
+#[cfg(feature = "paging")]
+#[derive(Debug)]
+pub struct PagerDuty {
+    client: reqwest::Client,
+    events_url: String,
+    api_url: String,
+    routing_key: String,
+    token: String,
+}
+
+#[cfg(feature = "paging")]
+impl PagerDuty {
+    async fn enqueue(&self, body: serde_json::Value) -> Result<(), PagingError> {
+        let response = self.client.post(format!("{}/v2/enqueue", self.events_url))
+            .json(&body)
+            .send().await.map_err(PagingError::Http)?;
+        check(response).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "paging")]
+#[async_trait]
+impl PagingProvider for PagerDuty {
+    fn name(&self) -> &'static str {
+        "PagerDuty"
+    }
+
+    async fn trigger(&mut self, page: &Page) -> Result<(), PagingError> {
+        self.enqueue(serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": page.id.to_string(),
+            "payload": {
+                "summary": format!("[{:?}] {}", page.severity, page.summary),
+                "source": "aivertco",
+                "severity": if page.severity == Severity::Sev1 { "critical" } else { "error" },
+                "custom_details": {
+                    "incident_id": page.incident_id,
+                    "responder": page.responder,
+                    "escalation_level": page.escalation_level,
+                },
+            },
+        })).await
+    }
+
+    async fn resolve(&mut self, page: &Page) -> Result<(), PagingError> {
+        self.enqueue(serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "resolve",
+            "dedup_key": page.id.to_string(),
+        })).await
+    }
+
+    async fn acknowledgements(&mut self, pages: &[&Page], _now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Acknowledgement>, PagingError> {
+        let mut acknowledgements = Vec::new();
+        for page in pages {
+            let response = self.client.get(format!("{}/incidents", self.api_url))
+                .header("Authorization", format!("Token token={}", self.token))
+                .header("Accept", "application/vnd.pagerduty+json;version=2")
+                .query(&[("incident_key", page.id.to_string()), ("date_range", "all".to_string())])
+                .send().await.map_err(PagingError::Http)?;
+            let body: serde_json::Value = check(response).await?.json().await.map_err(PagingError::Http)?;
+            let Some(incident) = body["incidents"].as_array().and_then(|incidents| incidents.first()) else {
+                continue;
+            };
+            if incident["status"] == "acknowledged" {
+                let by = incident["acknowledgements"][0]["acknowledger"]["summary"].as_str().map(str::to_string);
+                acknowledgements.push(Acknowledgement { page_id: page.id, by });
+            }
+        }
+        Ok(acknowledgements)
+    }
+}
+
+/// Alerts on an Opsgenie account, keyed by page id as the alert alias
+#[cfg(feature = "paging")]
+#[derive(Debug)]
+pub struct Opsgenie {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+#[cfg(feature = "paging")]
+#[async_trait]
+impl PagingProvider for Opsgenie {
+    fn name(&self) -> &'static str {
+        "Opsgenie"
+    }
+
+    async fn trigger(&mut self, page: &Page) -> Result<(), PagingError> {
+        // Opsgenie caps the alert message at 130 characters
+        let message: String = format!("[{:?}] {}", page.severity, page.summary).chars().take(130).collect();
+        let response = self.client.post(format!("{}/v2/alerts", self.api_url))
+            .header("Authorization", format!("GenieKey {}", self.api_key))
+            .json(&serde_json::json!({
+                "message": message,
+                "alias": page.id.to_string(),
+                "priority": if page.severity == Severity::Sev1 { "P1" } else { "P2" },
+                "tags": ["aivertco"],
+                "details": {
+                    "incident_id": page.incident_id.to_string(),
+                    "responder": page.responder.to_string(),
+                    "escalation_level": page.escalation_level.to_string(),
+                },
+            }))
+            .send().await.map_err(PagingError::Http)?;
+        check(response).await.map(|_| ())
+    }
+
+    async fn resolve(&mut self, page: &Page) -> Result<(), PagingError> {
+        let response = self.client.post(format!("{}/v2/alerts/{}/close", self.api_url, page.id))
+            .header("Authorization", format!("GenieKey {}", self.api_key))
+            .query(&[("identifierType", "alias")])
+            .json(&serde_json::json!({ "source": "aivertco" }))
+            .send().await.map_err(PagingError::Http)?;
+        check(response).await.map(|_| ())
+    }
+
+    async fn acknowledgements(&mut self, pages: &[&Page], _now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Acknowledgement>, PagingError> {
+        let mut acknowledgements = Vec::new();
+        for page in pages {
+            let response = self.client.get(format!("{}/v2/alerts/{}", self.api_url, page.id))
+                .header("Authorization", format!("GenieKey {}", self.api_key))
+                .query(&[("identifierType", "alias")])
+                .send().await.map_err(PagingError::Http)?;
+            let body: serde_json::Value = check(response).await?.json().await.map_err(PagingError::Http)?;
+            if body["data"]["acknowledged"] == true {
+                let by = body["data"]["report"]["acknowledgedBy"].as_str().map(str::to_string);
+                acknowledgements.push(Acknowledgement { page_id: page.id, by });
+            }
+        }
+        Ok(acknowledgements)
+    }
+}
+
+/// Paging errors
+#[derive(Debug, thiserror::Error)]
+pub enum PagingError {
+    #[cfg(not(feature = "paging"))]
+    #[error("PagerDuty and Opsgenie paging need a build with the paging feature")]
+    FeatureDisabled,
+
+    #[cfg(feature = "paging")]
+    #[error("Environment variable {0} with the paging service key is not set")]
+    MissingKey(String),
+
+    #[cfg(feature = "paging")]
+    #[error("Paging service request failed: {0}")]
+    Http(reqwest::Error),
+
+    #[cfg(feature = "paging")]
+    #[error("Paging service returned {status}: {body}")]
+    Api { status: u16, body: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn declared(severity: &str, at: chrono::DateTime<chrono::Utc>) -> EntityChange {
+        EntityChange {
+            event: EntityEvent::IncidentDeclared,
+            entity_id: Uuid::new_v4(),
+            occurred_at: at,
+            attributes: BTreeMap::new(),
+            entity: json!({ "title": format!("{} outage", severity), "severity": severity }),
+        }
+    }
+
+    fn pager(ack_probability: f64, responders: &[Uuid]) -> Pager {
+        let mut pager = Pager::with_provider(Box::new(MockPager::new(Some(7), ack_probability, (5, 5))), PagingSettings::default());
+        pager.set_rotation(responders.to_vec());
+        pager
+    }
+
+    #[tokio::test]
+    async fn test_severe_incidents_page_and_are_acknowledged() {
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-04T09:00:00Z".parse().unwrap();
+        let responders = [Uuid::new_v4(), Uuid::new_v4()];
+        let mut pager = pager(1.0, &responders);
+
+        let round = pager.run(&[declared("Sev1", start), declared("Sev3", start)], start).await;
+        assert_eq!(round.triggered.len(), 1);
+        assert_eq!(Some(round.triggered[0].responder), pager.on_call(start, 0));
+
+        let round = pager.run(&[], start + chrono::Duration::minutes(4)).await;
+        assert!(round.acknowledged.is_empty());
+        let round = pager.run(&[], start + chrono::Duration::minutes(5)).await;
+        assert_eq!(round.acknowledged.len(), 1);
+        assert_eq!(pager.open_pages(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_page_escalates_then_goes_unanswered() {
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-04T09:00:00Z".parse().unwrap();
+        let responders = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let mut pager = pager(0.0, &responders);
+        let incident = declared("Sev2", start);
+        pager.run(std::slice::from_ref(&incident), start).await;
+
+        let mut paged = vec![pager.on_call(start, 0).unwrap()];
+        for escalation in 1..=2 {
+            let round = pager.run(&[], start + chrono::Duration::minutes(15 * escalation)).await;
+            assert_eq!(round.escalated.len(), 1);
+            paged.push(round.escalated[0].responder);
+        }
+        paged.sort();
+        let mut expected = responders.to_vec();
+        expected.sort();
+        assert_eq!(paged, expected);
+
+        let round = pager.run(&[], start + chrono::Duration::minutes(45)).await;
+        assert_eq!(round.unanswered.len(), 1);
+        assert_eq!(pager.open_pages(), 0);
+
+        let resolved = EntityChange { event: EntityEvent::IncidentResolved, ..incident };
+        pager.run(&[resolved], start + chrono::Duration::minutes(50)).await;
+        assert!(pager.pages.is_empty());
+    }
+}
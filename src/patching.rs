@@ -0,0 +1,278 @@
+//! Patching - Pending Patches and Maintenance Windows
+//!
+//! This module implements how DevOps keeps its servers patched:
+//! - Patches pending per server: routine vendor releases, and security fixes
+//!   for the vulnerabilities InfoSec's scans found
+//! - Maintenance windows: servers with pending patches, patched together
+//!   once Ops approves the window as a change request. Routine patches wait
+//!   for the weekly window; a critical fix gets an emergency window at once
+//! - Servers go into maintenance for the length of the window, and a window
+//!   takes at most a set number of them so the rest keep serving
+//!
+//! Critical fixes left unpatched count against InfoSec's security posture.
+
+use crate::config::PatchingSettings;
+use crate::departments::infosec::Severity;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Patch ids remembered per server once applied; the oldest go first
+const MAX_APPLIED: usize = 200;
+
+/// Finished windows kept; the oldest go first
+const MAX_WINDOWS: usize = 100;
+
+/// A vulnerability InfoSec found, which a security patch fixes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Advisory {
+    pub vulnerability: String,
+    pub title: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Patch {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    /// Vulnerability the patch fixes, for security patches
+    pub vulnerability: Option<String>,
+    pub released_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Patch {
+    pub fn is_critical(&self) -> bool {
+        self.severity == Severity::Critical
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowStatus {
+    /// Waiting for Ops to approve the change request
+    AwaitingApproval,
+    Scheduled,
+    /// Servers are in maintenance and being patched
+    InProgress,
+    Completed,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: Uuid,
+    pub servers: Vec<String>,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    /// Opened at once for a critical fix rather than at the weekly window
+    pub emergency: bool,
+    pub status: WindowStatus,
+    /// Patches applied once it completed
+    pub patched: usize,
+}
+
+/// A window starting or finishing
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowEvent {
+    Started { window_id: Uuid, servers: Vec<String> },
+    /// `fixed` lists the vulnerabilities no server has a patch pending for
+    /// any more
+    Finished { window_id: Uuid, servers: Vec<String>, patched: usize, fixed: Vec<String> },
+}
+
+/// Patches pending on each server a DevOps agent runs, and its windows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchLedger {
+    /// Oldest first, by hostname
+    pending: BTreeMap<String, Vec<Patch>>,
+    /// Ids of the patches applied, by hostname
+    applied: BTreeMap<String, Vec<String>>,
+    windows: Vec<MaintenanceWindow>,
+}
+
+impl PatchLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pending(&self, server: &str) -> &[Patch] {
+        self.pending.get(server).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    pub fn windows(&self) -> &[MaintenanceWindow] {
+        &self.windows
+    }
+
+    /// Critical patches pending on any server, each counted once
+    pub fn unpatched_critical(&self) -> BTreeSet<String> {
+        self.pending.values().flatten().filter(|patch| patch.is_critical()).map(|patch| patch.id.clone()).collect()
+    }
+
+    /// Release patches onto the servers: a routine one now and then, and a
+    /// security patch for every advisory a server is not yet patched for.
+    /// Servers that are gone are forgotten. Returns how many were released.
+    pub fn release(&mut self, servers: &[String], advisories: &[Advisory], settings: &PatchingSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> usize {
+        self.pending.retain(|server, _| servers.contains(server));
+        self.applied.retain(|server, _| servers.contains(server));
+        let mut released = 0;
+        for server in servers {
+            let applied = self.applied.get(server).cloned().unwrap_or_default();
+            let pending = self.pending.entry(server.clone()).or_default();
+            if rng.gen_bool(settings.release_probability.clamp(0.0, 1.0)) {
+                let severity = if rng.gen_bool(0.3) { Severity::Medium } else { Severity::Low };
+                pending.push(Patch {
+                    id: format!("PATCH-{:06}", rng.gen_range(0..1_000_000)),
+                    title: "Routine package updates".to_string(),
+                    severity,
+                    vulnerability: None,
+                    released_at: now,
+                });
+                released += 1;
+            }
+            for advisory in advisories.iter().filter(|advisory| !applied.contains(&advisory.vulnerability)) {
+                if push_fix(pending, advisory, now) {
+                    released += 1;
+                }
+            }
+        }
+        self.pending.retain(|_, patches| !patches.is_empty());
+        released
+    }
+
+    /// Release the fix for one advisory onto every server, even those that
+    /// applied it before, for a fix a re-scan found did not take; returns
+    /// how many servers got it
+    pub fn release_fix(&mut self, servers: &[String], advisory: &Advisory, now: chrono::DateTime<chrono::Utc>) -> usize {
+        servers.iter().filter(|server| push_fix(self.pending.entry(server.to_string()).or_default(), advisory, now)).count()
+    }
+
+    /// Open a window for the servers with pending patches, those with
+    /// critical ones first, unless one is already open or coming up
+    pub fn schedule(&mut self, settings: &PatchingSettings, now: chrono::DateTime<chrono::Utc>) -> Option<&MaintenanceWindow> {
+        if self.windows.iter().any(|window| matches!(window.status, WindowStatus::AwaitingApproval | WindowStatus::Scheduled | WindowStatus::InProgress)) {
+            return None;
+        }
+        let mut servers: Vec<(&String, bool)> = self.pending.iter()
+            .map(|(server, patches)| (server, patches.iter().any(Patch::is_critical)))
+            .collect();
+        servers.sort_by_key(|(_, critical)| !critical);
+        servers.truncate(settings.max_servers_per_window.max(1));
+        let emergency = servers.iter().any(|(_, critical)| *critical);
+        if servers.is_empty() {
+            return None;
+        }
+        let starts_at = if emergency { now } else { crate::scheduler::next_maintenance_window(now) };
+        self.windows.push(MaintenanceWindow {
+            id: Uuid::new_v4(),
+            servers: servers.into_iter().map(|(server, _)| server.clone()).collect(),
+            starts_at,
+            ends_at: starts_at + chrono::Duration::hours(i64::from(settings.window_hours)),
+            emergency,
+            status: WindowStatus::AwaitingApproval,
+            patched: 0,
+        });
+        if self.windows.len() > MAX_WINDOWS {
+            self.windows.remove(0);
+        }
+        self.windows.last()
+    }
+
+    /// Apply Ops' decision on a window; returns the window decided on
+    pub fn decide(&mut self, window_id: Uuid, approved: bool) -> Option<&MaintenanceWindow> {
+        let window = self.windows.iter_mut().find(|window| window.id == window_id && window.status == WindowStatus::AwaitingApproval)?;
+        window.status = if approved { WindowStatus::Scheduled } else { WindowStatus::Rejected };
+        Some(window)
+    }
+
+    /// Start the approved window once its time comes, and patch its servers
+    /// once it is over
+    pub fn advance(&mut self, now: chrono::DateTime<chrono::Utc>) -> Option<WindowEvent> {
+        let window = self.windows.iter_mut().find(|window| matches!(window.status, WindowStatus::Scheduled | WindowStatus::InProgress))?;
+        if window.status == WindowStatus::Scheduled {
+            if window.starts_at > now {
+                return None;
+            }
+            window.status = WindowStatus::InProgress;
+            return Some(WindowEvent::Started { window_id: window.id, servers: window.servers.clone() });
+        }
+        if window.ends_at > now {
+            return None;
+        }
+
+        let mut vulnerabilities = BTreeSet::new();
+        for server in &window.servers {
+            let patches = self.pending.remove(server).unwrap_or_default();
+            window.patched += patches.len();
+            vulnerabilities.extend(patches.iter().filter_map(|patch| patch.vulnerability.clone()));
+            let applied = self.applied.entry(server.clone()).or_default();
+            applied.extend(patches.into_iter().map(|patch| patch.id));
+            if applied.len() > MAX_APPLIED {
+                let excess = applied.len() - MAX_APPLIED;
+                applied.drain(..excess);
+            }
+        }
+        window.status = WindowStatus::Completed;
+        let fixed = vulnerabilities.into_iter()
+            .filter(|vulnerability| !self.pending.values().flatten().any(|patch| patch.vulnerability.as_ref() == Some(vulnerability)))
+            .collect();
+        Some(WindowEvent::Finished { window_id: window.id, servers: window.servers.clone(), patched: window.patched, fixed })
+    }
+}
+
+/// Queue the security patch for an advisory unless it is already pending
+fn push_fix(pending: &mut Vec<Patch>, advisory: &Advisory, now: chrono::DateTime<chrono::Utc>) -> bool {
+    if pending.iter().any(|patch| patch.id == advisory.vulnerability) {
+        return false;
+    }
+    pending.push(Patch {
+        id: advisory.vulnerability.clone(),
+        title: format!("Fix for {}", advisory.title),
+        severity: advisory.severity.clone(),
+        vulnerability: Some(advisory.vulnerability.clone()),
+        released_at: now,
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_critical_fix_gets_emergency_window_and_is_patched() {
+        let settings = PatchingSettings { release_probability: 0.0, ..PatchingSettings::default() };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let now: chrono::DateTime<chrono::Utc> = "2024-03-06T12:00:00Z".parse().unwrap();
+        let servers = vec!["web-1".to_string(), "web-2".to_string()];
+        let advisory = Advisory { vulnerability: "CVE-2024-0001".to_string(), title: "Remote code execution".to_string(), severity: Severity::Critical };
+        let mut ledger = PatchLedger::new();
+        assert_eq!(ledger.release(&servers, &[advisory.clone()], &settings, now, &mut rng), 2);
+        assert_eq!(ledger.release(&servers, &[advisory.clone()], &settings, now, &mut rng), 0);
+        assert_eq!(ledger.unpatched_critical().len(), 1);
+
+        let window = ledger.schedule(&settings, now).unwrap();
+        assert!(window.emergency);
+        assert_eq!(window.starts_at, now);
+        let window_id = window.id;
+        assert!(ledger.schedule(&settings, now).is_none());
+        assert!(ledger.advance(now).is_none());
+
+        ledger.decide(window_id, true);
+        assert!(matches!(ledger.advance(now), Some(WindowEvent::Started { .. })));
+        let done = now + chrono::Duration::hours(i64::from(settings.window_hours));
+        let Some(WindowEvent::Finished { patched, fixed, .. }) = ledger.advance(done) else {
+            panic!("expected the window to finish");
+        };
+        assert_eq!((patched, fixed), (2, vec!["CVE-2024-0001".to_string()]));
+        assert!(ledger.unpatched_critical().is_empty());
+
+        // Applied fixes are not released again
+        assert_eq!(ledger.release(&servers, &[advisory], &settings, done, &mut rng), 0);
+    }
+}
@@ -0,0 +1,480 @@
+//! Typed Message Payloads
+//!
+//! This module implements structured payloads for messages that carry data:
+//! - A `MessagePayload` enum with one variant per structured message type
+//! - Payloads travel as JSON under the `payload` metadata key, next to the
+//!   cross-cutting keys (correlation, thread, channel) that stay flat
+//! - Messages from older snapshots are still read from their flat metadata,
+//!   but a malformed or missing value is now an error instead of a default
+
+use crate::autoscale::ScalingPolicy;
+use crate::capacity::CapacityForecast;
+use crate::catalog::Tenancy;
+use crate::certs::CertificateEvent;
+use crate::config::{BackupSettings, PatchingSettings, SecurityPolicySettings, ScanSettings, TrafficSettings};
+use crate::communication::{Message, MessagePriority};
+use crate::ddos::DdosEvent;
+use crate::deptreport::{DepartmentReport, StatusUpdate};
+use crate::departments::devops::DeploymentStrategy;
+use crate::departments::ops::{CustomerTier, Priority, ServiceHealth, Severity};
+use crate::dlp::Exposure;
+use crate::firewall::FirewallReport;
+use crate::infra::InfraPlan;
+use crate::patching::Advisory;
+use crate::phishing::PhishingTarget;
+use crate::pipeline::{PipelineEvent, PipelineStatus, Stage};
+use crate::secpolicy::PolicyEvidence;
+use crate::threatintel::ThreatAdvisory;
+use crate::traffic::ServiceDemand;
+use crate::wan::LatencySample;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Metadata key holding the JSON-encoded payload
+pub const PAYLOAD_KEY: &str = "payload";
+
+/// Structured content of a message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MessagePayload {
+    /// A department is assigned work on a project
+    ProjectAssignment {
+        project_id: Uuid,
+    },
+    /// Ops should open an incident
+    IncidentDeclared {
+        title: String,
+        severity: Severity,
+        affected_services: Vec<String>,
+        #[serde(default)]
+        affected_customers: Vec<String>,
+    },
+    /// Ops should open a support ticket
+    TicketRequest {
+        title: String,
+        priority: Priority,
+        customer_id: Option<String>,
+        customer_tier: CustomerTier,
+        /// Routing tags, e.g. `billing`
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// DevOps should deploy a project
+    DeploymentRequest {
+        project_id: Uuid,
+        environment: String,
+        /// Commit to deploy; the current build when unset
+        #[serde(default)]
+        commit: Option<String>,
+        #[serde(default)]
+        strategy: DeploymentStrategy,
+    },
+    /// DevOps is alerted to a degraded deployment
+    InfrastructureAlert {
+        service: String,
+        deployment: String,
+        tenancy: Tenancy,
+        affected_customers: Vec<String>,
+    },
+    /// Networking should send a share of an environment's traffic to a new
+    /// release
+    TrafficShift {
+        deployment_id: Uuid,
+        environment: String,
+        percent: u8,
+    },
+    /// A CI/CD pipeline reached a stage, passed or failed
+    PipelineUpdate {
+        pipeline_id: Uuid,
+        project_id: Uuid,
+        commit: String,
+        stage: Stage,
+        status: PipelineStatus,
+        detail: String,
+    },
+    /// An alert rule fired on a server or cluster DevOps runs
+    AlertFired {
+        alert_id: Uuid,
+        rule: String,
+        target: String,
+        severity: Severity,
+        value: f64,
+    },
+    /// A fired alert is no longer breached
+    AlertResolved {
+        alert_id: Uuid,
+        rule: String,
+        target: String,
+    },
+    /// DevOps should back up its systems, prune old backups and run a
+    /// restore drill when one is due
+    BackupRequest {
+        at: chrono::DateTime<chrono::Utc>,
+        settings: BackupSettings,
+    },
+    /// DevOps should scale its server fleets within these limits and costs
+    ScaleRequest {
+        policy: ScalingPolicy,
+    },
+    /// DevOps should bring the infrastructure in line with a plan
+    InfraPlanRequest {
+        plan: InfraPlan,
+    },
+    /// Ops should decide on a change request
+    ChangeReview {
+        /// What the requester is asking about, e.g. its infrastructure run
+        reference: Uuid,
+        title: String,
+        description: String,
+        /// What the change puts at risk
+        impact: String,
+        rollback_plan: String,
+        requested_by: Uuid,
+    },
+    /// Ops decided on a change request
+    ChangeDecision {
+        reference: Uuid,
+        requested_by: Uuid,
+        approved: bool,
+        reason: String,
+    },
+    /// Networking should point a load balancer at these servers, or remove
+    /// it when there are none
+    LoadBalancerUpdate {
+        name: String,
+        backends: Vec<String>,
+    },
+    /// DevOps should take in new patches, with security fixes for these
+    /// advisories, and schedule a maintenance window for them
+    PatchRequest {
+        at: chrono::DateTime<chrono::Utc>,
+        settings: PatchingSettings,
+        advisories: Vec<Advisory>,
+    },
+    /// Critical fixes DevOps has yet to apply, for InfoSec's posture score
+    PatchStatus {
+        unpatched_critical: u32,
+    },
+    /// Every server is patched for these vulnerabilities
+    PatchesApplied {
+        vulnerabilities: Vec<String>,
+    },
+    /// InfoSec should sync its asset inventory to these assets and scan
+    /// every one of them
+    ScanRequest {
+        at: chrono::DateTime<chrono::Utc>,
+        settings: ScanSettings,
+        servers: Vec<String>,
+        network_services: Vec<String>,
+        applications: Vec<String>,
+    },
+    /// DevOps should patch a serious finding before it is due
+    RemediationTask {
+        task_id: Uuid,
+        advisory: Advisory,
+        asset: String,
+        due_at: chrono::DateTime<chrono::Utc>,
+    },
+    /// Evidence of the company's actual state for InfoSec to audit its
+    /// security policies against
+    PolicyCheck {
+        at: chrono::DateTime<chrono::Utc>,
+        settings: SecurityPolicySettings,
+        evidence: PolicyEvidence,
+    },
+    /// How the agents phished by a campaign handled its lure
+    PhishingResults {
+        campaign_id: Uuid,
+        at: chrono::DateTime<chrono::Utc>,
+        lure: String,
+        targets: Vec<PhishingTarget>,
+    },
+    /// InfoSec booked security-awareness training for these agents
+    TrainingScheduled {
+        agents: Vec<Uuid>,
+        at: chrono::DateTime<chrono::Utc>,
+    },
+    /// A threat from the intelligence feeds was sighted in the company's
+    /// traffic
+    ThreatAdvisory {
+        advisory: ThreatAdvisory,
+    },
+    /// Classified data DLP monitoring saw reach lower-security segments
+    DataExposure {
+        exposures: Vec<Exposure>,
+    },
+    /// Analysis of Networking's firewall rules
+    FirewallFindings {
+        at: chrono::DateTime<chrono::Utc>,
+        report: FirewallReport,
+    },
+    /// Traffic the catalog services draw, for Networking to simulate
+    TrafficDemand {
+        demand: Vec<ServiceDemand>,
+        settings: TrafficSettings,
+    },
+    /// A TLS certificate is about to expire, failed to renew or lapsed
+    CertificateAlert {
+        event: CertificateEvent,
+    },
+    /// A day of round trips to the multi-region services, for Ops' SLAs
+    ServiceLatency {
+        at: chrono::DateTime<chrono::Utc>,
+        latency: BTreeMap<String, LatencySample>,
+    },
+    /// How the services fared over the last SLA check interval, for Ops to
+    /// measure against their SLAs
+    SlaCheck {
+        at: chrono::DateTime<chrono::Utc>,
+        minutes: f64,
+        health: Vec<ServiceHealth>,
+    },
+    /// Server capacity is forecast to run out; DevOps should add some ahead
+    /// of it
+    CapacityRequest {
+        forecast: CapacityForecast,
+    },
+    /// Finance should budget for capacity forecast to run out
+    BudgetRequest {
+        forecast: CapacityForecast,
+        /// Added monthly cost, when it can be estimated
+        monthly_cost: Option<f64>,
+    },
+    /// Ops' runbook did not fix an incident; DevOps should look into it
+    RunbookEscalation {
+        incident_id: Uuid,
+        title: String,
+        affected_services: Vec<String>,
+        runbook: String,
+        /// Actions tried, in order
+        steps_tried: Vec<String>,
+    },
+    /// A DDoS attack started, was mitigated further or ended
+    DdosAlert {
+        event: DdosEvent,
+    },
+    /// An agent's or a team's status for its manager
+    StatusUpdate {
+        update: StatusUpdate,
+    },
+    /// A department head's report for the CEO
+    DepartmentReport {
+        report: DepartmentReport,
+    },
+}
+
+impl MessagePayload {
+    /// Message type that carries this payload
+    pub fn message_type(&self) -> &'static str {
+        match self {
+            MessagePayload::ProjectAssignment { .. } => "project_assignment",
+            MessagePayload::IncidentDeclared { .. } => "declare_incident",
+            MessagePayload::TicketRequest { .. } => "create_ticket",
+            MessagePayload::DeploymentRequest { .. } => "deploy_request",
+            MessagePayload::InfrastructureAlert { .. } => "infrastructure_alert",
+            MessagePayload::TrafficShift { .. } => "shift_traffic",
+            MessagePayload::PipelineUpdate { .. } => "pipeline_event",
+            MessagePayload::AlertFired { .. } => "alert_fired",
+            MessagePayload::AlertResolved { .. } => "alert_resolved",
+            MessagePayload::BackupRequest { .. } => "backup_request",
+            MessagePayload::ScaleRequest { .. } => "scale_request",
+            MessagePayload::InfraPlanRequest { .. } => "infra_plan",
+            MessagePayload::ChangeReview { .. } => "change_request",
+            MessagePayload::ChangeDecision { .. } => "change_decision",
+            MessagePayload::LoadBalancerUpdate { .. } => "configure_load_balancer",
+            MessagePayload::PatchRequest { .. } => "patch_request",
+            MessagePayload::PatchStatus { .. } => "patch_status",
+            MessagePayload::PatchesApplied { .. } => "patches_applied",
+            MessagePayload::ScanRequest { .. } => "scan_request",
+            MessagePayload::RemediationTask { .. } => "remediation_task",
+            MessagePayload::PolicyCheck { .. } => "policy_check",
+            MessagePayload::PhishingResults { .. } => "phishing_results",
+            MessagePayload::TrainingScheduled { .. } => "training_scheduled",
+            MessagePayload::ThreatAdvisory { .. } => "threat_advisory",
+            MessagePayload::DataExposure { .. } => "data_exposure",
+            MessagePayload::FirewallFindings { .. } => "firewall_findings",
+            MessagePayload::TrafficDemand { .. } => "traffic_demand",
+            MessagePayload::CertificateAlert { .. } => "certificate_alert",
+            MessagePayload::ServiceLatency { .. } => "service_latency",
+            MessagePayload::SlaCheck { .. } => "sla_check",
+            MessagePayload::CapacityRequest { .. } => "capacity_request",
+            MessagePayload::BudgetRequest { .. } => "budget_request",
+            MessagePayload::RunbookEscalation { .. } => "runbook_escalation",
+            MessagePayload::DdosAlert { .. } => "ddos_alert",
+            MessagePayload::StatusUpdate { .. } => "status_update",
+            MessagePayload::DepartmentReport { .. } => "department_report",
+        }
+    }
+
+    /// Payload announcing a pipeline event
+    pub fn pipeline_update(event: &PipelineEvent) -> Self {
+        MessagePayload::PipelineUpdate {
+            pipeline_id: event.pipeline_id,
+            project_id: event.project_id,
+            commit: event.commit.clone(),
+            stage: event.stage,
+            status: event.status,
+            detail: event.detail.clone(),
+        }
+    }
+
+    /// Build a message carrying this payload
+    pub fn into_message(self, from_agent: Uuid, to_agent: Uuid, content: String, priority: MessagePriority) -> Message {
+        let mut message = Message {
+            id: Uuid::new_v4(),
+            from_agent,
+            to_agent,
+            message_type: self.message_type().to_string(),
+            content,
+            priority,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        };
+        self.attach(&mut message);
+        message
+    }
+
+    /// Store the payload on a message and set its type to match
+    pub fn attach(&self, message: &mut Message) {
+        message.message_type = self.message_type().to_string();
+        let json = serde_json::to_string(self).expect("payloads always serialize");
+        message.metadata.insert(PAYLOAD_KEY.to_string(), json);
+    }
+
+    /// Read the payload of a message
+    ///
+    /// Returns `Ok(None)` for message types without a structured payload.
+    pub fn from_message(message: &Message) -> Result<Option<Self>, PayloadError> {
+        if let Some(json) = message.metadata.get(PAYLOAD_KEY) {
+            let payload: MessagePayload = serde_json::from_str(json)
+                .map_err(|e| PayloadError::Malformed(e.to_string()))?;
+            if payload.message_type() != message.message_type {
+                return Err(PayloadError::TypeMismatch {
+                    message_type: message.message_type.clone(),
+                    payload: payload.message_type(),
+                });
+            }
+            return Ok(Some(payload));
+        }
+        Self::from_legacy_metadata(message)
+    }
+
+    /// Read a payload from the flat metadata used before typed payloads
+    fn from_legacy_metadata(message: &Message) -> Result<Option<Self>, PayloadError> {
+        let metadata = &message.metadata;
+        let payload = match message.message_type.as_str() {
+            "project_assignment" => MessagePayload::ProjectAssignment {
+                project_id: parse_uuid(metadata, "project_id")?,
+            },
+            "declare_incident" => MessagePayload::IncidentDeclared {
+                title: metadata.get("title").cloned().unwrap_or_else(|| "System Incident".to_string()),
+                severity: parse_variant(metadata, "severity")?.unwrap_or(Severity::Sev3),
+                affected_services: split_list(metadata.get("affected_services")).unwrap_or_else(|| vec!["unknown".to_string()]),
+                affected_customers: split_list(metadata.get("affected_customers")).unwrap_or_default(),
+            },
+            "create_ticket" => MessagePayload::TicketRequest {
+                title: metadata.get("title").cloned().unwrap_or_else(|| "Support Request".to_string()),
+                priority: parse_variant(metadata, "priority")?.unwrap_or(Priority::Normal),
+                customer_id: metadata.get("customer_id").cloned(),
+                customer_tier: parse_variant(metadata, "customer_tier")?.unwrap_or(CustomerTier::Standard),
+                tags: split_list(metadata.get("tags")).unwrap_or_default(),
+            },
+            "deploy_request" => MessagePayload::DeploymentRequest {
+                project_id: parse_uuid(metadata, "project_id")?,
+                environment: metadata.get("environment").cloned().unwrap_or_else(|| "staging".to_string()),
+                commit: metadata.get("commit").cloned(),
+                strategy: DeploymentStrategy::AllAtOnce,
+            },
+            "infrastructure_alert" => MessagePayload::InfrastructureAlert {
+                service: required(metadata, "service")?.clone(),
+                deployment: required(metadata, "deployment")?.clone(),
+                tenancy: match required(metadata, "tenancy")?.as_str() {
+                    "shared" => Tenancy::Shared,
+                    "dedicated" => Tenancy::Dedicated,
+                    other => return Err(PayloadError::InvalidField { field: "tenancy", value: other.to_string() }),
+                },
+                affected_customers: split_list(metadata.get("affected_customers")).unwrap_or_default(),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(payload))
+    }
+}
+
+fn required<'a>(metadata: &'a HashMap<String, String>, field: &'static str) -> Result<&'a String, PayloadError> {
+    metadata.get(field).ok_or(PayloadError::MissingField(field))
+}
+
+fn parse_uuid(metadata: &HashMap<String, String>, field: &'static str) -> Result<Uuid, PayloadError> {
+    let value = required(metadata, field)?;
+    Uuid::parse_str(value).map_err(|_| PayloadError::InvalidField { field, value: value.clone() })
+}
+
+/// Parse an optional enum variant written by its `Debug` name (e.g. "Sev2")
+fn parse_variant<T: DeserializeOwned>(metadata: &HashMap<String, String>, field: &'static str) -> Result<Option<T>, PayloadError> {
+    metadata.get(field)
+        .map(|value| serde_json::from_value(serde_json::Value::String(value.clone()))
+            .map_err(|_| PayloadError::InvalidField { field, value: value.clone() }))
+        .transpose()
+}
+
+fn split_list(value: Option<&String>) -> Option<Vec<String>> {
+    value.map(|list| list.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect())
+}
+
+/// Payload decoding errors
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadError {
+    #[error("Malformed payload: {0}")]
+    Malformed(String),
+
+    #[error("Payload for {payload} sent as {message_type} message")]
+    TypeMismatch { message_type: String, payload: &'static str },
+
+    #[error("Missing metadata field: {0}")]
+    MissingField(&'static str),
+
+    #[error("Invalid value '{value}' for metadata field {field}")]
+    InvalidField { field: &'static str, value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let payload = MessagePayload::IncidentDeclared {
+            title: "Degraded api-gateway-shared".to_string(),
+            severity: Severity::Sev2,
+            affected_services: vec!["api-gateway".to_string()],
+            affected_customers: vec!["cust-1".to_string(), "cust-2".to_string()],
+        };
+        let message = payload.clone().into_message(Uuid::new_v4(), Uuid::new_v4(), "Pool saturated".to_string(), MessagePriority::High);
+
+        assert_eq!(message.message_type, "declare_incident");
+        assert_eq!(MessagePayload::from_message(&message).unwrap(), Some(payload));
+    }
+
+    #[test]
+    fn test_legacy_metadata_is_strict() {
+        let mut message = MessagePayload::ProjectAssignment { project_id: Uuid::new_v4() }
+            .into_message(Uuid::new_v4(), Uuid::new_v4(), "Assigned".to_string(), MessagePriority::Normal);
+        message.metadata.clear();
+        message.message_type = "declare_incident".to_string();
+        message.metadata.insert("severity".to_string(), "Sev9".to_string());
+        assert!(matches!(MessagePayload::from_message(&message), Err(PayloadError::InvalidField { field: "severity", .. })));
+
+        message.metadata.insert("severity".to_string(), "Sev1".to_string());
+        let Some(MessagePayload::IncidentDeclared { severity, affected_services, .. }) = MessagePayload::from_message(&message).unwrap() else {
+            panic!("expected an incident payload");
+        };
+        assert_eq!(severity, Severity::Sev1);
+        assert_eq!(affected_services, vec!["unknown".to_string()]);
+
+        message.message_type = "status_update".to_string();
+        assert_eq!(MessagePayload::from_message(&message).unwrap(), None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Simulation Persistence - Snapshots, Restore and the Message Journal
+//!
+//! This module implements how a simulation outlives its process:
+//! - Capturing the complete state as a snapshot and writing it to disk
+//! - Restoring a simulation from a snapshot, including older formats
+//! - The daemon's snapshot rotation and summary reports
+//! - Opening the message journal and replaying a recorded one
+
+use super::*;
+
+impl CompanySimulation {
+    /// Restore a simulation from a snapshot file
+    pub(crate) async fn from_snapshot(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_snapshot_with_registry(path, AgentRegistry::builtin()).await
+    }
+
+    /// Restore a simulation from a snapshot file, its agents of registered
+    /// implementations through `registry` and the configured department scripts
+    async fn from_snapshot_with_registry(path: &Path, registry: AgentRegistry) -> Result<Self, Box<dyn std::error::Error>> {
+        let snapshot = SimulationSnapshot::load(path)?;
+        let registry = load_agent_scripts(registry, &snapshot.config.scripting)?;
+        let message_bus = Arc::new(MessageBus::new().await?);
+
+        // Older snapshots keyed agents by a separate id; key them by their own id
+        let rekeyed = snapshot.agents.iter().any(|(agent_id, agent)| *agent_id != agent.agent().id);
+
+        let mut agents = AgentRuntime::new();
+        for agent in snapshot.agents.into_values() {
+            agents.spawn(agent.into_agent(&registry)?);
+        }
+        let settings = &snapshot.config.vcs;
+        let repository = snapshot.repository
+            .unwrap_or_else(|| Repository::new(&settings.repository, &settings.default_branch, snapshot.taken_at));
+        let starting_cash = snapshot.config.economy.starting_cash;
+
+        let mut simulation = Self {
+            agents,
+            supervisor: snapshot.supervisor,
+            message_bus,
+            projects: snapshot.projects,
+            task_graph: snapshot.task_graph,
+            campaign_reports: snapshot.campaign_reports,
+            catalog: snapshot.catalog,
+            pending_messages: snapshot.pending_messages,
+            message_log: snapshot.message_log.into(),
+            threads: snapshot.threads,
+            router: snapshot.router,
+            intake: snapshot.intake,
+            wait_graph: snapshot.wait_graph,
+            kpi_history: snapshot.kpi_history,
+            entity_tracker: snapshot.entity_tracker,
+            webhooks: snapshot.webhooks,
+            repository,
+            release_notes: snapshot.release_notes,
+            scorecards: snapshot.scorecards,
+            quotas: snapshot.quotas,
+            risks: snapshot.risks,
+            org: snapshot.org,
+            executive: snapshot.executive,
+            staffing: snapshot.staffing,
+            economy: snapshot.economy.unwrap_or_else(|| Economy::new(starting_cash)),
+            knowledge: snapshot.knowledge,
+            pipelines: snapshot.pipelines,
+            promotions: snapshot.promotions,
+            alerts: snapshot.alerts,
+            red_team: snapshot.red_team,
+            iam: snapshot.iam,
+            phishing: snapshot.phishing,
+            threat_intel: snapshot.threat_intel,
+            dlp: snapshot.dlp,
+            ticket_queue: snapshot.ticket_queue,
+            capacity: snapshot.capacity,
+            status_page: snapshot.status_page,
+            shift_roster: snapshot.shift_roster,
+            agent_states: snapshot.agent_states,
+            memories: snapshot.memories,
+            manager_reports: snapshot.manager_reports,
+            progression: snapshot.progression,
+            policies: HashMap::new(),
+            registry,
+            step_count: snapshot.step_count,
+            scheduler: snapshot.scheduler.clone().unwrap_or_else(|| Scheduler::new(snapshot.taken_at)),
+            rng: Self::seeded_rng(snapshot.config.seed, snapshot.step_count),
+            config: snapshot.config,
+            daemon: None,
+            journal: None,
+            flow_log: None,
+            metrics: None,
+            #[cfg(feature = "api")]
+            api: None,
+            paused: false,
+            pending_steps: 0,
+            due_daily_tasks: Vec::new(),
+            console: None,
+            report_templates: None,
+            #[cfg(feature = "sqlite")]
+            history: None,
+            #[cfg(feature = "sqlite")]
+            delivered_tasks: Vec::new(),
+            pager: None,
+            email: None,
+            #[cfg(feature = "issue-sync")]
+            issue_sync: None,
+        };
+
+        // Snapshots from before the scheduler existed start a fresh schedule
+        if simulation.scheduler.is_empty() {
+            simulation.schedule_initial_events();
+        } else if rekeyed {
+            simulation.scheduler.cancel(|kind| matches!(kind, EventKind::AgentTurn(_)));
+            simulation.schedule_agent_turns();
+        }
+        // Snapshots from before operations reviews existed
+        if !simulation.scheduler.contains(&EventKind::OperationsReview) {
+            let review_at = scheduler::next_operations_review(simulation.scheduler.now());
+            simulation.scheduler.schedule_at(review_at, EventKind::OperationsReview);
+        }
+        if !simulation.scheduler.contains(&EventKind::RiskReview) {
+            let review_at = scheduler::next_risk_review(simulation.scheduler.now());
+            simulation.scheduler.schedule_at(review_at, EventKind::RiskReview);
+        }
+        if !simulation.scheduler.contains(&EventKind::ExecutiveReview) {
+            let review_at = scheduler::next_executive_review(simulation.scheduler.now());
+            simulation.scheduler.schedule_at(review_at, EventKind::ExecutiveReview);
+        }
+        if !simulation.scheduler.contains(&EventKind::EndOfDay) {
+            let midnight = scheduler::next_end_of_day(simulation.scheduler.now());
+            simulation.scheduler.schedule_at(midnight, EventKind::EndOfDay);
+        }
+        simulation.org.rebuild(simulation.agents.infos());
+        simulation.load_alert_routing();
+        simulation.connect_issue_tracker()?;
+        simulation.connect_pager()?;
+        simulation.connect_email()?;
+        simulation.assign_policies()?;
+
+        println!("♻️ Resumed simulation from {} at step {} ({} agents, {} pending messages)",
+               path.display(), simulation.step_count, simulation.agents.len(), simulation.pending_messages.len());
+        Ok(simulation)
+    }
+
+    /// Capture the complete simulation state
+    pub(crate) async fn snapshot(&self) -> Result<SimulationSnapshot, Box<dyn std::error::Error>> {
+        Ok(SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now(),
+            step_count: self.step_count,
+            config: self.config.clone(),
+            agents: self.agents.snapshot().await?,
+            projects: self.projects.clone(),
+            task_graph: self.task_graph.clone(),
+            pending_messages: self.pending_messages.clone(),
+            campaign_reports: self.campaign_reports.clone(),
+            catalog: self.catalog.clone(),
+            message_log: self.message_log.iter().cloned().collect(),
+            router: self.router.clone(),
+            intake: self.intake.clone(),
+            wait_graph: self.wait_graph.clone(),
+            kpi_history: self.kpi_history.clone(),
+            entity_tracker: self.entity_tracker.clone(),
+            webhooks: self.webhooks.clone(),
+            scheduler: Some(self.scheduler.clone()),
+            repository: Some(self.repository.clone()),
+            supervisor: self.supervisor.clone(),
+            release_notes: self.release_notes.clone(),
+            scorecards: self.scorecards.clone(),
+            quotas: self.quotas.clone(),
+            risks: self.risks.clone(),
+            progression: self.progression.clone(),
+            org: self.org.clone(),
+            executive: self.executive.clone(),
+            staffing: self.staffing.clone(),
+            economy: Some(self.economy.clone()),
+            knowledge: self.knowledge.clone(),
+            pipelines: self.pipelines.clone(),
+            promotions: self.promotions.clone(),
+            alerts: self.alerts.clone(),
+            red_team: self.red_team.clone(),
+            iam: self.iam.clone(),
+            phishing: self.phishing.clone(),
+            threat_intel: self.threat_intel.clone(),
+            dlp: self.dlp.clone(),
+            ticket_queue: self.ticket_queue.clone(),
+            capacity: self.capacity.clone(),
+            status_page: self.status_page.clone(),
+            shift_roster: self.shift_roster.clone(),
+            agent_states: self.agent_states.clone(),
+            memories: self.memories.clone(),
+            manager_reports: self.manager_reports.clone(),
+            threads: self.threads.clone(),
+        })
+    }
+
+    /// Write a snapshot to the configured path, if any
+    pub(crate) async fn save_snapshot(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.config.snapshot_path {
+            self.snapshot().await?.save(path)?;
+        }
+        Ok(())
+    }
+
+    /// Rotate snapshots and deliver summary reports when running as a daemon
+    pub(crate) async fn run_daemon_tasks(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(daemon) = &self.daemon else {
+            return Ok(());
+        };
+        let snapshot_due = daemon.snapshot_due(self.step_count);
+        let report_due = daemon.report_due(chrono::Utc::now());
+        if !report_due && daemon.report_post_due(chrono::Utc::now()) {
+            let daemon = self.daemon.as_mut().expect("daemon checked above");
+            if let Err(e) = daemon.post_pending_report(chrono::Utc::now()).await {
+                eprintln!("⚠️ {}", e);
+            }
+        }
+        if !snapshot_due && !report_due {
+            return Ok(());
+        }
+
+        let snapshot = self.snapshot().await?;
+        if snapshot_due && self.journal.is_some() {
+            // The journal header embeds the config, so it starts afresh with each snapshot
+            let roster = self.roster();
+            let journal = self.journal.as_mut().expect("journal checked above");
+            let rotated = journal.rotate(&self.config, roster, self.step_count, self.scheduler.now(), self.config.daemon.keep_snapshots)?;
+            println!("📓 Journal rotated to {}", rotated.display());
+        }
+        let daemon = self.daemon.as_mut().expect("daemon checked above");
+        if snapshot_due {
+            daemon.rotate_snapshot(&snapshot)?;
+        }
+        if report_due {
+            // A failed post is retried with backoff rather than stopping the daemon
+            if let Err(e) = daemon.deliver_report(&snapshot, self.email.as_mut()).await {
+                eprintln!("⚠️ {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start recording dispatched messages if a journal is configured
+    pub(crate) fn open_journal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = self.config.journal.path.clone() {
+            self.journal = Some(JournalWriter::open(&path, &self.config, self.roster(), self.scheduler.now())?);
+            println!("📼 Recording messages to {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Current agents, as recorded in a journal header
+    fn roster(&self) -> Vec<RosterEntry> {
+        let mut roster: Vec<RosterEntry> = self.agents.infos()
+            .map(|agent| RosterEntry { id: agent.id, name: agent.name.clone(), department: agent.department.as_str().to_string() })
+            .collect();
+        roster.sort_by(|a, b| (&a.department, &a.name).cmp(&(&b.department, &b.name)));
+        roster
+    }
+
+    /// Feed a journal's messages into this simulation, step by step
+    ///
+    /// Only recorded traffic drives the agents: no scheduled events or agent
+    /// turns run. What agents send in response is compared with the journal
+    /// rather than dispatched, so one divergence does not cascade.
+    pub(crate) async fn replay(&mut self, journal: Journal) -> Result<ReplayReport, Box<dyn std::error::Error>> {
+        let mut replay = Replay::new(journal, &self.roster())?;
+        let steps = replay.steps();
+        if let Some(started_at) = steps.first().and_then(|&step| replay.step_time(step)) {
+            self.scheduler = Scheduler::new(started_at);
+        }
+
+        for step in steps {
+            self.step_count = step;
+            if let Some(at) = replay.step_time(step) {
+                self.scheduler.advance_to(at);
+            }
+
+            self.deliver_messages().await?;
+            let mut sent = self.agents.drain_outboxes().await?;
+            sent.append(&mut self.pending_messages);
+            replay.compare(step, &sent);
+
+            self.pending_messages = replay.messages(step);
+            self.flush_pending_messages().await?;
+        }
+
+        Ok(replay.report)
+    }
+}
@@ -0,0 +1,116 @@
+//! Agent Personalities - Traits That Shape How Agents Work
+//!
+//! This module implements the personality traits agents bring to their work,
+//! each from 0.0 to 1.0 with 0.5 as the neutral middle:
+//! - Risk tolerance: how readily the agent takes on routine department work
+//!   on a turn
+//! - Thoroughness: how often it runs its daily tasks, and how long and how
+//!   evenly its incident work takes; thorough agents are slower but steadier
+//! - Communication: how many unprompted messages it sends to colleagues
+//! - Response latency: how often a slow responder leaves its inbox for a
+//!   later delivery round
+//!
+//! Traits come from `[personalities]` by agent, department or default, so
+//! runs with different team compositions behave differently. A neutral
+//! personality keeps an agent's chances as they were.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Chance a fully slow responder leaves its messages queued for a round
+const MAX_DEFER_CHANCE: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Personality {
+    pub risk_tolerance: f64,
+    pub thoroughness: f64,
+    pub communication: f64,
+    pub response_latency: f64,
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        Self {
+            risk_tolerance: 0.5,
+            thoroughness: 0.5,
+            communication: 0.5,
+            response_latency: 0.5,
+        }
+    }
+}
+
+impl Personality {
+    /// Traits by name, for validation
+    pub fn traits(&self) -> [(&'static str, f64); 4] {
+        [
+            ("risk_tolerance", self.risk_tolerance),
+            ("thoroughness", self.thoroughness),
+            ("communication", self.communication),
+            ("response_latency", self.response_latency),
+        ]
+    }
+
+    /// Chance of routine work on a turn, from the department's chance;
+    /// half as likely at no risk tolerance, half again as likely at full
+    pub fn activity_chance(&self, chance: f32) -> f32 {
+        (chance * (0.5 + self.risk_tolerance) as f32).clamp(0.0, 1.0)
+    }
+
+    /// Chance of running daily tasks on a turn, scaled by thoroughness
+    pub fn daily_tasks_chance(&self, chance: f32) -> f32 {
+        (chance * (0.5 + self.thoroughness) as f32).clamp(0.0, 1.0)
+    }
+
+    /// Factor on how long a piece of work takes: around 0.5 for careless
+    /// agents and 1.5 for thorough ones, spread wider the less thorough they
+    /// are
+    pub fn duration_factor(&self, rng: &mut impl Rng) -> f64 {
+        let mean = 0.5 + self.thoroughness;
+        let spread = 0.5 * (1.0 - self.thoroughness);
+        (mean * (1.0 + rng.gen_range(-spread..=spread))).max(0.1)
+    }
+
+    /// Factor on how many unprompted messages the agent sends
+    pub fn message_volume(&self) -> f64 {
+        0.5 + self.communication
+    }
+
+    /// Whether a slow responder leaves its messages for a later round;
+    /// agents at or below neutral latency answer right away
+    pub fn defers(&self, rng: &mut impl Rng) -> bool {
+        let chance = ((self.response_latency - 0.5) * 2.0 * MAX_DEFER_CHANCE).clamp(0.0, MAX_DEFER_CHANCE);
+        chance > 0.0 && rng.gen_bool(chance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_traits_shape_chances_durations_and_responses() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let neutral = Personality::default();
+        assert_eq!(neutral.activity_chance(0.4), 0.4);
+        assert_eq!(neutral.daily_tasks_chance(0.1), 0.1);
+        assert_eq!(neutral.message_volume(), 1.0);
+        assert!((0..100).all(|_| !neutral.defers(&mut rng)));
+
+        let bold = Personality { risk_tolerance: 1.0, communication: 0.0, ..Personality::default() };
+        assert_eq!(bold.activity_chance(0.5), 0.75);
+        assert_eq!(bold.activity_chance(0.8), 1.0);
+        assert_eq!(bold.message_volume(), 0.5);
+
+        // Thorough work takes longer but always about as long
+        let thorough = Personality { thoroughness: 1.0, ..Personality::default() };
+        assert!((0..100).all(|_| thorough.duration_factor(&mut rng) == 1.5));
+        let careless = Personality { thoroughness: 0.0, response_latency: 1.0, ..Personality::default() };
+        let factors: Vec<f64> = (0..100).map(|_| careless.duration_factor(&mut rng)).collect();
+        assert!(factors.iter().all(|factor| (0.25..=0.75).contains(factor)));
+        assert!(factors.iter().any(|factor| *factor < 0.4) && factors.iter().any(|factor| *factor > 0.6));
+        let deferred = (0..1000).filter(|_| careless.defers(&mut rng)).count();
+        assert!((650..850).contains(&deferred));
+    }
+}
@@ -0,0 +1,300 @@
+//! Phishing Simulation - Campaigns, Awareness and Training
+//!
+//! This module implements how well the company's people resist phishing:
+//! - An awareness stat per agent, from 0 to 1, starting higher for InfoSec
+//! - Simulated phishing campaigns against agents drawn across every
+//!   department in turn; an agent clicks with the base click probability
+//!   scaled down by their awareness, and one who does not click reports the
+//!   lure with a chance equal to their awareness
+//! - Security-awareness training sessions InfoSec schedules for the agents
+//!   who clicked; each session closes part of the gap to full awareness
+//! - Click rates per campaign and per department, and how awareness moved
+//!
+//! The orchestrator runs campaigns at a set interval and tells InfoSec who
+//! clicked; InfoSec records a security event for every click and books the
+//! training.
+
+use crate::config::PhishingSettings;
+use crate::runtime::AgentInfo;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Campaigns and completed trainings kept; the oldest go first
+const MAX_HISTORY: usize = 100;
+
+/// Awareness InfoSec agents start with
+const INFOSEC_AWARENESS: f64 = 0.8;
+
+const LURES: [&str; 5] = [
+    "Your mailbox is almost full",
+    "Invoice overdue: payment required",
+    "Shared document: Q3 bonus plan",
+    "Password expires today",
+    "Missed delivery, reschedule now",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Awareness {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: String,
+    /// From 0, clicks whatever arrives, to 1, never clicks
+    pub score: f64,
+    pub trainings: u32,
+    pub last_trained: Option<chrono::DateTime<chrono::Utc>>,
+    pub phished: u32,
+    pub clicked: u32,
+    pub reported: u32,
+}
+
+/// How one agent handled a campaign's lure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhishingTarget {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: String,
+    pub clicked: bool,
+    pub reported: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhishingCampaign {
+    pub id: Uuid,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub lure: String,
+    pub targets: Vec<PhishingTarget>,
+}
+
+impl PhishingCampaign {
+    pub fn clicked(&self) -> impl Iterator<Item = &PhishingTarget> {
+        self.targets.iter().filter(|target| target.clicked)
+    }
+
+    pub fn click_rate(&self) -> Option<f64> {
+        (!self.targets.is_empty()).then(|| self.clicked().count() as f64 / self.targets.len() as f64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingSession {
+    pub id: Uuid,
+    pub agents: Vec<Uuid>,
+    pub scheduled_for: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Targets and clicks of one department across every campaign
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DepartmentClicks {
+    pub targeted: usize,
+    pub clicked: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AwarenessReport {
+    pub campaigns: usize,
+    pub targeted: usize,
+    pub clicked: usize,
+    pub reported: usize,
+    pub by_department: BTreeMap<String, DepartmentClicks>,
+    pub trainings_completed: usize,
+    pub mean_awareness: Option<f64>,
+}
+
+/// Every agent's awareness, and the campaigns and trainings that moved it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AwarenessProgram {
+    people: BTreeMap<Uuid, Awareness>,
+    campaigns: Vec<PhishingCampaign>,
+    trainings: Vec<TrainingSession>,
+}
+
+impl AwarenessProgram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn people(&self) -> impl Iterator<Item = &Awareness> {
+        self.people.values()
+    }
+
+    pub fn campaigns(&self) -> &[PhishingCampaign] {
+        &self.campaigns
+    }
+
+    pub fn trainings(&self) -> &[TrainingSession] {
+        &self.trainings
+    }
+
+    /// Start tracking agents seen for the first time
+    pub fn enroll<'a>(&mut self, roster: impl IntoIterator<Item = &'a AgentInfo>, settings: &PhishingSettings) {
+        for info in roster {
+            self.people.entry(info.id).or_insert_with(|| Awareness {
+                agent_id: info.id,
+                name: info.name.clone(),
+                department: info.department.as_str().to_string(),
+                score: if info.department.as_str() == "InfoSec" { INFOSEC_AWARENESS } else { settings.initial_awareness },
+                trainings: 0,
+                last_trained: None,
+                phished: 0,
+                clicked: 0,
+                reported: 0,
+            });
+        }
+    }
+
+    /// Whether a campaign is due, `every_days` after the last one
+    pub fn due(&self, every_days: u32, now: chrono::DateTime<chrono::Utc>) -> bool {
+        every_days > 0 && self.campaigns.last()
+            .iter().all(|campaign| now - campaign.at >= chrono::Duration::days(i64::from(every_days)))
+    }
+
+    /// Phish up to `targets_per_campaign` of the agents in `active`, taking
+    /// one from each department in turn
+    pub fn run(&mut self, active: &[Uuid], settings: &PhishingSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> &PhishingCampaign {
+        let mut by_department: BTreeMap<&str, Vec<Uuid>> = BTreeMap::new();
+        for person in self.people.values().filter(|person| active.contains(&person.agent_id)) {
+            by_department.entry(&person.department).or_default().push(person.agent_id);
+        }
+        for members in by_department.values_mut() {
+            members.shuffle(rng);
+        }
+        let mut chosen = Vec::new();
+        while chosen.len() < settings.targets_per_campaign && by_department.values().any(|members| !members.is_empty()) {
+            for members in by_department.values_mut() {
+                if chosen.len() < settings.targets_per_campaign {
+                    chosen.extend(members.pop());
+                }
+            }
+        }
+
+        let mut targets = Vec::with_capacity(chosen.len());
+        for agent_id in chosen {
+            let Some(person) = self.people.get_mut(&agent_id) else {
+                continue;
+            };
+            let clicked = rng.gen_bool((settings.base_click_probability * (1.0 - person.score)).clamp(0.0, 1.0));
+            let reported = !clicked && rng.gen_bool(person.score.clamp(0.0, 1.0));
+            person.phished += 1;
+            person.clicked += u32::from(clicked);
+            person.reported += u32::from(reported);
+            targets.push(PhishingTarget {
+                agent_id,
+                name: person.name.clone(),
+                department: person.department.clone(),
+                clicked,
+                reported,
+            });
+        }
+        self.campaigns.push(PhishingCampaign {
+            id: Uuid::new_v4(),
+            at: now,
+            lure: LURES[rng.gen_range(0..LURES.len())].to_string(),
+            targets,
+        });
+        if self.campaigns.len() > MAX_HISTORY {
+            self.campaigns.remove(0);
+        }
+        self.campaigns.last().expect("just pushed")
+    }
+
+    /// Book a training session for agents InfoSec picked
+    pub fn schedule_training(&mut self, agents: Vec<Uuid>, at: chrono::DateTime<chrono::Utc>) -> Uuid {
+        let id = Uuid::new_v4();
+        self.trainings.push(TrainingSession { id, agents, scheduled_for: at, completed_at: None });
+        id
+    }
+
+    /// Hold every session due by `now`: each attendee closes `gain` of the
+    /// gap to full awareness. Returns the sessions held.
+    pub fn hold_trainings(&mut self, gain: f64, now: chrono::DateTime<chrono::Utc>) -> Vec<TrainingSession> {
+        let mut held = Vec::new();
+        for session in self.trainings.iter_mut().filter(|session| session.completed_at.is_none() && session.scheduled_for <= now) {
+            for agent_id in &session.agents {
+                if let Some(person) = self.people.get_mut(agent_id) {
+                    person.score += gain.clamp(0.0, 1.0) * (1.0 - person.score);
+                    person.trainings += 1;
+                    person.last_trained = Some(now);
+                }
+            }
+            session.completed_at = Some(now);
+            held.push(session.clone());
+        }
+        let completed = self.trainings.iter().filter(|session| session.completed_at.is_some()).count();
+        if completed > MAX_HISTORY {
+            let mut excess = completed - MAX_HISTORY;
+            self.trainings.retain(|session| {
+                let drop = excess > 0 && session.completed_at.is_some();
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+        held
+    }
+
+    pub fn report(&self) -> AwarenessReport {
+        let mut by_department: BTreeMap<String, DepartmentClicks> = BTreeMap::new();
+        for target in self.campaigns.iter().flat_map(|campaign| &campaign.targets) {
+            let clicks = by_department.entry(target.department.clone()).or_default();
+            clicks.targeted += 1;
+            clicks.clicked += usize::from(target.clicked);
+        }
+        let targets = || self.campaigns.iter().flat_map(|campaign| &campaign.targets);
+        AwarenessReport {
+            campaigns: self.campaigns.len(),
+            targeted: targets().count(),
+            clicked: targets().filter(|target| target.clicked).count(),
+            reported: targets().filter(|target| target.reported).count(),
+            by_department,
+            trainings_completed: self.trainings.iter().filter(|session| session.completed_at.is_some()).count(),
+            mean_awareness: (!self.people.is_empty())
+                .then(|| self.people.values().map(|person| person.score).sum::<f64>() / self.people.len() as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Department;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_campaign_spans_departments_and_training_lowers_clicks() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let now = chrono::Utc::now();
+        let roster: Vec<AgentInfo> = [Department::Engineering, Department::Engineering, Department::Engineering, Department::Marketing]
+            .into_iter()
+            .enumerate()
+            .map(|(n, department)| AgentInfo { id: Uuid::new_v4(), name: format!("Agent {}", n), department, manager_id: None })
+            .collect();
+        let active: Vec<Uuid> = roster.iter().map(|info| info.id).collect();
+        let settings = PhishingSettings { targets_per_campaign: 2, base_click_probability: 1.0, initial_awareness: 0.0, ..PhishingSettings::default() };
+        let mut program = AwarenessProgram::new();
+        program.enroll(&roster, &settings);
+
+        // One from each department, and with no awareness both click
+        let campaign = program.run(&active, &settings, now, &mut rng).clone();
+        let departments: Vec<&str> = campaign.targets.iter().map(|target| target.department.as_str()).collect();
+        assert_eq!(departments, vec!["Engineering", "Marketing"]);
+        assert_eq!(campaign.click_rate(), Some(1.0));
+        assert!(!program.due(14, now + chrono::Duration::days(13)));
+
+        // Training closes half the gap, halving the chance to click
+        program.schedule_training(campaign.clicked().map(|target| target.agent_id).collect(), now + chrono::Duration::days(1));
+        assert!(program.hold_trainings(0.5, now).is_empty());
+        assert_eq!(program.hold_trainings(0.5, now + chrono::Duration::days(1)).len(), 1);
+        let trained = program.people().filter(|person| person.trainings == 1).count();
+        assert_eq!(trained, 2);
+        assert!(program.people().filter(|person| person.trainings == 1).all(|person| person.score == 0.5));
+
+        let report = program.report();
+        assert_eq!((report.targeted, report.clicked, report.trainings_completed), (2, 2, 1));
+        assert_eq!(report.by_department["Marketing"], DepartmentClicks { targeted: 1, clicked: 1 });
+    }
+}
@@ -0,0 +1,261 @@
+//! CI/CD Pipelines - Build Queue Between Merge and Deployment
+//!
+//! This module implements the path a merged commit takes to production:
+//! - A pipeline per merge or revert, moving through build, test, artifact and
+//!   deploy stages on the simulated clock
+//! - A shared build queue: pipelines wait their turn while the concurrent
+//!   build limit is taken
+//! - Flaky tests, which fail a test run by chance and are re-run a few times
+//!   before the pipeline gives up
+//! - Pipeline events, one per stage change, which the orchestrator publishes
+//!   on the message bus to the commit's author and DevOps
+//!
+//! A pipeline that reaches the deploy stage hands its artifact to DevOps as a
+//! deployment request; the deployment itself is tracked by DevOps.
+
+use crate::config::PipelineSettings;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Finished pipelines kept; the oldest go first
+const MAX_FINISHED: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stage {
+    Build,
+    Test,
+    Artifact,
+    Deploy,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Build => "build",
+            Stage::Test => "test",
+            Stage::Artifact => "artifact",
+            Stage::Deploy => "deploy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PipelineStatus {
+    /// Waiting in the build queue
+    Queued,
+    Running,
+    /// Artifact handed to DevOps for deployment
+    Passed,
+    Failed,
+}
+
+/// One commit's way through build, test, artifact and deploy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub commit: String,
+    /// Engineer whose merge or revert started the pipeline
+    pub requested_by: Uuid,
+    pub stage: Stage,
+    pub status: PipelineStatus,
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the stage being run ends
+    pub stage_ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Test runs so far, flaky re-runs included
+    pub test_runs: u32,
+    pub artifact: Option<String>,
+}
+
+/// A pipeline reaching a stage, passing or failing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineEvent {
+    pub pipeline_id: Uuid,
+    pub project_id: Uuid,
+    pub commit: String,
+    pub requested_by: Uuid,
+    pub stage: Stage,
+    pub status: PipelineStatus,
+    pub detail: String,
+}
+
+impl PipelineEvent {
+    fn new(pipeline: &Pipeline, detail: String) -> Self {
+        Self {
+            pipeline_id: pipeline.id,
+            project_id: pipeline.project_id,
+            commit: pipeline.commit.clone(),
+            requested_by: pipeline.requested_by,
+            stage: pipeline.stage,
+            status: pipeline.status,
+            detail,
+        }
+    }
+}
+
+/// Pipelines of every team, sharing one pool of build slots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildQueue {
+    /// In the order they were queued
+    pipelines: Vec<Pipeline>,
+}
+
+impl BuildQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pipelines(&self) -> &[Pipeline] {
+        &self.pipelines
+    }
+
+    pub fn pipeline(&self, id: Uuid) -> Option<&Pipeline> {
+        self.pipelines.iter().find(|pipeline| pipeline.id == id)
+    }
+
+    /// Pipelines waiting for a build slot and pipelines holding one
+    pub fn load(&self) -> (usize, usize) {
+        let count = |status| self.pipelines.iter().filter(|pipeline| pipeline.status == status).count();
+        (count(PipelineStatus::Queued), count(PipelineStatus::Running))
+    }
+
+    /// Queue a pipeline for a commit
+    pub fn enqueue(&mut self, project_id: Uuid, commit: String, requested_by: Uuid, now: chrono::DateTime<chrono::Utc>) -> PipelineEvent {
+        let pipeline = Pipeline {
+            id: Uuid::new_v4(),
+            project_id,
+            commit,
+            requested_by,
+            stage: Stage::Build,
+            status: PipelineStatus::Queued,
+            queued_at: now,
+            started_at: None,
+            stage_ends_at: None,
+            finished_at: None,
+            test_runs: 0,
+            artifact: None,
+        };
+        let position = self.load().0 + 1;
+        let event = PipelineEvent::new(&pipeline, format!("queued at position {}", position));
+        self.pipelines.push(pipeline);
+        event
+    }
+
+    /// Run pipelines forward to `now` and start queued ones while build
+    /// slots are free; returns what happened, in order
+    pub fn advance(&mut self, now: chrono::DateTime<chrono::Utc>, settings: &PipelineSettings, rng: &mut impl Rng) -> Vec<PipelineEvent> {
+        let mut events = Vec::new();
+        for pipeline in self.pipelines.iter_mut().filter(|pipeline| pipeline.status == PipelineStatus::Running) {
+            run_pipeline(pipeline, now, settings, rng, &mut events);
+        }
+
+        let mut running = self.load().1;
+        for pipeline in self.pipelines.iter_mut().filter(|pipeline| pipeline.status == PipelineStatus::Queued) {
+            if running >= settings.max_concurrent_builds {
+                break;
+            }
+            running += 1;
+            pipeline.status = PipelineStatus::Running;
+            pipeline.started_at = Some(now);
+            pipeline.stage_ends_at = Some(now + stage_duration(settings.build_minutes, rng));
+            let waited = (now - pipeline.queued_at).num_minutes();
+            events.push(PipelineEvent::new(pipeline, format!("build started after {} min in the queue", waited)));
+        }
+
+        let finished = self.pipelines.iter().filter(|pipeline| pipeline.finished_at.is_some()).count();
+        if finished > MAX_FINISHED {
+            let mut excess = finished - MAX_FINISHED;
+            self.pipelines.retain(|pipeline| {
+                let drop = excess > 0 && pipeline.finished_at.is_some();
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+        events
+    }
+}
+
+/// Half to one and a half times the usual minutes
+fn stage_duration(minutes: u32, rng: &mut impl Rng) -> chrono::Duration {
+    let seconds = f64::from(minutes) * 60.0 * rng.gen_range(0.5..1.5);
+    chrono::Duration::seconds((seconds.round() as i64).max(1))
+}
+
+/// Move a running pipeline through every stage that ended by `now`
+fn run_pipeline(pipeline: &mut Pipeline, now: chrono::DateTime<chrono::Utc>, settings: &PipelineSettings, rng: &mut impl Rng, events: &mut Vec<PipelineEvent>) {
+    while let Some(ends_at) = pipeline.stage_ends_at.filter(|ends_at| *ends_at <= now) {
+        match pipeline.stage {
+            Stage::Build => {
+                pipeline.stage = Stage::Test;
+                pipeline.test_runs = 1;
+                pipeline.stage_ends_at = Some(ends_at + stage_duration(settings.test_minutes, rng));
+                events.push(PipelineEvent::new(pipeline, "build passed, running tests".to_string()));
+            }
+            Stage::Test if rng.gen_bool(settings.flaky_test_probability.clamp(0.0, 1.0)) => {
+                if pipeline.test_runs > settings.test_retries {
+                    pipeline.status = PipelineStatus::Failed;
+                    pipeline.stage_ends_at = None;
+                    pipeline.finished_at = Some(ends_at);
+                    events.push(PipelineEvent::new(pipeline, format!("tests failed on all {} runs", pipeline.test_runs)));
+                    return;
+                }
+                pipeline.test_runs += 1;
+                pipeline.stage_ends_at = Some(ends_at + stage_duration(settings.test_minutes, rng));
+                events.push(PipelineEvent::new(pipeline, format!("flaky test failed, re-running (run {})", pipeline.test_runs)));
+            }
+            Stage::Test => {
+                let artifact = format!("{}.tar.gz", crate::vcs::short(&pipeline.commit));
+                pipeline.stage = Stage::Artifact;
+                pipeline.artifact = Some(artifact.clone());
+                events.push(PipelineEvent::new(pipeline, format!("tests passed after {} run(s), published {}", pipeline.test_runs, artifact)));
+
+                pipeline.stage = Stage::Deploy;
+                pipeline.status = PipelineStatus::Passed;
+                pipeline.stage_ends_at = None;
+                pipeline.finished_at = Some(ends_at);
+                events.push(PipelineEvent::new(pipeline, format!("{} handed to DevOps", artifact)));
+            }
+            Stage::Artifact | Stage::Deploy => pipeline.stage_ends_at = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_queue_respects_build_limit_and_retries_flaky_tests() {
+        let settings = PipelineSettings { max_concurrent_builds: 1, build_minutes: 10, test_minutes: 20, flaky_test_probability: 0.0, ..PipelineSettings::default() };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let start = chrono::Utc::now();
+        let mut queue = BuildQueue::new();
+        let first = queue.enqueue(Uuid::new_v4(), "a1b2c3d4e5".to_string(), Uuid::new_v4(), start).pipeline_id;
+        let second = queue.enqueue(Uuid::new_v4(), "f6e5d4c3b2".to_string(), Uuid::new_v4(), start).pipeline_id;
+
+        queue.advance(start, &settings, &mut rng);
+        assert_eq!(queue.load(), (1, 1));
+        assert_eq!(queue.pipeline(first).unwrap().status, PipelineStatus::Running);
+
+        // Build and tests take at most 45 min, then the second gets the slot
+        let events = queue.advance(start + chrono::Duration::hours(1), &settings, &mut rng);
+        let first = queue.pipeline(first).unwrap();
+        assert_eq!((first.stage, first.status), (Stage::Deploy, PipelineStatus::Passed));
+        assert_eq!(first.artifact.as_deref(), Some("a1b2c3d.tar.gz"));
+        assert_eq!(events.last().unwrap().pipeline_id, second);
+        assert_eq!(queue.load(), (0, 1));
+
+        // Tests that always fail are re-run, then fail the pipeline
+        let flaky = PipelineSettings { flaky_test_probability: 1.0, test_retries: 2, ..settings };
+        let events = queue.advance(start + chrono::Duration::hours(4), &flaky, &mut rng);
+        let second = queue.pipeline(second).unwrap();
+        assert_eq!((second.stage, second.status, second.test_runs), (Stage::Test, PipelineStatus::Failed, 3));
+        assert_eq!(events.iter().filter(|event| event.detail.starts_with("flaky test failed")).count(), 2);
+    }
+}
@@ -0,0 +1,318 @@
+//! Decision Policies - Pluggable Agent Behavior
+//!
+//! This module implements the policies that decide what an agent does on its
+//! turn, so behavior can be swapped without touching department code:
+//! - Random: routine work and daily tasks by chance, as the simulation has
+//!   always done
+//! - Rule-based: routine work whenever the agent is not busy, daily tasks at
+//!   a fixed cadence
+//! - Scripted: a fixed sequence of turns, repeated, for reproducible
+//!   scenarios and tests
+//! - LLM: a model behind an OpenAI-compatible chat completions API picks the
+//!   turn (`llm` feature), falling back to chance when it does not answer
+//!
+//! Each agent gets its own policy instance from `[decisions]`; the
+//! orchestrator carries out whatever the policy decides.
+
+use crate::agents::Department;
+use crate::config::{PolicySettings, SimulationConfig};
+use crate::personality::Personality;
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What an agent does on a turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TurnDecision {
+    /// Do the department's routine work
+    pub activity: bool,
+    /// Run the agent's daily tasks
+    pub daily_tasks: bool,
+}
+
+/// What a policy knows about the agent whose turn it is
+#[derive(Debug, Clone)]
+pub struct AgentTurn {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: Department,
+    /// Open items the agent is working through
+    pub backlog: usize,
+    pub now: chrono::DateTime<chrono::Utc>,
+    pub working_hours: bool,
+    pub personality: Personality,
+}
+
+/// Decides an agent's turns
+#[async_trait]
+pub trait DecisionPolicy: Send + Sync + std::fmt::Debug {
+    /// Policy name for logs, e.g. "random"
+    fn name(&self) -> &'static str;
+
+    /// Decide the turn; chance comes from the orchestrator's seeded generator
+    async fn decide(&mut self, turn: &AgentTurn, rng: &mut StdRng) -> TurnDecision;
+}
+
+/// Longest an LLM turn may take before the agent decides by chance
+#[cfg(feature = "llm")]
+const LLM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// Build the policy an agent is configured with
+pub fn build(settings: &PolicySettings, config: &SimulationConfig) -> Result<Box<dyn DecisionPolicy>, PolicyError> {
+    let random = RandomPolicy { daily_tasks: config.event_probabilities.daily_tasks };
+    Ok(match settings {
+        PolicySettings::Random => Box::new(random),
+        PolicySettings::RuleBased { busy_backlog, daily_tasks_every } => Box::new(RuleBasedPolicy {
+            busy_backlog: *busy_backlog,
+            daily_tasks_every: *daily_tasks_every,
+            turns: 0,
+        }),
+        PolicySettings::Scripted { turns } => Box::new(ScriptedPolicy { turns: turns.clone(), position: 0 }),
+        #[cfg(feature = "llm")]
+        PolicySettings::Llm { api_url, model, api_key_env } => Box::new(LlmPolicy {
+            client: reqwest::Client::builder()
+                .user_agent(concat!("aivertco/", env!("CARGO_PKG_VERSION")))
+                .timeout(LLM_TIMEOUT)
+                .build()
+                .map_err(PolicyError::Http)?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            model: model.clone(),
+            api_key: std::env::var(api_key_env).map_err(|_| PolicyError::MissingKey(api_key_env.clone()))?,
+            fallback: random,
+            failures: 0,
+        }),
+        #[cfg(not(feature = "llm"))]
+        PolicySettings::Llm { .. } => return Err(PolicyError::FeatureDisabled),
+    })
+}
+
+/// Chance of routine work on a turn, by department
+fn activity_chance(department: &Department) -> Option<f32> {
+    match department {
+        Department::Engineering | Department::DevOps => Some(0.3),
+        Department::InfoSec => Some(0.2),
+        Department::Networking | Department::Marketing => Some(0.25),
+        Department::Ops => Some(0.4),
+        _ => None,
+    }
+}
+
+/// Routine work and daily tasks by chance
+#[derive(Debug, Clone)]
+pub struct RandomPolicy {
+    /// Chance of running daily tasks on a turn
+    pub daily_tasks: f32,
+}
+
+#[async_trait]
+impl DecisionPolicy for RandomPolicy {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    async fn decide(&mut self, turn: &AgentTurn, rng: &mut StdRng) -> TurnDecision {
+        // Departments without routine work draw nothing for it
+        let personality = &turn.personality;
+        let activity = activity_chance(&turn.department).is_some_and(|chance| rng.gen::<f32>() < personality.activity_chance(chance));
+        TurnDecision { activity, daily_tasks: rng.gen::<f32>() < personality.daily_tasks_chance(self.daily_tasks) }
+    }
+}
+
+/// Routine work while not busy, daily tasks every `daily_tasks_every` turns
+#[derive(Debug, Clone)]
+pub struct RuleBasedPolicy {
+    pub busy_backlog: usize,
+    pub daily_tasks_every: u32,
+    turns: u32,
+}
+
+#[async_trait]
+impl DecisionPolicy for RuleBasedPolicy {
+    fn name(&self) -> &'static str {
+        "rule-based"
+    }
+
+    async fn decide(&mut self, turn: &AgentTurn, _rng: &mut StdRng) -> TurnDecision {
+        self.turns = self.turns.wrapping_add(1);
+        TurnDecision {
+            activity: turn.working_hours && turn.backlog < self.busy_backlog && activity_chance(&turn.department).is_some(),
+            daily_tasks: self.turns % self.daily_tasks_every.max(1) == 0,
+        }
+    }
+}
+
+/// A fixed sequence of turns, repeated
+#[derive(Debug, Clone)]
+pub struct ScriptedPolicy {
+    turns: Vec<TurnDecision>,
+    position: usize,
+}
+
+#[async_trait]
+impl DecisionPolicy for ScriptedPolicy {
+    fn name(&self) -> &'static str {
+        "scripted"
+    }
+
+    async fn decide(&mut self, _turn: &AgentTurn, _rng: &mut StdRng) -> TurnDecision {
+        let Some(decision) = self.turns.get(self.position % self.turns.len().max(1)).copied() else {
+            return TurnDecision::default();
+        };
+        self.position += 1;
+        decision
+    }
+}
+
+/// Turns picked by a chat completions model
+///
+/// The model is asked for `{"activity": bool, "daily_tasks": bool}`; turns it
+/// does not answer usably are decided by chance instead.
+#[cfg(feature = "llm")]
+#[derive(Debug)]
+pub struct LlmPolicy {
+    client: reqwest::Client,
+    api_url: String,
+    model: String,
+    api_key: String,
+    fallback: RandomPolicy,
+    /// Turns decided by the fallback
+    pub failures: u64,
+}
+
+#[cfg(feature = "llm")]
+impl LlmPolicy {
+    async fn ask(&self, turn: &AgentTurn) -> Result<TurnDecision, PolicyError> {
+        let prompt = format!(
+            "You are {}, an agent in the {} department of a simulated company. It is {} ({} working hours) \
+             and you have {} open items. Your risk tolerance is {:.1} and your thoroughness {:.1}, from 0 to 1. \
+             Decide your turn: do routine department work, run your daily tasks, both or neither. \
+             Answer with JSON only: {{\"activity\": true|false, \"daily_tasks\": true|false}}",
+            turn.name, turn.department.as_str(), turn.now.format("%A %H:%M UTC"),
+            if turn.working_hours { "within" } else { "outside" }, turn.backlog,
+            turn.personality.risk_tolerance, turn.personality.thoroughness);
+        let response = self.client.post(format!("{}/chat/completions", self.api_url))
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "temperature": 0.2,
+            }))
+            .send().await
+            .map_err(PolicyError::Http)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PolicyError::Api { status: status.as_u16(), body });
+        }
+
+        let body: serde_json::Value = response.json().await.map_err(PolicyError::Http)?;
+        let content = body["choices"][0]["message"]["content"].as_str().unwrap_or_default();
+        // Models like to wrap JSON in prose or code fences
+        let json = content.find('{').zip(content.rfind('}'))
+            .filter(|(start, end)| start < end)
+            .map_or(content, |(start, end)| &content[start..=end]);
+        serde_json::from_str(json).map_err(|_| PolicyError::Unparseable(content.to_string()))
+    }
+}
+
+#[cfg(feature = "llm")]
+#[async_trait]
+impl DecisionPolicy for LlmPolicy {
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+
+    async fn decide(&mut self, turn: &AgentTurn, rng: &mut StdRng) -> TurnDecision {
+        match self.ask(turn).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                self.failures += 1;
+                println!("⚠️ {} decided by chance, the model did not answer: {}", turn.name, e);
+                self.fallback.decide(turn, rng).await
+            }
+        }
+    }
+}
+
+/// Policy construction and LLM request errors
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[cfg(not(feature = "llm"))]
+    #[error("The llm decision policy needs a build with the llm feature")]
+    FeatureDisabled,
+
+    #[cfg(feature = "llm")]
+    #[error("Environment variable {0} with the LLM API key is not set")]
+    MissingKey(String),
+
+    #[cfg(feature = "llm")]
+    #[error("LLM request failed: {0}")]
+    Http(reqwest::Error),
+
+    #[cfg(feature = "llm")]
+    #[error("LLM API returned {status}: {body}")]
+    Api { status: u16, body: String },
+
+    #[cfg(feature = "llm")]
+    #[error("LLM answer is not a turn decision: {0}")]
+    Unparseable(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn turn(department: Department, backlog: usize) -> AgentTurn {
+        AgentTurn {
+            agent_id: Uuid::new_v4(),
+            name: "Agent".to_string(),
+            department,
+            backlog,
+            now: "2024-03-04T10:00:00Z".parse().unwrap(),
+            working_hours: true,
+            personality: Personality::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policies_decide_turns() {
+        let config = SimulationConfig::default();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Same seed, same turns
+        let mut random = build(&PolicySettings::Random, &config).unwrap();
+        let first: Vec<TurnDecision> = decide_turns(&mut *random, &turn(Department::Ops, 0), &mut rng, 20).await;
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(decide_turns(&mut *random, &turn(Department::Ops, 0), &mut rng, 20).await, first);
+        // Finance has no routine work to do
+        assert!(decide_turns(&mut *random, &turn(Department::Finance, 0), &mut rng, 20).await.iter().all(|d| !d.activity));
+        // Risk-averse agents take on less routine work
+        let cautious = AgentTurn { personality: Personality { risk_tolerance: 0.0, ..Personality::default() }, ..turn(Department::Ops, 0) };
+        let bold = AgentTurn { personality: Personality { risk_tolerance: 1.0, ..Personality::default() }, ..turn(Department::Ops, 0) };
+        let count = |decisions: Vec<TurnDecision>| decisions.iter().filter(|d| d.activity).count();
+        assert!(count(decide_turns(&mut *random, &cautious, &mut rng, 200).await) < count(decide_turns(&mut *random, &bold, &mut rng, 200).await));
+
+        let mut rules = build(&PolicySettings::RuleBased { busy_backlog: 2, daily_tasks_every: 3 }, &config).unwrap();
+        let decisions = decide_turns(&mut *rules, &turn(Department::DevOps, 1), &mut rng, 3).await;
+        assert!(decisions.iter().all(|d| d.activity));
+        assert_eq!(decisions.iter().map(|d| d.daily_tasks).collect::<Vec<_>>(), vec![false, false, true]);
+        assert!(!rules.decide(&turn(Department::DevOps, 2), &mut rng).await.activity);
+
+        let script = vec![TurnDecision { activity: true, daily_tasks: false }, TurnDecision::default()];
+        let mut scripted = build(&PolicySettings::Scripted { turns: script.clone() }, &config).unwrap();
+        let decisions = decide_turns(&mut *scripted, &turn(Department::Ops, 0), &mut rng, 4).await;
+        assert_eq!(decisions, [script.clone(), script].concat());
+    }
+
+    async fn decide_turns(policy: &mut dyn DecisionPolicy, turn: &AgentTurn, rng: &mut StdRng, count: usize) -> Vec<TurnDecision> {
+        let mut decisions = Vec::with_capacity(count);
+        for _ in 0..count {
+            decisions.push(policy.decide(turn, rng).await);
+        }
+        decisions
+    }
+}
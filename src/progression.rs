@@ -0,0 +1,134 @@
+//! Skill Progression - Experience, Learning & Decay
+//!
+//! This module implements agents getting better at work they keep doing:
+//! - Experience from each finished piece of work of a kind, read from the
+//!   completion counts agents report
+//! - Skill points bought with experience, each costing more the higher the
+//!   skill already is, so experts improve slowly
+//! - Optional decay of skills an agent has not used for a while, down to a
+//!   floor
+//!
+//! The ledger only decides by how much a skill moves; the orchestrator
+//! applies the change through `DepartmentAgent::adjust_skill`.
+
+use crate::assignment::WorkKind;
+use crate::config::ProgressionSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// One agent's history with one kind of work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillRecord {
+    /// Completion count the agent last reported
+    pub completed: usize,
+    /// Experience banked towards the next skill point
+    pub experience: f64,
+    /// Last time the skill was used or decayed
+    pub idle_since: chrono::DateTime<chrono::Utc>,
+}
+
+/// Experience of every agent, by kind of work
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillProgression {
+    records: BTreeMap<Uuid, BTreeMap<WorkKind, SkillRecord>>,
+    /// Skill points gained and lost over the whole run
+    pub gained: u64,
+    pub lost: u64,
+}
+
+impl SkillProgression {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, agent_id: Uuid, kind: WorkKind) -> Option<&SkillRecord> {
+        self.records.get(&agent_id)?.get(&kind)
+    }
+
+    /// Take in an agent's completion count for a kind of work and return by
+    /// how many points its skill moves: up with experience, or down by one
+    /// when the skill has gone unused for `decay_after_days`
+    ///
+    /// The first count seen for an agent is a baseline and earns nothing.
+    pub fn observe(
+        &mut self,
+        agent_id: Uuid,
+        kind: WorkKind,
+        completed: usize,
+        skill: u8,
+        settings: &ProgressionSettings,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> i8 {
+        let record = self.records.entry(agent_id).or_default().entry(kind).or_insert(SkillRecord {
+            completed,
+            experience: 0.0,
+            idle_since: now,
+        });
+
+        // Counts drop when agents prune finished work; that is not a loss
+        let finished = completed.saturating_sub(record.completed);
+        record.completed = completed;
+        if finished > 0 {
+            record.experience += finished as f64 * settings.experience_per_task;
+            record.idle_since = now;
+
+            let mut gained: u8 = 0;
+            while skill + gained < 100 && record.experience >= point_cost(skill + gained, settings) {
+                record.experience -= point_cost(skill + gained, settings);
+                gained += 1;
+            }
+            self.gained += u64::from(gained);
+            return gained as i8;
+        }
+
+        let Some(decay_after_days) = settings.decay_after_days else {
+            return 0;
+        };
+        if skill > settings.decay_floor && now - record.idle_since >= chrono::Duration::days(decay_after_days) {
+            record.idle_since = now;
+            self.lost += 1;
+            return -1;
+        }
+        0
+    }
+
+    /// Forget agents that have left the company
+    pub fn retain(&mut self, agents: &[Uuid]) {
+        self.records.retain(|id, _| agents.contains(id));
+    }
+}
+
+/// Experience the next point costs at a skill level: the base cost at 1,
+/// about twice it at 50 and ten times it at 90
+pub fn point_cost(skill: u8, settings: &ProgressionSettings) -> f64 {
+    settings.experience_per_point * 100.0 / f64::from(101 - skill.min(100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experience_raises_skill_with_diminishing_returns_and_decays() {
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-01T09:00:00Z".parse().unwrap();
+        let settings = ProgressionSettings { experience_per_task: 1.0, experience_per_point: 5.0, decay_after_days: Some(30), decay_floor: 40 };
+        let mut progression = SkillProgression::new();
+        let (novice, expert) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // Baselines, then the same ten tickets for both
+        assert_eq!(progression.observe(novice, WorkKind::Ticket, 3, 50, &settings, start), 0);
+        assert_eq!(progression.observe(expert, WorkKind::Ticket, 0, 90, &settings, start), 0);
+        let novice_gain = progression.observe(novice, WorkKind::Ticket, 13, 50, &settings, start);
+        let expert_gain = progression.observe(expert, WorkKind::Ticket, 10, 90, &settings, start);
+        assert_eq!((novice_gain, expert_gain), (1, 0));
+        assert!(progression.record(expert, WorkKind::Ticket).unwrap().experience > 9.0);
+
+        // A month idle costs a point; the next only after another month
+        let idle = start + chrono::Duration::days(30);
+        assert_eq!(progression.observe(novice, WorkKind::Ticket, 13, 51, &settings, idle), -1);
+        assert_eq!(progression.observe(novice, WorkKind::Ticket, 13, 50, &settings, idle + chrono::Duration::days(1)), 0);
+        assert_eq!(progression.observe(expert, WorkKind::Ticket, 10, 40, &settings, idle), 0);
+        assert_eq!((progression.gained, progression.lost), (1, 1));
+    }
+}
@@ -0,0 +1,269 @@
+//! Promotions - Builds Moving Through Dev, Staging and Production
+//!
+//! This module implements the environments a build is promoted through:
+//! - Environments in the configured order, each recording the build running
+//!   there and when it was deployed
+//! - A promotion per build that passed its pipeline, deployed to one
+//!   environment at a time and moved on once the deployment shipped
+//! - Promotion rules: a soak time the build must run in an environment
+//!   before it leaves, and an Ops change approval before it enters an
+//!   environment that requires one, such as production
+//! - Superseding: a newer build of the same project takes over, and the
+//!   older promotion stops where it is
+//!
+//! The orchestrator sends the deployments to DevOps, asks Ops for the
+//! approvals and reports back how each deployment ended.
+
+use crate::config::EnvironmentSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Finished promotions kept; the oldest go first
+const MAX_FINISHED: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromotionStatus {
+    /// Being deployed to its environment
+    Deploying,
+    /// Running in its environment until the soak time is over
+    Soaking,
+    /// Waiting for Ops to approve the move to the next environment
+    AwaitingApproval,
+    /// Running in the last environment
+    Promoted,
+    /// A deployment failed; earlier environments keep the build
+    Failed,
+    /// Ops turned the next environment down
+    Rejected,
+    /// A newer build of the project took over
+    Superseded,
+}
+
+impl PromotionStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, PromotionStatus::Deploying | PromotionStatus::Soaking | PromotionStatus::AwaitingApproval)
+    }
+}
+
+/// One build's way from the first environment to the last
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub commit: String,
+    /// Engineer whose merge or revert started it
+    pub requested_by: Uuid,
+    /// Environment the build is running in or being deployed to
+    pub environment: String,
+    pub status: PromotionStatus,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// When the build started running in its environment
+    pub arrived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Why it stopped short of the last environment
+    pub reason: Option<String>,
+}
+
+/// Build running in an environment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentVersion {
+    pub project_id: Uuid,
+    pub commit: String,
+    pub deployed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What a promotion needs done next
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromotionAction {
+    /// Have DevOps deploy the build to an environment
+    Deploy { promotion_id: Uuid, project_id: Uuid, commit: String, requested_by: Uuid, environment: String },
+    /// Ask Ops to approve deploying the build to an environment
+    RequestApproval { promotion_id: Uuid, commit: String, from: String, to: String },
+}
+
+/// Every promotion, and the builds they left in each environment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Promotions {
+    /// In the order they started
+    promotions: Vec<Promotion>,
+    /// Build running in each environment, by name
+    versions: BTreeMap<String, EnvironmentVersion>,
+}
+
+impl Promotions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn promotions(&self) -> &[Promotion] {
+        &self.promotions
+    }
+
+    pub fn promotion(&self, id: Uuid) -> Option<&Promotion> {
+        self.promotions.iter().find(|promotion| promotion.id == id)
+    }
+
+    pub fn versions(&self) -> &BTreeMap<String, EnvironmentVersion> {
+        &self.versions
+    }
+
+    /// Start promoting a build from the first environment; unfinished
+    /// promotions of the same project are superseded
+    pub fn start(&mut self, project_id: Uuid, commit: String, requested_by: Uuid, environments: &[EnvironmentSettings], now: chrono::DateTime<chrono::Utc>) -> Option<PromotionAction> {
+        let first = environments.first()?;
+        for older in self.promotions.iter_mut().filter(|promotion| promotion.project_id == project_id && !promotion.status.is_finished()) {
+            older.status = PromotionStatus::Superseded;
+            older.finished_at = Some(now);
+            older.reason = Some(format!("superseded by {}", crate::vcs::short(&commit)));
+        }
+        let promotion = Promotion {
+            id: Uuid::new_v4(),
+            project_id,
+            commit,
+            requested_by,
+            environment: first.name.clone(),
+            status: PromotionStatus::Deploying,
+            started_at: now,
+            arrived_at: None,
+            finished_at: None,
+            reason: None,
+        };
+        let action = deploy(&promotion);
+        self.promotions.push(promotion);
+        self.trim();
+        Some(action)
+    }
+
+    /// Record how a promotion's deployment ended; a shipped build is the
+    /// environment's new version
+    pub fn deployment_finished(&mut self, promotion_id: Uuid, shipped: bool, detail: &str, environments: &[EnvironmentSettings], now: chrono::DateTime<chrono::Utc>) {
+        let Some(promotion) = self.promotions.iter_mut().find(|promotion| promotion.id == promotion_id && promotion.status == PromotionStatus::Deploying) else {
+            return;
+        };
+        if !shipped {
+            promotion.status = PromotionStatus::Failed;
+            promotion.finished_at = Some(now);
+            promotion.reason = Some(format!("deployment to {} failed: {}", promotion.environment, detail));
+            return;
+        }
+        self.versions.insert(promotion.environment.clone(), EnvironmentVersion {
+            project_id: promotion.project_id,
+            commit: promotion.commit.clone(),
+            deployed_at: now,
+        });
+        promotion.arrived_at = Some(now);
+        if environments.last().is_some_and(|last| last.name == promotion.environment) {
+            promotion.status = PromotionStatus::Promoted;
+            promotion.finished_at = Some(now);
+        } else {
+            promotion.status = PromotionStatus::Soaking;
+        }
+    }
+
+    /// Move on every build whose soak time is over, to the next environment
+    /// or to waiting for its approval
+    pub fn advance(&mut self, environments: &[EnvironmentSettings], now: chrono::DateTime<chrono::Utc>) -> Vec<PromotionAction> {
+        let mut actions = Vec::new();
+        for promotion in self.promotions.iter_mut().filter(|promotion| promotion.status == PromotionStatus::Soaking) {
+            let Some(index) = environments.iter().position(|environment| environment.name == promotion.environment) else {
+                continue;
+            };
+            let soaked = promotion.arrived_at
+                .is_some_and(|arrived_at| now - arrived_at >= chrono::Duration::minutes(i64::from(environments[index].soak_minutes)));
+            let Some(next) = environments.get(index + 1).filter(|_| soaked) else {
+                continue;
+            };
+            if next.requires_approval {
+                promotion.status = PromotionStatus::AwaitingApproval;
+                actions.push(PromotionAction::RequestApproval {
+                    promotion_id: promotion.id,
+                    commit: promotion.commit.clone(),
+                    from: promotion.environment.clone(),
+                    to: next.name.clone(),
+                });
+            } else {
+                promotion.environment = next.name.clone();
+                promotion.status = PromotionStatus::Deploying;
+                actions.push(deploy(promotion));
+            }
+        }
+        actions
+    }
+
+    /// Apply Ops' decision on a promotion waiting for approval
+    pub fn decide(&mut self, promotion_id: Uuid, approved: bool, reason: &str, environments: &[EnvironmentSettings], now: chrono::DateTime<chrono::Utc>) -> Option<PromotionAction> {
+        let promotion = self.promotions.iter_mut().find(|promotion| promotion.id == promotion_id && promotion.status == PromotionStatus::AwaitingApproval)?;
+        let index = environments.iter().position(|environment| environment.name == promotion.environment)?;
+        let next = environments.get(index + 1)?;
+        if !approved {
+            promotion.status = PromotionStatus::Rejected;
+            promotion.finished_at = Some(now);
+            promotion.reason = Some(format!("promotion to {} rejected: {}", next.name, reason));
+            return None;
+        }
+        promotion.environment = next.name.clone();
+        promotion.status = PromotionStatus::Deploying;
+        Some(deploy(promotion))
+    }
+
+    fn trim(&mut self) {
+        let finished = self.promotions.iter().filter(|promotion| promotion.status.is_finished()).count();
+        if finished > MAX_FINISHED {
+            let mut excess = finished - MAX_FINISHED;
+            self.promotions.retain(|promotion| {
+                let drop = excess > 0 && promotion.status.is_finished();
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+    }
+}
+
+fn deploy(promotion: &Promotion) -> PromotionAction {
+    PromotionAction::Deploy {
+        promotion_id: promotion.id,
+        project_id: promotion.project_id,
+        commit: promotion.commit.clone(),
+        requested_by: promotion.requested_by,
+        environment: promotion.environment.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_soaks_in_staging_and_waits_for_approval() {
+        let environments = vec![
+            EnvironmentSettings::new("dev", 0, false),
+            EnvironmentSettings::new("staging", 60, false),
+            EnvironmentSettings::new("production", 0, true),
+        ];
+        let start = chrono::Utc::now();
+        let minutes = |n: i64| start + chrono::Duration::minutes(n);
+        let project_id = Uuid::new_v4();
+        let mut promotions = Promotions::new();
+        let Some(PromotionAction::Deploy { promotion_id, environment, .. }) = promotions.start(project_id, "a1b2c3d4e5".to_string(), Uuid::nil(), &environments, start) else {
+            panic!("expected a deployment to dev");
+        };
+        assert_eq!(environment, "dev");
+
+        // Dev has no soak time, so the build goes on to staging at once
+        promotions.deployment_finished(promotion_id, true, "", &environments, minutes(10));
+        assert!(matches!(&promotions.advance(&environments, minutes(10))[..], [PromotionAction::Deploy { environment, .. }] if environment == "staging"));
+        promotions.deployment_finished(promotion_id, true, "", &environments, minutes(20));
+        assert!(promotions.advance(&environments, minutes(60)).is_empty());
+        assert!(matches!(&promotions.advance(&environments, minutes(80))[..], [PromotionAction::RequestApproval { to, .. }] if to == "production"));
+
+        // A newer build supersedes the one waiting, which never reaches production
+        promotions.start(project_id, "f6e5d4c3b2".to_string(), Uuid::nil(), &environments, minutes(90));
+        assert_eq!(promotions.decide(promotion_id, true, "Approved", &environments, minutes(95)), None);
+        assert_eq!(promotions.promotion(promotion_id).unwrap().status, PromotionStatus::Superseded);
+        assert_eq!(promotions.versions()["staging"].commit, "a1b2c3d4e5");
+        assert!(!promotions.versions().contains_key("production"));
+    }
+}
@@ -0,0 +1,373 @@
+//! Resource Quotas - Capacity Reservations on Shared Clusters
+//!
+//! This module implements quotas for the company's shared infrastructure:
+//! - CPU, memory and storage quotas per team and per project
+//! - Reservations checked when a deployment is requested: a project's latest
+//!   deployment holds its footprint, counted against its team as well
+//! - Quota-increase requests routed through an approval matrix that maps the
+//!   size of an increase to the department allowed to approve it
+//! - Utilization-vs-quota reports for DevOps capacity planning
+//!
+//! The ledger only keeps the books; the orchestrator reserves capacity, sends
+//! approval requests and releases deployments once their quota is raised.
+
+use crate::config::{ApprovalTier, QuotaSettings};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Message type asking an approver to decide on a quota increase
+pub const APPROVAL_REQUEST: &str = "approval_request";
+
+/// Message type carrying an approver's decision back
+pub const APPROVAL_DECISION: &str = "approval_decision";
+
+/// Message type carrying utilization-vs-quota figures to DevOps
+pub const CAPACITY_REPORT: &str = "capacity_report";
+
+/// Metadata key naming the quota-increase request
+pub const REQUEST_ID_KEY: &str = "quota_request_id";
+
+/// CPU, memory and storage, as reserved or as a limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resources {
+    pub cpu_cores: u32,
+    pub memory_gb: u32,
+    pub storage_gb: u32,
+}
+
+impl Resources {
+    pub fn new(cpu_cores: u32, memory_gb: u32, storage_gb: u32) -> Self {
+        Self { cpu_cores, memory_gb, storage_gb }
+    }
+
+    pub fn scaled(&self, factor: u32) -> Self {
+        Self::new(self.cpu_cores * factor, self.memory_gb * factor, self.storage_gb * factor)
+    }
+
+    pub fn plus(&self, other: &Self) -> Self {
+        Self::new(self.cpu_cores + other.cpu_cores, self.memory_gb + other.memory_gb, self.storage_gb + other.storage_gb)
+    }
+
+    pub fn minus(&self, other: &Self) -> Self {
+        Self::new(
+            self.cpu_cores.saturating_sub(other.cpu_cores),
+            self.memory_gb.saturating_sub(other.memory_gb),
+            self.storage_gb.saturating_sub(other.storage_gb),
+        )
+    }
+
+    /// Larger of each resource
+    pub fn max(&self, other: &Self) -> Self {
+        Self::new(self.cpu_cores.max(other.cpu_cores), self.memory_gb.max(other.memory_gb), self.storage_gb.max(other.storage_gb))
+    }
+
+    pub fn fits_within(&self, limit: &Self) -> bool {
+        self.cpu_cores <= limit.cpu_cores && self.memory_gb <= limit.memory_gb && self.storage_gb <= limit.storage_gb
+    }
+
+    /// Pairs of (amount, limit) for each resource
+    fn pairs(&self, limit: &Self) -> [(u32, u32); 3] {
+        [(self.cpu_cores, limit.cpu_cores), (self.memory_gb, limit.memory_gb), (self.storage_gb, limit.storage_gb)]
+    }
+
+    /// Highest share of the limit used by any one resource, in percent
+    pub fn utilization(&self, limit: &Self) -> f64 {
+        self.pairs(limit).iter()
+            .map(|&(used, cap)| if cap == 0 { if used == 0 { 0.0 } else { f64::INFINITY } } else { 100.0 * f64::from(used) / f64::from(cap) })
+            .fold(0.0, f64::max)
+    }
+}
+
+impl std::fmt::Display for Resources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} CPU, {} GB memory, {} GB storage", self.cpu_cores, self.memory_gb, self.storage_gb)
+    }
+}
+
+/// What a quota applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuotaScope {
+    /// Everything a department deploys
+    Team(String),
+    Project(Uuid),
+}
+
+impl QuotaScope {
+    /// Stable key and display name of the scope
+    pub fn label(&self) -> String {
+        match self {
+            QuotaScope::Team(team) => format!("team:{}", team),
+            QuotaScope::Project(project_id) => format!("project:{}", &project_id.simple().to_string()[..8]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quota {
+    pub scope: QuotaScope,
+    pub limit: Resources,
+    pub used: Resources,
+}
+
+/// Deployment waiting for a quota increase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedDeployment {
+    pub requested_by: Uuid,
+    pub team: String,
+    pub project_id: Uuid,
+    pub commit: String,
+    pub footprint: Resources,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RequestStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+/// Request to raise a quota, decided by the approver the matrix names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaRequest {
+    pub id: Uuid,
+    pub scope: QuotaScope,
+    pub current: Resources,
+    pub requested: Resources,
+    /// Largest increase over the current limit across resources
+    pub increase_percent: f64,
+    pub approver: String,
+    pub status: RequestStatus,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    pub decided_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub reason: Option<String>,
+    /// Deployment released once the request is approved
+    pub blocked: Option<BlockedDeployment>,
+}
+
+/// Utilization of one quota, for capacity reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUtilization {
+    pub scope: String,
+    pub used: Resources,
+    pub limit: Resources,
+    pub percent: f64,
+}
+
+/// Quotas, reservations and increase requests on shared clusters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaLedger {
+    /// Keyed by scope label
+    quotas: BTreeMap<String, Quota>,
+    /// Footprint each project's latest deployment holds, and its team
+    reservations: BTreeMap<Uuid, (String, Resources)>,
+    requests: BTreeMap<Uuid, QuotaRequest>,
+}
+
+impl QuotaLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn quota(&self, scope: &QuotaScope) -> Option<&Quota> {
+        self.quotas.get(&scope.label())
+    }
+
+    pub fn request(&self, id: &Uuid) -> Option<&QuotaRequest> {
+        self.requests.get(id)
+    }
+
+    pub fn pending_requests(&self) -> impl Iterator<Item = &QuotaRequest> {
+        self.requests.values().filter(|request| request.status == RequestStatus::Pending)
+    }
+
+    /// Reserve a deployment's footprint for its project and team; a project's
+    /// new deployment replaces the reservation of its previous one
+    pub fn reserve(&mut self, team: &str, project_id: Uuid, footprint: Resources, settings: &QuotaSettings) -> Result<(), QuotaError> {
+        let team_scope = QuotaScope::Team(team.to_string());
+        let team_limit = settings.teams.get(team).copied().unwrap_or(settings.team_default);
+        self.ensure(&team_scope, team_limit);
+
+        let previous = self.reservations.get(&project_id)
+            .filter(|(owner, _)| owner == team)
+            .map_or(Resources::default(), |(_, footprint)| *footprint);
+        let team_quota = &self.quotas[&team_scope.label()];
+        let team_needed = team_quota.used.minus(&previous).plus(&footprint);
+        if !team_needed.fits_within(&team_quota.limit) {
+            return Err(QuotaError::Exceeded { scope: team_scope, needed: team_needed, limit: team_quota.limit });
+        }
+
+        // Deployments outside any project only count against the team
+        let project_scope = (!project_id.is_nil()).then_some(QuotaScope::Project(project_id));
+        if let Some(scope) = &project_scope {
+            self.ensure(scope, settings.project_default);
+            let limit = self.quotas[&scope.label()].limit;
+            if !footprint.fits_within(&limit) {
+                return Err(QuotaError::Exceeded { scope: scope.clone(), needed: footprint, limit });
+            }
+            self.quotas.get_mut(&scope.label()).expect("ensured above").used = footprint;
+        }
+        self.quotas.get_mut(&team_scope.label()).expect("ensured above").used = team_needed;
+
+        // A project that changed teams gives its old team's reservation back
+        if let Some((owner, held)) = self.reservations.insert(project_id, (team.to_string(), footprint)) {
+            if owner != team {
+                if let Some(quota) = self.quotas.get_mut(&QuotaScope::Team(owner).label()) {
+                    quota.used = quota.used.minus(&held);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ask for a quota big enough for `needed`; the matrix picks the approver
+    ///
+    /// A scope with a request already pending gets that request back, now
+    /// holding the latest blocked deployment, instead of a second request.
+    pub fn request_increase(&mut self, scope: &QuotaScope, needed: Resources, blocked: BlockedDeployment, matrix: &[ApprovalTier], now: chrono::DateTime<chrono::Utc>) -> Result<&QuotaRequest, QuotaError> {
+        let quota = self.quota(scope).ok_or_else(|| QuotaError::UnknownScope(scope.label()))?;
+        let current = quota.limit;
+        let requested = current.max(&needed);
+
+        if let Some(id) = self.pending_requests().find(|request| request.scope == *scope).map(|request| request.id) {
+            let request = self.requests.get_mut(&id).expect("found above");
+            request.requested = request.requested.max(&requested);
+            request.blocked = Some(blocked);
+            return Ok(request);
+        }
+
+        let increase_percent = requested.pairs(&current).iter()
+            .map(|&(wanted, cap)| if cap == 0 { f64::INFINITY } else { 100.0 * (f64::from(wanted) - f64::from(cap)) / f64::from(cap) })
+            .fold(0.0, f64::max);
+        let tier = matrix.iter()
+            .find(|tier| increase_percent <= tier.max_increase_percent)
+            .ok_or(QuotaError::NoApprover { scope: scope.label(), increase_percent })?;
+
+        let request = QuotaRequest {
+            id: Uuid::new_v4(),
+            scope: scope.clone(),
+            current,
+            requested,
+            increase_percent,
+            approver: tier.approver.clone(),
+            status: RequestStatus::Pending,
+            requested_at: now,
+            decided_at: None,
+            reason: None,
+            blocked: Some(blocked),
+        };
+        let id = request.id;
+        self.requests.insert(id, request);
+        Ok(&self.requests[&id])
+    }
+
+    /// Record an approver's decision; an approved request raises the quota
+    pub fn decide(&mut self, id: Uuid, approved: bool, reason: String, now: chrono::DateTime<chrono::Utc>) -> Result<&QuotaRequest, QuotaError> {
+        let request = self.requests.get_mut(&id)
+            .filter(|request| request.status == RequestStatus::Pending)
+            .ok_or(QuotaError::UnknownRequest(id))?;
+        request.status = if approved { RequestStatus::Approved } else { RequestStatus::Denied };
+        request.decided_at = Some(now);
+        request.reason = Some(reason);
+
+        if approved {
+            if let Some(quota) = self.quotas.get_mut(&request.scope.label()) {
+                quota.limit = quota.limit.max(&request.requested);
+            }
+        }
+        Ok(request)
+    }
+
+    /// Utilization of every quota, fullest first
+    pub fn utilization(&self) -> Vec<QuotaUtilization> {
+        let mut report: Vec<QuotaUtilization> = self.quotas.values()
+            .map(|quota| QuotaUtilization {
+                scope: quota.scope.label(),
+                used: quota.used,
+                limit: quota.limit,
+                percent: quota.used.utilization(&quota.limit),
+            })
+            .collect();
+        report.sort_by(|a, b| b.percent.total_cmp(&a.percent));
+        report
+    }
+
+    fn ensure(&mut self, scope: &QuotaScope, limit: Resources) {
+        self.quotas.entry(scope.label()).or_insert_with(|| Quota {
+            scope: scope.clone(),
+            limit,
+            used: Resources::default(),
+        });
+    }
+}
+
+/// Quota errors
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("Quota {} exceeded: needs {needed}, limit is {limit}", .scope.label())]
+    Exceeded { scope: QuotaScope, needed: Resources, limit: Resources },
+
+    #[error("No approver for a {increase_percent:.0}% increase of {scope}")]
+    NoApprover { scope: String, increase_percent: f64 },
+
+    #[error("Unknown quota {0}")]
+    UnknownScope(String),
+
+    #[error("No pending quota request {0}")]
+    UnknownRequest(Uuid),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocked(project_id: Uuid, footprint: Resources) -> BlockedDeployment {
+        BlockedDeployment {
+            requested_by: Uuid::new_v4(),
+            team: "Engineering".to_string(),
+            project_id,
+            commit: "0123456789abcdef".to_string(),
+            footprint,
+        }
+    }
+
+    #[test]
+    fn test_team_quota_blocks_until_increase_is_approved() {
+        let settings = QuotaSettings {
+            team_default: Resources::new(8, 16, 100),
+            project_default: Resources::new(8, 16, 50),
+            ..QuotaSettings::default()
+        };
+        let now: chrono::DateTime<chrono::Utc> = "2024-03-04T09:00:00Z".parse().unwrap();
+        let footprint = Resources::new(4, 8, 40);
+        let mut ledger = QuotaLedger::new();
+
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        ledger.reserve("Engineering", first, footprint, &settings).unwrap();
+        ledger.reserve("Engineering", second, footprint, &settings).unwrap();
+        // Redeploying a project replaces its reservation rather than adding to it
+        ledger.reserve("Engineering", first, footprint, &settings).unwrap();
+
+        let team = QuotaScope::Team("Engineering".to_string());
+        let needed = match ledger.reserve("Engineering", third, footprint, &settings) {
+            Err(QuotaError::Exceeded { scope, needed, .. }) if scope == team => needed,
+            other => panic!("expected the team quota to be exceeded, got {:?}", other),
+        };
+        assert_eq!(needed, Resources::new(12, 24, 120));
+
+        // +50% falls in the matrix's second tier
+        let request = ledger.request_increase(&team, needed, blocked(third, footprint), &settings.approval, now).unwrap();
+        assert_eq!(request.approver, "Ops");
+        let id = request.id;
+        let again = ledger.request_increase(&team, needed, blocked(third, footprint), &settings.approval, now).unwrap();
+        assert_eq!(again.id, id);
+
+        ledger.decide(id, true, "Capacity available".to_string(), now).unwrap();
+        assert!(ledger.decide(id, true, "Twice".to_string(), now).is_err());
+        ledger.reserve("Engineering", third, footprint, &settings).unwrap();
+
+        let report = ledger.utilization();
+        assert_eq!(report[0].scope, "team:Engineering");
+        assert_eq!(report[0].percent, 100.0);
+    }
+}
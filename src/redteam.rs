@@ -0,0 +1,338 @@
+//! Red Team - Attack Chains Against the Company's Defenses
+//!
+//! This module implements simulated attacks and what they teach:
+//! - Campaigns that run a multi-stage attack chain: phishing an employee for
+//!   a foothold, moving laterally to a server, then exfiltrating a service's
+//!   data
+//! - Detection at every stage from InfoSec's security controls, weighted by
+//!   their effectiveness and status, and from Networking's firewall rules:
+//!   deny rules slow lateral movement, and logging rules catch data leaving;
+//!   an active threat advisory for a stage sharpens every defense watching it
+//! - A campaign stops at the first stage it is detected at; one that is never
+//!   detected exfiltrates its target's data
+//! - A purple-team report: how often each stage was caught, where the
+//!   defenses have gaps, and what would close them
+//!
+//! The orchestrator runs a campaign at a set interval in red team mode,
+//! reports detected ones to InfoSec as incidents and prints the purple-team
+//! report at the end of the run.
+
+use crate::departments::infosec::{ControlStatus, ControlType, SecurityControl};
+use crate::departments::networking::{FirewallAction, FirewallRule};
+use crate::threatintel::ThreatAdvisory;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Campaigns kept; the oldest go first
+const MAX_CAMPAIGNS: usize = 100;
+
+/// Detection chance a stage must reach before it is not a gap
+const GAP_BELOW: f64 = 0.5;
+
+/// Share of its effectiveness a control adds to detecting a stage it covers
+const CONTROL_WEIGHT: f64 = 0.5;
+
+/// Detection chance each firewall rule that applies to a stage adds
+const RULE_WEIGHT: f64 = 0.1;
+
+/// Firewall rules that count towards detecting a stage, at most
+const MAX_RULES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttackStage {
+    Phishing,
+    LateralMovement,
+    Exfiltration,
+}
+
+impl AttackStage {
+    pub const CHAIN: [AttackStage; 3] = [AttackStage::Phishing, AttackStage::LateralMovement, AttackStage::Exfiltration];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttackStage::Phishing => "phishing",
+            AttackStage::LateralMovement => "lateral movement",
+            AttackStage::Exfiltration => "exfiltration",
+        }
+    }
+
+    /// Controls that can catch the stage
+    fn detected_by(&self) -> &'static [ControlType] {
+        match self {
+            AttackStage::Phishing => &[ControlType::AccessControl, ControlType::Monitoring],
+            AttackStage::LateralMovement => &[ControlType::NetworkSecurity, ControlType::EndpointProtection, ControlType::Monitoring],
+            AttackStage::Exfiltration => &[ControlType::Monitoring, ControlType::NetworkSecurity],
+        }
+    }
+
+    /// Firewall rules that get in the stage's way
+    fn hindered_by(&self) -> Option<FirewallAction> {
+        match self {
+            AttackStage::Phishing => None,
+            AttackStage::LateralMovement => Some(FirewallAction::Deny),
+            AttackStage::Exfiltration => Some(FirewallAction::Log),
+        }
+    }
+
+    /// What would make the stage easier to catch
+    fn recommendation(&self) -> &'static str {
+        match self {
+            AttackStage::Phishing => "enforce multi-factor authentication and security awareness training",
+            AttackStage::LateralMovement => "add firewall deny rules between network segments and endpoint protection",
+            AttackStage::Exfiltration => "log egress traffic at the firewall and monitor for unusual transfers",
+        }
+    }
+}
+
+/// InfoSec's controls and Networking's firewall rules as an attack meets
+/// them, and the threat advisories InfoSec is acting on
+#[derive(Debug, Clone, Default)]
+pub struct Defenses {
+    pub controls: Vec<SecurityControl>,
+    pub firewall_rules: Vec<FirewallRule>,
+    pub advisories: Vec<ThreatAdvisory>,
+}
+
+impl Defenses {
+    /// Chance the stage is detected, and the defenses that would see it
+    pub fn detection(&self, stage: AttackStage) -> (f64, Vec<String>) {
+        let mut missed = 1.0;
+        let mut sources = Vec::new();
+        for control in self.controls.iter().filter(|control| stage.detected_by().contains(&control.control_type)) {
+            let share = match control.status {
+                ControlStatus::Active => 1.0,
+                ControlStatus::Degraded => 0.5,
+                ControlStatus::Inactive | ControlStatus::Failed => continue,
+            };
+            missed *= 1.0 - CONTROL_WEIGHT * share * f64::from(control.effectiveness.min(100)) / 100.0;
+            sources.push(control.name.clone());
+        }
+        if let Some(action) = stage.hindered_by() {
+            for rule in self.firewall_rules.iter().filter(|rule| rule.enabled && rule.action == action).take(MAX_RULES) {
+                missed *= 1.0 - RULE_WEIGHT;
+                sources.push(format!("firewall rule {}", rule.name));
+            }
+        }
+        for advisory in self.advisories.iter().filter(|advisory| advisory.stages.contains(&stage)) {
+            missed *= 1.0 - advisory.detection_boost.clamp(0.0, 1.0);
+            sources.push(format!("threat advisory {}", advisory.threat));
+        }
+        (1.0 - missed, sources)
+    }
+}
+
+/// One stage an attack attempted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageOutcome {
+    pub stage: AttackStage,
+    pub technique: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub detection_chance: f64,
+    /// Defense that caught it, if one did
+    pub detected_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackCampaign {
+    pub id: uuid::Uuid,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Employee phished, server moved to and service whose data was sought
+    pub phished: String,
+    pub server: String,
+    pub service: String,
+    /// Stages attempted, up to the one it was detected at
+    pub stages: Vec<StageOutcome>,
+}
+
+impl AttackCampaign {
+    /// Stage the campaign was caught at
+    pub fn detected_at(&self) -> Option<&StageOutcome> {
+        self.stages.iter().find(|outcome| outcome.detected_by.is_some())
+    }
+
+    /// Whether the target's data left the company
+    pub fn exfiltrated(&self) -> bool {
+        self.detected_at().is_none() && self.stages.len() == AttackStage::CHAIN.len()
+    }
+}
+
+/// How well one stage was caught across every campaign
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageCoverage {
+    pub stage: AttackStage,
+    pub attempted: usize,
+    pub detected: usize,
+}
+
+impl StageCoverage {
+    pub fn detection_rate(&self) -> Option<f64> {
+        (self.attempted > 0).then(|| self.detected as f64 / self.attempted as f64)
+    }
+}
+
+/// What the red team's campaigns showed the defenders
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PurpleTeamReport {
+    pub campaigns: usize,
+    pub detected: usize,
+    pub exfiltrated: usize,
+    pub stages: Vec<StageCoverage>,
+    /// Stages caught less than half the time, with what would help
+    pub gaps: Vec<String>,
+}
+
+/// Every campaign the red team ran
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttackSimulator {
+    campaigns: Vec<AttackCampaign>,
+}
+
+impl AttackSimulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn campaigns(&self) -> &[AttackCampaign] {
+        &self.campaigns
+    }
+
+    /// Whether a campaign is due, `every_days` after the last one
+    pub fn due(&self, every_days: u32, now: chrono::DateTime<chrono::Utc>) -> bool {
+        every_days > 0 && self.campaigns.last()
+            .iter().all(|campaign| now - campaign.started_at >= chrono::Duration::days(i64::from(every_days)))
+    }
+
+    /// Run the attack chain against the defenses, stopping at the first
+    /// stage that is detected; stages are an hour apart
+    pub fn run(&mut self, phished: &str, server: &str, service: &str, defenses: &Defenses, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> &AttackCampaign {
+        let mut stages = Vec::new();
+        for (hour, stage) in AttackStage::CHAIN.into_iter().enumerate() {
+            let technique = match stage {
+                AttackStage::Phishing => format!("Credential phishing email to {}", phished),
+                AttackStage::LateralMovement => format!("Reused credentials to reach {}", server),
+                AttackStage::Exfiltration => format!("Bulk export of {} data over DNS", service),
+            };
+            let (chance, sources) = defenses.detection(stage);
+            let detected_by = (!sources.is_empty() && rng.gen_bool(chance.clamp(0.0, 1.0)))
+                .then(|| sources[rng.gen_range(0..sources.len())].clone());
+            let caught = detected_by.is_some();
+            stages.push(StageOutcome {
+                stage,
+                technique,
+                at: now + chrono::Duration::hours(hour as i64),
+                detection_chance: chance,
+                detected_by,
+            });
+            if caught {
+                break;
+            }
+        }
+        self.campaigns.push(AttackCampaign {
+            id: uuid::Uuid::new_v4(),
+            started_at: now,
+            phished: phished.to_string(),
+            server: server.to_string(),
+            service: service.to_string(),
+            stages,
+        });
+        if self.campaigns.len() > MAX_CAMPAIGNS {
+            self.campaigns.remove(0);
+        }
+        self.campaigns.last().expect("just pushed")
+    }
+
+    pub fn report(&self) -> PurpleTeamReport {
+        let stages: Vec<StageCoverage> = AttackStage::CHAIN.into_iter()
+            .map(|stage| {
+                let outcomes: Vec<&StageOutcome> = self.campaigns.iter().flat_map(|campaign| &campaign.stages).filter(|outcome| outcome.stage == stage).collect();
+                StageCoverage {
+                    stage,
+                    attempted: outcomes.len(),
+                    detected: outcomes.iter().filter(|outcome| outcome.detected_by.is_some()).count(),
+                }
+            })
+            .collect();
+        let gaps = stages.iter()
+            .filter(|coverage| coverage.detection_rate().is_some_and(|rate| rate < GAP_BELOW))
+            .map(|coverage| format!("{} caught in {} of {} attempts: {}", coverage.stage.as_str(), coverage.detected, coverage.attempted, coverage.stage.recommendation()))
+            .collect();
+        PurpleTeamReport {
+            campaigns: self.campaigns.len(),
+            detected: self.campaigns.iter().filter(|campaign| campaign.detected_at().is_some()).count(),
+            exfiltrated: self.campaigns.iter().filter(|campaign| campaign.exfiltrated()).count(),
+            stages,
+            gaps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::networking::{PortRange, Protocol};
+    use rand::SeedableRng;
+
+    fn control(control_type: ControlType, status: ControlStatus) -> SecurityControl {
+        SecurityControl {
+            id: format!("{:?}", control_type),
+            name: format!("{:?}", control_type),
+            control_type,
+            status,
+            last_check: chrono::Utc::now(),
+            effectiveness: 100,
+        }
+    }
+
+    #[test]
+    fn test_undefended_chain_exfiltrates_and_defenses_catch_it() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        let now = chrono::Utc::now();
+        let mut simulator = AttackSimulator::new();
+        assert!(simulator.due(7, now));
+        assert!(simulator.run("Alice", "web-1", "api", &Defenses::default(), now, &mut rng).exfiltrated());
+        assert!(!simulator.due(7, now + chrono::Duration::days(6)));
+
+        // A failed control sees nothing; firewall deny rules only slow lateral movement
+        let defenses = Defenses {
+            controls: vec![control(ControlType::AccessControl, ControlStatus::Failed), control(ControlType::Monitoring, ControlStatus::Active)],
+            firewall_rules: vec![FirewallRule {
+                id: "fw-1".to_string(),
+                name: "deny-internal".to_string(),
+                source_segment: "dmz".to_string(),
+                destination_segment: "internal".to_string(),
+                port_range: PortRange { start: 0, end: 65535 },
+                protocol: Protocol::Any,
+                action: FirewallAction::Deny,
+                enabled: true,
+            }],
+            advisories: Vec::new(),
+        };
+        assert_eq!(defenses.detection(AttackStage::Phishing).0, 0.5);
+        assert!((defenses.detection(AttackStage::LateralMovement).0 - 0.55).abs() < 1e-9);
+
+        // An advisory for phishing halves what the defenses would miss there
+        let advisory = ThreatAdvisory {
+            id: uuid::Uuid::new_v4(),
+            threat: "FIN-Example".to_string(),
+            stages: vec![AttackStage::Phishing],
+            indicators: vec!["198.51.100.7".to_string()],
+            hosts: vec!["web-1".to_string()],
+            issued_at: now,
+            expires_at: now + chrono::Duration::days(7),
+            detection_boost: 0.5,
+        };
+        let advised = Defenses { advisories: vec![advisory], ..defenses.clone() };
+        assert_eq!(advised.detection(AttackStage::Phishing).0, 0.75);
+        assert_eq!(advised.detection(AttackStage::Exfiltration), defenses.detection(AttackStage::Exfiltration));
+
+        for _ in 0..20 {
+            simulator.run("Alice", "web-1", "api", &defenses, now, &mut rng);
+        }
+        let report = simulator.report();
+        assert_eq!(report.campaigns, 21);
+        assert_eq!(report.detected + report.exfiltrated, 21);
+        assert!(report.detected > 0);
+        assert_eq!(report.stages[0].attempted, 21);
+    }
+}
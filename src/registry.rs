@@ -0,0 +1,178 @@
+//! Agent Registry - Pluggable Agent Implementations per Department
+//!
+//! This module implements the registry the orchestrator builds every agent
+//! from, so new agent implementations need no change to the orchestrator:
+//! - A factory per department that creates new agents and restores them from
+//!   snapshots; the six built-in departments come registered
+//! - Registering a factory for a department replaces the built-in one, or
+//!   staffs a department that has no agents of its own, such as Sales or
+//!   Finance
+//! - Agents of registered implementations snapshot their state as JSON under
+//!   the factory's kind, and are restored by the factory of that kind
+//!
+//! The registry is put together with a builder when the simulation is built:
+//! `AgentRegistry::builtin().register(Department::Sales, factory)`. It is
+//! private to the binary; departments configured under `[scripting]` are
+//! registered through it at startup.
+
+use crate::agents::{AgentTrait, Department};
+use crate::departments::devops::DevOpsAgent;
+use crate::departments::engineering::EngineeringAgent;
+use crate::departments::infosec::InfoSecAgent;
+use crate::departments::marketing::MarketingAgent;
+use crate::departments::networking::NetworkingAgent;
+use crate::departments::ops::OpsAgent;
+use crate::departments::DepartmentAgent;
+use crate::snapshot::PluginSnapshot;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Creates and restores the agents of one implementation
+pub trait AgentFactory: Send + Sync {
+    /// Name saved with the agents' snapshots, e.g. "sales"
+    fn kind(&self) -> &str;
+
+    /// A new agent
+    fn create(&self, name: &str, manager_id: Option<Uuid>) -> Box<dyn DepartmentAgent>;
+
+    /// The agent a snapshot of this kind was taken of
+    fn restore(&self, snapshot: PluginSnapshot) -> Result<Box<dyn DepartmentAgent>, RegistryError>;
+}
+
+/// Factory for agents that are their own serialized state
+pub struct SerdeFactory<A> {
+    kind: String,
+    new: fn(String, Option<Uuid>) -> A,
+}
+
+impl<A> SerdeFactory<A> {
+    pub fn new(kind: &str, new: fn(String, Option<Uuid>) -> A) -> Self {
+        Self { kind: kind.to_string(), new }
+    }
+}
+
+impl<A: DepartmentAgent + DeserializeOwned> AgentFactory for SerdeFactory<A> {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn create(&self, name: &str, manager_id: Option<Uuid>) -> Box<dyn DepartmentAgent> {
+        Box::new((self.new)(name.to_string(), manager_id))
+    }
+
+    fn restore(&self, snapshot: PluginSnapshot) -> Result<Box<dyn DepartmentAgent>, RegistryError> {
+        let agent: A = serde_json::from_value(snapshot.state).map_err(|e| RegistryError::State(snapshot.kind, e))?;
+        Ok(Box::new(agent))
+    }
+}
+
+impl PluginSnapshot {
+    /// Snapshot of a registered agent, for its `DepartmentAgent::snapshot`
+    pub fn of<A: AgentTrait + Serialize>(kind: &str, agent: &A, handled_message_types: &[&str]) -> Self {
+        Self {
+            kind: kind.to_string(),
+            agent: agent.get_agent().clone(),
+            // Agent state is plain data; it always serializes
+            state: serde_json::to_value(agent).unwrap_or_default(),
+            handled_message_types: handled_message_types.iter().map(|message_type| message_type.to_string()).collect(),
+        }
+    }
+}
+
+/// Agent factories by department
+#[derive(Clone, Default)]
+pub struct AgentRegistry {
+    factories: BTreeMap<String, Arc<dyn AgentFactory>>,
+}
+
+impl AgentRegistry {
+    /// A registry without any departments
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in department agents
+    pub fn builtin() -> Self {
+        Self::new()
+            .register(Department::Engineering, SerdeFactory::new("engineering", EngineeringAgent::new))
+            .register(Department::DevOps, SerdeFactory::new("devops", DevOpsAgent::new))
+            .register(Department::InfoSec, SerdeFactory::new("infosec", InfoSecAgent::new))
+            .register(Department::Networking, SerdeFactory::new("networking", NetworkingAgent::new))
+            .register(Department::Ops, SerdeFactory::new("ops", OpsAgent::new))
+            .register(Department::Marketing, SerdeFactory::new("marketing", MarketingAgent::new))
+    }
+
+    /// Build the department's agents with `factory`, replacing any factory
+    /// it had
+    pub fn register(mut self, department: Department, factory: impl AgentFactory + 'static) -> Self {
+        self.factories.insert(department.as_str().to_string(), Arc::new(factory));
+        self
+    }
+
+    /// Whether agents can be created for the department
+    pub fn staffs(&self, department: Department) -> bool {
+        self.factories.contains_key(department.as_str())
+    }
+
+    /// A new agent for the department
+    pub fn create(&self, department: Department, name: &str, manager_id: Option<Uuid>) -> Result<Box<dyn DepartmentAgent>, RegistryError> {
+        let factory = self.factories.get(department.as_str())
+            .ok_or_else(|| RegistryError::NoAgentType(department.as_str().to_string()))?;
+        Ok(factory.create(name, manager_id))
+    }
+
+    /// The agent a registered implementation's snapshot was taken of
+    pub fn restore(&self, snapshot: PluginSnapshot) -> Result<Box<dyn DepartmentAgent>, RegistryError> {
+        let factory = self.factories.values()
+            .find(|factory| factory.kind() == snapshot.kind)
+            .ok_or_else(|| RegistryError::UnknownKind(snapshot.kind.clone()))?;
+        factory.restore(snapshot)
+    }
+}
+
+impl std::fmt::Debug for AgentRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.factories.iter().map(|(department, factory)| (department, factory.kind()))).finish()
+    }
+}
+
+/// Agent creation and restore errors
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    #[error("No agent implementation is registered for the {0} department")]
+    NoAgentType(String),
+
+    #[error("No agent implementation of kind '{0}' is registered to restore the snapshot")]
+    UnknownKind(String),
+
+    #[error("Snapshot state of a '{0}' agent is unreadable: {1}")]
+    State(String, serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::AgentSnapshot;
+
+    #[test]
+    fn test_registered_agents_are_created_and_restored() {
+        let registry = AgentRegistry::builtin();
+        assert!(registry.staffs(Department::Ops));
+        assert!(matches!(registry.create(Department::Sales, "Sam", None), Err(RegistryError::NoAgentType(department)) if department == "Sales"));
+
+        // Ops agents standing in for a Sales implementation
+        let registry = registry.register(Department::Sales, SerdeFactory::new("sales", OpsAgent::new));
+        let agent = registry.create(Department::Sales, "Sam", None).unwrap();
+        assert_eq!(agent.get_agent().name, "Sam");
+
+        let ops = OpsAgent::new("Olivia".to_string(), None);
+        let snapshot = PluginSnapshot::of("sales", &ops, &["support_request"]);
+        let restored = registry.restore(snapshot.clone()).unwrap();
+        assert_eq!(restored.get_agent().id, ops.get_agent().id);
+        assert_eq!(AgentSnapshot::Plugin(snapshot.clone()).handled_message_types(), vec!["support_request"]);
+        assert!(matches!(AgentRegistry::builtin().restore(snapshot), Err(RegistryError::UnknownKind(kind)) if kind == "sales"));
+    }
+}
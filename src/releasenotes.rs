@@ -0,0 +1,260 @@
+//! Release Notes - Changelogs per Deployment
+//!
+//! This module implements release notes for every shipped deployment:
+//! - Notes compiled from the repository commits between the previously
+//!   shipped commit and the deployed one: new work, bug fixes and rollbacks,
+//!   with the projects (tasks) and engineers involved
+//! - A release log kept across snapshots, feeding the customer digest
+//! - A Markdown status page listing releases newest first
+//! - Customer-facing copy that Marketing can polish after the fact
+//!
+//! Notes are compiled when a deployment reaches `Success`, not when it is
+//! requested, so the log only ever lists what customers actually have.
+
+use crate::vcs::{self, CommitKind, Repository, VcsError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Message type asking Marketing to polish customer-facing copy
+pub const POLISH_REQUEST: &str = "polish_release_notes";
+
+/// Message type carrying Marketing's polished copy back
+pub const POLISHED: &str = "release_notes_polished";
+
+/// Metadata key naming the release notes a polish message is about
+pub const NOTES_ID_KEY: &str = "release_notes_id";
+
+/// Releases kept in the log
+const MAX_NOTES: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+    pub id: Uuid,
+    pub deployment_id: Uuid,
+    /// Release tag of the deployed commit, or its short hash
+    pub version: String,
+    pub commit: String,
+    /// Commit shipped before this one; everything since is in the notes
+    pub previous_commit: Option<String>,
+    pub environment: String,
+    pub shipped_at: chrono::DateTime<chrono::Utc>,
+    pub features: Vec<String>,
+    pub fixes: Vec<String>,
+    pub rollbacks: Vec<String>,
+    /// Projects whose work is included
+    pub projects: Vec<Uuid>,
+    pub authors: Vec<String>,
+    /// Customer-facing copy written by Marketing
+    pub customer_summary: Option<String>,
+}
+
+impl ReleaseNotes {
+    /// Compile notes for a deployed commit from the repository history
+    pub fn compile(repository: &Repository, deployment_id: Uuid, commit: &str, environment: &str, previous_commit: Option<&str>, now: chrono::DateTime<chrono::Utc>) -> Result<Self, VcsError> {
+        let commit = repository.resolve(commit)?;
+        let version = repository.tags().iter().rev()
+            .find(|tag| tag.commit == commit)
+            .map(|tag| tag.name.clone())
+            .unwrap_or_else(|| vcs::short(&commit).to_string());
+
+        let mut notes = Self {
+            id: Uuid::new_v4(),
+            deployment_id,
+            version,
+            commit: commit.clone(),
+            previous_commit: previous_commit.map(str::to_string),
+            environment: environment.to_string(),
+            shipped_at: now,
+            features: Vec::new(),
+            fixes: Vec::new(),
+            rollbacks: Vec::new(),
+            projects: Vec::new(),
+            authors: Vec::new(),
+            customer_summary: None,
+        };
+
+        let mut projects = BTreeSet::new();
+        let mut authors = BTreeSet::new();
+        // Oldest first reads naturally in a changelog
+        for entry in repository.commits_between(previous_commit, &commit)?.into_iter().rev() {
+            let subject = entry.message.lines().next().unwrap_or_default().to_string();
+            match &entry.kind {
+                CommitKind::Initial | CommitKind::Merge { .. } => continue,
+                CommitKind::Revert { .. } => notes.rollbacks.push(subject),
+                CommitKind::Change if subject.starts_with("Fix") => notes.fixes.push(subject),
+                CommitKind::Change => notes.features.push(subject),
+            }
+            projects.extend(entry.project_id);
+            authors.insert(entry.author.name.clone());
+        }
+        notes.projects = projects.into_iter().collect();
+        notes.authors = authors.into_iter().collect();
+        Ok(notes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty() && self.fixes.is_empty() && self.rollbacks.is_empty()
+    }
+
+    /// One line for customers: Marketing's copy when there is some
+    pub fn summary(&self) -> String {
+        if let Some(summary) = &self.customer_summary {
+            return summary.clone();
+        }
+        let mut parts = vec![format!("{} improvements", self.features.len()), format!("{} fixes", self.fixes.len())];
+        if !self.rollbacks.is_empty() {
+            parts.push(format!("{} rollbacks", self.rollbacks.len()));
+        }
+        parts.join(", ")
+    }
+
+    /// Fields for report templates
+    pub fn template_context(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": self.version,
+            "date": self.shipped_at.format("%Y-%m-%d").to_string(),
+            "summary": self.summary(),
+            "feature_count": self.features.len(),
+            "fix_count": self.fixes.len(),
+            "rollback_count": self.rollbacks.len(),
+        })
+    }
+
+    /// Section of the status page for this release
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("## {} — {}\n\n{}\n", self.version, self.shipped_at.format("%Y-%m-%d %H:%M UTC"), self.summary());
+        for (heading, entries) in [("New", &self.features), ("Fixed", &self.fixes), ("Rolled back", &self.rollbacks)] {
+            if entries.is_empty() {
+                continue;
+            }
+            markdown.push_str(&format!("\n### {}\n\n", heading));
+            for entry in entries {
+                markdown.push_str(&format!("- {}\n", entry));
+            }
+        }
+        markdown.push_str(&format!("\nDeployed `{}` to {}.\n", vcs::short(&self.commit), self.environment));
+        markdown
+    }
+}
+
+/// Release notes of every shipped deployment, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReleaseLog {
+    notes: Vec<ReleaseNotes>,
+    /// Deployments notes were compiled for, so each ships once
+    shipped: HashSet<Uuid>,
+    /// Most recently shipped commit
+    last_commit: Option<String>,
+}
+
+impl ReleaseLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn notes(&self) -> &[ReleaseNotes] {
+        &self.notes
+    }
+
+    pub fn is_shipped(&self, deployment_id: &Uuid) -> bool {
+        self.shipped.contains(deployment_id)
+    }
+
+    pub fn last_commit(&self) -> Option<&str> {
+        self.last_commit.as_deref()
+    }
+
+    /// Add notes for a shipped deployment
+    pub fn record(&mut self, notes: ReleaseNotes) {
+        self.shipped.insert(notes.deployment_id);
+        self.last_commit = Some(notes.commit.clone());
+        self.notes.push(notes);
+        if self.notes.len() > MAX_NOTES {
+            let excess = self.notes.len() - MAX_NOTES;
+            self.notes.drain(..excess);
+        }
+    }
+
+    /// Remember a deployment that gets no notes, e.g. one that shipped nothing new
+    pub fn skip(&mut self, deployment_id: Uuid) {
+        self.shipped.insert(deployment_id);
+    }
+
+    /// Set Marketing's customer-facing copy; false if the notes are gone
+    pub fn polish(&mut self, notes_id: Uuid, summary: String) -> bool {
+        match self.notes.iter_mut().find(|notes| notes.id == notes_id) {
+            Some(notes) => {
+                notes.customer_summary = Some(summary);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Releases shipped at or after the given time, oldest first
+    pub fn since(&self, start: chrono::DateTime<chrono::Utc>) -> impl Iterator<Item = &ReleaseNotes> {
+        self.notes.iter().filter(move |notes| notes.shipped_at >= start)
+    }
+
+    /// Status page listing every release, newest first
+    pub fn status_page(&self, repository: &str) -> String {
+        let mut page = format!("# {} Release Notes\n", repository);
+        for notes in self.notes.iter().rev() {
+            page.push('\n');
+            page.push_str(&notes.to_markdown());
+        }
+        page
+    }
+
+    /// Write the status page, creating its directory if needed
+    pub fn write_status_page(&self, repository: &str, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.status_page(repository))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs::Signature;
+
+    #[test]
+    fn test_notes_cover_commits_since_last_release() {
+        let start: chrono::DateTime<chrono::Utc> = "2024-03-04T09:00:00Z".parse().unwrap();
+        let author = Signature { id: Uuid::new_v4(), name: "Engineering Agent 1".to_string() };
+        let mut repo = Repository::new("platform", "main", start);
+        let project_id = Uuid::new_v4();
+
+        repo.create_branch("feature/a", "main").unwrap();
+        repo.commit("feature/a", author.clone(), "Add search index", Some(project_id), start).unwrap();
+        repo.commit("feature/a", author.clone(), "Fix empty query crash", Some(project_id), start).unwrap();
+        let first = repo.merge("feature/a", "main", author.clone(), start).unwrap();
+        repo.release(&first, start).unwrap();
+
+        let mut log = ReleaseLog::new();
+        let notes = ReleaseNotes::compile(&repo, Uuid::new_v4(), &first, "production", log.last_commit(), start).unwrap();
+        assert_eq!(notes.version, "v1.0.0");
+        assert_eq!(notes.features, vec!["Add search index".to_string()]);
+        assert_eq!(notes.fixes, vec!["Fix empty query crash".to_string()]);
+        assert_eq!(notes.projects, vec![project_id]);
+        log.record(notes);
+
+        let revert = repo.revert("main", &first, author, start).unwrap();
+        let notes = ReleaseNotes::compile(&repo, Uuid::new_v4(), &revert, "production", log.last_commit(), start).unwrap();
+        assert_eq!(notes.version, vcs::short(&revert));
+        assert!(notes.features.is_empty() && notes.fixes.is_empty());
+        assert_eq!(notes.rollbacks.len(), 1);
+        let notes_id = notes.id;
+        log.record(notes);
+
+        assert!(log.polish(notes_id, "We rolled back last week's search update.".to_string()));
+        let page = log.status_page("platform");
+        assert!(page.find(vcs::short(&revert)).unwrap() < page.find("v1.0.0").unwrap());
+        assert!(page.contains("We rolled back last week's search update."));
+        assert!(page.contains("### Fixed\n\n- Fix empty query crash"));
+    }
+}
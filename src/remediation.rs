@@ -0,0 +1,214 @@
+//! Remediation - Tasks for Serious Findings, Verified by Re-scan
+//!
+//! This module implements how InfoSec sees serious findings through to the end:
+//! - A remediation task for every Critical or High finding, assigned to
+//!   DevOps to patch, due when the severity's remediation deadline runs out
+//! - Tasks move from assigned to fix applied once DevOps patched the
+//!   vulnerability, and are only verified once a re-scan of the asset no
+//!   longer finds it; a fix that did not take sends the task back to DevOps
+//! - Tasks for assets that were retired are dropped
+//!
+//! InfoSec holds the tracker and closes a finding only once its task is
+//! verified.
+
+use crate::config::RemediationDays;
+use crate::departments::infosec::{Severity, Vulnerability};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Closed tasks kept; the oldest go first
+const MAX_CLOSED: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskStatus {
+    /// With DevOps, waiting for a patch
+    Assigned,
+    /// Patched; waiting for the re-scan to confirm it
+    FixApplied,
+    /// The re-scan no longer finds the vulnerability
+    Verified,
+    /// The asset was retired before it was fixed
+    Dropped,
+}
+
+impl TaskStatus {
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TaskStatus::Verified | TaskStatus::Dropped)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationTask {
+    pub id: Uuid,
+    pub vulnerability: String,
+    pub title: String,
+    pub severity: Severity,
+    pub asset: String,
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+    /// When the finding's remediation deadline runs out
+    pub due_at: chrono::DateTime<chrono::Utc>,
+    pub status: TaskStatus,
+    /// DevOps agent whose patch last fixed it
+    pub fixed_by: Option<Uuid>,
+    pub fixed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Re-scans that still found the vulnerability after a patch
+    pub failed_verifications: u32,
+}
+
+impl RemediationTask {
+    pub fn is_overdue(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        !self.status.is_closed() && now > self.due_at
+    }
+}
+
+/// Every remediation task InfoSec opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemediationTracker {
+    /// In the order they were opened
+    tasks: Vec<RemediationTask>,
+}
+
+impl RemediationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tasks(&self) -> &[RemediationTask] {
+        &self.tasks
+    }
+
+    pub fn task(&self, id: Uuid) -> Option<&RemediationTask> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    /// Tasks not yet verified or dropped
+    pub fn open_tasks(&self) -> impl Iterator<Item = &RemediationTask> {
+        self.tasks.iter().filter(|task| !task.status.is_closed())
+    }
+
+    /// Open a task for every Critical or High finding that has none yet;
+    /// returns the new tasks
+    pub fn open(&mut self, findings: &[Vulnerability], days: &RemediationDays, now: chrono::DateTime<chrono::Utc>) -> Vec<RemediationTask> {
+        let mut opened = Vec::new();
+        for finding in findings.iter().filter(|finding| matches!(finding.severity, Severity::Critical | Severity::High)) {
+            if self.active(&finding.id, &finding.affected_system).is_some() {
+                continue;
+            }
+            let deadline = crate::vulnscan::remediation_deadline(days, &finding.severity).unwrap_or_default();
+            let task = RemediationTask {
+                id: Uuid::new_v4(),
+                vulnerability: finding.id.clone(),
+                title: finding.title.clone(),
+                severity: finding.severity.clone(),
+                asset: finding.affected_system.clone(),
+                opened_at: now,
+                due_at: finding.discovered_at + deadline,
+                status: TaskStatus::Assigned,
+                fixed_by: None,
+                fixed_at: None,
+                closed_at: None,
+                failed_verifications: 0,
+            };
+            opened.push(task.clone());
+            self.tasks.push(task);
+        }
+        self.trim();
+        opened
+    }
+
+    /// Mark the tasks for patched vulnerabilities as fixed, to be verified
+    pub fn fix_applied(&mut self, vulnerabilities: &[String], fixed_by: Uuid, now: chrono::DateTime<chrono::Utc>) {
+        for task in self.tasks.iter_mut().filter(|task| task.status == TaskStatus::Assigned && vulnerabilities.contains(&task.vulnerability)) {
+            task.status = TaskStatus::FixApplied;
+            task.fixed_by = Some(fixed_by);
+            task.fixed_at = Some(now);
+        }
+    }
+
+    /// Record a verification re-scan of a finding. A fix that took closes
+    /// the task; one that did not sends it back to DevOps, and the task is
+    /// returned so it can be assigned again.
+    pub fn verify(&mut self, vulnerability: &str, asset: &str, still_present: bool, now: chrono::DateTime<chrono::Utc>) -> Option<&RemediationTask> {
+        let task = self.tasks.iter_mut()
+            .find(|task| task.status == TaskStatus::FixApplied && task.vulnerability == vulnerability && task.asset == asset)?;
+        if still_present {
+            task.status = TaskStatus::Assigned;
+            task.failed_verifications += 1;
+            return Some(task);
+        }
+        task.status = TaskStatus::Verified;
+        task.closed_at = Some(now);
+        None
+    }
+
+    /// Drop the tasks for an asset that is gone
+    pub fn drop_asset(&mut self, asset: &str, now: chrono::DateTime<chrono::Utc>) {
+        for task in self.tasks.iter_mut().filter(|task| !task.status.is_closed() && task.asset == asset) {
+            task.status = TaskStatus::Dropped;
+            task.closed_at = Some(now);
+        }
+    }
+
+    fn active(&self, vulnerability: &str, asset: &str) -> Option<&RemediationTask> {
+        self.open_tasks().find(|task| task.vulnerability == vulnerability && task.asset == asset)
+    }
+
+    fn trim(&mut self) {
+        let closed = self.tasks.iter().filter(|task| task.status.is_closed()).count();
+        if closed > MAX_CLOSED {
+            let mut excess = closed - MAX_CLOSED;
+            self.tasks.retain(|task| {
+                let drop = excess > 0 && task.status.is_closed();
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(id: &str, severity: Severity, discovered_at: chrono::DateTime<chrono::Utc>) -> Vulnerability {
+        Vulnerability {
+            id: id.to_string(),
+            title: "Remote code execution in OpenSSH".to_string(),
+            severity,
+            cvss_score: 9.8,
+            description: String::new(),
+            affected_system: "web-1".to_string(),
+            remediation: "Upgrade OpenSSH".to_string(),
+            discovered_at,
+            last_seen: Some(discovered_at),
+            times_seen: 1,
+        }
+    }
+
+    #[test]
+    fn test_task_is_verified_only_once_the_rescan_is_clean() {
+        let now = chrono::Utc::now();
+        let days = RemediationDays::default();
+        let findings = vec![finding("CVE-2024-00001", Severity::Critical, now), finding("CVE-2024-00002", Severity::Medium, now)];
+        let mut tracker = RemediationTracker::new();
+        let opened = tracker.open(&findings, &days, now);
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].due_at, now + chrono::Duration::days(7));
+        assert!(tracker.open(&findings, &days, now).is_empty());
+
+        // The first patch did not take, so the task goes back to DevOps
+        let devops = Uuid::new_v4();
+        tracker.fix_applied(&["CVE-2024-00001".to_string()], devops, now);
+        assert_eq!(tracker.verify("CVE-2024-00001", "web-1", true, now).map(|task| task.failed_verifications), Some(1));
+        assert_eq!(tracker.task(opened[0].id).unwrap().status, TaskStatus::Assigned);
+        assert!(tracker.task(opened[0].id).unwrap().is_overdue(now + chrono::Duration::days(8)));
+
+        tracker.fix_applied(&["CVE-2024-00001".to_string()], devops, now);
+        assert!(tracker.verify("CVE-2024-00001", "web-1", false, now).is_none());
+        assert_eq!(tracker.task(opened[0].id).unwrap().status, TaskStatus::Verified);
+        assert_eq!(tracker.open_tasks().count(), 0);
+    }
+}
@@ -0,0 +1,502 @@
+//! Company Reports - End-of-Day and End-of-Run Roll-Ups
+//!
+//! This module implements a single structured report of the whole company,
+//! aggregated from a snapshot:
+//! - Operations: every Ops agent's `OpsReport`, with tickets, incidents and
+//!   change outcomes totalled and the worst SLA compliance per tier
+//! - Security: posture score and open vulnerabilities from InfoSec scans,
+//!   and the critical fixes DevOps has yet to patch
+//! - Deployments by status across DevOps
+//! - Backups: the worst recovery point and recovery time across DevOps,
+//!   restore drills run and failed, and systems no drill could restore
+//! - Finances: revenue, cash and this month's profit, customers by tenancy
+//!   and marketing spend per lead
+//! - Customers: satisfaction overall and by tier, the accounts at risk, and
+//!   the status page's open incidents, notices and SLA credits
+//!
+//! Reports are written as JSON next to a Markdown and an HTML rendering of
+//! the `company_report` templates, at the end of every simulated day and
+//! when the simulation stops.
+
+use crate::backup::RecoveryMetrics;
+use crate::departments::devops::DeploymentStatus;
+use crate::departments::ops::{OpsError, OpsReport};
+use crate::snapshot::{AgentSnapshot, SimulationSnapshot};
+use crate::templates::{self, ReportTemplate, TemplatePack, TemplateError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Satisfaction below which a customer is reported as at risk
+const AT_RISK_SATISFACTION: f64 = 50.0;
+
+/// When a company report is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// Midnight on the simulated clock
+    EndOfDay,
+    /// The simulation stopped
+    EndOfRun,
+}
+
+/// State of the whole company at a point in simulated time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyReport {
+    pub kind: ReportKind,
+    /// Simulated time the report was taken at
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub total_steps: u64,
+    /// Agents by department name
+    pub agents: BTreeMap<String, usize>,
+    pub operations: OperationsSection,
+    pub security: SecuritySection,
+    pub deployments: DeploymentSection,
+    #[serde(default)]
+    pub backups: RecoveryMetrics,
+    pub finances: FinanceSection,
+    #[serde(default)]
+    pub customers: CustomerSection,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OperationsSection {
+    pub total_tickets: u32,
+    pub open_tickets: u32,
+    pub closed_tickets: u32,
+    pub active_incidents: u32,
+    pub sev1_incidents: u32,
+    /// Lowest uptime any Ops agent measured for each service, in percent
+    pub sla_compliance: BTreeMap<String, f32>,
+    pub upcoming_changes: Vec<String>,
+    /// Changes carried out, and of those the ones rolled back or failed
+    /// beyond rollback
+    #[serde(default)]
+    pub changes_completed: u32,
+    #[serde(default)]
+    pub changes_rolled_back: u32,
+    #[serde(default)]
+    pub changes_failed: u32,
+    /// Each Ops agent's own report
+    pub reports: Vec<OpsReport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecuritySection {
+    /// Lowest overall posture score across InfoSec agents
+    pub posture_score: Option<u8>,
+    /// Unremediated vulnerabilities by severity
+    pub open_vulnerabilities: BTreeMap<String, usize>,
+    pub active_incidents: usize,
+    /// Critical fixes pending on DevOps servers
+    #[serde(default)]
+    pub unpatched_critical: u32,
+    /// Open vulnerabilities past their remediation deadline
+    #[serde(default)]
+    pub overdue_vulnerabilities: usize,
+    /// Days the oldest open vulnerability has been open
+    #[serde(default)]
+    pub oldest_open_days: Option<i64>,
+    /// Remediation tasks DevOps has yet to get verified
+    #[serde(default)]
+    pub open_remediation_tasks: usize,
+    /// Of those, the ones past their due date
+    #[serde(default)]
+    pub overdue_remediation_tasks: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeploymentSection {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub rolled_back: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinanceSection {
+    pub monthly_revenue: f64,
+    /// Cash in the bank, for snapshots that track the economy
+    pub cash: Option<f64>,
+    /// Profit so far this simulated month
+    pub month_profit: Option<f64>,
+    pub shared_customers: usize,
+    pub dedicated_customers: usize,
+    pub marketing_spend: f64,
+    pub marketing_leads: u32,
+    pub cost_per_lead: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomerSection {
+    pub average_satisfaction: Option<f64>,
+    /// Mean satisfaction by tier
+    pub satisfaction_by_tier: BTreeMap<String, f64>,
+    pub active_projects: usize,
+    /// Customers below the at-risk satisfaction, least satisfied first
+    pub at_risk: Vec<CustomerStanding>,
+    /// Incidents on the status page not yet resolved
+    #[serde(default)]
+    pub status_incidents: usize,
+    #[serde(default)]
+    pub notices_sent: u64,
+    /// SLA credits owed so far
+    #[serde(default)]
+    pub sla_credits: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerStanding {
+    pub id: String,
+    pub name: String,
+    pub tier: String,
+    pub satisfaction: f64,
+    pub active_projects: usize,
+}
+
+impl CompanyReport {
+    /// Aggregate every department's state in a snapshot
+    pub async fn from_snapshot(snapshot: &SimulationSnapshot, kind: ReportKind) -> Result<Self, ReportError> {
+        let mut agents = BTreeMap::new();
+        let mut operations = OperationsSection::default();
+        let mut security = SecuritySection::default();
+        let mut deployments = DeploymentSection::default();
+        let mut backups = RecoveryMetrics::default();
+
+        for agent in snapshot.agents.values() {
+            *agents.entry(agent.agent().department.as_str().to_string()).or_insert(0) += 1;
+            match agent {
+                AgentSnapshot::Ops(ops) => operations.add(ops.generate_report().await.map_err(ReportError::Ops)?),
+                AgentSnapshot::InfoSec(infosec) => {
+                    let score = infosec.security_posture.overall_score;
+                    security.posture_score = Some(security.posture_score.map_or(score, |lowest| lowest.min(score)));
+                    for vulnerability in &infosec.open_vulnerabilities {
+                        *security.open_vulnerabilities.entry(format!("{:?}", vulnerability.severity)).or_insert(0) += 1;
+                    }
+                    security.active_incidents += infosec.active_incidents.len();
+                    security.unpatched_critical = security.unpatched_critical.max(infosec.security_posture.unpatched_critical);
+                    let aging = infosec.aging(snapshot.simulated_now());
+                    security.overdue_vulnerabilities += aging.overdue;
+                    security.oldest_open_days = security.oldest_open_days.max(aging.oldest_open_days);
+                    for task in infosec.remediation_tasks.open_tasks() {
+                        security.open_remediation_tasks += 1;
+                        if task.is_overdue(snapshot.simulated_now()) {
+                            security.overdue_remediation_tasks += 1;
+                        }
+                    }
+                }
+                AgentSnapshot::DevOps(devops) => {
+                    for deployment in devops.active_deployments.values() {
+                        let count = match deployment.status {
+                            DeploymentStatus::Pending => &mut deployments.pending,
+                            DeploymentStatus::InProgress => &mut deployments.in_progress,
+                            DeploymentStatus::Success => &mut deployments.succeeded,
+                            DeploymentStatus::Failed => &mut deployments.failed,
+                            DeploymentStatus::RolledBack => &mut deployments.rolled_back,
+                        };
+                        *count += 1;
+                    }
+                    backups.combine(devops.recovery_metrics(snapshot.simulated_now()));
+                }
+                _ => {}
+            }
+        }
+
+        let (shared_customers, dedicated_customers) = snapshot.catalog.tenancy_counts();
+        let marketing_spend: f64 = snapshot.campaign_reports.values().map(|r| r.total_spend).sum();
+        let marketing_leads: u32 = snapshot.campaign_reports.values().map(|r| r.total_leads).sum();
+
+        let mut customers = CustomerSection {
+            average_satisfaction: snapshot.catalog.average_satisfaction(),
+            status_incidents: snapshot.status_page.active().count(),
+            notices_sent: snapshot.status_page.notices_sent,
+            sla_credits: snapshot.status_page.credited,
+            ..CustomerSection::default()
+        };
+        let mut tiers: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for customer in snapshot.catalog.customers.values() {
+            tiers.entry(format!("{:?}", customer.tier)).or_default().push(customer.satisfaction);
+            customers.active_projects += customer.active_projects.len();
+            if customer.satisfaction < AT_RISK_SATISFACTION {
+                customers.at_risk.push(CustomerStanding {
+                    id: customer.id.clone(),
+                    name: customer.name.clone(),
+                    tier: format!("{:?}", customer.tier),
+                    satisfaction: customer.satisfaction,
+                    active_projects: customer.active_projects.len(),
+                });
+            }
+        }
+        customers.satisfaction_by_tier = tiers.into_iter()
+            .map(|(tier, scores)| (tier, scores.iter().sum::<f64>() / scores.len() as f64))
+            .collect();
+        customers.at_risk.sort_by(|a, b| a.satisfaction.total_cmp(&b.satisfaction).then_with(|| a.id.cmp(&b.id)));
+
+        Ok(Self {
+            kind,
+            generated_at: snapshot.simulated_now(),
+            total_steps: snapshot.step_count,
+            agents,
+            operations,
+            security,
+            deployments,
+            backups,
+            finances: FinanceSection {
+                monthly_revenue: snapshot.catalog.monthly_revenue(),
+                cash: snapshot.economy.as_ref().map(|economy| economy.cash),
+                month_profit: snapshot.economy.as_ref()
+                    .and_then(|economy| economy.statement(snapshot.simulated_now()))
+                    .map(|statement| statement.profit()),
+                shared_customers,
+                dedicated_customers,
+                marketing_spend,
+                marketing_leads,
+                cost_per_lead: (marketing_leads > 0).then(|| marketing_spend / marketing_leads as f64),
+            },
+            customers,
+        })
+    }
+
+    /// What the report covers, e.g. "2024-03-04" or "run ending 2024-03-04 15:30"
+    pub fn title(&self) -> String {
+        match self.kind {
+            // Written at midnight, so the day covered is the one just ended
+            ReportKind::EndOfDay => (self.generated_at - chrono::Duration::seconds(1)).format("%Y-%m-%d").to_string(),
+            ReportKind::EndOfRun => format!("run ending {}", self.generated_at.format("%Y-%m-%d %H:%M")),
+        }
+    }
+
+    /// File name, without extension, the report is written under
+    pub fn file_stem(&self) -> String {
+        match self.kind {
+            ReportKind::EndOfDay => format!("company-day-{}", self.title()),
+            ReportKind::EndOfRun => format!("company-run-{}", self.generated_at.format("%Y-%m-%d-%H%M")),
+        }
+    }
+
+    /// Values available to the company report templates
+    pub fn template_context(&self) -> serde_json::Value {
+        let finances = &self.finances;
+        serde_json::json!({
+            "title": self.title(),
+            "end_of_run": self.kind == ReportKind::EndOfRun,
+            "generated_at": self.generated_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            "total_steps": self.total_steps,
+            "total_agents": self.agents.values().sum::<usize>(),
+            "departments": self.agents.iter()
+                .map(|(name, count)| serde_json::json!({ "name": name, "agents": count }))
+                .collect::<Vec<_>>(),
+            "operations": &self.operations,
+            "sla_compliance": self.operations.sla_compliance.iter()
+                .map(|(tier, compliance)| serde_json::json!({ "tier": tier, "percent": format!("{:.2}", compliance) }))
+                .collect::<Vec<_>>(),
+            "security": {
+                "posture_score": self.security.posture_score,
+                "active_incidents": self.security.active_incidents,
+                "unpatched_critical": self.security.unpatched_critical,
+                "overdue_vulnerabilities": self.security.overdue_vulnerabilities,
+                "oldest_open_days": self.security.oldest_open_days,
+                "open_remediation_tasks": self.security.open_remediation_tasks,
+                "overdue_remediation_tasks": self.security.overdue_remediation_tasks,
+                "has_vulnerabilities": !self.security.open_vulnerabilities.is_empty(),
+                "vulnerabilities": self.security.open_vulnerabilities.iter()
+                    .map(|(severity, count)| serde_json::json!({ "severity": severity, "count": count }))
+                    .collect::<Vec<_>>(),
+            },
+            "deployments": &self.deployments,
+            "backups": {
+                "systems": self.backups.systems,
+                "backups": self.backups.backups,
+                "rpo_hours": self.backups.rpo_minutes.map(|minutes| format!("{:.1}", minutes as f64 / 60.0)),
+                "rto_hours": self.backups.rto_minutes.map(|minutes| format!("{:.1}", minutes as f64 / 60.0)),
+                "drills": self.backups.drills,
+                "failed_drills": self.backups.failed_drills,
+                "unrestorable": &self.backups.unrestorable,
+            },
+            "finances": {
+                "monthly_revenue": format!("{:.2}", finances.monthly_revenue),
+                "cash": finances.cash.map(|cash| format!("{:.2}", cash)),
+                "month_profit": finances.month_profit.map(|profit| format!("{:.2}", profit)),
+                "shared_customers": finances.shared_customers,
+                "dedicated_customers": finances.dedicated_customers,
+                "marketing_spend": format!("{:.2}", finances.marketing_spend),
+                "marketing_leads": finances.marketing_leads,
+                "cost_per_lead": finances.cost_per_lead.map(|cost| format!("{:.2}", cost)),
+            },
+            "customers": {
+                "average_satisfaction": self.customers.average_satisfaction.map(|score| format!("{:.1}", score)),
+                "active_projects": self.customers.active_projects,
+                "tiers": self.customers.satisfaction_by_tier.iter()
+                    .map(|(tier, score)| serde_json::json!({ "tier": tier, "satisfaction": format!("{:.1}", score) }))
+                    .collect::<Vec<_>>(),
+                "has_at_risk": !self.customers.at_risk.is_empty(),
+                "at_risk": self.customers.at_risk.iter()
+                    .map(|customer| serde_json::json!({
+                        "name": customer.name,
+                        "tier": customer.tier,
+                        "satisfaction": format!("{:.1}", customer.satisfaction),
+                        "active_projects": customer.active_projects,
+                    }))
+                    .collect::<Vec<_>>(),
+                "status_incidents": self.customers.status_incidents,
+                "notices_sent": self.customers.notices_sent,
+                "sla_credits": format!("{:.2}", self.customers.sla_credits),
+            },
+        })
+    }
+
+    /// Write the report as JSON, Markdown and HTML; returns the JSON path
+    pub fn write(&self, dir: &Path, templates: &TemplatePack) -> Result<PathBuf, ReportError> {
+        std::fs::create_dir_all(dir).map_err(|e| ReportError::Io(dir.to_path_buf(), e))?;
+        let path = dir.join(format!("{}.json", self.file_stem()));
+        let json = serde_json::to_string_pretty(self).map_err(ReportError::Json)?;
+        std::fs::write(&path, json).map_err(|e| ReportError::Io(path.clone(), e))?;
+
+        let context = self.template_context();
+        for (report, context) in [
+            (ReportTemplate::CompanyReport, context.clone()),
+            (ReportTemplate::CompanyReportPage, templates::escape_html(&context)),
+        ] {
+            let rendered = templates.render(report, &context).map_err(ReportError::Template)?;
+            let rendered_path = path.with_extension(templates.extension(report));
+            std::fs::write(&rendered_path, rendered).map_err(|e| ReportError::Io(rendered_path.clone(), e))?;
+        }
+        Ok(path)
+    }
+}
+
+impl OperationsSection {
+    fn add(&mut self, report: OpsReport) {
+        let tickets = &report.ticket_summary;
+        self.total_tickets += tickets.total_tickets;
+        self.open_tickets += tickets.open_tickets;
+        self.closed_tickets += tickets.closed_tickets;
+        self.active_incidents += report.incident_summary.active_incidents;
+        self.sev1_incidents += report.incident_summary.sev1_incidents;
+        self.changes_completed += report.change_summary.completed;
+        self.changes_rolled_back += report.change_summary.rolled_back;
+        self.changes_failed += report.change_summary.failed;
+        for (tier, compliance) in &report.sla_compliance {
+            let lowest = self.sla_compliance.entry(tier.clone()).or_insert(*compliance);
+            *lowest = lowest.min(*compliance);
+        }
+        for change in &report.upcoming_changes {
+            if !self.upcoming_changes.contains(change) {
+                self.upcoming_changes.push(change.clone());
+            }
+        }
+        self.reports.push(report);
+    }
+}
+
+/// Company report errors
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("Ops report failed: {0}")]
+    Ops(OpsError),
+
+    #[error("Failed to write company report {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Failed to serialize company report: {0}")]
+    Json(serde_json::Error),
+
+    #[error("Failed to render company report: {0}")]
+    Template(TemplateError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::ServiceCatalog;
+    use crate::config::SimulationConfig;
+    use crate::departments::ops::{CustomerTier, OpsAgent, Priority, TicketRequest};
+    use crate::departments::DepartmentAgent;
+    use crate::scheduler::Scheduler;
+    use crate::snapshot::SNAPSHOT_VERSION;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_company_report_aggregates_and_renders() {
+        let mut ops = OpsAgent::new("Ops".to_string(), None);
+        ops.create_ticket(TicketRequest {
+            title: "Cannot log in".to_string(),
+            description: String::new(),
+            priority: Priority::High,
+            customer_id: None,
+            customer_tier: CustomerTier::Standard,
+            tags: vec![],
+        }).await.unwrap();
+        let midnight: chrono::DateTime<chrono::Utc> = "2024-03-05T00:00:00Z".parse().unwrap();
+        let mut catalog = ServiceCatalog::new(vec![]);
+        let unhappy = catalog.onboard_customer("Acme", CustomerTier::Premium, crate::catalog::Tenancy::Shared);
+        catalog.customers.get_mut(&unhappy).unwrap().satisfaction = 30.0;
+        catalog.onboard_customer("Globex", CustomerTier::Premium, crate::catalog::Tenancy::Shared);
+        let snapshot = SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now(),
+            step_count: 96,
+            config: SimulationConfig::default(),
+            agents: HashMap::from([(ops.get_agent().id, ops.snapshot())]),
+            projects: HashMap::new(),
+            pending_messages: vec![],
+            campaign_reports: HashMap::new(),
+            catalog,
+            message_log: vec![],
+            scheduler: Some(Scheduler::new(midnight)),
+            router: crate::routing::MessageRouter::new(),
+            intake: crate::intake::IntakeController::new(),
+            wait_graph: crate::waitgraph::WaitForGraph::new(),
+            kpi_history: crate::analytics::KpiHistory::new(),
+            entity_tracker: crate::webhooks::EntityTracker::new(),
+            webhooks: crate::webhooks::WebhookDispatcher::new(),
+            repository: None,
+            supervisor: crate::supervisor::Supervisor::new(),
+            release_notes: crate::releasenotes::ReleaseLog::new(),
+            scorecards: crate::scorecard::ScorecardBook::new(),
+            quotas: crate::quota::QuotaLedger::new(),
+            task_graph: crate::taskgraph::TaskGraph::new(),
+            risks: crate::risk::RiskRegister::new(),
+            progression: crate::progression::SkillProgression::new(),
+            org: crate::orgchart::OrgChart::new(),
+            executive: crate::executive::ExecutiveAgent::new(),
+            staffing: crate::staffing::StaffingLog::new(),
+            economy: None,
+            knowledge: crate::knowledge::KnowledgeBase::new(),
+            pipelines: crate::pipeline::BuildQueue::new(),
+            promotions: crate::promotion::Promotions::new(),
+            alerts: crate::alerting::AlertEngine::new(),
+            red_team: crate::redteam::AttackSimulator::new(),
+            iam: crate::iam::IdentityDirectory::new(),
+            phishing: crate::phishing::AwarenessProgram::new(),
+            threat_intel: crate::threatintel::ThreatIntel::new(),
+            dlp: crate::dlp::DlpMonitor::new(),
+            ticket_queue: crate::ticketqueue::TicketQueue::new(),
+            capacity: crate::capacity::CapacityPlanner::new(),
+            status_page: crate::statuspage::StatusPage::new(),
+            shift_roster: crate::shifts::ShiftRoster::new(),
+            agent_states: crate::agentstate::AgentStates::new(),
+            memories: crate::memory::AgentMemories::new(),
+            manager_reports: crate::deptreport::ManagerReports::new(),
+            threads: crate::threads::ConversationThreads::new(),
+        };
+
+        let report = CompanyReport::from_snapshot(&snapshot, ReportKind::EndOfDay).await.unwrap();
+        assert_eq!(report.operations.total_tickets, 1);
+        assert_eq!(report.operations.reports.len(), 1);
+        assert_eq!(report.agents.get("Ops"), Some(&1));
+        assert_eq!(report.file_stem(), "company-day-2024-03-04");
+        assert_eq!(report.customers.at_risk.len(), 1);
+        assert_eq!(report.customers.satisfaction_by_tier.get("Premium"), Some(&52.5));
+
+        let dir = std::env::temp_dir().join(format!("aivertco-company-{}", uuid::Uuid::new_v4()));
+        let path = report.write(&dir, &TemplatePack::builtin()).unwrap();
+        let markdown = std::fs::read_to_string(path.with_extension("md")).unwrap();
+        assert!(markdown.contains("# AIvertCo Company Report 2024-03-04"));
+        assert!(markdown.contains("- Tickets: 1 total"));
+        assert!(markdown.contains("- At risk: Acme (Premium) at 30.0/100"));
+        assert!(std::fs::read_to_string(path.with_extension("html")).unwrap().contains("<td>1</td>"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+//! Risk Register - Company-Level Risks & Quarterly Executive Review
+//!
+//! This module implements the executive risk register:
+//! - Risks gathered from every department: late projects, open security
+//!   findings, compliance gaps, failing vendors and single points of failure
+//! - A likelihood × impact score per risk, refreshed as its source changes
+//! - Risks closed as mitigated once their source goes away
+//! - A quarterly review where department managers, as the executive layer,
+//!   take ownership of the risks above the bar with a mitigation due date
+//!
+//! The register only keeps the books; the orchestrator collects the findings
+//! from departments, holds the review and names the owners.
+
+use crate::config::RiskSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Reviews kept in the register
+const MAX_REVIEWS: usize = 8;
+
+/// Risks listed as the top of a review
+const TOP_RISKS: usize = 5;
+
+/// Where a risk comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RiskCategory {
+    /// Project running behind its estimates
+    Project,
+    /// Open vulnerabilities on a system
+    Security,
+    /// Compliance standard below target or with open issues
+    Compliance,
+    /// External provider failing deliveries
+    Vendor,
+    /// Work or capability resting on a single task or agent
+    SinglePointOfFailure,
+}
+
+impl RiskCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskCategory::Project => "project",
+            RiskCategory::Security => "security",
+            RiskCategory::Compliance => "compliance",
+            RiskCategory::Vendor => "vendor",
+            RiskCategory::SinglePointOfFailure => "single point of failure",
+        }
+    }
+
+    /// What the owner is asked to do
+    pub fn mitigation(&self, title: &str) -> String {
+        match self {
+            RiskCategory::Project => format!("Re-plan or add staff: {}", title),
+            RiskCategory::Security => format!("Remediate findings: {}", title),
+            RiskCategory::Compliance => format!("Close compliance gap: {}", title),
+            RiskCategory::Vendor => format!("Review vendor or add a fallback: {}", title),
+            RiskCategory::SinglePointOfFailure => format!("Add redundancy: {}", title),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskStatus {
+    /// Identified, nobody has taken it on
+    Open,
+    /// Owned, mitigation under way
+    Mitigating,
+    /// Source went away
+    Mitigated,
+}
+
+/// Risk as a department reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskFinding {
+    /// Stable key of the source, e.g. "security:database"
+    pub key: String,
+    pub category: RiskCategory,
+    pub title: String,
+    /// Department the risk comes from, whose manager owns it
+    pub department: String,
+    /// 1 (rare) to 5 (almost certain)
+    pub likelihood: u8,
+    /// 1 (minor) to 5 (severe)
+    pub impact: u8,
+}
+
+/// Entry of the register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Risk {
+    pub key: String,
+    pub category: RiskCategory,
+    pub title: String,
+    pub department: String,
+    pub likelihood: u8,
+    pub impact: u8,
+    pub status: RiskStatus,
+    /// Executive accountable for the mitigation
+    pub owner: Option<Uuid>,
+    pub mitigation: Option<String>,
+    pub mitigation_due: Option<chrono::DateTime<chrono::Utc>>,
+    pub identified_at: chrono::DateTime<chrono::Utc>,
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Risk {
+    /// Likelihood × impact, 1-25
+    pub fn score(&self) -> u8 {
+        self.likelihood * self.impact
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status != RiskStatus::Mitigated
+    }
+
+    /// Owned but still open after its mitigation was due
+    pub fn is_overdue(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.is_open() && self.mitigation_due.is_some_and(|due| due < now)
+    }
+}
+
+/// Outcome of a quarterly risk review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReview {
+    pub held_at: chrono::DateTime<chrono::Utc>,
+    /// Open risks by category name
+    pub open_by_category: BTreeMap<String, usize>,
+    /// Keys of the highest-scoring open risks
+    pub top_risks: Vec<String>,
+    /// Keys of the risks given an owner at this review
+    pub assigned: Vec<String>,
+    /// Keys of the owned risks past their mitigation date
+    pub overdue: Vec<String>,
+    /// Risks mitigated since the previous review
+    pub mitigated: usize,
+}
+
+/// Every risk the company knows of, by key, and the reviews held
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskRegister {
+    risks: BTreeMap<String, Risk>,
+    reviews: Vec<RiskReview>,
+}
+
+impl RiskRegister {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn risk(&self, key: &str) -> Option<&Risk> {
+        self.risks.get(key)
+    }
+
+    /// Risks not yet mitigated
+    pub fn open(&self) -> impl Iterator<Item = &Risk> {
+        self.risks.values().filter(|risk| risk.is_open())
+    }
+
+    pub fn latest_review(&self) -> Option<&RiskReview> {
+        self.reviews.last()
+    }
+
+    /// Bring the register in line with the latest findings; returns the keys
+    /// of newly identified risks
+    ///
+    /// Open risks missing from the findings are closed as mitigated, and a
+    /// mitigated risk that shows up again is reopened without an owner.
+    pub fn refresh(&mut self, findings: Vec<RiskFinding>, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+        let mut identified = Vec::new();
+        let mut seen = Vec::with_capacity(findings.len());
+        for finding in findings {
+            seen.push(finding.key.clone());
+            match self.risks.get_mut(&finding.key) {
+                Some(risk) if risk.is_open() => {
+                    risk.title = finding.title;
+                    risk.likelihood = finding.likelihood.clamp(1, 5);
+                    risk.impact = finding.impact.clamp(1, 5);
+                }
+                _ => {
+                    identified.push(finding.key.clone());
+                    self.risks.insert(finding.key.clone(), Risk {
+                        key: finding.key,
+                        category: finding.category,
+                        title: finding.title,
+                        department: finding.department,
+                        likelihood: finding.likelihood.clamp(1, 5),
+                        impact: finding.impact.clamp(1, 5),
+                        status: RiskStatus::Open,
+                        owner: None,
+                        mitigation: None,
+                        mitigation_due: None,
+                        identified_at: now,
+                        closed_at: None,
+                    });
+                }
+            }
+        }
+
+        for risk in self.risks.values_mut().filter(|risk| risk.is_open() && !seen.contains(&risk.key)) {
+            risk.status = RiskStatus::Mitigated;
+            risk.closed_at = Some(now);
+        }
+        identified
+    }
+
+    /// Hold the quarterly review: refresh from the findings and give every
+    /// open risk at or above the bar to its department's executive
+    ///
+    /// Risks whose owner is no longer an executive are handed over too.
+    pub fn review(
+        &mut self,
+        findings: Vec<RiskFinding>,
+        executives: &BTreeMap<String, Uuid>,
+        settings: &RiskSettings,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> &RiskReview {
+        let since = self.reviews.last().map(|review| review.held_at);
+        self.refresh(findings, now);
+
+        let mut assigned = Vec::new();
+        for risk in self.risks.values_mut().filter(|risk| risk.is_open() && risk.score() >= settings.owner_threshold) {
+            let owned = risk.owner.is_some_and(|owner| executives.values().any(|executive| *executive == owner));
+            if owned {
+                continue;
+            }
+            let Some(executive) = executives.get(&risk.department) else {
+                continue;
+            };
+            risk.owner = Some(*executive);
+            risk.status = RiskStatus::Mitigating;
+            risk.mitigation = Some(risk.category.mitigation(&risk.title));
+            risk.mitigation_due.get_or_insert(now + chrono::Duration::days(settings.mitigation_days));
+            assigned.push(risk.key.clone());
+        }
+
+        let mut open: Vec<&Risk> = self.open().collect();
+        open.sort_by(|a, b| b.score().cmp(&a.score()).then(a.key.cmp(&b.key)));
+        let mut open_by_category = BTreeMap::new();
+        for risk in &open {
+            *open_by_category.entry(risk.category.as_str().to_string()).or_insert(0) += 1;
+        }
+        let mitigated = self.risks.values()
+            .filter(|risk| risk.closed_at.is_some_and(|closed| since.is_none_or(|since| closed > since)))
+            .count();
+
+        self.reviews.push(RiskReview {
+            held_at: now,
+            open_by_category,
+            top_risks: open.iter().take(TOP_RISKS).map(|risk| risk.key.clone()).collect(),
+            assigned,
+            overdue: open.iter().filter(|risk| risk.is_overdue(now)).map(|risk| risk.key.clone()).collect(),
+            mitigated,
+        });
+        if self.reviews.len() > MAX_REVIEWS {
+            self.reviews.remove(0);
+        }
+        self.reviews.last().expect("review just pushed")
+    }
+
+    /// Drop mitigated risks closed before `cutoff`
+    pub fn prune_mitigated(&mut self, cutoff: chrono::DateTime<chrono::Utc>) -> usize {
+        let before = self.risks.len();
+        self.risks.retain(|_, risk| risk.closed_at.is_none_or(|closed| closed >= cutoff));
+        before - self.risks.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(key: &str, category: RiskCategory, department: &str, likelihood: u8, impact: u8) -> RiskFinding {
+        RiskFinding {
+            key: key.to_string(),
+            category,
+            title: key.to_string(),
+            department: department.to_string(),
+            likelihood,
+            impact,
+        }
+    }
+
+    #[test]
+    fn test_quarterly_review_assigns_owners_and_tracks_mitigation() {
+        let now: chrono::DateTime<chrono::Utc> = "2024-04-01T10:00:00Z".parse().unwrap();
+        let settings = RiskSettings { owner_threshold: 9, mitigation_days: 30 };
+        let cso = Uuid::new_v4();
+        let executives = BTreeMap::from([("InfoSec".to_string(), cso)]);
+        let mut register = RiskRegister::new();
+
+        let findings = vec![
+            finding("security:database", RiskCategory::Security, "InfoSec", 4, 4),
+            finding("compliance:SOC 2", RiskCategory::Compliance, "InfoSec", 2, 3),
+            finding("vendor:email", RiskCategory::Vendor, "Marketing", 3, 3),
+        ];
+        let review = register.review(findings, &executives, &settings, now).clone();
+        assert_eq!(review.top_risks[0], "security:database");
+        // Below the bar, and no Marketing executive to own the vendor risk
+        assert_eq!(review.assigned, vec!["security:database".to_string()]);
+        let database = register.risk("security:database").unwrap();
+        assert_eq!((database.owner, database.status, database.score()), (Some(cso), RiskStatus::Mitigating, 16));
+
+        // The database is patched; the SOC 2 gap outlives the next quarter
+        let later = now + chrono::Duration::days(91);
+        let findings = vec![
+            finding("compliance:SOC 2", RiskCategory::Compliance, "InfoSec", 4, 3),
+            finding("vendor:email", RiskCategory::Vendor, "Marketing", 3, 3),
+        ];
+        let review = register.review(findings, &executives, &settings, later).clone();
+        assert_eq!(register.risk("security:database").unwrap().status, RiskStatus::Mitigated);
+        assert_eq!((review.mitigated, review.assigned), (1, vec!["compliance:SOC 2".to_string()]));
+        assert!(review.overdue.is_empty());
+        assert!(register.risk("compliance:SOC 2").unwrap().is_overdue(later + chrono::Duration::days(31)));
+        assert_eq!(register.open().count(), 2);
+    }
+}
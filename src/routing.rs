@@ -0,0 +1,365 @@
+//! Message Routing - Per-Agent Inboxes & Delivery Acknowledgements
+//!
+//! This module implements delivering bus traffic to agents:
+//! - Every dispatched message is queued in its recipient's inbox
+//! - Each step delivers a batch per inbox into `process_message`, most urgent
+//!   priority first and in arrival order within a priority
+//! - Messages passed over for too many batches jump the queue, so a steady
+//!   stream of urgent traffic cannot starve low-priority messages
+//! - Each delivery attempt is acknowledged as delivered, failed or
+//!   undeliverable so senders and reports can see what happened
+//! - Failed deliveries are retried with exponential backoff and end up in a
+//!   dead-letter queue once their retries are used up
+
+use crate::communication::{Message, MessagePriority};
+use crate::config::DeliverySettings;
+use crate::departments::AgentError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Number of acknowledgements kept for lookups
+const ACK_LOG_CAPACITY: usize = 5_000;
+
+/// Metadata key asking for a `delivery_ack` message back to the sender
+pub const ACK_REQUESTED_KEY: &str = "ack_requested";
+
+/// Outcome of a delivery attempt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DeliveryStatus {
+    /// Waiting in the recipient's inbox
+    Queued,
+    /// Processed by the recipient
+    Delivered,
+    /// The recipient returned an error while processing
+    Failed(#[serde(deserialize_with = "crate::departments::agent_error::deserialize")] AgentError),
+    /// No agent with the recipient id exists
+    Undeliverable,
+    /// A failed delivery will be attempted again
+    Retrying {
+        attempt: u32,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+    },
+    /// Retries are used up; the message is in the dead-letter queue
+    DeadLettered,
+}
+
+/// Acknowledgement of a single message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAck {
+    pub message_id: Uuid,
+    pub recipient: Uuid,
+    pub status: DeliveryStatus,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Running delivery counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliveryStats {
+    pub delivered: u64,
+    pub failed: u64,
+    pub undeliverable: u64,
+    #[serde(default)]
+    pub retried: u64,
+    #[serde(default)]
+    pub dead_lettered: u64,
+}
+
+/// Message that could not be delivered after all retries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub message: Message,
+    /// Outcome of the final delivery attempt
+    pub reason: DeliveryStatus,
+    pub attempts: u32,
+    pub dead_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Failed message waiting for its next delivery attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingRetry {
+    message: Message,
+    next_attempt_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What happens to a message after a failed delivery attempt
+#[derive(Debug, Clone, PartialEq)]
+pub enum FailureOutcome {
+    Retry { attempt: u32, next_attempt_at: chrono::DateTime<chrono::Utc> },
+    DeadLettered,
+}
+
+/// Delivery order of a priority; priorities above High all rank as critical
+fn priority_rank(priority: &MessagePriority) -> u8 {
+    match priority {
+        MessagePriority::Low => 0,
+        MessagePriority::Normal => 1,
+        MessagePriority::High => 2,
+        _ => 3,
+    }
+}
+
+/// Per-agent inboxes and delivery bookkeeping
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageRouter {
+    inboxes: HashMap<Uuid, VecDeque<Message>>,
+    acks: VecDeque<DeliveryAck>,
+    pub stats: DeliveryStats,
+    /// Failed delivery attempts per message still being retried
+    #[serde(default)]
+    attempts: HashMap<Uuid, u32>,
+    #[serde(default)]
+    retries: Vec<PendingRetry>,
+    #[serde(default)]
+    dead_letters: VecDeque<DeadLetter>,
+    /// Batches each queued message has been left out of
+    #[serde(default)]
+    passed_over: HashMap<Uuid, u32>,
+}
+
+impl MessageRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message in its recipient's inbox
+    pub fn enqueue(&mut self, message: Message) {
+        self.record(message.id, message.to_agent, DeliveryStatus::Queued);
+        self.inboxes.entry(message.to_agent).or_default().push_back(message);
+    }
+
+    /// Take the next batch of messages for a recipient
+    ///
+    /// Messages passed over `max_passed_over` times come first, oldest
+    /// first; the rest of the batch is filled by priority, then arrival.
+    pub fn take_batch(&mut self, recipient: Uuid, settings: &DeliverySettings) -> Vec<Message> {
+        let Some(inbox) = self.inboxes.remove(&recipient) else {
+            return Vec::new();
+        };
+
+        let mut order: Vec<usize> = (0..inbox.len()).collect();
+        order.sort_by_key(|&index| {
+            let message = &inbox[index];
+            let starving = self.passed_over.get(&message.id).is_some_and(|&count| count >= settings.max_passed_over);
+            let urgency = if starving { u8::MAX } else { priority_rank(&message.priority) };
+            (std::cmp::Reverse(urgency), index)
+        });
+
+        let mut inbox: Vec<Option<Message>> = inbox.into_iter().map(Some).collect();
+        let batch: Vec<Message> = order.iter()
+            .take(settings.batch_size)
+            .filter_map(|&index| inbox[index].take())
+            .collect();
+        for message in &batch {
+            self.passed_over.remove(&message.id);
+        }
+
+        let waiting: VecDeque<Message> = inbox.into_iter().flatten().collect();
+        for message in &waiting {
+            *self.passed_over.entry(message.id).or_insert(0) += 1;
+        }
+        if !waiting.is_empty() {
+            self.inboxes.insert(recipient, waiting);
+        }
+        batch
+    }
+
+    /// Recipients with queued messages
+    pub fn recipients(&self) -> Vec<Uuid> {
+        self.inboxes.iter()
+            .filter(|(_, inbox)| !inbox.is_empty())
+            .map(|(recipient, _)| *recipient)
+            .collect()
+    }
+
+    /// Number of messages waiting across all inboxes
+    pub fn queued(&self) -> usize {
+        self.inboxes.values().map(VecDeque::len).sum()
+    }
+
+    /// Record the outcome of a delivery attempt
+    pub fn acknowledge(&mut self, message: &Message, status: DeliveryStatus) {
+        match &status {
+            DeliveryStatus::Delivered => self.stats.delivered += 1,
+            DeliveryStatus::Failed(_) => self.stats.failed += 1,
+            DeliveryStatus::Undeliverable => self.stats.undeliverable += 1,
+            DeliveryStatus::Queued | DeliveryStatus::Retrying { .. } | DeliveryStatus::DeadLettered => {}
+        }
+        self.record(message.id, message.to_agent, status);
+    }
+
+    /// Retry a failed delivery after a backoff, or dead-letter it once retries are used up
+    pub fn fail(&mut self, message: Message, status: DeliveryStatus, settings: &DeliverySettings, now: chrono::DateTime<chrono::Utc>) -> FailureOutcome {
+        self.acknowledge(&message, status.clone());
+        let attempts = self.attempts.entry(message.id).or_insert(0);
+        *attempts += 1;
+        let attempt = *attempts;
+
+        if attempt <= settings.max_retries {
+            // Backoff doubles with every attempt
+            let backoff = chrono::Duration::minutes(settings.retry_backoff_minutes * 2_i64.pow(attempt - 1));
+            let next_attempt_at = now + backoff;
+            self.stats.retried += 1;
+            self.record(message.id, message.to_agent, DeliveryStatus::Retrying { attempt, next_attempt_at });
+            self.retries.push(PendingRetry { message, next_attempt_at });
+            return FailureOutcome::Retry { attempt, next_attempt_at };
+        }
+
+        self.attempts.remove(&message.id);
+        self.stats.dead_lettered += 1;
+        self.record(message.id, message.to_agent, DeliveryStatus::DeadLettered);
+        // A lower capacity after a restore trims the queue down to size
+        while self.dead_letters.len() >= settings.dead_letter_capacity.max(1) {
+            self.dead_letters.pop_front();
+        }
+        self.dead_letters.push_back(DeadLetter { message, reason: status, attempts: attempt, dead_at: now });
+        FailureOutcome::DeadLettered
+    }
+
+    /// Move retries that are due back into their recipients' inboxes
+    pub fn requeue_due_retries(&mut self, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.retries)
+            .into_iter()
+            .partition(|retry| retry.next_attempt_at <= now);
+        self.retries = waiting;
+        let count = due.len();
+        for retry in due {
+            self.enqueue(retry.message);
+        }
+        count
+    }
+
+    /// Forget retry bookkeeping once a message is delivered
+    pub fn delivered(&mut self, message: &Message) {
+        self.attempts.remove(&message.id);
+        self.acknowledge(message, DeliveryStatus::Delivered);
+    }
+
+    /// Messages given up on, oldest first
+    pub fn dead_letters(&self) -> &VecDeque<DeadLetter> {
+        &self.dead_letters
+    }
+
+    /// Number of messages waiting for a retry
+    pub fn pending_retries(&self) -> usize {
+        self.retries.len()
+    }
+
+    /// Latest known status of a message
+    pub fn status(&self, message_id: Uuid) -> Option<&DeliveryStatus> {
+        self.acks.iter().rev()
+            .find(|ack| ack.message_id == message_id)
+            .map(|ack| &ack.status)
+    }
+
+    fn record(&mut self, message_id: Uuid, recipient: Uuid, status: DeliveryStatus) {
+        if self.acks.len() == ACK_LOG_CAPACITY {
+            self.acks.pop_front();
+        }
+        self.acks.push_back(DeliveryAck {
+            message_id,
+            recipient,
+            status,
+            at: chrono::Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::MessagePriority;
+    use crate::testsupport::test_message;
+
+
+    #[test]
+    fn test_inbox_order_and_acks() {
+        let mut router = MessageRouter::new();
+        let recipient = Uuid::new_v4();
+        let first = test_message(Uuid::new_v4(), recipient, "status_update");
+        let second = test_message(Uuid::new_v4(), recipient, "status_update");
+        router.enqueue(first.clone());
+        router.enqueue(second.clone());
+
+        assert_eq!(router.queued(), 2);
+        assert_eq!(router.status(first.id), Some(&DeliveryStatus::Queued));
+
+        let inbox = router.take_batch(recipient, &DeliverySettings::default());
+        assert_eq!(inbox.iter().map(|m| m.id).collect::<Vec<_>>(), vec![first.id, second.id]);
+        assert_eq!(router.queued(), 0);
+
+        router.acknowledge(&first, DeliveryStatus::Delivered);
+        router.acknowledge(&second, DeliveryStatus::Failed(AgentError::TaskExecution("boom".to_string())));
+        assert_eq!(router.status(first.id), Some(&DeliveryStatus::Delivered));
+        assert_eq!(router.stats.delivered, 1);
+        assert_eq!(router.stats.failed, 1);
+    }
+
+    #[test]
+    fn test_retries_back_off_then_dead_letter() {
+        let settings = DeliverySettings { max_retries: 2, retry_backoff_minutes: 5, dead_letter_capacity: 10, ..DeliverySettings::default() };
+        let mut router = MessageRouter::new();
+        let now = chrono::Utc::now();
+        let lost = test_message(Uuid::new_v4(), Uuid::new_v4(), "status_update");
+
+        let first = router.fail(lost.clone(), DeliveryStatus::Undeliverable, &settings, now);
+        assert_eq!(first, FailureOutcome::Retry { attempt: 1, next_attempt_at: now + chrono::Duration::minutes(5) });
+        assert_eq!(router.requeue_due_retries(now), 0);
+        assert_eq!(router.requeue_due_retries(now + chrono::Duration::minutes(5)), 1);
+        let retried = router.take_batch(lost.to_agent, &settings).remove(0);
+
+        let second = router.fail(retried, DeliveryStatus::Undeliverable, &settings, now);
+        assert_eq!(second, FailureOutcome::Retry { attempt: 2, next_attempt_at: now + chrono::Duration::minutes(10) });
+        router.requeue_due_retries(now + chrono::Duration::minutes(10));
+        let retried = router.take_batch(lost.to_agent, &settings).remove(0);
+
+        assert_eq!(router.fail(retried, DeliveryStatus::Undeliverable, &settings, now), FailureOutcome::DeadLettered);
+        assert_eq!(router.dead_letters().len(), 1);
+        assert_eq!(router.dead_letters()[0].attempts, 3);
+        assert_eq!(router.status(lost.id), Some(&DeliveryStatus::DeadLettered));
+        assert_eq!(router.stats.undeliverable, 3);
+        assert_eq!(router.pending_retries(), 0);
+
+        let no_retries = DeliverySettings { max_retries: 0, ..settings.clone() };
+        for _ in 0..4 {
+            router.fail(test_message(Uuid::new_v4(), Uuid::new_v4(), "status_update"), DeliveryStatus::Undeliverable, &no_retries, now);
+        }
+        let smaller = DeliverySettings { dead_letter_capacity: 2, ..no_retries };
+        router.fail(test_message(Uuid::new_v4(), Uuid::new_v4(), "status_update"), DeliveryStatus::Undeliverable, &smaller, now);
+        assert_eq!(router.dead_letters().len(), 2);
+    }
+
+    #[test]
+    fn test_priority_preempts_without_starving_low() {
+        let settings = DeliverySettings { batch_size: 2, max_passed_over: 2, ..DeliverySettings::default() };
+        let mut router = MessageRouter::new();
+        let recipient = Uuid::new_v4();
+        let with_priority = |priority| Message { priority, ..test_message(Uuid::new_v4(), recipient, "status_update") };
+
+        let low = with_priority(MessagePriority::Low);
+        let normal = with_priority(MessagePriority::Normal);
+        router.enqueue(low.clone());
+        router.enqueue(normal.clone());
+        let mut urgent = Vec::new();
+        for _ in 0..3 {
+            let high = with_priority(MessagePriority::High);
+            urgent.push(high.id);
+            router.enqueue(high);
+        }
+
+        let ids = |batch: Vec<Message>| batch.iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(router.take_batch(recipient, &settings)), vec![urgent[0], urgent[1]]);
+
+        // A fresh urgent message still beats the passed-over Normal one...
+        let high = with_priority(MessagePriority::High);
+        router.enqueue(high.clone());
+        assert_eq!(ids(router.take_batch(recipient, &settings)), vec![urgent[2], high.id]);
+
+        // ...until both waiting messages have been passed over twice
+        for _ in 0..2 {
+            router.enqueue(with_priority(MessagePriority::High));
+        }
+        assert_eq!(ids(router.take_batch(recipient, &settings)), vec![low.id, normal.id]);
+        assert_eq!(router.queued(), 2);
+    }
+}
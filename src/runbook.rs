@@ -0,0 +1,166 @@
+//! Runbook Automation - Scripted Remediation of Common Incidents
+//!
+//! This module implements the runbooks Ops runs on incidents it has seen
+//! before:
+//! - A runbook per common kind of incident, found by the keywords it shares
+//!   with the incident's title, description and services
+//! - Ordered remediation steps, each taking a few minutes and fixing the
+//!   incident with its own chance; the first that works resolves it
+//! - A runbook whose every step failed gives up, and the incident is
+//!   escalated to DevOps and worked by hand
+//!
+//! The orchestrator runs a step whenever an Ops agent gets to the incident
+//! and the last one has had its time, and records how each run went in the
+//! knowledge base.
+
+use crate::config::{RunbookDefinition, RunbookSettings};
+use crate::departments::ops::Incident;
+use crate::knowledge;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Runbook sharing the most keywords with an incident; the first wins ties
+pub fn find<'a>(incident: &Incident, runbooks: &'a [RunbookDefinition]) -> Option<&'a RunbookDefinition> {
+    let words = knowledge::keywords(incident);
+    runbooks.iter()
+        .map(|runbook| (runbook, runbook.keywords.iter().filter(|keyword| words.contains(keyword.as_str())).count()))
+        .filter(|(_, shared)| *shared > 0)
+        .fold(None, |best: Option<(&RunbookDefinition, usize)>, (runbook, shared)| match best {
+            Some((_, most)) if most >= shared => best,
+            _ => Some((runbook, shared)),
+        })
+        .map(|(runbook, _)| runbook)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunbookOutcome {
+    /// A step fixed the incident
+    Resolved,
+    /// Every step failed and the incident went to DevOps
+    Escalated,
+}
+
+/// A runbook being run on an incident
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunbookRun {
+    pub runbook: String,
+    pub started_at: DateTime<Utc>,
+    /// When the next step is done
+    pub next_step_at: DateTime<Utc>,
+    pub steps_tried: usize,
+    pub outcome: Option<RunbookOutcome>,
+}
+
+/// How a runbook step went
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult {
+    pub action: String,
+    /// Position in the runbook, from 0
+    pub step: usize,
+    pub fixed: bool,
+}
+
+impl RunbookRun {
+    pub fn start(runbook: &RunbookDefinition, settings: &RunbookSettings, now: DateTime<Utc>) -> Self {
+        Self {
+            runbook: runbook.name.clone(),
+            started_at: now,
+            next_step_at: now + chrono::Duration::minutes(i64::from(settings.step_minutes)),
+            steps_tried: 0,
+            outcome: None,
+        }
+    }
+
+    /// Finish the current step once it has had its time; the run is over
+    /// when it fixed the incident or was the last
+    pub fn step(&mut self, runbook: &RunbookDefinition, settings: &RunbookSettings, now: DateTime<Utc>, rng: &mut impl Rng) -> Option<StepResult> {
+        if self.outcome.is_some() || now < self.next_step_at {
+            return None;
+        }
+        let step = self.steps_tried;
+        let Some(definition) = runbook.steps.get(step) else {
+            self.outcome = Some(RunbookOutcome::Escalated);
+            return None;
+        };
+        let fixed = rng.gen_bool(definition.success_chance.clamp(0.0, 1.0));
+        self.steps_tried += 1;
+        self.next_step_at = now + chrono::Duration::minutes(i64::from(settings.step_minutes));
+        if fixed {
+            self.outcome = Some(RunbookOutcome::Resolved);
+        } else if self.steps_tried >= runbook.steps.len() {
+            self.outcome = Some(RunbookOutcome::Escalated);
+        }
+        Some(StepResult { action: definition.action.clone(), step, fixed })
+    }
+
+    pub fn minutes(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.started_at).num_minutes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RunbookStep;
+    use crate::departments::ops::{IncidentStatus, Severity};
+    use rand::SeedableRng;
+    use uuid::Uuid;
+
+    fn incident(description: &str) -> Incident {
+        Incident {
+            id: Uuid::new_v4(),
+            title: "Degraded web-pool".to_string(),
+            description: description.to_string(),
+            severity: Severity::Sev3,
+            status: IncidentStatus::Open,
+            affected_services: vec!["web".to_string()],
+            root_cause: None,
+            resolution: None,
+            created_at: Utc::now(),
+            resolved_at: None,
+            assigned_team: None,
+            duplicate_of: None,
+            duplicates: vec![],
+            correlation_ids: vec![],
+            status_history: vec![],
+            breaches: vec![],
+            knowledge: None,
+            runbook: None,
+        }
+    }
+
+    #[test]
+    fn test_steps_run_in_order_until_one_works_or_the_runbook_gives_up() {
+        let settings = RunbookSettings::default();
+        let runbook_for = |description: &str| find(&incident(description), &settings.runbooks).map(|runbook| runbook.name.clone());
+        assert_eq!(runbook_for("High CPU usage detected on web-pool"), Some("High CPU".to_string()));
+        assert_eq!(runbook_for("Noisy neighbour on web-pool: acme is saturating the pool"), Some("Noisy neighbour".to_string()));
+        assert_eq!(runbook_for("Login page shows a typo"), None);
+
+        let runbook = RunbookDefinition {
+            name: "Flaky".to_string(),
+            keywords: vec!["cpu".to_string()],
+            steps: vec![
+                RunbookStep { action: "Restart".to_string(), success_chance: 0.0 },
+                RunbookStep { action: "Fail over".to_string(), success_chance: 0.0 },
+            ],
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+        let start = Utc::now();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let mut run = RunbookRun::start(&runbook, &settings, start);
+        assert!(run.step(&runbook, &settings, at(10), &mut rng).is_none());
+        let first = run.step(&runbook, &settings, at(15), &mut rng).unwrap();
+        assert_eq!((first.step, first.fixed, run.outcome), (0, false, None));
+        assert_eq!(run.step(&runbook, &settings, at(30), &mut rng).unwrap().action, "Fail over");
+        assert_eq!((run.steps_tried, run.outcome), (2, Some(RunbookOutcome::Escalated)));
+
+        let mut run = RunbookRun::start(&runbook, &settings, start);
+        let mut fixing = runbook.clone();
+        fixing.steps[0].success_chance = 1.0;
+        assert!(run.step(&fixing, &settings, at(15), &mut rng).unwrap().fixed);
+        assert_eq!(run.outcome, Some(RunbookOutcome::Resolved));
+    }
+}
@@ -0,0 +1,355 @@
+//! Agent Runtime - Actor Tasks & Mailboxes
+//!
+//! This module implements running every agent as an actor:
+//! - Each agent lives on a tokio task of its own and is only reached through
+//!   its mpsc mailbox; nothing outside the task holds a reference to it
+//! - Work (message batches, daily tasks) is sent as commands carrying a
+//!   oneshot reply, so the orchestrator only sends and collects status
+//! - Calls run a closure against the agent inside its task and send back an
+//!   owned result, for KPIs, reports and snapshots
+//! - Identity (name, department, manager) is cached on the handle, so routing
+//!   and logging never wait on an agent
+//!
+//! Commands to different agents run concurrently; commands to one agent run
+//! in the order they were sent. A stopped actor hands its agent back.
+
+use crate::agents::{AgentTrait, Department};
+use crate::communication::Message;
+use crate::departments::{AgentError, DepartmentAgent};
+use crate::routing::DeliveryStatus;
+use crate::snapshot::AgentSnapshot;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Commands an actor's mailbox holds before senders wait
+const MAILBOX_CAPACITY: usize = 64;
+
+/// Closure run against an agent inside its task
+type Job = Box<dyn FnOnce(&mut dyn DepartmentAgent) + Send>;
+
+/// Outcome of one delivered message
+pub type Delivery = (Uuid, Message, DeliveryStatus);
+
+enum Command {
+    /// Process messages in order and report how each went
+    Deliver(Vec<Message>, oneshot::Sender<Vec<(Message, DeliveryStatus)>>),
+    /// Run the agent's daily tasks
+    DailyTasks(oneshot::Sender<Result<(), AgentError>>),
+    Call(Job),
+    /// Leave the loop and hand the agent back
+    Stop(oneshot::Sender<Box<dyn DepartmentAgent>>),
+}
+
+/// What the orchestrator knows about an agent without asking it
+#[derive(Debug, Clone)]
+pub struct AgentInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub department: Department,
+    pub manager_id: Option<Uuid>,
+}
+
+struct AgentHandle {
+    info: AgentInfo,
+    mailbox: mpsc::Sender<Command>,
+    task: JoinHandle<()>,
+}
+
+/// Agents running as actors, keyed by agent id
+#[derive(Default)]
+pub struct AgentRuntime {
+    actors: BTreeMap<Uuid, AgentHandle>,
+}
+
+impl std::fmt::Debug for AgentRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentRuntime").field("actors", &self.actors.len()).finish()
+    }
+}
+
+impl AgentRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an actor for the agent; returns the agent's id
+    pub fn spawn(&mut self, agent: Box<dyn DepartmentAgent>) -> Uuid {
+        let identity = agent.get_agent();
+        let info = AgentInfo {
+            id: identity.id,
+            name: identity.name.clone(),
+            department: identity.department.clone(),
+            manager_id: identity.manager_id,
+        };
+        let (mailbox, inbox) = mpsc::channel(MAILBOX_CAPACITY);
+        let task = tokio::spawn(run_actor(agent, inbox));
+
+        let id = info.id;
+        if let Some(replaced) = self.actors.insert(id, AgentHandle { info, mailbox, task }) {
+            replaced.task.abort();
+        }
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.actors.contains_key(id)
+    }
+
+    /// Agent ids in a stable order
+    pub fn ids(&self) -> Vec<Uuid> {
+        self.actors.keys().copied().collect()
+    }
+
+    pub fn info(&self, id: &Uuid) -> Option<&AgentInfo> {
+        self.actors.get(id).map(|handle| &handle.info)
+    }
+
+    /// Agents whose actor task has ended without being stopped, e.g. by a panic
+    pub fn crashed(&self) -> Vec<Uuid> {
+        self.actors.iter()
+            .filter(|(_, handle)| handle.task.is_finished())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Every agent's identity, in id order
+    pub fn infos(&self) -> impl Iterator<Item = &AgentInfo> {
+        self.actors.values().map(|handle| &handle.info)
+    }
+
+    /// Hand each batch to its agent and wait for all of them
+    ///
+    /// Every batch is sent before any reply is awaited, so agents work
+    /// through their batches at the same time. Outcomes come back in batch
+    /// order. A batch for an unknown agent is undeliverable; one whose actor
+    /// stops before replying fails as a whole, so the messages can be retried.
+    pub async fn deliver(&self, batches: Vec<(Uuid, Vec<Message>)>) -> Vec<Delivery> {
+        let mut replies = Vec::with_capacity(batches.len());
+        for (id, batch) in batches {
+            let (reply, outcome) = oneshot::channel();
+            let sent = self.send(id, Command::Deliver(batch.clone(), reply)).await.map(|()| outcome);
+            replies.push((id, batch, sent));
+        }
+
+        let mut deliveries = Vec::new();
+        for (id, batch, sent) in replies {
+            let error = match sent {
+                Ok(outcome) => match outcome.await {
+                    Ok(processed) => {
+                        deliveries.extend(processed.into_iter().map(|(message, status)| (id, message, status)));
+                        continue;
+                    }
+                    Err(_) => RuntimeError::Stopped(id),
+                },
+                Err(e) => e,
+            };
+            let status = match error {
+                RuntimeError::UnknownAgent(_) => DeliveryStatus::Undeliverable,
+                stopped => DeliveryStatus::Failed(stopped.into()),
+            };
+            deliveries.extend(batch.into_iter().map(|message| (id, message, status.clone())));
+        }
+        deliveries
+    }
+
+    /// Run the daily tasks of every listed agent and wait for all of them
+    ///
+    /// Every command is sent before any reply is awaited, so agents work at
+    /// the same time. Outcomes come back in the order given; an actor that
+    /// stops or panics fails on its own, and the rest are still awaited. The
+    /// inner error is the agent's own.
+    pub async fn daily_tasks(&self, ids: &[Uuid]) -> Vec<(Uuid, Result<Result<(), AgentError>, RuntimeError>)> {
+        let mut replies = Vec::with_capacity(ids.len());
+        for &id in ids {
+            let (reply, outcome) = oneshot::channel();
+            let sent = self.send(id, Command::DailyTasks(reply)).await.map(|()| outcome);
+            replies.push((id, sent));
+        }
+
+        let mut outcomes = Vec::with_capacity(replies.len());
+        for (id, sent) in replies {
+            let outcome = match sent {
+                Ok(outcome) => outcome.await.map_err(|_| RuntimeError::Stopped(id)),
+                Err(e) => Err(e),
+            };
+            outcomes.push((id, outcome));
+        }
+        outcomes
+    }
+
+    /// Run a closure against one agent and return its result
+    pub async fn call<R: Send + 'static>(&self, id: Uuid, f: impl FnOnce(&mut dyn DepartmentAgent) -> R + Send + 'static) -> Result<R, RuntimeError> {
+        let (reply, outcome) = oneshot::channel();
+        self.send(id, Command::Call(Box::new(move |agent| {
+            let _ = reply.send(f(agent));
+        }))).await?;
+        outcome.await.map_err(|_| RuntimeError::Stopped(id))
+    }
+
+    /// Run a closure against every agent; results are in id order
+    pub async fn call_all<R: Send + 'static>(&self, f: impl Fn(&mut dyn DepartmentAgent) -> R + Send + Sync + 'static) -> Result<Vec<R>, RuntimeError> {
+        let f = Arc::new(f);
+        let mut replies = Vec::with_capacity(self.actors.len());
+        for &id in self.actors.keys() {
+            let (reply, outcome) = oneshot::channel();
+            let f = f.clone();
+            self.send(id, Command::Call(Box::new(move |agent| {
+                let _ = reply.send(f(agent));
+            }))).await?;
+            replies.push((id, outcome));
+        }
+
+        let mut results = Vec::with_capacity(replies.len());
+        for (id, outcome) in replies {
+            results.push(outcome.await.map_err(|_| RuntimeError::Stopped(id))?);
+        }
+        Ok(results)
+    }
+
+    /// First agent, in id order, the predicate holds for
+    pub async fn find(&self, f: impl Fn(&mut dyn DepartmentAgent) -> bool + Send + Sync + 'static) -> Result<Option<Uuid>, RuntimeError> {
+        let matches = self.call_all(f).await?;
+        Ok(self.actors.keys().zip(matches).find(|(_, matched)| *matched).map(|(id, _)| *id))
+    }
+
+    /// Take the messages every agent has queued for others
+    pub async fn drain_outboxes(&self) -> Result<Vec<Message>, RuntimeError> {
+        Ok(self.call_all(|agent| agent.drain_outbox()).await?.into_iter().flatten().collect())
+    }
+
+    /// Capture every agent's state
+    pub async fn snapshot(&self) -> Result<HashMap<Uuid, AgentSnapshot>, RuntimeError> {
+        Ok(self.call_all(|agent| (agent.get_agent().id, agent.snapshot())).await?.into_iter().collect())
+    }
+
+    /// Stop an actor once it has finished the commands already sent, and take its agent back
+    pub async fn stop(&mut self, id: Uuid) -> Result<Box<dyn DepartmentAgent>, RuntimeError> {
+        let (reply, outcome) = oneshot::channel();
+        self.send(id, Command::Stop(reply)).await?;
+        let agent = outcome.await.map_err(|_| RuntimeError::Stopped(id))?;
+        self.actors.remove(&id);
+        Ok(agent)
+    }
+
+    async fn send(&self, id: Uuid, command: Command) -> Result<(), RuntimeError> {
+        let handle = self.actors.get(&id).ok_or(RuntimeError::UnknownAgent(id))?;
+        handle.mailbox.send(command).await.map_err(|_| RuntimeError::Stopped(id))
+    }
+}
+
+impl Drop for AgentRuntime {
+    fn drop(&mut self) {
+        for handle in self.actors.values() {
+            handle.task.abort();
+        }
+    }
+}
+
+/// Actor loop: the only place the agent is ever touched
+async fn run_actor(mut agent: Box<dyn DepartmentAgent>, mut inbox: mpsc::Receiver<Command>) {
+    while let Some(command) = inbox.recv().await {
+        match command {
+            Command::Deliver(batch, reply) => {
+                let mut processed = Vec::with_capacity(batch.len());
+                for message in batch {
+                    let status = match agent.process_message(message.clone()).await {
+                        Ok(()) => DeliveryStatus::Delivered,
+                        Err(e) => DeliveryStatus::Failed(AgentError::from_boxed(e)),
+                    };
+                    processed.push((message, status));
+                }
+                let _ = reply.send(processed);
+            }
+            Command::DailyTasks(reply) => {
+                let result = agent.perform_daily_tasks().await.map_err(AgentError::from_boxed);
+                let _ = reply.send(result);
+            }
+            Command::Call(job) => job(agent.as_mut()),
+            Command::Stop(reply) => {
+                let _ = reply.send(agent);
+                return;
+            }
+        }
+    }
+}
+
+/// Agent runtime errors
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeError {
+    #[error("No agent {0} in the runtime")]
+    UnknownAgent(Uuid),
+
+    #[error("Agent {0} has stopped")]
+    Stopped(Uuid),
+}
+
+/// A stopped actor needs recovering; an agent missing from the runtime is
+/// something the caller relied on
+impl From<RuntimeError> for AgentError {
+    fn from(error: RuntimeError) -> Self {
+        match error {
+            RuntimeError::UnknownAgent(_) => AgentError::Dependency(error.to_string()),
+            RuntimeError::Stopped(_) => AgentError::Fatal(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::MessagePriority;
+    use crate::departments::ops::OpsAgent;
+    use crate::system::SystemActor;
+
+    #[tokio::test]
+    async fn test_actor_processes_batches_and_hands_agent_back() {
+        let mut runtime = AgentRuntime::new();
+        let id = runtime.spawn(Box::new(OpsAgent::new("Ops Agent 1".to_string(), None)));
+        assert_eq!(runtime.info(&id).unwrap().name, "Ops Agent 1");
+
+        let tickets: Vec<Message> = (0..3).map(|i| Message {
+            id: Uuid::new_v4(),
+            from_agent: SystemActor::EventGenerator.id(),
+            to_agent: id,
+            message_type: "create_ticket".to_string(),
+            content: format!("Ticket {}", i),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }).collect();
+        let deliveries = runtime.deliver(vec![(id, tickets)]).await;
+        assert_eq!(deliveries.len(), 3);
+        assert!(deliveries.iter().all(|(_, _, status)| *status == DeliveryStatus::Delivered));
+
+        let open = runtime.call(id, |agent| agent.as_ops().map_or(0, |ops| ops.support_tickets.len())).await.unwrap();
+        assert_eq!(open, 3);
+
+        let agent = runtime.stop(id).await.unwrap();
+        assert_eq!(agent.backlog(), 3);
+        assert!(runtime.is_empty());
+        assert!(matches!(runtime.call(id, |_| ()).await, Err(RuntimeError::UnknownAgent(_))));
+    }
+
+    #[tokio::test]
+    async fn test_a_panicking_actor_fails_alone() {
+        let mut runtime = AgentRuntime::new();
+        let crashing = runtime.spawn(Box::new(OpsAgent::new("Ops Agent 1".to_string(), None)));
+        let healthy = runtime.spawn(Box::new(OpsAgent::new("Ops Agent 2".to_string(), None)));
+        let _ = runtime.call(crashing, |_| panic!("agent bug")).await;
+
+        let outcomes = runtime.daily_tasks(&[crashing, healthy]).await;
+        assert!(matches!(outcomes[0], (id, Err(RuntimeError::Stopped(_))) if id == crashing));
+        assert!(matches!(outcomes[1], (id, Ok(Ok(()))) if id == healthy));
+    }
+}
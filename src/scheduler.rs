@@ -0,0 +1,302 @@
+//! Event Scheduler - Simulated Clock & Timed Events
+//!
+//! This module implements the priority queue that drives the simulation:
+//! - A simulated clock that jumps straight to the next event
+//! - Recurring orchestrator steps and per-agent turns
+//! - Maintenance windows, SLA checks, operations reviews, executive reviews,
+//!   end-of-day reports, scripted staffing changes and injected
+//!   infrastructure faults
+//!
+//! Nothing waits on the wall clock here; the orchestrator decides whether to
+//! pace events in real time or run them back-to-back in headless mode.
+
+use crate::staffing::StaffingChange;
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use uuid::Uuid;
+
+/// What happens when a scheduled event fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EventKind {
+    /// Orchestrator step: messages, company events and health checks
+    Step,
+    /// One agent's turn to work
+    AgentTurn(Uuid),
+    /// Periodic SLA compliance check by Ops
+    SlaCheck,
+    /// Weekly maintenance window run by Ops
+    MaintenanceWindow,
+    /// Infrastructure fault injected by the chaos engine
+    InfrastructureFault,
+    /// Monthly operations review grading every service
+    OperationsReview,
+    /// Escalation of an alert on a deployment if its incident is still open
+    AlertEscalation { correlation_id: Uuid, deployment_id: Uuid },
+    /// Quarterly executive review of the company risk register
+    RiskReview,
+    /// Weekly CEO review of the company KPIs against the quarter's goals
+    ExecutiveReview,
+    /// Hire or offboarding scripted in `[staffing] changes`
+    Staffing(StaffingChange),
+    /// Midnight, when the end-of-day company report is written
+    EndOfDay,
+}
+
+/// Event due at a point in simulated time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScheduledEvent {
+    pub at: chrono::DateTime<chrono::Utc>,
+    /// Insertion order, breaks ties between events due at the same time
+    pub seq: u64,
+    pub kind: EventKind,
+}
+
+// Reversed so the max-heap pops the earliest event first
+impl Ord for ScheduledEvent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.at.cmp(&self.at).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Priority queue of timed events with a simulated clock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scheduler {
+    now: chrono::DateTime<chrono::Utc>,
+    next_seq: u64,
+    queue: BinaryHeap<ScheduledEvent>,
+}
+
+impl Scheduler {
+    /// Create an empty scheduler with the clock at `start`
+    pub fn new(start: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            now: start,
+            next_seq: 0,
+            queue: BinaryHeap::new(),
+        }
+    }
+
+    /// Current simulated time
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.now
+    }
+
+    /// Number of pending events
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Schedule an event at an absolute time (never earlier than now)
+    pub fn schedule_at(&mut self, at: chrono::DateTime<chrono::Utc>, kind: EventKind) {
+        let event = ScheduledEvent {
+            at: at.max(self.now),
+            seq: self.next_seq,
+            kind,
+        };
+        self.next_seq += 1;
+        self.queue.push(event);
+    }
+
+    /// Schedule an event after a delay from now
+    pub fn schedule_in(&mut self, delay: chrono::Duration, kind: EventKind) {
+        self.schedule_at(self.now + delay, kind);
+    }
+
+    /// Time of the next pending event
+    pub fn peek_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.queue.peek().map(|event| event.at)
+    }
+
+    /// Take the next event and advance the clock to it
+    pub fn pop(&mut self) -> Option<ScheduledEvent> {
+        let event = self.queue.pop()?;
+        self.now = self.now.max(event.at);
+        Some(event)
+    }
+
+    /// Move the clock forward without passing the next pending event
+    pub fn advance_to(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        let limit = self.peek_time().map_or(at, |next| at.min(next));
+        self.now = self.now.max(limit);
+    }
+
+    /// Whether an event of this kind is pending
+    pub fn contains(&self, kind: &EventKind) -> bool {
+        self.queue.iter().any(|event| event.kind == *kind)
+    }
+
+    /// Drop all pending events matching a predicate
+    pub fn cancel(&mut self, predicate: impl Fn(&EventKind) -> bool) {
+        self.queue.retain(|event| !predicate(&event.kind));
+    }
+}
+
+/// Whether a weekday is Saturday or Sunday
+fn is_weekend(day: chrono::Weekday) -> bool {
+    matches!(day, chrono::Weekday::Sat | chrono::Weekday::Sun)
+}
+
+/// Whether a time falls inside the working hours `(start, end)` of a weekday
+pub fn within_working_hours(at: chrono::DateTime<chrono::Utc>, working_hours: (u8, u8)) -> bool {
+    let hour = at.hour() as u8;
+    !is_weekend(at.weekday()) && hour >= working_hours.0 && hour < working_hours.1
+}
+
+/// Start of the next working period at or after `at`
+pub fn next_working_start(at: chrono::DateTime<chrono::Utc>, working_hours: (u8, u8)) -> chrono::DateTime<chrono::Utc> {
+    if within_working_hours(at, working_hours) {
+        return at;
+    }
+    let start_today = at.date_naive()
+        .and_hms_opt(working_hours.0 as u32, 0, 0)
+        .expect("validated working hours")
+        .and_utc();
+    let mut start = if at < start_today { start_today } else { start_today + chrono::Duration::days(1) };
+    while is_weekend(start.weekday()) {
+        start += chrono::Duration::days(1);
+    }
+    start
+}
+
+/// Next weekly maintenance window (Sunday 02:00) after `at`
+pub fn next_maintenance_window(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    let days_until_sunday = (7 - at.weekday().num_days_from_sunday()) % 7;
+    let candidate = (at.date_naive() + chrono::Duration::days(days_until_sunday as i64))
+        .and_hms_opt(2, 0, 0)
+        .expect("valid time")
+        .and_utc();
+    if candidate > at {
+        candidate
+    } else {
+        candidate + chrono::Duration::days(7)
+    }
+}
+
+/// Next weekly executive review (Monday 08:00) after `at`
+pub fn next_executive_review(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    let days_until_monday = (7 - at.weekday().num_days_from_monday()) % 7;
+    let candidate = (at.date_naive() + chrono::Duration::days(days_until_monday as i64))
+        .and_hms_opt(8, 0, 0)
+        .expect("valid time")
+        .and_utc();
+    if candidate > at {
+        candidate
+    } else {
+        candidate + chrono::Duration::days(7)
+    }
+}
+
+/// Next monthly operations review (1st of the month, 09:00) after `at`
+pub fn next_operations_review(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    let (year, month) = match at.month() {
+        12 => (at.year() + 1, 1),
+        month => (at.year(), month + 1),
+    };
+    let first_this_month = at.date_naive().with_day(1).expect("valid day")
+        .and_hms_opt(9, 0, 0)
+        .expect("valid time")
+        .and_utc();
+    if first_this_month > at {
+        return first_this_month;
+    }
+    chrono::NaiveDate::from_ymd_opt(year, month, 1).expect("valid date")
+        .and_hms_opt(9, 0, 0)
+        .expect("valid time")
+        .and_utc()
+}
+
+/// Next midnight after `at`
+pub fn next_end_of_day(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    (at.date_naive() + chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("valid time")
+        .and_utc()
+}
+
+/// Next quarterly risk review (1st of January, April, July and October,
+/// 10:00) after `at`
+pub fn next_risk_review(at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    let quarter_start = |year: i32, month: u32| {
+        chrono::NaiveDate::from_ymd_opt(year, month, 1).expect("valid date")
+            .and_hms_opt(10, 0, 0)
+            .expect("valid time")
+            .and_utc()
+    };
+    let this_quarter = quarter_start(at.year(), (at.month() - 1) / 3 * 3 + 1);
+    if this_quarter > at {
+        return this_quarter;
+    }
+    match (at.month() - 1) / 3 {
+        3 => quarter_start(at.year() + 1, 1),
+        quarter => quarter_start(at.year(), quarter * 3 + 4),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> chrono::DateTime<chrono::Utc> {
+        // 2024-06-02 was a Sunday
+        chrono::Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_events_pop_in_time_order() {
+        let mut scheduler = Scheduler::new(at(3, 9));
+        scheduler.schedule_in(chrono::Duration::hours(2), EventKind::SlaCheck);
+        scheduler.schedule_in(chrono::Duration::minutes(1), EventKind::Step);
+        scheduler.schedule_in(chrono::Duration::minutes(1), EventKind::InfrastructureFault);
+
+        assert_eq!(scheduler.pop().unwrap().kind, EventKind::Step);
+        assert_eq!(scheduler.pop().unwrap().kind, EventKind::InfrastructureFault);
+        let last = scheduler.pop().unwrap();
+        assert_eq!(last.kind, EventKind::SlaCheck);
+        assert_eq!(scheduler.now(), at(3, 11));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_advance_stops_at_next_event() {
+        let mut scheduler = Scheduler::new(at(3, 9));
+        scheduler.schedule_at(at(3, 10), EventKind::Step);
+        scheduler.advance_to(at(3, 12));
+        assert_eq!(scheduler.now(), at(3, 10));
+    }
+
+    #[test]
+    fn test_working_hours_and_maintenance() {
+        assert_eq!(next_working_start(at(3, 7), (9, 18)), at(3, 9));
+        assert_eq!(next_working_start(at(3, 12), (9, 18)), at(3, 12));
+        assert_eq!(next_working_start(at(3, 20), (9, 18)), at(4, 9));
+        // Friday evening and Saturday both resume on Monday morning
+        assert!(!within_working_hours(at(8, 12), (9, 18)));
+        assert_eq!(next_working_start(at(7, 20), (9, 18)), at(10, 9));
+        assert_eq!(next_working_start(at(8, 7), (9, 18)), at(10, 9));
+
+        assert_eq!(next_maintenance_window(at(3, 12)), at(9, 2));
+        assert_eq!(next_maintenance_window(at(2, 1)), at(2, 2));
+        assert_eq!(next_maintenance_window(at(2, 2)), at(9, 2));
+
+        assert_eq!(next_operations_review(at(1, 8)), at(1, 9));
+        assert_eq!(next_operations_review(at(3, 12)), chrono::Utc.with_ymd_and_hms(2024, 7, 1, 9, 0, 0).unwrap());
+        assert_eq!(next_risk_review(at(3, 12)), chrono::Utc.with_ymd_and_hms(2024, 7, 1, 10, 0, 0).unwrap());
+        assert_eq!(next_executive_review(at(3, 7)), at(3, 8));
+        assert_eq!(next_executive_review(at(3, 12)), at(10, 8));
+        assert_eq!(next_end_of_day(at(3, 0)), at(4, 0));
+        assert_eq!(next_end_of_day(at(3, 23)), at(4, 0));
+    }
+}
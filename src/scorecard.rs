@@ -0,0 +1,384 @@
+//! Service Scorecards - Reliability, Security & Cost Grades
+//!
+//! This module implements per-service scorecards and the monthly operations review:
+//! - SLO compliance from hourly availability samples
+//! - Open vulnerabilities reported by InfoSec and incidents raised this period
+//! - Cost per request from hosting cost and customer traffic
+//! - Tech debt accruing over time and with every incident, paid down by improvements
+//! - A weighted score and letter grade per service
+//! - Improvement tasks for the owners of services graded below the bar
+//!
+//! The book only keeps score; the orchestrator feeds it samples, holds the
+//! review and sends the improvement tasks to their owners.
+
+use crate::config::ScorecardSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Message type assigning an improvement task to a service owner
+pub const IMPROVEMENT_TASK: &str = "improvement_task";
+
+/// Message type reporting a finished improvement task back
+pub const IMPROVEMENT_DONE: &str = "improvement_completed";
+
+/// Metadata key carrying the improvement task id
+pub const TASK_ID_KEY: &str = "task_id";
+
+/// Tech debt points an incident leaves behind
+const DEBT_PER_INCIDENT: f64 = 2.0;
+
+/// Reviews kept in the book
+const MAX_REVIEWS: usize = 12;
+
+/// Letter grade of a service, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Grade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl Grade {
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 90.0 => Grade::A,
+            s if s >= 80.0 => Grade::B,
+            s if s >= 70.0 => Grade::C,
+            s if s >= 60.0 => Grade::D,
+            _ => Grade::F,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+            Grade::D => "D",
+            Grade::F => "F",
+        }
+    }
+}
+
+/// Scored dimension of a service, and the focus of an improvement task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Dimension {
+    Slo,
+    Security,
+    Incidents,
+    Cost,
+    TechDebt,
+}
+
+impl Dimension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dimension::Slo => "slo",
+            Dimension::Security => "security",
+            Dimension::Incidents => "incidents",
+            Dimension::Cost => "cost",
+            Dimension::TechDebt => "tech_debt",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "slo" => Some(Dimension::Slo),
+            "security" => Some(Dimension::Security),
+            "incidents" => Some(Dimension::Incidents),
+            "cost" => Some(Dimension::Cost),
+            "tech_debt" => Some(Dimension::TechDebt),
+            _ => None,
+        }
+    }
+
+    /// What the owner is asked to do
+    pub fn task_title(&self, service: &str) -> String {
+        match self {
+            Dimension::Slo => format!("Raise availability of {}", service),
+            Dimension::Security => format!("Remediate open vulnerabilities in {}", service),
+            Dimension::Incidents => format!("Address recurring incidents on {}", service),
+            Dimension::Cost => format!("Cut hosting cost per request of {}", service),
+            Dimension::TechDebt => format!("Pay down tech debt in {}", service),
+        }
+    }
+}
+
+/// Raw measurements of one service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceMetrics {
+    /// Hourly availability samples this period
+    pub samples: u32,
+    /// Samples taken while the service had an open incident
+    pub degraded_samples: u32,
+    pub incidents: u32,
+    pub requests: u64,
+    pub cost: f64,
+    /// Carried across periods until paid down
+    pub tech_debt: f64,
+}
+
+/// One service's graded state at a review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub service: String,
+    pub owner: Option<Uuid>,
+    /// Share of samples the service was healthy, in percent
+    pub slo_compliance: f64,
+    pub open_vulnerabilities: u32,
+    pub incidents: u32,
+    pub cost_per_request: Option<f64>,
+    pub tech_debt: f64,
+    /// Score per dimension, 0-100
+    pub dimension_scores: BTreeMap<String, f64>,
+    pub score: f64,
+    pub grade: Grade,
+}
+
+impl Scorecard {
+    /// Lowest-scoring dimension, the one an improvement task targets
+    pub fn weakest(&self) -> Dimension {
+        self.dimension_scores.iter()
+            .filter_map(|(name, score)| Dimension::from_str(name).map(|dimension| (dimension, *score)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(Dimension::TechDebt, |(dimension, _)| dimension)
+    }
+}
+
+/// Work assigned to a service owner after a review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImprovementTask {
+    pub id: Uuid,
+    pub service: String,
+    pub owner: Uuid,
+    pub focus: Dimension,
+    pub title: String,
+    pub assigned_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Outcome of a monthly operations review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationsReview {
+    pub held_at: chrono::DateTime<chrono::Utc>,
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub scorecards: Vec<Scorecard>,
+    /// Ids of the improvement tasks the review assigned
+    pub tasks: Vec<Uuid>,
+}
+
+/// Measurements, owners, reviews and improvement tasks of every service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScorecardBook {
+    metrics: BTreeMap<String, ServiceMetrics>,
+    owners: BTreeMap<String, Uuid>,
+    tasks: BTreeMap<Uuid, ImprovementTask>,
+    reviews: Vec<OperationsReview>,
+    period_start: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ScorecardBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn metrics(&self, service: &str) -> Option<&ServiceMetrics> {
+        self.metrics.get(service)
+    }
+
+    pub fn owner(&self, service: &str) -> Option<Uuid> {
+        self.owners.get(service).copied()
+    }
+
+    pub fn reviews(&self) -> &[OperationsReview] {
+        &self.reviews
+    }
+
+    pub fn latest_review(&self) -> Option<&OperationsReview> {
+        self.reviews.last()
+    }
+
+    pub fn task(&self, id: &Uuid) -> Option<&ImprovementTask> {
+        self.tasks.get(id)
+    }
+
+    /// Improvement tasks not yet completed
+    pub fn open_tasks(&self) -> impl Iterator<Item = &ImprovementTask> {
+        self.tasks.values().filter(|task| task.completed_at.is_none())
+    }
+
+    /// Record an hour of a service: whether it was healthy, its traffic and
+    /// the tech debt it built up meanwhile
+    pub fn record_hour(&mut self, service: &str, healthy: bool, requests: u64, cost: f64, settings: &ScorecardSettings, now: chrono::DateTime<chrono::Utc>) {
+        self.period_start.get_or_insert(now);
+        let metrics = self.metrics.entry(service.to_string()).or_default();
+        metrics.samples += 1;
+        metrics.degraded_samples += u32::from(!healthy);
+        metrics.requests += requests;
+        metrics.cost += cost;
+        metrics.tech_debt += settings.tech_debt_per_day / 24.0;
+    }
+
+    pub fn record_incident(&mut self, service: &str) {
+        let metrics = self.metrics.entry(service.to_string()).or_default();
+        metrics.incidents += 1;
+        metrics.tech_debt += DEBT_PER_INCIDENT;
+    }
+
+    /// Make an agent the owner of a service
+    pub fn assign_owner(&mut self, service: &str, owner: Uuid) {
+        self.owners.insert(service.to_string(), owner);
+    }
+
+    /// Grade every measured service; vulnerabilities come from InfoSec
+    pub fn scorecards(&self, vulnerabilities: &BTreeMap<String, u32>, settings: &ScorecardSettings) -> Vec<Scorecard> {
+        self.metrics.iter()
+            .map(|(service, metrics)| {
+                let open_vulnerabilities = vulnerabilities.get(service).copied().unwrap_or(0);
+                score_service(service, self.owner(service), metrics, open_vulnerabilities, settings)
+            })
+            .collect()
+    }
+
+    /// Hold the operations review: grade every service, open an improvement
+    /// task for each owned service graded below the bar and start a new period
+    pub fn review(&mut self, vulnerabilities: &BTreeMap<String, u32>, settings: &ScorecardSettings, now: chrono::DateTime<chrono::Utc>) -> &OperationsReview {
+        let scorecards = self.scorecards(vulnerabilities, settings);
+        let mut tasks = Vec::new();
+        for card in scorecards.iter().filter(|card| card.grade > settings.min_grade) {
+            let Some(owner) = card.owner else {
+                continue;
+            };
+            let focus = card.weakest();
+            let task = ImprovementTask {
+                id: Uuid::new_v4(),
+                service: card.service.clone(),
+                owner,
+                focus,
+                title: focus.task_title(&card.service),
+                assigned_at: now,
+                completed_at: None,
+            };
+            tasks.push(task.id);
+            self.tasks.insert(task.id, task);
+        }
+
+        self.reviews.push(OperationsReview {
+            held_at: now,
+            period_start: self.period_start.unwrap_or(now),
+            scorecards,
+            tasks,
+        });
+        if self.reviews.len() > MAX_REVIEWS {
+            self.reviews.remove(0);
+        }
+
+        // Tech debt carries over; everything else is measured afresh
+        for metrics in self.metrics.values_mut() {
+            *metrics = ServiceMetrics { tech_debt: metrics.tech_debt, ..ServiceMetrics::default() };
+        }
+        self.period_start = Some(now);
+        self.reviews.last().expect("review just pushed")
+    }
+
+    /// Close an improvement task; paying down debt is the one effect the book applies itself
+    pub fn complete_task(&mut self, id: Uuid, now: chrono::DateTime<chrono::Utc>) -> Option<&ImprovementTask> {
+        let task = self.tasks.get_mut(&id).filter(|task| task.completed_at.is_none())?;
+        task.completed_at = Some(now);
+        if task.focus == Dimension::TechDebt {
+            if let Some(metrics) = self.metrics.get_mut(&task.service) {
+                metrics.tech_debt /= 2.0;
+            }
+        }
+        Some(task)
+    }
+}
+
+/// Score each dimension 0-100 and combine them with equal weight
+fn score_service(service: &str, owner: Option<Uuid>, metrics: &ServiceMetrics, open_vulnerabilities: u32, settings: &ScorecardSettings) -> Scorecard {
+    let slo_compliance = match metrics.samples {
+        0 => 100.0,
+        samples => 100.0 * f64::from(samples - metrics.degraded_samples) / f64::from(samples),
+    };
+    let cost_per_request = (metrics.requests > 0).then(|| metrics.cost / metrics.requests as f64);
+
+    // Full marks at the SLO target, none five points below it
+    let slo = ((slo_compliance - settings.slo_target + 5.0) * 20.0).clamp(0.0, 100.0);
+    let security = (100.0 - 15.0 * f64::from(open_vulnerabilities)).max(0.0);
+    let incidents = (100.0 - 10.0 * f64::from(metrics.incidents)).max(0.0);
+    let cost = cost_per_request.map_or(100.0, |actual| (100.0 * settings.cost_per_request_budget / actual).min(100.0));
+    let tech_debt = (100.0 - metrics.tech_debt).clamp(0.0, 100.0);
+
+    let dimension_scores = BTreeMap::from([
+        (Dimension::Slo.as_str().to_string(), slo),
+        (Dimension::Security.as_str().to_string(), security),
+        (Dimension::Incidents.as_str().to_string(), incidents),
+        (Dimension::Cost.as_str().to_string(), cost),
+        (Dimension::TechDebt.as_str().to_string(), tech_debt),
+    ]);
+    let score = dimension_scores.values().sum::<f64>() / dimension_scores.len() as f64;
+
+    Scorecard {
+        service: service.to_string(),
+        owner,
+        slo_compliance,
+        open_vulnerabilities,
+        incidents: metrics.incidents,
+        cost_per_request,
+        tech_debt: metrics.tech_debt,
+        dimension_scores,
+        score,
+        grade: Grade::from_score(score),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> chrono::DateTime<chrono::Utc> {
+        "2024-03-01T00:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_review_grades_services_and_assigns_tasks_below_the_bar() {
+        let settings = ScorecardSettings { tech_debt_per_day: 0.0, ..ScorecardSettings::default() };
+        let mut book = ScorecardBook::new();
+        let owner = Uuid::new_v4();
+        book.assign_owner("database", owner);
+        book.assign_owner("api-gateway", Uuid::new_v4());
+
+        for hour in 0..100 {
+            let now = start() + chrono::Duration::hours(hour);
+            book.record_hour("api-gateway", true, 10_000, 1.0, &settings, now);
+            // A database down a fifth of the time and costly to run
+            book.record_hour("database", hour % 5 != 0, 1_000, 4.0, &settings, now);
+        }
+        for _ in 0..4 {
+            book.record_incident("database");
+        }
+        let vulnerabilities = BTreeMap::from([("database".to_string(), 3)]);
+
+        let review = book.review(&vulnerabilities, &settings, start() + chrono::Duration::days(30));
+        let grades: BTreeMap<&str, Grade> = review.scorecards.iter().map(|card| (card.service.as_str(), card.grade)).collect();
+        assert_eq!(grades["api-gateway"], Grade::A);
+        assert_eq!(grades["database"], Grade::F);
+        assert_eq!(review.tasks.len(), 1);
+
+        let task_id = review.tasks[0];
+        let task = book.task(&task_id).unwrap();
+        assert_eq!((task.service.as_str(), task.owner, task.focus), ("database", owner, Dimension::Slo));
+
+        // Measurements start over, tech debt does not
+        let database = book.metrics("database").unwrap();
+        assert_eq!((database.samples, database.incidents), (0, 0));
+        assert_eq!(database.tech_debt, 8.0);
+
+        assert!(book.complete_task(task_id, start()).is_some());
+        assert!(book.complete_task(task_id, start()).is_none());
+        assert_eq!(book.open_tasks().count(), 0);
+    }
+}
@@ -0,0 +1,336 @@
+//! Scripted Agents - Agent Behavior Defined in Rhai Scripts
+//!
+//! This module implements agents whose behavior lives in a Rhai script loaded
+//! at startup, so it can be changed without recompiling:
+//! - A department's script defines `process_message(message)` and
+//!   `perform_daily_tasks()`; either may be left out
+//! - Inside them `this` is the agent: its `id`, `name` and `department`, a
+//!   `state` map kept between calls and saved in snapshots, and
+//!   `send(department, message_type, content)` and
+//!   `send_to(agent_id, message_type, content)`, which queue messages for
+//!   the message bus
+//! - Scripts run sandboxed: they cannot import modules, `eval` code or touch
+//!   files or the network, and every call is cut off after `max_operations`
+//!
+//! Scripted agents are registered with the agent registry for their
+//! department. Built only with the `scripting` feature.
+
+use crate::agents::{Agent, AgentTrait, Department};
+use crate::communication::{Message, MessagePriority};
+use crate::config::ScriptingSettings;
+use crate::departments::{AgentError, DepartmentAgent};
+use crate::registry::{AgentFactory, RegistryError};
+use crate::snapshot::{AgentSnapshot, PluginSnapshot};
+use async_trait::async_trait;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Deepest a script may nest function calls
+const MAX_CALL_LEVELS: usize = 32;
+/// Longest string a script may build
+const MAX_STRING_SIZE: usize = 64 * 1024;
+/// Most entries in a script's arrays and maps
+const MAX_COLLECTION_SIZE: usize = 10_000;
+
+/// A compiled department script
+#[derive(Debug)]
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// Message types the script acts on
+    message_types: Vec<String>,
+}
+
+impl Script {
+    /// Read and compile a script file
+    pub fn load(path: &Path, message_types: &[String], settings: &ScriptingSettings) -> Result<Self, ScriptError> {
+        let source = std::fs::read_to_string(path).map_err(|e| ScriptError::Read(path.to_path_buf(), e))?;
+        Self::compile(&source, message_types, settings).map_err(|error| match error {
+            ScriptError::Compile(_, error) => ScriptError::Compile(path.to_path_buf(), error),
+            error => error,
+        })
+    }
+
+    /// Compile a script in a sandboxed engine
+    pub fn compile(source: &str, message_types: &[String], settings: &ScriptingSettings) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(settings.max_operations);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_array_size(MAX_COLLECTION_SIZE);
+        engine.set_max_map_size(MAX_COLLECTION_SIZE);
+        engine.on_print(|text| println!("📜 {}", text));
+        register_agent_api(&mut engine);
+
+        let ast = engine.compile(source).map_err(|e| ScriptError::Compile(PathBuf::new(), e.to_string()))?;
+        Ok(Self { engine, ast, message_types: message_types.to_vec() })
+    }
+
+    /// Whether the script defines a function taking `arity` arguments
+    fn defines(&self, function: &str, arity: usize) -> bool {
+        self.ast.iter_functions().any(|f| f.name == function && f.params.len() == arity)
+    }
+}
+
+/// What a script sees of its agent as `this`
+#[derive(Debug, Clone)]
+struct ScriptAgent {
+    id: Uuid,
+    name: String,
+    department: String,
+    state: Map,
+    outbox: Vec<Message>,
+}
+
+impl ScriptAgent {
+    fn message(&self, to_agent: Uuid, message_type: &str, content: &str) -> Message {
+        Message {
+            id: Uuid::new_v4(),
+            from_agent: self.id,
+            to_agent,
+            message_type: message_type.to_string(),
+            content: content.to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+}
+
+/// The agent's properties and messaging, the only things scripts can reach
+fn register_agent_api(engine: &mut Engine) {
+    engine.register_type_with_name::<ScriptAgent>("Agent");
+    engine.register_get("id", |agent: &mut ScriptAgent| agent.id.to_string());
+    engine.register_get("name", |agent: &mut ScriptAgent| agent.name.clone());
+    engine.register_get("department", |agent: &mut ScriptAgent| agent.department.clone());
+    engine.register_get_set("state",
+        |agent: &mut ScriptAgent| agent.state.clone(),
+        |agent: &mut ScriptAgent, state: Map| agent.state = state);
+
+    // The orchestrator picks the department's recipient, as for other agents
+    engine.register_fn("send", |agent: &mut ScriptAgent, department: &str, message_type: &str, content: &str| {
+        let mut message = agent.message(Uuid::nil(), message_type, content);
+        message.metadata.insert("to_department".to_string(), department.to_string());
+        agent.outbox.push(message);
+    });
+    engine.register_fn("send_to", |agent: &mut ScriptAgent, to_agent: &str, message_type: &str, content: &str| -> Result<(), Box<EvalAltResult>> {
+        let to_agent = Uuid::parse_str(to_agent).map_err(|_| format!("'{}' is not an agent id", to_agent))?;
+        let message = agent.message(to_agent, message_type, content);
+        agent.outbox.push(message);
+        Ok(())
+    });
+}
+
+/// A message as the map scripts receive
+fn message_map(message: &Message) -> Map {
+    let metadata: Map = message.metadata.iter().map(|(key, value)| (key.as_str().into(), value.clone().into())).collect();
+    Map::from([
+        ("id".into(), message.id.to_string().into()),
+        ("from".into(), message.from_agent.to_string().into()),
+        ("message_type".into(), message.message_type.clone().into()),
+        ("content".into(), message.content.clone().into()),
+        ("priority".into(), format!("{:?}", message.priority).into()),
+        ("metadata".into(), Dynamic::from_map(metadata)),
+    ])
+}
+
+/// An agent run by its department's script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedAgent {
+    agent: Agent,
+    /// Kind of the factory that restores the agent
+    kind: String,
+    /// Values the script keeps between calls
+    state: Map,
+    #[serde(skip)]
+    outbox: Vec<Message>,
+    #[serde(skip)]
+    script: Option<Arc<Script>>,
+}
+
+impl ScriptedAgent {
+    /// Run one of the script's functions with the agent as `this`; messages
+    /// it queued are kept only if it succeeds
+    fn call(&mut self, function: &str, args: impl FuncArgs) -> Result<(), Box<EvalAltResult>> {
+        let Some(script) = self.script.clone() else {
+            return Err("the agent's script is not loaded".into());
+        };
+        let mut this = Dynamic::from(ScriptAgent {
+            id: self.agent.id,
+            name: self.agent.name.clone(),
+            department: self.agent.department.as_str().to_string(),
+            state: std::mem::take(&mut self.state),
+            outbox: Vec::new(),
+        });
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+        let result = script.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, function, args);
+
+        if let Some(agent) = this.try_cast::<ScriptAgent>() {
+            self.state = agent.state;
+            if result.is_ok() {
+                self.outbox.extend(agent.outbox);
+            }
+        }
+        result.map(|_| ())
+    }
+
+    fn defines(&self, function: &str, arity: usize) -> bool {
+        self.script.as_ref().is_some_and(|script| script.defines(function, arity))
+    }
+}
+
+#[async_trait]
+impl AgentTrait for ScriptedAgent {
+    async fn process_message(&mut self, message: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.defines("process_message", 1) {
+            println!("🤷 {}: No script handler for {}", self.agent.name, message.message_type);
+            return Ok(());
+        }
+        self.call("process_message", (Dynamic::from_map(message_map(&message)),))
+            .map_err(|e| AgentError::MessageHandling(format!("{} script: {}", self.kind, e)))?;
+        Ok(())
+    }
+
+    async fn perform_daily_tasks(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.defines("perform_daily_tasks", 0) {
+            return Ok(());
+        }
+        self.call("perform_daily_tasks", ())
+            .map_err(|e| AgentError::TaskExecution(format!("{} script: {}", self.kind, e)))?;
+        Ok(())
+    }
+
+    fn get_agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    fn get_agent_mut(&mut self) -> &mut Agent {
+        &mut self.agent
+    }
+}
+
+impl DepartmentAgent for ScriptedAgent {
+    fn drain_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn snapshot(&self) -> AgentSnapshot {
+        let message_types: Vec<&str> = self.script.iter()
+            .flat_map(|script| script.message_types.iter().map(String::as_str))
+            .collect();
+        AgentSnapshot::Plugin(PluginSnapshot::of(&self.kind, self, &message_types))
+    }
+}
+
+/// Creates and restores a department's scripted agents
+pub struct ScriptFactory {
+    kind: String,
+    department: Department,
+    script: Arc<Script>,
+}
+
+impl ScriptFactory {
+    pub fn new(department: Department, script: Script) -> Self {
+        Self {
+            kind: format!("script:{}", department.as_str().to_lowercase()),
+            department,
+            script: Arc::new(script),
+        }
+    }
+}
+
+impl AgentFactory for ScriptFactory {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn create(&self, name: &str, manager_id: Option<Uuid>) -> Box<dyn DepartmentAgent> {
+        Box::new(ScriptedAgent {
+            agent: Agent::new(name.to_string(), self.department, manager_id),
+            kind: self.kind.clone(),
+            state: Map::new(),
+            outbox: Vec::new(),
+            script: Some(self.script.clone()),
+        })
+    }
+
+    fn restore(&self, snapshot: PluginSnapshot) -> Result<Box<dyn DepartmentAgent>, RegistryError> {
+        let mut agent: ScriptedAgent = serde_json::from_value(snapshot.state).map_err(|e| RegistryError::State(snapshot.kind, e))?;
+        agent.script = Some(self.script.clone());
+        Ok(Box::new(agent))
+    }
+}
+
+/// Script loading errors
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptError {
+    #[error("Cannot read script {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Script {0} does not compile: {1}")]
+    Compile(PathBuf, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::AgentRegistry;
+
+    const SALES: &str = r#"
+        fn process_message(message) {
+            if message.message_type == "lead" {
+                this.state.leads = (this.state.leads ?? 0) + 1;
+                this.send_to(message.from, "lead_accepted", "Thanks, " + this.name + " has it");
+            }
+        }
+
+        fn perform_daily_tasks() {
+            this.send("Marketing", "campaign_report", "Leads so far: " + (this.state.leads ?? 0));
+        }
+    "#;
+
+    #[tokio::test]
+    async fn test_scripts_handle_messages_keep_state_and_restore() {
+        let settings = ScriptingSettings::default();
+        let script = Script::compile(SALES, &["lead".to_string()], &settings).unwrap();
+        let registry = AgentRegistry::builtin().register(Department::Sales, ScriptFactory::new(Department::Sales, script));
+        let mut agent = registry.create(Department::Sales, "Sam", None).unwrap();
+
+        let marketer = Uuid::new_v4();
+        let message = Message {
+            id: Uuid::new_v4(),
+            from_agent: marketer,
+            to_agent: agent.get_agent().id,
+            message_type: "lead".to_string(),
+            content: "Acme wants a demo".to_string(),
+            priority: MessagePriority::Normal,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        };
+        agent.process_message(message).await.unwrap();
+        let outbox = agent.drain_outbox();
+        assert_eq!(outbox.len(), 1);
+        assert_eq!((outbox[0].to_agent, outbox[0].content.as_str()), (marketer, "Thanks, Sam has it"));
+
+        // State survives a snapshot round trip and reaches the next call
+        let snapshot = serde_json::from_value(serde_json::to_value(agent.snapshot()).unwrap()).unwrap();
+        let mut restored = AgentSnapshot::into_agent(snapshot, &registry).unwrap();
+        restored.perform_daily_tasks().await.unwrap();
+        let outbox = restored.drain_outbox();
+        assert_eq!(outbox[0].metadata.get("to_department").map(String::as_str), Some("Marketing"));
+        assert_eq!(outbox[0].content, "Leads so far: 1");
+
+        // Runaway scripts are cut off
+        let settings = ScriptingSettings { max_operations: 1_000, ..ScriptingSettings::default() };
+        let script = Script::compile("fn perform_daily_tasks() { loop {} }", &[], &settings).unwrap();
+        let mut looping = ScriptFactory::new(Department::Sales, script).create("Lou", None);
+        assert!(looping.perform_daily_tasks().await.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Security Policies - Evaluable Policies and the Compliance They Add Up To
+//!
+//! This module implements the policies InfoSec audits the company against:
+//! - MFA required on every enabled account in the identity directory
+//! - Encryption at rest on every DevOps server
+//! - A firewall that denies by default: an enabled catch-all deny rule in
+//!   Networking's firewall
+//! - Backups kept for at least the configured number of days
+//! - Access reviewed: no orphaned or over-privileged accounts left
+//!
+//! Each policy is checked against evidence of the actual DevOps, Networking
+//! and identity state, and scores how much of it complies; the GDPR, SOC 2
+//! and ISO 27001 scores are the mean of the policies each standard maps to.
+//! The orchestrator gathers the evidence daily; InfoSec evaluates it at
+//! every compliance audit and keeps violations as open compliance issues.
+
+use crate::config::SecurityPolicySettings;
+use crate::departments::infosec::Severity;
+use crate::departments::networking::{FirewallAction, FirewallRule, Protocol};
+use crate::iam::AccessFindings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Standard {
+    Gdpr,
+    Soc2,
+    Iso27001,
+}
+
+impl Standard {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Standard::Gdpr => "GDPR",
+            Standard::Soc2 => "SOC 2",
+            Standard::Iso27001 => "ISO 27001",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    MfaRequired,
+    EncryptionAtRest,
+    FirewallDefaultDeny,
+    BackupRetention,
+    AccessReviewed,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 5] = [
+        Policy::MfaRequired,
+        Policy::EncryptionAtRest,
+        Policy::FirewallDefaultDeny,
+        Policy::BackupRetention,
+        Policy::AccessReviewed,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Policy::MfaRequired => "mfa-required",
+            Policy::EncryptionAtRest => "encryption-at-rest",
+            Policy::FirewallDefaultDeny => "firewall-default-deny",
+            Policy::BackupRetention => "backup-retention",
+            Policy::AccessReviewed => "access-reviewed",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::MfaRequired => "MFA required",
+            Policy::EncryptionAtRest => "Encryption at rest",
+            Policy::FirewallDefaultDeny => "Firewall default-deny",
+            Policy::BackupRetention => "Backup retention",
+            Policy::AccessReviewed => "Access reviewed",
+        }
+    }
+
+    /// Standards whose score the policy counts towards
+    pub fn standards(&self) -> &'static [Standard] {
+        match self {
+            Policy::MfaRequired | Policy::FirewallDefaultDeny | Policy::BackupRetention => &[Standard::Soc2, Standard::Iso27001],
+            Policy::EncryptionAtRest | Policy::AccessReviewed => &[Standard::Gdpr, Standard::Soc2, Standard::Iso27001],
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Policy::MfaRequired | Policy::EncryptionAtRest | Policy::FirewallDefaultDeny => Severity::High,
+            Policy::BackupRetention | Policy::AccessReviewed => Severity::Medium,
+        }
+    }
+
+    /// What would bring the company back into compliance
+    pub fn recommendation(&self) -> &'static str {
+        match self {
+            Policy::MfaRequired => "Enroll every account in multi-factor authentication",
+            Policy::EncryptionAtRest => "Encrypt the disks of every server",
+            Policy::FirewallDefaultDeny => "Add a firewall rule denying all traffic not explicitly allowed",
+            Policy::BackupRetention => "Keep backups for longer",
+            Policy::AccessReviewed => "Run an access review",
+        }
+    }
+}
+
+/// What the company actually looks like, gathered for an audit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyEvidence {
+    /// Enabled accounts in the identity directory
+    pub accounts: usize,
+    pub access: AccessFindings,
+    /// DevOps servers by hostname, and whether their disks are encrypted
+    pub servers: BTreeMap<String, bool>,
+    /// Name of Networking's catch-all deny rule, if it has one
+    pub default_deny_rule: Option<String>,
+    /// Days DevOps keeps backups
+    pub backup_retention_days: u32,
+}
+
+/// An enabled rule denying any protocol on every port, the firewall's
+/// default-deny
+pub fn default_deny(rules: &[FirewallRule]) -> Option<&FirewallRule> {
+    rules.iter().find(|rule| {
+        rule.enabled && rule.action == FirewallAction::Deny && rule.protocol == Protocol::Any
+            && rule.port_range.start == 0 && rule.port_range.end == u16::MAX
+    })
+}
+
+/// How far one policy is met
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyResult {
+    pub policy: Policy,
+    /// Share of what the policy covers that complies, from 0 to 1
+    pub compliance: f64,
+    /// What does not comply
+    pub violations: Vec<String>,
+}
+
+impl PolicyResult {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyEvaluation {
+    pub results: Vec<PolicyResult>,
+}
+
+impl PolicyEvaluation {
+    /// Score of a standard out of 100: the mean compliance of its policies
+    pub fn score(&self, standard: Standard) -> u8 {
+        let covered: Vec<f64> = self.results.iter()
+            .filter(|result| result.policy.standards().contains(&standard))
+            .map(|result| result.compliance)
+            .collect();
+        if covered.is_empty() {
+            return 100;
+        }
+        (covered.iter().sum::<f64>() / covered.len() as f64 * 100.0).round().clamp(0.0, 100.0) as u8
+    }
+
+    pub fn violated(&self) -> impl Iterator<Item = &PolicyResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+/// Check every policy against the evidence
+pub fn evaluate(evidence: &PolicyEvidence, settings: &SecurityPolicySettings) -> PolicyEvaluation {
+    let results = Policy::ALL.into_iter()
+        .map(|policy| {
+            let (compliance, violations) = match policy {
+                Policy::MfaRequired => share_of(evidence.accounts, evidence.access.without_mfa.clone()),
+                Policy::EncryptionAtRest => {
+                    let unencrypted = evidence.servers.iter().filter(|(_, encrypted)| !**encrypted).map(|(hostname, _)| hostname.clone()).collect();
+                    share_of(evidence.servers.len(), unencrypted)
+                }
+                Policy::FirewallDefaultDeny => match evidence.default_deny_rule {
+                    Some(_) => (1.0, Vec::new()),
+                    None => (0.0, vec!["no enabled rule denies all other traffic".to_string()]),
+                },
+                Policy::BackupRetention => {
+                    let (kept, needed) = (evidence.backup_retention_days, settings.min_backup_retention_days);
+                    if kept >= needed {
+                        (1.0, Vec::new())
+                    } else {
+                        (f64::from(kept) / f64::from(needed), vec![format!("backups kept {} days, {} needed", kept, needed)])
+                    }
+                }
+                Policy::AccessReviewed => {
+                    let access = &evidence.access;
+                    share_of(evidence.accounts, access.orphaned.iter().chain(&access.over_privileged).cloned().collect())
+                }
+            };
+            PolicyResult { policy, compliance, violations }
+        })
+        .collect();
+    PolicyEvaluation { results }
+}
+
+/// Compliance of `total` items when `failing` of them do not comply
+fn share_of(total: usize, failing: Vec<String>) -> (f64, Vec<String>) {
+    if total == 0 {
+        return (1.0, failing);
+    }
+    ((1.0 - failing.len() as f64 / total as f64).max(0.0), failing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_follow_the_evidence() {
+        let settings = SecurityPolicySettings::default();
+        let mut evidence = PolicyEvidence {
+            accounts: 4,
+            access: AccessFindings { without_mfa: vec!["Alice".to_string()], ..AccessFindings::default() },
+            servers: BTreeMap::from([("web-1".to_string(), true), ("web-2".to_string(), false)]),
+            default_deny_rule: Some("deny-all".to_string()),
+            backup_retention_days: 30,
+        };
+        let evaluation = evaluate(&evidence, &settings);
+        assert_eq!(evaluation.violated().map(|result| result.policy).collect::<Vec<_>>(), vec![Policy::MfaRequired, Policy::EncryptionAtRest]);
+        // GDPR: encryption half met, access fully met
+        assert_eq!(evaluation.score(Standard::Gdpr), 75);
+        // SOC 2: MFA 0.75, encryption 0.5, firewall, backups and access 1.0
+        assert_eq!(evaluation.score(Standard::Soc2), 85);
+
+        evidence.access.without_mfa.clear();
+        evidence.servers.insert("web-2".to_string(), true);
+        evidence.default_deny_rule = None;
+        evidence.backup_retention_days = 15;
+        let evaluation = evaluate(&evidence, &settings);
+        assert_eq!(evaluation.score(Standard::Gdpr), 100);
+        assert_eq!(evaluation.score(Standard::Iso27001), 70);
+        assert_eq!(evaluation.violated().count(), 2);
+    }
+}
@@ -0,0 +1,209 @@
+//! Shift Scheduling - Round-the-Clock Ops Shifts and Handoffs
+//!
+//! This module implements the shifts Ops covers the whole day with:
+//! - Shifts by hour of the day, configured or derived from the working
+//!   hours: a day shift over them and a night shift over the rest
+//! - Each Ops agent belongs to one shift, new agents joining the one with the
+//!   fewest; agents only take their turns while their shift is on
+//! - At each shift change the outgoing agents hand their open tickets and
+//!   active incidents to the incoming ones, with a handoff note listing what
+//!   changed hands
+//! - Hours with nobody on shift are counted as gaps; services answer slower
+//!   and tickets wait for the next shift until someone is back
+//!
+//! The orchestrator checks for a shift change every simulated hour.
+
+use crate::config::{ShiftDefinition, ShiftSettings};
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use uuid::Uuid;
+
+/// The configured shifts, or a day shift over the working hours and a night
+/// shift over the rest
+pub fn shifts(settings: &ShiftSettings, working_hours: (u8, u8)) -> Vec<ShiftDefinition> {
+    if !settings.shifts.is_empty() {
+        return settings.shifts.clone();
+    }
+    let (start, end) = working_hours;
+    let shift = |name: &str, start_hour: u8, end_hour: u8| ShiftDefinition { name: name.to_string(), start_hour, end_hour };
+    if end - start >= 24 {
+        return vec![shift("Day", 0, 24)];
+    }
+    vec![shift("Day", start, end), shift("Night", end % 24, start)]
+}
+
+fn covers(shift: &ShiftDefinition, hour: u8) -> bool {
+    if shift.start_hour < shift.end_hour {
+        (shift.start_hour..shift.end_hour).contains(&hour)
+    } else {
+        hour >= shift.start_hour || hour < shift.end_hour
+    }
+}
+
+/// Shift on at `at`; the first listed wins where shifts overlap
+pub fn current(shifts: &[ShiftDefinition], at: DateTime<Utc>) -> Option<&ShiftDefinition> {
+    shifts.iter().find(|shift| covers(shift, at.hour() as u8))
+}
+
+/// When the shift next starts after `at`
+pub fn next_start(shift: &ShiftDefinition, at: DateTime<Utc>) -> DateTime<Utc> {
+    let today = at.date_naive().and_hms_opt(u32::from(shift.start_hour % 24), 0, 0).expect("valid hour").and_utc();
+    if today > at { today } else { today + chrono::Duration::days(1) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoffKind {
+    Ticket,
+    Incident,
+}
+
+/// Open work that changed hands at a shift change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffItem {
+    pub kind: HandoffKind,
+    pub id: Uuid,
+    pub title: String,
+    /// Its status when handed over
+    pub status: String,
+    pub to: Uuid,
+}
+
+/// What an outgoing agent handed over at the end of its shift
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffNote {
+    pub at: DateTime<Utc>,
+    pub from_shift: String,
+    pub to_shift: String,
+    pub from: Uuid,
+    pub items: Vec<HandoffItem>,
+}
+
+impl HandoffNote {
+    pub fn summary(&self) -> String {
+        let count = |kind: HandoffKind| self.items.iter().filter(|item| item.kind == kind).count();
+        let items: Vec<String> = self.items.iter().map(|item| format!("'{}' ({})", item.title, item.status)).collect();
+        format!("{} to {} handoff: {} tickets and {} incidents: {}",
+                self.from_shift, self.to_shift, count(HandoffKind::Ticket), count(HandoffKind::Incident), items.join(", "))
+    }
+}
+
+/// A shift ending and the next one starting
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShiftChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// Which shift each Ops agent works, the shift on and the handoffs so far
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShiftRoster {
+    members: BTreeMap<Uuid, String>,
+    /// Shift on at the last check
+    current: Option<String>,
+    notes: VecDeque<HandoffNote>,
+    /// Tickets and incidents handed over so far
+    pub handed_over: u64,
+    /// Hours nobody was on shift
+    pub unstaffed_hours: u64,
+}
+
+impl ShiftRoster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop agents that left and put new ones on the shift with the fewest;
+    /// returns the new ones with their shift
+    pub fn sync(&mut self, agents: &[Uuid], shifts: &[ShiftDefinition]) -> Vec<(Uuid, String)> {
+        self.members.retain(|agent_id, shift| agents.contains(agent_id) && shifts.iter().any(|defined| defined.name == *shift));
+        let mut joined = Vec::new();
+        for agent_id in agents {
+            if self.members.contains_key(agent_id) {
+                continue;
+            }
+            let Some(shift) = shifts.iter().min_by_key(|shift| self.staff(&shift.name).len()) else {
+                break;
+            };
+            self.members.insert(*agent_id, shift.name.clone());
+            joined.push((*agent_id, shift.name.clone()));
+        }
+        joined
+    }
+
+    pub fn shift_of(&self, agent_id: &Uuid) -> Option<&str> {
+        self.members.get(agent_id).map(String::as_str)
+    }
+
+    /// Agents working the shift
+    pub fn staff(&self, shift: &str) -> Vec<Uuid> {
+        self.members.iter().filter(|(_, name)| name.as_str() == shift).map(|(agent_id, _)| *agent_id).collect()
+    }
+
+    /// Whether anyone works the shift on at `at`
+    pub fn staffed(&self, shifts: &[ShiftDefinition], at: DateTime<Utc>) -> bool {
+        current(shifts, at).is_some_and(|shift| !self.staff(&shift.name).is_empty())
+    }
+
+    /// Note the shift on at `at`, counting the hour as a gap when nobody works
+    /// it; returns the change when a new shift started since the last check
+    pub fn advance(&mut self, shifts: &[ShiftDefinition], at: DateTime<Utc>) -> Option<ShiftChange> {
+        if !self.staffed(shifts, at) {
+            self.unstaffed_hours += 1;
+        }
+        let now_on = current(shifts, at).map(|shift| shift.name.clone());
+        let previous = std::mem::replace(&mut self.current, now_on.clone());
+        match (previous, now_on) {
+            (Some(from), Some(to)) if from != to => Some(ShiftChange { from, to }),
+            _ => None,
+        }
+    }
+
+    /// Keep a handoff note, dropping the oldest past `max_notes`
+    pub fn record(&mut self, note: HandoffNote, max_notes: usize) {
+        self.handed_over += note.items.len() as u64;
+        self.notes.push_back(note);
+        while self.notes.len() > max_notes {
+            self.notes.pop_front();
+        }
+    }
+
+    /// Handoff notes, oldest first
+    pub fn notes(&self) -> impl DoubleEndedIterator<Item = &HandoffNote> {
+        self.notes.iter()
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agents_split_across_shifts_that_change_around_the_clock() {
+        let settings = ShiftSettings::default();
+        let shifts = shifts(&settings, (9, 18));
+        assert_eq!(shifts.iter().map(|shift| (shift.start_hour, shift.end_hour)).collect::<Vec<_>>(), vec![(9, 18), (18, 9)]);
+        let at = |hour: u32| -> DateTime<Utc> { format!("2024-03-01T{:02}:00:00Z", hour).parse().unwrap() };
+        assert_eq!(current(&shifts, at(23)).unwrap().name, "Night");
+        assert_eq!(current(&shifts, at(3)).unwrap().name, "Night");
+        assert_eq!(next_start(&shifts[0], at(20)), at(9) + chrono::Duration::days(1));
+
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut roster = ShiftRoster::new();
+        roster.sync(&[alice], &shifts);
+        assert_eq!(roster.advance(&shifts, at(10)), None);
+        // Nobody works nights yet
+        assert_eq!(roster.advance(&shifts, at(18)), Some(ShiftChange { from: "Day".to_string(), to: "Night".to_string() }));
+        assert_eq!(roster.unstaffed_hours, 1);
+
+        assert_eq!(roster.sync(&[alice, bob], &shifts), vec![(bob, "Night".to_string())]);
+        assert!(roster.staffed(&shifts, at(19)));
+        roster.sync(&[bob], &shifts);
+        assert!(!roster.staffed(&shifts, at(10)));
+    }
+}
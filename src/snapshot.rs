@@ -0,0 +1,424 @@
+//! Simulation Snapshots - Persist & Restore
+//!
+//! This module implements saving and restoring the full simulation state:
+//! - Every agent with its department-specific state
+//! - Active projects and per-agent reports held by the orchestrator
+//! - Messages queued but not yet dispatched, and recently dispatched ones
+//!   for incident timelines and thread histories
+//! - Step counter and configuration, so a resumed run continues exactly
+//!   where it left off
+//! - Agents of registered implementations as JSON state under their kind,
+//!   restored through the agent registry
+
+use crate::catalog::ServiceCatalog;
+use crate::agents::{Agent, AgentTrait};
+use crate::agentstate::AgentStates;
+use crate::alerting::AlertEngine;
+use crate::analytics::KpiHistory;
+use crate::capacity::CapacityPlanner;
+use crate::communication::Message;
+use crate::config::SimulationConfig;
+use crate::departments::devops::DevOpsAgent;
+use crate::departments::engineering::EngineeringAgent;
+use crate::departments::infosec::InfoSecAgent;
+use crate::departments::marketing::{CampaignReport, MarketingAgent};
+use crate::departments::networking::NetworkingAgent;
+use crate::departments::ops::OpsAgent;
+use crate::departments::{devops, engineering, infosec, marketing, networking, ops, DepartmentAgent};
+use crate::deptreport::ManagerReports;
+use crate::economy::Economy;
+use crate::dlp::DlpMonitor;
+use crate::executive::ExecutiveAgent;
+use crate::iam::IdentityDirectory;
+use crate::knowledge::KnowledgeBase;
+use crate::memory::AgentMemories;
+use crate::pipeline::BuildQueue;
+use crate::promotion::Promotions;
+use crate::redteam::AttackSimulator;
+use crate::intake::IntakeController;
+use crate::orgchart::OrgChart;
+use crate::phishing::AwarenessProgram;
+use crate::progression::SkillProgression;
+use crate::projects::Project;
+use crate::quota::QuotaLedger;
+use crate::registry::{AgentRegistry, RegistryError};
+use crate::routing::MessageRouter;
+use crate::scheduler::Scheduler;
+use crate::statuspage::StatusPage;
+use crate::shifts::ShiftRoster;
+use crate::threads::{self, ConversationThreads, Thread};
+use crate::threatintel::ThreatIntel;
+use crate::ticketqueue::TicketQueue;
+use crate::releasenotes::ReleaseLog;
+use crate::risk::RiskRegister;
+use crate::scorecard::ScorecardBook;
+use crate::staffing::StaffingLog;
+use crate::supervisor::Supervisor;
+use crate::taskgraph::TaskGraph;
+use crate::timeline::Timeline;
+use crate::vcs::Repository;
+use crate::waitgraph::WaitForGraph;
+use crate::webhooks::{EntityTracker, WebhookDispatcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Current snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Complete serialized state of a company simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationSnapshot {
+    pub version: u32,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub step_count: u64,
+    pub config: SimulationConfig,
+    pub agents: HashMap<Uuid, AgentSnapshot>,
+    pub projects: HashMap<Uuid, Project>,
+    pub pending_messages: Vec<Message>,
+    pub campaign_reports: HashMap<Uuid, CampaignReport>,
+    #[serde(default)]
+    pub catalog: ServiceCatalog,
+    /// Recently dispatched messages, oldest first
+    #[serde(default)]
+    pub message_log: Vec<Message>,
+    /// Pending timed events and the simulated clock
+    #[serde(default)]
+    pub scheduler: Option<Scheduler>,
+    /// Inboxes of messages dispatched but not yet delivered
+    #[serde(default)]
+    pub router: MessageRouter,
+    /// Department circuit breakers and deferred intake
+    #[serde(default)]
+    pub intake: IntakeController,
+    /// Open waits between agents and broken deadlocks
+    #[serde(default)]
+    pub wait_graph: WaitForGraph,
+    /// Hourly KPI samples on the simulated clock
+    #[serde(default)]
+    pub kpi_history: KpiHistory,
+    /// Last seen status of every ticket, incident and deployment
+    #[serde(default)]
+    pub entity_tracker: EntityTracker,
+    /// Webhook notifications not yet delivered
+    #[serde(default)]
+    pub webhooks: WebhookDispatcher,
+    /// Product source code history
+    #[serde(default)]
+    pub repository: Option<Repository>,
+    /// Agent failures, backoffs and restarts
+    #[serde(default)]
+    pub supervisor: Supervisor,
+    /// Release notes of shipped deployments
+    #[serde(default)]
+    pub release_notes: ReleaseLog,
+    /// Service measurements, operations reviews and improvement tasks
+    #[serde(default)]
+    pub scorecards: ScorecardBook,
+    /// Quotas, reservations and quota-increase requests
+    #[serde(default)]
+    pub quotas: QuotaLedger,
+    /// Project tasks and their dependencies
+    #[serde(default)]
+    pub task_graph: TaskGraph,
+    /// Company risks and quarterly risk reviews
+    #[serde(default)]
+    pub risks: RiskRegister,
+    /// Experience of every agent by kind of work
+    #[serde(default)]
+    pub progression: SkillProgression,
+    /// Rollups, approvals and escalations along the reporting lines
+    #[serde(default)]
+    pub org: OrgChart,
+    /// Goals, allocations and reviews of the CEO
+    #[serde(default)]
+    pub executive: ExecutiveAgent,
+    /// Agents hired and offboarded during the run
+    #[serde(default)]
+    pub staffing: StaffingLog,
+    /// Cash and monthly P&L; snapshots from before the economy start afresh
+    #[serde(default)]
+    pub economy: Option<Economy>,
+    /// Articles agents have written about past incidents
+    #[serde(default)]
+    pub knowledge: KnowledgeBase,
+    /// Pipelines queued, running or recently finished
+    #[serde(default)]
+    pub pipelines: BuildQueue,
+    /// Builds being promoted and the version running in each environment
+    #[serde(default)]
+    pub promotions: Promotions,
+    /// Active and recently resolved alerts
+    #[serde(default)]
+    pub alerts: AlertEngine,
+    /// Red team campaigns run so far
+    #[serde(default)]
+    pub red_team: AttackSimulator,
+    /// Accounts, access requests and access reviews
+    #[serde(default)]
+    pub iam: IdentityDirectory,
+    /// Phishing campaigns, awareness and training
+    #[serde(default)]
+    pub phishing: AwarenessProgram,
+    /// Threat indicators, sightings and advisories
+    #[serde(default)]
+    pub threat_intel: ThreatIntel,
+    /// Transfers DLP monitored and the exposures it found
+    #[serde(default)]
+    pub dlp: DlpMonitor,
+    /// Support tickets waiting for an Ops agent
+    #[serde(default)]
+    pub ticket_queue: TicketQueue,
+    /// Utilization history and capacity forecasts
+    #[serde(default)]
+    pub capacity: CapacityPlanner,
+    /// Incidents posted for customers, their notices and SLA credits
+    #[serde(default)]
+    pub status_page: StatusPage,
+    /// Ops shifts and their handoff notes
+    #[serde(default)]
+    pub shift_roster: ShiftRoster,
+    /// Every agent's state and who is on leave
+    #[serde(default)]
+    pub agent_states: AgentStates,
+    /// What each agent remembers of its recent messages and work
+    #[serde(default)]
+    pub memories: AgentMemories,
+    /// Status updates managers are collecting, and their latest reports
+    #[serde(default)]
+    pub manager_reports: ManagerReports,
+    /// Incidents and causality ids linked to their conversation thread
+    #[serde(default)]
+    pub threads: ConversationThreads,
+}
+
+/// Serialized form of a department agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "department", content = "state")]
+pub enum AgentSnapshot {
+    DevOps(DevOpsAgent),
+    Engineering(EngineeringAgent),
+    InfoSec(InfoSecAgent),
+    Networking(NetworkingAgent),
+    Ops(OpsAgent),
+    Marketing(MarketingAgent),
+    /// An agent of a registered implementation
+    Plugin(PluginSnapshot),
+}
+
+/// Serialized form of an agent of a registered implementation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSnapshot {
+    /// Kind of the factory that restores the agent
+    pub kind: String,
+    pub agent: Agent,
+    pub state: serde_json::Value,
+    pub handled_message_types: Vec<String>,
+}
+
+impl AgentSnapshot {
+    /// Base agent properties of the snapshotted agent
+    pub fn agent(&self) -> &Agent {
+        match self {
+            AgentSnapshot::DevOps(agent) => agent.get_agent(),
+            AgentSnapshot::Engineering(agent) => agent.get_agent(),
+            AgentSnapshot::InfoSec(agent) => agent.get_agent(),
+            AgentSnapshot::Networking(agent) => agent.get_agent(),
+            AgentSnapshot::Ops(agent) => agent.get_agent(),
+            AgentSnapshot::Marketing(agent) => agent.get_agent(),
+            AgentSnapshot::Plugin(plugin) => &plugin.agent,
+        }
+    }
+
+    /// Rebuild the live agent from its snapshot; registered implementations
+    /// are restored by their factory in `registry`
+    pub fn into_agent(self, registry: &AgentRegistry) -> Result<Box<dyn DepartmentAgent>, RegistryError> {
+        Ok(match self {
+            AgentSnapshot::DevOps(agent) => Box::new(agent),
+            AgentSnapshot::Engineering(agent) => Box::new(agent),
+            AgentSnapshot::InfoSec(agent) => Box::new(agent),
+            AgentSnapshot::Networking(agent) => Box::new(agent),
+            AgentSnapshot::Ops(agent) => Box::new(agent),
+            AgentSnapshot::Marketing(agent) => Box::new(agent),
+            AgentSnapshot::Plugin(plugin) => registry.restore(plugin)?,
+        })
+    }
+
+    /// Message types the agent's department acts on
+    pub fn handled_message_types(&self) -> Vec<&str> {
+        match self {
+            AgentSnapshot::DevOps(_) => devops::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::Engineering(_) => engineering::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::InfoSec(_) => infosec::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::Networking(_) => networking::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::Ops(_) => ops::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::Marketing(_) => marketing::HANDLED_MESSAGE_TYPES.to_vec(),
+            AgentSnapshot::Plugin(plugin) => plugin.handled_message_types.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl SimulationSnapshot {
+    /// Write the snapshot to disk as JSON
+    ///
+    /// The file is written to a temporary path first and renamed into place so
+    /// an interrupted write never leaves a truncated snapshot behind.
+    pub fn save(&self, path: &Path) -> Result<(), SnapshotError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| SnapshotError::Serialization(e.to_string()))?;
+
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, path)?;
+
+        println!("💾 Saved simulation snapshot at step {} to {}", self.step_count, path.display());
+        Ok(())
+    }
+
+    /// Read a snapshot from disk
+    pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+        let json = std::fs::read_to_string(path)?;
+        let snapshot: Self = serde_json::from_str(&json)
+            .map_err(|e| SnapshotError::Serialization(e.to_string()))?;
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Simulated time at which the snapshot was taken
+    pub fn simulated_now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.scheduler.as_ref().map_or(self.taken_at, Scheduler::now)
+    }
+
+    /// Reconstruct the timeline of an incident held by any Ops agent
+    pub fn incident_timeline(&self, incident_id: Uuid) -> Option<Timeline> {
+        let deployments: Vec<_> = self.agents.values()
+            .filter_map(|agent| match agent {
+                AgentSnapshot::DevOps(devops) => Some(devops.active_deployments.values().cloned()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        self.agents.values()
+            .find_map(|agent| match agent {
+                AgentSnapshot::Ops(ops) => ops.incidents.get(&incident_id),
+                _ => None,
+            })
+            .map(|incident| incident.timeline(&self.message_log, &deployments))
+    }
+
+    /// Every recorded message of a conversation thread
+    pub fn thread_history(&self, thread_id: Uuid) -> Option<Thread> {
+        threads::history(&self.message_log, thread_id)
+    }
+}
+
+/// Snapshot-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("Snapshot I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Snapshot serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Unsupported snapshot version: {0}")]
+    UnsupportedVersion(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_snapshot() -> SimulationSnapshot {
+        let ops = OpsAgent::new("Snapshot Ops".to_string(), None);
+        let marketing = MarketingAgent::new("Snapshot Marketing".to_string(), None);
+
+        SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now(),
+            step_count: 42,
+            config: SimulationConfig::default(),
+            agents: HashMap::from([
+                (Uuid::new_v4(), ops.snapshot()),
+                (Uuid::new_v4(), marketing.snapshot()),
+            ]),
+            projects: HashMap::new(),
+            pending_messages: vec![],
+            campaign_reports: HashMap::new(),
+            catalog: ServiceCatalog::default(),
+            message_log: vec![],
+            scheduler: None,
+            router: MessageRouter::new(),
+            intake: IntakeController::new(),
+            wait_graph: WaitForGraph::new(),
+            kpi_history: KpiHistory::new(),
+            entity_tracker: EntityTracker::new(),
+            webhooks: WebhookDispatcher::new(),
+            repository: None,
+            supervisor: Supervisor::new(),
+            release_notes: ReleaseLog::new(),
+            scorecards: ScorecardBook::new(),
+            quotas: QuotaLedger::new(),
+            task_graph: TaskGraph::new(),
+            risks: RiskRegister::new(),
+            progression: SkillProgression::new(),
+            org: OrgChart::new(),
+            executive: ExecutiveAgent::new(),
+            staffing: StaffingLog::new(),
+            economy: None,
+            knowledge: KnowledgeBase::new(),
+            pipelines: BuildQueue::new(),
+            promotions: Promotions::new(),
+            alerts: AlertEngine::new(),
+            red_team: AttackSimulator::new(),
+            iam: IdentityDirectory::new(),
+            phishing: AwarenessProgram::new(),
+            threat_intel: ThreatIntel::new(),
+            dlp: DlpMonitor::new(),
+            ticket_queue: TicketQueue::new(),
+            capacity: CapacityPlanner::new(),
+            status_page: StatusPage::new(),
+            shift_roster: ShiftRoster::new(),
+            agent_states: AgentStates::new(),
+            memories: AgentMemories::new(),
+            manager_reports: ManagerReports::new(),
+            threads: ConversationThreads::new(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let path = std::env::temp_dir().join(format!("snapshot-{}.json", Uuid::new_v4()));
+        let snapshot = test_snapshot();
+        snapshot.save(&path).unwrap();
+
+        let restored = SimulationSnapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(restored.step_count, 42);
+        assert_eq!(restored.agents.len(), 2);
+        let names: Vec<String> = restored.agents.into_values()
+            .map(|agent| agent.into_agent(&AgentRegistry::builtin()).unwrap().get_agent().name.clone())
+            .collect();
+        assert!(names.contains(&"Snapshot Ops".to_string()));
+        assert!(names.contains(&"Snapshot Marketing".to_string()));
+    }
+
+    #[test]
+    fn test_snapshot_version_mismatch() {
+        let path = std::env::temp_dir().join(format!("snapshot-{}.json", Uuid::new_v4()));
+        let mut snapshot = test_snapshot();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        snapshot.save(&path).unwrap();
+
+        let result = SimulationSnapshot::load(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SnapshotError::UnsupportedVersion(_))));
+    }
+}
@@ -0,0 +1,197 @@
+//! Staffing - Hiring and Offboarding at Runtime
+//!
+//! This module implements changing who works at the company while it runs:
+//! - Hires and offboardings requested through the control API or scripted
+//!   for a day of the run
+//! - HR's daily workload review: hiring into departments whose open work
+//!   per agent runs high or that are below the CEO's headcount allocation,
+//!   and offboarding idle agents down to the department's starting size
+//! - A log of every hire and exit
+//!
+//! The orchestrator carries the changes out: new agents get a manager and
+//! turns, and a leaver's tickets, incidents and project tasks go to the
+//! least busy colleague in the department.
+
+use crate::config::StaffingSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Log entries kept
+const MAX_LOG: usize = 200;
+
+/// Change to the company's staff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StaffingChange {
+    /// Hire into a department, named after it when no name is given
+    Hire {
+        department: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Offboard an agent by name or id
+    Offboard { agent: String },
+}
+
+/// Staffing change scripted for a day of the run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledChange {
+    /// Simulated days after the run starts
+    pub day: u32,
+    #[serde(flatten)]
+    pub change: StaffingChange,
+}
+
+/// A department's workload as HR sees it
+#[derive(Debug, Clone, Default)]
+pub struct DepartmentLoad {
+    pub staff: usize,
+    pub backlog: usize,
+    /// Starting size, which HR never offboards below
+    pub floor: usize,
+    /// Headcount the CEO allocated, if any
+    pub allocated: Option<usize>,
+    /// Least busy agent who manages nobody
+    pub idlest: Option<Uuid>,
+}
+
+/// What HR decided for one department
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Hire(String),
+    Offboard(Uuid),
+}
+
+/// HR's workload review: at most one hire or offboarding per department
+pub fn plan(loads: &BTreeMap<String, DepartmentLoad>, hiring_freeze: bool, settings: &StaffingSettings) -> Vec<Decision> {
+    let mut decisions = Vec::new();
+    for (department, load) in loads {
+        let per_agent = load.backlog as f64 / load.staff.max(1) as f64;
+        let short = load.allocated.is_some_and(|allocated| load.staff < allocated);
+        if !hiring_freeze && load.staff < settings.max_department_size && (short || per_agent > settings.hire_above_backlog) {
+            decisions.push(Decision::Hire(department.clone()));
+            continue;
+        }
+        let surplus = load.staff > load.floor && load.allocated.is_none_or(|allocated| load.staff > allocated);
+        if surplus && per_agent < settings.offboard_below_backlog {
+            if let Some(agent) = load.idlest {
+                decisions.push(Decision::Offboard(agent));
+            }
+        }
+    }
+    decisions
+}
+
+/// First "<department> Agent <n>" not already taken
+pub fn next_agent_name<'a>(department: &str, taken: impl IntoIterator<Item = &'a str>) -> String {
+    let taken: Vec<&str> = taken.into_iter().collect();
+    (1..)
+        .map(|n| format!("{} Agent {}", department, n))
+        .find(|name| !taken.contains(&name.as_str()))
+        .expect("unbounded range")
+}
+
+/// A hire or exit that happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaffingEvent {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: String,
+    pub hired: bool,
+    /// Who asked for it: HR, the scenario or the control API
+    pub by: String,
+}
+
+/// Hires and exits so far
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StaffingLog {
+    events: Vec<StaffingEvent>,
+    hires: u32,
+    exits: u32,
+}
+
+impl StaffingLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: StaffingEvent) {
+        if event.hired {
+            self.hires += 1;
+        } else {
+            self.exits += 1;
+        }
+        self.events.push(event);
+        if self.events.len() > MAX_LOG {
+            self.events.remove(0);
+        }
+    }
+
+    pub fn events(&self) -> &[StaffingEvent] {
+        &self.events
+    }
+
+    /// Hires and exits over the whole run
+    pub fn totals(&self) -> (u32, u32) {
+        (self.hires, self.exits)
+    }
+}
+
+/// Why a staffing change could not be made
+#[derive(Debug, thiserror::Error)]
+pub enum StaffingError {
+    #[error("Unknown department '{0}'")]
+    UnknownDepartment(String),
+
+    #[error("No agent type for the {0} department yet")]
+    NoAgentType(String),
+
+    #[error("No agent named '{0}'")]
+    UnknownAgent(String),
+
+    #[error("An agent named '{0}' already works here")]
+    NameTaken(String),
+
+    #[error("{0} is the last agent in the department")]
+    LastInDepartment(String),
+
+    #[error("{0} still manages other agents")]
+    HasReports(String),
+
+    #[error("Runtime error: {0}")]
+    Runtime(#[from] crate::runtime::RuntimeError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_hires_and_offboards() {
+        let settings = StaffingSettings { hire_above_backlog: 6.0, offboard_below_backlog: 1.0, max_department_size: 4, ..StaffingSettings::default() };
+        let idle = Uuid::new_v4();
+        let loads = BTreeMap::from([
+            ("Ops".to_string(), DepartmentLoad { staff: 3, backlog: 21, floor: 3, allocated: None, idlest: None }),
+            ("DevOps".to_string(), DepartmentLoad { staff: 4, backlog: 0, floor: 3, allocated: None, idlest: Some(idle) }),
+            ("InfoSec".to_string(), DepartmentLoad { staff: 2, backlog: 2, floor: 2, allocated: Some(3), idlest: None }),
+            ("Networking".to_string(), DepartmentLoad { staff: 4, backlog: 40, floor: 3, allocated: None, idlest: None }),
+        ]);
+
+        assert_eq!(plan(&loads, false, &settings), vec![
+            Decision::Offboard(idle),
+            Decision::Hire("InfoSec".to_string()),
+            Decision::Hire("Ops".to_string()),
+        ]);
+        // Nobody is hired during a freeze, but idle agents still leave
+        assert_eq!(plan(&loads, true, &settings), vec![Decision::Offboard(idle)]);
+    }
+
+    #[test]
+    fn test_scheduled_change_from_toml() {
+        let change: ScheduledChange = toml::from_str("day = 3\naction = \"hire\"\ndepartment = \"Ops\"").unwrap();
+        assert_eq!(change.change, StaffingChange::Hire { department: "Ops".to_string(), name: None });
+        assert_eq!(next_agent_name("Ops", ["Ops Agent 1", "Ops Agent 3"]), "Ops Agent 2");
+    }
+}
@@ -0,0 +1,413 @@
+//! Status Page - Public Incident Updates, Customer Notices and SLA Credits
+//!
+//! This module implements the status page Ops keeps customers informed on:
+//! - An entry per customer-facing incident, Sev2 and above by default, that
+//!   follows Ops' work on it: investigating while it is open or under
+//!   investigation, identified while it is mitigated, monitoring once it is
+//!   resolved, and resolved after it has been watched for a while or closed
+//! - An update posted for each phase, with a notice to every customer the
+//!   incident affects
+//! - Component status per service: degraded, partial or major outage while
+//!   an incident on it has not been fixed
+//! - SLA credits: outages (Sev1 and Sev2) count against each affected
+//!   customer's uptime for the month, and a customer whose uptime falls
+//!   below a credit tier is credited that share of the monthly bill
+//!
+//! The orchestrator feeds it the Ops incidents every step, emails the notices
+//! when email is on and books the credits.
+
+use crate::catalog::ServiceCatalog;
+use crate::config::StatusPageSettings;
+use crate::departments::ops::{Incident, IncidentStatus, Severity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Minutes in the month uptime is measured over
+const MONTH_MINUTES: f64 = 30.0 * 24.0 * 60.0;
+
+/// Resolved incidents kept on the page
+const MAX_RESOLVED: usize = 100;
+
+/// Notices and credits kept for the report
+const MAX_LOG: usize = 500;
+
+/// Months of outage minutes kept per customer
+const MAX_MONTHS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Investigating,
+    Identified,
+    Monitoring,
+    Resolved,
+}
+
+impl Phase {
+    /// Phase an incident's status puts it in, before monitoring ends
+    pub fn of(status: &IncidentStatus) -> Self {
+        match status {
+            IncidentStatus::Open | IncidentStatus::Investigating => Phase::Investigating,
+            IncidentStatus::Mitigating => Phase::Identified,
+            IncidentStatus::Resolved => Phase::Monitoring,
+            IncidentStatus::PostMortem | IncidentStatus::Closed => Phase::Resolved,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Investigating => "Investigating",
+            Phase::Identified => "Identified",
+            Phase::Monitoring => "Monitoring",
+            Phase::Resolved => "Resolved",
+        }
+    }
+
+    fn message(&self, services: &str) -> String {
+        match self {
+            Phase::Investigating => format!("We are investigating a problem affecting {}.", services),
+            Phase::Identified => format!("The cause of the problem affecting {} has been identified and a fix is being put in place.", services),
+            Phase::Monitoring => format!("A fix has been put in place for {} and we are monitoring the results.", services),
+            Phase::Resolved => format!("The problem affecting {} has been resolved.", services),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Operational,
+    Degraded,
+    PartialOutage,
+    MajorOutage,
+}
+
+impl ComponentStatus {
+    fn of(severity: &Severity) -> Self {
+        match severity {
+            Severity::Sev1 => ComponentStatus::MajorOutage,
+            Severity::Sev2 => ComponentStatus::PartialOutage,
+            Severity::Sev3 | Severity::Sev4 => ComponentStatus::Degraded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusUpdate {
+    pub phase: Phase,
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// An incident as customers see it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusIncident {
+    pub incident_id: Uuid,
+    pub title: String,
+    pub severity: Severity,
+    pub affected_services: Vec<String>,
+    pub affected_customers: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Oldest first
+    pub updates: Vec<StatusUpdate>,
+}
+
+impl StatusIncident {
+    pub fn phase(&self) -> Phase {
+        self.updates.last().map_or(Phase::Investigating, |update| update.phase)
+    }
+
+    fn services(&self) -> String {
+        if self.affected_services.is_empty() {
+            "some of our services".to_string()
+        } else {
+            self.affected_services.join(", ")
+        }
+    }
+}
+
+/// Message to a customer about an incident affecting them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomerNotice {
+    pub customer_id: String,
+    pub incident_id: Uuid,
+    pub phase: Phase,
+    pub subject: String,
+    pub body: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Share of a monthly bill owed back for missed uptime
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlaCredit {
+    pub customer_id: String,
+    pub incident_id: Uuid,
+    /// e.g. "2024-03"
+    pub month: String,
+    /// Customer's uptime for the month, in percent
+    pub uptime: f64,
+    /// Share of the bill credited by this incident, in percent
+    pub percent: f64,
+    pub amount: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// A customer's outages over a month
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Outage {
+    minutes: f64,
+    /// Share of the bill credited so far, in percent
+    credited_percent: f64,
+}
+
+/// What a round of observing posted
+#[derive(Debug, Clone, Default)]
+pub struct Posted {
+    /// Incident titles and the updates posted on them
+    pub updates: Vec<(String, StatusUpdate)>,
+    pub notices: Vec<CustomerNotice>,
+    pub credits: Vec<SlaCredit>,
+}
+
+/// Incidents on the status page, the notices sent and the credits owed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusPage {
+    pub incidents: Vec<StatusIncident>,
+    /// Outages by month, then customer
+    outages: BTreeMap<String, BTreeMap<String, Outage>>,
+    /// Most recent notices, oldest first
+    pub notices: Vec<CustomerNotice>,
+    /// Most recent credits, oldest first
+    pub credits: Vec<SlaCredit>,
+    /// Notices sent so far
+    pub notices_sent: u64,
+    /// Credits owed so far
+    pub credited: f64,
+}
+
+impl StatusPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Incidents still being worked
+    pub fn active(&self) -> impl Iterator<Item = &StatusIncident> {
+        self.incidents.iter().filter(|incident| incident.phase() < Phase::Resolved)
+    }
+
+    /// Status of each service with an incident that is not fixed yet; every
+    /// other service is operational
+    pub fn components(&self) -> BTreeMap<String, ComponentStatus> {
+        let mut components = BTreeMap::new();
+        for incident in self.active().filter(|incident| incident.phase() < Phase::Monitoring) {
+            for service in &incident.affected_services {
+                let status = components.entry(service.clone()).or_insert(ComponentStatus::Operational);
+                *status = (*status).max(ComponentStatus::of(&incident.severity));
+            }
+        }
+        components
+    }
+
+    /// Worst component status
+    pub fn overall(&self) -> ComponentStatus {
+        self.components().into_values().max().unwrap_or(ComponentStatus::Operational)
+    }
+
+    /// Post updates for the incidents that moved on, notify the customers
+    /// they affect and credit those whose uptime fell below a tier
+    pub fn observe(&mut self, incidents: &[Incident], catalog: &ServiceCatalog, settings: &StatusPageSettings, now: DateTime<Utc>) -> Posted {
+        let mut posted = Posted::default();
+        for incident in incidents {
+            if incident.duplicate_of.is_some() || incident.severity.rank() < settings.min_severity.rank() {
+                continue;
+            }
+            let mut phase = Phase::of(&incident.status);
+            let monitored_until = incident.resolved_at.map(|at| at + chrono::Duration::minutes(i64::from(settings.monitoring_minutes)));
+            if phase == Phase::Monitoring && monitored_until.is_some_and(|until| until <= now) {
+                phase = Phase::Resolved;
+            }
+            let index = match self.incidents.iter().position(|entry| entry.incident_id == incident.id) {
+                Some(index) => index,
+                // Over before it was seen
+                None if phase == Phase::Resolved => continue,
+                None => {
+                    self.incidents.push(StatusIncident {
+                        incident_id: incident.id,
+                        title: incident.title.clone(),
+                        severity: incident.severity.clone(),
+                        affected_services: incident.affected_services.clone(),
+                        affected_customers: Vec::new(),
+                        started_at: incident.created_at,
+                        resolved_at: None,
+                        updates: Vec::new(),
+                    });
+                    self.incidents.len() - 1
+                }
+            };
+            let entry = &mut self.incidents[index];
+            if entry.resolved_at.is_none() {
+                entry.severity = incident.severity.clone();
+                for customer in catalog.customers.values() {
+                    let affected = customer.open_incidents.iter().any(|(id, _)| incident.correlation_ids.contains(id));
+                    if affected && !entry.affected_customers.contains(&customer.id) {
+                        entry.affected_customers.push(customer.id.clone());
+                    }
+                }
+            }
+            if !entry.updates.is_empty() && phase <= entry.phase() {
+                continue;
+            }
+
+            let update = StatusUpdate { phase, at: now, message: phase.message(&entry.services()) };
+            entry.updates.push(update.clone());
+            if settings.notify_customers {
+                for customer_id in &entry.affected_customers {
+                    let name = catalog.customers.get(customer_id).map_or(customer_id.as_str(), |customer| customer.name.as_str());
+                    posted.notices.push(CustomerNotice {
+                        customer_id: customer_id.clone(),
+                        incident_id: entry.incident_id,
+                        phase,
+                        subject: format!("[{}] {}", phase.name(), entry.title),
+                        body: format!("Hello {},\n\n{}\n\nFollow this incident on our status page.\n\nAIvertCo Operations\n", name, update.message),
+                        at: now,
+                    });
+                }
+            }
+            posted.updates.push((entry.title.clone(), update));
+
+            if phase >= Phase::Monitoring && entry.resolved_at.is_none() {
+                let resolved_at = incident.resolved_at.unwrap_or(now);
+                entry.resolved_at = Some(resolved_at);
+                // Lesser incidents degrade the service without taking it down
+                if entry.severity.rank() >= Severity::Sev2.rank() {
+                    let minutes = (resolved_at - entry.started_at).num_minutes().max(0) as f64;
+                    let entry = entry.clone();
+                    posted.credits.extend(self.credit(&entry, minutes, catalog, settings, resolved_at));
+                }
+            }
+        }
+
+        self.notices_sent += posted.notices.len() as u64;
+        self.notices.extend(posted.notices.iter().cloned());
+        self.credited += posted.credits.iter().map(|credit| credit.amount).sum::<f64>();
+        self.credits.extend(posted.credits.iter().cloned());
+        if self.notices.len() > MAX_LOG {
+            self.notices.drain(..self.notices.len() - MAX_LOG);
+        }
+        if self.credits.len() > MAX_LOG {
+            self.credits.drain(..self.credits.len() - MAX_LOG);
+        }
+        let resolved = self.incidents.iter().filter(|incident| incident.phase() == Phase::Resolved).count();
+        let mut excess = resolved.saturating_sub(MAX_RESOLVED);
+        self.incidents.retain(|incident| {
+            let drop = incident.phase() == Phase::Resolved && excess > 0;
+            if drop {
+                excess -= 1;
+            }
+            !drop
+        });
+        posted
+    }
+
+    /// Count an outage against its customers' month and credit those whose
+    /// uptime fell into a higher tier
+    fn credit(&mut self, incident: &StatusIncident, minutes: f64, catalog: &ServiceCatalog, settings: &StatusPageSettings, at: DateTime<Utc>) -> Vec<SlaCredit> {
+        let month = at.format("%Y-%m").to_string();
+        let outages = self.outages.entry(month.clone()).or_default();
+        let mut credits = Vec::new();
+        for customer_id in &incident.affected_customers {
+            let outage = outages.entry(customer_id.clone()).or_default();
+            outage.minutes += minutes;
+            let uptime = (1.0 - outage.minutes / MONTH_MINUTES).max(0.0) * 100.0;
+            let percent = settings.credit_tiers.iter()
+                .filter(|tier| uptime < tier.below_uptime)
+                .map(|tier| tier.credit_percent)
+                .fold(0.0, f64::max);
+            let owed = percent - outage.credited_percent;
+            if owed <= 0.0 {
+                continue;
+            }
+            outage.credited_percent = percent;
+            let amount = catalog.monthly_cost(customer_id).unwrap_or(0.0) * owed / 100.0;
+            credits.push(SlaCredit {
+                customer_id: customer_id.clone(),
+                incident_id: incident.incident_id,
+                month: month.clone(),
+                uptime,
+                percent: owed,
+                amount,
+                at,
+            });
+        }
+        while self.outages.len() > MAX_MONTHS {
+            self.outages.pop_first();
+        }
+        credits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{ServiceDefinition, Tenancy};
+    use crate::departments::ops::CustomerTier;
+
+    fn incident(correlation_id: Uuid, created_at: DateTime<Utc>) -> Incident {
+        Incident {
+            id: Uuid::new_v4(),
+            title: "API errors".to_string(),
+            description: "5xx on every request".to_string(),
+            severity: Severity::Sev1,
+            status: IncidentStatus::Open,
+            affected_services: vec!["api".to_string()],
+            root_cause: None,
+            resolution: None,
+            created_at,
+            resolved_at: None,
+            assigned_team: None,
+            duplicate_of: None,
+            duplicates: vec![],
+            correlation_ids: vec![correlation_id],
+            status_history: vec![],
+            breaches: vec![],
+            knowledge: None,
+            runbook: None,
+        }
+    }
+
+    #[test]
+    fn test_incident_phases_notify_customers_and_credit_outages() {
+        let settings = StatusPageSettings::default();
+        let mut catalog = ServiceCatalog::new(vec![ServiceDefinition { name: "api".to_string(), base_monthly_price: 1000.0 }]);
+        let acme = catalog.onboard_customer("Acme", CustomerTier::Enterprise, Tenancy::Shared);
+        let correlation_id = Uuid::new_v4();
+        let start: DateTime<Utc> = "2024-03-10T08:00:00Z".parse().unwrap();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        catalog.customers.get_mut(&acme).unwrap().open_incidents.push((correlation_id, Severity::Sev1));
+        let mut incident = incident(correlation_id, start);
+        let mut page = StatusPage::new();
+
+        let posted = page.observe(&[incident.clone()], &catalog, &settings, at(5));
+        assert_eq!(posted.notices.len(), 1);
+        assert_eq!(posted.notices[0].subject, "[Investigating] API errors");
+        assert_eq!(page.overall(), ComponentStatus::MajorOutage);
+        assert!(page.observe(&[incident.clone()], &catalog, &settings, at(10)).updates.is_empty());
+
+        // Down for 90 minutes: 99.79% uptime is under the 99.9% tier
+        incident.status = IncidentStatus::Resolved;
+        incident.resolved_at = Some(at(90));
+        let posted = page.observe(&[incident.clone()], &catalog, &settings, at(95));
+        assert_eq!(posted.updates[0].1.phase, Phase::Monitoring);
+        assert_eq!(page.overall(), ComponentStatus::Operational);
+        assert_eq!((posted.credits.len(), posted.credits[0].percent), (1, 10.0));
+        assert_eq!(posted.credits[0].amount, 100.0);
+
+        let posted = page.observe(&[incident], &catalog, &settings, at(90 + i64::from(settings.monitoring_minutes)));
+        assert_eq!(posted.updates[0].1.phase, Phase::Resolved);
+        assert!(posted.credits.is_empty());
+        assert_eq!((page.active().count(), page.notices_sent), (0, 3));
+    }
+}
@@ -0,0 +1,151 @@
+//! Agent Status Reports - A Structured Look Inside Any Agent
+//!
+//! This module implements the report every agent gives of itself through
+//! `DepartmentAgent::status_report`:
+//! - Its workload: open items, and what it has finished of each kind of work
+//! - The tickets, incidents, deployments or features it has open, each with
+//!   its status
+//! - Its skill for each kind of work it does
+//! - Its state and recent activity, which the orchestrator adds from what it
+//!   tracks of the agent: messages and finished work, newest first
+//!
+//! The control API, the console's `agents` command and the managers' status
+//! rollups all read these reports.
+
+use crate::agentstate::AgentState;
+use crate::assignment::WorkKind;
+use crate::memory::AgentMemory;
+use crate::orgchart::WorkStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Recent activity a report lists
+pub const RECENT_ACTIVITY: usize = 10;
+
+/// Something an agent has open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveItem {
+    /// E.g. "ticket" or "deployment"
+    pub kind: String,
+    pub id: Uuid,
+    pub title: String,
+    pub status: String,
+}
+
+impl ActiveItem {
+    pub fn new(kind: &str, id: Uuid, title: impl Into<String>, status: impl std::fmt::Debug) -> Self {
+        Self { kind: kind.to_string(), id, title: title.into(), status: format!("{:?}", status) }
+    }
+}
+
+/// Something an agent did lately
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Activity {
+    pub at: DateTime<Utc>,
+    pub description: String,
+}
+
+/// An agent's current workload, open items, skills and recent activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentStatusReport {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub department: String,
+    pub manager_id: Option<Uuid>,
+    /// Open work items
+    pub backlog: usize,
+    pub active_items: Vec<ActiveItem>,
+    /// Skill for each kind of work the agent does
+    pub skills: BTreeMap<WorkKind, u8>,
+    /// Finished work of each kind still on record
+    pub completed: BTreeMap<WorkKind, usize>,
+    /// Set by the orchestrator; agents do not track their own state
+    pub state: Option<AgentState>,
+    /// Newest first
+    pub recent_activity: Vec<Activity>,
+}
+
+impl AgentStatusReport {
+    /// Open and finished work, for status rollups
+    pub fn work(&self) -> WorkStatus {
+        WorkStatus { backlog: self.backlog, completed: self.completed.values().sum() }
+    }
+
+    /// Add the agent's state and up to `limit` of its latest remembered
+    /// messages and finished work; `name` names other agents
+    pub fn observe(&mut self, state: AgentState, memory: Option<&AgentMemory>, limit: usize, name: impl Fn(Uuid) -> String) {
+        self.state = Some(state);
+        let Some(memory) = memory else {
+            return;
+        };
+        let messages = memory.interactions().map(|interaction| Activity {
+            at: interaction.at,
+            description: if interaction.sent {
+                format!("sent {} to {}", interaction.message_type, name(interaction.with))
+            } else {
+                format!("got {} from {}", interaction.message_type, name(interaction.with))
+            },
+        });
+        let tasks = memory.tasks().map(|task| Activity { at: task.at, description: format!("resolved '{}'", task.title) });
+        let mut activity: Vec<Activity> = messages.chain(tasks).collect();
+        // Stable, so equal times keep the order they were remembered in
+        activity.sort_by(|a, b| b.at.cmp(&a.at));
+        activity.truncate(limit);
+        self.recent_activity = activity;
+    }
+
+    /// One line for the console
+    pub fn summary(&self) -> String {
+        let state = self.state.map_or("unknown", |state| state.as_str());
+        let skills: Vec<String> = self.skills.iter().map(|(kind, skill)| format!("{:?} {}", kind, skill)).collect();
+        let mut line = format!("{} ({}, {}): {} open, {} done", self.name, self.department, state, self.backlog, self.work().completed);
+        if !skills.is_empty() {
+            line.push_str(&format!("; skills {}", skills.join(", ")));
+        }
+        if let Some(latest) = self.recent_activity.first() {
+            line.push_str(&format!("; last {}", latest.description));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemorySettings;
+    use crate::memory::{AgentMemories, CompletedTask};
+
+    #[test]
+    fn test_reports_add_state_and_recent_activity() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let start: DateTime<Utc> = "2024-03-04T10:00:00Z".parse().unwrap();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let settings = MemorySettings::default();
+        let mut memories = AgentMemories::new();
+        memories.remember_message(alice, bob, "status_update", at(0), &settings);
+        memories.remember_task(alice, CompletedTask::ticket("Cannot log in", at(30)), &settings);
+        memories.remember_message(bob, alice, "issue_report", at(10), &settings);
+
+        let mut report = AgentStatusReport {
+            agent_id: alice,
+            name: "Alice".to_string(),
+            department: "Ops".to_string(),
+            manager_id: None,
+            backlog: 2,
+            active_items: vec![ActiveItem::new("ticket", Uuid::new_v4(), "Slow dashboard", crate::departments::ops::TicketStatus::Open)],
+            skills: BTreeMap::from([(WorkKind::Ticket, 70), (WorkKind::Incident, 60)]),
+            completed: BTreeMap::from([(WorkKind::Ticket, 4), (WorkKind::Incident, 1)]),
+            state: None,
+            recent_activity: vec![],
+        };
+        assert_eq!(report.work(), WorkStatus { backlog: 2, completed: 5 });
+        assert_eq!(report.active_items[0].status, "Open");
+
+        report.observe(AgentState::Working, memories.get(&alice), 2, |_| "Bob".to_string());
+        let activity: Vec<&str> = report.recent_activity.iter().map(|activity| activity.description.as_str()).collect();
+        assert_eq!(activity, vec!["resolved 'Cannot log in'", "got issue_report from Bob"]);
+        assert_eq!(report.summary(), "Alice (Ops, working): 2 open, 5 done; skills Ticket 70, Incident 60; last resolved 'Cannot log in'");
+    }
+}
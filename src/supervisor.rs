@@ -0,0 +1,208 @@
+//! Agent Supervision - Health Registry & Crash Recovery
+//!
+//! This module implements supervision of agent actors:
+//! - A health registry recording every agent's failures, last error and
+//!   restarts, so one failing agent no longer fails the simulation step
+//! - Exponential backoff: a failing agent sits out turns and deliveries until
+//!   its backoff elapses, while its messages wait in its inbox
+//! - Restarts from the agent's last healthy checkpoint after repeated
+//!   failures, a fatal error or a crash of its actor task
+//! - Replacement with a fresh agent of the same identity once restarts are
+//!   used up or there is no checkpoint to restart from
+//!
+//! The supervisor only decides; the orchestrator respawns the agents.
+
+use crate::config::SupervisionSettings;
+use crate::departments::AgentError;
+use crate::snapshot::AgentSnapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// How a failed agent is brought back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recovery {
+    /// Respawn from the last healthy checkpoint
+    Restart,
+    /// Respawn as a fresh agent with the same id, name and manager
+    Replace,
+}
+
+/// Failure history of one agent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentHealth {
+    /// Failures since the agent last succeeded
+    pub consecutive_failures: u32,
+    pub total_failures: u32,
+    pub last_error: Option<String>,
+    pub last_failure_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The agent sits out turns and deliveries until then
+    pub backoff_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub restarts: u32,
+    pub replacements: u32,
+}
+
+/// Health registry and recovery policy for all agents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Supervisor {
+    health: BTreeMap<Uuid, AgentHealth>,
+    /// Last state of each agent taken while it was healthy
+    #[serde(skip)]
+    checkpoints: HashMap<Uuid, AgentSnapshot>,
+    last_checkpoint_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn health(&self, id: &Uuid) -> Option<&AgentHealth> {
+        self.health.get(id)
+    }
+
+    /// Agents that have failed since they last succeeded
+    pub fn unhealthy(&self) -> impl Iterator<Item = (&Uuid, &AgentHealth)> {
+        self.health.iter().filter(|(_, health)| health.consecutive_failures > 0)
+    }
+
+    /// Restarts and replacements across all agents
+    pub fn recoveries(&self) -> (u32, u32) {
+        self.health.values().fold((0, 0), |(restarts, replacements), health| {
+            (restarts + health.restarts, replacements + health.replacements)
+        })
+    }
+
+    /// Whether the agent may take its turn or receive messages now
+    pub fn can_run(&self, id: &Uuid, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.health.get(id)
+            .and_then(|health| health.backoff_until)
+            .is_none_or(|until| now >= until)
+    }
+
+    pub fn record_success(&mut self, id: Uuid) {
+        if let Some(health) = self.health.get_mut(&id) {
+            health.consecutive_failures = 0;
+            health.backoff_until = None;
+        }
+    }
+
+    /// Record a failed turn or delivery; returns how to recover after a fatal
+    /// error or once the agent has failed too often in a row, otherwise
+    /// backs it off
+    pub fn record_failure(&mut self, id: Uuid, error: AgentError, settings: &SupervisionSettings, now: chrono::DateTime<chrono::Utc>) -> Option<Recovery> {
+        let health = self.health.entry(id).or_default();
+        health.consecutive_failures += 1;
+        health.total_failures += 1;
+        health.last_error = Some(error.to_string());
+        health.last_failure_at = Some(now);
+
+        if !error.is_retryable() || health.consecutive_failures >= settings.restart_after_failures {
+            return Some(self.recovery_for(id, settings));
+        }
+        let exponent = (health.consecutive_failures - 1).min(16);
+        let backoff = settings.backoff_minutes.saturating_mul(1 << exponent).min(settings.max_backoff_minutes);
+        health.backoff_until = Some(now + chrono::Duration::minutes(backoff));
+        None
+    }
+
+    /// Record that the agent's actor task died; it always needs recovering
+    pub fn record_crash(&mut self, id: Uuid, settings: &SupervisionSettings, now: chrono::DateTime<chrono::Utc>) -> Recovery {
+        let health = self.health.entry(id).or_default();
+        health.consecutive_failures += 1;
+        health.total_failures += 1;
+        health.last_error = Some("actor task stopped unexpectedly".to_string());
+        health.last_failure_at = Some(now);
+        self.recovery_for(id, settings)
+    }
+
+    /// Record a completed recovery; the agent starts over with a clean slate
+    pub fn recovered(&mut self, id: Uuid, recovery: Recovery) {
+        let health = self.health.entry(id).or_default();
+        match recovery {
+            Recovery::Restart => health.restarts += 1,
+            Recovery::Replace => {
+                health.replacements += 1;
+                health.restarts = 0;
+                self.checkpoints.remove(&id);
+            }
+        }
+        health.consecutive_failures = 0;
+        health.backoff_until = None;
+    }
+
+    /// The agent's last healthy checkpoint
+    pub fn checkpoint(&self, id: &Uuid) -> Option<&AgentSnapshot> {
+        self.checkpoints.get(id)
+    }
+
+    pub fn checkpoint_due(&self, settings: &SupervisionSettings, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.last_checkpoint_at
+            .is_none_or(|last| now - last >= chrono::Duration::minutes(settings.checkpoint_interval_minutes))
+    }
+
+    /// Keep the given agent states as checkpoints, skipping agents that are failing
+    pub fn store_checkpoints(&mut self, snapshots: HashMap<Uuid, AgentSnapshot>, now: chrono::DateTime<chrono::Utc>) {
+        for (id, snapshot) in snapshots {
+            if self.health.get(&id).is_none_or(|health| health.consecutive_failures == 0) {
+                self.checkpoints.insert(id, snapshot);
+            }
+        }
+        self.last_checkpoint_at = Some(now);
+    }
+
+    /// Restart while restarts remain and there is a checkpoint, else replace
+    fn recovery_for(&self, id: Uuid, settings: &SupervisionSettings) -> Recovery {
+        let restarts = self.health.get(&id).map_or(0, |health| health.restarts);
+        if restarts < settings.max_restarts && self.checkpoints.contains_key(&id) {
+            Recovery::Restart
+        } else {
+            Recovery::Replace
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::ops::OpsAgent;
+    use crate::departments::DepartmentAgent;
+
+    fn start() -> chrono::DateTime<chrono::Utc> {
+        "2024-03-04T09:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_failures_back_off_then_restart_then_replace() {
+        let settings = SupervisionSettings { restart_after_failures: 3, max_restarts: 1, backoff_minutes: 5, ..SupervisionSettings::default() };
+        let mut supervisor = Supervisor::new();
+        let id = Uuid::new_v4();
+        let boom = || AgentError::TaskExecution("boom".to_string());
+        supervisor.store_checkpoints(HashMap::from([(id, OpsAgent::new("Ops Agent 1".to_string(), None).snapshot())]), start());
+
+        assert_eq!(supervisor.record_failure(id, boom(), &settings, start()), None);
+        assert!(!supervisor.can_run(&id, start() + chrono::Duration::minutes(4)));
+        assert!(supervisor.can_run(&id, start() + chrono::Duration::minutes(5)));
+
+        // The second failure in a row doubles the backoff
+        assert_eq!(supervisor.record_failure(id, boom(), &settings, start()), None);
+        assert!(!supervisor.can_run(&id, start() + chrono::Duration::minutes(9)));
+
+        let recovery = supervisor.record_failure(id, boom(), &settings, start());
+        assert_eq!(recovery, Some(Recovery::Restart));
+        supervisor.recovered(id, Recovery::Restart);
+        assert!(supervisor.can_run(&id, start()));
+        assert_eq!(supervisor.unhealthy().count(), 0);
+
+        // Restarts used up: the next crash replaces the agent
+        assert_eq!(supervisor.record_crash(id, &settings, start()), Recovery::Replace);
+        supervisor.recovered(id, Recovery::Replace);
+        assert_eq!(supervisor.recoveries(), (0, 1));
+        assert!(supervisor.checkpoint(&id).is_none());
+
+        // A fatal error recovers the agent without backing off first
+        let fatal = AgentError::Fatal("actor gone".to_string());
+        assert_eq!(supervisor.record_failure(id, fatal, &settings, start()), Some(Recovery::Replace));
+        assert_eq!(supervisor.health(&id).unwrap().total_failures, 5);
+    }
+}
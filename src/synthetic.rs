@@ -0,0 +1,531 @@
+//! Synthetic Data Generation - Labeled Operational Datasets
+//!
+//! This module implements generating datasets from the simulation's models
+//! without running the live loop:
+//! - Support ticket corpora labeled by category and priority
+//! - Incident logs labeled by root cause, built on catalog fault injection
+//! - Network flow records labeled benign or by attack class
+//! - Security event streams labeled malicious or benign
+//!
+//! Each dataset is written as JSON Lines or CSV together with a manifest
+//! describing its columns and label distribution.
+
+use crate::catalog::{FaultKind, ServiceCatalog, Tenancy};
+use crate::config::{CustomerSettings, SyntheticSettings};
+use crate::departments::infosec::{EventType, Severity as SecuritySeverity};
+use crate::departments::ops::{CustomerTier, Priority};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Dataset kinds the generator can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    Tickets,
+    Incidents,
+    NetworkFlows,
+    SecurityEvents,
+}
+
+impl Dataset {
+    pub const ALL: [Dataset; 4] = [Dataset::Tickets, Dataset::Incidents, Dataset::NetworkFlows, Dataset::SecurityEvents];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dataset::Tickets => "tickets",
+            Dataset::Incidents => "incidents",
+            Dataset::NetworkFlows => "network_flows",
+            Dataset::SecurityEvents => "security_events",
+        }
+    }
+}
+
+/// File format of generated datasets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportSchema {
+    Jsonl,
+    Csv,
+}
+
+impl ExportSchema {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportSchema::Jsonl => "jsonl",
+            ExportSchema::Csv => "csv",
+        }
+    }
+}
+
+/// Flat record that can be written as a CSV row or JSON line
+pub trait Record: Serialize {
+    /// Column names, in output order
+    fn columns() -> &'static [&'static str];
+    /// Field values in column order
+    fn values(&self) -> Vec<String>;
+    /// Ground-truth label of the record
+    fn label(&self) -> &str;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketRecord {
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub customer_id: String,
+    pub customer_tier: CustomerTier,
+    pub service: String,
+    pub priority: Priority,
+    pub category: String,
+    pub title: String,
+    pub description: String,
+    pub resolution_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentRecord {
+    pub id: Uuid,
+    pub declared_at: chrono::DateTime<chrono::Utc>,
+    pub service: String,
+    pub deployment: String,
+    pub tenancy: Tenancy,
+    pub severity: String,
+    pub affected_customers: usize,
+    pub root_cause: String,
+    pub time_to_resolve_minutes: i64,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowRecord {
+    pub ts: chrono::DateTime<chrono::Utc>,
+    pub src_ip: String,
+    pub dst_ip: String,
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub protocol: String,
+    pub bytes: u64,
+    pub packets: u32,
+    pub duration_ms: u32,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityEventRecord {
+    pub id: Uuid,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub event_type: EventType,
+    pub severity: SecuritySeverity,
+    pub source: String,
+    pub description: String,
+    pub malicious: bool,
+    pub label: String,
+}
+
+impl Record for TicketRecord {
+    fn columns() -> &'static [&'static str] {
+        &["id", "created_at", "customer_id", "customer_tier", "service", "priority", "category", "title", "description", "resolution_hours"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(), self.created_at.to_rfc3339(), self.customer_id.clone(),
+            format!("{:?}", self.customer_tier), self.service.clone(), format!("{:?}", self.priority),
+            self.category.clone(), self.title.clone(), self.description.clone(),
+            format!("{:.2}", self.resolution_hours),
+        ]
+    }
+
+    fn label(&self) -> &str {
+        &self.category
+    }
+}
+
+impl Record for IncidentRecord {
+    fn columns() -> &'static [&'static str] {
+        &["id", "declared_at", "service", "deployment", "tenancy", "severity", "affected_customers", "root_cause", "time_to_resolve_minutes", "summary"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(), self.declared_at.to_rfc3339(), self.service.clone(), self.deployment.clone(),
+            self.tenancy.as_str().to_string(), self.severity.clone(), self.affected_customers.to_string(),
+            self.root_cause.clone(), self.time_to_resolve_minutes.to_string(), self.summary.clone(),
+        ]
+    }
+
+    fn label(&self) -> &str {
+        &self.root_cause
+    }
+}
+
+impl Record for FlowRecord {
+    fn columns() -> &'static [&'static str] {
+        &["ts", "src_ip", "dst_ip", "src_port", "dst_port", "protocol", "bytes", "packets", "duration_ms", "label"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.ts.to_rfc3339(), self.src_ip.clone(), self.dst_ip.clone(), self.src_port.to_string(),
+            self.dst_port.to_string(), self.protocol.clone(), self.bytes.to_string(),
+            self.packets.to_string(), self.duration_ms.to_string(), self.label.clone(),
+        ]
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl Record for SecurityEventRecord {
+    fn columns() -> &'static [&'static str] {
+        &["id", "timestamp", "event_type", "severity", "source", "description", "malicious", "label"]
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(), self.timestamp.to_rfc3339(), format!("{:?}", self.event_type),
+            format!("{:?}", self.severity), self.source.clone(), self.description.clone(),
+            self.malicious.to_string(), self.label.clone(),
+        ]
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Summary written next to each dataset file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    pub dataset: Dataset,
+    pub schema: ExportSchema,
+    pub file: PathBuf,
+    pub records: usize,
+    pub columns: Vec<String>,
+    pub label_counts: BTreeMap<String, usize>,
+    pub seed: u64,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Ticket templates: (category, service, title, description, base priority)
+const TICKET_TEMPLATES: &[(&str, &str, &str, &str, Priority)] = &[
+    ("performance", "web-frontend", "Website loading slowly", "Pages take more than 10 seconds to load", Priority::Normal),
+    ("outage", "api-gateway", "API returning 503 errors", "All API calls fail with service unavailable", Priority::Urgent),
+    ("billing", "web-frontend", "Invoice amount incorrect", "Charged twice for the monthly plan", Priority::Low),
+    ("access", "web-frontend", "Cannot log in", "Password reset email never arrives", Priority::Normal),
+    ("data", "database", "Missing records after import", "Rows imported yesterday are not visible", Priority::High),
+    ("feature_request", "api-gateway", "Request for bulk endpoint", "Would like to submit records in batches", Priority::Low),
+];
+
+/// Incident root causes: (label, summary)
+const ROOT_CAUSES: &[(&str, &str)] = &[
+    ("bad_deploy", "Regression shipped in the latest release"),
+    ("capacity", "Resource exhaustion under peak load"),
+    ("config_change", "Misconfigured setting pushed to production"),
+    ("dependency", "Upstream dependency failure"),
+    ("hardware", "Host hardware failure"),
+];
+
+/// Generator for labeled synthetic datasets
+pub struct SyntheticGenerator {
+    rng: StdRng,
+    seed: u64,
+    catalog: ServiceCatalog,
+    start: chrono::DateTime<chrono::Utc>,
+    span: chrono::Duration,
+}
+
+impl SyntheticGenerator {
+    /// Build a generator over a freshly onboarded customer base, with
+    /// records spread over the `days` before `end`
+    pub fn new(seed: u64, customers: &CustomerSettings, days: u32, end: chrono::DateTime<chrono::Utc>) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut catalog = ServiceCatalog::default();
+        catalog.onboard_random_customers(customers, &mut rng);
+        let span = chrono::Duration::days(days.max(1) as i64);
+
+        Self {
+            rng,
+            seed,
+            catalog,
+            start: end - span,
+            span,
+        }
+    }
+
+    /// Random time within the generated period
+    fn random_time(&mut self) -> chrono::DateTime<chrono::Utc> {
+        self.start + chrono::Duration::seconds(self.rng.gen_range(0..self.span.num_seconds()))
+    }
+
+    /// Record id drawn from the seeded generator, so ids repeat with the seed
+    fn random_id(&mut self) -> Uuid {
+        uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid()
+    }
+
+    pub fn tickets(&mut self, count: usize) -> Vec<TicketRecord> {
+        let mut records: Vec<TicketRecord> = (0..count).map(|_| {
+            let (category, service, title, description, base_priority) = &TICKET_TEMPLATES[self.rng.gen_range(0..TICKET_TEMPLATES.len())];
+            let (customer_id, customer_tier) = match self.catalog.random_customer(&mut self.rng) {
+                Some(customer) => (customer.id.clone(), customer.tier),
+                None => ("cust-0".to_string(), CustomerTier::Standard),
+            };
+            // Enterprise customers escalate normal issues
+            let priority = match (base_priority, customer_tier) {
+                (Priority::Normal, CustomerTier::Enterprise) => Priority::High,
+                (priority, _) => priority.clone(),
+            };
+            let resolution_hours = match priority {
+                Priority::Critical | Priority::Urgent => self.rng.gen_range(0.5..4.0),
+                Priority::High => self.rng.gen_range(2.0..24.0),
+                _ => self.rng.gen_range(8.0..96.0),
+            };
+
+            TicketRecord {
+                id: self.random_id(),
+                created_at: self.random_time(),
+                customer_id,
+                customer_tier,
+                service: service.to_string(),
+                priority,
+                category: category.to_string(),
+                title: title.to_string(),
+                description: description.to_string(),
+                resolution_hours,
+            }
+        }).collect();
+        records.sort_by_key(|r| r.created_at);
+        records
+    }
+
+    pub fn incidents(&mut self, count: usize) -> Vec<IncidentRecord> {
+        let mut records = Vec::with_capacity(count);
+        for _ in 0..count {
+            let Some(fault) = self.catalog.inject_fault(&mut self.rng) else {
+                // Only happens when no deployment has customers, so retrying cannot help
+                println!("⚠️ Synthetic: generated {} of {} incidents, no deployment has customers", records.len(), count);
+                break;
+            };
+            let (root_cause, summary) = match fault.kind {
+                FaultKind::NoisyNeighbor => ("noisy_neighbor", format!(
+                    "{} saturated the shared pool", fault.noisy_customer.as_deref().unwrap_or("A tenant"))),
+                FaultKind::Degradation => {
+                    let (label, summary) = ROOT_CAUSES[self.rng.gen_range(0..ROOT_CAUSES.len())];
+                    (label, summary.to_string())
+                }
+            };
+            let severity = if fault.affected_customers.len() > 1 { "Sev2" } else { "Sev3" };
+
+            records.push(IncidentRecord {
+                id: self.random_id(),
+                declared_at: self.random_time(),
+                service: fault.service,
+                deployment: fault.deployment,
+                tenancy: fault.tenancy,
+                severity: severity.to_string(),
+                affected_customers: fault.affected_customers.len(),
+                root_cause: root_cause.to_string(),
+                time_to_resolve_minutes: self.rng.gen_range(10..480),
+                summary,
+            });
+        }
+        records.sort_by_key(|r| r.declared_at);
+        records
+    }
+
+    pub fn network_flows(&mut self, count: usize) -> Vec<FlowRecord> {
+        let mut records: Vec<FlowRecord> = (0..count).map(|_| {
+            let roll = self.rng.gen::<f32>();
+            let label = match roll {
+                r if r < 0.90 => "benign",
+                r if r < 0.95 => "port_scan",
+                r if r < 0.98 => "ddos",
+                _ => "exfiltration",
+            };
+            let src_ip = format!("203.0.{}.{}", self.rng.gen_range(0..=255), self.rng.gen_range(1..255));
+            let dst_ip = format!("10.0.{}.{}", self.rng.gen_range(1..4), self.rng.gen_range(1..255));
+            let (dst_port, protocol, bytes, packets, duration_ms) = match label {
+                "port_scan" => (self.rng.gen_range(1..1024), "TCP", self.rng.gen_range(40..120), 1, self.rng.gen_range(1..5)),
+                "ddos" => (443, "UDP", self.rng.gen_range(50_000..5_000_000), self.rng.gen_range(1_000..50_000), self.rng.gen_range(100..2_000)),
+                "exfiltration" => (443, "TCP", self.rng.gen_range(50_000_000..500_000_000), self.rng.gen_range(40_000..400_000), self.rng.gen_range(60_000..600_000)),
+                _ => ([80, 443, 5432][self.rng.gen_range(0..3)], "TCP", self.rng.gen_range(500..200_000), self.rng.gen_range(4..200), self.rng.gen_range(5..5_000)),
+            };
+
+            FlowRecord {
+                ts: self.random_time(),
+                src_ip,
+                dst_ip,
+                src_port: self.rng.gen_range(1024..=65535),
+                dst_port,
+                protocol: protocol.to_string(),
+                bytes,
+                packets,
+                duration_ms,
+                label: label.to_string(),
+            }
+        }).collect();
+        records.sort_by_key(|r| r.ts);
+        records
+    }
+
+    pub fn security_events(&mut self, count: usize) -> Vec<SecurityEventRecord> {
+        let mut records: Vec<SecurityEventRecord> = (0..count).map(|_| {
+            let malicious = self.rng.gen::<f32>() < 0.15;
+            let (event_type, severity, label, description) = if malicious {
+                match self.rng.gen_range(0..4) {
+                    0 => (EventType::UnauthorizedAccess, SecuritySeverity::High, "credential_stuffing", "Burst of failed logins followed by success"),
+                    1 => (EventType::MalwareDetected, SecuritySeverity::Critical, "malware", "Endpoint agent quarantined a trojan"),
+                    2 => (EventType::DDoSAttack, SecuritySeverity::High, "ddos", "Request rate 40x above baseline"),
+                    _ => (EventType::DataBreach, SecuritySeverity::Critical, "exfiltration", "Large outbound transfer to unknown host"),
+                }
+            } else {
+                match self.rng.gen_range(0..3) {
+                    0 => (EventType::PolicyViolation, SecuritySeverity::Low, "benign", "Password older than rotation policy"),
+                    1 => (EventType::SuspiciousActivity, SecuritySeverity::Info, "benign", "Login from new device of known user"),
+                    _ => (EventType::UnauthorizedAccess, SecuritySeverity::Low, "benign", "Single mistyped password"),
+                }
+            };
+
+            SecurityEventRecord {
+                id: self.random_id(),
+                timestamp: self.random_time(),
+                event_type,
+                severity,
+                source: ["waf", "edr", "idp", "siem"][self.rng.gen_range(0..4)].to_string(),
+                description: description.to_string(),
+                malicious,
+                label: label.to_string(),
+            }
+        }).collect();
+        records.sort_by_key(|r| r.timestamp);
+        records
+    }
+
+    /// Generate one dataset and write it with its manifest into `dir`
+    pub fn export(&mut self, dataset: Dataset, count: usize, schema: ExportSchema, dir: &Path) -> Result<DatasetManifest, SyntheticError> {
+        match dataset {
+            Dataset::Tickets => { let records = self.tickets(count); write_dataset(dataset, &records, schema, dir, self.seed) }
+            Dataset::Incidents => { let records = self.incidents(count); write_dataset(dataset, &records, schema, dir, self.seed) }
+            Dataset::NetworkFlows => { let records = self.network_flows(count); write_dataset(dataset, &records, schema, dir, self.seed) }
+            Dataset::SecurityEvents => { let records = self.security_events(count); write_dataset(dataset, &records, schema, dir, self.seed) }
+        }
+    }
+}
+
+/// Configured record count for a dataset
+pub fn configured_volume(settings: &SyntheticSettings, dataset: Dataset) -> usize {
+    match dataset {
+        Dataset::Tickets => settings.tickets,
+        Dataset::Incidents => settings.incidents,
+        Dataset::NetworkFlows => settings.network_flows,
+        Dataset::SecurityEvents => settings.security_events,
+    }
+}
+
+fn write_dataset<R: Record>(dataset: Dataset, records: &[R], schema: ExportSchema, dir: &Path, seed: u64) -> Result<DatasetManifest, SyntheticError> {
+    std::fs::create_dir_all(dir)?;
+    let file = dir.join(format!("{}.{}", dataset.as_str(), schema.extension()));
+
+    let mut output = String::new();
+    match schema {
+        ExportSchema::Jsonl => {
+            for record in records {
+                output.push_str(&serde_json::to_string(record).map_err(|e| SyntheticError::Serialization(e.to_string()))?);
+                output.push('\n');
+            }
+        }
+        ExportSchema::Csv => {
+            output.push_str(&R::columns().join(","));
+            output.push('\n');
+            for record in records {
+                let row: Vec<String> = record.values().iter().map(|value| csv_escape(value)).collect();
+                output.push_str(&row.join(","));
+                output.push('\n');
+            }
+        }
+    }
+    std::fs::write(&file, output)?;
+
+    let mut label_counts = BTreeMap::new();
+    for record in records {
+        *label_counts.entry(record.label().to_string()).or_insert(0) += 1;
+    }
+    let manifest = DatasetManifest {
+        dataset,
+        schema,
+        file: file.clone(),
+        records: records.len(),
+        columns: R::columns().iter().map(|c| c.to_string()).collect(),
+        label_counts,
+        seed,
+        generated_at: chrono::Utc::now(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| SyntheticError::Serialization(e.to_string()))?;
+    std::fs::write(dir.join(format!("{}.manifest.json", dataset.as_str())), manifest_json)?;
+
+    Ok(manifest)
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Synthetic generation errors
+#[derive(Debug, thiserror::Error)]
+pub enum SyntheticError {
+    #[error("Dataset I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Dataset serialization error: {0}")]
+    Serialization(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_is_labeled_and_ordered() {
+        let end = chrono::Utc::now();
+        let mut generator = SyntheticGenerator::new(5, &CustomerSettings::default(), 7, end);
+
+        let flows = generator.network_flows(500);
+        assert_eq!(flows.len(), 500);
+        assert!(flows.windows(2).all(|pair| pair[0].ts <= pair[1].ts));
+        assert!(flows.iter().any(|f| f.label == "benign"));
+
+        let incidents = generator.incidents(50);
+        assert!(incidents.iter().all(|i| i.tenancy == Tenancy::Dedicated || i.affected_customers >= 1));
+        assert!(incidents.iter().filter(|i| i.root_cause == "noisy_neighbor").all(|i| i.tenancy == Tenancy::Shared));
+
+        // The same seed and period give the same records, ids included
+        let again = SyntheticGenerator::new(5, &CustomerSettings::default(), 7, end).network_flows(500);
+        assert_eq!(serde_json::to_string(&flows).unwrap(), serde_json::to_string(&again).unwrap());
+        let tickets = |generator: &mut SyntheticGenerator| serde_json::to_string(&generator.tickets(20)).unwrap();
+        assert_eq!(tickets(&mut SyntheticGenerator::new(3, &CustomerSettings::default(), 7, end)),
+                   tickets(&mut SyntheticGenerator::new(3, &CustomerSettings::default(), 7, end)));
+    }
+
+    #[test]
+    fn test_csv_export_writes_manifest() {
+        let dir = std::env::temp_dir().join(format!("synthetic-{}", Uuid::new_v4()));
+        let mut generator = SyntheticGenerator::new(9, &CustomerSettings::default(), 30, chrono::Utc::now());
+
+        let manifest = generator.export(Dataset::Tickets, 25, ExportSchema::Csv, &dir).unwrap();
+        let contents = std::fs::read_to_string(&manifest.file).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(manifest.records, 25);
+        assert_eq!(manifest.label_counts.values().sum::<usize>(), 25);
+        assert_eq!(contents.lines().count(), 26);
+        assert!(contents.starts_with("id,created_at,customer_id"));
+    }
+}
@@ -0,0 +1,102 @@
+//! System Actors - Non-Agent Message Senders
+//!
+//! This module implements the identities the simulation itself uses when it
+//! sends or receives messages:
+//! - Orchestrator: reports and replies addressed to the simulation
+//! - Scheduler: work assignments and recurring jobs
+//! - EventGenerator: customer projects, tickets and security events
+//! - ChaosEngine: injected infrastructure failures
+//! - Operator: work injected through the control API
+//! - CEO: top of the org chart, taking rollups, approvals and escalations
+//!   the department heads pass up
+//! - HR: hires and offboards agents as workload changes
+//!
+//! Each actor has a stable, well-known id so messages can be attributed,
+//! replied to and filtered the same way as agent traffic.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Built-in system identity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SystemActor {
+    Orchestrator,
+    Scheduler,
+    EventGenerator,
+    ChaosEngine,
+    Operator,
+    Ceo,
+    Hr,
+}
+
+/// Common prefix of all system actor ids ("SYSTEM" in ASCII)
+const SYSTEM_ID_PREFIX: u128 = 0x5359_5354_454d_0000_0000_0000_0000_0000;
+
+impl SystemActor {
+    /// Every system actor
+    pub const ALL: [SystemActor; 7] = [
+        SystemActor::Orchestrator,
+        SystemActor::Scheduler,
+        SystemActor::EventGenerator,
+        SystemActor::ChaosEngine,
+        SystemActor::Operator,
+        SystemActor::Ceo,
+        SystemActor::Hr,
+    ];
+
+    /// Stable id used as message sender or recipient
+    pub fn id(&self) -> Uuid {
+        let index = match self {
+            SystemActor::Orchestrator => 1,
+            SystemActor::Scheduler => 2,
+            SystemActor::EventGenerator => 3,
+            SystemActor::ChaosEngine => 4,
+            SystemActor::Operator => 5,
+            SystemActor::Ceo => 6,
+            SystemActor::Hr => 7,
+        };
+        Uuid::from_u128(SYSTEM_ID_PREFIX | index)
+    }
+
+    /// Look up the system actor with the given id
+    pub fn from_id(id: Uuid) -> Option<Self> {
+        Self::ALL.into_iter().find(|actor| actor.id() == id)
+    }
+
+    /// Whether the id belongs to a system actor rather than an agent
+    pub fn is_system(id: Uuid) -> bool {
+        Self::from_id(id).is_some()
+    }
+
+    /// Display name used in logs and reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemActor::Orchestrator => "Orchestrator",
+            SystemActor::Scheduler => "Scheduler",
+            SystemActor::EventGenerator => "Event Generator",
+            SystemActor::ChaosEngine => "Chaos Engine",
+            SystemActor::Operator => "Operator",
+            SystemActor::Ceo => "CEO",
+            SystemActor::Hr => "HR",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_actor_ids_round_trip() {
+        for actor in SystemActor::ALL {
+            assert_eq!(SystemActor::from_id(actor.id()), Some(actor));
+            assert!(!actor.id().is_nil());
+        }
+    }
+
+    #[test]
+    fn test_agent_ids_are_not_system() {
+        assert!(!SystemActor::is_system(Uuid::new_v4()));
+        assert!(!SystemActor::is_system(Uuid::nil()));
+    }
+}
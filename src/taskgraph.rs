@@ -0,0 +1,320 @@
+//! Task Dependencies - Ordering, Critical Path & Completion Estimates
+//!
+//! This module implements the dependency graph of project tasks:
+//! - Tasks that depend on other tasks added before them
+//! - A topological order in which every task follows its dependencies
+//! - The critical path of a project and its estimated completion date
+//! - A gate that refuses to start a task before its dependencies are done
+//!
+//! Only the orchestrator starts tasks, by assigning ready ones to their
+//! department, so the gate also keeps agents off blocked work.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Estimated hours for Engineering to build and merge a project's feature
+pub const BUILD_ESTIMATE_HOURS: i64 = 72;
+
+/// Estimated hours for Ops to roll a merged project out to customers
+pub const ROLLOUT_ESTIMATE_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskState {
+    Pending,
+    InProgress,
+    Done,
+}
+
+/// Piece of project work owned by one department
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTask {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    /// Department the task is assigned to
+    pub department: String,
+    pub estimate_hours: i64,
+    /// Tasks that must be done before this one starts
+    pub dependencies: Vec<Uuid>,
+    pub state: TaskState,
+    /// Agent working on the task
+    #[serde(default)]
+    pub assignee: Option<Uuid>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ProjectTask {
+    pub fn new(project_id: Uuid, title: &str, department: &str, estimate_hours: i64, dependencies: Vec<Uuid>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            project_id,
+            title: title.to_string(),
+            department: department.to_string(),
+            estimate_hours,
+            dependencies,
+            state: TaskState::Pending,
+            assignee: None,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+}
+
+/// Longest chain of dependent tasks in a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPath {
+    /// Tasks in dependency order
+    pub tasks: Vec<Uuid>,
+    /// When the last task on the path is expected to finish
+    pub estimated_completion: chrono::DateTime<chrono::Utc>,
+}
+
+/// Project tasks and the dependencies between them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskGraph {
+    tasks: BTreeMap<Uuid, ProjectTask>,
+}
+
+impl TaskGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn task(&self, id: &Uuid) -> Option<&ProjectTask> {
+        self.tasks.get(id)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &ProjectTask> {
+        self.tasks.values()
+    }
+
+    /// Tasks of one project
+    pub fn project_tasks(&self, project_id: Uuid) -> impl Iterator<Item = &ProjectTask> {
+        self.tasks.values().filter(move |task| task.project_id == project_id)
+    }
+
+    /// Plan the standard work for a new project: Engineering builds it,
+    /// then Ops rolls it out
+    pub fn plan_project(&mut self, project_id: Uuid) -> Result<Vec<Uuid>, TaskGraphError> {
+        let build = ProjectTask::new(project_id, "Build feature", "Engineering", BUILD_ESTIMATE_HOURS, vec![]);
+        let rollout = ProjectTask::new(project_id, "Roll out to customers", "Ops", ROLLOUT_ESTIMATE_HOURS, vec![build.id]);
+        let ids = vec![build.id, rollout.id];
+        self.add(build)?;
+        self.add(rollout)?;
+        Ok(ids)
+    }
+
+    /// Add a task whose dependencies are already in the graph, so that a
+    /// new task can never close a cycle
+    pub fn add(&mut self, task: ProjectTask) -> Result<(), TaskGraphError> {
+        if let Some(missing) = task.dependencies.iter().find(|id| !self.tasks.contains_key(id)) {
+            return Err(TaskGraphError::UnknownTask(*missing));
+        }
+        self.tasks.insert(task.id, task);
+        Ok(())
+    }
+
+    /// Every task after all of its dependencies
+    pub fn topological_order(&self) -> Result<Vec<Uuid>, TaskGraphError> {
+        let mut remaining: HashMap<Uuid, usize> = self.tasks.values()
+            .map(|task| (task.id, task.dependencies.len()))
+            .collect();
+        let mut dependents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for task in self.tasks.values() {
+            for dependency in &task.dependencies {
+                dependents.entry(*dependency).or_default().push(task.id);
+            }
+        }
+
+        // Tasks are visited in id order so the result is stable
+        let mut ready: VecDeque<Uuid> = self.tasks.keys().filter(|id| remaining[id] == 0).copied().collect();
+        let mut order = Vec::with_capacity(self.tasks.len());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for dependent in dependents.get(&id).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).expect("every dependent is a task");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(*dependent);
+                }
+            }
+        }
+
+        if order.len() < self.tasks.len() {
+            let stuck = self.tasks.keys().find(|id| !order.contains(id)).copied().expect("some task is unordered");
+            return Err(TaskGraphError::Cycle(stuck));
+        }
+        Ok(order)
+    }
+
+    /// Pending tasks whose dependencies are all done
+    pub fn ready(&self) -> impl Iterator<Item = &ProjectTask> {
+        self.tasks.values().filter(|task| task.state == TaskState::Pending && self.waiting_on(task).is_empty())
+    }
+
+    /// Pending tasks held up by unfinished dependencies
+    pub fn blocked(&self) -> impl Iterator<Item = &ProjectTask> {
+        self.tasks.values().filter(|task| task.state == TaskState::Pending && !self.waiting_on(task).is_empty())
+    }
+
+    /// In-progress tasks running past their estimate
+    pub fn overdue(&self, now: chrono::DateTime<chrono::Utc>) -> impl Iterator<Item = &ProjectTask> {
+        self.tasks.values().filter(move |task| {
+            task.state == TaskState::InProgress
+                && task.started_at.is_some_and(|started_at| started_at + chrono::Duration::hours(task.estimate_hours) < now)
+        })
+    }
+
+    /// Unfinished tasks that at least `min_dependents` unfinished tasks wait
+    /// on, with how many do
+    pub fn bottlenecks(&self, min_dependents: usize) -> Vec<(&ProjectTask, usize)> {
+        let mut dependents: BTreeMap<Uuid, usize> = BTreeMap::new();
+        for task in self.tasks.values().filter(|task| task.state != TaskState::Done) {
+            for dependency in &task.dependencies {
+                *dependents.entry(*dependency).or_default() += 1;
+            }
+        }
+        dependents.into_iter()
+            .filter(|(_, count)| *count >= min_dependents)
+            .filter_map(|(id, count)| self.tasks.get(&id).filter(|task| task.state != TaskState::Done).map(|task| (task, count)))
+            .collect()
+    }
+
+    /// Start a task; refused while any of its dependencies is unfinished
+    pub fn start(&mut self, id: Uuid, now: chrono::DateTime<chrono::Utc>) -> Result<&ProjectTask, TaskGraphError> {
+        let task = self.tasks.get(&id).ok_or(TaskGraphError::UnknownTask(id))?;
+        let waiting_on = self.waiting_on(task);
+        if !waiting_on.is_empty() {
+            return Err(TaskGraphError::Blocked { task: id, waiting_on });
+        }
+        let task = self.tasks.get_mut(&id).expect("looked up above");
+        if task.state == TaskState::Pending {
+            task.state = TaskState::InProgress;
+            task.started_at = Some(now);
+        }
+        Ok(task)
+    }
+
+    /// Hand a started task to an agent, re-estimated for the agent's skill
+    pub fn assign(&mut self, id: Uuid, agent_id: Uuid, estimate_hours: i64) {
+        if let Some(task) = self.tasks.get_mut(&id) {
+            task.assignee = Some(agent_id);
+            task.estimate_hours = estimate_hours;
+        }
+    }
+
+    /// Finish the in-progress task of a project assigned to a department
+    pub fn complete(&mut self, project_id: Uuid, department: &str, now: chrono::DateTime<chrono::Utc>) -> Option<&ProjectTask> {
+        let task = self.tasks.values_mut()
+            .find(|task| task.project_id == project_id && task.department == department && task.state == TaskState::InProgress)?;
+        task.state = TaskState::Done;
+        task.completed_at = Some(now);
+        Some(task)
+    }
+
+    /// Critical path of a project, estimated from the task estimates
+    ///
+    /// Done tasks count as finished when they were; unstarted tasks start
+    /// once their dependencies finish, but never before `now`.
+    pub fn critical_path(&self, project_id: Uuid, now: chrono::DateTime<chrono::Utc>) -> Result<Option<CriticalPath>, TaskGraphError> {
+        let mut finish: HashMap<Uuid, (chrono::DateTime<chrono::Utc>, Option<Uuid>)> = HashMap::new();
+        for id in self.topological_order()? {
+            let task = &self.tasks[&id];
+            let latest_dependency = task.dependencies.iter()
+                .map(|dependency| (finish[dependency].0, *dependency))
+                .max();
+            let estimate = chrono::Duration::hours(task.estimate_hours);
+            let end = match (task.state, task.completed_at, task.started_at) {
+                (TaskState::Done, Some(completed_at), _) => completed_at,
+                (_, _, Some(started_at)) => (started_at + estimate).max(now),
+                _ => latest_dependency.map_or(now, |(end, _)| end.max(now)) + estimate,
+            };
+            finish.insert(id, (end, latest_dependency.map(|(_, dependency)| dependency)));
+        }
+
+        let Some(last) = self.project_tasks(project_id).max_by_key(|task| (finish[&task.id].0, task.id)) else {
+            return Ok(None);
+        };
+        let estimated_completion = finish[&last.id].0;
+        let mut tasks = vec![last.id];
+        while let Some(previous) = finish[tasks.last().expect("path is never empty")].1 {
+            tasks.push(previous);
+        }
+        tasks.reverse();
+        Ok(Some(CriticalPath { tasks, estimated_completion }))
+    }
+
+    /// Drop the tasks of projects whose every task is done
+    pub fn prune_finished(&mut self) -> usize {
+        let unfinished: Vec<Uuid> = self.tasks.values()
+            .filter(|task| task.state != TaskState::Done)
+            .map(|task| task.project_id)
+            .collect();
+        let before = self.tasks.len();
+        self.tasks.retain(|_, task| unfinished.contains(&task.project_id));
+        before - self.tasks.len()
+    }
+
+    fn waiting_on(&self, task: &ProjectTask) -> Vec<Uuid> {
+        task.dependencies.iter()
+            .filter(|dependency| self.tasks.get(dependency).is_none_or(|dependency| dependency.state != TaskState::Done))
+            .copied()
+            .collect()
+    }
+}
+
+/// Task graph errors
+#[derive(Debug, thiserror::Error)]
+pub enum TaskGraphError {
+    #[error("Unknown task {0}")]
+    UnknownTask(Uuid),
+
+    #[error("Dependency cycle through task {0}")]
+    Cycle(Uuid),
+
+    #[error("Task {task} waits on {} unfinished dependencies", .waiting_on.len())]
+    Blocked { task: Uuid, waiting_on: Vec<Uuid> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_path_and_dependency_gate() {
+        let now: chrono::DateTime<chrono::Utc> = "2024-03-04T09:00:00Z".parse().unwrap();
+        let project_id = Uuid::new_v4();
+        let mut graph = TaskGraph::new();
+
+        // design → (backend, docs) → launch; backend is the long branch
+        let design = ProjectTask::new(project_id, "Design", "Engineering", 8, vec![]);
+        let backend = ProjectTask::new(project_id, "Backend", "Engineering", 40, vec![design.id]);
+        let docs = ProjectTask::new(project_id, "Docs", "Marketing", 4, vec![design.id]);
+        let launch = ProjectTask::new(project_id, "Launch", "Ops", 2, vec![backend.id, docs.id]);
+        let (design_id, backend_id, docs_id, launch_id) = (design.id, backend.id, docs.id, launch.id);
+        for task in [design, backend, docs, launch] {
+            graph.add(task).unwrap();
+        }
+
+        let order = graph.topological_order().unwrap();
+        let position = |id: Uuid| order.iter().position(|task| *task == id).unwrap();
+        assert!(position(design_id) < position(docs_id) && position(docs_id) < position(launch_id));
+
+        let path = graph.critical_path(project_id, now).unwrap().unwrap();
+        assert_eq!(path.tasks, vec![design_id, backend_id, launch_id]);
+        assert_eq!(path.estimated_completion, now + chrono::Duration::hours(50));
+
+        let bottlenecks = graph.bottlenecks(2);
+        assert_eq!((bottlenecks.len(), bottlenecks[0].0.id, bottlenecks[0].1), (1, design_id, 2));
+
+        assert!(matches!(graph.start(launch_id, now), Err(TaskGraphError::Blocked { ref waiting_on, .. }) if waiting_on.len() == 2));
+        graph.start(design_id, now).unwrap();
+        graph.complete(project_id, "Engineering", now + chrono::Duration::hours(2)).unwrap();
+        assert_eq!(graph.ready().count(), 2);
+
+        let orphan = ProjectTask::new(project_id, "Orphan", "Ops", 1, vec![Uuid::new_v4()]);
+        assert!(matches!(graph.add(orphan), Err(TaskGraphError::UnknownTask(_))));
+    }
+}
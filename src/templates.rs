@@ -0,0 +1,361 @@
+//! Report Templates - Swappable Template Packs
+//!
+//! This module implements rendering reports from text templates:
+//! - A small Mustache-style renderer: `{{name}}`, dotted paths, sections
+//!   `{{#list}}…{{/list}}`, inverted sections `{{^flag}}…{{/flag}}` and
+//!   comments `{{! … }}`
+//! - Template packs: a directory with a `pack.toml` manifest and one file per
+//!   report, selected in config so reports can be rebranded or translated
+//! - Built-in English templates (`templates/default`) used for anything a
+//!   pack does not override
+//!
+//! Lines holding nothing but a section tag are dropped from the output, so
+//! templates can put their control tags on lines of their own.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Manifest file every template pack must contain
+pub const PACK_MANIFEST: &str = "pack.toml";
+
+/// Reports that can be rendered from a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportTemplate {
+    /// `report` command output for a saved snapshot
+    SnapshotReport,
+    /// Periodic summary digest written by the daemon
+    SummaryReport,
+    /// End-of-day and end-of-run company report
+    CompanyReport,
+    /// Web page version of the company report, rendered with HTML-escaped values
+    CompanyReportPage,
+}
+
+impl ReportTemplate {
+    pub const ALL: [ReportTemplate; 4] = [
+        ReportTemplate::SnapshotReport,
+        ReportTemplate::SummaryReport,
+        ReportTemplate::CompanyReport,
+        ReportTemplate::CompanyReportPage,
+    ];
+
+    /// File name, without extension, of the template in a pack
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            ReportTemplate::SnapshotReport => "snapshot_report",
+            ReportTemplate::SummaryReport => "summary_report",
+            ReportTemplate::CompanyReport => "company_report",
+            ReportTemplate::CompanyReportPage => "company_report_page",
+        }
+    }
+
+    fn builtin(&self) -> Template {
+        match self {
+            ReportTemplate::SnapshotReport => Template {
+                extension: "txt".to_string(),
+                body: include_str!("../templates/default/snapshot_report.txt").to_string(),
+            },
+            ReportTemplate::SummaryReport => Template {
+                extension: "md".to_string(),
+                body: include_str!("../templates/default/summary_report.md").to_string(),
+            },
+            ReportTemplate::CompanyReport => Template {
+                extension: "md".to_string(),
+                body: include_str!("../templates/default/company_report.md").to_string(),
+            },
+            ReportTemplate::CompanyReportPage => Template {
+                extension: "html".to_string(),
+                body: include_str!("../templates/default/company_report_page.html").to_string(),
+            },
+        }
+    }
+}
+
+/// Contents of `pack.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    /// Language tag of the pack's text, e.g. "en" or "de"
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+#[derive(Debug, Clone)]
+struct Template {
+    /// Extension of the template file, reused for rendered output
+    extension: String,
+    body: String,
+}
+
+/// Set of report templates
+#[derive(Debug, Clone)]
+pub struct TemplatePack {
+    pub manifest: PackManifest,
+    templates: HashMap<ReportTemplate, Template>,
+}
+
+impl Default for TemplatePack {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl TemplatePack {
+    /// The built-in English templates
+    pub fn builtin() -> Self {
+        Self {
+            manifest: PackManifest { name: "default".to_string(), language: default_language() },
+            templates: ReportTemplate::ALL.iter().map(|&report| (report, report.builtin())).collect(),
+        }
+    }
+
+    /// Load a pack directory, falling back to built-in templates it leaves out
+    pub fn load(dir: &Path) -> Result<Self, TemplateError> {
+        let manifest_path = dir.join(PACK_MANIFEST);
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| TemplateError::Io(manifest_path.clone(), e))?;
+        let manifest: PackManifest = toml::from_str(&manifest)
+            .map_err(|e| TemplateError::Manifest(manifest_path, e.to_string()))?;
+
+        let mut pack = Self { manifest, ..Self::builtin() };
+        let entries = std::fs::read_dir(dir).map_err(|e| TemplateError::Io(dir.to_path_buf(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| TemplateError::Io(dir.to_path_buf(), e))?.path();
+            let (Some(stem), Some(extension)) = (path.file_stem().and_then(|s| s.to_str()), path.extension().and_then(|e| e.to_str())) else {
+                continue;
+            };
+            let Some(report) = ReportTemplate::ALL.into_iter().find(|r| r.file_stem() == stem) else {
+                continue;
+            };
+
+            let body = std::fs::read_to_string(&path).map_err(|e| TemplateError::Io(path.clone(), e))?;
+            // Catch unbalanced sections at load time rather than on the first report
+            render(&body, &Value::Null).map_err(|e| TemplateError::InTemplate(path.clone(), Box::new(e)))?;
+            pack.templates.insert(report, Template { extension: extension.to_string(), body });
+        }
+
+        Ok(pack)
+    }
+
+    /// Load the configured pack, or the built-in one when none is configured
+    pub fn from_config(path: Option<&Path>) -> Result<Self, TemplateError> {
+        match path {
+            Some(dir) => Self::load(dir),
+            None => Ok(Self::builtin()),
+        }
+    }
+
+    /// Render a report with the given context
+    pub fn render(&self, report: ReportTemplate, context: &Value) -> Result<String, TemplateError> {
+        render(&self.templates[&report].body, context)
+    }
+
+    /// Extension rendered output of a report should be saved with
+    pub fn extension(&self, report: ReportTemplate) -> &str {
+        &self.templates[&report].extension
+    }
+}
+
+/// Render a template against a JSON context
+pub fn render(template: &str, context: &Value) -> Result<String, TemplateError> {
+    let template = strip_standalone_tags(template);
+    let mut output = String::new();
+    render_into(&template, &mut vec![context], &mut output)?;
+    Ok(output)
+}
+
+/// Copy of a context with every string escaped for HTML output
+pub fn escape_html(context: &Value) -> Value {
+    match context {
+        Value::String(text) => Value::String(text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")),
+        Value::Array(items) => Value::Array(items.iter().map(escape_html).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(key, value)| (key.clone(), escape_html(value))).collect()),
+        other => other.clone(),
+    }
+}
+
+fn render_into<'a>(template: &str, stack: &mut Vec<&'a Value>, output: &mut String) -> Result<(), TemplateError> {
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or(TemplateError::UnclosedTag)?;
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        match tag.chars().next() {
+            Some(kind @ ('#' | '^')) => {
+                let name = tag[1..].trim();
+                let (body, remainder) = split_section(rest, name)?;
+                rest = remainder;
+                let value = lookup(stack, name);
+                if kind == '^' {
+                    if !truthy(value) {
+                        render_into(body, stack, output)?;
+                    }
+                    continue;
+                }
+                match value {
+                    Some(Value::Array(items)) => {
+                        for item in items {
+                            stack.push(item);
+                            render_into(body, stack, output)?;
+                            stack.pop();
+                        }
+                    }
+                    Some(value) if truthy(Some(value)) => {
+                        stack.push(value);
+                        render_into(body, stack, output)?;
+                        stack.pop();
+                    }
+                    _ => {}
+                }
+            }
+            Some('/') => return Err(TemplateError::UnexpectedClose(tag[1..].trim().to_string())),
+            Some('!') => {}
+            _ => output.push_str(&display(lookup(stack, tag))),
+        }
+    }
+    output.push_str(rest);
+    Ok(())
+}
+
+/// Split off a section body up to its matching close tag
+fn split_section<'t>(template: &'t str, name: &str) -> Result<(&'t str, &'t str), TemplateError> {
+    let mut depth = 0;
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("{{") {
+        let tag_start = offset + start;
+        let end = template[tag_start..].find("}}").ok_or(TemplateError::UnclosedTag)? + tag_start;
+        let tag = template[tag_start + 2..end].trim();
+        let tag_name = tag.get(1..).map(str::trim);
+
+        if (tag.starts_with('#') || tag.starts_with('^')) && tag_name == Some(name) {
+            depth += 1;
+        } else if tag.starts_with('/') && tag_name == Some(name) {
+            if depth == 0 {
+                return Ok((&template[..tag_start], &template[end + 2..]));
+            }
+            depth -= 1;
+        }
+        offset = end + 2;
+    }
+    Err(TemplateError::UnclosedSection(name.to_string()))
+}
+
+/// Resolve a dotted name against the context stack, innermost first
+fn lookup<'a>(stack: &[&'a Value], name: &str) -> Option<&'a Value> {
+    if name == "." {
+        return stack.last().copied();
+    }
+    let mut parts = name.split('.');
+    let first = parts.next()?;
+    let mut value = stack.iter().rev().find_map(|scope| scope.get(first))?;
+    for part in parts {
+        value = match value {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => value.get(part)?,
+        };
+    }
+    Some(value)
+}
+
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::String(text)) => !text.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn display(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Strip the indentation and line break around lines that only hold a
+/// section, close or comment tag
+fn strip_standalone_tags(template: &str) -> String {
+    template.split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            let standalone = trimmed.starts_with("{{") && trimmed.ends_with("}}")
+                && trimmed.matches("{{").count() == 1
+                && matches!(trimmed[2..].trim_start().chars().next(), Some('#' | '^' | '/' | '!'));
+            if standalone { trimmed } else { line }
+        })
+        .collect()
+}
+
+/// Template loading and rendering errors
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("Cannot read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Invalid pack manifest {0}: {1}")]
+    Manifest(PathBuf, String),
+
+    #[error("In template {0}: {1}")]
+    InTemplate(PathBuf, Box<TemplateError>),
+
+    #[error("Tag opened with {{{{ is never closed")]
+    UnclosedTag,
+
+    #[error("Section '{0}' is never closed")]
+    UnclosedSection(String),
+
+    #[error("Close tag for '{0}' without a matching section")]
+    UnexpectedClose(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_sections_and_paths() {
+        let template = "Report {{title}}\n{{#departments}}\n- {{name}}: {{agents}} ({{title}})\n{{/departments}}\n{{^departments}}\nNo departments\n{{/departments}}\nRevenue: {{totals.revenue}}\n";
+        let context = json!({
+            "title": "Q3",
+            "departments": [{ "name": "Ops", "agents": 4 }, { "name": "DevOps", "agents": 3 }],
+            "totals": { "revenue": "1200.00" },
+        });
+
+        assert_eq!(render(template, &context).unwrap(),
+                   "Report Q3\n- Ops: 4 (Q3)\n- DevOps: 3 (Q3)\nRevenue: 1200.00\n");
+        assert_eq!(render(template, &json!({ "departments": [] })).unwrap(), "Report \nNo departments\nRevenue: \n");
+        assert!(matches!(render("{{#open}}never closed", &context), Err(TemplateError::UnclosedSection(_))));
+    }
+
+    #[test]
+    fn test_pack_overrides_and_falls_back() {
+        let dir = std::env::temp_dir().join(format!("template-pack-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(PACK_MANIFEST), "name = \"acme\"\nlanguage = \"de\"\n").unwrap();
+        std::fs::write(dir.join("summary_report.html"), "<h1>Zusammenfassung {{total_steps}}</h1>").unwrap();
+
+        let pack = TemplatePack::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(pack.manifest.language, "de");
+        assert_eq!(pack.extension(ReportTemplate::SummaryReport), "html");
+        assert_eq!(pack.render(ReportTemplate::SummaryReport, &json!({ "total_steps": 7 })).unwrap(), "<h1>Zusammenfassung 7</h1>");
+        assert_eq!(pack.extension(ReportTemplate::SnapshotReport), "txt");
+    }
+}
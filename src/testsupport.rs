@@ -0,0 +1,23 @@
+//! Test Support - Shared Test Fixtures
+//!
+//! This module implements fixtures the unit tests of several modules share:
+//! - A plain message between two agents, for tests to adjust the fields
+//!   they care about
+
+use crate::communication::{Message, MessagePriority};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Normal-priority message of `message_type` sent now, with no content or metadata
+pub(crate) fn test_message(from: Uuid, to: Uuid, message_type: &str) -> Message {
+    Message {
+        id: Uuid::new_v4(),
+        from_agent: from,
+        to_agent: to,
+        message_type: message_type.to_string(),
+        content: String::new(),
+        priority: MessagePriority::Normal,
+        timestamp: chrono::Utc::now(),
+        metadata: HashMap::new(),
+    }
+}
@@ -0,0 +1,222 @@
+//! Conversation Threads - Message History by Thread
+//!
+//! This module implements grouping related messages into conversation
+//! threads, so the whole exchange about one piece of work reads as one:
+//! - Every dispatched message that names an incident or carries a causality
+//!   id is stamped with a `thread_id`; messages that already carry one keep it
+//! - An incident is linked to the causality ids of the events it came from,
+//!   so alerts, the declaration, pages and follow-ups share the incident's
+//!   thread
+//! - A thread's history, its participants and the most recently active
+//!   threads are read back from the dispatched messages
+//!
+//! Threads are rendered on the console (`threads`, `thread <id>`), on the
+//! control API and by `report --thread`.
+
+use crate::communication::Message;
+use crate::timeline::{self, INCIDENT_KEY, THREAD_KEY};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// Longest subject kept for a thread listing
+const SUBJECT_CHARS: usize = 60;
+
+/// Read the thread id carried by a message, if any
+pub fn thread_id(message: &Message) -> Option<Uuid> {
+    message.metadata.get(THREAD_KEY).and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Incident and causality ids mapped to the thread they belong to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversationThreads {
+    links: BTreeMap<Uuid, Uuid>,
+}
+
+impl ConversationThreads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `id` and every one of `related` in the same thread: the thread
+    /// one of them is already in, otherwise a thread named after `id`
+    pub fn link(&mut self, id: Uuid, related: &[Uuid]) -> Uuid {
+        let thread = std::iter::once(&id).chain(related)
+            .find_map(|key| self.links.get(key).copied())
+            .unwrap_or(id);
+        for key in std::iter::once(&id).chain(related) {
+            self.links.insert(*key, thread);
+        }
+        thread
+    }
+
+    /// Stamp a message with the thread of the incident or chain of events it
+    /// belongs to; messages that already carry a thread id join it
+    pub fn assign(&mut self, message: &mut Message) -> Option<Uuid> {
+        let incident = message.metadata.get(INCIDENT_KEY).and_then(|id| Uuid::parse_str(id).ok());
+        let keys: Vec<Uuid> = incident.into_iter().chain(timeline::correlation_id(message)).collect();
+        let thread = match (thread_id(message), keys.split_first()) {
+            (Some(thread), _) => {
+                for key in &keys {
+                    self.links.entry(*key).or_insert(thread);
+                }
+                thread
+            }
+            (None, Some((first, rest))) => self.link(*first, rest),
+            (None, None) => return None,
+        };
+        message.metadata.insert(THREAD_KEY.to_string(), thread.to_string());
+        Some(thread)
+    }
+
+    /// Forget links to threads none of `messages` belong to any more
+    pub fn retain<'a>(&mut self, messages: impl IntoIterator<Item = &'a Message>) {
+        let live: BTreeSet<Uuid> = messages.into_iter().filter_map(thread_id).collect();
+        self.links.retain(|_, thread| live.contains(thread));
+    }
+}
+
+/// Overview of a thread, for listings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThreadSummary {
+    pub id: Uuid,
+    /// Type and content of the message that started the thread
+    pub subject: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub last_at: chrono::DateTime<chrono::Utc>,
+    pub messages: usize,
+    /// Senders and recipients, agents and system actors alike
+    pub participants: BTreeSet<Uuid>,
+}
+
+impl ThreadSummary {
+    fn start(id: Uuid, message: &Message) -> Self {
+        let subject = format!("{}: {}", message.message_type, message.content);
+        Self {
+            id,
+            subject: match subject.char_indices().nth(SUBJECT_CHARS) {
+                Some((end, _)) => format!("{}…", &subject[..end]),
+                None => subject,
+            },
+            started_at: message.timestamp,
+            last_at: message.timestamp,
+            messages: 0,
+            participants: BTreeSet::new(),
+        }
+    }
+
+    fn add(&mut self, message: &Message) {
+        self.last_at = self.last_at.max(message.timestamp);
+        self.messages += 1;
+        self.participants.extend([message.from_agent, message.to_agent]);
+    }
+}
+
+/// Every message of a thread, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct Thread {
+    #[serde(flatten)]
+    pub summary: ThreadSummary,
+    pub messages: Vec<Message>,
+}
+
+impl Thread {
+    /// Render the thread as plain text, naming actors with `name`
+    pub fn render(&self, name: impl Fn(Uuid) -> String) -> String {
+        let summary = &self.summary;
+        let mut output = format!("Thread {} '{}': {} message(s) between {} participant(s)\n",
+                                 summary.id.simple(), summary.subject, summary.messages, summary.participants.len());
+        for message in &self.messages {
+            output.push_str(&format!("  {} {} → {} [{}] {}\n", message.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                                     name(message.from_agent), name(message.to_agent), message.message_type, message.content));
+        }
+        output
+    }
+}
+
+/// History of a thread from dispatched messages; `None` if none belong to it
+pub fn history<'a>(messages: impl IntoIterator<Item = &'a Message>, id: Uuid) -> Option<Thread> {
+    let mut messages: Vec<Message> = messages.into_iter()
+        .filter(|message| thread_id(message) == Some(id))
+        .cloned()
+        .collect();
+    // Stable sort keeps dispatch order for messages with the same timestamp
+    messages.sort_by_key(|message| message.timestamp);
+    let mut summary = ThreadSummary::start(id, messages.first()?);
+    messages.iter().for_each(|message| summary.add(message));
+    Some(Thread { summary, messages })
+}
+
+/// The `limit` threads with the most recent activity, latest first
+pub fn recent<'a>(messages: impl IntoIterator<Item = &'a Message>, limit: usize) -> Vec<ThreadSummary> {
+    let mut threads: BTreeMap<Uuid, ThreadSummary> = BTreeMap::new();
+    for message in messages {
+        if let Some(id) = thread_id(message) {
+            let thread = threads.entry(id).or_insert_with(|| ThreadSummary::start(id, message));
+            if message.timestamp < thread.started_at {
+                let replaced = ThreadSummary::start(id, message);
+                thread.subject = replaced.subject;
+                thread.started_at = replaced.started_at;
+            }
+            thread.add(message);
+        }
+    }
+    let mut threads: Vec<ThreadSummary> = threads.into_values().collect();
+    threads.sort_by(|a, b| b.last_at.cmp(&a.last_at));
+    threads.truncate(limit);
+    threads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::MessagePriority;
+    use crate::testsupport::test_message;
+    use crate::timeline::CORRELATION_KEY;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn message(message_type: &str, minute: i64, metadata: &[(&str, Uuid)]) -> Message {
+        Message {
+            content: "Checkout is down".to_string(),
+            priority: MessagePriority::High,
+            timestamp: chrono::Utc.with_ymd_and_hms(2024, 5, 6, 12, 0, 0).unwrap() + chrono::Duration::minutes(minute),
+            metadata: metadata.iter().map(|(key, id)| (key.to_string(), id.to_string())).collect::<HashMap<_, _>>(),
+            ..test_message(Uuid::new_v4(), Uuid::new_v4(), message_type)
+        }
+    }
+
+    #[test]
+    fn test_an_incidents_exchange_shares_one_thread() {
+        let mut threads = ConversationThreads::new();
+        let (correlation_id, incident_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut alert = message("infrastructure_alert", 0, &[(CORRELATION_KEY, correlation_id)]);
+        let mut declared = message("incident_declared", 1, &[(CORRELATION_KEY, correlation_id)]);
+        let mut chatter = message("status_update", 2, &[]);
+        assert_eq!(threads.assign(&mut alert), Some(correlation_id));
+        assert_eq!(threads.assign(&mut declared), Some(correlation_id));
+        assert_eq!(threads.assign(&mut chatter), None);
+
+        // Once the incident is known its pages join the thread of its events
+        assert_eq!(threads.link(incident_id, &[correlation_id]), correlation_id);
+        let mut page = message("page", 3, &[(INCIDENT_KEY, incident_id)]);
+        assert_eq!(threads.assign(&mut page), Some(correlation_id));
+
+        let log = vec![page, chatter, declared, alert];
+        let thread = history(&log, correlation_id).unwrap();
+        let types: Vec<&str> = thread.messages.iter().map(|message| message.message_type.as_str()).collect();
+        assert_eq!(types, vec!["infrastructure_alert", "incident_declared", "page"]);
+        assert_eq!((thread.summary.messages, thread.summary.participants.len()), (3, 6));
+        assert_eq!(thread.summary.subject, "infrastructure_alert: Checkout is down");
+        assert!(thread.render(|id| id.simple().to_string()).contains("[page] Checkout is down"));
+        assert!(history(&log, incident_id).is_none());
+
+        let listed = recent(&log, 5);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0], thread.summary);
+
+        threads.retain(&log[1..2]);
+        assert_eq!(threads.link(incident_id, &[]), incident_id);
+    }
+}
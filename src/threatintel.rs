@@ -0,0 +1,394 @@
+//! Threat Intelligence - Indicator Feeds, Sightings and Advisories
+//!
+//! This module implements what InfoSec learns from outside the company:
+//! - Indicator feeds read from files: STIX 2.1 bundles, as exported from a
+//!   TAXII collection, or simple JSON lists of indicators. Each indicator is
+//!   an IP address, domain, URL or file hash tied to a threat, with the
+//!   attack stages it is used in and an optional expiry
+//! - Simulated network traffic and logs: firewall, DNS, proxy and endpoint
+//!   observations on the company's hosts, a small share of them threat
+//!   activity matching a known indicator, and the addresses in Networking's
+//!   flow logs
+//! - Sightings of active indicators in the observations, and a targeted
+//!   advisory per threat sighted, raising the chance of detecting the attack
+//!   stages the threat is known for while the advisory lasts
+//!
+//! The orchestrator reloads the feeds and hunts through the day's traffic
+//! every evening, hands new advisories to InfoSec and lets active ones
+//! strengthen the defenses red team campaigns meet.
+
+use crate::config::ThreatIntelSettings;
+use crate::redteam::AttackStage;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Sightings and advisories kept; the oldest go first
+const MAX_HISTORY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndicatorKind {
+    IpAddress,
+    Domain,
+    Url,
+    FileHash,
+}
+
+impl IndicatorKind {
+    /// STIX cyber-observable type of a pattern's comparison
+    fn from_stix(object_type: &str) -> Option<Self> {
+        match object_type {
+            "ipv4-addr" | "ipv6-addr" => Some(IndicatorKind::IpAddress),
+            "domain-name" => Some(IndicatorKind::Domain),
+            "url" => Some(IndicatorKind::Url),
+            "file" => Some(IndicatorKind::FileHash),
+            _ => None,
+        }
+    }
+
+    /// Log the kind of value shows up in
+    fn source(&self) -> &'static str {
+        match self {
+            IndicatorKind::IpAddress => "firewall log",
+            IndicatorKind::Domain => "DNS log",
+            IndicatorKind::Url => "proxy log",
+            IndicatorKind::FileHash => "endpoint log",
+        }
+    }
+
+    /// A value of the kind that matches no indicator
+    fn benign(&self, rng: &mut impl Rng) -> String {
+        match self {
+            IndicatorKind::IpAddress => format!("10.{}.{}.{}", rng.gen_range(0..=255), rng.gen_range(0..=255), rng.gen_range(1..255)),
+            IndicatorKind::Domain => format!("cdn{}.example.net", rng.gen_range(1..1000)),
+            IndicatorKind::Url => format!("https://updates.example.com/{}", rng.gen_range(1..10_000)),
+            IndicatorKind::FileHash => format!("{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>()),
+        }
+    }
+}
+
+/// Attack stage a STIX kill chain phase belongs to
+fn stage_of_phase(phase: &str) -> Option<AttackStage> {
+    match phase {
+        "reconnaissance" | "delivery" | "initial-access" | "phishing" => Some(AttackStage::Phishing),
+        "lateral-movement" | "privilege-escalation" | "credential-access" => Some(AttackStage::LateralMovement),
+        "exfiltration" | "collection" | "command-and-control" => Some(AttackStage::Exfiltration),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Indicator {
+    pub kind: IndicatorKind,
+    pub value: String,
+    /// Threat actor or campaign the indicator belongs to
+    pub threat: String,
+    /// Attack stages the threat uses it in
+    #[serde(default)]
+    pub stages: Vec<AttackStage>,
+    /// 0 to 100
+    #[serde(default = "default_confidence")]
+    pub confidence: u8,
+    #[serde(default)]
+    pub valid_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Feed it came from
+    #[serde(default)]
+    pub source: String,
+}
+
+fn default_confidence() -> u8 {
+    50
+}
+
+impl Indicator {
+    pub fn is_active(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.valid_until.iter().all(|valid_until| now < *valid_until)
+    }
+}
+
+/// A value seen in the company's traffic or logs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub kind: IndicatorKind,
+    pub value: String,
+    pub host: String,
+    pub source: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An observation that matched an active indicator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sighting {
+    pub indicator: String,
+    pub threat: String,
+    pub observation: Observation,
+}
+
+/// Warning that a threat is active against the company
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreatAdvisory {
+    pub id: Uuid,
+    pub threat: String,
+    /// Attack stages to watch for
+    pub stages: Vec<AttackStage>,
+    /// Indicators sighted, and the hosts they were seen on
+    pub indicators: Vec<String>,
+    pub hosts: Vec<String>,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Share of what would have been missed that watching for it catches
+    pub detection_boost: f64,
+}
+
+impl ThreatAdvisory {
+    pub fn is_active(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// Indicators from every feed, where they were sighted and the advisories
+/// raised
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreatIntel {
+    /// By value
+    indicators: BTreeMap<String, Indicator>,
+    sightings: Vec<Sighting>,
+    advisories: Vec<ThreatAdvisory>,
+}
+
+impl ThreatIntel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn indicators(&self) -> impl Iterator<Item = &Indicator> {
+        self.indicators.values()
+    }
+
+    pub fn sightings(&self) -> &[Sighting] {
+        &self.sightings
+    }
+
+    pub fn advisories(&self) -> &[ThreatAdvisory] {
+        &self.advisories
+    }
+
+    pub fn active_advisories(&self, now: chrono::DateTime<chrono::Utc>) -> impl Iterator<Item = &ThreatAdvisory> {
+        self.advisories.iter().filter(move |advisory| advisory.is_active(now))
+    }
+
+    /// Read a feed file; returns the indicators it added or updated
+    pub fn ingest_file(&mut self, path: &Path) -> Result<usize, ThreatIntelError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ThreatIntelError::Io(path.to_path_buf(), e))?;
+        self.ingest(&path.display().to_string(), &text)
+    }
+
+    /// Read a STIX 2.1 bundle, a JSON list of indicators or an object with
+    /// an `indicators` list
+    pub fn ingest(&mut self, source: &str, text: &str) -> Result<usize, ThreatIntelError> {
+        let feed: serde_json::Value = serde_json::from_str(text).map_err(|e| ThreatIntelError::Feed(source.to_string(), e.to_string()))?;
+        let indicators = if feed.get("type").and_then(|kind| kind.as_str()) == Some("bundle") {
+            parse_stix(&feed)
+        } else {
+            let list = feed.get("indicators").cloned().unwrap_or(feed);
+            serde_json::from_value::<Vec<Indicator>>(list).map_err(|e| ThreatIntelError::Feed(source.to_string(), e.to_string()))?
+        };
+        let mut changed = 0;
+        for mut indicator in indicators {
+            indicator.source = source.to_string();
+            if self.indicators.get(&indicator.value) != Some(&indicator) {
+                self.indicators.insert(indicator.value.clone(), indicator);
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// A day of simulated traffic and logs on `hosts`: `observations_per_day`
+    /// values, each threat activity matching an active indicator with
+    /// `malicious_probability`
+    pub fn observe(&self, hosts: &[String], settings: &ThreatIntelSettings, now: chrono::DateTime<chrono::Utc>, rng: &mut impl Rng) -> Vec<Observation> {
+        const KINDS: [IndicatorKind; 4] = [IndicatorKind::IpAddress, IndicatorKind::Domain, IndicatorKind::Url, IndicatorKind::FileHash];
+        let active: Vec<&Indicator> = self.indicators.values().filter(|indicator| indicator.is_active(now)).collect();
+        (0..settings.observations_per_day)
+            .map(|_| {
+                let (kind, value) = if !active.is_empty() && rng.gen_bool(settings.malicious_probability.clamp(0.0, 1.0)) {
+                    let indicator = active[rng.gen_range(0..active.len())];
+                    (indicator.kind, indicator.value.clone())
+                } else {
+                    let kind = KINDS[rng.gen_range(0..KINDS.len())];
+                    (kind, kind.benign(rng))
+                };
+                let host = if hosts.is_empty() { "gateway".to_string() } else { hosts[rng.gen_range(0..hosts.len())].clone() };
+                Observation { kind, value, host, source: kind.source().to_string(), at: now - chrono::Duration::minutes(rng.gen_range(0..24 * 60)) }
+            })
+            .collect()
+    }
+
+    /// Match observations against the active indicators and raise an
+    /// advisory for every threat sighted that has none active; returns the
+    /// new advisories
+    pub fn hunt(&mut self, observations: Vec<Observation>, settings: &ThreatIntelSettings, now: chrono::DateTime<chrono::Utc>) -> Vec<ThreatAdvisory> {
+        let mut by_threat: BTreeMap<String, Vec<Sighting>> = BTreeMap::new();
+        for observation in observations {
+            let Some(indicator) = self.indicators.get(&observation.value).filter(|indicator| indicator.kind == observation.kind && indicator.is_active(now)) else {
+                continue;
+            };
+            by_threat.entry(indicator.threat.clone()).or_default().push(Sighting {
+                indicator: indicator.value.clone(),
+                threat: indicator.threat.clone(),
+                observation,
+            });
+        }
+
+        let mut raised = Vec::new();
+        for (threat, sightings) in by_threat {
+            let advised = self.active_advisories(now).any(|advisory| advisory.threat == threat);
+            if !advised {
+                let indicators: BTreeSet<String> = sightings.iter().map(|sighting| sighting.indicator.clone()).collect();
+                let hosts: BTreeSet<String> = sightings.iter().map(|sighting| sighting.observation.host.clone()).collect();
+                let stages: BTreeSet<AttackStage> = indicators.iter()
+                    .filter_map(|value| self.indicators.get(value))
+                    .flat_map(|indicator| indicator.stages.iter().copied())
+                    .collect();
+                let advisory = ThreatAdvisory {
+                    id: Uuid::new_v4(),
+                    threat,
+                    // An indicator without stages could be used anywhere in the chain
+                    stages: if stages.is_empty() { AttackStage::CHAIN.to_vec() } else { stages.into_iter().collect() },
+                    indicators: indicators.into_iter().collect(),
+                    hosts: hosts.into_iter().collect(),
+                    issued_at: now,
+                    expires_at: now + chrono::Duration::days(i64::from(settings.advisory_days)),
+                    detection_boost: settings.detection_boost,
+                };
+                raised.push(advisory.clone());
+                self.advisories.push(advisory);
+            }
+            self.sightings.extend(sightings);
+        }
+        trim(&mut self.sightings);
+        trim(&mut self.advisories);
+        raised
+    }
+}
+
+fn trim<T>(records: &mut Vec<T>) {
+    if records.len() > MAX_HISTORY {
+        let excess = records.len() - MAX_HISTORY;
+        records.drain(..excess);
+    }
+}
+
+/// Indicators in a STIX 2.1 bundle; every comparison in an indicator's
+/// pattern on a supported observable becomes one
+fn parse_stix(bundle: &serde_json::Value) -> Vec<Indicator> {
+    let objects = bundle.get("objects").and_then(|objects| objects.as_array()).cloned().unwrap_or_default();
+    let mut indicators = Vec::new();
+    for object in objects.iter().filter(|object| object.get("type").and_then(|kind| kind.as_str()) == Some("indicator")) {
+        let Some(pattern) = object.get("pattern").and_then(|pattern| pattern.as_str()) else {
+            continue;
+        };
+        let threat = object.get("name").and_then(|name| name.as_str())
+            .or_else(|| object.get("id").and_then(|id| id.as_str()))
+            .unwrap_or("unknown")
+            .to_string();
+        let stages: BTreeSet<AttackStage> = object.get("kill_chain_phases").and_then(|phases| phases.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|phase| phase.get("phase_name").and_then(|name| name.as_str()))
+            .filter_map(stage_of_phase)
+            .collect();
+        let confidence = object.get("confidence").and_then(|confidence| confidence.as_u64()).map_or(default_confidence(), |confidence| confidence.min(100) as u8);
+        let valid_until = object.get("valid_until").and_then(|until| until.as_str())
+            .and_then(|until| chrono::DateTime::parse_from_rfc3339(until).ok())
+            .map(|until| until.with_timezone(&chrono::Utc));
+        for (kind, value) in parse_pattern(pattern) {
+            indicators.push(Indicator {
+                kind,
+                value,
+                threat: threat.clone(),
+                stages: stages.iter().copied().collect(),
+                confidence,
+                valid_until,
+                source: String::new(),
+            });
+        }
+    }
+    indicators
+}
+
+/// `(kind, value)` of every equality comparison in a STIX pattern such as
+/// `[ipv4-addr:value = '198.51.100.1' OR domain-name:value = 'evil.example']`
+fn parse_pattern(pattern: &str) -> Vec<(IndicatorKind, String)> {
+    pattern.replace(['[', ']'], " OR ")
+        .replace(" AND ", " OR ")
+        .split(" OR ")
+        .filter_map(|comparison| {
+            let (path, value) = comparison.split_once('=')?;
+            let kind = IndicatorKind::from_stix(path.trim().split(':').next()?)?;
+            let value = value.trim().trim_matches('\'').to_string();
+            (!value.is_empty()).then_some((kind, value))
+        })
+        .collect()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThreatIntelError {
+    #[error("Cannot read threat feed {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Invalid threat feed {0}: {1}")]
+    Feed(String, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const BUNDLE: &str = r#"{
+        "type": "bundle",
+        "id": "bundle--1",
+        "objects": [
+            {
+                "type": "indicator",
+                "id": "indicator--1",
+                "name": "FIN-Example",
+                "pattern": "[ipv4-addr:value = '198.51.100.7' OR domain-name:value = 'exfil.example']",
+                "pattern_type": "stix",
+                "confidence": 85,
+                "kill_chain_phases": [{"kill_chain_name": "mitre-attack", "phase_name": "exfiltration"}]
+            },
+            {"type": "malware", "id": "malware--1", "name": "ignored"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_stix_and_simple_feeds_raise_advisories_on_sightings() {
+        let now = chrono::Utc::now();
+        let mut intel = ThreatIntel::new();
+        assert_eq!(intel.ingest("stix", BUNDLE).unwrap(), 2);
+        assert_eq!(intel.ingest("stix", BUNDLE).unwrap(), 0);
+        let simple = r#"[{"kind": "file_hash", "value": "abc123", "threat": "Loader", "valid_until": "2000-01-01T00:00:00Z"}]"#;
+        assert_eq!(intel.ingest("simple", simple).unwrap(), 1);
+        assert!(matches!(intel.ingest("broken", "{"), Err(ThreatIntelError::Feed(..))));
+
+        // Every observation is threat activity; the expired hash never is
+        let settings = ThreatIntelSettings { observations_per_day: 20, malicious_probability: 1.0, ..ThreatIntelSettings::default() };
+        let observations = intel.observe(&["web-1".to_string()], &settings, now, &mut rand::rngs::StdRng::seed_from_u64(2));
+        assert!(observations.iter().all(|observation| observation.value != "abc123"));
+        let advisories = intel.hunt(observations.clone(), &settings, now);
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].threat, "FIN-Example");
+        assert_eq!(advisories[0].stages, vec![AttackStage::Exfiltration]);
+        assert_eq!(intel.sightings().len(), 20);
+
+        // The threat is already advised until the advisory expires
+        assert!(intel.hunt(observations, &settings, now).is_empty());
+        assert_eq!(intel.active_advisories(now + chrono::Duration::days(i64::from(settings.advisory_days))).count(), 0);
+    }
+}
@@ -0,0 +1,218 @@
+//! Ticket Queue - Shared Ops Queue and Ticket Routing
+//!
+//! This module implements the queue new support tickets wait in until an Ops
+//! agent takes them:
+//! - Tickets leave the queue most urgent first: by priority, then customer
+//!   tier, then how long they have waited
+//! - Routing rules send tickets with a tag, or of a priority and above, to
+//!   agents with enough support skill; when none of those has room, any
+//!   agent with room takes the ticket
+//! - Load balancing: the most skilled agent wins, discounted by the tickets
+//!   each already holds, and nobody takes more than the open-ticket limit.
+//!   Tickets nobody has room for stay queued
+//! - Reassignment: an agent over the limit, after escalations or hand-overs,
+//!   gives its newest unanswered tickets to agents with room
+//! - Queue depth, the oldest ticket's wait and the mean wait of recently
+//!   routed tickets
+//!
+//! The orchestrator queues every new ticket, routes the queue every step and
+//! samples its metrics for Prometheus and the KPIs.
+
+use crate::assignment::{self, Candidate};
+use crate::config::{TicketQueueSettings, TicketRoute};
+use crate::departments::ops::{CustomerTier, Priority, TicketRequest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// Waits of routed tickets kept for the mean
+const RECENT_WAITS: usize = 100;
+
+fn tier_rank(tier: CustomerTier) -> u8 {
+    match tier {
+        CustomerTier::Standard => 0,
+        CustomerTier::Premium => 1,
+        CustomerTier::Enterprise => 2,
+    }
+}
+
+/// Support skill the routing rules want for a ticket; 0 when none match
+pub fn required_skill(tags: &[String], priority: &Priority, routes: &[TicketRoute]) -> u8 {
+    routes.iter()
+        .filter(|route| {
+            route.tag.as_ref().is_some_and(|tag| tags.contains(tag))
+                || route.min_priority.as_ref().is_some_and(|min| priority.rank() >= min.rank())
+        })
+        .map(|route| route.min_skill)
+        .max()
+        .unwrap_or(0)
+}
+
+/// An Ops agent as the router sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueAgent {
+    pub agent_id: Uuid,
+    pub support_skill: u8,
+    /// Tickets neither resolved nor closed
+    pub open_tickets: usize,
+}
+
+/// Agent a ticket should go to: the best of those with room and the skill,
+/// or of all those with room when none has the skill
+pub fn pick(agents: &[QueueAgent], skill: u8, max_open: usize, exclude: Option<Uuid>) -> Option<Uuid> {
+    let with_room: Vec<&QueueAgent> = agents.iter()
+        .filter(|agent| agent.open_tickets < max_open && Some(agent.agent_id) != exclude)
+        .collect();
+    let skilled: Vec<&QueueAgent> = with_room.iter().copied().filter(|agent| agent.support_skill >= skill).collect();
+    let pool = if skilled.is_empty() { with_room } else { skilled };
+    let candidates: Vec<Candidate> = pool.iter()
+        .map(|agent| Candidate { agent_id: agent.agent_id, skill: agent.support_skill, load: agent.open_tickets })
+        .collect();
+    assignment::best_candidate(&candidates).map(|candidate| candidate.agent_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTicket {
+    pub id: Uuid,
+    pub request: TicketRequest,
+    /// Who raised it
+    pub from: Uuid,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// A ticket taken off the queue
+#[derive(Debug, Clone)]
+pub struct Routed {
+    pub ticket: QueuedTicket,
+    pub agent_id: Uuid,
+    pub waited_minutes: i64,
+}
+
+/// Tickets waiting for an Ops agent and how long recent ones waited
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketQueue {
+    waiting: Vec<QueuedTicket>,
+    /// Minutes recently routed tickets waited, oldest first
+    waits: VecDeque<i64>,
+    /// Tickets routed so far
+    pub routed: u64,
+    /// Tickets moved off overloaded agents so far
+    pub reassigned: u64,
+}
+
+impl TicketQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a new ticket; returns its queue id
+    pub fn enqueue(&mut self, request: TicketRequest, from: Uuid, now: DateTime<Utc>) -> Uuid {
+        let id = Uuid::new_v4();
+        self.waiting.push(QueuedTicket { id, request, from, queued_at: now });
+        id
+    }
+
+    pub fn waiting(&self) -> &[QueuedTicket] {
+        &self.waiting
+    }
+
+    pub fn depth(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Minutes the longest-waiting ticket has waited; 0 when the queue is empty
+    pub fn oldest_wait_minutes(&self, now: DateTime<Utc>) -> i64 {
+        self.waiting.iter().map(|ticket| (now - ticket.queued_at).num_minutes()).max().unwrap_or(0)
+    }
+
+    /// Mean minutes the recently routed tickets waited
+    pub fn mean_wait_minutes(&self) -> Option<f64> {
+        if self.waits.is_empty() {
+            return None;
+        }
+        Some(self.waits.iter().sum::<i64>() as f64 / self.waits.len() as f64)
+    }
+
+    /// Route waiting tickets, most urgent first, to agents with room; their
+    /// open tickets count up as they take them
+    pub fn route(&mut self, agents: &mut [QueueAgent], settings: &TicketQueueSettings, now: DateTime<Utc>) -> Vec<Routed> {
+        self.waiting.sort_by(|a, b| {
+            b.request.priority.rank().cmp(&a.request.priority.rank())
+                .then(tier_rank(b.request.customer_tier).cmp(&tier_rank(a.request.customer_tier)))
+                .then(a.queued_at.cmp(&b.queued_at))
+        });
+        let mut routed = Vec::new();
+        let mut waiting = Vec::new();
+        for ticket in std::mem::take(&mut self.waiting) {
+            let skill = required_skill(&ticket.request.tags, &ticket.request.priority, &settings.routes);
+            let Some(agent_id) = pick(agents, skill, settings.max_open_per_agent, None) else {
+                waiting.push(ticket);
+                continue;
+            };
+            if let Some(agent) = agents.iter_mut().find(|agent| agent.agent_id == agent_id) {
+                agent.open_tickets += 1;
+            }
+            let waited_minutes = (now - ticket.queued_at).num_minutes();
+            self.waits.push_back(waited_minutes);
+            if self.waits.len() > RECENT_WAITS {
+                self.waits.pop_front();
+            }
+            self.routed += 1;
+            routed.push(Routed { ticket, agent_id, waited_minutes });
+        }
+        self.waiting = waiting;
+        routed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(title: &str, priority: Priority, tags: &[&str]) -> TicketRequest {
+        TicketRequest {
+            title: title.to_string(),
+            description: String::new(),
+            priority,
+            customer_id: None,
+            customer_tier: CustomerTier::Standard,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_urgent_tickets_go_first_to_skilled_agents_with_room() {
+        let settings = TicketQueueSettings {
+            enabled: true,
+            max_open_per_agent: 3,
+            routes: vec![TicketRoute { tag: Some("billing".to_string()), min_priority: None, min_skill: 92 }],
+        };
+        let (senior, junior) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut agents = vec![
+            QueueAgent { agent_id: senior, support_skill: 95, open_tickets: 2 },
+            QueueAgent { agent_id: junior, support_skill: 90, open_tickets: 0 },
+        ];
+        let start = Utc::now();
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+        let mut queue = TicketQueue::new();
+        queue.enqueue(request("Slow page", Priority::Normal, &[]), Uuid::nil(), at(0));
+        queue.enqueue(request("Invoice wrong", Priority::High, &["billing"]), Uuid::nil(), at(0));
+        queue.enqueue(request("Site down", Priority::Urgent, &[]), Uuid::nil(), at(10));
+        queue.enqueue(request("Typo", Priority::Low, &[]), Uuid::nil(), at(0));
+        queue.enqueue(request("Dark mode", Priority::Low, &[]), Uuid::nil(), at(20));
+
+        let routed = queue.route(&mut agents, &settings, at(30));
+        let titles: Vec<(&str, Uuid)> = routed.iter().map(|routed| (routed.ticket.request.title.as_str(), routed.agent_id)).collect();
+        // The junior is less loaded, but billing needs the senior
+        assert_eq!(titles, vec![("Site down", junior), ("Invoice wrong", senior), ("Slow page", junior), ("Typo", junior)]);
+        assert_eq!(queue.waiting()[0].request.title, "Dark mode");
+        assert_eq!(queue.oldest_wait_minutes(at(30)), 10);
+        assert_eq!(queue.mean_wait_minutes(), Some(27.5));
+
+        // Without a skilled agent with room, anyone with room takes it
+        agents[1].open_tickets = 2;
+        assert_eq!(pick(&agents, 92, 3, Some(senior)), Some(junior));
+        assert_eq!(pick(&agents, 92, 3, Some(junior)), None);
+    }
+}
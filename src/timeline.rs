@@ -0,0 +1,144 @@
+//! Incident Timelines - Postmortem Reconstruction
+//!
+//! This module implements the ordered, cross-department view of an incident:
+//! - Status changes recorded by Ops
+//! - Alerts and messages linked by causality id
+//! - Deployments linked by causality id or started shortly before the incident
+//! - Chat-channel entries tagged with the incident
+//!
+//! Messages are linked through the `correlation_id` metadata key, which every
+//! sender involved in the same chain of events copies forward.
+
+use crate::communication::Message;
+use crate::departments::devops::Deployment;
+use crate::transcripts::CHANNEL_KEY;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Metadata key linking messages, deployments and incidents caused by the same event
+pub const CORRELATION_KEY: &str = "correlation_id";
+
+/// Metadata key naming the incident a message refers to
+pub const INCIDENT_KEY: &str = "incident_id";
+
+/// Metadata key naming the chat thread a message belongs to
+pub const THREAD_KEY: &str = "thread_id";
+
+/// How far before an incident deployments are considered suspects
+pub const DEPLOY_LOOKBACK_MINUTES: i64 = 60;
+
+/// Read the correlation id carried by a message, if any
+pub fn correlation_id(message: &Message) -> Option<Uuid> {
+    message.metadata.get(CORRELATION_KEY).and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Kind of timeline entry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TimelineEntryKind {
+    StatusChange,
+    Alert,
+    Message,
+    Deploy,
+    Chat,
+}
+
+impl TimelineEntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimelineEntryKind::StatusChange => "status",
+            TimelineEntryKind::Alert => "alert",
+            TimelineEntryKind::Message => "message",
+            TimelineEntryKind::Deploy => "deploy",
+            TimelineEntryKind::Chat => "chat",
+        }
+    }
+}
+
+/// Single point on an incident timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub kind: TimelineEntryKind,
+    /// Agent, system actor or department responsible for the entry
+    pub actor: Option<Uuid>,
+    pub summary: String,
+    /// Id of the source message, deployment or incident
+    pub source_id: Uuid,
+}
+
+/// Ordered timeline of everything related to an incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timeline {
+    pub incident_id: Uuid,
+    pub title: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    /// Create an empty timeline
+    pub fn new(incident_id: Uuid, title: String) -> Self {
+        Self {
+            incident_id,
+            title,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add an entry built from a message
+    pub fn push_message(&mut self, message: &Message) {
+        // Every message of an incident shares its thread; chat is what was posted in a channel
+        let kind = if message.metadata.contains_key(CHANNEL_KEY) {
+            TimelineEntryKind::Chat
+        } else if message.message_type.ends_with("_alert") {
+            TimelineEntryKind::Alert
+        } else {
+            TimelineEntryKind::Message
+        };
+
+        self.entries.push(TimelineEntry {
+            at: message.timestamp,
+            kind,
+            actor: Some(message.from_agent),
+            summary: format!("{}: {}", message.message_type, message.content),
+            source_id: message.id,
+        });
+    }
+
+    /// Add an entry built from a deployment
+    pub fn push_deployment(&mut self, deployment: &Deployment) {
+        self.entries.push(TimelineEntry {
+            at: deployment.start_time,
+            kind: TimelineEntryKind::Deploy,
+            actor: None,
+            summary: format!("Deployment of project {} to {} ({:?})",
+                             deployment.project_id.simple(), deployment.environment, deployment.status),
+            source_id: deployment.id,
+        });
+    }
+
+    /// Sort entries chronologically and drop repeated sources
+    pub fn finish(mut self) -> Self {
+        // Stable sort keeps insertion order for entries with the same timestamp
+        self.entries.sort_by_key(|entry| entry.at);
+        let mut seen = std::collections::HashSet::new();
+        self.entries.retain(|entry| entry.kind == TimelineEntryKind::StatusChange || seen.insert(entry.source_id));
+        self
+    }
+
+    /// Time from the first to the last entry
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let first = self.entries.first()?;
+        let last = self.entries.last()?;
+        Some(last.at - first.at)
+    }
+
+    /// Render the timeline as plain text for postmortems and logs
+    pub fn render(&self) -> String {
+        let mut output = format!("Timeline for incident '{}' ({})\n", self.title, self.incident_id.simple());
+        for entry in &self.entries {
+            output.push_str(&format!("  {} [{:>7}] {}\n",
+                                     entry.at.format("%Y-%m-%d %H:%M:%S"), entry.kind.as_str(), entry.summary));
+        }
+        output
+    }
+}
@@ -0,0 +1,242 @@
+//! Network Topology Graph - Reachability, Paths and Partitions
+//!
+//! This module implements Networking's topology as a graph of segments and
+//! devices:
+//! - Every segment and every device is a node; a device is linked to the
+//!   segment it sits in
+//! - Segments are linked to the segments they connect to through their
+//!   online routers and firewalls, or directly when they have none
+//! - Reachability and shortest paths between segments and devices, routed
+//!   around devices that are not online
+//! - Partitions: segments that would reach each other with every device
+//!   online but are cut apart by the devices now offline
+//! - Export as DOT for GraphViz
+//!
+//! The graph is built on demand from the topology Networking keeps, so it
+//! always reflects the current device status.
+
+use crate::departments::networking::{DeviceStatus, DeviceType, NetworkTopology, SecurityLevel};
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::{Bfs, EdgeRef, NodeFiltered};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TopologyNode {
+    Segment {
+        name: String,
+        security_level: SecurityLevel,
+    },
+    Device {
+        id: String,
+        device_type: DeviceType,
+        online: bool,
+    },
+}
+
+impl TopologyNode {
+    /// Segment name or device id
+    pub fn name(&self) -> &str {
+        match self {
+            TopologyNode::Segment { name, .. } => name,
+            TopologyNode::Device { id, .. } => id,
+        }
+    }
+
+    /// Whether traffic can pass through it
+    fn passable(&self) -> bool {
+        match self {
+            TopologyNode::Segment { .. } => true,
+            TopologyNode::Device { online, .. } => *online,
+        }
+    }
+
+    fn dot_attributes(&self) -> &'static str {
+        match self {
+            TopologyNode::Segment { .. } => "shape=box",
+            TopologyNode::Device { online: true, .. } => "shape=ellipse",
+            TopologyNode::Device { online: false, .. } => "shape=ellipse, style=dashed, color=red",
+        }
+    }
+}
+
+impl fmt::Display for TopologyNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyNode::Segment { name, security_level } => write!(f, "{} ({:?})", name, security_level),
+            TopologyNode::Device { id, device_type, .. } => write!(f, "{:?} {}", device_type, id),
+        }
+    }
+}
+
+/// Segments and devices, and the links between them
+#[derive(Debug, Clone, Default)]
+pub struct TopologyGraph {
+    graph: UnGraph<TopologyNode, ()>,
+    /// Node of every segment by name and device by id
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl TopologyGraph {
+    pub fn build(topology: &NetworkTopology) -> Self {
+        let mut graph = UnGraph::new_undirected();
+        let mut nodes = HashMap::new();
+        let mut segment_names = HashMap::new();
+        for segment in topology.segments.values() {
+            let node = graph.add_node(TopologyNode::Segment { name: segment.name.clone(), security_level: segment.security_level.clone() });
+            nodes.insert(segment.name.clone(), node);
+            segment_names.insert(segment.id.clone(), segment.name.clone());
+        }
+        for segment in topology.segments.values() {
+            let segment_node = nodes[&segment.name];
+            let mut gateways = Vec::new();
+            for device in &segment.devices {
+                let node = graph.add_node(TopologyNode::Device {
+                    id: device.id.clone(),
+                    device_type: device.device_type.clone(),
+                    online: device.status == DeviceStatus::Online,
+                });
+                nodes.insert(device.id.clone(), node);
+                graph.add_edge(segment_node, node, ());
+                if matches!(device.device_type, DeviceType::Router | DeviceType::Firewall) {
+                    gateways.push(node);
+                }
+            }
+            // Links may name the other segment or give its id
+            for linked in &segment.connected_segments {
+                let name = segment_names.get(linked).unwrap_or(linked);
+                let Some(&other) = nodes.get(name) else {
+                    continue;
+                };
+                if gateways.is_empty() {
+                    graph.update_edge(segment_node, other, ());
+                }
+                for &gateway in &gateways {
+                    graph.update_edge(gateway, other, ());
+                }
+            }
+        }
+        Self { graph, nodes }
+    }
+
+    /// Names of every node reachable from `from` through online devices
+    pub fn reachable_from(&self, from: &str) -> BTreeSet<String> {
+        let Some(&start) = self.nodes.get(from).filter(|&&node| self.graph[node].passable()) else {
+            return BTreeSet::new();
+        };
+        let online = NodeFiltered::from_fn(&self.graph, |node| self.graph[node].passable());
+        let mut bfs = Bfs::new(&online, start);
+        let mut reached = BTreeSet::new();
+        while let Some(node) = bfs.next(&online) {
+            reached.insert(self.graph[node].name().to_string());
+        }
+        reached
+    }
+
+    pub fn reachable(&self, from: &str, to: &str) -> bool {
+        self.reachable_from(from).contains(to)
+    }
+
+    /// Fewest hops from `from` to `to` through online devices, as the
+    /// names of the nodes on the way
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let (&start, &goal) = (self.nodes.get(from)?, self.nodes.get(to)?);
+        if !self.graph[start].passable() {
+            return None;
+        }
+        let online = NodeFiltered::from_fn(&self.graph, |node| self.graph[node].passable());
+        let (_, path) = petgraph::algo::astar(&online, start, |node| node == goal, |_| 1, |_| 0)?;
+        Some(path.into_iter().map(|node| self.graph[node].name().to_string()).collect())
+    }
+
+    /// Segments grouped by which reach each other, through every device or
+    /// only through online ones
+    fn segment_groups(&self, online_only: bool) -> Vec<BTreeSet<String>> {
+        let graph = NodeFiltered::from_fn(&self.graph, |node| !online_only || self.graph[node].passable());
+        let mut seen = BTreeSet::new();
+        let mut groups = Vec::new();
+        for start in self.graph.node_indices().filter(|&node| matches!(self.graph[node], TopologyNode::Segment { .. })) {
+            if seen.contains(&start) {
+                continue;
+            }
+            let mut bfs = Bfs::new(&graph, start);
+            let mut group = BTreeSet::new();
+            while let Some(node) = bfs.next(&graph) {
+                seen.insert(node);
+                if let TopologyNode::Segment { name, .. } = &self.graph[node] {
+                    group.insert(name.clone());
+                }
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
+    /// Segments cut apart by offline devices: each network that is whole
+    /// with every device online but split now, as the pieces it split into
+    pub fn partitions(&self) -> Vec<Vec<BTreeSet<String>>> {
+        let online = self.segment_groups(true);
+        self.segment_groups(false).into_iter()
+            .map(|whole| online.iter().filter(|piece| piece.is_subset(&whole)).cloned().collect::<Vec<_>>())
+            .filter(|pieces| pieces.len() > 1)
+            .collect()
+    }
+
+    /// The topology in GraphViz DOT; offline devices are dashed
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("graph topology {\n");
+        for node in self.graph.node_indices() {
+            output.push_str(&format!("    {} [label={:?}, {}]\n", node.index(), self.graph[node].to_string(), self.graph[node].dot_attributes()));
+        }
+        for edge in self.graph.edge_references() {
+            output.push_str(&format!("    {} -- {}\n", edge.source().index(), edge.target().index()));
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::networking::{NetworkingAgent, SegmentConfig};
+
+    #[tokio::test]
+    async fn test_paths_route_around_offline_devices_and_partitions_are_found() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        let now = chrono::Utc::now();
+        for (name, cidr) in [("office", "10.0.1.0/24"), ("core", "10.0.2.0/24"), ("db", "10.0.3.0/24")] {
+            agent.configure_segment(SegmentConfig { name: name.to_string(), cidr: cidr.to_string(), security_level: SecurityLevel::Internal }).await.unwrap();
+        }
+        agent.add_device("office", DeviceType::Router, "00:00:5e:00:53:01", now).unwrap();
+        agent.add_device("office", DeviceType::Router, "00:00:5e:00:53:02", now).unwrap();
+        agent.add_device("db", DeviceType::Server, "00:00:5e:00:53:03", now).unwrap();
+        agent.connect_segments("office", "core").unwrap();
+        agent.connect_segments("core", "db").unwrap();
+        let id = |agent: &NetworkingAgent, segment: &str, index: usize| {
+            agent.network_topology.segments.values().find(|s| s.name == segment).unwrap().devices[index].id.clone()
+        };
+        let (router_1, router_2, server) = (id(&agent, "office", 0), id(&agent, "office", 1), id(&agent, "db", 0));
+
+        let graph = agent.topology_graph();
+        assert!(graph.partitions().is_empty());
+        let path = graph.shortest_path("office", &server).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[2], "core");
+        assert!(graph.to_dot().contains("shape=box"));
+
+        // One router down leaves the other; both down cut the office off
+        agent.set_device_status(&router_1, DeviceStatus::Offline);
+        assert!(agent.topology_graph().reachable("office", &server));
+        agent.set_device_status(&router_2, DeviceStatus::Maintenance);
+        let graph = agent.topology_graph();
+        assert!(!graph.reachable("office", "db"));
+        assert!(graph.reachable("core", &server));
+        assert!(graph.shortest_path(&router_1, "office").is_none());
+        let partitions = graph.partitions();
+        assert_eq!(partitions.len(), 1);
+        assert!(partitions[0].contains(&BTreeSet::from(["office".to_string()])));
+        assert!(graph.to_dot().contains("style=dashed"));
+    }
+}
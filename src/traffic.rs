@@ -0,0 +1,223 @@
+//! Traffic Simulation - Service Flows and the Metrics They Drive
+//!
+//! This module implements the traffic crossing Networking's segments:
+//! - Demand per catalog service from the customers it serves and the
+//!   projects being built for them
+//! - Flows generated from that demand: customer traffic enters at the least
+//!   secure segment and goes to the servers, project traffic comes from
+//!   internal segments; each day's volume varies around the demand
+//! - Flows routed over the topology graph and aggregated per segment and
+//!   per link between segments; flows with no route are lost
+//! - Latency and packet loss derived from how full each segment on a flow's
+//!   path is: queueing delay grows as utilization nears capacity, and
+//!   traffic beyond capacity is dropped
+//!
+//! Networking upgrades the capacity of segments running hot, which shows up
+//! as lower latency and loss on the next day's traffic.
+
+use crate::config::TrafficSettings;
+use crate::departments::networking::{LatencyStats, PacketLossStats, SecurityLevel};
+use crate::topology::TopologyGraph;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Average packet size
+const PACKET_BITS: u64 = 1500 * 8;
+
+/// Utilization queueing delay is computed at most for
+const MAX_QUEUE_UTILIZATION: f64 = 0.95;
+
+/// Utilization above which queues start dropping packets
+const LOSS_UTILIZATION: f64 = 0.9;
+
+/// Traffic a catalog service draws
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceDemand {
+    pub service: String,
+    /// Customers the service's deployments serve
+    pub customers: usize,
+    /// Projects being built for those customers
+    pub projects: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flow {
+    pub service: String,
+    pub source: String,
+    pub destination: String,
+    pub bps: u64,
+}
+
+/// Traffic through one segment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SegmentLoad {
+    pub bps: u64,
+    pub capacity_bps: u64,
+    pub utilization: f64,
+}
+
+/// A day of traffic and what it did to the network
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrafficReport {
+    pub flows: usize,
+    pub segments: BTreeMap<String, SegmentLoad>,
+    /// Traffic between two linked segments, keyed `a <-> b`
+    pub links: BTreeMap<String, u64>,
+    /// Traffic of flows with no route to their destination
+    pub unrouted_bps: u64,
+    pub latency: Option<LatencyStats>,
+    pub packet_loss: Option<PacketLossStats>,
+}
+
+/// A day of flows for the demand. `segments` are the segment names and
+/// levels; customer traffic goes to `servers` from the least secure one.
+pub fn generate(demand: &[ServiceDemand], segments: &BTreeMap<String, SecurityLevel>, servers: &str, settings: &TrafficSettings, rng: &mut impl Rng) -> Vec<Flow> {
+    let Some(ingress) = segments.iter().min_by_key(|(_, level)| (*level).clone()).map(|(name, _)| name.clone()) else {
+        return Vec::new();
+    };
+    let internal: Vec<&String> = segments.iter()
+        .filter(|(name, level)| **level >= SecurityLevel::Internal && name.as_str() != servers)
+        .map(|(name, _)| name)
+        .collect();
+    let variability = settings.variability.clamp(0.0, 1.0);
+    let mut volume = |mbps: f64| (mbps * 1_000_000.0 * (1.0 + rng.gen_range(-variability..=variability))).max(0.0) as u64;
+
+    let mut flows = Vec::new();
+    for service in demand {
+        if service.customers > 0 {
+            flows.push(Flow {
+                service: service.service.clone(),
+                source: ingress.clone(),
+                destination: servers.to_string(),
+                bps: volume(settings.customer_mbps * service.customers as f64),
+            });
+        }
+        for project in 0..service.projects {
+            let source = internal.get(project % internal.len().max(1)).map_or(servers, |name| name.as_str());
+            flows.push(Flow {
+                service: service.service.clone(),
+                source: source.to_string(),
+                destination: servers.to_string(),
+                bps: volume(settings.project_mbps),
+            });
+        }
+    }
+    flows
+}
+
+/// Share of a segment's traffic its queues drop
+fn loss(utilization: f64) -> f64 {
+    if utilization > 1.0 {
+        1.0 - 1.0 / utilization
+    } else if utilization > LOSS_UTILIZATION {
+        (utilization - LOSS_UTILIZATION) * 0.1
+    } else {
+        0.0
+    }
+}
+
+/// Route the flows over the topology and derive segment loads, latency and
+/// packet loss. `capacities` are the segments' capacities in bps.
+pub fn simulate(flows: &[Flow], graph: &TopologyGraph, capacities: &BTreeMap<String, u64>, settings: &TrafficSettings) -> TrafficReport {
+    let mut report = TrafficReport { flows: flows.len(), ..TrafficReport::default() };
+    let mut routed = Vec::new();
+    for flow in flows {
+        let path = if flow.source == flow.destination { Some(vec![flow.source.clone()]) } else { graph.shortest_path(&flow.source, &flow.destination) };
+        let Some(path) = path.filter(|path| capacities.contains_key(&path[0])) else {
+            report.unrouted_bps += flow.bps;
+            continue;
+        };
+        // Devices on the way are not capacity-limited
+        let path: Vec<String> = path.into_iter().filter(|node| capacities.contains_key(node)).collect();
+        for segment in &path {
+            report.segments.entry(segment.clone()).or_default().bps += flow.bps;
+        }
+        for pair in path.windows(2) {
+            let (a, b) = if pair[0] <= pair[1] { (&pair[0], &pair[1]) } else { (&pair[1], &pair[0]) };
+            *report.links.entry(format!("{} <-> {}", a, b)).or_default() += flow.bps;
+        }
+        routed.push((flow.bps, path));
+    }
+    for (name, load) in report.segments.iter_mut() {
+        load.capacity_bps = capacities[name];
+        load.utilization = load.bps as f64 / load.capacity_bps.max(1) as f64;
+    }
+
+    // Every segment on the path queues the flow and may drop some of it
+    let mut flow_stats: Vec<(f64, f64, u64)> = routed.iter()
+        .map(|(bps, path)| {
+            let (latency, delivered) = path.iter().fold((0.0, 1.0), |(latency, delivered), segment| {
+                let utilization = report.segments[segment].utilization;
+                (latency + settings.hop_latency_ms / (1.0 - utilization.min(MAX_QUEUE_UTILIZATION)), delivered * (1.0 - loss(utilization)))
+            });
+            (latency, 1.0 - delivered, *bps)
+        })
+        .collect();
+    let total_bps: u64 = routed.iter().map(|(bps, _)| bps).sum::<u64>() + report.unrouted_bps;
+    if total_bps == 0 {
+        return report;
+    }
+    flow_stats.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let routed_bps = (total_bps - report.unrouted_bps).max(1) as f64;
+    let average = flow_stats.iter().map(|(latency, _, bps)| latency * *bps as f64).sum::<f64>() / routed_bps;
+    let mut cumulative = 0;
+    let p95 = flow_stats.iter()
+        .find(|(_, _, bps)| {
+            cumulative += bps;
+            cumulative as f64 >= 0.95 * routed_bps
+        })
+        .map_or(average, |(latency, _, _)| *latency);
+    if let (Some(min), Some(max)) = (flow_stats.first(), flow_stats.last()) {
+        report.latency = Some(LatencyStats { average_ms: average as f32, min_ms: min.0 as f32, max_ms: max.0 as f32, p95_ms: p95 as f32 });
+    }
+
+    let lost_bps = flow_stats.iter().map(|(_, lost, bps)| lost * *bps as f64).sum::<f64>() + report.unrouted_bps as f64;
+    let share = lost_bps / total_bps as f64;
+    // A day of traffic
+    let total_packets = total_bps.saturating_mul(86_400) / PACKET_BITS;
+    report.packet_loss = Some(PacketLossStats {
+        percentage: (share * 100.0) as f32,
+        total_packets,
+        lost_packets: (total_packets as f64 * share) as u64,
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::networking::{NetworkingAgent, SegmentConfig};
+    use rand::SeedableRng;
+
+    #[tokio::test]
+    async fn test_utilization_drives_latency_and_loss() {
+        let mut agent = NetworkingAgent::new("Test Agent".to_string(), None);
+        for (name, cidr, security_level) in [("dmz", "10.0.1.0/24", SecurityLevel::DMZ), ("office", "10.0.2.0/24", SecurityLevel::Internal), ("servers", "10.1.0.0/16", SecurityLevel::Internal)] {
+            agent.configure_segment(SegmentConfig { name: name.to_string(), cidr: cidr.to_string(), security_level }).await.unwrap();
+        }
+        agent.connect_segments("dmz", "servers").unwrap();
+        agent.connect_segments("office", "servers").unwrap();
+        let graph = agent.topology_graph();
+        let segments = agent.network_topology.segments.values().map(|s| (s.name.clone(), s.security_level.clone())).collect();
+
+        let settings = TrafficSettings { variability: 0.0, ..TrafficSettings::default() };
+        let demand = [ServiceDemand { service: "api".to_string(), customers: 10, projects: 2 }];
+        let flows = generate(&demand, &segments, "servers", &settings, &mut rand::rngs::StdRng::seed_from_u64(1));
+        assert_eq!(flows.len(), 3);
+        assert_eq!((flows[0].source.as_str(), flows[0].bps), ("dmz", (settings.customer_mbps * 10.0 * 1_000_000.0) as u64));
+        assert!(flows[1..].iter().all(|flow| flow.source == "office"));
+
+        let capacity = |mbps: u64| segments.keys().map(|name: &String| (name.clone(), mbps * 1_000_000)).collect::<BTreeMap<_, _>>();
+        let idle = simulate(&flows, &graph, &capacity(100_000), &settings);
+        let busy = simulate(&flows, &graph, &capacity(300), &settings);
+        assert_eq!(idle.links.len(), 2);
+        assert_eq!(busy.segments["servers"].bps, flows.iter().map(|flow| flow.bps).sum::<u64>());
+        assert!(busy.segments["servers"].utilization > 1.0);
+        let latency = |report: &TrafficReport| report.latency.as_ref().unwrap().average_ms;
+        let loss = |report: &TrafficReport| report.packet_loss.as_ref().unwrap().percentage;
+        assert!(latency(&busy) > latency(&idle));
+        assert_eq!(loss(&idle), 0.0);
+        assert!(loss(&busy) > 0.0);
+    }
+}
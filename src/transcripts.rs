@@ -0,0 +1,303 @@
+//! Conversation Transcripts - Chat Exports
+//!
+//! This module implements rendering agent conversations as chat logs:
+//! - Messages grouped into channels (explicit `channel` metadata, otherwise
+//!   the recipient's department)
+//! - Replies grouped into threads by thread or causality id
+//! - Slack-export-style JSON (`users.json`, `channels.json`, one file per
+//!   channel per day) or readable plain-text logs per channel
+
+use crate::agents::Agent;
+use crate::communication::Message;
+use crate::system::SystemActor;
+use crate::timeline::{CORRELATION_KEY, THREAD_KEY};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Metadata key naming the channel a message was posted in
+pub const CHANNEL_KEY: &str = "channel";
+
+/// Output format of a transcript export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Slack workspace export layout
+    Slack,
+    /// Plain-text log per channel
+    Text,
+}
+
+/// Participant in a transcript
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatUser {
+    pub id: Uuid,
+    pub name: String,
+    /// Department name, or "System" for system actors
+    pub title: String,
+}
+
+/// Single rendered chat message
+#[derive(Debug, Clone)]
+pub struct ChatLine {
+    pub message_id: Uuid,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub user: Uuid,
+    pub text: String,
+    /// Timestamp of the first message in the thread, when this is a reply
+    pub thread_root: Option<chrono::DateTime<chrono::Utc>>,
+    /// Sender of the first message in the thread, when this is a reply
+    pub thread_root_user: Option<Uuid>,
+    /// Whether later messages reply to this one
+    pub has_replies: bool,
+}
+
+/// Conversations grouped by channel
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub users: BTreeMap<Uuid, ChatUser>,
+    pub channels: BTreeMap<String, Vec<ChatLine>>,
+}
+
+impl Transcript {
+    /// Build a transcript from dispatched messages
+    pub fn from_messages(messages: &[Message], agents: &[&Agent]) -> Self {
+        let agents_by_id: HashMap<Uuid, &Agent> = agents.iter().map(|agent| (agent.id, *agent)).collect();
+        let mut transcript = Transcript::default();
+
+        let mut ordered: Vec<&Message> = messages.iter().collect();
+        ordered.sort_by_key(|message| message.timestamp);
+
+        // Thread key → (channel, index of the root line)
+        let mut thread_roots: HashMap<String, (String, usize)> = HashMap::new();
+
+        for message in ordered {
+            transcript.add_user(message.from_agent, &agents_by_id);
+
+            let channel = message.metadata.get(CHANNEL_KEY)
+                .map(String::as_str)
+                .map(channel_name)
+                .unwrap_or_else(|| channel_for(message.to_agent, &agents_by_id));
+            let thread_key = message.metadata.get(THREAD_KEY)
+                .or_else(|| message.metadata.get(CORRELATION_KEY))
+                .cloned();
+
+            let lines = transcript.channels.entry(channel.clone()).or_default();
+            let mut thread_root = None;
+            if let Some(key) = thread_key {
+                match thread_roots.get(&key) {
+                    Some((root_channel, root_index)) if *root_channel == channel => {
+                        let root = &mut lines[*root_index];
+                        root.has_replies = true;
+                        thread_root = Some((root.at, root.user));
+                    }
+                    _ => {
+                        thread_roots.insert(key, (channel.clone(), lines.len()));
+                    }
+                }
+            }
+
+            lines.push(ChatLine {
+                message_id: message.id,
+                at: message.timestamp,
+                user: message.from_agent,
+                text: message.content.clone(),
+                thread_root: thread_root.map(|(at, _)| at),
+                thread_root_user: thread_root.map(|(_, user)| user),
+                has_replies: false,
+            });
+        }
+
+        transcript
+    }
+
+    fn add_user(&mut self, id: Uuid, agents: &HashMap<Uuid, &Agent>) {
+        if self.users.contains_key(&id) {
+            return;
+        }
+        let (name, title) = match (agents.get(&id), SystemActor::from_id(id)) {
+            (Some(agent), _) => (agent.name.clone(), agent.department.as_str().to_string()),
+            (None, Some(actor)) => (actor.name().to_string(), "System".to_string()),
+            (None, None) => (format!("unknown-{}", id.simple()), "Unknown".to_string()),
+        };
+        self.users.insert(id, ChatUser { id, name, title });
+    }
+
+    fn user_name(&self, id: Uuid) -> &str {
+        self.users.get(&id).map(|user| user.name.as_str()).unwrap_or("unknown")
+    }
+
+    /// Write the transcript in the requested format into a directory
+    pub fn export(&self, format: ExportFormat, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        match format {
+            ExportFormat::Slack => self.write_slack_export(dir),
+            ExportFormat::Text => {
+                for channel in self.channels.keys() {
+                    std::fs::write(dir.join(format!("{}.log", channel)), self.render_channel(channel))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Write a Slack-style workspace export
+    pub fn write_slack_export(&self, dir: &Path) -> std::io::Result<()> {
+        let users: Vec<serde_json::Value> = self.users.values()
+            .map(|user| serde_json::json!({
+                "id": slack_user_id(user.id),
+                "name": user.name.to_lowercase().replace(' ', "."),
+                "real_name": user.name,
+                "profile": { "title": user.title, "real_name": user.name },
+            }))
+            .collect();
+        write_json(&dir.join("users.json"), &users)?;
+
+        let channels: Vec<serde_json::Value> = self.channels.iter()
+            .map(|(name, lines)| {
+                let mut members: Vec<String> = lines.iter().map(|line| slack_user_id(line.user)).collect();
+                members.sort();
+                members.dedup();
+                serde_json::json!({
+                    "id": format!("C{}", name.to_uppercase().replace('-', "")),
+                    "name": name,
+                    "created": lines.first().map(|line| line.at.timestamp()).unwrap_or(0),
+                    "members": members,
+                })
+            })
+            .collect();
+        write_json(&dir.join("channels.json"), &channels)?;
+
+        for (channel, lines) in &self.channels {
+            let channel_dir = dir.join(channel);
+            std::fs::create_dir_all(&channel_dir)?;
+
+            let mut days: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+            for line in lines {
+                let mut entry = serde_json::json!({
+                    "type": "message",
+                    "user": slack_user_id(line.user),
+                    "user_name": self.user_name(line.user),
+                    "text": line.text,
+                    "ts": slack_ts(line.at),
+                    "client_msg_id": line.message_id.to_string(),
+                });
+                if let (Some(root), Some(root_user)) = (line.thread_root, line.thread_root_user) {
+                    entry["thread_ts"] = slack_ts(root).into();
+                    entry["parent_user_id"] = slack_user_id(root_user).into();
+                } else if line.has_replies {
+                    entry["thread_ts"] = slack_ts(line.at).into();
+                }
+                days.entry(line.at.format("%Y-%m-%d").to_string()).or_default().push(entry);
+            }
+
+            for (day, entries) in days {
+                write_json(&channel_dir.join(format!("{}.json", day)), &entries)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render one channel as a readable chat log
+    pub fn render_channel(&self, channel: &str) -> String {
+        let mut output = format!("#{}\n", channel);
+        for line in self.channels.get(channel).into_iter().flatten() {
+            let indent = if line.thread_root.is_some() { "    ↳ " } else { "" };
+            output.push_str(&format!("{}[{}] {}: {}\n",
+                                     indent, line.at.format("%Y-%m-%d %H:%M:%S"), self.user_name(line.user), line.text));
+        }
+        output
+    }
+}
+
+/// Default channel for a message: the recipient's department
+fn channel_for(recipient: Uuid, agents: &HashMap<Uuid, &Agent>) -> String {
+    match agents.get(&recipient) {
+        Some(agent) => agent.department.as_str().to_lowercase(),
+        None if SystemActor::is_system(recipient) => "system".to_string(),
+        None => "general".to_string(),
+    }
+}
+
+/// Channel name safe to use as a file name: lowercase letters, digits,
+/// `-` and `_`, as Slack allows
+fn channel_name(name: &str) -> String {
+    let name: String = name.trim().to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() { "general".to_string() } else { name.to_string() }
+}
+
+fn slack_user_id(id: Uuid) -> String {
+    format!("U{}", id.simple().to_string().to_uppercase())
+}
+
+fn slack_ts(at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("{}.{:06}", at.timestamp(), at.timestamp_subsec_micros())
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentTrait;
+    use crate::testsupport::test_message;
+    use crate::departments::devops::DevOpsAgent;
+    use crate::departments::ops::OpsAgent;
+
+    fn message(from: Uuid, to: Uuid, content: &str, thread: Option<&str>, offset_secs: i64) -> Message {
+        let mut message = test_message(from, to, "status_update");
+        message.content = content.to_string();
+        message.timestamp += chrono::Duration::seconds(offset_secs);
+        if let Some(thread) = thread {
+            message.metadata.insert(THREAD_KEY.to_string(), thread.to_string());
+        }
+        message
+    }
+
+    #[test]
+    fn test_threads_and_channels() {
+        let ops = OpsAgent::new("Dana Ops".to_string(), None);
+        let devops = DevOpsAgent::new("Riley DevOps".to_string(), None);
+        let (ops_id, devops_id) = (ops.get_agent().id, devops.get_agent().id);
+
+        let messages = vec![
+            message(devops_id, ops_id, "Deploy finished", Some("t1"), 0),
+            message(ops_id, ops_id, "Thanks, verifying", Some("t1"), 5),
+            message(ops_id, devops_id, "Can you check disk usage?", None, 10),
+        ];
+        let transcript = Transcript::from_messages(&messages, &[ops.get_agent(), devops.get_agent()]);
+
+        assert_eq!(transcript.channels.len(), 2);
+        let ops_channel = &transcript.channels["ops"];
+        assert!(ops_channel[0].has_replies);
+        assert_eq!(ops_channel[1].thread_root, Some(ops_channel[0].at));
+
+        assert_eq!(ops_channel[1].thread_root_user, Some(devops_id));
+
+        let rendered = transcript.render_channel("ops");
+        assert!(rendered.contains("Riley DevOps: Deploy finished"));
+        assert!(rendered.contains("    ↳ "));
+    }
+
+    #[test]
+    fn test_channel_names_stay_inside_the_export() {
+        let ops = OpsAgent::new("Dana Ops".to_string(), None);
+        let ops_id = ops.get_agent().id;
+        let mut escaping = message(ops_id, ops_id, "Hello", None, 0);
+        escaping.metadata.insert(CHANNEL_KEY.to_string(), "../../etc/War Room".to_string());
+        let mut dots = message(ops_id, ops_id, "Hi", None, 1);
+        dots.metadata.insert(CHANNEL_KEY.to_string(), "..".to_string());
+
+        let transcript = Transcript::from_messages(&[escaping, dots], &[ops.get_agent()]);
+        let names: Vec<&str> = transcript.channels.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["etc-war-room", "general"]);
+    }
+}
@@ -0,0 +1,511 @@
+//! Source Control - Simulated Git Repository
+//!
+//! This module implements a lightweight git-like repository for the company's
+//! product code:
+//! - Commits with content-derived hashes, authors and parent links, made on
+//!   named branches
+//! - Merge commits joining a feature branch into the default branch, and
+//!   reverts that undo an earlier commit
+//! - Release tags cut from merges, which deployments and changelogs refer to
+//! - `VcsRequest`s, sent by Engineering agents to the orchestrator, which
+//!   owns the repository and applies them in message order
+//!
+//! There is no file content: a commit records who changed what and why, which
+//! is all deployments, reverts and release notes need.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// Message type of a `VcsRequest` sent to the orchestrator
+pub const VCS_REQUEST: &str = "vcs_request";
+
+/// Shortest hash prefix accepted as a revision
+const MIN_PREFIX_LEN: usize = 4;
+
+/// Who made a commit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Signature {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommitKind {
+    Initial,
+    Change,
+    /// Joins `branch` into the branch the commit was made on
+    Merge { branch: String },
+    /// Undoes the commit with hash `reverts`
+    Revert { reverts: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Commit {
+    pub hash: String,
+    /// First parent is the branch's previous head; merges have a second
+    pub parents: Vec<String>,
+    pub author: Signature,
+    pub message: String,
+    /// Branch the commit was made on
+    pub branch: String,
+    pub kind: CommitKind,
+    pub project_id: Option<Uuid>,
+    pub committed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Commit {
+    /// Abbreviated hash for logs
+    pub fn short_hash(&self) -> &str {
+        short(&self.hash)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub commit: String,
+    pub message: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Change an Engineering agent asks the orchestrator to make
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum VcsRequest {
+    /// Commit to a branch, creating it from the default branch if needed
+    Commit {
+        branch: String,
+        message: String,
+        project_id: Option<Uuid>,
+    },
+    /// Merge a branch into the default branch and delete it
+    Merge {
+        branch: String,
+        project_id: Option<Uuid>,
+    },
+    /// Revert a commit on the default branch
+    Revert {
+        commit: String,
+        reason: String,
+    },
+}
+
+/// What applying a `VcsRequest` did
+#[derive(Debug, Clone, PartialEq)]
+pub enum Applied {
+    Committed { hash: String, branch: String },
+    Merged { hash: String, branch: String, project_id: Option<Uuid> },
+    Reverted { hash: String, reverted: String },
+}
+
+/// A repository of commits, branches and tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Repository {
+    pub name: String,
+    pub default_branch: String,
+    commits: HashMap<String, Commit>,
+    /// Commit hashes in the order they were made
+    history: Vec<String>,
+    /// Branch name to head commit
+    branches: BTreeMap<String, String>,
+    /// Tags in the order they were cut
+    tags: Vec<Tag>,
+}
+
+impl Repository {
+    /// Create a repository holding only an initial commit on the default branch
+    pub fn new(name: &str, default_branch: &str, now: chrono::DateTime<chrono::Utc>) -> Self {
+        let mut repository = Self {
+            name: name.to_string(),
+            default_branch: default_branch.to_string(),
+            commits: HashMap::new(),
+            history: Vec::new(),
+            branches: BTreeMap::new(),
+            tags: Vec::new(),
+        };
+        let root = repository.record(
+            Vec::new(),
+            Signature { id: Uuid::nil(), name: "system".to_string() },
+            "Initial commit".to_string(),
+            default_branch,
+            CommitKind::Initial,
+            None,
+            now,
+        );
+        repository.branches.insert(default_branch.to_string(), root);
+        repository
+    }
+
+    /// Number of commits in the repository
+    pub fn commit_count(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Merges of a project's work that have not been reverted
+    pub fn merge_count(&self, project_id: Uuid) -> usize {
+        self.commits.values()
+            .filter(|commit| commit.project_id == Some(project_id) && matches!(commit.kind, CommitKind::Merge { .. }))
+            .filter(|commit| !self.is_reverted(&commit.hash))
+            .count()
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&Commit> {
+        self.commits.get(hash)
+    }
+
+    /// Head commit of a branch
+    pub fn head(&self, branch: &str) -> Option<&Commit> {
+        self.branches.get(branch).and_then(|hash| self.commits.get(hash))
+    }
+
+    /// Branch names with their head commit hashes
+    pub fn branches(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.branches.iter().map(|(name, head)| (name.as_str(), head.as_str()))
+    }
+
+    /// Tags, oldest first
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub fn latest_tag(&self) -> Option<&Tag> {
+        self.tags.last()
+    }
+
+    /// Start a branch at a revision
+    pub fn create_branch(&mut self, name: &str, from: &str) -> Result<String, VcsError> {
+        if self.branches.contains_key(name) {
+            return Err(VcsError::BranchExists(name.to_string()));
+        }
+        let head = self.resolve(from)?;
+        self.branches.insert(name.to_string(), head.clone());
+        Ok(head)
+    }
+
+    /// Delete a branch; its commits stay reachable from wherever they were merged
+    pub fn delete_branch(&mut self, name: &str) -> Result<(), VcsError> {
+        if name == self.default_branch {
+            return Err(VcsError::DefaultBranch(name.to_string()));
+        }
+        self.branches.remove(name).map(|_| ()).ok_or_else(|| VcsError::UnknownBranch(name.to_string()))
+    }
+
+    /// Add a commit on top of a branch
+    pub fn commit(&mut self, branch: &str, author: Signature, message: &str, project_id: Option<Uuid>, now: chrono::DateTime<chrono::Utc>) -> Result<String, VcsError> {
+        let parent = self.branch_head(branch)?;
+        Ok(self.record(NewCommit {
+            parents: vec![parent],
+            author,
+            message: message.to_string(),
+            branch,
+            kind: CommitKind::Change,
+            project_id,
+            committed_at: now,
+        }))
+    }
+
+    /// Join `source` into `target` with a merge commit
+    pub fn merge(&mut self, source: &str, target: &str, author: Signature, now: chrono::DateTime<chrono::Utc>) -> Result<String, VcsError> {
+        let source_head = self.branch_head(source)?;
+        let target_head = self.branch_head(target)?;
+        if self.is_ancestor(&source_head, &target_head) {
+            return Err(VcsError::AlreadyMerged { branch: source.to_string(), target: target.to_string() });
+        }
+
+        let project_id = self.commits[&source_head].project_id;
+        let message = format!("Merge branch '{}' into {}", source, target);
+        let kind = CommitKind::Merge { branch: source.to_string() };
+        Ok(self.record(NewCommit {
+            parents: vec![target_head, source_head],
+            author,
+            message,
+            branch: target,
+            kind,
+            project_id,
+            committed_at: now,
+        }))
+    }
+
+    /// Undo a commit reachable from `branch` with a new commit on it
+    pub fn revert(&mut self, branch: &str, revision: &str, author: Signature, now: chrono::DateTime<chrono::Utc>) -> Result<String, VcsError> {
+        let head = self.branch_head(branch)?;
+        let target = self.resolve(revision)?;
+        let reachable = self.reachable(&head);
+        if !reachable.contains(&target) {
+            return Err(VcsError::NotOnBranch { commit: short(&target).to_string(), branch: branch.to_string() });
+        }
+        if self.is_reverted(&target) {
+            return Err(VcsError::AlreadyReverted(short(&target).to_string()));
+        }
+
+        let original = &self.commits[&target];
+        let project_id = original.project_id;
+        let subject = original.message.lines().next().unwrap_or_default();
+        let message = format!("Revert \"{}\"\n\nThis reverts commit {}.", subject, target);
+        let kind = CommitKind::Revert { reverts: target };
+        Ok(self.record(NewCommit {
+            parents: vec![head],
+            author,
+            message,
+            branch,
+            kind,
+            project_id,
+            committed_at: now,
+        }))
+    }
+
+    /// Whether a commit has been undone by a later revert
+    pub fn is_reverted(&self, hash: &str) -> bool {
+        self.commits.values().any(|commit| matches!(&commit.kind, CommitKind::Revert { reverts } if reverts == hash))
+    }
+
+    /// Tag a revision
+    pub fn tag(&mut self, name: &str, revision: &str, message: &str, now: chrono::DateTime<chrono::Utc>) -> Result<&Tag, VcsError> {
+        if self.tags.iter().any(|tag| tag.name == name) {
+            return Err(VcsError::TagExists(name.to_string()));
+        }
+        let commit = self.resolve(revision)?;
+        self.tags.push(Tag { name: name.to_string(), commit, message: message.to_string(), created_at: now });
+        Ok(self.tags.last().expect("tag just pushed"))
+    }
+
+    /// Tag a revision with the next release version
+    pub fn release(&mut self, revision: &str, now: chrono::DateTime<chrono::Utc>) -> Result<&Tag, VcsError> {
+        let name = format!("v1.{}.0", self.tags.len());
+        let message = format!("Release {}", name);
+        self.tag(&name, revision, &message, now)
+    }
+
+    /// Full hash of a branch, tag, full hash or unique hash prefix
+    pub fn resolve(&self, revision: &str) -> Result<String, VcsError> {
+        if let Some(head) = self.branches.get(revision) {
+            return Ok(head.clone());
+        }
+        if let Some(tag) = self.tags.iter().find(|tag| tag.name == revision) {
+            return Ok(tag.commit.clone());
+        }
+        if self.commits.contains_key(revision) {
+            return Ok(revision.to_string());
+        }
+        if revision.len() >= MIN_PREFIX_LEN {
+            let mut matches = self.history.iter().filter(|hash| hash.starts_with(revision));
+            if let (Some(hash), None) = (matches.next(), matches.next()) {
+                return Ok(hash.clone());
+            }
+        }
+        Err(VcsError::UnknownRevision(revision.to_string()))
+    }
+
+    /// Whether `ancestor` is `descendant` or one of its ancestors
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> bool {
+        self.reachable(descendant).contains(ancestor)
+    }
+
+    /// Commits reachable from a revision, newest first
+    pub fn log(&self, revision: &str) -> Result<Vec<&Commit>, VcsError> {
+        let reachable = self.reachable(&self.resolve(revision)?);
+        Ok(self.newest_first(|hash| reachable.contains(hash)))
+    }
+
+    /// Commits reachable from `to` but not from `from`, newest first
+    ///
+    /// With `from` unset this is the whole log of `to`.
+    pub fn commits_between(&self, from: Option<&str>, to: &str) -> Result<Vec<&Commit>, VcsError> {
+        let included = self.reachable(&self.resolve(to)?);
+        let excluded = match from {
+            Some(from) => self.reachable(&self.resolve(from)?),
+            None => HashSet::new(),
+        };
+        Ok(self.newest_first(|hash| included.contains(hash) && !excluded.contains(hash)))
+    }
+
+    /// Apply a request from an Engineering agent
+    pub fn apply(&mut self, request: &VcsRequest, author: Signature, now: chrono::DateTime<chrono::Utc>) -> Result<Applied, VcsError> {
+        match request {
+            VcsRequest::Commit { branch, message, project_id } => {
+                if !self.branches.contains_key(branch) {
+                    let default_branch = self.default_branch.clone();
+                    self.create_branch(branch, &default_branch)?;
+                }
+                let hash = self.commit(branch, author, message, *project_id, now)?;
+                Ok(Applied::Committed { hash, branch: branch.clone() })
+            }
+            VcsRequest::Merge { branch, project_id } => {
+                let default_branch = self.default_branch.clone();
+                let hash = self.merge(branch, &default_branch, author, now)?;
+                self.delete_branch(branch)?;
+                Ok(Applied::Merged { hash, branch: branch.clone(), project_id: *project_id })
+            }
+            VcsRequest::Revert { commit, .. } => {
+                let reverted = self.resolve(commit)?;
+                let default_branch = self.default_branch.clone();
+                let hash = self.revert(&default_branch, &reverted, author, now)?;
+                Ok(Applied::Reverted { hash, reverted })
+            }
+        }
+    }
+
+    fn branch_head(&self, branch: &str) -> Result<String, VcsError> {
+        self.branches.get(branch).cloned().ok_or_else(|| VcsError::UnknownBranch(branch.to_string()))
+    }
+
+    fn reachable(&self, hash: &str) -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![hash.to_string()];
+        while let Some(hash) = stack.pop() {
+            if let Some(commit) = self.commits.get(&hash) {
+                if seen.insert(hash) {
+                    stack.extend(commit.parents.iter().cloned());
+                }
+            }
+        }
+        seen
+    }
+
+    fn newest_first(&self, include: impl Fn(&String) -> bool) -> Vec<&Commit> {
+        self.history.iter().rev()
+            .filter(|hash| include(hash))
+            .map(|hash| &self.commits[hash])
+            .collect()
+    }
+
+    /// Add a commit and move its branch's head to it
+    fn record(&mut self, new: NewCommit) -> String {
+        // The history length keeps otherwise identical commits apart
+        let input = format!("{}\n{}\n{}\n{}\n{}\n{}", self.name, self.history.len(), new.parents.join(" "), new.author.id, new.committed_at.to_rfc3339(), new.message);
+        let hash = commit_hash(input.as_bytes());
+
+        self.history.push(hash.clone());
+        self.branches.insert(new.branch.to_string(), hash.clone());
+        self.commits.insert(hash.clone(), Commit {
+            hash: hash.clone(),
+            parents: new.parents,
+            author: new.author,
+            message: new.message,
+            branch: new.branch.to_string(),
+            kind: new.kind,
+            project_id: new.project_id,
+            committed_at: new.committed_at,
+        });
+        hash
+    }
+}
+
+/// Commit about to be recorded, before its hash is known
+struct NewCommit<'a> {
+    parents: Vec<String>,
+    author: Signature,
+    message: String,
+    branch: &'a str,
+    kind: CommitKind,
+    project_id: Option<Uuid>,
+    committed_at: chrono::DateTime<chrono::Utc>,
+}
+}
+
+/// Abbreviated form of a commit hash
+pub fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+/// 40 hex digits from three FNV-1a passes with different offsets
+fn commit_hash(input: &[u8]) -> String {
+    const OFFSETS: [u64; 3] = [0xcbf2_9ce4_8422_2325, 0x8422_2325_cbf2_9ce4, 0x9e37_79b9_7f4a_7c15];
+    let mut hex = String::with_capacity(48);
+    for offset in OFFSETS {
+        let hash = input.iter().fold(offset, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3));
+        hex.push_str(&format!("{:016x}", hash));
+    }
+    hex.truncate(40);
+    hex
+}
+
+/// Repository errors
+#[derive(Debug, thiserror::Error)]
+pub enum VcsError {
+    #[error("No branch named '{0}'")]
+    UnknownBranch(String),
+
+    #[error("Branch '{0}' already exists")]
+    BranchExists(String),
+
+    #[error("Cannot delete the default branch '{0}'")]
+    DefaultBranch(String),
+
+    #[error("No commit, branch or tag matches '{0}'")]
+    UnknownRevision(String),
+
+    #[error("Tag '{0}' already exists")]
+    TagExists(String),
+
+    #[error("Branch '{branch}' is already merged into {target}")]
+    AlreadyMerged { branch: String, target: String },
+
+    #[error("Commit {0} has already been reverted")]
+    AlreadyReverted(String),
+
+    #[error("Commit {commit} is not on branch '{branch}'")]
+    NotOnBranch { commit: String, branch: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engineer() -> Signature {
+        Signature { id: Uuid::new_v4(), name: "Engineering Agent 1".to_string() }
+    }
+
+    fn start() -> chrono::DateTime<chrono::Utc> {
+        "2024-03-04T09:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_feature_branch_merges_and_releases() {
+        let mut repo = Repository::new("platform", "main", start());
+        let project_id = Some(Uuid::new_v4());
+        let feature = VcsRequest::Commit { branch: "feature/search".to_string(), message: "Add search index".to_string(), project_id };
+
+        let Applied::Committed { hash: first, .. } = repo.apply(&feature, engineer(), start()).unwrap() else { panic!("expected a commit") };
+        repo.apply(&feature, engineer(), start()).unwrap();
+        assert_ne!(repo.head("feature/search").unwrap().hash, first);
+        assert_eq!(repo.head("main").unwrap().kind, CommitKind::Initial);
+
+        let merge = VcsRequest::Merge { branch: "feature/search".to_string(), project_id };
+        let Applied::Merged { hash, .. } = repo.apply(&merge, engineer(), start()).unwrap() else { panic!("expected a merge") };
+        assert_eq!(hash.len(), 40);
+        assert_eq!(repo.head("main").unwrap().parents.len(), 2);
+        assert!(repo.head("feature/search").is_none());
+        assert!(matches!(repo.apply(&merge, engineer(), start()), Err(VcsError::UnknownBranch(_))));
+
+        let tag = repo.release(&hash, start()).unwrap().name.clone();
+        assert_eq!(tag, "v1.0.0");
+        assert_eq!(repo.resolve(&tag).unwrap(), hash);
+        assert_eq!(repo.resolve(&hash[..7]).unwrap(), hash);
+
+        // The merge brought in both feature commits
+        assert!(repo.commits_between(Some(&tag), "main").unwrap().is_empty());
+        let all = repo.commits_between(None, &tag).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].hash, hash);
+    }
+
+    #[test]
+    fn test_revert_undoes_a_commit_once() {
+        let mut repo = Repository::new("platform", "main", start());
+        let change = repo.commit("main", engineer(), "Enable connection pooling", None, start()).unwrap();
+
+        let revert = VcsRequest::Revert { commit: short(&change).to_string(), reason: "latency spike".to_string() };
+        let Applied::Reverted { hash, reverted } = repo.apply(&revert, engineer(), start()).unwrap() else { panic!("expected a revert") };
+        assert_eq!(reverted, change);
+        assert!(repo.is_reverted(&change));
+        assert!(repo.get(&hash).unwrap().message.starts_with("Revert \"Enable connection pooling\""));
+        assert!(matches!(repo.apply(&revert, engineer(), start()), Err(VcsError::AlreadyReverted(_))));
+
+        repo.create_branch("hotfix", "main").unwrap();
+        let side = repo.commit("hotfix", engineer(), "Tune timeouts", None, start()).unwrap();
+        assert!(matches!(repo.revert("main", &side, engineer(), start()), Err(VcsError::NotOnBranch { .. })));
+    }
+}
@@ -0,0 +1,300 @@
+//! Vulnerability Scanning - Asset Inventory, Catalog and Findings
+//!
+//! This module implements what InfoSec scans and what it finds there:
+//! - An asset inventory synced from DevOps servers, Networking services and
+//!   the service catalog; assets that are gone are retired
+//! - A vulnerability catalog of known CVEs, each with a severity drawn from
+//!   the configured distribution, a CVSS score in that severity's band and
+//!   the kind of asset it affects
+//! - Findings per asset: a scan finds an asset exposed to vulnerabilities
+//!   that apply to its kind, a vulnerability already open on the asset is
+//!   seen again rather than found twice, and one remediated there is not
+//!   reported again
+//! - Aging: how long findings have been open, which are past their
+//!   severity's remediation deadline, and how long remediation took
+//!
+//! InfoSec agents hold the inventory, the catalog and the findings; the
+//! orchestrator sends them the assets to sync with every weekly scan.
+
+use crate::config::{RemediationDays, ScanSettings};
+use crate::departments::infosec::{Severity, Vulnerability};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Remediated findings kept; the oldest go first
+pub const MAX_REMEDIATED: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetKind {
+    /// A DevOps server, by hostname
+    Server,
+    /// A Networking service such as a load balancer or DNS
+    NetworkService,
+    /// A service in the catalog, as customers use it
+    Application,
+}
+
+impl AssetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::Server => "server",
+            AssetKind::NetworkService => "network service",
+            AssetKind::Application => "application",
+        }
+    }
+
+    /// Software a vulnerability of this kind of asset is in
+    fn components(&self) -> &'static [&'static str] {
+        match self {
+            AssetKind::Server => &["Linux kernel", "OpenSSH", "glibc", "sudo", "systemd"],
+            AssetKind::NetworkService => &["nginx", "HAProxy", "BIND", "OpenSSL", "Envoy"],
+            AssetKind::Application => &["web framework", "JSON parser", "ORM", "template engine", "auth library"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub kind: AssetKind,
+    pub first_seen: chrono::DateTime<chrono::Utc>,
+    pub last_scanned: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Everything InfoSec scans, by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetInventory {
+    assets: BTreeMap<String, Asset>,
+}
+
+impl AssetInventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn assets(&self) -> impl Iterator<Item = &Asset> {
+        self.assets.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Asset> {
+        self.assets.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Make the assets of a kind exactly `names`: new ones are added and
+    /// the ones no longer there retired. Returns the retired names.
+    pub fn sync(&mut self, kind: AssetKind, names: &[String], now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+        let retired: Vec<String> = self.assets.values()
+            .filter(|asset| asset.kind == kind && !names.contains(&asset.name))
+            .map(|asset| asset.name.clone())
+            .collect();
+        for name in &retired {
+            self.assets.remove(name);
+        }
+        for name in names {
+            self.assets.entry(name.clone()).or_insert_with(|| Asset { name: name.clone(), kind, first_seen: now, last_scanned: None });
+        }
+        retired
+    }
+
+    /// Add an asset scanned by name alone, as an application unless known
+    pub fn ensure(&mut self, name: &str, now: chrono::DateTime<chrono::Utc>) -> &mut Asset {
+        self.assets.entry(name.to_string())
+            .or_insert_with(|| Asset { name: name.to_string(), kind: AssetKind::Application, first_seen: now, last_scanned: None })
+    }
+}
+
+/// A known vulnerability scans look for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub cvss_score: f32,
+    pub affects: AssetKind,
+    pub remediation: String,
+}
+
+impl CatalogEntry {
+    /// The entry found open on an asset
+    pub fn finding(&self, asset: &str, now: chrono::DateTime<chrono::Utc>) -> Vulnerability {
+        Vulnerability {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            severity: self.severity.clone(),
+            cvss_score: self.cvss_score,
+            description: format!("{} found on {} {}", self.title, self.affects.as_str(), asset),
+            affected_system: asset.to_string(),
+            remediation: self.remediation.clone(),
+            discovered_at: now,
+            last_seen: Some(now),
+            times_seen: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VulnerabilityCatalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl VulnerabilityCatalog {
+    /// A catalog of `catalog_size` vulnerabilities, with severities drawn
+    /// from the configured weights
+    pub fn generate(settings: &ScanSettings, rng: &mut impl Rng) -> Self {
+        const FLAWS: [&str; 5] = ["Remote code execution", "Privilege escalation", "Denial of service", "Information disclosure", "Authentication bypass"];
+        const KINDS: [AssetKind; 3] = [AssetKind::Server, AssetKind::NetworkService, AssetKind::Application];
+        let mut ids = BTreeSet::new();
+        let mut entries = Vec::with_capacity(settings.catalog_size);
+        while entries.len() < settings.catalog_size {
+            let id = format!("CVE-{}-{:05}", rng.gen_range(2019..=2024), rng.gen_range(1000..100_000));
+            if !ids.insert(id.clone()) {
+                continue;
+            }
+            let severity = draw_severity(settings, rng);
+            let (low, high) = cvss_band(&severity);
+            let affects = KINDS[rng.gen_range(0..KINDS.len())];
+            let components = affects.components();
+            let component = components[rng.gen_range(0..components.len())];
+            entries.push(CatalogEntry {
+                id,
+                title: format!("{} in {}", FLAWS[rng.gen_range(0..FLAWS.len())], component),
+                cvss_score: ((low + rng.gen_range(0.0..=1.0) * (high - low)) * 10.0).round() / 10.0,
+                severity,
+                affects,
+                remediation: format!("Upgrade {} to the fixed release", component),
+            });
+        }
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Vulnerabilities affecting a kind of asset
+    pub fn applicable(&self, kind: AssetKind) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.iter().filter(move |entry| entry.affects == kind)
+    }
+}
+
+fn draw_severity(settings: &ScanSettings, rng: &mut impl Rng) -> Severity {
+    let weights = &settings.severity_weights;
+    let weighted = [
+        (Severity::Critical, weights.critical),
+        (Severity::High, weights.high),
+        (Severity::Medium, weights.medium),
+        (Severity::Low, weights.low),
+        (Severity::Info, weights.info),
+    ];
+    let total: f64 = weighted.iter().map(|(_, weight)| weight.max(0.0)).sum();
+    let mut pick = rng.gen_range(0.0..total.max(f64::MIN_POSITIVE));
+    for (severity, weight) in weighted {
+        if pick < weight.max(0.0) {
+            return severity;
+        }
+        pick -= weight.max(0.0);
+    }
+    Severity::Info
+}
+
+/// CVSS scores a severity covers
+fn cvss_band(severity: &Severity) -> (f32, f32) {
+    match severity {
+        Severity::Critical => (9.0, 10.0),
+        Severity::High => (7.0, 8.9),
+        Severity::Medium => (4.0, 6.9),
+        Severity::Low => (0.1, 3.9),
+        Severity::Info => (0.0, 0.0),
+    }
+}
+
+/// Days a finding of a severity may stay open; informational ones never
+/// fall due
+pub fn remediation_deadline(days: &RemediationDays, severity: &Severity) -> Option<chrono::Duration> {
+    let days = match severity {
+        Severity::Critical => days.critical,
+        Severity::High => days.high,
+        Severity::Medium => days.medium,
+        Severity::Low => days.low,
+        Severity::Info => return None,
+    };
+    Some(chrono::Duration::days(i64::from(days)))
+}
+
+/// A finding that was closed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remediation {
+    pub vulnerability: String,
+    pub asset: String,
+    pub severity: Severity,
+    pub discovered_at: chrono::DateTime<chrono::Utc>,
+    pub remediated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Age of the open findings and speed of remediation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgingReport {
+    pub open: usize,
+    /// Open past their severity's remediation deadline
+    pub overdue: usize,
+    pub oldest_open_days: Option<i64>,
+    pub remediated: usize,
+    pub mean_days_to_remediate: Option<f64>,
+}
+
+/// Age the open findings and time the remediated ones at `now`
+pub fn aging(open: &[Vulnerability], remediated: &[Remediation], days: &RemediationDays, now: chrono::DateTime<chrono::Utc>) -> AgingReport {
+    let overdue = open.iter()
+        .filter(|finding| remediation_deadline(days, &finding.severity).is_some_and(|deadline| now - finding.discovered_at > deadline))
+        .count();
+    let total_days: f64 = remediated.iter().map(|fix| (fix.remediated_at - fix.discovered_at).num_minutes() as f64 / (24.0 * 60.0)).sum();
+    AgingReport {
+        open: open.len(),
+        overdue,
+        oldest_open_days: open.iter().map(|finding| (now - finding.discovered_at).num_days()).max(),
+        remediated: remediated.len(),
+        mean_days_to_remediate: (!remediated.is_empty()).then(|| total_days / remediated.len() as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_inventory_sync_and_catalog_distribution() {
+        let now = chrono::Utc::now();
+        let mut inventory = AssetInventory::new();
+        inventory.sync(AssetKind::Server, &["web-1".to_string(), "web-2".to_string()], now);
+        inventory.sync(AssetKind::NetworkService, &["dns".to_string()], now);
+        assert_eq!(inventory.sync(AssetKind::Server, &["web-2".to_string()], now), vec!["web-1".to_string()]);
+        assert_eq!(inventory.len(), 2);
+
+        // Only critical weight: every entry is critical, scored 9.0 or up
+        let weights = crate::config::SeverityWeights { critical: 1.0, high: 0.0, medium: 0.0, low: 0.0, info: 0.0 };
+        let settings = ScanSettings { severity_weights: weights, ..ScanSettings::default() };
+        let catalog = VulnerabilityCatalog::generate(&settings, &mut rand::rngs::StdRng::seed_from_u64(11));
+        assert_eq!(catalog.entries().len(), settings.catalog_size);
+        assert!(catalog.entries().iter().all(|entry| entry.severity == Severity::Critical && entry.cvss_score >= 9.0));
+
+        // A critical finding open for 8 days is past its 7-day deadline
+        let finding = catalog.entries()[0].finding("web-2", now - chrono::Duration::days(8));
+        let report = aging(&[finding], &[], &settings.remediation_days, now);
+        assert_eq!((report.open, report.overdue, report.oldest_open_days), (1, 1, Some(8)));
+    }
+}
@@ -0,0 +1,261 @@
+//! Wait-For Graph - Cross-Department Deadlock Detection
+//!
+//! This module implements tracking which agents are blocked on which:
+//! - Blocking requests (environment, approval, fix and resource requests)
+//!   add a wait edge from the sender to the recipient
+//! - Any other message back from the recipient resolves the wait; asking
+//!   the same agent again while waiting adds no second wait
+//! - Waits left unanswered longer than the configured limit are dropped,
+//!   so unanswered chatter neither piles up nor forms deadlocks
+//! - Cycles in the graph are deadlocks; each is broken by dropping its
+//!   newest wait and escalating to management
+//! - A bounded history of deadlocks for frequency reporting
+
+use crate::communication::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Number of broken deadlocks kept for reports
+const DEADLOCK_HISTORY_CAPACITY: usize = 500;
+
+/// Metadata key marking any message as a blocking request
+pub const BLOCKING_KEY: &str = "blocking";
+
+/// Message types whose sender waits for the recipient to answer
+const BLOCKING_REQUESTS: &[&str] = &[
+    "collaboration_request",
+    "resource_request",
+    "environment_request",
+    "approval_request",
+    "fix_request",
+];
+
+/// One agent waiting on another
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WaitEdge {
+    pub waiter: Uuid,
+    pub holder: Uuid,
+    /// Request the waiter is blocked on
+    pub request_id: Uuid,
+    pub message_type: String,
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+/// Deadlock that was detected and broken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlockRecord {
+    pub id: Uuid,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+    /// Agents in wait order; the last one waits on the first
+    pub cycle: Vec<Uuid>,
+    /// Wait dropped to break the cycle
+    pub broken: WaitEdge,
+}
+
+/// Directed graph of agents waiting on each other
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WaitForGraph {
+    edges: Vec<WaitEdge>,
+    history: VecDeque<DeadlockRecord>,
+    /// Deadlocks detected since tracking began
+    pub detected: u64,
+    /// Simulated time the first message was observed
+    pub tracking_since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl WaitForGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update waits from a dispatched message
+    pub fn observe(&mut self, message: &Message, now: chrono::DateTime<chrono::Utc>) {
+        self.tracking_since.get_or_insert(now);
+        if message.from_agent == message.to_agent {
+            return;
+        }
+
+        if is_blocking(message) {
+            // The first unanswered request already has the sender waiting
+            if self.edges.iter().any(|edge| edge.waiter == message.from_agent && edge.holder == message.to_agent) {
+                return;
+            }
+            self.edges.push(WaitEdge {
+                waiter: message.from_agent,
+                holder: message.to_agent,
+                request_id: message.id,
+                message_type: message.message_type.clone(),
+                since: now,
+            });
+        } else {
+            // Any answer from the holder unblocks the waiter
+            self.edges.retain(|edge| !(edge.holder == message.from_agent && edge.waiter == message.to_agent));
+        }
+    }
+
+    /// Current waits
+    pub fn edges(&self) -> &[WaitEdge] {
+        &self.edges
+    }
+
+    /// Drop waits older than `max_wait`, returning how many were dropped
+    pub fn expire(&mut self, max_wait: chrono::Duration, now: chrono::DateTime<chrono::Utc>) -> usize {
+        let before = self.edges.len();
+        self.edges.retain(|edge| now - edge.since <= max_wait);
+        before - self.edges.len()
+    }
+
+    /// Find one cycle of waiting agents, if any
+    pub fn find_cycle(&self) -> Option<Vec<Uuid>> {
+        let mut adjacency: BTreeMap<Uuid, Vec<Uuid>> = BTreeMap::new();
+        for edge in &self.edges {
+            adjacency.entry(edge.waiter).or_default().push(edge.holder);
+        }
+        for holders in adjacency.values_mut() {
+            holders.sort();
+            holders.dedup();
+        }
+
+        let mut finished = HashSet::new();
+        for &start in adjacency.keys() {
+            let mut path = Vec::new();
+            if let Some(cycle) = find_cycle_from(start, &adjacency, &mut path, &mut finished) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    /// Break every cycle by dropping its newest wait, returning what was broken
+    pub fn break_deadlocks(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<DeadlockRecord> {
+        let mut broken = Vec::new();
+        while let Some(cycle) = self.find_cycle() {
+            let victim = (0..cycle.len())
+                .filter_map(|i| {
+                    let (waiter, holder) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                    self.edges.iter().position(|edge| edge.waiter == waiter && edge.holder == holder)
+                })
+                .max_by_key(|&index| self.edges[index].since)
+                .expect("every cycle step has an edge");
+            let edge = self.edges.remove(victim);
+            // Drop parallel waits on the same pair so the cycle is really gone
+            self.edges.retain(|e| !(e.waiter == edge.waiter && e.holder == edge.holder));
+
+            let record = DeadlockRecord {
+                id: Uuid::new_v4(),
+                detected_at: now,
+                cycle,
+                broken: edge,
+            };
+            self.detected += 1;
+            if self.history.len() == DEADLOCK_HISTORY_CAPACITY {
+                self.history.pop_front();
+            }
+            self.history.push_back(record.clone());
+            broken.push(record);
+        }
+        broken
+    }
+
+    /// Recently broken deadlocks, oldest first
+    pub fn history(&self) -> impl Iterator<Item = &DeadlockRecord> {
+        self.history.iter()
+    }
+
+    /// Deadlocks per simulated day since tracking began
+    pub fn deadlocks_per_day(&self, now: chrono::DateTime<chrono::Utc>) -> f64 {
+        let Some(since) = self.tracking_since else {
+            return 0.0;
+        };
+        let days = (now - since).num_minutes() as f64 / (24.0 * 60.0);
+        if days <= 0.0 {
+            0.0
+        } else {
+            self.detected as f64 / days
+        }
+    }
+}
+
+fn is_blocking(message: &Message) -> bool {
+    BLOCKING_REQUESTS.contains(&message.message_type.as_str())
+        || message.metadata.get(BLOCKING_KEY).is_some_and(|v| v == "true")
+}
+
+/// Depth-first search returning the first cycle reachable from `node`
+fn find_cycle_from(node: Uuid, adjacency: &BTreeMap<Uuid, Vec<Uuid>>, path: &mut Vec<Uuid>, finished: &mut HashSet<Uuid>) -> Option<Vec<Uuid>> {
+    if let Some(position) = path.iter().position(|&n| n == node) {
+        return Some(path[position..].to_vec());
+    }
+    if finished.contains(&node) {
+        return None;
+    }
+
+    path.push(node);
+    for &next in adjacency.get(&node).into_iter().flatten() {
+        if let Some(cycle) = find_cycle_from(next, adjacency, path, finished) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    finished.insert(node);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::test_message;
+
+
+    #[test]
+    fn test_three_way_cycle_is_broken_at_newest_wait() {
+        let (engineering, devops, infosec) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let start = chrono::Utc::now();
+        let mut graph = WaitForGraph::new();
+
+        graph.observe(&test_message(engineering, devops, "environment_request"), start);
+        graph.observe(&test_message(devops, infosec, "approval_request"), start + chrono::Duration::minutes(5));
+        assert!(graph.find_cycle().is_none());
+        graph.observe(&test_message(infosec, engineering, "fix_request"), start + chrono::Duration::minutes(10));
+
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(cycle.len(), 3);
+
+        let broken = graph.break_deadlocks(start + chrono::Duration::hours(12));
+        assert_eq!(broken.len(), 1);
+        assert_eq!((broken[0].broken.waiter, broken[0].broken.holder), (infosec, engineering));
+        assert!(graph.find_cycle().is_none());
+        assert_eq!(graph.edges().len(), 2);
+        assert_eq!(graph.deadlocks_per_day(start + chrono::Duration::hours(12)), 2.0);
+    }
+
+    #[test]
+    fn test_reply_resolves_wait() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut graph = WaitForGraph::new();
+        graph.observe(&test_message(a, b, "resource_request"), chrono::Utc::now());
+        graph.observe(&test_message(b, a, "status_update"), chrono::Utc::now());
+        assert!(graph.edges().is_empty());
+    }
+
+    #[test]
+    fn test_unanswered_requests_stop_counting() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let start = chrono::Utc::now();
+        let mut graph = WaitForGraph::new();
+
+        // Asking twice is still one wait
+        graph.observe(&test_message(a, b, "collaboration_request"), start);
+        graph.observe(&test_message(a, b, "resource_request"), start + chrono::Duration::minutes(30));
+        assert_eq!(graph.edges().len(), 1);
+        assert_eq!(graph.edges()[0].since, start);
+
+        // Neither ever answers; the crossed requests would otherwise be a deadlock
+        graph.observe(&test_message(b, a, "collaboration_request"), start + chrono::Duration::hours(7));
+        assert_eq!(graph.expire(chrono::Duration::hours(8), start + chrono::Duration::hours(9)), 1);
+        assert!(graph.find_cycle().is_none());
+        assert_eq!(graph.expire(chrono::Duration::hours(8), start + chrono::Duration::hours(16)), 1);
+        assert!(graph.edges().is_empty());
+    }
+}
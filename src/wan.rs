@@ -0,0 +1,286 @@
+//! WAN Sites - Links, Failover Routing and Cross-Site Latency
+//!
+//! This module implements the company's sites and the WAN between them:
+//! - Sites joined by WAN links and site-to-site VPNs, each with a latency
+//!   and an hourly chance of failing; a failed link comes back once it is
+//!   repaired
+//! - Routing the way BGP picks best paths: between every pair of sites the
+//!   lowest-latency path over the links that are up. When a link fails,
+//!   traffic fails over to the next best path, and back once it is repaired
+//! - Latency of multi-region services: clients at every site reach the
+//!   nearest site running the service, and the round trip is sampled every
+//!   step, as is every site cut off from the service
+//!
+//! Networking runs the WAN every simulation step; each evening the day's
+//! latencies go to Ops for its SLA tracking.
+
+use crate::config::{WanLinkSettings, WanSettings};
+use chrono::{DateTime, Utc};
+use petgraph::graph::{NodeIndex, UnGraph};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// Leased line or MPLS circuit
+    #[default]
+    Wan,
+    /// Site-to-site VPN over the internet
+    Vpn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WanLink {
+    pub from: String,
+    pub to: String,
+    pub kind: LinkKind,
+    /// One-way latency
+    pub latency_ms: f64,
+    /// Chance of failing in any hour
+    pub failure_probability: f64,
+    pub up: bool,
+    /// When a failed link is repaired
+    pub down_until: Option<DateTime<Utc>>,
+    pub failures: u64,
+}
+
+impl WanLink {
+    fn new(settings: &WanLinkSettings) -> Self {
+        Self {
+            from: settings.from.clone(),
+            to: settings.to.clone(),
+            kind: settings.kind,
+            latency_ms: settings.latency_ms,
+            failure_probability: settings.failure_probability,
+            up: true,
+            down_until: None,
+            failures: 0,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        pair(&self.from, &self.to)
+    }
+}
+
+/// Key of the route or link between two sites, in either direction
+fn pair(a: &str, b: &str) -> String {
+    if a <= b { format!("{} <-> {}", a, b) } else { format!("{} <-> {}", b, a) }
+}
+
+/// Best path between two sites
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    /// Sites on the way from one end to the other
+    pub path: Vec<String>,
+    /// One-way latency
+    pub latency_ms: f64,
+}
+
+/// Round trips to a service from every site
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySample {
+    pub samples: u64,
+    pub total_ms: f64,
+    pub worst_ms: f64,
+    /// Samples of sites that could not reach the service
+    pub unreachable: u64,
+}
+
+impl LatencySample {
+    pub fn average_ms(&self) -> f64 {
+        if self.samples == 0 { 0.0 } else { self.total_ms / self.samples as f64 }
+    }
+
+    fn record(&mut self, round_trip_ms: Option<f64>) {
+        match round_trip_ms {
+            Some(ms) => {
+                self.samples += 1;
+                self.total_ms += ms;
+                self.worst_ms = self.worst_ms.max(ms);
+            }
+            None => self.unreachable += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WanEvent {
+    LinkDown { link: String, kind: LinkKind },
+    LinkUp { link: String, kind: LinkKind },
+    /// Traffic between two sites took another path, or none is left
+    Failover { sites: String, from: Vec<String>, to: Option<Vec<String>> },
+    /// Two sites cut off from each other can reach each other again
+    Restored { sites: String, path: Vec<String> },
+}
+
+/// Sites, the links between them and the routes they give
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Wan {
+    pub sites: Vec<String>,
+    pub links: Vec<WanLink>,
+    /// Sites each multi-region service runs in
+    pub services: BTreeMap<String, Vec<String>>,
+    /// Best path between every pair of sites that reach each other
+    pub routes: BTreeMap<String, Route>,
+    /// Round trips to each service since they were last taken
+    pub latency: BTreeMap<String, LatencySample>,
+}
+
+impl Wan {
+    /// Take the sites, links and services from the settings; links already
+    /// known keep whether they are up
+    pub fn configure(&mut self, settings: &WanSettings) {
+        let mut known: HashMap<String, WanLink> = self.links.drain(..).map(|link| (link.name(), link)).collect();
+        self.links = settings.links.iter()
+            .map(|link_settings| {
+                let fresh = WanLink::new(link_settings);
+                match known.remove(&fresh.name()) {
+                    Some(link) => WanLink { up: link.up, down_until: link.down_until, failures: link.failures, ..fresh },
+                    None => fresh,
+                }
+            })
+            .collect();
+        self.sites = settings.sites.clone();
+        self.services = settings.services.clone();
+        self.routes = self.best_routes();
+    }
+
+    /// Lowest-latency path between every pair of sites over the links up
+    fn best_routes(&self) -> BTreeMap<String, Route> {
+        let mut graph = UnGraph::<String, f64>::new_undirected();
+        let nodes: HashMap<&str, NodeIndex> = self.sites.iter().map(|site| (site.as_str(), graph.add_node(site.clone()))).collect();
+        for link in self.links.iter().filter(|link| link.up) {
+            if let (Some(&a), Some(&b)) = (nodes.get(link.from.as_str()), nodes.get(link.to.as_str())) {
+                graph.add_edge(a, b, link.latency_ms.max(0.0));
+            }
+        }
+        let mut routes = BTreeMap::new();
+        for (index, from) in self.sites.iter().enumerate() {
+            for to in &self.sites[index + 1..] {
+                let found = petgraph::algo::astar(&graph, nodes[from.as_str()], |node| node == nodes[to.as_str()], |edge| *edge.weight(), |_| 0.0);
+                if let Some((latency_ms, path)) = found {
+                    routes.insert(pair(from, to), Route { path: path.into_iter().map(|node| graph[node].clone()).collect(), latency_ms });
+                }
+            }
+        }
+        routes
+    }
+
+    /// One-way latency between two sites over the current routes
+    pub fn latency_between(&self, a: &str, b: &str) -> Option<f64> {
+        if a == b {
+            return Some(0.0);
+        }
+        self.routes.get(&pair(a, b)).map(|route| route.latency_ms)
+    }
+
+    /// Fail and repair links over `elapsed_seconds`, reroute around the
+    /// links down and sample the services' latency
+    pub fn step(&mut self, now: DateTime<Utc>, elapsed_seconds: u64, repair_minutes: u32, rng: &mut impl Rng) -> Vec<WanEvent> {
+        let hours = elapsed_seconds as f64 / 3600.0;
+        let mut events = Vec::new();
+        for link in &mut self.links {
+            if !link.up {
+                if link.down_until.filter(|until| *until > now).is_none() {
+                    link.up = true;
+                    link.down_until = None;
+                    events.push(WanEvent::LinkUp { link: link.name(), kind: link.kind });
+                }
+                continue;
+            }
+            let chance = 1.0 - (1.0 - link.failure_probability.clamp(0.0, 1.0)).powf(hours);
+            if rng.gen_bool(chance.clamp(0.0, 1.0)) {
+                link.up = false;
+                link.down_until = Some(now + chrono::Duration::minutes(i64::from(repair_minutes)));
+                link.failures += 1;
+                events.push(WanEvent::LinkDown { link: link.name(), kind: link.kind });
+            }
+        }
+
+        if !events.is_empty() {
+            let routes = self.best_routes();
+            let sites: BTreeSet<&String> = self.routes.keys().chain(routes.keys()).collect();
+            for sites in sites {
+                match (self.routes.get(sites), routes.get(sites)) {
+                    (Some(old), Some(new)) if new.path == old.path => {}
+                    (Some(old), new) => events.push(WanEvent::Failover {
+                        sites: sites.clone(),
+                        from: old.path.clone(),
+                        to: new.map(|new| new.path.clone()),
+                    }),
+                    (None, Some(new)) => events.push(WanEvent::Restored { sites: sites.clone(), path: new.path.clone() }),
+                    _ => {}
+                }
+            }
+            self.routes = routes;
+        }
+
+        for (service, service_sites) in &self.services {
+            let sample = self.latency.entry(service.clone()).or_default();
+            for client in &self.sites {
+                let nearest = service_sites.iter()
+                    .filter_map(|site| if site == client { Some(0.0) } else { self.routes.get(&pair(client, site)).map(|route| route.latency_ms) })
+                    .min_by(f64::total_cmp);
+                sample.record(nearest.map(|one_way| one_way * 2.0));
+            }
+        }
+        events
+    }
+
+    /// The latency sampled since the last call
+    pub fn take_latency(&mut self) -> BTreeMap<String, LatencySample> {
+        std::mem::take(&mut self.latency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn link(from: &str, to: &str, kind: LinkKind, latency_ms: f64) -> WanLinkSettings {
+        WanLinkSettings { from: from.to_string(), to: to.to_string(), kind, latency_ms, failure_probability: 0.0 }
+    }
+
+    #[test]
+    fn test_failover_reroutes_and_cut_off_sites_are_unreachable() {
+        let settings = WanSettings {
+            sites: vec!["east".to_string(), "west".to_string(), "eu".to_string()],
+            links: vec![link("east", "west", LinkKind::Wan, 30.0), link("east", "eu", LinkKind::Wan, 40.0), link("west", "eu", LinkKind::Vpn, 90.0)],
+            services: BTreeMap::from([("api".to_string(), vec!["east".to_string()])]),
+            ..WanSettings::default()
+        };
+        let mut wan = Wan::default();
+        wan.configure(&settings);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let now = Utc::now();
+        assert_eq!(wan.latency_between("west", "eu"), Some(70.0));
+        assert_eq!(wan.routes[&pair("west", "eu")].path, vec!["west", "east", "eu"]);
+        assert!(wan.step(now, 3600, 60, &mut rng).is_empty());
+
+        // The east-west link failing sends west's traffic over the VPN
+        wan.links[0].failure_probability = 1.0;
+        let events = wan.step(now, 3600, 60, &mut rng);
+        assert!(events.contains(&WanEvent::LinkDown { link: pair("east", "west"), kind: LinkKind::Wan }));
+        assert!(events.iter().any(|event| matches!(event, WanEvent::Failover { sites, to: Some(path), .. } if *sites == pair("west", "eu") && path.len() == 2)));
+        assert_eq!(wan.latency_between("east", "west"), Some(130.0));
+
+        // With the VPN down too west is cut off until the repair
+        wan.links[0].failure_probability = 0.0;
+        wan.links[2].up = false;
+        wan.links[2].down_until = Some(now + chrono::Duration::hours(2));
+        wan.routes = wan.best_routes();
+        wan.step(now, 3600, 60, &mut rng);
+        let latency = wan.take_latency();
+        assert_eq!(latency["api"].unreachable, 1);
+        assert_eq!(latency["api"].worst_ms, 260.0);
+        assert!(wan.take_latency().is_empty());
+
+        let events = wan.step(now + chrono::Duration::hours(2), 3600, 60, &mut rng);
+        assert_eq!(events.len(), 4);
+        assert!(events.iter().any(|event| matches!(event, WanEvent::Restored { .. })));
+    }
+}
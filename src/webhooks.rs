@@ -0,0 +1,459 @@
+//! Entity Webhooks - Outbound Change Notifications
+//!
+//! This module implements mirroring the simulation's workflow to external
+//! systems (a Jira or PagerDuty sandbox, a chat bot, ...):
+//! - An entity tracker that turns ticket, incident and deployment state
+//!   changes into events such as `ticket.created` or `deployment.failed`
+//! - Subscriptions per endpoint, filtered by event and entity attributes
+//! - HMAC-SHA256 signed POSTs with retries and exponential backoff on the
+//!   simulated clock
+//!
+//! The HTTP client is implemented here and only covers what the webhooks
+//! need. Every POST is bounded by a timeout, and a subscriber that fails is
+//! not tried again in the same round, so a hung endpoint cannot hold up the
+//! simulation.
+
+use crate::config::{WebhookSettings, WebhookSubscription};
+use crate::departments::devops::{Deployment, DeploymentStatus};
+use crate::departments::ops::{Incident, IncidentStatus, SupportTicket, TicketStatus};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+/// Header naming the event of a notification
+pub const EVENT_HEADER: &str = "X-AIvertCo-Event";
+
+/// Header carrying the delivery id, stable across retries
+pub const DELIVERY_HEADER: &str = "X-AIvertCo-Delivery";
+
+/// Header carrying `sha256=<hex HMAC of the body>` when the subscription has a secret
+pub const SIGNATURE_HEADER: &str = "X-AIvertCo-Signature";
+
+/// Notifications waiting for delivery before the oldest are dropped
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Longest a POST may take, from connecting to reading the response
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Entity changes subscribers can listen for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EntityEvent {
+    #[serde(rename = "ticket.created")]
+    TicketCreated,
+    #[serde(rename = "ticket.resolved")]
+    TicketResolved,
+    #[serde(rename = "incident.declared")]
+    IncidentDeclared,
+    #[serde(rename = "incident.resolved")]
+    IncidentResolved,
+    #[serde(rename = "deployment.succeeded")]
+    DeploymentSucceeded,
+    #[serde(rename = "deployment.failed")]
+    DeploymentFailed,
+}
+
+impl EntityEvent {
+    /// Name used in config and in the event header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityEvent::TicketCreated => "ticket.created",
+            EntityEvent::TicketResolved => "ticket.resolved",
+            EntityEvent::IncidentDeclared => "incident.declared",
+            EntityEvent::IncidentResolved => "incident.resolved",
+            EntityEvent::DeploymentSucceeded => "deployment.succeeded",
+            EntityEvent::DeploymentFailed => "deployment.failed",
+        }
+    }
+}
+
+/// Entity whose state is checked for changes
+#[derive(Debug, Clone, Copy)]
+pub enum TrackedEntity<'a> {
+    Ticket(&'a SupportTicket),
+    Incident(&'a Incident),
+    Deployment(&'a Deployment),
+}
+
+/// A change to an entity, as sent to subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityChange {
+    pub event: EntityEvent,
+    pub entity_id: Uuid,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    /// Values subscription filters match against, e.g. `severity` or `service`
+    pub attributes: BTreeMap<String, Vec<String>>,
+    /// Full state of the entity after the change
+    pub entity: serde_json::Value,
+}
+
+/// Turns entity state into change events by remembering the last status seen
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityTracker {
+    /// Last status of every entity seen in the latest pass; entities no
+    /// longer held by any agent are forgotten
+    statuses: HashMap<Uuid, String>,
+    /// Whether the first pass has run; it records state without emitting events
+    primed: bool,
+}
+
+impl EntityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare entities with their last seen status and return what changed
+    ///
+    /// `entities` must be every entity there is: those missing are forgotten.
+    pub fn observe<'a>(&mut self, entities: impl IntoIterator<Item = TrackedEntity<'a>>, now: chrono::DateTime<chrono::Utc>) -> Vec<EntityChange> {
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        for entity in entities {
+            let (id, status) = match entity {
+                TrackedEntity::Ticket(ticket) => (ticket.id, format!("{:?}", ticket.status)),
+                TrackedEntity::Incident(incident) => (incident.id, format!("{:?}", incident.status)),
+                TrackedEntity::Deployment(deployment) => (deployment.id, format!("{:?}", deployment.status)),
+            };
+            seen.insert(id);
+            let previous = self.statuses.insert(id, status.clone());
+            if !self.primed || previous.as_deref() == Some(status.as_str()) {
+                continue;
+            }
+            if let Some(event) = event_for(entity, previous.is_none()) {
+                changes.push(EntityChange {
+                    event,
+                    entity_id: id,
+                    occurred_at: now,
+                    attributes: attributes(entity),
+                    entity: match entity {
+                        TrackedEntity::Ticket(ticket) => serde_json::to_value(ticket),
+                        TrackedEntity::Incident(incident) => serde_json::to_value(incident),
+                        TrackedEntity::Deployment(deployment) => serde_json::to_value(deployment),
+                    }.unwrap_or_default(),
+                });
+            }
+        }
+        self.statuses.retain(|id, _| seen.contains(id));
+        self.primed = true;
+        changes
+    }
+}
+
+/// Event raised by an entity that was just created or changed status
+fn event_for(entity: TrackedEntity<'_>, created: bool) -> Option<EntityEvent> {
+    match entity {
+        TrackedEntity::Ticket(_) if created => Some(EntityEvent::TicketCreated),
+        TrackedEntity::Ticket(ticket) => matches!(ticket.status, TicketStatus::Resolved)
+            .then_some(EntityEvent::TicketResolved),
+        TrackedEntity::Incident(incident) if incident.duplicate_of.is_some() => None,
+        TrackedEntity::Incident(_) if created => Some(EntityEvent::IncidentDeclared),
+        TrackedEntity::Incident(incident) => matches!(incident.status, IncidentStatus::Resolved)
+            .then_some(EntityEvent::IncidentResolved),
+        TrackedEntity::Deployment(deployment) => match deployment.status {
+            DeploymentStatus::Success => Some(EntityEvent::DeploymentSucceeded),
+            DeploymentStatus::Failed | DeploymentStatus::RolledBack => Some(EntityEvent::DeploymentFailed),
+            DeploymentStatus::Pending | DeploymentStatus::InProgress => None,
+        },
+    }
+}
+
+fn attributes(entity: TrackedEntity<'_>) -> BTreeMap<String, Vec<String>> {
+    let mut attributes = BTreeMap::new();
+    let mut set = |key: &str, values: Vec<String>| {
+        attributes.insert(key.to_string(), values);
+    };
+    match entity {
+        TrackedEntity::Ticket(ticket) => {
+            set("status", vec![format!("{:?}", ticket.status)]);
+            set("priority", vec![format!("{:?}", ticket.priority)]);
+            set("customer_tier", vec![format!("{:?}", ticket.customer_tier)]);
+            set("customer", ticket.customer_id.iter().cloned().collect());
+        }
+        TrackedEntity::Incident(incident) => {
+            set("status", vec![format!("{:?}", incident.status)]);
+            set("severity", vec![format!("{:?}", incident.severity)]);
+            set("service", incident.affected_services.clone());
+        }
+        TrackedEntity::Deployment(deployment) => {
+            set("status", vec![format!("{:?}", deployment.status)]);
+            set("environment", vec![deployment.environment.clone()]);
+            set("project", vec![deployment.project_id.to_string()]);
+        }
+    }
+    attributes
+}
+
+/// Whether a subscription wants a change: the event is listed and every
+/// filter shares at least one value with the change's attribute
+fn subscribed(subscription: &WebhookSubscription, change: &EntityChange) -> bool {
+    subscription.events.contains(&change.event)
+        && subscription.filters.iter().all(|(key, allowed)| {
+            change.attributes.get(key).is_some_and(|values| values.iter().any(|value| allowed.contains(value)))
+        })
+}
+
+/// Notification waiting to be posted to one subscriber
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    pub id: Uuid,
+    /// Subscriber endpoint; its secret is looked up in config when sending
+    pub url: String,
+    pub change: EntityChange,
+    /// Failed attempts so far
+    pub attempts: u32,
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Notification given up on after its last retry
+#[derive(Debug, Clone)]
+pub struct FailedDelivery {
+    pub delivery: PendingDelivery,
+    pub error: String,
+}
+
+/// Webhook delivery counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookStats {
+    pub delivered: u64,
+    pub retried: u64,
+    pub failed: u64,
+    /// Dropped because the queue was full
+    pub dropped: u64,
+}
+
+/// Queue of notifications for subscribers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookDispatcher {
+    queue: VecDeque<PendingDelivery>,
+    pub stats: WebhookStats,
+}
+
+impl WebhookDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue each change for every subscription that wants it
+    pub fn enqueue(&mut self, settings: &WebhookSettings, changes: &[EntityChange]) {
+        for change in changes {
+            for subscription in settings.subscriptions.iter().filter(|s| subscribed(s, change)) {
+                if self.queue.len() == QUEUE_CAPACITY {
+                    self.queue.pop_front();
+                    self.stats.dropped += 1;
+                }
+                self.queue.push_back(PendingDelivery {
+                    id: Uuid::new_v4(),
+                    url: subscription.url.clone(),
+                    change: change.clone(),
+                    attempts: 0,
+                    next_attempt_at: change.occurred_at,
+                });
+            }
+        }
+    }
+
+    /// Post every due notification, rescheduling failures with backoff
+    ///
+    /// Returns the notifications that ran out of retries.
+    /// A subscriber that fails is not tried again this round; its other
+    /// notifications wait for the next one.
+    pub async fn dispatch(&mut self, settings: &WebhookSettings, now: chrono::DateTime<chrono::Utc>) -> Vec<FailedDelivery> {
+        let mut failed = Vec::new();
+        let mut down: HashSet<String> = HashSet::new();
+        for mut delivery in std::mem::take(&mut self.queue) {
+            if delivery.next_attempt_at > now || down.contains(&delivery.url) {
+                self.queue.push_back(delivery);
+                continue;
+            }
+            // Deliveries for subscriptions removed from config are dropped
+            let Some(subscription) = settings.subscriptions.iter().find(|s| s.url == delivery.url) else {
+                continue;
+            };
+
+            match send(subscription, &delivery).await {
+                Ok(()) => self.stats.delivered += 1,
+                Err(error) if delivery.attempts < settings.max_retries => {
+                    down.insert(delivery.url.clone());
+                    delivery.attempts += 1;
+                    let backoff = settings.retry_backoff_minutes * (1 << (delivery.attempts - 1));
+                    delivery.next_attempt_at = now + chrono::Duration::minutes(backoff);
+                    self.stats.retried += 1;
+                    println!("🪝 Webhook {} to {} failed ({}), retrying at {}",
+                             delivery.change.event.as_str(), delivery.url, error, delivery.next_attempt_at.format("%Y-%m-%d %H:%M"));
+                    self.queue.push_back(delivery);
+                }
+                Err(error) => {
+                    down.insert(delivery.url.clone());
+                    self.stats.failed += 1;
+                    failed.push(FailedDelivery { delivery, error: error.to_string() });
+                }
+            }
+        }
+        failed
+    }
+
+    /// Notifications waiting for delivery or a retry
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// Post one notification, signing it when the subscription names a secret;
+/// the secret is read from the environment on every send
+async fn send(subscription: &WebhookSubscription, delivery: &PendingDelivery) -> Result<(), WebhookError> {
+    let body = serde_json::to_string(&serde_json::json!({
+        "id": delivery.id,
+        "event": delivery.change.event,
+        "entity_id": delivery.change.entity_id,
+        "occurred_at": delivery.change.occurred_at,
+        "entity": delivery.change.entity,
+    }))?;
+
+    let mut headers = vec![
+        (EVENT_HEADER, delivery.change.event.as_str().to_string()),
+        (DELIVERY_HEADER, delivery.id.to_string()),
+    ];
+    if let Some(secret_env) = &subscription.secret_env {
+        let secret = std::env::var(secret_env).map_err(|_| WebhookError::MissingSecret(secret_env.clone()))?;
+        headers.push((SIGNATURE_HEADER, signature(&secret, &body)));
+    }
+    post_json(&subscription.url, &body, &headers).await
+}
+
+/// Signature header value for a body: `sha256=<hex HMAC>`
+pub fn signature(secret: &str, body: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(body.as_bytes());
+    let hex: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// POST a JSON body to a plain-HTTP endpoint, giving up after `POST_TIMEOUT`
+pub async fn post_json(url: &str, body: &str, headers: &[(&str, String)]) -> Result<(), WebhookError> {
+    tokio::time::timeout(POST_TIMEOUT, post(url, body, headers)).await
+        .map_err(|_| WebhookError::Timeout(url.to_string()))?
+}
+
+async fn post(url: &str, body: &str, headers: &[(&str, String)]) -> Result<(), WebhookError> {
+    let rest = url.strip_prefix("http://")
+        .ok_or_else(|| WebhookError::UnsupportedUrl(url.to_string()))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let address = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+
+    let mut stream = TcpStream::connect(&address).await
+        .map_err(|e| WebhookError::Connect(address.clone(), e))?;
+    let extra: String = headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        path, authority, body.len(), extra, body);
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let status = response.split_whitespace().nth(1).unwrap_or("");
+    if status.starts_with('2') {
+        Ok(())
+    } else {
+        Err(WebhookError::Status(url.to_string(), status.to_string()))
+    }
+}
+
+/// Webhook delivery errors
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("Unsupported URL '{0}', only http:// is supported")]
+    UnsupportedUrl(String),
+
+    #[error("Cannot connect to {0}: {1}")]
+    Connect(String, std::io::Error),
+
+    #[error("Webhook I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0} answered with status '{1}'")]
+    Status(String, String),
+
+    #[error("{0} did not answer in time")]
+    Timeout(String),
+
+    #[error("Environment variable {0} with the webhook secret is not set")]
+    MissingSecret(String),
+
+    #[error("Cannot encode notification: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::departments::ops::Severity;
+
+    fn incident(severity: Severity, service: &str) -> Incident {
+        Incident {
+            id: Uuid::new_v4(),
+            title: "Checkout latency".to_string(),
+            description: "p99 above 2s".to_string(),
+            severity,
+            status: IncidentStatus::Open,
+            affected_services: vec![service.to_string()],
+            root_cause: None,
+            resolution: None,
+            created_at: chrono::Utc::now(),
+            resolved_at: None,
+            assigned_team: None,
+            duplicate_of: None,
+            duplicates: vec![],
+            correlation_ids: vec![],
+            status_history: vec![],
+            breaches: vec![],
+            knowledge: None,
+            runbook: None,
+        }
+    }
+
+    #[test]
+    fn test_signature_matches_rfc_4231() {
+        assert_eq!(signature("Jefe", "what do ya want for nothing?"),
+                   "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn test_changes_are_filtered_per_subscription() {
+        let now = chrono::Utc::now();
+        let (mut sev1, sev3) = (incident(Severity::Sev1, "checkout"), incident(Severity::Sev3, "checkout"));
+        let mut tracker = EntityTracker::new();
+        assert!(tracker.observe([], now).is_empty());
+
+        let declared = tracker.observe([TrackedEntity::Incident(&sev1), TrackedEntity::Incident(&sev3)], now);
+        assert_eq!(declared.len(), 2);
+        assert!(tracker.observe([TrackedEntity::Incident(&sev1)], now).is_empty());
+        // The Sev3 incident is no longer held by anyone
+        assert_eq!(tracker.statuses.len(), 1);
+        sev1.status = IncidentStatus::Resolved;
+        let resolved = tracker.observe([TrackedEntity::Incident(&sev1)], now);
+        assert_eq!(resolved[0].event, EntityEvent::IncidentResolved);
+
+        let settings = WebhookSettings {
+            subscriptions: vec![WebhookSubscription {
+                url: "http://pagerduty.sandbox/hooks".to_string(),
+                events: vec![EntityEvent::IncidentDeclared, EntityEvent::IncidentResolved],
+                secret_env: Some("AIVERTCO_WEBHOOK_SECRET".to_string()),
+                filters: BTreeMap::from([("severity".to_string(), vec!["Sev1".to_string(), "Sev2".to_string()])]),
+            }],
+            ..WebhookSettings::default()
+        };
+        let mut dispatcher = WebhookDispatcher::new();
+        dispatcher.enqueue(&settings, &declared);
+        dispatcher.enqueue(&settings, &resolved);
+        assert_eq!(dispatcher.pending(), 2);
+        assert!(dispatcher.queue.iter().all(|delivery| delivery.change.entity_id == sev1.id));
+    }
+}